@@ -0,0 +1,54 @@
+//! HTML export, registered next to the core's built-in formats.
+
+use pulldown_cmark::{Options, Parser, html};
+use zinnia_core::FormatConverter;
+
+/// Renders a note as a standalone HTML page.
+pub struct Html;
+
+impl FormatConverter for Html {
+    fn id(&self) -> &'static str {
+        "html"
+    }
+    fn name(&self) -> &'static str {
+        "HTML"
+    }
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+    fn convert(&self, markdown: &str) -> String {
+        let options = Options::ENABLE_TABLES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TASKLISTS
+            | Options::ENABLE_FOOTNOTES;
+        let mut body = String::new();
+        html::push_html(&mut body, Parser::new_ext(markdown, options));
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n</head>\n<body>\n{}</body>\n</html>\n",
+            body
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use zinnia_core::NotesApi;
+
+    #[test]
+    fn test_html_export() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.register_format(Box::new(Html));
+        api.create_note("guide").unwrap();
+        api.save_note("guide", "---\ntitle: Guide\n---\n# Setup\n\n- [x] a < b\n")
+            .unwrap();
+
+        let html = api.render_note("guide", "html").unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h1>Setup</h1>"));
+        assert!(html.contains("a &lt; b"));
+        assert!(!html.contains("title: Guide"));
+    }
+}
@@ -0,0 +1,199 @@
+//! `zinnia`: the vault from the command line.
+//!
+//! Opens the same vault as the app (see `get_default_notes_path`), so notes created here
+//! show up in a running app through its watcher. Commands that only read open the vault
+//! read-only.
+
+mod html;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use zinnia_core::{Error, NotesApi, Result, get_default_notes_path};
+
+#[derive(Parser)]
+#[command(name = "zinnia", version, about = "Zinnia notes from the command line")]
+struct Cli {
+    /// Notes folder to use instead of the app's
+    #[arg(long, global = true)]
+    vault: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create an empty note
+    New { path: String },
+    /// Print a note's content
+    Cat { path: String },
+    /// Print the paths of notes matching a full-text query
+    Search { query: String },
+    /// Print the note hierarchy, or the part below a note
+    Tree { path: Option<String> },
+    /// Move a note and its children to the archive
+    Archive { path: String },
+    /// Render notes in another format, to stdout or as files in a folder
+    Export {
+        #[arg(required = true)]
+        paths: Vec<String>,
+        /// markdown, org, asciidoc, or html
+        #[arg(long, default_value = "markdown")]
+        format: String,
+        /// Folder to write `<title>.<extension>` files to
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+impl Command {
+    fn writes(&self) -> bool {
+        matches!(self, Command::New { .. } | Command::Archive { .. })
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("zinnia: {}", describe(&e));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let root = match cli.vault {
+        Some(vault) => vault,
+        None => get_default_notes_path(cfg!(debug_assertions)).ok_or_else(|| {
+            Error::InvalidInput("Could not determine the notes folder".to_string())
+        })?,
+    };
+    let mut api = if cli.command.writes() {
+        NotesApi::new(root)?
+    } else if root.is_dir() {
+        NotesApi::new_read_only(root)?
+    } else {
+        // Opening would create an empty vault just to print nothing from it
+        return Err(Error::InvalidInput(format!(
+            "No vault at {}",
+            root.display()
+        )));
+    };
+    // Pick up edits made while neither the app nor its watcher was running
+    api.startup_sync()?;
+    api.register_format(Box::new(html::Html));
+
+    match cli.command {
+        Command::New { path } => {
+            println!("{}", api.create_note(&path)?.path);
+        }
+        Command::Cat { path } => {
+            print!("{}", api.get_note(&path)?.content);
+        }
+        Command::Search { query } => {
+            for note in api.search(&query)? {
+                println!("{}", note.path);
+            }
+        }
+        Command::Tree { path } => {
+            for line in tree(&api, path.as_deref())? {
+                println!("{}", line);
+            }
+        }
+        Command::Archive { path } => {
+            api.archive_note(&path)?;
+        }
+        Command::Export {
+            paths,
+            format,
+            out: Some(out),
+        } => {
+            let written = api.export_notes(&paths, &format, &out)?;
+            println!("Exported {} notes to {}", written, out.display());
+        }
+        Command::Export {
+            paths,
+            format,
+            out: None,
+        } => {
+            let [path] = paths.as_slice() else {
+                return Err(Error::InvalidInput(
+                    "Pass --out to export more than one note".to_string(),
+                ));
+            };
+            print!("{}", api.render_note(path, &format)?);
+        }
+    }
+    Ok(())
+}
+
+/// One line per note below `path` (or the whole vault), indented by depth and in the
+/// sidebar's order. Archived notes are left out.
+fn tree(api: &NotesApi, path: Option<&str>) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    let mut pending = vec![];
+    let top = match path {
+        Some(path) => api.get_children(path)?,
+        None => api.get_root_notes()?,
+    };
+    pending.extend(top.into_iter().rev().map(|note| (note, 0)));
+    while let Some((note, depth)) = pending.pop() {
+        if note.archived {
+            continue;
+        }
+        let name = note.path.rsplit('/').next().unwrap_or(&note.path);
+        lines.push(format!("{}{}", "  ".repeat(depth), name));
+        let children = api.get_children(&note.path)?;
+        pending.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+    }
+    Ok(lines)
+}
+
+fn describe(error: &Error) -> String {
+    match error {
+        Error::NotFound(path) => format!("No note at {}", path),
+        Error::AlreadyExists(path) => format!("{} already exists", path),
+        Error::ParentNotFound(path) => format!("No parent note for {}", path),
+        Error::InvalidInput(message) => message.clone(),
+        Error::Encrypted(path) => format!("{} is encrypted; unlock the vault in the app", path),
+        Error::Io(e) => e.to_string(),
+        Error::Database(e) => format!("Database: {}", e),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in ["projects", "projects/garden", "projects/garden/beds"] {
+            api.create_note(path).unwrap();
+        }
+        api.create_note("projects/old").unwrap();
+        api.archive_note("projects/old").unwrap();
+
+        assert_eq!(
+            tree(&api, None).unwrap(),
+            ["projects", "  garden", "    beds"]
+        );
+        assert_eq!(tree(&api, Some("projects")).unwrap(), ["garden", "  beds"]);
+    }
+
+    #[test]
+    fn test_reading_a_missing_vault_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing");
+        let cli = Cli::parse_from(["zinnia", "--vault", missing.to_str().unwrap(), "tree"]);
+
+        assert!(matches!(run(cli), Err(Error::InvalidInput(_))));
+        assert!(!missing.exists());
+    }
+}
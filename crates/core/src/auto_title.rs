@@ -0,0 +1,175 @@
+//! Names for new notes from what's typed into them.
+//!
+//! A note created as "untitled" can take its name from its first H1, or its first
+//! line when there's no H1, so it can be written before it's named. The editor
+//! decides when to ask (after typing pauses, with `editor.auto_title` on); the
+//! rename goes through `rename_note`, so links to the note follow.
+
+use crate::frontmatter::Frontmatter;
+use crate::notes::{Error, NotesApi, Result, get_parent_path};
+use crate::reparent::NoteMoved;
+use crate::stats::strip_markdown;
+
+/// Longest name taken from a note's content, in characters.
+pub const MAX_TITLE_CHARS: usize = 60;
+
+/// Characters Windows doesn't allow in file names; `:`, `\`, and `/` become `-`, the
+/// rest are dropped.
+const SEPARATORS: [char; 3] = [':', '\\', '/'];
+const FORBIDDEN: [char; 6] = ['?', '*', '"', '<', '>', '|'];
+
+/// Names Windows reserves for devices, with or without an extension.
+fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    let upper = stem.to_ascii_uppercase();
+    match upper.as_str() {
+        "CON" | "PRN" | "AUX" | "NUL" => true,
+        _ => ["COM", "LPT"].iter().any(|device| {
+            upper
+                .strip_prefix(device)
+                .is_some_and(|n| n.len() == 1 && matches!(n.as_bytes()[0], b'1'..=b'9'))
+        }),
+    }
+}
+
+/// Whether `name` is `base`, or `base N` as given to avoid a sibling's name.
+fn is_numbered(name: &str, base: &str) -> bool {
+    match name.strip_prefix(base) {
+        Some("") => true,
+        Some(rest) => rest
+            .strip_prefix(' ')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())),
+        None => false,
+    }
+}
+
+/// The first H1 of the content, or its first non-empty line, as a note name:
+/// plain text without leading dots, characters file systems reject, or more than
+/// `MAX_TITLE_CHARS`. Names Windows reserves (`CON`, `LPT1`, …) give `None`.
+pub fn title_from_content(content: &str) -> Option<String> {
+    let (_, body) = Frontmatter::parse(content);
+    let line = body
+        .lines()
+        .find(|line| line.starts_with("# "))
+        .or_else(|| body.lines().find(|line| !line.trim().is_empty()))?;
+
+    let text: String = strip_markdown(line)
+        .chars()
+        .filter(|c| !FORBIDDEN.contains(c) && !c.is_control())
+        .map(|c| if SEPARATORS.contains(&c) { '-' } else { c })
+        .collect();
+    let text = text.trim_start_matches('.').trim();
+    let title = match text.char_indices().nth(MAX_TITLE_CHARS) {
+        // Cut at the last word boundary that fits, if there is one
+        Some((cut, _)) => {
+            let head = &text[..cut];
+            head.rfind(' ')
+                .map_or(head, |space| &head[..space])
+                .trim_end()
+        }
+        None => text,
+    };
+    // Windows drops trailing dots and spaces, so `a.` and `a` would be one file
+    let title = title.trim_end_matches(['.', ' ']);
+    (!title.is_empty() && !is_reserved_name(title)).then(|| title.to_string())
+}
+
+impl NotesApi {
+    /// Renames the note after its first H1 or first line, adding " 2", " 3", … if a
+    /// sibling already has that name. Returns `None` if the content gives no name or
+    /// the note already has it (numbered or not).
+    pub fn title_from_first_line(&mut self, path: &str) -> Result<Option<NoteMoved>> {
+        self.ensure_writable()?;
+
+        let content = self.get_note_internal(path)?.content;
+        let Some(title) = title_from_content(&content) else {
+            return Ok(None);
+        };
+        let name = path.rsplit('/').next().unwrap_or(path);
+        if is_numbered(name, &title) {
+            return Ok(None);
+        }
+        if title.ends_with(".md") {
+            return Err(Error::InvalidInput(format!("Invalid note name: {}", title)));
+        }
+
+        let prefix = get_parent_path(path)
+            .map(|parent| format!("{}/", parent))
+            .unwrap_or_default();
+        let mut target = format!("{}{}", prefix, title);
+        let mut n = 2;
+        while self.note_exists(&target)? {
+            target = format!("{}{} {}", prefix, title, n);
+            n += 1;
+        }
+
+        let revision = self.rename_note(path, &target)?;
+        Ok(Some(NoteMoved {
+            from: path.to_string(),
+            to: target,
+            revision,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_title_from_content() {
+        assert_eq!(
+            title_from_content("---\ntags: [a]\n---\nintro\n# **Big** plans\n"),
+            Some("Big plans".to_string())
+        );
+        assert_eq!(
+            title_from_content("\n- [ ] buy [[milk|oat milk]]/eggs"),
+            Some("buy oat milk-eggs".to_string())
+        );
+        assert_eq!(title_from_content("...\n"), None);
+        assert_eq!(
+            title_from_content("# Q3: what's *next*? <draft> | \"v2\" a\\b"),
+            Some("Q3- what's next draft  v2 a-b".to_string())
+        );
+        assert_eq!(
+            title_from_content("# Sorted..."),
+            Some("Sorted".to_string())
+        );
+        assert_eq!(title_from_content("# con"), None);
+        assert_eq!(title_from_content("# LPT1.txt"), None);
+        assert_eq!(title_from_content("# Console"), Some("Console".to_string()));
+        assert_eq!(title_from_content("# COM10"), Some("COM10".to_string()));
+        assert_eq!(title_from_content(""), None);
+
+        let long = "word ".repeat(20);
+        let title = title_from_content(&long).unwrap();
+        assert!(title.chars().count() <= MAX_TITLE_CHARS);
+        assert!(title.ends_with("word"));
+    }
+
+    #[test]
+    fn test_title_from_first_line_renames_and_relinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("ideas").unwrap();
+        api.create_note("ideas/untitled").unwrap();
+        api.create_note("ideas/Garden").unwrap();
+        api.create_note("index").unwrap();
+        api.save_note("index", "See [[ideas/untitled]]").unwrap();
+        api.save_note("ideas/untitled", "# Garden\n\nbeds").unwrap();
+
+        let moved = api
+            .title_from_first_line("ideas/untitled")
+            .unwrap()
+            .unwrap();
+        assert_eq!(moved.to, "ideas/Garden 2");
+        assert_eq!(
+            api.get_note("index").unwrap().content,
+            "See [[ideas/Garden 2]]"
+        );
+
+        // Nothing to do once the name matches
+        assert_eq!(api.title_from_first_line("ideas/Garden 2").unwrap(), None);
+    }
+}
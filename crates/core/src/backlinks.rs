@@ -0,0 +1,262 @@
+//! Links between notes and the backlinks index.
+//!
+//! A note links to another with a `[[path]]` wiki-link (optionally `[[path|text]]`)
+//! or a Markdown link to its `zinnia://note/...` URL. Links in code and the
+//! frontmatter don't count. Each note's link targets are indexed on sync, so
+//! backlinks are a lookup, and renaming a note rewrites the links pointing at it.
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use rusqlite::{Connection, params};
+
+use crate::note_links::{note_url, parse_note_url};
+use crate::notes::{Error, NoteMetadata, NotesApi, Result, metadata_from_row};
+use crate::tags::escape_like;
+use crate::unlinked_mentions::excluded_ranges;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LinkForm {
+    /// `[[path]]`
+    Wiki,
+    /// `[text](zinnia://note/path)`
+    Url,
+}
+
+/// A link in note content and the path it names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LinkRef {
+    /// The whole link
+    pub span: Range<usize>,
+    /// Where its target is written
+    pub range: Range<usize>,
+    pub path: String,
+    /// The `[[path|text]]` or `[text](url)` text, if any
+    pub text: Option<String>,
+    pub form: LinkForm,
+}
+
+pub(crate) fn link_refs(content: &str) -> Vec<LinkRef> {
+    // Links are among the ranges kept out of mention matching, next to code and URLs
+    excluded_ranges(content)
+        .into_iter()
+        .filter_map(|range| {
+            let text = &content[range.clone()];
+            if let Some(inner) = text.strip_prefix("[[").and_then(|t| t.strip_suffix("]]")) {
+                let (target, alias) = match inner.split_once('|') {
+                    Some((target, alias)) => (target, Some(alias.trim().to_string())),
+                    None => (inner, None),
+                };
+                let path = target.trim().trim_matches('/');
+                let start = range.start + 2;
+                return (!path.is_empty()).then(|| LinkRef {
+                    span: range.clone(),
+                    range: start..start + target.len(),
+                    path: path.to_string(),
+                    text: alias,
+                    form: LinkForm::Wiki,
+                });
+            }
+            let text_end = text.strip_prefix('[')?.find("](")? + 1;
+            let url_start = text_end + 2;
+            let url = text.get(url_start..text.len() - 1)?;
+            Some(LinkRef {
+                span: range.clone(),
+                range: range.start + url_start..range.end - 1,
+                path: parse_note_url(url)?,
+                text: Some(text[1..text_end].to_string()),
+                form: LinkForm::Url,
+            })
+        })
+        .collect()
+}
+
+/// Paths of the notes linked from `content`.
+pub fn extract_links(content: &str) -> BTreeSet<String> {
+    link_refs(content)
+        .into_iter()
+        .map(|link| link.path)
+        .collect()
+}
+
+/// Replaces the indexed links of a note.
+pub(crate) fn index_links(db: &Connection, note_id: i64, content: &str) -> Result<()> {
+    db.execute("DELETE FROM links WHERE source_id = ?1", params![note_id])?;
+    for target in extract_links(content) {
+        db.execute(
+            "INSERT OR IGNORE INTO links (source_id, target) VALUES (?1, ?2)",
+            params![note_id, target],
+        )?;
+    }
+    Ok(())
+}
+
+/// `content` with links to `old` and the notes below it pointed at `new`, or
+/// `None` if it has no such links.
+fn rewrite_in_content(content: &str, old: &str, new: &str) -> Option<String> {
+    let mut updated = content.to_string();
+    let mut changed = false;
+
+    // Back to front, so earlier ranges stay valid
+    for link in link_refs(content).into_iter().rev() {
+        let path = if link.path == old {
+            new.to_string()
+        } else if let Some(rest) = link.path.strip_prefix(old).filter(|r| r.starts_with('/')) {
+            format!("{}{}", new, rest)
+        } else {
+            continue;
+        };
+        let target = match link.form {
+            LinkForm::Wiki => path,
+            LinkForm::Url => note_url(&path),
+        };
+        updated.replace_range(link.range, &target);
+        changed = true;
+    }
+
+    changed.then_some(updated)
+}
+
+impl NotesApi {
+    /// Returns the non-archived notes that link to `path`, ordered by path.
+    pub fn get_backlinks(&self, path: &str) -> Result<Vec<NoteMetadata>> {
+        let mut stmt = self.db.prepare(
+            "SELECT notes.id, notes.path, notes.mtime, notes.archived
+             FROM links
+             JOIN notes ON links.source_id = notes.id
+             WHERE links.target = ?1 AND notes.archived = 0 AND notes.path <> ?1
+             ORDER BY notes.path ASC",
+        )?;
+        let notes = stmt
+            .query_map(params![path], metadata_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(notes)
+    }
+
+    /// Returns the paths `path` links to, including notes that don't exist yet.
+    pub fn get_outgoing_links(&self, path: &str) -> Result<Vec<String>> {
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        let mut stmt = self.db.prepare(
+            "SELECT links.target
+             FROM links
+             JOIN notes ON links.source_id = notes.id
+             WHERE notes.path = ?1
+             ORDER BY links.target ASC",
+        )?;
+        let targets = stmt
+            .query_map(params![path], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(targets)
+    }
+
+    /// Points links to `old` and the notes below it at `new`, after a rename.
+    pub(crate) fn rewrite_links(&mut self, old: &str, new: &str) -> Result<()> {
+        let sources: Vec<String> = self
+            .db
+            .prepare(
+                "SELECT DISTINCT notes.path
+                 FROM links
+                 JOIN notes ON links.source_id = notes.id
+                 WHERE links.target = ?1 OR links.target LIKE ?2 ESCAPE '\\'
+                 ORDER BY notes.path ASC",
+            )?
+            .query_map(params![old, format!("{}/%", escape_like(old))], |row| {
+                row.get(0)
+            })?
+            .collect::<std::result::Result<_, _>>()?;
+
+        let mut planned: Vec<(String, String, String)> = Vec::new();
+        for source in sources {
+            let content = self.read_content(&source)?;
+            if let Some(updated) = rewrite_in_content(&content, old, new) {
+                planned.push((source, content, updated));
+            }
+        }
+        if planned.is_empty() {
+            return Ok(());
+        }
+        let writes: Vec<(&str, &str, &str)> = planned
+            .iter()
+            .map(|(path, content, updated)| (path.as_str(), content.as_str(), updated.as_str()))
+            .collect();
+        self.write_batch(&writes)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_extract_links() {
+        let content = "---\nsee: [[not/a/link]]\n---\n\
+             [[projects/apollo]], [[ people/ada | Ada ]] and [the plan](zinnia://note/projects/apollo/plan%20b)\n\
+             `[[in/code]]` [web](https://example.com)\n```\n[[fenced]]\n```\n";
+        let links: Vec<String> = extract_links(content).into_iter().collect();
+        assert_eq!(
+            links,
+            ["people/ada", "projects/apollo", "projects/apollo/plan b"]
+        );
+    }
+
+    #[test]
+    fn test_backlinks_follow_edits() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in ["hub", "a", "b"] {
+            api.create_note(path).unwrap();
+        }
+        api.save_note("a", "See [[hub]] and [[missing]]").unwrap();
+        api.save_note("b", "[Hub](zinnia://note/hub)").unwrap();
+
+        let paths = |notes: Vec<NoteMetadata>| -> Vec<String> {
+            notes.into_iter().map(|n| n.path).collect()
+        };
+        assert_eq!(paths(api.get_backlinks("hub").unwrap()), ["a", "b"]);
+        assert_eq!(api.get_outgoing_links("a").unwrap(), ["hub", "missing"]);
+
+        api.save_note("a", "No links anymore").unwrap();
+        assert_eq!(paths(api.get_backlinks("hub").unwrap()), ["b"]);
+
+        api.delete_note("b").unwrap();
+        assert!(api.get_backlinks("hub").unwrap().is_empty());
+        assert!(matches!(
+            api.get_outgoing_links("b"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_rename_rewrites_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in ["projects", "projects/apollo", "notes", "unrelated"] {
+            api.create_note(path).unwrap();
+        }
+        api.save_note(
+            "notes",
+            "[[projects]], [[projects/apollo|Apollo]], [plan](zinnia://note/projects/apollo) and [[projects-old]]",
+        )
+        .unwrap();
+        api.save_note("unrelated", "Nothing here").unwrap();
+
+        api.rename_note("projects", "work").unwrap();
+
+        assert_eq!(
+            api.get_note("notes").unwrap().content,
+            "[[work]], [[work/apollo|Apollo]], [plan](zinnia://note/work/apollo) and [[projects-old]]"
+        );
+        assert_eq!(api.get_note("unrelated").unwrap().content, "Nothing here");
+        let backlinks: Vec<String> = api
+            .get_backlinks("work/apollo")
+            .unwrap()
+            .into_iter()
+            .map(|n| n.path)
+            .collect();
+        assert_eq!(backlinks, ["notes"]);
+    }
+}
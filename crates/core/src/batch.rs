@@ -0,0 +1,908 @@
+//! Operations on several notes at once (multi-select in the sidebar).
+//!
+//! A batch is validated as a whole before anything is touched, so a bad selection
+//! (missing note, name clash at the destination) fails without side effects. The index
+//! updates of all items run inside one savepoint; if a filesystem step still fails
+//! halfway, the savepoint is rolled back and the index is rebuilt from disk so it
+//! matches whatever was already moved. `batch` also takes back the steps that ran
+//! before the failure.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::changes::Revision;
+use crate::frontmatter::{
+    Frontmatter, PropertyValue, remove_property_in_content, set_property_in_content,
+};
+use crate::notes::{Error, NotesApi, OperationGuard, Result, archived_path};
+
+/// Which notes a bulk edit applies to.
+#[derive(Debug, Clone)]
+pub enum NoteSelection {
+    Paths(Vec<String>),
+    /// The non-archived direct children of a note
+    ChildrenOf(String),
+    /// Notes matching a full-text search (see `search`)
+    Search(String),
+}
+
+/// One note's property before and after a bulk edit (`None` means unset).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyChange {
+    pub path: String,
+    pub before: Option<PropertyValue>,
+    pub after: Option<PropertyValue>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BulkPropertyEdit {
+    /// Notes whose property actually changes; notes already set are left out
+    pub changes: Vec<PropertyChange>,
+    /// Revision after the edit; `None` for a dry run or when nothing changed
+    pub revision: Option<Revision>,
+}
+
+/// A note touched by a vault-wide replace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplaceChange {
+    pub path: String,
+    /// Occurrences replaced (or, for a dry run, that would be)
+    pub matches: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplaceEdit {
+    pub changes: Vec<ReplaceChange>,
+    /// Revision after the replace; `None` for a dry run or when nothing matched
+    pub revision: Option<Revision>,
+}
+
+/// One step of a `batch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteOp {
+    Create { path: String },
+    Save { path: String, content: String },
+    Rename { from: String, to: String },
+    Delete { path: String },
+    Archive { path: String },
+}
+
+/// How to take back a step of a `batch` that failed further on.
+enum Undo {
+    /// A note that didn't exist before
+    Remove(String),
+    /// Raw file content to put back
+    Write {
+        path: String,
+        raw: String,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    /// A deleted note to bring back from the trash
+    Untrash(String),
+    /// Raw files of a deleted subtree, for stores without a trash
+    Rewrite(Vec<(String, String)>),
+}
+
+/// Dedupes a selection and drops notes whose ancestor is also selected, since
+/// operating on the ancestor already covers them.
+fn selection_roots(paths: &[String]) -> Vec<String> {
+    let mut roots: Vec<String> = Vec::new();
+    for path in paths {
+        let covered = paths
+            .iter()
+            .any(|other| path.starts_with(&format!("{}/", other)));
+        if !covered && !roots.contains(path) {
+            roots.push(path.clone());
+        }
+    }
+    roots
+}
+
+fn note_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+impl NotesApi {
+    /// Archives every selected note; see `archive_note`.
+    pub fn archive_notes(&mut self, paths: &[String]) -> Result<Revision> {
+        self.ensure_writable()?;
+
+        let roots = selection_roots(paths);
+        self.ensure_all_exist(&roots)?;
+
+        self.run_batch(|api| {
+            for path in &roots {
+                api.archive_note(path)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Deletes every selected note and its descendants; see `delete_note`.
+    ///
+    /// A checkpoint is created first so the deletion can be undone.
+    pub fn delete_notes(&mut self, paths: &[String]) -> Result<Revision> {
+        self.ensure_writable()?;
+
+        let roots = selection_roots(paths);
+        self.ensure_all_exist(&roots)?;
+        self.create_checkpoint(&format!("Before deleting {} notes", roots.len()))?;
+
+        self.run_batch(|api| {
+            for path in &roots {
+                api.delete_note(path)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Moves every selected note under `dest_parent` (empty for the vault root),
+    /// keeping their names.
+    ///
+    /// Fails before moving anything if a destination is taken, two selected notes share
+    /// a name, or a note would be moved into its own subtree. Notes already under
+    /// `dest_parent` are left alone.
+    pub fn move_notes(&mut self, paths: &[String], dest_parent: &str) -> Result<Revision> {
+        self.ensure_writable()?;
+
+        if !dest_parent.is_empty() && !self.note_exists(dest_parent)? {
+            return Err(Error::ParentNotFound(dest_parent.to_string()));
+        }
+
+        let roots = selection_roots(paths);
+        self.ensure_all_exist(&roots)?;
+
+        let mut moves: Vec<(String, String)> = Vec::new();
+        let mut targets: HashSet<String> = HashSet::new();
+        for path in roots {
+            if dest_parent == path || dest_parent.starts_with(&format!("{}/", path)) {
+                return Err(Error::InvalidInput(format!(
+                    "Cannot move {} into itself",
+                    path
+                )));
+            }
+
+            let target = if dest_parent.is_empty() {
+                note_name(&path).to_string()
+            } else {
+                format!("{}/{}", dest_parent, note_name(&path))
+            };
+            if target == path {
+                continue;
+            }
+            if !targets.insert(target.clone()) || self.note_exists(&target)? {
+                return Err(Error::AlreadyExists(target));
+            }
+            moves.push((path, target));
+        }
+
+        self.run_batch(|api| {
+            for (from, to) in &moves {
+                api.rename_note(from, to)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Sets (or with `None`, removes) the frontmatter property `key` on every selected
+    /// note.
+    ///
+    /// With `dry_run` nothing is written and the result previews the changes. Otherwise
+    /// all notes are updated as one batch: if any write fails, the notes already
+    /// written are restored and the index is rebuilt.
+    pub fn bulk_set_property(
+        &mut self,
+        selection: &NoteSelection,
+        key: &str,
+        value: Option<PropertyValue>,
+        dry_run: bool,
+    ) -> Result<BulkPropertyEdit> {
+        crate::properties::validate_property_key(key)?;
+        if !dry_run {
+            self.ensure_writable()?;
+        }
+
+        let paths: Vec<String> = match selection {
+            NoteSelection::Paths(paths) => {
+                let mut seen = HashSet::new();
+                let unique: Vec<String> =
+                    paths.iter().filter(|p| seen.insert(*p)).cloned().collect();
+                self.ensure_all_exist(&unique)?;
+                unique
+            }
+            NoteSelection::ChildrenOf(parent) => {
+                if !self.note_exists(parent)? {
+                    return Err(Error::NotFound(parent.clone()));
+                }
+                self.get_children(parent)?
+                    .into_iter()
+                    .filter(|child| !child.archived)
+                    .map(|child| child.path)
+                    .collect()
+            }
+            NoteSelection::Search(query) => self
+                .search(query)?
+                .into_iter()
+                .map(|note| note.path)
+                .collect(),
+        };
+
+        // (path, original content, updated content, previous value)
+        let mut planned: Vec<(String, String, String, Option<PropertyValue>)> = Vec::new();
+        for path in paths {
+            let original = self.read_content(&path)?;
+            let (frontmatter, _) = Frontmatter::parse(&original);
+            let before = frontmatter.get(key).cloned();
+            if before == value {
+                continue;
+            }
+            let updated = match &value {
+                Some(value) => set_property_in_content(&original, key, value.clone()),
+                None => remove_property_in_content(&original, key),
+            };
+            planned.push((path, original, updated, before));
+        }
+
+        let revision = if dry_run || planned.is_empty() {
+            None
+        } else {
+            let writes: Vec<(&str, &str, &str)> = planned
+                .iter()
+                .map(|(path, original, updated, _)| {
+                    (path.as_str(), original.as_str(), updated.as_str())
+                })
+                .collect();
+            Some(self.write_batch(&writes)?)
+        };
+
+        Ok(BulkPropertyEdit {
+            changes: planned
+                .into_iter()
+                .map(|(path, _, _, before)| PropertyChange {
+                    path,
+                    before,
+                    after: value.clone(),
+                })
+                .collect(),
+            revision,
+        })
+    }
+
+    /// Changes the value `old` of frontmatter property `key` to `new` across all
+    /// non-archived notes, both where it is the whole value and where it is a list item.
+    ///
+    /// With `dry_run` nothing is written and the result previews each note's value
+    /// before and after. Otherwise all notes are updated as one batch.
+    pub fn rename_property_value(
+        &mut self,
+        key: &str,
+        old: &str,
+        new: &str,
+        dry_run: bool,
+    ) -> Result<BulkPropertyEdit> {
+        let (old, new) = (old.trim(), new.trim());
+        if old.is_empty() || new.is_empty() || new.contains('\n') {
+            return Err(Error::InvalidInput(format!(
+                "Cannot rename {:?} to {:?}",
+                old, new
+            )));
+        }
+        if !dry_run {
+            self.ensure_writable()?;
+        }
+
+        let rename = |value: &str| {
+            if value == old {
+                new.to_string()
+            } else {
+                value.to_string()
+            }
+        };
+
+        // (path, original content, updated content, change)
+        let mut planned: Vec<(String, String, String, PropertyChange)> = Vec::new();
+        for note in self.get_all_notes()? {
+            // Encrypted notes are left out while the vault is locked
+            let Ok(original) = self.read_content(&note.path) else {
+                continue;
+            };
+            let (frontmatter, _) = Frontmatter::parse(&original);
+            let Some(before) = frontmatter.get(key).cloned() else {
+                continue;
+            };
+            let after = match &before {
+                PropertyValue::Text(text) => PropertyValue::Text(rename(text)),
+                PropertyValue::List(items) => {
+                    PropertyValue::List(items.iter().map(|item| rename(item)).collect())
+                }
+            };
+            if after == before {
+                continue;
+            }
+            let updated = set_property_in_content(&original, key, after.clone());
+            let change = PropertyChange {
+                path: note.path.clone(),
+                before: Some(before),
+                after: Some(after),
+            };
+            planned.push((note.path, original, updated, change));
+        }
+        planned.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let revision = if dry_run || planned.is_empty() {
+            None
+        } else {
+            let writes: Vec<(&str, &str, &str)> = planned
+                .iter()
+                .map(|(path, original, updated, _)| {
+                    (path.as_str(), original.as_str(), updated.as_str())
+                })
+                .collect();
+            Some(self.write_batch(&writes)?)
+        };
+
+        Ok(BulkPropertyEdit {
+            changes: planned
+                .into_iter()
+                .map(|(_, _, _, change)| change)
+                .collect(),
+            revision,
+        })
+    }
+
+    /// Replaces every occurrence of `find` (literal, case-sensitive) with `replacement`
+    /// across all non-archived notes.
+    ///
+    /// With `dry_run` nothing is written and the result lists the notes that would
+    /// change. Applied replaces run as one batch like `bulk_set_property` and are
+    /// recorded in the search history so they can be re-run.
+    pub fn replace_in_notes(
+        &mut self,
+        find: &str,
+        replacement: &str,
+        dry_run: bool,
+    ) -> Result<ReplaceEdit> {
+        if find.is_empty() {
+            return Err(Error::InvalidInput("Nothing to replace".to_string()));
+        }
+        if !dry_run {
+            self.ensure_writable()?;
+        }
+
+        let mut planned: Vec<(String, String, usize)> = Vec::new();
+        for note in self.get_all_notes()? {
+            // Encrypted notes are left out while the vault is locked
+            let Ok(content) = self.read_content(&note.path) else {
+                continue;
+            };
+            let matches = content.matches(find).count();
+            if matches > 0 {
+                planned.push((note.path, content, matches));
+            }
+        }
+        planned.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let revision = if dry_run {
+            None
+        } else {
+            self.record_replace(find, replacement)?;
+            if planned.is_empty() {
+                None
+            } else {
+                let updated: Vec<String> = planned
+                    .iter()
+                    .map(|(_, content, _)| content.replace(find, replacement))
+                    .collect();
+                let writes: Vec<(&str, &str, &str)> = planned
+                    .iter()
+                    .zip(&updated)
+                    .map(|((path, original, _), updated)| {
+                        (path.as_str(), original.as_str(), updated.as_str())
+                    })
+                    .collect();
+                Some(self.write_batch(&writes)?)
+            }
+        };
+
+        Ok(ReplaceEdit {
+            changes: planned
+                .into_iter()
+                .map(|(path, _, matches)| ReplaceChange { path, matches })
+                .collect(),
+            revision,
+        })
+    }
+
+    /// Applies `ops` in order as one batch, e.g. for an import or a drag and drop of
+    /// several notes.
+    ///
+    /// The watcher is suppressed once for the whole batch and every index update lands
+    /// in one savepoint. The first failing step stops the batch, and the steps before
+    /// it are taken back newest first: created notes are removed, saved ones get their
+    /// old content, moves are reversed, and deleted notes come back from the trash.
+    /// The index is then rebuilt from disk. Person notes created for new @mentions
+    /// are kept.
+    pub fn batch(&mut self, ops: Vec<NoteOp>) -> Result<Revision> {
+        self.ensure_writable()?;
+
+        self.run_batch(|api| {
+            let mut done = Vec::new();
+            for op in ops {
+                match api.apply_op(op) {
+                    Ok(undo) => done.push(undo),
+                    Err(e) => {
+                        // Best effort: run_batch resyncs the index with what is on disk
+                        for undo in done.into_iter().rev() {
+                            let _ = api.undo(undo);
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Runs one step of a `batch` and returns how to take it back.
+    fn apply_op(&mut self, op: NoteOp) -> Result<Undo> {
+        match op {
+            NoteOp::Create { path } => {
+                self.create_note(&path)?;
+                Ok(Undo::Remove(path))
+            }
+            NoteOp::Save { path, content } => {
+                let before = self.fs.read_note(&path).ok();
+                self.save_note(&path, &content)?;
+                Ok(match before {
+                    Some(raw) => Undo::Write { path, raw },
+                    None => Undo::Remove(path),
+                })
+            }
+            NoteOp::Rename { from, to } => {
+                self.rename_note(&from, &to)?;
+                Ok(Undo::Rename { from: to, to: from })
+            }
+            NoteOp::Delete { path } => {
+                if self.fs.is_persistent() {
+                    self.delete_note(&path)?;
+                    return Ok(Undo::Untrash(path));
+                }
+                let mut files = Vec::new();
+                for note in self.fs.scan_subtree(&path)? {
+                    let raw = self.fs.read_note(&note.path)?;
+                    files.push((note.path, raw));
+                }
+                self.delete_note(&path)?;
+                Ok(Undo::Rewrite(files))
+            }
+            NoteOp::Archive { path } => {
+                self.archive_note(&path)?;
+                Ok(Undo::Rename {
+                    from: archived_path(&path),
+                    to: path,
+                })
+            }
+        }
+    }
+
+    fn undo(&mut self, undo: Undo) -> Result<()> {
+        match undo {
+            Undo::Remove(path) => self.fs.delete_note(&path)?,
+            Undo::Write { path, raw } => self.fs.write_note(&path, &raw)?,
+            Undo::Rename { from, to } => {
+                self.rename_note(&from, &to)?;
+            }
+            Undo::Untrash(path) => {
+                self.restore_from_trash(&path)?;
+            }
+            Undo::Rewrite(files) => {
+                for (path, raw) in files {
+                    self.fs.write_note(&path, &raw)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes `(path, original, updated)` triples of plain text as one batch, each the
+    /// way `save_note` writes (encrypted again if need be, through the journal). If a
+    /// write fails, the notes already written get their original content back and the
+    /// index is resynced; otherwise person notes are created for new @mentions.
+    pub(crate) fn write_batch(&mut self, writes: &[(&str, &str, &str)]) -> Result<Revision> {
+        self.run_batch(|api| {
+            for (i, (path, _, updated)) in writes.iter().enumerate() {
+                if let Err(e) = api.write_note_content(path, updated) {
+                    // Put back what was already written; run_batch then resyncs
+                    for (path, original, _) in &writes[..=i] {
+                        if let Ok(original) = api.content_for_disk(path, original) {
+                            let _ = api.fs.write_note(path, &original);
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+            for (path, _, _) in writes {
+                api.create_mentioned_people(path)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn ensure_all_exist(&self, paths: &[String]) -> Result<()> {
+        for path in paths {
+            if !self.note_exists(path)? {
+                return Err(Error::NotFound(path.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `ops` with all index writes in one savepoint and returns the revision after it.
+    pub(crate) fn run_batch<F>(&mut self, ops: F) -> Result<Revision>
+    where
+        F: FnOnce(&mut NotesApi) -> Result<()>,
+    {
+        let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
+        self.db.execute_batch("SAVEPOINT batch")?;
+
+        match ops(self) {
+            Ok(()) => {
+                self.db.execute_batch("RELEASE batch")?;
+                self.revision()
+            }
+            Err(e) => {
+                self.db.execute_batch("ROLLBACK TO batch; RELEASE batch")?;
+                // Earlier items may already have been moved on disk
+                self.rescan()?;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in ["inbox", "inbox/a", "inbox/a/sub", "inbox/b", "projects"] {
+            api.create_note(path).unwrap();
+        }
+        (temp_dir, api)
+    }
+
+    fn paths(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_selection_roots() {
+        let roots = selection_roots(&paths(&["a/b", "a", "c", "a", "ab"]));
+        assert_eq!(roots, paths(&["a", "c", "ab"]));
+    }
+
+    #[test]
+    fn test_move_notes() {
+        let (temp_dir, mut api) = setup();
+
+        api.move_notes(&paths(&["inbox/a", "inbox/b", "inbox/a/sub"]), "projects")
+            .unwrap();
+
+        assert!(api.note_exists("projects/a").unwrap());
+        assert!(api.note_exists("projects/a/sub").unwrap());
+        assert!(api.note_exists("projects/b").unwrap());
+        assert!(!api.note_exists("inbox/a").unwrap());
+        assert!(temp_dir.path().join("projects/a/sub/_index.md").exists());
+    }
+
+    #[test]
+    fn test_move_notes_clash_moves_nothing() {
+        let (_temp_dir, mut api) = setup();
+        api.create_note("projects/b").unwrap();
+
+        assert!(matches!(
+            api.move_notes(&paths(&["inbox/a", "inbox/b"]), "projects"),
+            Err(Error::AlreadyExists(p)) if p == "projects/b"
+        ));
+        assert!(api.note_exists("inbox/a").unwrap());
+        assert!(!api.note_exists("projects/a").unwrap());
+    }
+
+    #[test]
+    fn test_move_notes_into_own_subtree() {
+        let (_temp_dir, mut api) = setup();
+
+        assert!(matches!(
+            api.move_notes(&paths(&["inbox"]), "inbox/a"),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_archive_notes() {
+        let (_temp_dir, mut api) = setup();
+
+        api.archive_notes(&paths(&["inbox/a", "inbox/b"])).unwrap();
+
+        assert!(api.note_exists("inbox/_archive/a").unwrap());
+        assert!(api.note_exists("inbox/_archive/a/sub").unwrap());
+        assert!(api.note_exists("inbox/_archive/b").unwrap());
+    }
+
+    #[test]
+    fn test_delete_notes() {
+        let (temp_dir, mut api) = setup();
+        let before = api.revision().unwrap();
+
+        let revision = api.delete_notes(&paths(&["inbox/a", "projects"])).unwrap();
+
+        assert!(revision > before);
+        assert!(!api.note_exists("inbox/a/sub").unwrap());
+        assert!(!api.note_exists("projects").unwrap());
+        assert!(api.note_exists("inbox/b").unwrap());
+        assert!(!temp_dir.path().join("projects").exists());
+    }
+
+    #[test]
+    fn test_batch_applies_ops_in_order() {
+        let (_temp_dir, mut api) = setup();
+        let revision = api.revision().unwrap();
+
+        api.batch(vec![
+            NoteOp::Create {
+                path: "projects/rust".to_string(),
+            },
+            NoteOp::Save {
+                path: "projects/rust".to_string(),
+                content: "Ownership".to_string(),
+            },
+            NoteOp::Rename {
+                from: "projects/rust".to_string(),
+                to: "projects/rustlang".to_string(),
+            },
+            NoteOp::Archive {
+                path: "inbox/b".to_string(),
+            },
+            NoteOp::Delete {
+                path: "inbox/a".to_string(),
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(
+            api.get_note("projects/rustlang").unwrap().content,
+            "Ownership"
+        );
+        assert!(!api.note_exists("projects/rust").unwrap());
+        assert!(!api.note_exists("inbox/a/sub").unwrap());
+        assert!(api.revision().unwrap() > revision);
+    }
+
+    #[test]
+    fn test_failed_batch_is_taken_back() {
+        let (temp_dir, mut api) = setup();
+        api.save_note("projects", "Plans").unwrap();
+
+        let result = api.batch(vec![
+            NoteOp::Create {
+                path: "inbox/c".to_string(),
+            },
+            NoteOp::Save {
+                path: "projects".to_string(),
+                content: "Overwritten".to_string(),
+            },
+            NoteOp::Rename {
+                from: "inbox/a".to_string(),
+                to: "inbox/renamed".to_string(),
+            },
+            NoteOp::Archive {
+                path: "inbox/b".to_string(),
+            },
+            NoteOp::Delete {
+                path: "inbox/renamed".to_string(),
+            },
+            NoteOp::Rename {
+                from: "inbox/missing".to_string(),
+                to: "inbox/d".to_string(),
+            },
+            NoteOp::Create {
+                path: "inbox/e".to_string(),
+            },
+        ]);
+
+        assert!(matches!(result, Err(Error::NotFound(_))));
+        for path in ["inbox/c", "inbox/e", "inbox/renamed", "inbox/_archive/b"] {
+            assert!(!api.note_exists(path).unwrap(), "{} exists", path);
+        }
+        for path in ["inbox/a", "inbox/a/sub", "inbox/b"] {
+            assert!(api.note_exists(path).unwrap(), "{} is missing", path);
+        }
+        assert_eq!(api.get_note("projects").unwrap().content, "Plans");
+        let children = api.get_children("inbox").unwrap();
+        assert!(children.iter().all(|child| !child.archived));
+        assert!(!temp_dir.path().join("inbox/c").exists());
+        assert!(api.list_trash().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_failed_batch_is_taken_back_in_memory() {
+        let store = crate::memory_store::MemoryNoteStore::new();
+        let mut api = NotesApi::with_store(Box::new(store)).unwrap();
+        api.create_note("a").unwrap();
+        api.save_note("a", "Kept").unwrap();
+
+        let result = api.batch(vec![
+            NoteOp::Delete {
+                path: "a".to_string(),
+            },
+            NoteOp::Delete {
+                path: "missing".to_string(),
+            },
+        ]);
+
+        assert!(result.is_err());
+        assert_eq!(api.get_note("a").unwrap().content, "Kept");
+    }
+
+    #[test]
+    fn test_missing_note_fails_before_changes() {
+        let (_temp_dir, mut api) = setup();
+
+        assert!(matches!(
+            api.delete_notes(&paths(&["inbox/a", "nope"])),
+            Err(Error::NotFound(_))
+        ));
+        assert!(api.note_exists("inbox/a").unwrap());
+    }
+
+    #[test]
+    fn test_bulk_set_property_dry_run_and_apply() {
+        let (_temp_dir, mut api) = setup();
+        api.save_note("inbox/b", "---\nstatus: done\n---\nB")
+            .unwrap();
+        let done = Some(PropertyValue::Text("done".to_string()));
+        let selection = NoteSelection::ChildrenOf("inbox".to_string());
+
+        let preview = api
+            .bulk_set_property(&selection, "status", done.clone(), true)
+            .unwrap();
+        // inbox/b already has the value, so only inbox/a would change
+        assert_eq!(
+            preview.changes,
+            vec![PropertyChange {
+                path: "inbox/a".to_string(),
+                before: None,
+                after: done.clone(),
+            }]
+        );
+        assert!(preview.revision.is_none());
+        assert!(!api.get_note("inbox/a").unwrap().content.contains("status"));
+
+        let before = api.revision().unwrap();
+        let edit = api
+            .bulk_set_property(&selection, "status", done.clone(), false)
+            .unwrap();
+        assert!(edit.revision.unwrap() > before);
+        let content = api.get_note("inbox/a").unwrap().content;
+        let (frontmatter, _) = Frontmatter::parse(&content);
+        assert_eq!(frontmatter.get_text("status"), Some("done"));
+    }
+
+    #[test]
+    fn test_bulk_edits_save_like_save_note() {
+        let (temp_dir, mut api) = setup();
+        let mut config = api.config().clone();
+        config.mentions.auto_create = true;
+        api.set_config(config).unwrap();
+        api.save_note("inbox/b", "ask Ada").unwrap();
+
+        api.bulk_set_property(
+            &NoteSelection::Paths(paths(&["inbox/a"])),
+            "status",
+            Some(PropertyValue::Text("open".to_string())),
+            false,
+        )
+        .unwrap();
+        api.replace_in_notes("ask Ada", "ask @Ada", false).unwrap();
+
+        // Both edits went through the journal, and the new mention got its note
+        let journal =
+            std::fs::read_to_string(temp_dir.path().join(crate::journal::JOURNAL_FILE_NAME))
+                .unwrap();
+        assert!(journal.contains("status: open"));
+        assert!(journal.contains("ask @Ada"));
+        assert!(api.note_exists("people/ada").unwrap());
+    }
+
+    #[test]
+    fn test_bulk_remove_property() {
+        let (_temp_dir, mut api) = setup();
+        api.save_note("inbox/a", "---\ntags: [x]\nstatus: open\n---\nA")
+            .unwrap();
+        api.save_note("inbox/b", "---\nstatus: open\n---\nB")
+            .unwrap();
+
+        let edit = api
+            .bulk_set_property(
+                &NoteSelection::Paths(paths(&["inbox/a", "inbox/b", "inbox/a"])),
+                "status",
+                None,
+                false,
+            )
+            .unwrap();
+
+        assert_eq!(edit.changes.len(), 2);
+        assert_eq!(
+            api.get_note("inbox/a").unwrap().content,
+            "---\ntags: [x]\n---\nA"
+        );
+        assert!(matches!(
+            api.bulk_set_property(
+                &NoteSelection::Paths(paths(&["nope"])),
+                "status",
+                None,
+                false
+            ),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            api.bulk_set_property(&NoteSelection::Paths(Vec::new()), "a: b", None, true),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_rename_property_value() {
+        let (_temp_dir, mut api) = setup();
+        api.save_note("inbox/a", "---\nstatus: doing\n---\nA")
+            .unwrap();
+        api.save_note("inbox/b", "---\nstatus: [doing, blocked]\n---\nB")
+            .unwrap();
+        api.save_note("projects", "---\nstatus: done\n---\nstatus: doing")
+            .unwrap();
+
+        let preview = api
+            .rename_property_value("status", "doing", "in progress", true)
+            .unwrap();
+        assert!(preview.revision.is_none());
+        assert_eq!(
+            preview.changes,
+            [
+                PropertyChange {
+                    path: "inbox/a".to_string(),
+                    before: Some(PropertyValue::Text("doing".to_string())),
+                    after: Some(PropertyValue::Text("in progress".to_string())),
+                },
+                PropertyChange {
+                    path: "inbox/b".to_string(),
+                    before: Some(PropertyValue::List(paths(&["doing", "blocked"]))),
+                    after: Some(PropertyValue::List(paths(&["in progress", "blocked"]))),
+                },
+            ]
+        );
+        assert_eq!(
+            api.get_note("inbox/a").unwrap().content,
+            "---\nstatus: doing\n---\nA"
+        );
+
+        let edit = api
+            .rename_property_value("status", "doing", "in progress", false)
+            .unwrap();
+        assert!(edit.revision.is_some());
+        assert_eq!(
+            api.get_note("inbox/a").unwrap().content,
+            "---\nstatus: in progress\n---\nA"
+        );
+        // Only the frontmatter value changes, not the same text in the body
+        assert_eq!(
+            api.get_note("projects").unwrap().content,
+            "---\nstatus: done\n---\nstatus: doing"
+        );
+    }
+}
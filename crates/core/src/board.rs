@@ -0,0 +1,211 @@
+use crate::changes::Revision;
+use crate::frontmatter::{Frontmatter, PropertyValue};
+use crate::notes::{Error, NoteMetadata, NotesApi, Result, get_parent_path};
+
+/// Frontmatter key on the parent note listing the board columns in order.
+pub const COLUMNS_KEY: &str = "columns";
+
+/// Frontmatter key holding a note's status; on board cards, the column they belong to.
+pub const STATUS_KEY: &str = "status";
+
+/// A Trello-style view of a note's children, grouped by their `status` property.
+#[derive(Debug, Clone)]
+pub struct Board {
+    pub path: String,
+    pub columns: Vec<BoardColumn>,
+    /// Children whose status is missing or doesn't match any column
+    pub uncategorized: Vec<NoteMetadata>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BoardColumn {
+    pub name: String,
+    pub cards: Vec<NoteMetadata>,
+}
+
+impl NotesApi {
+    /// Builds a board for the note at `path` from its non-archived children.
+    ///
+    /// Columns come from the parent's `columns:` frontmatter list. If it isn't set,
+    /// columns are derived from the distinct `status` values of the children.
+    pub fn get_board(&self, path: &str) -> Result<Board> {
+        let parent_content = self.read_content(path)?;
+        let (parent_fm, _) = Frontmatter::parse(&parent_content);
+        let declared: Vec<String> = parent_fm
+            .get(COLUMNS_KEY)
+            .map(|v| v.as_list())
+            .unwrap_or_default();
+
+        let mut cards: Vec<(NoteMetadata, Option<String>)> = Vec::new();
+        for child in self.get_children(path)? {
+            if child.archived {
+                continue;
+            }
+            let content = self.read_content(&child.path).unwrap_or_default();
+            let (fm, _) = Frontmatter::parse(&content);
+            let status = fm.get_text(STATUS_KEY).map(|s| s.to_string());
+            cards.push((child, status));
+        }
+
+        Ok(group_cards(path, declared, cards))
+    }
+
+    /// Moves a card to another column by rewriting the child's `status` property.
+    ///
+    /// The column must exist on the parent's board. The card is saved like any other
+    /// edit, through the journal.
+    pub fn move_card(&mut self, path: &str, to_column: &str) -> Result<Revision> {
+        self.ensure_writable()?;
+
+        let parent_path = get_parent_path(path).ok_or_else(|| Error::NotFound(path.to_string()))?;
+
+        let board = self.get_board(&parent_path)?;
+        if !board.columns.iter().any(|c| c.name == to_column) {
+            return Err(Error::InvalidInput(format!(
+                "Unknown board column: {}",
+                to_column
+            )));
+        }
+
+        self.set_property(path, STATUS_KEY, PropertyValue::Text(to_column.to_string()))
+    }
+}
+
+/// Groups cards (children with their status) into board columns.
+///
+/// Columns come from `declared`; when it's empty they are derived from the
+/// distinct statuses. Cards keep their relative order within a column.
+pub(crate) fn group_cards(
+    path: &str,
+    declared: Vec<String>,
+    cards: Vec<(NoteMetadata, Option<String>)>,
+) -> Board {
+    let column_names = if declared.is_empty() {
+        let mut names: Vec<String> = cards.iter().filter_map(|(_, s)| s.clone()).collect();
+        names.sort();
+        names.dedup();
+        names
+    } else {
+        declared
+    };
+
+    let mut columns: Vec<BoardColumn> = column_names
+        .into_iter()
+        .map(|name| BoardColumn {
+            name,
+            cards: Vec::new(),
+        })
+        .collect();
+    let mut uncategorized = Vec::new();
+
+    for (card, status) in cards {
+        let column = status
+            .as_deref()
+            .and_then(|s| columns.iter_mut().find(|c| c.name == s));
+        match column {
+            Some(column) => column.cards.push(card),
+            None => uncategorized.push(card),
+        }
+    }
+
+    Board {
+        path: path.to_string(),
+        columns,
+        uncategorized,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn card_paths(column: &BoardColumn) -> Vec<&str> {
+        column.cards.iter().map(|c| c.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_board_with_declared_columns() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("project").unwrap();
+        api.save_note("project", "---\ncolumns: [todo, doing, done]\n---\n")
+            .unwrap();
+        api.create_note("project/a").unwrap();
+        api.save_note("project/a", "---\nstatus: todo\n---\nA")
+            .unwrap();
+        api.create_note("project/b").unwrap();
+        api.save_note("project/b", "---\nstatus: done\n---\nB")
+            .unwrap();
+        api.create_note("project/c").unwrap();
+
+        let board = api.get_board("project").unwrap();
+        let names: Vec<_> = board.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["todo", "doing", "done"]);
+        assert_eq!(card_paths(&board.columns[0]), vec!["project/a"]);
+        assert!(board.columns[1].cards.is_empty());
+        assert_eq!(card_paths(&board.columns[2]), vec!["project/b"]);
+        assert_eq!(board.uncategorized.len(), 1);
+        assert_eq!(board.uncategorized[0].path, "project/c");
+    }
+
+    #[test]
+    fn test_board_derives_columns_from_children() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("project").unwrap();
+        api.create_note("project/a").unwrap();
+        api.save_note("project/a", "---\nstatus: doing\n---\n")
+            .unwrap();
+        api.create_note("project/b").unwrap();
+        api.save_note("project/b", "---\nstatus: backlog\n---\n")
+            .unwrap();
+
+        let board = api.get_board("project").unwrap();
+        let names: Vec<_> = board.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["backlog", "doing"]);
+    }
+
+    #[test]
+    fn test_move_card() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("project").unwrap();
+        api.save_note("project", "---\ncolumns: [todo, done]\n---\n")
+            .unwrap();
+        api.create_note("project/a").unwrap();
+        api.save_note("project/a", "# Card A\n").unwrap();
+
+        api.move_card("project/a", "done").unwrap();
+
+        let content = std::fs::read_to_string(temp_dir.path().join("project/a/_index.md")).unwrap();
+        assert_eq!(content, "---\nstatus: done\n---\n# Card A\n");
+
+        let board = api.get_board("project").unwrap();
+        assert_eq!(card_paths(&board.columns[1]), vec!["project/a"]);
+        assert!(api.last_save.is_some());
+        let journal =
+            std::fs::read_to_string(temp_dir.path().join(crate::journal::JOURNAL_FILE_NAME))
+                .unwrap();
+        assert!(journal.contains("status: done"));
+    }
+
+    #[test]
+    fn test_move_card_unknown_column() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("project").unwrap();
+        api.save_note("project", "---\ncolumns: [todo]\n---\n")
+            .unwrap();
+        api.create_note("project/a").unwrap();
+
+        assert!(matches!(
+            api.move_card("project/a", "nope"),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,245 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::OptionalExtension;
+use serde_json::{Value, json};
+
+use crate::changes::Revision;
+use crate::filesystem::NoteStore;
+use crate::notes::{Error, NotesApi, OperationGuard, Result, get_parent_path};
+
+/// A canvas document stored as `_canvas.json` alongside a note's `_index.md`.
+///
+/// The data is kept as free-form JSON (excalidraw-style `{ "elements": [...] }`)
+/// so frontends can evolve the drawing format without core changes.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    pub path: String,
+    pub data: Value,
+    pub modified: SystemTime,
+}
+
+/// Returns the data written for a freshly created canvas.
+fn empty_canvas() -> Value {
+    json!({ "elements": [] })
+}
+
+/// Collects every string stored under a `text` key, depth-first.
+///
+/// Excalidraw stores labels on text elements and on bound containers
+/// (`label.text`), so a recursive walk covers both without knowing the schema.
+pub fn extract_canvas_labels(data: &Value) -> Vec<String> {
+    let mut labels = Vec::new();
+    collect_labels(data, &mut labels);
+    labels
+}
+
+fn collect_labels(value: &Value, labels: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match value {
+                    Value::String(text) if key == "text" => {
+                        if !text.trim().is_empty() {
+                            labels.push(text.clone());
+                        }
+                    }
+                    _ => collect_labels(value, labels),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_labels(item, labels);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Reads the canvas for a note (if any) and returns its labels joined for indexing.
+pub(crate) fn canvas_search_text(fs: &dyn NoteStore, path: &str) -> Option<String> {
+    if !fs.canvas_exists(path) {
+        return None;
+    }
+    let raw = fs.read_canvas(path).ok()?;
+    let data: Value = serde_json::from_str(&raw).ok()?;
+    let labels = extract_canvas_labels(&data);
+    if labels.is_empty() {
+        None
+    } else {
+        Some(labels.join("\n"))
+    }
+}
+
+impl NotesApi {
+    /// Creates a new canvas document at the specified path.
+    ///
+    /// The canvas lives in the regular note hierarchy: an empty `_index.md` is created
+    /// if the note doesn't exist yet, and an empty `_canvas.json` is written next to it.
+    /// Returns an error if a canvas already exists at this path.
+    pub fn create_canvas(&mut self, path: &str) -> Result<Canvas> {
+        self.ensure_writable()?;
+
+        if self.fs.canvas_exists(path) {
+            return Err(Error::AlreadyExists(path.to_string()));
+        }
+
+        if !self.note_exists(path)? {
+            self.create_note(path)?;
+        }
+
+        {
+            let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
+            let data = serde_json::to_string_pretty(&empty_canvas())
+                .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+            self.fs.write_canvas(path, &data)?;
+        }
+
+        self.get_canvas(path)
+    }
+
+    /// Retrieves the canvas document for a note.
+    ///
+    /// Returns `NotFound` if the note has no canvas.
+    pub fn get_canvas(&self, path: &str) -> Result<Canvas> {
+        let raw = self.fs.read_canvas(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Error::NotFound(path.to_string()),
+            _ => Error::Io(e),
+        })?;
+        let data = serde_json::from_str(&raw)
+            .map_err(|e| Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
+
+        let mtime: i64 = self
+            .db
+            .query_row(
+                "SELECT mtime FROM notes WHERE path = ?1",
+                rusqlite::params![path],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| Error::NotFound(path.to_string()))?;
+
+        Ok(Canvas {
+            path: path.to_string(),
+            data,
+            modified: UNIX_EPOCH + std::time::Duration::from_secs(mtime as u64),
+        })
+    }
+
+    /// Saves canvas data and re-indexes its text labels for search.
+    ///
+    /// The owning note must already exist (use `create_canvas` for new canvases).
+    pub fn save_canvas(&mut self, path: &str, data: &Value) -> Result<Revision> {
+        self.ensure_writable()?;
+
+        let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
+
+        if !self.note_exists(path)? {
+            if let Some(parent) = get_parent_path(path) {
+                return Err(Error::ParentNotFound(parent));
+            }
+            return Err(Error::NotFound(path.to_string()));
+        }
+
+        let raw =
+            serde_json::to_string_pretty(data).map_err(|e| Error::Io(std::io::Error::other(e)))?;
+        self.fs.write_canvas(path, &raw)?;
+
+        self.sync_note(path)?;
+
+        self.revision()
+    }
+
+    /// Returns true if the note at `path` has a canvas document.
+    pub fn has_canvas(&self, path: &str) -> bool {
+        self.fs.canvas_exists(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_create_canvas() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        let canvas = api.create_canvas("board").unwrap();
+        assert_eq!(canvas.path, "board");
+        assert_eq!(canvas.data, empty_canvas());
+
+        assert!(api.note_exists("board").unwrap());
+        assert!(api.has_canvas("board"));
+        assert!(temp_dir.path().join("board/_canvas.json").exists());
+        assert!(temp_dir.path().join("board/_index.md").exists());
+    }
+
+    #[test]
+    fn test_create_canvas_twice_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_canvas("board").unwrap();
+        assert!(matches!(
+            api.create_canvas("board"),
+            Err(Error::AlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn test_save_canvas_indexes_labels() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_canvas("board").unwrap();
+        let data = json!({
+            "elements": [
+                { "type": "text", "text": "brainstorm" },
+                { "type": "rectangle", "label": { "text": "roadmap" } }
+            ]
+        });
+        api.save_canvas("board", &data).unwrap();
+
+        assert_eq!(api.get_canvas("board").unwrap().data, data);
+        assert_eq!(api.search("brainstorm").unwrap().len(), 1);
+        assert_eq!(api.search("roadmap").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_get_canvas_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("plain").unwrap();
+        assert!(matches!(api.get_canvas("plain"), Err(Error::NotFound(_))));
+    }
+
+    #[test]
+    fn test_get_canvas_reports_io_errors() {
+        let store = crate::MemoryNoteStore::new();
+        let mut api = NotesApi::with_store(Box::new(store.clone())).unwrap();
+        api.create_canvas("board").unwrap();
+
+        store.fail_next_read(std::io::ErrorKind::PermissionDenied);
+        assert!(matches!(
+            api.get_canvas("board"),
+            Err(Error::Io(e)) if e.kind() == std::io::ErrorKind::PermissionDenied
+        ));
+        assert!(api.get_canvas("board").is_ok());
+    }
+
+    #[test]
+    fn test_extract_canvas_labels() {
+        let data = json!({
+            "elements": [
+                { "text": "one" },
+                { "text": "   " },
+                { "children": [{ "text": "two" }] }
+            ]
+        });
+        assert_eq!(extract_canvas_labels(&data), vec!["one", "two"]);
+    }
+}
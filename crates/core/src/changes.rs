@@ -0,0 +1,287 @@
+//! Vault revision counter and change log.
+//!
+//! Every insert, delete, move, and content change on the notes table is appended to
+//! the `changes` log by triggers (see the v7/v8 migrations), whether it came from the
+//! API, a watcher sync, or a rescan. The log's autoincrement key is the vault revision:
+//! it only ever grows, so clients can cache by revision and ask what changed since.
+
+use rusqlite::{OptionalExtension, params};
+
+use crate::notes::{NotesApi, Result};
+use crate::revision_diff::{EditSummary, edit_summary};
+
+/// A point in the vault's history; mutating APIs return the revision after their change.
+pub type Revision = i64;
+
+/// How many log entries `startup_sync` keeps; older revisions get a full reset.
+const CHANGE_LOG_LIMIT: i64 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// Path changed (rename, move, archive); the previous path is in `old_path`
+    Moved,
+    /// Content changed
+    Modified,
+}
+
+impl ChangeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Moved => "moved",
+            ChangeKind::Modified => "modified",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<ChangeKind> {
+        match s {
+            "added" => Some(ChangeKind::Added),
+            "removed" => Some(ChangeKind::Removed),
+            "moved" => Some(ChangeKind::Moved),
+            "modified" => Some(ChangeKind::Modified),
+            _ => None,
+        }
+    }
+}
+
+/// A single entry of the change log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub revision: Revision,
+    pub kind: ChangeKind,
+    pub path: String,
+    pub old_path: Option<String>,
+    /// For `Modified`: what the edit did, when the earlier content was indexed as text
+    pub edit: Option<EditSummary>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Changes {
+    /// Latest revision; pass it to the next `get_changes_since` call
+    pub revision: Revision,
+    /// True if the requested revision is older than the retained log; the caller must
+    /// discard cached state and refetch
+    pub reset: bool,
+    /// Log entries after the requested revision, oldest first
+    pub changes: Vec<Change>,
+}
+
+impl NotesApi {
+    /// Returns the current vault revision (0 for a vault with no recorded changes).
+    pub fn revision(&self) -> Result<Revision> {
+        let revision: Option<i64> = self
+            .db
+            .query_row(
+                "SELECT seq FROM sqlite_sequence WHERE name = 'changes'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(revision.unwrap_or(0))
+    }
+
+    /// Returns every change recorded after `since`.
+    pub fn get_changes_since(&self, since: Revision) -> Result<Changes> {
+        let revision = self.revision()?;
+        if self.changes_pruned_after(since, revision)? {
+            return Ok(Changes {
+                revision,
+                reset: true,
+                changes: Vec::new(),
+            });
+        }
+
+        let mut stmt = self.db.prepare(
+            "SELECT revision, kind, path, old_path, words_added, words_removed, headings
+             FROM changes WHERE revision > ?1 ORDER BY revision",
+        )?;
+        let changes = stmt
+            .query_map(params![since], |row| {
+                let kind: String = row.get(1)?;
+                let words: Option<(i64, i64)> = match (row.get(4)?, row.get(5)?) {
+                    (Some(added), Some(removed)) => Some((added, removed)),
+                    _ => None,
+                };
+                let headings: Option<String> = row.get(6)?;
+                let edit = words.map(|(added, removed)| EditSummary {
+                    words_added: added as usize,
+                    words_removed: removed as usize,
+                    headings: headings
+                        .iter()
+                        .flat_map(|headings| headings.lines())
+                        .map(str::to_string)
+                        .collect(),
+                });
+                Ok((row.get(0)?, kind, row.get(2)?, row.get(3)?, edit))
+            })?
+            .filter_map(|row| match row {
+                Ok((revision, kind, path, old_path, edit)) => {
+                    ChangeKind::parse(&kind).map(|kind| {
+                        Ok(Change {
+                            revision,
+                            kind,
+                            path,
+                            old_path,
+                            edit,
+                        })
+                    })
+                }
+                Err(e) => Some(Err(e)),
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(Changes {
+            revision,
+            reset: false,
+            changes,
+        })
+    }
+
+    /// Returns true if entries after `since` have been pruned from the log.
+    pub(crate) fn changes_pruned_after(&self, since: Revision, latest: Revision) -> Result<bool> {
+        if since >= latest {
+            return Ok(false);
+        }
+        let oldest: Option<i64> =
+            self.db
+                .query_row("SELECT MIN(revision) FROM changes", [], |row| row.get(0))?;
+        Ok(oldest.is_none_or(|oldest| since < oldest - 1))
+    }
+
+    /// Attaches the summary of the edit from `old` to `new` to the log entry the note's
+    /// content change just added.
+    pub(crate) fn record_edit(&self, path: &str, old: &str, new: &str) -> Result<()> {
+        let summary = edit_summary(old, new);
+        self.db.execute(
+            "UPDATE changes SET words_added = ?2, words_removed = ?3, headings = ?4
+             WHERE revision = (SELECT MAX(revision) FROM changes)
+               AND kind = 'modified' AND path = ?1",
+            params![
+                path,
+                summary.words_added as i64,
+                summary.words_removed as i64,
+                summary.headings.join("\n"),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Drops change log entries beyond the most recent `CHANGE_LOG_LIMIT`.
+    pub(crate) fn prune_changes(&mut self) -> Result<()> {
+        self.db.execute(
+            "DELETE FROM changes WHERE revision <= (SELECT MAX(revision) FROM changes) - ?1",
+            params![CHANGE_LOG_LIMIT],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_mutations_advance_revision() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        assert_eq!(api.revision().unwrap(), 0);
+
+        let note = api.create_note("inbox").unwrap();
+        assert_eq!(note.revision, api.revision().unwrap());
+
+        let saved = api.save_note("inbox", "hello").unwrap();
+        assert!(saved > note.revision);
+
+        // Saving identical content is not a change
+        assert_eq!(api.save_note("inbox", "hello").unwrap(), saved);
+
+        let renamed = api.rename_note("inbox", "outbox").unwrap();
+        assert!(renamed > saved);
+    }
+
+    #[test]
+    fn test_get_changes_since() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        let start = api.revision().unwrap();
+
+        api.create_note("inbox").unwrap();
+        api.save_note("inbox", "hello").unwrap();
+        api.rename_note("inbox", "outbox").unwrap();
+        api.delete_note("outbox").unwrap();
+
+        let changes = api.get_changes_since(start).unwrap();
+        assert!(!changes.reset);
+        assert_eq!(changes.revision, api.revision().unwrap());
+        let summary: Vec<_> = changes
+            .changes
+            .iter()
+            .map(|c| (c.kind, c.path.as_str(), c.old_path.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Added, "inbox", None),
+                (ChangeKind::Modified, "inbox", None),
+                (ChangeKind::Moved, "outbox", Some("inbox")),
+                (ChangeKind::Removed, "outbox", None),
+            ]
+        );
+
+        let latest = api.get_changes_since(changes.revision).unwrap();
+        assert!(latest.changes.is_empty());
+    }
+
+    #[test]
+    fn test_modified_changes_summarize_the_edit() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("plan").unwrap();
+        let start = api.revision().unwrap();
+
+        api.save_note("plan", "# Plan\nship it").unwrap();
+        api.save_note("plan", "# Plan\nship it today\n## Later\nrest")
+            .unwrap();
+
+        let edits: Vec<_> = api
+            .get_changes_since(start)
+            .unwrap()
+            .changes
+            .into_iter()
+            .map(|change| change.edit.unwrap())
+            .collect();
+        assert_eq!(
+            edits,
+            vec![
+                EditSummary {
+                    words_added: 3,
+                    words_removed: 0,
+                    headings: vec!["Plan".to_string()],
+                },
+                EditSummary {
+                    words_added: 3,
+                    words_removed: 0,
+                    headings: vec!["Later".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changes_reset_when_log_pruned() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.create_note("b").unwrap();
+        api.db.execute("DELETE FROM changes", []).unwrap();
+        api.create_note("c").unwrap();
+
+        assert!(api.get_changes_since(0).unwrap().reset);
+        let latest = api.revision().unwrap();
+        assert!(!api.get_changes_since(latest - 1).unwrap().reset);
+    }
+}
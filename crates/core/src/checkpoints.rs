@@ -0,0 +1,602 @@
+//! Whole-vault checkpoints with restore.
+//!
+//! A checkpoint records every vault file (as a content-addressed object, so unchanged
+//! files are stored once across checkpoints) plus a snapshot of the index database,
+//! which holds data the files can't rebuild: frecency, archive state, reminders.
+//! Everything lives under `.checkpoints/` in the vault:
+//!
+//! ```text
+//! .checkpoints/
+//!   objects/<object id>     file contents
+//!   <id>.json               manifest: label, time, and path -> object for each file
+//!   <id>.db                 index snapshot
+//! ```
+//!
+//! Old checkpoints are pruned to stay within `CheckpointConfig` limits.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+
+use crate::layout::{CANVAS_FILE, NoteFiles};
+use crate::notes::{DATABASE_FILE_NAME, Error, NotesApi, OperationGuard, Result, run_migrations};
+use crate::recovery;
+use crate::vault_archive::{archive_path, collect_files};
+
+/// Folder in the vault root holding checkpoints (hidden, so it's never indexed).
+pub const CHECKPOINTS_DIR: &str = ".checkpoints";
+
+const OBJECTS_DIR: &str = "objects";
+
+/// A saved state of the whole vault.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub id: String,
+    pub label: String,
+    /// Unix timestamp in seconds
+    pub created_at: i64,
+    /// Number of vault files recorded
+    pub files: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    id: String,
+    label: String,
+    created_at: i64,
+    files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestFile {
+    path: String,
+    object: String,
+    mtime: i64,
+}
+
+impl Manifest {
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            id: self.id.clone(),
+            label: self.label.clone(),
+            created_at: self.created_at,
+            files: self.files.len(),
+        }
+    }
+}
+
+/// Names file contents by hash and length; the length makes collisions between
+/// different contents even less likely.
+fn object_id(contents: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    format!("{:016x}-{}", hasher.finish(), contents.len())
+}
+
+fn unix_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Removes empty, non-hidden directories below `dir` (but not `dir` itself).
+fn remove_empty_dirs(dir: &Path) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() || entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        remove_empty_dirs(&path)?;
+        if fs::read_dir(&path)?.next().is_none() {
+            fs::remove_dir(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// The stored object holding the note at `path` in a checkpoint, if it has one.
+fn note_object<'a>(manifest: &'a Manifest, files: &NoteFiles, path: &str) -> Option<&'a str> {
+    manifest
+        .files
+        .iter()
+        .find(|file| {
+            file.path.rsplit('/').next() != Some(CANVAS_FILE)
+                && files.note_of_file(&file.path).as_deref() == Some(path)
+        })
+        .map(|file| file.object.as_str())
+}
+
+impl NotesApi {
+    fn checkpoints_dir(&self) -> PathBuf {
+        self.fs.root_path().join(CHECKPOINTS_DIR)
+    }
+
+    fn read_manifest(&self, id: &str) -> Result<Manifest> {
+        // Ids are generated here; anything else can't name a checkpoint file
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit() || c == '-') {
+            return Err(Error::NotFound(id.to_string()));
+        }
+        let raw = fs::read_to_string(self.checkpoints_dir().join(format!("{}.json", id)))
+            .map_err(|_| Error::NotFound(id.to_string()))?;
+        serde_json::from_str(&raw)
+            .map_err(|e| Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+    }
+
+    fn read_manifests(&self) -> Result<Vec<Manifest>> {
+        let dir = self.checkpoints_dir();
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut manifests = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if let Some(id) = name.strip_suffix(".json")
+                && let Ok(manifest) = self.read_manifest(id)
+            {
+                manifests.push(manifest);
+            }
+        }
+        // Newest first; ids sort by creation time
+        manifests.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(manifests)
+    }
+
+    /// Records the current state of every vault file and the index.
+    ///
+    /// Unchanged files share storage with earlier checkpoints. Older checkpoints are
+    /// pruned afterwards to stay within the configured limits.
+    pub fn create_checkpoint(&mut self, label: &str) -> Result<Checkpoint> {
+        self.ensure_writable()?;
+        self.write_checkpoint(label, &[])
+    }
+
+    /// `create_checkpoint`, with the checkpoints in `keep` protected from pruning.
+    fn write_checkpoint(&mut self, label: &str, keep: &[&str]) -> Result<Checkpoint> {
+        let dir = self.checkpoints_dir();
+        let objects = dir.join(OBJECTS_DIR);
+        fs::create_dir_all(&objects)?;
+
+        let now = self.clock.now();
+        let millis = now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let mut id = format!("{:013}", millis);
+        let mut n = 1;
+        while dir.join(format!("{}.json", id)).exists() {
+            id = format!("{:013}-{}", millis, n);
+            n += 1;
+        }
+
+        let root = self.fs.root_path().to_path_buf();
+        let mut relative_files = Vec::new();
+        collect_files(&root, &root, &mut relative_files)?;
+        relative_files.sort();
+
+        let mut files = Vec::with_capacity(relative_files.len());
+        for relative in relative_files {
+            let full = root.join(&relative);
+            let contents = fs::read(&full)?;
+            let object = object_id(&contents);
+            let object_path = objects.join(&object);
+            if !object_path.exists() {
+                fs::write(&object_path, &contents)?;
+            }
+            files.push(ManifestFile {
+                path: archive_path(&relative),
+                object,
+                mtime: fs::metadata(&full)?.modified().map(unix_secs).unwrap_or(0),
+            });
+        }
+
+        let snapshot = dir.join(format!("{}.db", id));
+        self.db.execute(
+            "VACUUM INTO ?1",
+            params![snapshot.to_string_lossy().as_ref()],
+        )?;
+
+        let manifest = Manifest {
+            id: id.clone(),
+            label: label.to_string(),
+            created_at: unix_secs(now),
+            files,
+        };
+        let raw = serde_json::to_string_pretty(&manifest).map_err(std::io::Error::other)?;
+        // The manifest goes last: a checkpoint without one is ignored
+        fs::write(dir.join(format!("{}.json", id)), raw)?;
+
+        let mut keep = keep.to_vec();
+        keep.push(&id);
+        self.prune_checkpoints(&keep)?;
+        Ok(manifest.checkpoint())
+    }
+
+    /// Lists checkpoints, newest first.
+    pub fn list_checkpoints(&self) -> Result<Vec<Checkpoint>> {
+        Ok(self
+            .read_manifests()?
+            .iter()
+            .map(Manifest::checkpoint)
+            .collect())
+    }
+
+    /// Lists the checkpoints that recorded a new version of the note at `path`, newest
+    /// first: each is the first checkpoint with that version, so unchanged copies of
+    /// the note in later checkpoints are left out.
+    pub fn get_note_history(&self, path: &str) -> Result<Vec<Checkpoint>> {
+        let files = self.note_files();
+        let manifests = self.read_manifests()?;
+        let mut history = Vec::new();
+        let mut previous = None;
+        for manifest in manifests.iter().rev() {
+            let object = note_object(manifest, &files, path);
+            if object.is_some() && object != previous {
+                history.push(manifest.checkpoint());
+            }
+            previous = object;
+        }
+        history.reverse();
+        Ok(history)
+    }
+
+    /// Returns the content the note at `path` had when checkpoint `id` was taken.
+    pub fn get_note_at_checkpoint(&self, path: &str, id: &str) -> Result<String> {
+        let manifest = self.read_manifest(id)?;
+        let object = note_object(&manifest, &self.note_files(), path)
+            .ok_or_else(|| Error::NotFound(path.to_string()))?;
+        Ok(fs::read_to_string(
+            self.checkpoints_dir().join(OBJECTS_DIR).join(object),
+        )?)
+    }
+
+    /// Deletes a checkpoint and any stored files only it referenced.
+    pub fn delete_checkpoint(&mut self, id: &str) -> Result<()> {
+        self.ensure_writable()?;
+        self.read_manifest(id)?;
+
+        let dir = self.checkpoints_dir();
+        fs::remove_file(dir.join(format!("{}.json", id)))?;
+        let _ = fs::remove_file(dir.join(format!("{}.db", id)));
+        self.collect_unreferenced_objects()
+    }
+
+    /// Returns the vault's files and index to the state saved in a checkpoint.
+    ///
+    /// The current state is checkpointed first ("Before restore"), so a restore can
+    /// itself be undone.
+    pub fn restore_checkpoint(&mut self, id: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let manifest = self.read_manifest(id)?;
+        let dir = self.checkpoints_dir();
+        let root = self.fs.root_path().to_path_buf();
+
+        // Nothing is touched unless every stored file is there to restore from
+        let objects = dir.join(OBJECTS_DIR);
+        if let Some(missing) = manifest
+            .files
+            .iter()
+            .find(|f| !objects.join(&f.object).is_file())
+        {
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("checkpoint {} is missing {}", id, missing.path),
+            )));
+        }
+
+        self.write_checkpoint("Before restore", &[id])?;
+
+        {
+            let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
+
+            let wanted: HashMap<&str, &ManifestFile> = manifest
+                .files
+                .iter()
+                .map(|f| (f.path.as_str(), f))
+                .collect();
+
+            let mut current = Vec::new();
+            collect_files(&root, &root, &mut current)?;
+            for relative in current {
+                let path = archive_path(&relative);
+                let full = root.join(&relative);
+                match wanted.get(path.as_str()) {
+                    None => fs::remove_file(&full)?,
+                    Some(file) if object_id(&fs::read(&full)?) == file.object => {}
+                    Some(_) => fs::remove_file(&full)?,
+                }
+            }
+
+            for file in &manifest.files {
+                let dest = root.join(&file.path);
+                if dest.exists() {
+                    continue;
+                }
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(dir.join(OBJECTS_DIR).join(&file.object), &dest)?;
+                File::options()
+                    .write(true)
+                    .open(&dest)?
+                    .set_modified(UNIX_EPOCH + Duration::from_secs(file.mtime.max(0) as u64))?;
+            }
+            remove_empty_dirs(&root)?;
+        }
+
+        // Swap in the index snapshot, then reconcile it with the restored files
+        let snapshot = dir.join(format!("{}.db", id));
+        if snapshot.exists() {
+            let db_path = root.join(DATABASE_FILE_NAME);
+            // Staged next to the index and checked before the live one is let go
+            let staged = root.join(format!("{}.restore", DATABASE_FILE_NAME));
+            let _ = fs::remove_file(&staged);
+            fs::copy(&snapshot, &staged)?;
+            let checked = recovery::open_database(&staged, self.clock.as_ref())
+                .and_then(|(db, _)| run_migrations(&db));
+            if let Err(e) = checked {
+                let _ = fs::remove_file(&staged);
+                return Err(e);
+            }
+
+            self.db = Connection::open_in_memory()?;
+            for suffix in ["-journal", "-wal", "-shm"] {
+                let mut sidecar = db_path.as_os_str().to_owned();
+                sidecar.push(suffix);
+                let _ = fs::remove_file(PathBuf::from(sidecar));
+            }
+            fs::rename(&staged, &db_path)?;
+            let (db, _) = recovery::open_database(&db_path, self.clock.as_ref())?;
+            self.db = db;
+        }
+        self.rescan()
+    }
+
+    /// Drops the oldest checkpoints beyond the configured count or size limits, never
+    /// removing those in `keep` (or the newest checkpoint).
+    fn prune_checkpoints(&mut self, keep: &[&str]) -> Result<()> {
+        let limits = self.config.checkpoints.clone();
+        let dir = self.checkpoints_dir();
+        let objects = dir.join(OBJECTS_DIR);
+        let mut manifests = self.read_manifests()?;
+
+        let total_size = |manifests: &[Manifest]| -> u64 {
+            let mut seen = HashSet::new();
+            manifests
+                .iter()
+                .map(|m| {
+                    let objects_size: u64 = m
+                        .files
+                        .iter()
+                        .filter(|f| seen.insert(f.object.clone()))
+                        .map(|f| file_size(&objects.join(&f.object)))
+                        .sum();
+                    objects_size + file_size(&dir.join(format!("{}.db", m.id)))
+                })
+                .sum()
+        };
+
+        let mut removed = false;
+        loop {
+            let over_count = manifests.len() > limits.keep.max(1);
+            if !over_count && (manifests.len() <= 1 || total_size(&manifests) <= limits.max_bytes) {
+                break;
+            }
+            // Oldest checkpoint that isn't protected
+            let Some(index) = manifests
+                .iter()
+                .rposition(|m| !keep.contains(&m.id.as_str()))
+                .filter(|&i| i > 0)
+            else {
+                break;
+            };
+            let manifest = manifests.remove(index);
+            fs::remove_file(dir.join(format!("{}.json", manifest.id)))?;
+            let _ = fs::remove_file(dir.join(format!("{}.db", manifest.id)));
+            removed = true;
+        }
+
+        if removed {
+            self.collect_unreferenced_objects()?;
+        }
+        Ok(())
+    }
+
+    /// Deletes stored files no checkpoint refers to.
+    fn collect_unreferenced_objects(&self) -> Result<()> {
+        let objects = self.checkpoints_dir().join(OBJECTS_DIR);
+        if !objects.is_dir() {
+            return Ok(());
+        }
+        let referenced: HashSet<String> = self
+            .read_manifests()?
+            .into_iter()
+            .flat_map(|m| m.files.into_iter().map(|f| f.object))
+            .collect();
+        for entry in fs::read_dir(objects)? {
+            let entry = entry?;
+            if !referenced.contains(entry.file_name().to_string_lossy().as_ref()) {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::VaultConfig;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_checkpoint_and_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("kept").unwrap();
+        api.save_note("kept", "original").unwrap();
+        api.create_note("kept/child").unwrap();
+        let remind_at = SystemTime::now() + Duration::from_secs(3600);
+        let reminder = api.set_reminder("kept", remind_at, "check").unwrap();
+
+        let checkpoint = api.create_checkpoint("Before changes").unwrap();
+        assert_eq!(checkpoint.label, "Before changes");
+        assert_eq!(checkpoint.files, 2);
+
+        api.save_note("kept", "edited").unwrap();
+        api.delete_note("kept/child").unwrap();
+        api.create_note("added").unwrap();
+        api.delete_reminder(reminder.id).unwrap();
+
+        api.restore_checkpoint(&checkpoint.id).unwrap();
+
+        assert_eq!(api.get_note("kept").unwrap().content, "original");
+        assert!(api.note_exists("kept/child").unwrap());
+        assert!(!api.note_exists("added").unwrap());
+        assert!(!temp_dir.path().join("added").exists());
+        // Index-only state comes back from the snapshot
+        assert_eq!(api.list_upcoming_reminders().unwrap().len(), 1);
+
+        // The state before restoring was checkpointed too
+        let labels: Vec<String> = api
+            .list_checkpoints()
+            .unwrap()
+            .into_iter()
+            .map(|c| c.label)
+            .collect();
+        assert_eq!(labels, vec!["Before restore", "Before changes"]);
+    }
+
+    #[test]
+    fn test_note_history_lists_each_version_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.create_note("b").unwrap();
+        api.save_note("a", "first").unwrap();
+        let first = api.create_checkpoint("one").unwrap();
+        api.save_note("b", "only b changed").unwrap();
+        api.create_checkpoint("two").unwrap();
+        api.save_note("a", "second").unwrap();
+        let second = api.create_checkpoint("three").unwrap();
+
+        let ids: Vec<String> = api
+            .get_note_history("a")
+            .unwrap()
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, vec![second.id.clone(), first.id.clone()]);
+        assert_eq!(api.get_note_at_checkpoint("a", &first.id).unwrap(), "first");
+        assert_eq!(
+            api.get_note_at_checkpoint("a", &second.id).unwrap(),
+            "second"
+        );
+        assert!(matches!(
+            api.get_note_at_checkpoint("missing", &first.id),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_unchanged_files_are_stored_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.save_note("a", "same").unwrap();
+
+        api.create_checkpoint("one").unwrap();
+        api.create_checkpoint("two").unwrap();
+
+        let objects = temp_dir.path().join(CHECKPOINTS_DIR).join(OBJECTS_DIR);
+        assert_eq!(fs::read_dir(objects).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_pruning_keeps_limit_and_collects_objects() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        let mut config = VaultConfig::default();
+        config.checkpoints.keep = 2;
+        api.set_config(config).unwrap();
+        api.create_note("a").unwrap();
+
+        for i in 0..3 {
+            api.save_note("a", &format!("version {}", i)).unwrap();
+            api.create_checkpoint(&format!("v{}", i)).unwrap();
+        }
+
+        let labels: Vec<String> = api
+            .list_checkpoints()
+            .unwrap()
+            .into_iter()
+            .map(|c| c.label)
+            .collect();
+        assert_eq!(labels, vec!["v2", "v1"]);
+        let objects = temp_dir.path().join(CHECKPOINTS_DIR).join(OBJECTS_DIR);
+        assert_eq!(fs::read_dir(objects).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_restore_at_retention_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        let mut config = VaultConfig::default();
+        config.checkpoints.keep = 2;
+        api.set_config(config).unwrap();
+        api.create_note("a").unwrap();
+        api.save_note("a", "version 0").unwrap();
+        let oldest = api.create_checkpoint("v0").unwrap();
+        api.save_note("a", "version 1").unwrap();
+        api.create_checkpoint("v1").unwrap();
+        api.save_note("a", "version 2").unwrap();
+
+        // "Before restore" pushes past the limit; the checkpoint being restored stays
+        api.restore_checkpoint(&oldest.id).unwrap();
+
+        assert_eq!(api.get_note("a").unwrap().content, "version 0");
+        let labels: Vec<String> = api
+            .list_checkpoints()
+            .unwrap()
+            .into_iter()
+            .map(|c| c.label)
+            .collect();
+        assert_eq!(labels, vec!["Before restore", "v0"]);
+    }
+
+    #[test]
+    fn test_checkpoints_are_not_indexed() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.create_checkpoint("one").unwrap();
+
+        api.rescan().unwrap();
+        assert_eq!(api.get_all_notes().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_unknown_checkpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        assert!(matches!(
+            api.restore_checkpoint("../escape"),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            api.delete_checkpoint("123"),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
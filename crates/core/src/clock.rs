@@ -0,0 +1,160 @@
+//! Time source for everything in core that reads the clock or waits.
+//!
+//! `NotesApi`, the ingest debouncer, and the maintenance scheduler take their time
+//! from a `Clock`, so tests can swap in a `ManualClock` and fast-forward through
+//! debounce windows and idle timeouts instead of sleeping.
+
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub trait Clock: Send + Sync {
+    /// The current wall-clock time.
+    fn now(&self) -> SystemTime;
+
+    /// Blocks the calling thread until `duration` has passed on this clock.
+    fn sleep(&self, duration: Duration);
+
+    /// Seconds since the Unix epoch, as stored in the database.
+    fn unix_secs(&self) -> i64 {
+        self.now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0)
+    }
+}
+
+/// The real clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// `sleep` blocks until another thread advances the clock past the wake-up time,
+/// so background threads run their next iteration exactly when a test says so.
+#[derive(Debug)]
+pub struct ManualClock {
+    state: Mutex<ManualState>,
+    changed: Condvar,
+}
+
+#[derive(Debug)]
+struct ManualState {
+    now: SystemTime,
+    /// Wake-up times of threads inside `sleep`
+    sleeping: Vec<SystemTime>,
+}
+
+impl ManualClock {
+    pub fn new(start: SystemTime) -> Self {
+        Self {
+            state: Mutex::new(ManualState {
+                now: start,
+                sleeping: Vec::new(),
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Moves the clock forward, waking sleepers whose time has come.
+    pub fn advance(&self, duration: Duration) {
+        self.state.lock().unwrap().now += duration;
+        self.changed.notify_all();
+    }
+
+    /// Jumps to `time`, which may be in the past (like a user changing the system clock).
+    pub fn set(&self, time: SystemTime) {
+        self.state.lock().unwrap().now = time;
+        self.changed.notify_all();
+    }
+
+    /// Blocks until at least `count` threads are sleeping with a wake-up time still
+    /// ahead, so a test can advance the clock knowing a background thread is waiting
+    /// on it. Threads whose time has already come don't count.
+    pub fn wait_for_sleepers(&self, count: usize) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let now = state.now;
+            if state
+                .sleeping
+                .iter()
+                .filter(|wake_at| **wake_at > now)
+                .count()
+                >= count
+            {
+                return;
+            }
+            state = self.changed.wait(state).unwrap();
+        }
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        self.state.lock().unwrap().now
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let wake_at = state.now + duration;
+        state.sleeping.push(wake_at);
+        self.changed.notify_all();
+        while state.now < wake_at {
+            state = self.changed.wait(state).unwrap();
+        }
+        if let Some(i) = state.sleeping.iter().position(|w| *w == wake_at) {
+            state.sleeping.swap_remove(i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_manual_clock_only_moves_when_advanced() {
+        let clock = ManualClock::default();
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.now(), start + Duration::from_secs(90));
+        assert_eq!(clock.unix_secs(), 1_700_000_090);
+    }
+
+    #[test]
+    fn test_manual_clock_sleep_wakes_on_advance() {
+        let clock = Arc::new(ManualClock::default());
+        let (tx, rx) = mpsc::channel();
+        let sleeper = Arc::clone(&clock);
+        std::thread::spawn(move || {
+            sleeper.sleep(Duration::from_secs(60));
+            tx.send(()).unwrap();
+        });
+
+        clock.wait_for_sleepers(1);
+        clock.advance(Duration::from_secs(30));
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+
+        clock.advance(Duration::from_secs(30));
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+}
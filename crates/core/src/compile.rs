@@ -0,0 +1,231 @@
+//! Compiling a subtree into one document ("book view").
+//!
+//! Notes are concatenated depth-first, each under a heading named after it whose
+//! level follows its depth below the compiled note. Headings inside a note's body
+//! are shifted down by the same amount so the outline stays nested.
+
+use crate::frontmatter::Frontmatter;
+use crate::notes::{Error, NotesApi, Result};
+
+/// Deepest heading level Markdown supports; deeper headings are clamped to it.
+const MAX_HEADING_LEVEL: usize = 6;
+
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+    /// Heading level of the compiled note's own title (1-6)
+    pub base_heading_level: usize,
+    /// How many levels of descendants to include; `None` for the whole subtree
+    pub max_depth: Option<usize>,
+    /// Include archived descendants
+    pub include_archived: bool,
+    /// Keep each note's frontmatter block instead of stripping it
+    pub keep_frontmatter: bool,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            base_heading_level: 1,
+            max_depth: None,
+            include_archived: false,
+            keep_frontmatter: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompiledDocument {
+    pub path: String,
+    pub content: String,
+    /// Number of notes included
+    pub notes: usize,
+}
+
+impl NotesApi {
+    /// Concatenates the note at `path` and its descendants into a single Markdown
+    /// document. Siblings are ordered by path.
+    pub fn compile_subtree(
+        &self,
+        path: &str,
+        options: &CompileOptions,
+    ) -> Result<CompiledDocument> {
+        if !(1..=MAX_HEADING_LEVEL).contains(&options.base_heading_level) {
+            return Err(Error::InvalidInput(format!(
+                "Heading level must be between 1 and {}",
+                MAX_HEADING_LEVEL
+            )));
+        }
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+
+        let mut sections = Vec::new();
+        self.compile_note(path, 0, options, &mut sections)?;
+
+        Ok(CompiledDocument {
+            path: path.to_string(),
+            notes: sections.len(),
+            content: sections.join("\n"),
+        })
+    }
+
+    fn compile_note(
+        &self,
+        path: &str,
+        depth: usize,
+        options: &CompileOptions,
+        sections: &mut Vec<String>,
+    ) -> Result<()> {
+        let content = self.read_content(path).unwrap_or_default();
+        let level = options.base_heading_level + depth;
+        sections.push(compile_section(path, &content, level, options));
+
+        if options.max_depth.is_some_and(|max| depth >= max) {
+            return Ok(());
+        }
+
+        let mut children = self.get_children(path)?;
+        children.retain(|child| options.include_archived || !child.archived);
+        children.sort_by(|a, b| a.path.cmp(&b.path));
+        for child in children {
+            self.compile_note(&child.path, depth + 1, options, sections)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders one note as a section headed by its title at `level`.
+fn compile_section(path: &str, content: &str, level: usize, options: &CompileOptions) -> String {
+    let (frontmatter, body) = Frontmatter::parse(content);
+    let title = path.rsplit('/').next().unwrap_or(path);
+    let mut body = shift_headings(body, level);
+
+    // Don't repeat the title if the note already starts with it
+    let own_heading = format!("{} {}", "#".repeat(level.min(MAX_HEADING_LEVEL) + 1), title);
+    if let Some(rest) = body.strip_prefix(&own_heading)
+        && (rest.is_empty() || rest.starts_with('\n'))
+    {
+        body = rest.trim_start_matches('\n').to_string();
+    }
+
+    let mut section = format!("{} {}\n\n", "#".repeat(level.min(MAX_HEADING_LEVEL)), title);
+    if options.keep_frontmatter && !frontmatter.is_empty() {
+        section.push_str(&frontmatter.render());
+        section.push('\n');
+    }
+    let body = body.trim();
+    if !body.is_empty() {
+        section.push_str(body);
+        section.push('\n');
+    }
+    section
+}
+
+/// Pushes every ATX heading in `body` down by `by` levels, leaving fenced code alone.
+fn shift_headings(body: &str, by: usize) -> String {
+    let mut in_fence = false;
+    let mut out = String::with_capacity(body.len());
+
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+
+        let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+        let is_heading = !in_fence
+            && (1..=MAX_HEADING_LEVEL).contains(&hashes)
+            && trimmed[hashes..].starts_with([' ', '\n'])
+            && line.len() - trimmed.len() <= 3;
+
+        if is_heading {
+            let level = (hashes + by).min(MAX_HEADING_LEVEL);
+            out.push_str(&"#".repeat(level));
+            out.push_str(&trimmed[hashes..]);
+        } else {
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("book").unwrap();
+        api.save_note("book", "---\nstatus: draft\n---\nIntro\n")
+            .unwrap();
+        api.create_note("book/2 middle").unwrap();
+        api.save_note("book/2 middle", "## Scene\nText\n").unwrap();
+        api.create_note("book/1 start").unwrap();
+        api.save_note("book/1 start", "# 1 start\nOnce upon a time\n")
+            .unwrap();
+        api.create_note("book/1 start/aside").unwrap();
+        api.save_note("book/1 start/aside", "```\n# not a heading\n```\n")
+            .unwrap();
+        (temp_dir, api)
+    }
+
+    #[test]
+    fn test_compile_subtree() {
+        let (_temp_dir, api) = setup();
+
+        let doc = api
+            .compile_subtree("book", &CompileOptions::default())
+            .unwrap();
+
+        assert_eq!(doc.notes, 4);
+        assert_eq!(
+            doc.content,
+            "# book\n\nIntro\n\
+             \n## 1 start\n\nOnce upon a time\n\
+             \n### aside\n\n```\n# not a heading\n```\n\
+             \n## 2 middle\n\n#### Scene\nText\n"
+        );
+    }
+
+    #[test]
+    fn test_compile_options() {
+        let (_temp_dir, mut api) = setup();
+        api.archive_note("book/2 middle").unwrap();
+
+        let options = CompileOptions {
+            base_heading_level: 2,
+            max_depth: Some(1),
+            keep_frontmatter: true,
+            ..Default::default()
+        };
+        let doc = api.compile_subtree("book", &options).unwrap();
+
+        assert_eq!(doc.notes, 2);
+        assert_eq!(
+            doc.content,
+            "## book\n\n---\nstatus: draft\n---\n\nIntro\n\
+             \n### 1 start\n\nOnce upon a time\n"
+        );
+    }
+
+    #[test]
+    fn test_shift_headings_clamps() {
+        assert_eq!(
+            shift_headings("##### deep\n#tag\n", 3),
+            "###### deep\n#tag\n"
+        );
+    }
+
+    #[test]
+    fn test_compile_missing_note() {
+        let temp_dir = TempDir::new().unwrap();
+        let api = NotesApi::new(temp_dir.path()).unwrap();
+
+        assert!(matches!(
+            api.compile_subtree("missing", &CompileOptions::default()),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
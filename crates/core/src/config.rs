@@ -0,0 +1,474 @@
+//! Per-vault configuration stored as JSON next to the database.
+//!
+//! Every field has a default, so a missing or partial config file is valid and
+//! older vaults pick up new settings without a migration step.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::layout::{DEFAULT_NOTE_FILE, VaultLayout};
+use crate::snippets::{Snippet, default_snippets};
+
+/// File name of the config file inside the notes root.
+pub const CONFIG_FILE_NAME: &str = ".notes.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VaultConfig {
+    pub mentions: MentionsConfig,
+    pub snippets: Vec<Snippet>,
+    pub paste: PasteConfig,
+    pub appearance: AppearanceConfig,
+    pub editor: EditorConfig,
+    pub search: SearchConfig,
+    pub ingest: IngestConfig,
+    pub checkpoints: CheckpointConfig,
+    pub diagnostics: DiagnosticsConfig,
+    pub timing: TimingConfig,
+    pub cache: CacheConfig,
+    pub startup: StartupConfig,
+    pub daily: DailyNotesConfig,
+    pub export: ExportConfig,
+    pub git: GitConfig,
+    /// Frontmatter rules for structured notes, checked on save and in the health check
+    pub schemas: Vec<NoteSchema>,
+    /// UI language (e.g. `nl` or `en-GB`); detected from the OS when unset
+    pub locale: Option<String>,
+    /// File each note is stored in, e.g. `README.md` for GitHub-browsable vaults;
+    /// change it with `NotesApi::set_note_file` so existing notes are renamed
+    pub note_file: String,
+    /// Whether notes without children are folders or plain files; change it with
+    /// `NotesApi::set_layout` so existing notes are moved
+    pub layout: VaultLayout,
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            mentions: MentionsConfig::default(),
+            snippets: default_snippets(),
+            paste: PasteConfig::default(),
+            appearance: AppearanceConfig::default(),
+            editor: EditorConfig::default(),
+            search: SearchConfig::default(),
+            ingest: IngestConfig::default(),
+            checkpoints: CheckpointConfig::default(),
+            diagnostics: DiagnosticsConfig::default(),
+            timing: TimingConfig::default(),
+            cache: CacheConfig::default(),
+            startup: StartupConfig::default(),
+            daily: DailyNotesConfig::default(),
+            export: ExportConfig::default(),
+            git: GitConfig::default(),
+            schemas: Vec::new(),
+            locale: None,
+            note_file: DEFAULT_NOTE_FILE.to_string(),
+            layout: VaultLayout::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasteConfig {
+    /// Fetch page titles for pasted URLs (makes network requests, off by default)
+    pub fetch_url_titles: bool,
+    /// Give up on a title fetch after this many milliseconds
+    pub url_title_timeout_ms: u64,
+}
+
+impl Default for PasteConfig {
+    fn default() -> Self {
+        Self {
+            fetch_url_titles: false,
+            url_title_timeout_ms: 3000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceConfig {
+    /// Editor text size in pixels
+    pub editor_font_size: u32,
+    /// CSS font stack for the editor; the interface font is used when unset
+    pub editor_font_family: Option<String>,
+    /// Zoom factor for the whole interface (1.0 = 100%)
+    pub ui_scale: f64,
+    /// Stronger text, border, and selection colors
+    pub high_contrast: bool,
+}
+
+/// Smallest and largest `ui_scale`, and how far one zoom step moves it
+pub const UI_SCALE_MIN: f64 = 0.5;
+pub const UI_SCALE_MAX: f64 = 2.0;
+pub const UI_SCALE_STEP: f64 = 0.1;
+
+impl AppearanceConfig {
+    /// Zooms in (`1`), out (`-1`), or back to 100% (`0`), keeping the scale on a step
+    /// within range. Returns the new scale.
+    pub fn step_ui_scale(&mut self, direction: i32) -> f64 {
+        self.ui_scale = if direction == 0 {
+            1.0
+        } else {
+            let steps = (self.ui_scale / UI_SCALE_STEP).round() + f64::from(direction.signum());
+            // Round off float error so the scale reads back as e.g. 1.1
+            (steps * UI_SCALE_STEP * 100.0).round() / 100.0
+        }
+        .clamp(UI_SCALE_MIN, UI_SCALE_MAX);
+        self.ui_scale
+    }
+}
+
+impl Default for AppearanceConfig {
+    fn default() -> Self {
+        Self {
+            editor_font_size: 14,
+            editor_font_family: None,
+            ui_scale: 1.0,
+            high_contrast: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorConfig {
+    /// Curly quotes, en/em dashes, and ellipses as you type (outside code)
+    pub smart_typography: bool,
+    /// New "untitled" notes take their name from their first H1 or line as you type
+    pub auto_title: bool,
+}
+
+/// Weights for ranking search results around the open note; 0 turns a signal off.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Visit count or frecency, relative to the best match
+    pub ranking_weight: f64,
+    /// Closeness in the tree: children, parent, and siblings rank highest
+    pub proximity_weight: f64,
+    /// How recently the note was opened, halving per day
+    pub recency_weight: f64,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            ranking_weight: 1.0,
+            proximity_weight: 1.0,
+            recency_weight: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestConfig {
+    /// Folder whose `.md` and `.txt` files are turned into notes; disabled when unset
+    pub dir: Option<String>,
+    /// Parent note for ingested notes (empty for the root)
+    pub parent: String,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            parent: "inbox".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckpointConfig {
+    /// Most checkpoints to keep; the oldest are removed first
+    pub keep: usize,
+    /// Disk budget for all checkpoints together, in bytes
+    pub max_bytes: u64,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            keep: 20,
+            max_bytes: 512 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiagnosticsConfig {
+    /// Write a crash report into the vault when the app panics (takes effect on restart)
+    pub crash_reports: bool,
+}
+
+/// Delays around file changes; slow network drives need longer windows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimingConfig {
+    /// How long the vault must be quiet before changed files are re-indexed
+    pub watcher_debounce_ms: u64,
+    /// A burst touching more notes than this is handled with one full rescan
+    pub rescan_threshold: usize,
+    /// How long the editor waits after the last keystroke before saving
+    pub autosave_delay_ms: u64,
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        Self {
+            watcher_debounce_ms: 500,
+            rescan_threshold: 200,
+            autosave_delay_ms: 500,
+        }
+    }
+}
+
+/// Memory the vault may use for caches; lower it on machines short of RAM.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Notes whose content is kept in memory (see `content_cache`); 0 turns it off
+    pub content_entries: usize,
+    /// Most memory the cached content may take, in MiB
+    pub content_mb: usize,
+    /// SQLite page cache for the index and its full-text tables, in MiB
+    pub index_mb: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            content_entries: 256,
+            content_mb: 16,
+            index_mb: 8,
+        }
+    }
+}
+
+/// How the app brings the index up to date when it opens the vault.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StartupConfig {
+    /// Show the existing index right away and sync it with the files in the background
+    pub background_sync: bool,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            background_sync: true,
+        }
+    }
+}
+
+/// Where daily notes live and what they start with; see `NotesApi::get_or_create_daily_note`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyNotesConfig {
+    /// Path of each day's note; `{{date}}`, `{{year}}`, `{{month}}`, and `{{day}}` are
+    /// filled in
+    pub path: String,
+    /// Note whose content (with the same variables filled in) seeds new daily notes
+    pub template: Option<String>,
+}
+
+impl Default for DailyNotesConfig {
+    fn default() -> Self {
+        Self {
+            path: "journal/{{year}}/{{month}}/{{day}}".to_string(),
+            template: None,
+        }
+    }
+}
+
+/// How links between notes are written in exported and copied notes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkStyle {
+    /// Leave `[[wiki-links]]` and note URLs as they are
+    #[default]
+    Wiki,
+    /// Markdown links to the exported files (`[app](app.md)`)
+    Relative,
+    /// Markdown links to the note's path under `base_url`
+    Published,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportConfig {
+    pub links: LinkStyle,
+    /// Where the vault is published (e.g. `https://notes.example.com`), for `Published` links
+    pub base_url: String,
+}
+
+/// Versioning the vault with git; see the `git` module.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Set by `NotesApi::git_init`; nothing touches git while off
+    pub enabled: bool,
+    /// Commit changes in the background once saving has paused
+    pub auto_commit: bool,
+    /// How long no note must have been saved before changes are committed
+    pub commit_delay_ms: u64,
+    /// Remote pulled from and pushed to
+    pub remote: String,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            auto_commit: true,
+            commit_delay_ms: 30_000,
+            remote: "origin".to_string(),
+        }
+    }
+}
+
+/// Frontmatter fields expected on a kind of note (e.g. every project needs a status).
+///
+/// A schema applies to the direct children of `parent`, to notes whose `type`
+/// property equals `note_type`, or to either when both are set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoteSchema {
+    /// Shown in warnings so users know which rule a note broke
+    pub name: String,
+    pub parent: Option<String>,
+    #[serde(rename = "type")]
+    pub note_type: Option<String>,
+    pub fields: Vec<SchemaField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchemaField {
+    pub key: String,
+    /// Warn when the field is missing or empty
+    pub required: bool,
+    pub kind: FieldKind,
+    /// Permitted values; any value is accepted when empty
+    pub allowed: Vec<String>,
+}
+
+impl Default for SchemaField {
+    fn default() -> Self {
+        Self {
+            key: String::new(),
+            required: true,
+            kind: FieldKind::Any,
+            allowed: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    #[default]
+    Any,
+    Text,
+    List,
+    /// A `YYYY-MM-DD` date
+    Date,
+    Number,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MentionsConfig {
+    /// Parent note under which person notes live
+    pub people_root: String,
+    /// Create a person note the first time someone is @mentioned
+    pub auto_create: bool,
+}
+
+impl Default for MentionsConfig {
+    fn default() -> Self {
+        Self {
+            people_root: "people".to_string(),
+            auto_create: false,
+        }
+    }
+}
+
+impl VaultConfig {
+    /// Loads the config for a vault, falling back to defaults if the file doesn't exist.
+    pub fn load(notes_root: &Path) -> io::Result<Self> {
+        let path = notes_root.join(CONFIG_FILE_NAME);
+        match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes the config to the vault's config file.
+    pub fn save(&self, notes_root: &Path) -> io::Result<()> {
+        let raw = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(notes_root.join(CONFIG_FILE_NAME), raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_config_uses_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = VaultConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config, VaultConfig::default());
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = VaultConfig::default();
+        config.mentions.auto_create = true;
+
+        config.save(temp_dir.path()).unwrap();
+        assert_eq!(VaultConfig::load(temp_dir.path()).unwrap(), config);
+    }
+
+    #[test]
+    fn test_partial_config_fills_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE_NAME),
+            r#"{ "mentions": { "auto_create": true } }"#,
+        )
+        .unwrap();
+
+        let config = VaultConfig::load(temp_dir.path()).unwrap();
+        assert!(config.mentions.auto_create);
+        assert_eq!(config.mentions.people_root, "people");
+        assert_eq!(config.appearance, AppearanceConfig::default());
+        assert_eq!(config.timing, TimingConfig::default());
+    }
+
+    #[test]
+    fn test_step_ui_scale() {
+        let mut appearance = AppearanceConfig::default();
+        assert_eq!(appearance.step_ui_scale(1), 1.1);
+        assert_eq!(appearance.step_ui_scale(1), 1.2);
+        assert_eq!(appearance.step_ui_scale(0), 1.0);
+
+        appearance.ui_scale = 0.53;
+        assert_eq!(appearance.step_ui_scale(-1), 0.5);
+        assert_eq!(appearance.step_ui_scale(-1), 0.5);
+        appearance.ui_scale = 2.0;
+        assert_eq!(appearance.step_ui_scale(1), 2.0);
+    }
+}
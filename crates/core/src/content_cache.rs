@@ -0,0 +1,432 @@
+//! In-memory cache of note content, bounded by the vault's `cache` config.
+//!
+//! Persistent stores are wrapped in a `CachedStore`, so reads through the API can be
+//! served from memory and writes through it drop the cached copy. Edits made outside
+//! the app are caught by comparing the note's modification time and size on every
+//! hit, which is a stat instead of a read. The size catches edits that keep the
+//! mtime, e.g. on filesystems with coarse timestamps or tools that restore it. `warm_cache` loads notes the frontend is about to show.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::config::CacheConfig;
+use crate::filesystem::{FSNoteMetadata, NoteStore};
+use crate::layout::VaultLayout;
+use crate::notes::{NotesApi, Result};
+
+const MIB: usize = 1024 * 1024;
+
+/// Size and effectiveness of the content cache, for diagnostics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+struct Entry {
+    content: String,
+    mtime: SystemTime,
+    /// Size of the file the content was read from
+    size: u64,
+    /// Value of `ContentCache::tick` when the entry was last used
+    used: u64,
+}
+
+/// Least recently used note contents, bounded by count and total size.
+pub struct ContentCache {
+    entries: HashMap<String, Entry>,
+    bytes: usize,
+    tick: u64,
+    max_entries: usize,
+    max_bytes: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl ContentCache {
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            entries: HashMap::new(),
+            bytes: 0,
+            tick: 0,
+            max_entries: config.content_entries,
+            max_bytes: config.content_mb.saturating_mul(MIB),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Applies new limits, evicting what no longer fits.
+    pub fn set_limits(&mut self, config: &CacheConfig) {
+        self.max_entries = config.content_entries;
+        self.max_bytes = config.content_mb.saturating_mul(MIB);
+        self.evict();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.len(),
+            bytes: self.bytes,
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+
+    /// Returns the cached content of `path` if the note hasn't changed since (its
+    /// modification time and size are still `mtime` and `size`).
+    fn get(&mut self, path: &str, mtime: SystemTime, size: u64) -> Option<String> {
+        self.tick += 1;
+        match self.entries.get_mut(path) {
+            Some(entry) if entry.mtime == mtime && entry.size == size => {
+                entry.used = self.tick;
+                self.hits += 1;
+                Some(entry.content.clone())
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    fn insert(&mut self, path: &str, content: String, mtime: SystemTime, size: u64) {
+        // A note that alone exceeds the budget isn't worth pushing everything out for
+        if self.max_entries == 0 || content.len() > self.max_bytes {
+            return;
+        }
+        self.remove(path);
+        self.tick += 1;
+        self.bytes += content.len();
+        self.entries.insert(
+            path.to_string(),
+            Entry {
+                content,
+                mtime,
+                size,
+                used: self.tick,
+            },
+        );
+        self.evict();
+    }
+
+    fn remove(&mut self, path: &str) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.bytes -= entry.content.len();
+        }
+    }
+
+    /// Drops `path` and its descendants.
+    fn remove_subtree(&mut self, path: &str) {
+        let prefix = format!("{}/", path);
+        let bytes = &mut self.bytes;
+        self.entries.retain(|key, entry| {
+            let keep = key != path && !key.starts_with(&prefix);
+            if !keep {
+                *bytes -= entry.content.len();
+            }
+            keep
+        });
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.max_entries || self.bytes > self.max_bytes {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+    }
+}
+
+/// A store whose note reads go through a `ContentCache`.
+pub(crate) struct CachedStore {
+    inner: Box<dyn NoteStore>,
+    cache: Arc<Mutex<ContentCache>>,
+}
+
+impl CachedStore {
+    pub(crate) fn new(inner: Box<dyn NoteStore>, cache: Arc<Mutex<ContentCache>>) -> Self {
+        Self { inner, cache }
+    }
+
+    fn forget(&self, path: &str) {
+        self.cache.lock().unwrap().remove(path);
+    }
+
+    fn forget_subtree(&self, path: &str) {
+        self.cache.lock().unwrap().remove_subtree(path);
+    }
+
+    fn forget_all(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+}
+
+impl NoteStore for CachedStore {
+    fn root_path(&self) -> &Path {
+        self.inner.root_path()
+    }
+
+    fn is_available(&self) -> bool {
+        self.inner.is_available()
+    }
+
+    fn is_persistent(&self) -> bool {
+        self.inner.is_persistent()
+    }
+
+    fn read_note(&self, path: &str) -> io::Result<String> {
+        // Taken before the read, so a change in between makes the next hit miss
+        let (mtime, size) = self.inner.note_stamp(path)?;
+        if let Some(content) = self.cache.lock().unwrap().get(path, mtime, size) {
+            return Ok(content);
+        }
+        let content = self.inner.read_note(path)?;
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(path, content.clone(), mtime, size);
+        Ok(content)
+    }
+
+    fn write_note(&self, path: &str, content: &str) -> io::Result<()> {
+        self.forget(path);
+        self.inner.write_note(path, content)
+    }
+
+    fn write_note_durable(&self, path: &str, content: &str) -> io::Result<()> {
+        self.forget(path);
+        self.inner.write_note_durable(path, content)
+    }
+
+    fn create_note(&self, path: &str) -> io::Result<()> {
+        self.forget(path);
+        self.inner.create_note(path)
+    }
+
+    fn delete_note(&self, path: &str) -> io::Result<()> {
+        self.forget_subtree(path);
+        self.inner.delete_note(path)
+    }
+
+    fn trash_note(&self, path: &str) -> io::Result<()> {
+        self.forget_subtree(path);
+        self.inner.trash_note(path)
+    }
+
+    fn read_canvas(&self, path: &str) -> io::Result<String> {
+        self.inner.read_canvas(path)
+    }
+
+    fn write_canvas(&self, path: &str, data: &str) -> io::Result<()> {
+        self.inner.write_canvas(path, data)
+    }
+
+    fn canvas_exists(&self, path: &str) -> bool {
+        self.inner.canvas_exists(path)
+    }
+
+    fn scan_all(&self) -> io::Result<Vec<FSNoteMetadata>> {
+        self.inner.scan_all()
+    }
+
+    fn scan_subtree(&self, path: &str) -> io::Result<Vec<FSNoteMetadata>> {
+        self.inner.scan_subtree(path)
+    }
+
+    fn note_mtime(&self, path: &str) -> io::Result<SystemTime> {
+        self.inner.note_mtime(path)
+    }
+
+    fn note_stamp(&self, path: &str) -> io::Result<(SystemTime, u64)> {
+        self.inner.note_stamp(path)
+    }
+
+    fn set_note_mtime(&self, path: &str, mtime: SystemTime) -> io::Result<()> {
+        self.forget(path);
+        self.inner.set_note_mtime(path, mtime)
+    }
+
+    fn set_note_file(&mut self, name: &str) {
+        self.forget_all();
+        self.inner.set_note_file(name);
+    }
+
+    fn rename_note_files(&mut self, name: &str) -> io::Result<usize> {
+        self.forget_all();
+        self.inner.rename_note_files(name)
+    }
+
+    fn set_layout(&mut self, layout: VaultLayout) {
+        self.forget_all();
+        self.inner.set_layout(layout);
+    }
+
+    fn convert_layout(&mut self, layout: VaultLayout) -> io::Result<usize> {
+        self.forget_all();
+        self.inner.convert_layout(layout)
+    }
+
+    fn promote_note(&self, path: &str) -> io::Result<bool> {
+        self.forget(path);
+        self.inner.promote_note(path)
+    }
+
+    fn demote_note(&self, path: &str) -> io::Result<bool> {
+        self.forget(path);
+        self.inner.demote_note(path)
+    }
+
+    fn get_ancestors(&self, path: &str) -> Vec<String> {
+        self.inner.get_ancestors(path)
+    }
+}
+
+impl NotesApi {
+    /// Loads the given notes into the content cache, e.g. the ones in the visible part
+    /// of the tree, so opening them doesn't wait on the disk. Notes that are already
+    /// cached or missing are skipped; returns how many were loaded.
+    ///
+    /// Stops early once the cache is full, so warming never evicts notes it just
+    /// loaded.
+    pub fn warm_cache(&self, paths: &[String]) -> Result<usize> {
+        self.ensure_available()?;
+        let limit = self.config().cache.content_entries;
+        let mut loaded = 0;
+        for path in paths.iter().take(limit) {
+            if self.content_cache.lock().unwrap().contains(path) {
+                continue;
+            }
+            if self.fs.read_note(path).is_ok() {
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Entries, size, and hit rate of the content cache.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.content_cache.lock().unwrap().stats()
+    }
+
+    /// Sets SQLite's page cache, shared by the index and its full-text tables.
+    pub(crate) fn apply_index_cache(&self) -> Result<()> {
+        let kib = self.config().cache.index_mb.saturating_mul(1024) as i64;
+        // A negative size is in KiB rather than pages
+        self.db.pragma_update(None, "cache_size", -kib)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn limits(content_entries: usize, content_mb: usize) -> CacheConfig {
+        CacheConfig {
+            content_entries,
+            content_mb,
+            ..CacheConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = ContentCache::new(&limits(2, 1));
+        let t = SystemTime::UNIX_EPOCH;
+        cache.insert("a", "A".to_string(), t, 1);
+        cache.insert("b", "B".to_string(), t, 1);
+        assert!(cache.get("a", t, 1).is_some());
+        cache.insert("c", "C".to_string(), t, 1);
+
+        assert!(cache.contains("a") && cache.contains("c"));
+        assert!(!cache.contains("b"));
+
+        cache.set_limits(&limits(2, 0));
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(cache.stats().bytes, 0);
+    }
+
+    #[test]
+    fn test_reads_are_cached_and_follow_outside_edits() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("inbox").unwrap();
+        api.save_note("inbox", "first").unwrap();
+
+        assert_eq!(api.get_note("inbox").unwrap().content, "first");
+        let before = api.cache_stats();
+        assert_eq!(api.get_note("inbox").unwrap().content, "first");
+        assert_eq!(api.cache_stats().hits, before.hits + 1);
+
+        api.save_note("inbox", "second").unwrap();
+        assert_eq!(api.get_note("inbox").unwrap().content, "second");
+
+        // Written behind the app's back, with a different modification time
+        let file = temp_dir.path().join("inbox/_index.md");
+        fs::write(&file, "third").unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(api.get_note("inbox").unwrap().content, "third");
+
+        // Same modification time, different size
+        fs::write(&file, "fourth").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(api.get_note("inbox").unwrap().content, "fourth");
+    }
+
+    #[test]
+    fn test_warm_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        let mut config = api.config().clone();
+        config.cache.content_entries = 2;
+        api.set_config(config).unwrap();
+        for path in ["a", "b", "c"] {
+            api.create_note(path).unwrap();
+        }
+        // Reopen for an empty cache
+        drop(api);
+        let api = NotesApi::new(temp_dir.path()).unwrap();
+
+        let paths: Vec<String> = ["a", "b", "c", "missing"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(api.warm_cache(&paths).unwrap(), 2);
+        assert_eq!(api.cache_stats().entries, 2);
+        // Already cached
+        assert_eq!(api.warm_cache(&paths[..1]).unwrap(), 0);
+    }
+}
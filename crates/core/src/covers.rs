@@ -0,0 +1,102 @@
+//! Note cover images.
+//!
+//! A note's `cover` frontmatter field names an image stored next to it, e.g.
+//! `cover: header.jpg` for `projects/q3/header.jpg`. The field is cached with the
+//! note's stats so card-style lists get it without reading every file.
+
+use std::path::{Component, Path, PathBuf};
+
+use rusqlite::{OptionalExtension, params};
+
+use crate::notes::{Error, NotesApi, Result};
+
+/// Frontmatter property naming a note's cover image.
+pub const COVER_PROPERTY: &str = "cover";
+
+/// Resolves `cover` inside `note_dir`, or `None` if it would leave the folder
+/// (absolute paths, `..`, URLs).
+fn resolve_cover(note_dir: &Path, cover: &str) -> Option<PathBuf> {
+    if cover.contains("://") {
+        return None;
+    }
+    let cover = Path::new(cover);
+    let inside = cover
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    inside.then(|| note_dir.join(cover))
+}
+
+impl NotesApi {
+    /// Returns the file holding the cover image of the note at `path`, or `None`
+    /// if it has no cover or the file doesn't exist.
+    ///
+    /// Covers are confined to the note's folder, so a note can't point at files
+    /// elsewhere on disk.
+    pub fn get_cover_path(&self, path: &str) -> Result<Option<PathBuf>> {
+        let cover: Option<String> = self
+            .db
+            .query_row(
+                "SELECT cover FROM notes WHERE path = ?1",
+                params![path],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| Error::NotFound(path.to_string()))?;
+
+        let note_dir = self.notes_root().join(path);
+        Ok(cover
+            .and_then(|cover| resolve_cover(&note_dir, &cover))
+            .filter(|file| file.is_file()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cover_is_cached_and_resolved() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("trips").unwrap();
+        api.create_note("trips/lisbon").unwrap();
+        api.save_note("trips/lisbon", "---\ncover: photos/tram.jpg\n---\nDay one")
+            .unwrap();
+
+        let children = api.get_children_with_stats("trips").unwrap();
+        let stats = children[0].stats.as_ref().unwrap();
+        assert_eq!(stats.cover.as_deref(), Some("photos/tram.jpg"));
+
+        // Not there yet
+        assert_eq!(api.get_cover_path("trips/lisbon").unwrap(), None);
+
+        let photos = temp_dir.path().join("trips/lisbon/photos");
+        std::fs::create_dir_all(&photos).unwrap();
+        std::fs::write(photos.join("tram.jpg"), [0xff, 0xd8]).unwrap();
+        assert_eq!(
+            api.get_cover_path("trips/lisbon").unwrap(),
+            Some(photos.join("tram.jpg"))
+        );
+
+        assert_eq!(api.get_cover_path("trips").unwrap(), None);
+        assert!(matches!(
+            api.get_cover_path("missing"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_cover_cannot_leave_note_folder() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("secret.png"), [0u8; 4]).unwrap();
+        api.create_note("note").unwrap();
+
+        for cover in ["../secret.png", "/etc/passwd", "https://example.com/a.png"] {
+            api.save_note("note", &format!("---\ncover: {}\n---\n", cover))
+                .unwrap();
+            assert_eq!(api.get_cover_path("note").unwrap(), None, "{}", cover);
+        }
+    }
+}
@@ -0,0 +1,418 @@
+//! Importing CSV/TSV tables, either as one child note per row or as a Markdown
+//! table appended to a note.
+//!
+//! Spreadsheet exports vary a lot, so the encoding (UTF-8, UTF-16 with a BOM, or
+//! Windows-1252) and the delimiter (`,` `;` tab `|`) are detected from the data.
+
+use std::path::Path;
+
+use crate::frontmatter::{Frontmatter, PropertyValue};
+use crate::notes::{Error, NotesApi, Result};
+
+/// Delimiters tried during detection, in order of preference on a tie.
+const DELIMITERS: [char; 4] = [',', '\t', ';', '|'];
+
+/// Lines sampled when detecting the delimiter.
+const SNIFF_LINES: usize = 10;
+
+/// Header names (case-insensitive) that name the column used as note titles.
+const TITLE_COLUMNS: [&str; 2] = ["title", "name"];
+
+/// Windows-1252 characters for bytes 0x80-0x9F; the rest match Latin-1.
+const CP1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+];
+
+#[derive(Debug, Clone, Copy)]
+pub enum CsvSource<'a> {
+    /// A file on disk, e.g. one dropped onto the window
+    Path(&'a Path),
+    /// Raw file contents, e.g. pasted text
+    Content(&'a [u8]),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvImportMode {
+    /// One child note per row; the header row becomes frontmatter keys
+    ChildNotes,
+    /// A Markdown table appended to the target note
+    Table,
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvImport {
+    /// Data rows imported (excluding the header)
+    pub rows: usize,
+    /// Notes created in `ChildNotes` mode
+    pub created: Vec<String>,
+    pub delimiter: char,
+    pub encoding: &'static str,
+}
+
+/// Decodes file bytes, returning the text and the detected encoding name.
+pub fn decode(bytes: &[u8]) -> (String, &'static str) {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return (String::from_utf8_lossy(rest).into_owned(), "utf-8");
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return (decode_utf16(rest, u16::from_le_bytes), "utf-16le");
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return (decode_utf16(rest, u16::from_be_bytes), "utf-16be");
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => (text.to_string(), "utf-8"),
+        Err(_) => {
+            let text = bytes
+                .iter()
+                .map(|&b| match b {
+                    0x80..=0x9F => CP1252_HIGH[(b - 0x80) as usize],
+                    _ => b as char,
+                })
+                .collect();
+            (text, "windows-1252")
+        }
+    }
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| from_bytes([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Counts `delimiter` outside quoted fields on each of the first lines.
+fn delimiter_counts(text: &str, delimiter: char) -> Vec<usize> {
+    let mut counts = Vec::new();
+    let mut count = 0;
+    let mut in_quotes = false;
+    for c in text.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\n' if !in_quotes => {
+                counts.push(count);
+                count = 0;
+                if counts.len() == SNIFF_LINES {
+                    return counts;
+                }
+            }
+            c if c == delimiter && !in_quotes => count += 1,
+            _ => {}
+        }
+    }
+    if count > 0 {
+        counts.push(count);
+    }
+    counts
+}
+
+/// Picks the delimiter that splits the sampled lines most consistently.
+///
+/// A delimiter appearing the same number of times on every line wins over one
+/// that doesn't; among those, the one producing more columns wins.
+pub fn detect_delimiter(text: &str) -> char {
+    let mut best = (false, 0, ',');
+    for delimiter in DELIMITERS {
+        let counts = delimiter_counts(text, delimiter);
+        let Some(&first) = counts.first().filter(|&&n| n > 0) else {
+            continue;
+        };
+        let consistent = counts.iter().all(|&n| n == first);
+        if (consistent, first) > (best.0, best.1) {
+            best = (consistent, first, delimiter);
+        }
+    }
+    best.2
+}
+
+/// Parses delimited text into rows of fields.
+///
+/// Fields may be quoted with `"` (doubled to escape) and quoted fields may span
+/// lines. Blank lines are skipped.
+pub fn parse(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    let mut end_row = |row: &mut Vec<String>, field: &mut String| {
+        row.push(std::mem::take(field));
+        if row.iter().any(|f| !f.trim().is_empty()) {
+            rows.push(std::mem::take(row));
+        } else {
+            row.clear();
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\r' if chars.peek() == Some(&'\n') => {}
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => end_row(&mut row, &mut field),
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        end_row(&mut row, &mut field);
+    }
+    rows
+}
+
+/// Renders rows as a Markdown table; the first row is the header.
+pub fn render_table(rows: &[Vec<String>]) -> String {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if width == 0 {
+        return String::new();
+    }
+
+    let render_row = |row: &[String]| {
+        let cells: Vec<String> = (0..width)
+            .map(|i| {
+                row.get(i)
+                    .map(|cell| {
+                        cell.trim()
+                            .replace('|', "\\|")
+                            .replace("\r\n", "<br>")
+                            .replace('\n', "<br>")
+                    })
+                    .unwrap_or_default()
+            })
+            .collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut out = render_row(&rows[0]);
+    out.push_str(&format!("|{}\n", " --- |".repeat(width)));
+    for row in &rows[1..] {
+        out.push_str(&render_row(row));
+    }
+    out
+}
+
+/// Turns a header cell into a frontmatter key (`Due Date` becomes `due_date`).
+fn property_key(header: &str, index: usize) -> String {
+    let key: String = header
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    let key = key.trim_matches('_').to_string();
+    if key.is_empty() {
+        format!("column_{}", index + 1)
+    } else {
+        key
+    }
+}
+
+impl NotesApi {
+    /// Imports a CSV or TSV table into the vault at `target`.
+    ///
+    /// In `ChildNotes` mode every data row becomes a child of `target` (created if
+    /// missing). The `title`/`name` column, or else the first column, names the
+    /// note and the other columns become its frontmatter. In `Table` mode the rows
+    /// are appended to the existing note at `target` as a Markdown table.
+    pub fn import_csv(
+        &mut self,
+        source: CsvSource,
+        target: &str,
+        mode: CsvImportMode,
+    ) -> Result<CsvImport> {
+        self.ensure_writable()?;
+
+        let bytes = match source {
+            CsvSource::Path(path) => std::fs::read(path)?,
+            CsvSource::Content(bytes) => bytes.to_vec(),
+        };
+        let (text, encoding) = decode(&bytes);
+        let delimiter = detect_delimiter(&text);
+        let rows = parse(&text, delimiter);
+        if rows.len() < 2 {
+            return Err(Error::InvalidInput(
+                "Table needs a header row and at least one data row".to_string(),
+            ));
+        }
+
+        let mut import = CsvImport {
+            rows: rows.len() - 1,
+            created: Vec::new(),
+            delimiter,
+            encoding,
+        };
+
+        match mode {
+            CsvImportMode::Table => {
+                if !self.note_exists(target)? {
+                    return Err(Error::NotFound(target.to_string()));
+                }
+                let existing = self.read_content(target)?;
+                let mut content = existing.trim_end().to_string();
+                if !content.is_empty() {
+                    content.push_str("\n\n");
+                }
+                content.push_str(&render_table(&rows));
+                self.save_note(target, &content)?;
+            }
+            CsvImportMode::ChildNotes => {
+                let header = &rows[0];
+                let title_column = header
+                    .iter()
+                    .position(|h| TITLE_COLUMNS.contains(&h.trim().to_lowercase().as_str()))
+                    .unwrap_or(0);
+                let keys: Vec<String> = header
+                    .iter()
+                    .enumerate()
+                    .map(|(i, h)| property_key(h, i))
+                    .collect();
+
+                for row in &rows[1..] {
+                    let title = row
+                        .get(title_column)
+                        .map(|t| t.trim().replace('/', "-"))
+                        .filter(|t| !t.is_empty())
+                        .unwrap_or_else(|| "untitled".to_string());
+
+                    let mut frontmatter = Frontmatter::default();
+                    for (i, value) in row.iter().enumerate() {
+                        let value = value.trim();
+                        if i == title_column || value.is_empty() || i >= keys.len() {
+                            continue;
+                        }
+                        // Frontmatter values are single-line
+                        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+                        frontmatter.set(&keys[i], PropertyValue::Text(value));
+                    }
+
+                    let path = self.create_unique_child(target, &title)?;
+                    let content = format!("{}# {}\n", frontmatter.render(), title);
+                    self.save_note(&path, &content)?;
+                    import.created.push(path);
+                }
+            }
+        }
+
+        Ok(import)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_decode_encodings() {
+        assert_eq!(decode(b"\xEF\xBB\xBFa,b"), ("a,b".to_string(), "utf-8"));
+        assert_eq!(
+            decode(b"\xFF\xFEa\0,\0b\0"),
+            ("a,b".to_string(), "utf-16le")
+        );
+        assert_eq!(
+            decode(b"caf\xE9 \x80"),
+            ("café €".to_string(), "windows-1252")
+        );
+    }
+
+    #[test]
+    fn test_detect_delimiter() {
+        assert_eq!(detect_delimiter("a,b,c\n1,2,3\n"), ',');
+        assert_eq!(detect_delimiter("a\tb\n1\t2,5\n"), '\t');
+        // European exports use `;` with `,` as the decimal separator
+        assert_eq!(detect_delimiter("name;price\nTea;1,50\nCake;12,00\n"), ';');
+        assert_eq!(detect_delimiter("\"x, y\";z\n\"1, 2\";3\n"), ';');
+    }
+
+    #[test]
+    fn test_parse_quoted_fields() {
+        let rows = parse(
+            "a,\"b, c\",\"say \"\"hi\"\"\"\r\n\r\n1,\"two\nlines\",3",
+            ',',
+        );
+        assert_eq!(
+            rows,
+            vec![
+                vec!["a", "b, c", "say \"hi\""],
+                vec!["1", "two\nlines", "3"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_table() {
+        let rows = vec![
+            vec!["Name".to_string(), "Note".to_string()],
+            vec!["a|b".to_string()],
+        ];
+        assert_eq!(
+            render_table(&rows),
+            "| Name | Note |\n| --- | --- |\n| a\\|b |  |\n"
+        );
+    }
+
+    #[test]
+    fn test_import_child_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        let csv = b"Status,Name,Due Date\nactive,Website,2026-05-01\ndone,Launch,\n";
+        let import = api
+            .import_csv(
+                CsvSource::Content(csv),
+                "projects",
+                CsvImportMode::ChildNotes,
+            )
+            .unwrap();
+
+        assert_eq!(import.rows, 2);
+        assert_eq!(import.created, vec!["projects/Website", "projects/Launch"]);
+        assert_eq!(
+            api.get_note("projects/Website").unwrap().content,
+            "---\nstatus: active\ndue_date: 2026-05-01\n---\n# Website\n"
+        );
+        assert_eq!(
+            api.get_note("projects/Launch").unwrap().content,
+            "---\nstatus: done\n---\n# Launch\n"
+        );
+    }
+
+    #[test]
+    fn test_import_table_from_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("budget").unwrap();
+        api.save_note("budget", "# Budget\n").unwrap();
+
+        let file = temp_dir.path().join("budget.tsv");
+        std::fs::write(&file, "Item\tCost\nRent\t900\n").unwrap();
+        let import = api
+            .import_csv(CsvSource::Path(&file), "budget", CsvImportMode::Table)
+            .unwrap();
+
+        assert_eq!(import.delimiter, '\t');
+        assert_eq!(
+            api.get_note("budget").unwrap().content,
+            "# Budget\n\n| Item | Cost |\n| --- | --- |\n| Rent | 900 |\n"
+        );
+        assert!(matches!(
+            api.import_csv(CsvSource::Path(&file), "missing", CsvImportMode::Table),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
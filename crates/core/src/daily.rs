@@ -0,0 +1,154 @@
+//! Daily notes: one note per day under a path pattern from the vault config.
+//!
+//! The pattern (`journal/{{year}}/{{month}}/{{day}}` by default) decides where each
+//! day's note lives. A new daily note starts from the content of the template note,
+//! if one is configured, with the same date variables filled in. Template variables
+//! (see `templates`) get their labels, since there's no one to ask.
+
+use std::collections::HashMap;
+
+use crate::dates::Date;
+use crate::notes::{Error, Note, NotesApi, Result};
+use crate::templates::render_template;
+
+/// The most days `get_daily_notes_range` checks in one call, a little over a year
+const MAX_RANGE_DAYS: i64 = 400;
+
+/// A day that has a daily note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyNote {
+    pub date: Date,
+    pub path: String,
+}
+
+/// Fills in `{{date}}` (YYYY-MM-DD), `{{year}}`, `{{month}}`, and `{{day}}` (zero-padded).
+pub fn render_daily(pattern: &str, date: Date) -> String {
+    pattern
+        .replace("{{date}}", &date.to_string())
+        .replace("{{year}}", &format!("{:04}", date.year))
+        .replace("{{month}}", &format!("{:02}", date.month))
+        .replace("{{day}}", &format!("{:02}", date.day))
+}
+
+impl NotesApi {
+    /// Path of the daily note for `date`, whether or not it exists.
+    pub fn daily_note_path(&self, date: Date) -> String {
+        let path = render_daily(&self.config.daily.path, date);
+        path.trim_matches('/').to_string()
+    }
+
+    /// Returns the daily note for `date`, creating it (and any missing parent notes)
+    /// first if needed. A new note gets the rendered template note's content, or
+    /// starts blank if the template note is gone.
+    pub fn get_or_create_daily_note(&mut self, date: Date) -> Result<Note> {
+        let path = self.daily_note_path(date);
+        if !self.note_exists(&path)? {
+            let template = match self.config.daily.template.clone() {
+                Some(template) => match self.get_note_internal(&template) {
+                    Ok(note) => Some(note.content),
+                    Err(Error::NotFound(_)) => None,
+                    Err(e) => return Err(e),
+                },
+                None => None,
+            };
+            self.create_missing(&path)?;
+            if let Some(template) = template {
+                let rendered = render_template(&render_daily(&template, date), &HashMap::new());
+                self.save_note(&path, &rendered.content)?;
+            }
+        }
+        self.get_note(&path)
+    }
+
+    /// Lists the daily notes that exist between `from` and `to` (inclusive), oldest
+    /// first, e.g. to mark days in a calendar. Spans of more than 400 days are
+    /// refused, since each day is a lookup.
+    pub fn get_daily_notes_range(&self, from: Date, to: Date) -> Result<Vec<DailyNote>> {
+        if to.to_days() - from.to_days() >= MAX_RANGE_DAYS {
+            return Err(Error::InvalidInput(format!(
+                "Daily note ranges are limited to {} days",
+                MAX_RANGE_DAYS
+            )));
+        }
+        let mut notes = Vec::new();
+        let mut date = from;
+        while date <= to {
+            let path = self.daily_note_path(date);
+            if self.note_exists(&path)? {
+                notes.push(DailyNote { date, path });
+            }
+            date = date.add_days(1);
+        }
+        Ok(notes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_store::MemoryNoteStore;
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_daily_note_is_created_from_template() {
+        let mut api = NotesApi::with_store(Box::new(MemoryNoteStore::new())).unwrap();
+        api.create_note("templates").unwrap();
+        api.create_note("templates/day").unwrap();
+        api.save_note("templates/day", "# {{date}}\n\n## Tasks")
+            .unwrap();
+        let mut config = api.config().clone();
+        config.daily.template = Some("templates/day".to_string());
+        api.set_config(config).unwrap();
+
+        let note = api.get_or_create_daily_note(date(2024, 5, 17)).unwrap();
+        assert_eq!(note.path, "journal/2024/05/17");
+        assert_eq!(note.content, "# 2024-05-17\n\n## Tasks");
+        assert!(api.note_exists("journal/2024").unwrap());
+
+        // An existing daily note is returned untouched
+        api.save_note("journal/2024/05/17", "edited").unwrap();
+        let note = api.get_or_create_daily_note(date(2024, 5, 17)).unwrap();
+        assert_eq!(note.content, "edited");
+
+        // A deleted template leaves new daily notes blank instead of failing
+        api.delete_note("templates/day").unwrap();
+        let note = api.get_or_create_daily_note(date(2024, 5, 18)).unwrap();
+        assert_eq!(note.content, "");
+    }
+
+    #[test]
+    fn test_daily_notes_range_lists_existing_days() {
+        let mut api = NotesApi::with_store(Box::new(MemoryNoteStore::new())).unwrap();
+        let mut config = api.config().clone();
+        config.daily.path = "days/{{date}}".to_string();
+        api.set_config(config).unwrap();
+        for day in [date(2024, 2, 28), date(2024, 3, 1), date(2024, 3, 9)] {
+            api.get_or_create_daily_note(day).unwrap();
+        }
+
+        let notes = api
+            .get_daily_notes_range(date(2024, 2, 28), date(2024, 3, 2))
+            .unwrap();
+        assert_eq!(
+            notes,
+            vec![
+                DailyNote {
+                    date: date(2024, 2, 28),
+                    path: "days/2024-02-28".to_string()
+                },
+                DailyNote {
+                    date: date(2024, 3, 1),
+                    path: "days/2024-03-01".to_string()
+                },
+            ]
+        );
+
+        assert!(matches!(
+            api.get_daily_notes_range(date(1, 1, 1), date(9999, 12, 31)),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
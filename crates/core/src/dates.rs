@@ -0,0 +1,162 @@
+//! Calendar date helpers (proleptic Gregorian, UTC) used by date-aware features.
+//!
+//! Kept dependency-free on purpose; only whole days and UTC timestamps are needed.
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Creates a date, returning `None` if the day doesn't exist.
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Date> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    /// Parses an ISO `YYYY-MM-DD` date (anything after the day, like a time, is ignored).
+    pub fn parse(s: &str) -> Option<Date> {
+        let s = s.trim();
+        let date_part = s.get(..10)?;
+        let mut parts = date_part.split('-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        Date::new(year, month, day)
+    }
+
+    /// Returns the UTC date containing `time`.
+    pub fn from_system_time(time: SystemTime) -> Date {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        };
+        Date::from_days(secs.div_euclid(SECONDS_PER_DAY))
+    }
+
+    /// Days since 1970-01-01.
+    pub fn to_days(self) -> i64 {
+        // Algorithm from Howard Hinnant's "chrono-compatible low-level date algorithms"
+        let y = if self.month <= 2 {
+            self.year as i64 - 1
+        } else {
+            self.year as i64
+        };
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let m = self.month as i64;
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    /// Inverse of `to_days`.
+    pub fn from_days(days: i64) -> Date {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+        Date { year, month, day }
+    }
+
+    /// Returns the date `days` days later (or earlier, if negative).
+    pub fn add_days(self, days: i64) -> Date {
+        Date::from_days(self.to_days() + days)
+    }
+
+    /// Midnight UTC at the start of this date.
+    pub fn to_system_time(self) -> SystemTime {
+        let secs = self.to_days() * SECONDS_PER_DAY;
+        if secs >= 0 {
+            UNIX_EPOCH + Duration::from_secs(secs as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs((-secs) as u64)
+        }
+    }
+
+    /// Compact `YYYYMMDD` form used by iCalendar `VALUE=DATE` fields.
+    pub fn to_compact(self) -> String {
+        format!("{:04}{:02}{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// Formats a timestamp as an iCalendar UTC date-time (`YYYYMMDDTHHMMSSZ`).
+pub fn format_utc_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let date = Date::from_days(secs.div_euclid(SECONDS_PER_DAY));
+    let rem = secs.rem_euclid(SECONDS_PER_DAY);
+    format!(
+        "{}T{:02}{:02}{:02}Z",
+        date.to_compact(),
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_round_trip() {
+        assert_eq!(Date::new(1970, 1, 1).unwrap().to_days(), 0);
+        assert_eq!(Date::from_days(0), Date::new(1970, 1, 1).unwrap());
+
+        let date = Date::new(2024, 2, 29).unwrap();
+        assert_eq!(Date::from_days(date.to_days()), date);
+        assert_eq!(date.add_days(1), Date::new(2024, 3, 1).unwrap());
+        assert_eq!(Date::new(1969, 12, 31).unwrap(), Date::from_days(-1));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Date::parse("2024-05-17"), Date::new(2024, 5, 17));
+        assert_eq!(Date::parse("2024-05-17T10:00"), Date::new(2024, 5, 17));
+        assert_eq!(Date::parse("2023-02-29"), None);
+        assert_eq!(Date::parse("soon"), None);
+    }
+
+    #[test]
+    fn test_format_utc_timestamp() {
+        let time = Date::new(2024, 5, 17).unwrap().to_system_time() + Duration::from_secs(3_723);
+        assert_eq!(format_utc_timestamp(time), "20240517T010203Z");
+    }
+}
@@ -127,6 +127,24 @@ fn get_linux_path() -> Option<PathBuf> {
     Some(path)
 }
 
+/// Returns where edits made while a vault is unavailable are queued (see `offline`):
+/// the local data directory, so they survive a synced drive dropping away.
+pub fn get_offline_queue_dir() -> Option<PathBuf> {
+    let mut path = dirs::data_local_dir()?;
+    path.push("zinnia");
+    path.push("pending");
+    Some(path)
+}
+
+/// Returns where each vault's remote sync state is kept (see `remote_sync`): the
+/// local data directory, since it describes this device's copy of the vault.
+pub fn get_sync_state_dir() -> Option<PathBuf> {
+    let mut path = dirs::data_local_dir()?;
+    path.push("zinnia");
+    path.push("sync");
+    Some(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,266 @@
+//! Diagnostics bundles for bug reports, and opt-in local crash reports.
+//!
+//! A bundle is a zip with version info, the vault config (snippet text and
+//! folder paths redacted), index stats, a health-check summary, and the most
+//! recent crash reports. It never contains note content: crash reports leave out
+//! the panic message, which can quote whatever string was being handled.
+
+use std::backtrace::Backtrace;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::json;
+use zip::write::SimpleFileOptions;
+
+use crate::clock::Clock;
+use crate::config::VaultConfig;
+use crate::notes::{Error, NotesApi, Result};
+
+/// Hidden folder in the notes root that crash reports are written to.
+pub const CRASH_REPORT_DIR: &str = ".crash-reports";
+
+/// How many of the newest crash reports go into a bundle.
+const BUNDLED_CRASH_REPORTS: usize = 5;
+
+const REDACTED: &str = "<redacted>";
+
+/// What went into a diagnostics bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagnosticsSummary {
+    pub crash_reports: usize,
+}
+
+fn bundle_error(e: impl std::fmt::Display) -> Error {
+    Error::Io(io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+/// Strips user content from the config: snippet expansions and the ingest folder path.
+fn redact_config(config: &VaultConfig) -> VaultConfig {
+    let mut config = config.clone();
+    for snippet in &mut config.snippets {
+        snippet.expansion = REDACTED.to_string();
+    }
+    if config.ingest.dir.is_some() {
+        config.ingest.dir = Some(REDACTED.to_string());
+    }
+    config
+}
+
+/// Crash report files in `dir`, newest first.
+fn crash_reports(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    // Names start with a Unix timestamp, so they sort chronologically
+    reports.sort();
+    reports.reverse();
+    reports
+}
+
+/// Replaces the panic message in a crash report with `REDACTED`.
+///
+/// The message follows the `panicked at` line up to the blank line before the
+/// backtrace. Reports written before messages were left out still have one.
+fn redact_crash_report(report: &str) -> String {
+    let mut redacted = Vec::new();
+    let mut in_message = false;
+    for line in report.lines() {
+        if in_message {
+            if line.is_empty() {
+                in_message = false;
+                redacted.push(REDACTED);
+                redacted.push(line);
+            }
+            continue;
+        }
+        redacted.push(line);
+        in_message = line.starts_with("panicked at ");
+    }
+    if in_message {
+        redacted.push(REDACTED);
+    }
+    redacted.join("\n") + "\n"
+}
+
+/// Installs a panic hook that writes a crash report into `dir` before running
+/// the previous hook. Reports hold the panic location and a backtrace; the message
+/// is left out since it can contain note text. Reports are named by `clock`'s time.
+pub fn install_crash_reporter(dir: PathBuf, clock: Arc<dyn Clock>) {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let location = info
+            .location()
+            .map_or_else(|| "unknown location".to_string(), ToString::to_string);
+        let report = format!(
+            "zinnia {} ({} {})\npanicked at {}:\n{}\n\n{}\n",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            location,
+            REDACTED,
+            Backtrace::force_capture(),
+        );
+        let path = dir.join(format!("{}-crash.txt", clock.unix_secs()));
+        if let Err(e) = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, report)) {
+            eprintln!("Failed to write crash report: {:?}", e);
+        }
+        previous(info);
+    }));
+}
+
+impl NotesApi {
+    /// Folder crash reports for this vault are written to.
+    pub fn crash_report_dir(&self) -> PathBuf {
+        self.notes_root().join(CRASH_REPORT_DIR)
+    }
+
+    /// Writes a diagnostics bundle (a zip file) to `dest` for attaching to bug reports.
+    pub fn create_diagnostics_bundle<P: AsRef<Path>>(&self, dest: P) -> Result<DiagnosticsSummary> {
+        let schema_version: i64 = self
+            .db
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        let (notes, archived): (i64, i64) = self.db.query_row(
+            "SELECT COUNT(*), COUNT(archived_at) FROM notes",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let storage = self.get_storage_report()?;
+
+        let info = json!({
+            "app_version": env!("CARGO_PKG_VERSION"),
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+            "generated_at": self.clock.unix_secs(),
+            "read_only": self.is_read_only(),
+            "available": self.capabilities().available,
+        });
+        let startup = self.startup_report();
+        let database = json!({
+            "schema_version": schema_version,
+            "notes": notes,
+            "archived": archived,
+            "startup": {
+                "stale_files": startup.stale_files,
+                "wal_checkpointed": startup.wal_checkpointed,
+                "index_rebuilt": startup.index_rebuilt,
+                "salvaged": startup.salvaged,
+                "journal_replayed": startup.journal_replayed.len(),
+                "open_duration_ms": startup.open_duration.as_millis() as u64,
+            },
+            "storage": {
+                "notes": storage.notes,
+                "archive": storage.archive,
+                "attachments": storage.attachments,
+                "history": storage.history,
+                "database": storage.database,
+            },
+        });
+        // Counts only: the warnings themselves name notes and quote their values
+        let health = match self.health_check() {
+            Ok(report) => json!({
+                "notes_checked": report.notes_checked,
+                "notes_with_warnings": report.schema_warnings.len(),
+                "warnings": report.schema_warnings.values().map(Vec::len).sum::<usize>(),
+            }),
+            Err(e) => json!({ "error": format!("{:?}", e) }),
+        };
+
+        let mut zip = zip::ZipWriter::new(File::create(dest)?);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let files = [
+            ("info.json", info),
+            ("config.json", json!(redact_config(&self.config))),
+            ("database.json", database),
+            ("health.json", health),
+        ];
+        for (name, value) in files {
+            zip.start_file(name, options).map_err(bundle_error)?;
+            zip.write_all(
+                serde_json::to_string_pretty(&value)
+                    .map_err(bundle_error)?
+                    .as_bytes(),
+            )?;
+        }
+
+        let reports = crash_reports(&self.crash_report_dir());
+        let reports = &reports[..reports.len().min(BUNDLED_CRASH_REPORTS)];
+        for report in reports {
+            let Some(name) = report.file_name() else {
+                continue;
+            };
+            zip.start_file(format!("crash-reports/{}", name.to_string_lossy()), options)
+                .map_err(bundle_error)?;
+            zip.write_all(redact_crash_report(&fs::read_to_string(report)?).as_bytes())?;
+        }
+        zip.finish().map_err(bundle_error)?;
+
+        Ok(DiagnosticsSummary {
+            crash_reports: reports.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_diagnostics_bundle_redacts_and_includes_crash_reports() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("secret").unwrap();
+        api.save_note("secret", "# Secret plans").unwrap();
+        let mut config = api.config().clone();
+        config.ingest.dir = Some("/Users/someone/Inbox".to_string());
+        api.set_config(config).unwrap();
+
+        let crash_dir = api.crash_report_dir();
+        fs::create_dir_all(&crash_dir).unwrap();
+        for i in 0..7 {
+            fs::write(
+                crash_dir.join(format!("{}-crash.txt", 1000 + i)),
+                "zinnia 0.1.0 (macos aarch64)\npanicked at src/notes.rs:1:1:\nbyte index 3 is not a char boundary in `Secret plans`\n\nbacktrace\n",
+            )
+            .unwrap();
+        }
+
+        let dest = temp_dir.path().join("diagnostics.zip");
+        let summary = api.create_diagnostics_bundle(&dest).unwrap();
+        assert_eq!(summary.crash_reports, BUNDLED_CRASH_REPORTS);
+
+        let mut zip = zip::ZipArchive::new(File::open(&dest).unwrap()).unwrap();
+        assert!(zip.by_name("crash-reports/1006-crash.txt").is_ok());
+        assert!(zip.by_name("crash-reports/1001-crash.txt").is_err());
+
+        let mut all = String::new();
+        for i in 0..zip.len() {
+            zip.by_index(i).unwrap().read_to_string(&mut all).unwrap();
+        }
+        assert!(all.contains("\"notes\": 1"));
+        assert!(all.contains(REDACTED));
+        assert!(!all.contains("Secret plans"));
+        assert!(!all.contains("/Users/someone"));
+        assert!(all.contains("panicked at src/notes.rs:1:1:"));
+    }
+
+    #[test]
+    fn test_crash_report_redaction_keeps_location_and_backtrace() {
+        let report = "zinnia 1.0.0 (linux x86_64)\npanicked at src/lib.rs:4:2:\nfirst line\nsecond line\n\n0: zinnia::main\n";
+        let redacted = redact_crash_report(report);
+        assert_eq!(
+            redacted,
+            "zinnia 1.0.0 (linux x86_64)\npanicked at src/lib.rs:4:2:\n<redacted>\n\n0: zinnia::main\n"
+        );
+        assert_eq!(redact_crash_report(&redacted), redacted);
+    }
+}
@@ -0,0 +1,212 @@
+//! Copying a note, optionally with its whole subtree.
+
+use std::fs;
+use std::path::Path;
+
+use rusqlite::params_from_iter;
+
+use crate::changes::Revision;
+use crate::layout::NoteFiles;
+use crate::notes::{
+    Error, NotesApi, Result, descendants_clause, descendants_params, get_parent_path,
+};
+
+/// Copies the attachments in the folder `from` (files that aren't note files) to
+/// the folder `to`. Subfolders are left out; child notes are copied on their own.
+fn copy_attachments(from: &Path, to: &Path, files: &NoteFiles) -> std::io::Result<()> {
+    let Ok(entries) = fs::read_dir(from) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if !entry.file_type()?.is_file() || name.starts_with('.') || files.is_note_file(name) {
+            continue;
+        }
+        fs::create_dir_all(to)?;
+        fs::copy(entry.path(), to.join(name))?;
+    }
+    Ok(())
+}
+
+impl NotesApi {
+    /// Copies the note at `path` to `new_path`, with all its descendants if
+    /// `recursive` is set. Canvases and attachments are copied along.
+    ///
+    /// The copies start out as new notes: visits, reminders, and history stay with
+    /// the originals. Fails before writing anything if `new_path` exists, its parent
+    /// doesn't, or it lies inside the subtree being copied. All index updates land
+    /// in one savepoint (see `batch`). Returns the revision after the copy.
+    pub fn duplicate_note(
+        &mut self,
+        path: &str,
+        new_path: &str,
+        recursive: bool,
+    ) -> Result<Revision> {
+        self.ensure_writable()?;
+
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        if self.note_exists(new_path)? {
+            return Err(Error::AlreadyExists(new_path.to_string()));
+        }
+        if recursive && new_path.starts_with(&format!("{}/", path)) {
+            return Err(Error::InvalidInput(format!(
+                "Cannot copy {} into itself",
+                path
+            )));
+        }
+        if let Some(parent) = get_parent_path(new_path)
+            && !self.note_exists(&parent)?
+        {
+            return Err(Error::ParentNotFound(parent));
+        }
+
+        let mut sources = vec![path.to_string()];
+        if recursive {
+            let descendants = self
+                .db
+                .prepare(&format!(
+                    "SELECT path FROM notes WHERE {} ORDER BY path",
+                    descendants_clause(1)
+                ))?
+                .query_map(params_from_iter(descendants_params(path)), |row| row.get(0))?
+                .collect::<std::result::Result<Vec<String>, _>>()?;
+            sources.extend(descendants);
+        }
+
+        let root = self.fs.root_path().to_path_buf();
+        let files = self.note_files();
+        self.run_batch(|api| {
+            // Parents sort before their children, so every copy has its parent
+            for source in &sources {
+                let target = format!("{}{}", new_path, &source[path.len()..]);
+                let content = api.fs.read_note(source)?;
+                api.fs.write_note(&target, &content)?;
+                if api.fs.canvas_exists(source) {
+                    let canvas = api.fs.read_canvas(source)?;
+                    api.fs.write_canvas(&target, &canvas)?;
+                }
+                let source_dir = root.join(source);
+                if source_dir.is_dir() {
+                    // Attachments need the copy to have a folder of its own
+                    api.fs.promote_note(&target)?;
+                    copy_attachments(&source_dir, &root.join(&target), &files)?;
+                }
+                api.sync_note(&target)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for (path, content) in [
+            ("projects", ""),
+            ("projects/alpha", "# Alpha\n"),
+            ("projects/alpha/notes", "Meeting notes\n"),
+            ("projects/alpha/notes/monday", "Monday\n"),
+        ] {
+            api.create_note(path).unwrap();
+            api.save_note(path, content).unwrap();
+        }
+        fs::write(temp_dir.path().join("projects/alpha/logo.png"), [0u8; 4]).unwrap();
+        (temp_dir, api)
+    }
+
+    #[test]
+    fn test_duplicate_subtree() {
+        let (temp_dir, mut api) = setup();
+
+        api.duplicate_note("projects/alpha", "projects/beta", true)
+            .unwrap();
+
+        assert_eq!(api.get_note("projects/beta").unwrap().content, "# Alpha\n");
+        assert_eq!(
+            api.get_note("projects/beta/notes/monday").unwrap().content,
+            "Monday\n"
+        );
+        assert!(temp_dir.path().join("projects/beta/logo.png").is_file());
+        // The originals are untouched
+        assert_eq!(api.get_note("projects/alpha").unwrap().content, "# Alpha\n");
+        assert!(api.note_exists("projects/alpha/notes/monday").unwrap());
+    }
+
+    #[test]
+    fn test_duplicate_single_note() {
+        let (temp_dir, mut api) = setup();
+        api.save_canvas("projects/alpha", &serde_json::json!({"nodes": []}))
+            .unwrap();
+
+        api.duplicate_note("projects/alpha", "alpha copy", false)
+            .unwrap();
+
+        assert_eq!(api.get_note("alpha copy").unwrap().content, "# Alpha\n");
+        assert!(api.get_children("alpha copy").unwrap().is_empty());
+        assert!(temp_dir.path().join("alpha copy/_canvas.json").is_file());
+        assert!(temp_dir.path().join("alpha copy/logo.png").is_file());
+    }
+
+    #[test]
+    fn test_duplicate_copies_only_descendants() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in [
+            "a_b",
+            "a_b/mine",
+            "axb",
+            "axb/other",
+            "alpha",
+            "alpha/secret",
+        ] {
+            api.create_note(path).unwrap();
+        }
+
+        api.duplicate_note("a_b", "copy", true).unwrap();
+        assert!(api.note_exists("copy/mine").unwrap());
+        assert!(!api.note_exists("copy/other").unwrap());
+
+        // Case-insensitive file systems can't hold both
+        if api.create_note("Alpha").is_ok() {
+            api.duplicate_note("Alpha", "copy2", true).unwrap();
+            assert!(!api.note_exists("copy2/secret").unwrap());
+        }
+    }
+
+    #[test]
+    fn test_duplicate_checks_destination() {
+        let (_temp_dir, mut api) = setup();
+
+        assert!(matches!(
+            api.duplicate_note("projects/alpha", "projects", true),
+            Err(Error::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            api.duplicate_note("projects/alpha", "missing/alpha", true),
+            Err(Error::ParentNotFound(_))
+        ));
+        assert!(matches!(
+            api.duplicate_note("projects/alpha", "projects/alpha/notes/copy", true),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            api.duplicate_note("missing", "copy", false),
+            Err(Error::NotFound(_))
+        ));
+        // Copying into its own subtree is fine without the descendants
+        api.duplicate_note("projects/alpha", "projects/alpha/copy", false)
+            .unwrap();
+        assert!(api.note_exists("projects/alpha/copy").unwrap());
+    }
+}
@@ -0,0 +1,404 @@
+//! Notes encrypted at rest.
+//!
+//! The vault key is derived from a passphrase with Argon2id. Its salt and a check value
+//! (a known plaintext encrypted with the key) live in `.notes.key` next to the vault
+//! config rather than in the index, so rebuilding the index never loses them. The first
+//! `unlock` sets the passphrase.
+//!
+//! An encrypted note's file holds `ENCRYPTED_HEADER` followed by the hex of a random
+//! 24-byte nonce and the XChaCha20-Poly1305 ciphertext. The index lists encrypted notes
+//! by path only: their text is not searched, previewed, or scanned for links and tags.
+//! While the vault is unlocked they read and save like any other note; while it is
+//! locked reading one fails with `Error::Encrypted`.
+
+use std::fs;
+use std::path::Path;
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rusqlite::{OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::changes::Revision;
+use crate::notes::{Error, NotesApi, Result};
+
+/// First line of an encrypted note's file
+pub const ENCRYPTED_HEADER: &str = "zinnia-encrypted:v1\n";
+
+/// Where the key's salt and check value are kept, in the notes root
+pub const KEY_FILE_NAME: &str = ".notes.key";
+
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+/// Encrypted into the key file's check value to recognise the right passphrase
+const CHECK_PLAINTEXT: &[u8] = b"zinnia vault key";
+
+/// The derived vault key; wiped from memory when dropped.
+pub(crate) type VaultKey = Zeroizing<[u8; 32]>;
+
+#[derive(PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct KeyFile {
+    salt: String,
+    check: String,
+}
+
+/// Whether `content` is an encrypted note's file.
+pub fn is_encrypted(content: &str) -> bool {
+    content.starts_with(ENCRYPTED_HEADER)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes).map_err(|e| Error::Io(std::io::Error::other(e.to_string())))?;
+    Ok(bytes)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<VaultKey> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| Error::InvalidInput(format!("Could not derive the vault key: {}", e)))?;
+    Ok(key)
+}
+
+fn seal(key: &VaultKey, plaintext: &[u8]) -> Result<String> {
+    let nonce = random_bytes::<NONCE_LEN>()?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .map_err(|_| Error::InvalidInput("Could not encrypt".to_string()))?;
+    Ok(to_hex(&[nonce.as_slice(), &ciphertext].concat()))
+}
+
+/// `None` when the data is damaged or was sealed with another key.
+fn open(key: &VaultKey, sealed: &str) -> Option<Vec<u8>> {
+    let bytes = from_hex(sealed.trim())?;
+    if bytes.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
+    cipher.decrypt(XNonce::from_slice(nonce), ciphertext).ok()
+}
+
+/// Encrypts note content into the form stored on disk.
+pub(crate) fn encrypt_content(key: &VaultKey, content: &str) -> Result<String> {
+    Ok(format!(
+        "{}{}\n",
+        ENCRYPTED_HEADER,
+        seal(key, content.as_bytes())?
+    ))
+}
+
+/// Decrypts an encrypted note's file content.
+pub(crate) fn decrypt_content(key: &VaultKey, path: &str, stored: &str) -> Result<String> {
+    let sealed = stored.strip_prefix(ENCRYPTED_HEADER).unwrap_or(stored);
+    let plaintext = open(key, sealed)
+        .ok_or_else(|| Error::InvalidInput(format!("Could not decrypt {}", path)))?;
+    String::from_utf8(plaintext)
+        .map_err(|_| Error::InvalidInput(format!("Could not decrypt {}", path)))
+}
+
+pub(crate) fn parse_key_file(raw: &str) -> Result<KeyFile> {
+    serde_json::from_str(raw)
+        .map_err(|e| Error::InvalidInput(format!("Damaged {}: {}", KEY_FILE_NAME, e)))
+}
+
+pub(crate) fn read_key_file(notes_root: &Path) -> Result<Option<KeyFile>> {
+    match fs::read_to_string(notes_root.join(KEY_FILE_NAME)) {
+        Ok(raw) => parse_key_file(&raw).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl NotesApi {
+    /// Derives the vault key from `passphrase` so encrypted notes can be read and
+    /// written. The first unlock of a vault sets its passphrase.
+    ///
+    /// Fails with `Error::PermissionDenied` if the passphrase is wrong.
+    pub fn unlock(&mut self, passphrase: &str) -> Result<()> {
+        let root = self.notes_root().to_path_buf();
+        let key = match read_key_file(&root)? {
+            Some(file) => {
+                let salt = from_hex(&file.salt)
+                    .ok_or_else(|| Error::InvalidInput(format!("Damaged {}", KEY_FILE_NAME)))?;
+                let key = derive_key(passphrase, &salt)?;
+                if open(&key, &file.check).as_deref() != Some(CHECK_PLAINTEXT) {
+                    return Err(Error::PermissionDenied("Wrong passphrase".to_string()));
+                }
+                key
+            }
+            None => {
+                self.ensure_writable()?;
+                if passphrase.is_empty() {
+                    return Err(Error::InvalidInput(
+                        "The passphrase can't be empty".to_string(),
+                    ));
+                }
+                let salt = random_bytes::<SALT_LEN>()?;
+                let key = derive_key(passphrase, &salt)?;
+                let file = KeyFile {
+                    salt: to_hex(&salt),
+                    check: seal(&key, CHECK_PLAINTEXT)?,
+                };
+                let raw = serde_json::to_string_pretty(&file)
+                    .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+                fs::write(root.join(KEY_FILE_NAME), raw)?;
+                key
+            }
+        };
+        self.vault_key = Some(key);
+        Ok(())
+    }
+
+    /// Forgets the vault key; encrypted notes can't be read until the next `unlock`.
+    pub fn lock(&mut self) {
+        self.vault_key = None;
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.vault_key.is_some()
+    }
+
+    /// Whether the vault has a passphrase, i.e. has been unlocked before.
+    pub fn has_passphrase(&self) -> bool {
+        self.notes_root().join(KEY_FILE_NAME).exists()
+    }
+
+    /// Whether the index knows `path` as encrypted.
+    pub fn is_note_encrypted(&self, path: &str) -> Result<bool> {
+        Ok(self
+            .db
+            .query_row(
+                "SELECT encrypted FROM notes WHERE path = ?1",
+                params![path],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+            .is_some_and(|encrypted| encrypted != 0))
+    }
+
+    /// Reads a note's file, decrypting it if it is encrypted.
+    pub(crate) fn read_content(&self, path: &str) -> Result<String> {
+        let content = self.fs.read_note(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Error::NotFound(path.to_string()),
+            _ => Error::Io(e),
+        })?;
+        if !is_encrypted(&content) {
+            return Ok(content);
+        }
+        let key = self
+            .vault_key
+            .as_ref()
+            .ok_or_else(|| Error::Encrypted(path.to_string()))?;
+        decrypt_content(key, path, &content)
+    }
+
+    /// What to write to the file of the note at `path` for `content`: encrypted notes
+    /// stay encrypted, which needs an unlocked vault.
+    pub(crate) fn content_for_disk(&self, path: &str, content: &str) -> Result<String> {
+        if !self.is_note_encrypted(path)? {
+            return Ok(content.to_string());
+        }
+        let key = self
+            .vault_key
+            .as_ref()
+            .ok_or_else(|| Error::Encrypted(path.to_string()))?;
+        encrypt_content(key, content)
+    }
+
+    pub(crate) fn require_key(&self) -> Result<&VaultKey> {
+        self.vault_key
+            .as_ref()
+            .ok_or_else(|| Error::InvalidInput("Unlock the vault first".to_string()))
+    }
+
+    /// Encrypts a note's file; later saves stay encrypted. Requires an unlocked vault.
+    pub fn encrypt_note(&mut self, path: &str) -> Result<Revision> {
+        self.ensure_writable()?;
+        let key = self.require_key()?.clone();
+        let content = self.fs.read_note(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Error::NotFound(path.to_string()),
+            _ => Error::Io(e),
+        })?;
+        if is_encrypted(&content) {
+            return self.revision();
+        }
+        self.journaled_write(path, &encrypt_content(&key, &content)?)?;
+        // Earlier saves journaled the plain text
+        self.clear_journal()?;
+        self.sync_note(path)?;
+        self.revision()
+    }
+
+    /// Stores a note's file as plain text again. Requires an unlocked vault.
+    pub fn decrypt_note(&mut self, path: &str) -> Result<Revision> {
+        self.ensure_writable()?;
+        if !self.is_note_encrypted(path)? {
+            return self.revision();
+        }
+        let content = self.read_content(path)?;
+        self.journaled_write(path, &content)?;
+        self.sync_note(path)?;
+        self.revision()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_encrypted_note_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("diary").unwrap();
+        api.save_note("diary", "# Secret #private-tag").unwrap();
+
+        assert!(matches!(
+            api.encrypt_note("diary"),
+            Err(Error::InvalidInput(_))
+        ));
+        api.unlock("correct horse").unwrap();
+        api.encrypt_note("diary").unwrap();
+
+        let on_disk = std::fs::read_to_string(temp_dir.path().join("diary/_index.md")).unwrap();
+        assert!(is_encrypted(&on_disk));
+        assert!(!on_disk.contains("Secret"));
+        assert!(api.is_note_encrypted("diary").unwrap());
+        assert!(api.search("Secret").unwrap().is_empty());
+
+        // Saves while unlocked stay encrypted
+        let versioned = api.get_note_with_version("diary").unwrap();
+        let outcome = api
+            .save_note_versioned("diary", "# Still secret", &versioned.content_hash)
+            .unwrap();
+        assert!(matches!(outcome, crate::SaveOutcome::Saved { .. }));
+        assert_eq!(api.get_note("diary").unwrap().content, "# Still secret");
+        let on_disk = std::fs::read_to_string(temp_dir.path().join("diary/_index.md")).unwrap();
+        assert!(is_encrypted(&on_disk));
+
+        // Locked, the note is listed but its content is withheld
+        api.lock();
+        assert!(api.note_exists("diary").unwrap());
+        assert!(matches!(api.get_note("diary"), Err(Error::Encrypted(_))));
+        assert!(matches!(
+            api.save_note("diary", "overwrite"),
+            Err(Error::Encrypted(_))
+        ));
+
+        api.unlock("correct horse").unwrap();
+        api.decrypt_note("diary").unwrap();
+        let on_disk = std::fs::read_to_string(temp_dir.path().join("diary/_index.md")).unwrap();
+        assert_eq!(on_disk, "# Still secret");
+        assert!(!api.is_note_encrypted("diary").unwrap());
+    }
+
+    #[test]
+    fn test_encrypting_clears_journaled_plain_text() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("diary").unwrap();
+        api.save_note("diary", "# Secret").unwrap();
+        let journal = temp_dir.path().join(crate::journal::JOURNAL_FILE_NAME);
+        assert!(
+            std::fs::read_to_string(&journal)
+                .unwrap()
+                .contains("Secret")
+        );
+
+        api.unlock("correct horse").unwrap();
+        api.encrypt_note("diary").unwrap();
+        assert!(
+            !std::fs::read_to_string(&journal)
+                .unwrap()
+                .contains("Secret")
+        );
+    }
+
+    #[test]
+    fn test_edits_keep_encrypted_notes_encrypted() {
+        use crate::{NoteSelection, PropertyValue};
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("diary").unwrap();
+        api.save_note("diary", "# Secret").unwrap();
+        api.unlock("correct horse").unwrap();
+        api.encrypt_note("diary").unwrap();
+        let on_disk = || std::fs::read_to_string(temp_dir.path().join("diary/_index.md")).unwrap();
+
+        api.set_property("diary", "status", PropertyValue::Text("done".to_string()))
+            .unwrap();
+        assert!(is_encrypted(&on_disk()));
+        assert_eq!(
+            api.get_note("diary").unwrap().content,
+            "---\nstatus: done\n---\n# Secret"
+        );
+
+        api.bulk_set_property(
+            &NoteSelection::Paths(vec!["diary".to_string()]),
+            "mood",
+            Some(PropertyValue::Text("calm".to_string())),
+            false,
+        )
+        .unwrap();
+        assert!(is_encrypted(&on_disk()));
+        assert!(api.is_note_encrypted("diary").unwrap());
+        assert_eq!(
+            api.get_note("diary").unwrap().content,
+            "---\nstatus: done\nmood: calm\n---\n# Secret"
+        );
+        assert_eq!(api.render_note("diary", "markdown").unwrap(), "# Secret");
+
+        api.duplicate_note("diary", "copy", false).unwrap();
+        assert!(api.is_note_encrypted("copy").unwrap());
+        assert!(api.get_note("copy").unwrap().content.ends_with("# Secret"));
+
+        // Locked, edits that would need the plain text fail instead of writing it
+        api.lock();
+        assert!(matches!(
+            api.set_property("diary", "status", PropertyValue::Text("x".to_string())),
+            Err(Error::Encrypted(_))
+        ));
+        assert!(matches!(
+            api.render_note("diary", "markdown"),
+            Err(Error::Encrypted(_))
+        ));
+        assert!(is_encrypted(&on_disk()));
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        assert!(!api.has_passphrase());
+        api.unlock("first").unwrap();
+        api.lock();
+
+        assert!(api.has_passphrase());
+        assert!(matches!(
+            api.unlock("second"),
+            Err(Error::PermissionDenied(_))
+        ));
+        assert!(!api.is_unlocked());
+    }
+}
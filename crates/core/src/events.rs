@@ -0,0 +1,111 @@
+//! The events the vault reports to frontends.
+//!
+//! The watcher, the write queue, and the availability monitor all report through
+//! `NotesEvent`. A frontend forwards each one under `name`, a stable string the webview
+//! listens for, with `change` as its payload. Events without a change mean anything
+//! may have changed and the frontend should reload.
+//!
+//! Only changes to the vault belong here. Events a frontend raises itself with its
+//! own payloads (moved notes, tree diffs, reminders) stay in the frontend.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotesEvent {
+    /// Many notes may have changed at once (e.g. files were ingested); reload everything
+    NotesChanged,
+    /// The vault was rescanned after notes were renamed or moved; reload everything
+    NotesRenamed,
+    /// A note appeared on disk
+    NoteCreated { path: String },
+    /// A note's content changed on disk
+    NoteModified { path: String },
+    /// A note was removed from disk
+    NoteDeleted { path: String },
+    /// A note folder was renamed or moved on disk
+    NoteRenamed { from: String, to: String },
+    /// The startup sync finished; anything shown from the stale index should reload
+    IndexRefreshed,
+    /// Frecency scores were updated (navigation should refresh)
+    FrecencyUpdated,
+    /// The notes root disappeared (e.g. an external drive was unplugged); writes are blocked
+    VaultUnavailable,
+    /// The notes root is back; the index should be rescanned
+    VaultAvailable,
+    /// A save through the write queue was refused because the note changed on disk
+    NoteConflicted { path: String },
+}
+
+/// The payload of a note-level event, as the webview's `NoteChange` type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NoteChange {
+    Created { path: String },
+    Modified { path: String },
+    Deleted { path: String },
+    Renamed { from: String, to: String },
+    Conflicted { path: String },
+}
+
+impl NotesEvent {
+    /// The event name the webview listens for. Several events share a name and are
+    /// told apart by their `change`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            NotesEvent::NotesChanged
+            | NotesEvent::NoteCreated { .. }
+            | NotesEvent::NoteModified { .. }
+            | NotesEvent::NoteDeleted { .. } => "notes:changed",
+            NotesEvent::NotesRenamed | NotesEvent::NoteRenamed { .. } => "notes:renamed",
+            NotesEvent::IndexRefreshed => "notes:index-refreshed",
+            NotesEvent::FrecencyUpdated => "notes:frecency",
+            NotesEvent::VaultUnavailable => "notes:vault-unavailable",
+            NotesEvent::VaultAvailable => "notes:vault-available",
+            NotesEvent::NoteConflicted { .. } => "notes:conflicted",
+        }
+    }
+
+    /// The note the event is about, or `None` when the frontend should reload
+    /// everything.
+    pub fn change(&self) -> Option<NoteChange> {
+        let change = match self.clone() {
+            NotesEvent::NoteCreated { path } => NoteChange::Created { path },
+            NotesEvent::NoteModified { path } => NoteChange::Modified { path },
+            NotesEvent::NoteDeleted { path } => NoteChange::Deleted { path },
+            NotesEvent::NoteRenamed { from, to } => NoteChange::Renamed { from, to },
+            NotesEvent::NoteConflicted { path } => NoteChange::Conflicted { path },
+            _ => return None,
+        };
+        Some(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webview_names_and_payloads() {
+        let modified = NotesEvent::NoteModified {
+            path: "inbox".to_string(),
+        };
+        assert_eq!(modified.name(), "notes:changed");
+        assert_eq!(
+            serde_json::to_value(modified.change()).unwrap(),
+            serde_json::json!({ "kind": "modified", "path": "inbox" })
+        );
+
+        let renamed = NotesEvent::NoteRenamed {
+            from: "a".to_string(),
+            to: "b".to_string(),
+        };
+        assert_eq!(renamed.name(), NotesEvent::NotesRenamed.name());
+        assert_eq!(NotesEvent::NotesRenamed.change(), None);
+        assert_eq!(NotesEvent::VaultAvailable.name(), "notes:vault-available");
+        assert_eq!(
+            serde_json::to_value(&renamed).unwrap(),
+            serde_json::json!({ "type": "note_renamed", "from": "a", "to": "b" })
+        );
+    }
+}
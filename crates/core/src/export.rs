@@ -0,0 +1,811 @@
+//! Rendering notes in other markup languages (Org-mode, AsciiDoc).
+//!
+//! Each target is a [`FormatConverter`] held in a [`FormatRegistry`], so frontends can
+//! plug in their own formats next to the built-in ones. The built-in converters are
+//! line-based: block structure (headings, lists, quotes, code, tables, rules) is
+//! translated per line and inline markup per span. Anything they don't recognise is
+//! passed through as text.
+//!
+//! Before conversion, links between notes are rewritten in the vault's
+//! [`LinkStyle`], so every format gets ordinary Markdown links to work with.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::backlinks::link_refs;
+use crate::config::LinkStyle;
+use crate::frontmatter::Frontmatter;
+use crate::note_links::percent_encode;
+use crate::notes::{Error, NotesApi, Result};
+
+/// Converts a note body from Markdown to another markup language.
+pub trait FormatConverter: Send + Sync {
+    /// Stable identifier used to pick the format (e.g. `"org"`)
+    fn id(&self) -> &'static str;
+    /// Human-readable name for menus
+    fn name(&self) -> &'static str;
+    /// File extension used for exported files, without the dot
+    fn extension(&self) -> &'static str;
+    fn convert(&self, markdown: &str) -> String;
+}
+
+/// The export formats available to a vault.
+pub struct FormatRegistry {
+    converters: Vec<Box<dyn FormatConverter>>,
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        Self {
+            converters: vec![Box::new(Markdown), Box::new(OrgMode), Box::new(AsciiDoc)],
+        }
+    }
+}
+
+impl FormatRegistry {
+    /// Adds a converter, replacing any existing one with the same id.
+    pub fn register(&mut self, converter: Box<dyn FormatConverter>) {
+        self.converters.retain(|c| c.id() != converter.id());
+        self.converters.push(converter);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn FormatConverter> {
+        self.converters
+            .iter()
+            .find(|c| c.id() == id)
+            .map(|c| c.as_ref())
+    }
+
+    pub fn formats(&self) -> impl Iterator<Item = &dyn FormatConverter> {
+        self.converters.iter().map(|c| c.as_ref())
+    }
+}
+
+impl NotesApi {
+    /// Adds an export format (or replaces a built-in one with the same id).
+    pub fn register_format(&mut self, converter: Box<dyn FormatConverter>) {
+        self.formats.register(converter);
+    }
+
+    /// Returns the export formats available for `render_note` and `export_notes`.
+    pub fn export_formats(&self) -> &FormatRegistry {
+        &self.formats
+    }
+
+    /// Renders a note's body (without frontmatter) in the given format, with links
+    /// in the configured `LinkStyle`.
+    pub fn render_note(&self, path: &str, format: &str) -> Result<String> {
+        let converter = self.converter(format)?;
+        self.render_with_links(path, converter, &HashMap::new())
+    }
+
+    /// Writes each note to `dest_dir` as `<title>.<extension>` and returns how many
+    /// files were written. Titles that repeat get a numeric suffix, which relative
+    /// links between the exported notes follow. Private notes are skipped unless
+    /// `set_include_private` opted in.
+    pub fn export_notes<P: AsRef<Path>>(
+        &self,
+        paths: &[String],
+        format: &str,
+        dest_dir: P,
+    ) -> Result<usize> {
+        let converter = self.converter(format)?;
+        let dest_dir = dest_dir.as_ref();
+        std::fs::create_dir_all(dest_dir)?;
+
+        let mut exported = Vec::new();
+        for path in paths {
+            if !self.note_exists(path)? {
+                return Err(Error::NotFound(path.clone()));
+            }
+            if !self.is_hidden(path)? {
+                exported.push(path);
+            }
+        }
+        let paths = exported;
+
+        let mut used = HashSet::new();
+        let mut files = HashMap::new();
+        for path in &paths {
+            let title = export_title(path);
+            let mut file_name = format!("{}.{}", title, converter.extension());
+            let mut n = 2;
+            while !used.insert(file_name.clone()) {
+                file_name = format!("{} ({}).{}", title, n, converter.extension());
+                n += 1;
+            }
+            files.insert(path.to_string(), file_name);
+        }
+
+        for path in &paths {
+            let rendered = self.render_with_links(path, converter, &files)?;
+            std::fs::write(dest_dir.join(&files[path.as_str()]), rendered)?;
+        }
+        Ok(paths.len())
+    }
+
+    /// Renders a note after rewriting its links; relative links point into `files`
+    /// (note path to exported file name), or at `<title>.<extension>` otherwise.
+    fn render_with_links(
+        &self,
+        path: &str,
+        converter: &dyn FormatConverter,
+        files: &HashMap<String, String>,
+    ) -> Result<String> {
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        let content = self.read_content(path)?;
+        let (_, body) = Frontmatter::parse(&content);
+
+        let export = &self.config().export;
+        let body = match export.links {
+            LinkStyle::Wiki => body.to_string(),
+            LinkStyle::Relative => rewrite_links(body, |target| match files.get(target) {
+                Some(file) => percent_encode(file),
+                None => percent_encode(&format!(
+                    "{}.{}",
+                    export_title(target),
+                    converter.extension()
+                )),
+            }),
+            LinkStyle::Published => rewrite_links(body, |target| {
+                let encoded: Vec<String> = target.split('/').map(percent_encode).collect();
+                format!(
+                    "{}/{}",
+                    export.base_url.trim_end_matches('/'),
+                    encoded.join("/")
+                )
+            }),
+        };
+        Ok(converter.convert(&body))
+    }
+
+    fn converter(&self, format: &str) -> Result<&dyn FormatConverter> {
+        self.formats
+            .get(format)
+            .ok_or_else(|| Error::InvalidInput(format!("Unknown export format: {}", format)))
+    }
+}
+
+/// The file title a note is exported under: its last path segment.
+fn export_title(path: &str) -> &str {
+    let title = path.rsplit('/').next().unwrap_or(path);
+    if title.is_empty() { "notes" } else { title }
+}
+
+/// Replaces links between notes with Markdown links to `url_for(path)`. Wiki-links
+/// keep their `|text`, or are labelled with the linked note's title.
+fn rewrite_links(body: &str, url_for: impl Fn(&str) -> String) -> String {
+    let mut rewritten = body.to_string();
+    // Back to front, so earlier spans stay valid
+    for link in link_refs(body).into_iter().rev() {
+        let text = link
+            .text
+            .unwrap_or_else(|| export_title(&link.path).to_string());
+        rewritten.replace_range(link.span, &format!("[{}]({})", text, url_for(&link.path)));
+    }
+    rewritten
+}
+
+// Built-in formats
+
+/// Passes notes through unchanged, so Markdown sits alongside the other formats.
+struct Markdown;
+
+impl FormatConverter for Markdown {
+    fn id(&self) -> &'static str {
+        "markdown"
+    }
+    fn name(&self) -> &'static str {
+        "Markdown"
+    }
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+    fn convert(&self, markdown: &str) -> String {
+        markdown.to_string()
+    }
+}
+
+struct OrgMode;
+
+impl FormatConverter for OrgMode {
+    fn id(&self) -> &'static str {
+        "org"
+    }
+    fn name(&self) -> &'static str {
+        "Org-mode"
+    }
+    fn extension(&self) -> &'static str {
+        "org"
+    }
+    fn convert(&self, markdown: &str) -> String {
+        convert_blocks(markdown, self)
+    }
+}
+
+struct AsciiDoc;
+
+impl FormatConverter for AsciiDoc {
+    fn id(&self) -> &'static str {
+        "asciidoc"
+    }
+    fn name(&self) -> &'static str {
+        "AsciiDoc"
+    }
+    fn extension(&self) -> &'static str {
+        "adoc"
+    }
+    fn convert(&self, markdown: &str) -> String {
+        convert_blocks(markdown, self)
+    }
+}
+
+/// How a target language spells each construct the line converter recognises.
+trait Markup {
+    fn heading(&self, level: usize, text: &str) -> String;
+    fn code_start(&self, lang: &str) -> String;
+    fn code_end(&self) -> String;
+    fn quote_start(&self) -> String;
+    fn quote_end(&self) -> String;
+    fn rule(&self) -> String;
+    /// `depth` counts from 0 for top-level items
+    fn list_item(&self, depth: usize, ordered: bool, text: &str) -> String;
+    /// Returns `None` to emit nothing for the row (e.g. Markdown's `|---|` separator)
+    fn table_row(&self, cells: &[String], separator: bool) -> Option<String>;
+    fn table_start(&self) -> Option<String>;
+    fn table_end(&self) -> Option<String>;
+    fn inline(&self, span: Inline) -> String;
+}
+
+/// An inline construct with its content already converted.
+enum Inline<'a> {
+    Strong(&'a str),
+    Emphasis(&'a str),
+    Strike(&'a str),
+    Code(&'a str),
+    Link { text: &'a str, url: &'a str },
+    Image { alt: &'a str, url: &'a str },
+}
+
+impl Markup for OrgMode {
+    fn heading(&self, level: usize, text: &str) -> String {
+        format!("{} {}", "*".repeat(level), text)
+    }
+    fn code_start(&self, lang: &str) -> String {
+        if lang.is_empty() {
+            "#+BEGIN_SRC".to_string()
+        } else {
+            format!("#+BEGIN_SRC {}", lang)
+        }
+    }
+    fn code_end(&self) -> String {
+        "#+END_SRC".to_string()
+    }
+    fn quote_start(&self) -> String {
+        "#+BEGIN_QUOTE".to_string()
+    }
+    fn quote_end(&self) -> String {
+        "#+END_QUOTE".to_string()
+    }
+    fn rule(&self) -> String {
+        "-----".to_string()
+    }
+    fn list_item(&self, depth: usize, ordered: bool, text: &str) -> String {
+        let marker = if ordered { "1." } else { "-" };
+        format!("{}{} {}", "  ".repeat(depth), marker, text)
+    }
+    fn table_row(&self, cells: &[String], separator: bool) -> Option<String> {
+        if separator {
+            let dashes: Vec<String> = cells.iter().map(|c| "-".repeat(c.len().max(3))).collect();
+            return Some(format!("|{}|", dashes.join("+")));
+        }
+        Some(format!("| {} |", cells.join(" | ")))
+    }
+    fn table_start(&self) -> Option<String> {
+        None
+    }
+    fn table_end(&self) -> Option<String> {
+        None
+    }
+    fn inline(&self, span: Inline) -> String {
+        match span {
+            Inline::Strong(text) => format!("*{}*", text),
+            Inline::Emphasis(text) => format!("/{}/", text),
+            Inline::Strike(text) => format!("+{}+", text),
+            Inline::Code(text) => format!("~{}~", text),
+            Inline::Link { text, url } => format!("[[{}][{}]]", url, text),
+            Inline::Image { url, .. } => format!("[[{}]]", url),
+        }
+    }
+}
+
+impl Markup for AsciiDoc {
+    fn heading(&self, level: usize, text: &str) -> String {
+        // A single `=` is the document title, so sections start at `==`
+        format!("{} {}", "=".repeat(level + 1), text)
+    }
+    fn code_start(&self, lang: &str) -> String {
+        if lang.is_empty() {
+            "----".to_string()
+        } else {
+            format!("[source,{}]\n----", lang)
+        }
+    }
+    fn code_end(&self) -> String {
+        "----".to_string()
+    }
+    fn quote_start(&self) -> String {
+        "____".to_string()
+    }
+    fn quote_end(&self) -> String {
+        "____".to_string()
+    }
+    fn rule(&self) -> String {
+        "'''".to_string()
+    }
+    fn list_item(&self, depth: usize, ordered: bool, text: &str) -> String {
+        let marker = if ordered { "." } else { "*" };
+        format!("{} {}", marker.repeat(depth + 1), text)
+    }
+    fn table_row(&self, cells: &[String], separator: bool) -> Option<String> {
+        if separator {
+            return None;
+        }
+        Some(format!("| {}", cells.join(" | ")))
+    }
+    fn table_start(&self) -> Option<String> {
+        Some("|===".to_string())
+    }
+    fn table_end(&self) -> Option<String> {
+        Some("|===".to_string())
+    }
+    fn inline(&self, span: Inline) -> String {
+        match span {
+            Inline::Strong(text) => format!("*{}*", text),
+            Inline::Emphasis(text) => format!("_{}_", text),
+            Inline::Strike(text) => format!("[.line-through]#{}#", text),
+            Inline::Code(text) => format!("`+{}+`", text),
+            Inline::Link { text, url } if url.contains("://") => format!("{}[{}]", url, text),
+            Inline::Link { text, url } => format!("link:{}[{}]", url, text),
+            Inline::Image { alt, url } => format!("image:{}[{}]", url, alt),
+        }
+    }
+}
+
+// Block conversion
+
+pub(crate) fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6)
+        .contains(&level)
+        .then(|| (level, text.trim_end_matches('#').trim()))
+}
+
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&c| compact.chars().all(|x| x == c))
+}
+
+/// Returns (indent width, ordered, item text) for a list item line.
+fn list_item(line: &str) -> Option<(usize, bool, &str)> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(text) = trimmed.strip_prefix(marker) {
+            return Some((indent, false, text));
+        }
+    }
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        let rest = &trimmed[digits..];
+        if let Some(text) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return Some((indent, true, text));
+        }
+    }
+    None
+}
+
+fn table_cells(line: &str) -> Option<Vec<String>> {
+    let trimmed = line.trim();
+    let inner = trimmed.strip_prefix('|')?;
+    let inner = inner.strip_suffix('|').unwrap_or(inner);
+    Some(
+        inner
+            .split('|')
+            .map(|cell| cell.trim().to_string())
+            .collect(),
+    )
+}
+
+fn is_table_separator(cells: &[String]) -> bool {
+    cells.iter().all(|cell| {
+        let cell = cell.trim_matches(':');
+        !cell.is_empty() && cell.chars().all(|c| c == '-')
+    })
+}
+
+fn convert_blocks<M: Markup>(markdown: &str, markup: &M) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut in_code = false;
+    let mut in_quote = false;
+    let mut in_table = false;
+    // Indent widths of the open list levels, to turn indentation into depth
+    let mut list_indents: Vec<usize> = Vec::new();
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+
+        if in_code {
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                out.push(markup.code_end());
+                in_code = false;
+            } else {
+                out.push(line.to_string());
+            }
+            continue;
+        }
+
+        let quoted = trimmed
+            .strip_prefix("> ")
+            .or_else(|| (trimmed == ">").then_some(""));
+        if quoted.is_some() != in_quote {
+            out.push(if in_quote {
+                markup.quote_end()
+            } else {
+                markup.quote_start()
+            });
+            in_quote = !in_quote;
+        }
+        let line = quoted.unwrap_or(line);
+        let trimmed = line.trim_start();
+
+        let cells = table_cells(line);
+        if cells.is_some() != in_table {
+            let edge = if in_table {
+                markup.table_end()
+            } else {
+                markup.table_start()
+            };
+            out.extend(edge);
+            in_table = !in_table;
+        }
+        if let Some(cells) = cells {
+            let separator = is_table_separator(&cells);
+            let cells: Vec<String> = if separator {
+                cells
+            } else {
+                cells.iter().map(|c| convert_inline(c, markup)).collect()
+            };
+            out.extend(markup.table_row(&cells, separator));
+            continue;
+        }
+
+        if let Some(lang) = trimmed
+            .strip_prefix("```")
+            .or_else(|| trimmed.strip_prefix("~~~"))
+        {
+            out.push(markup.code_start(lang.trim()));
+            in_code = true;
+        } else if let Some((level, text)) = heading(trimmed) {
+            out.push(markup.heading(level, &convert_inline(text, markup)));
+        } else if is_rule(trimmed) {
+            out.push(markup.rule());
+        } else if let Some((indent, ordered, text)) = list_item(line) {
+            while list_indents.last().is_some_and(|&open| open > indent) {
+                list_indents.pop();
+            }
+            if list_indents.last() != Some(&indent) {
+                list_indents.push(indent);
+            }
+            let depth = list_indents.len() - 1;
+            out.push(markup.list_item(depth, ordered, &convert_inline(text, markup)));
+        } else {
+            if !line.starts_with(' ') {
+                list_indents.clear();
+            }
+            out.push(convert_inline(line, markup));
+        }
+    }
+
+    if in_code {
+        out.push(markup.code_end());
+    }
+    if in_table {
+        out.extend(markup.table_end());
+    }
+    if in_quote {
+        out.push(markup.quote_end());
+    }
+
+    let mut result = out.join("\n");
+    if markdown.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+// Inline conversion
+
+/// Finds the closing `delim` after `start`, requiring non-empty content.
+fn find_closing(text: &str, start: usize, delim: &str) -> Option<usize> {
+    text[start..]
+        .find(delim)
+        .filter(|&i| i > 0)
+        .map(|i| start + i)
+}
+
+/// Parses `[text](url)` at the start of `text`, returning (label, url, length).
+fn link_at(text: &str) -> Option<(&str, &str, usize)> {
+    let rest = text.strip_prefix('[')?;
+    let close = rest.find("](")?;
+    let after = &rest[close + 2..];
+    let end = after.find(')')?;
+    Some((&rest[..close], &after[..end], 1 + close + 2 + end + 1))
+}
+
+fn convert_inline<M: Markup>(text: &str, markup: &M) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < text.len() {
+        let rest = &text[i..];
+
+        if let Some(after) = rest.strip_prefix('`')
+            && let Some(end) = after.find('`')
+        {
+            out.push_str(&markup.inline(Inline::Code(&after[..end])));
+            i += end + 2;
+            continue;
+        }
+
+        if let Some(after) = rest.strip_prefix('!')
+            && let Some((alt, url, len)) = link_at(after)
+        {
+            out.push_str(&markup.inline(Inline::Image { alt, url }));
+            i += 1 + len;
+            continue;
+        }
+
+        if let Some((label, url, len)) = link_at(rest) {
+            let label = convert_inline(label, markup);
+            out.push_str(&markup.inline(Inline::Link { text: &label, url }));
+            i += len;
+            continue;
+        }
+
+        let mut matched = false;
+        for delim in ["**", "__", "~~", "*", "_"] {
+            if !rest.starts_with(delim) {
+                continue;
+            }
+            // Underscores inside words (snake_case) aren't emphasis
+            let prev = text[..i].chars().next_back();
+            if delim.starts_with('_') && prev.is_some_and(|c| c.is_alphanumeric()) {
+                continue;
+            }
+            if let Some(end) = find_closing(rest, delim.len(), delim) {
+                let inner = convert_inline(&rest[delim.len()..end], markup);
+                let span = match delim {
+                    "**" | "__" => Inline::Strong(&inner),
+                    "~~" => Inline::Strike(&inner),
+                    _ => Inline::Emphasis(&inner),
+                };
+                out.push_str(&markup.inline(span));
+                i += end + delim.len();
+                matched = true;
+                break;
+            }
+        }
+        if matched {
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        out.push(c);
+        i += c.len_utf8();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SAMPLE: &str = "\
+# Plan
+Some **bold**, *italic* and `code` with a [link](https://example.com).
+
+- one
+  - nested ~~done~~
+1. first
+
+> quoted
+
+```rust
+fn main() {}
+```
+
+| a | b |
+|---|---|
+| 1 | 2 |
+---
+";
+
+    #[test]
+    fn test_org_mode() {
+        assert_eq!(
+            OrgMode.convert(SAMPLE),
+            "\
+* Plan
+Some *bold*, /italic/ and ~code~ with a [[https://example.com][link]].
+
+- one
+  - nested +done+
+1. first
+
+#+BEGIN_QUOTE
+quoted
+#+END_QUOTE
+
+#+BEGIN_SRC rust
+fn main() {}
+#+END_SRC
+
+| a | b |
+|---+---|
+| 1 | 2 |
+-----
+"
+        );
+    }
+
+    #[test]
+    fn test_asciidoc() {
+        assert_eq!(
+            AsciiDoc.convert(SAMPLE),
+            "\
+== Plan
+Some *bold*, _italic_ and `+code+` with a https://example.com[link].
+
+* one
+** nested [.line-through]#done#
+. first
+
+____
+quoted
+____
+
+[source,rust]
+----
+fn main() {}
+----
+
+|===
+| a | b
+| 1 | 2
+|===
+'''
+"
+        );
+    }
+
+    #[test]
+    fn test_inline_edge_cases() {
+        assert_eq!(
+            convert_inline("snake_case_name and ![alt](a.png)", &AsciiDoc),
+            "snake_case_name and image:a.png[alt]"
+        );
+        assert_eq!(
+            convert_inline("a * b and [x](notes/y)", &AsciiDoc),
+            "a * b and link:notes/y[x]"
+        );
+    }
+
+    #[test]
+    fn test_render_and_export_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.create_note("b").unwrap();
+        api.create_note("a/todo").unwrap();
+        api.save_note("a/todo", "---\ntags: [x]\n---\n# Todo\n")
+            .unwrap();
+        api.create_note("b/todo").unwrap();
+        api.save_note("b/todo", "- item\n").unwrap();
+
+        assert_eq!(api.render_note("a/todo", "org").unwrap(), "* Todo\n");
+        assert!(matches!(
+            api.render_note("a/todo", "rtf"),
+            Err(Error::InvalidInput(_))
+        ));
+
+        let dest = temp_dir.path().join("out");
+        let paths = vec!["a/todo".to_string(), "b/todo".to_string()];
+        assert_eq!(api.export_notes(&paths, "asciidoc", &dest).unwrap(), 2);
+        assert_eq!(
+            std::fs::read_to_string(dest.join("todo.adoc")).unwrap(),
+            "== Todo\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dest.join("todo (2).adoc")).unwrap(),
+            "* item\n"
+        );
+    }
+
+    #[test]
+    fn test_export_link_styles() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in ["guide", "guide/setup", "ideas"] {
+            api.create_note(path).unwrap();
+        }
+        api.save_note(
+            "guide",
+            "See [[guide/setup]], [[ideas|my ideas]] and [the setup](zinnia://note/guide/setup).\n`[[code]]`\n",
+        )
+        .unwrap();
+
+        // Wiki links are kept by default
+        assert!(
+            api.render_note("guide", "markdown")
+                .unwrap()
+                .starts_with("See [[guide/setup]]")
+        );
+
+        let mut config = api.config().clone();
+        config.export.links = LinkStyle::Relative;
+        api.set_config(config.clone()).unwrap();
+        let dest = temp_dir.path().join("out");
+        let paths = vec!["guide".to_string(), "guide/setup".to_string()];
+        api.export_notes(&paths, "markdown", &dest).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dest.join("guide.md")).unwrap(),
+            "See [setup](setup.md), [my ideas](ideas.md) and [the setup](setup.md).\n`[[code]]`\n"
+        );
+
+        config.export.links = LinkStyle::Published;
+        config.export.base_url = "https://notes.example.com/".to_string();
+        api.set_config(config).unwrap();
+        assert_eq!(
+            api.render_note("guide", "org").unwrap(),
+            "See [[https://notes.example.com/guide/setup][setup]], \
+             [[https://notes.example.com/ideas][my ideas]] and \
+             [[https://notes.example.com/guide/setup][the setup]].\n~[[code]]~\n"
+        );
+    }
+
+    #[test]
+    fn test_register_format() {
+        struct Shout;
+        impl FormatConverter for Shout {
+            fn id(&self) -> &'static str {
+                "shout"
+            }
+            fn name(&self) -> &'static str {
+                "Shout"
+            }
+            fn extension(&self) -> &'static str {
+                "txt"
+            }
+            fn convert(&self, markdown: &str) -> String {
+                markdown.to_uppercase()
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("n").unwrap();
+        api.save_note("n", "hi").unwrap();
+        api.register_format(Box::new(Shout));
+
+        assert_eq!(api.render_note("n", "shout").unwrap(), "HI");
+        assert!(api.export_formats().formats().any(|f| f.id() == "org"));
+    }
+}
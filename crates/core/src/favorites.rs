@@ -0,0 +1,93 @@
+//! Starred notes, listed in the order they were starred.
+//!
+//! Favorites are kept by note id, so they follow renames and moves and go away
+//! when the note is deleted.
+
+use rusqlite::{OptionalExtension, params};
+
+use crate::notes::{Error, NoteMetadata, NotesApi, Result, metadata_from_row};
+
+impl NotesApi {
+    /// Stars the note; starring it again keeps its place in the list.
+    pub fn favorite_note(&mut self, path: &str) -> Result<()> {
+        let id = self.favorite_note_id(path)?;
+        self.db.execute(
+            "INSERT OR IGNORE INTO favorites (note_id, added_at) VALUES (?1, ?2)",
+            params![id, self.clock.unix_secs()],
+        )?;
+        Ok(())
+    }
+
+    pub fn unfavorite_note(&mut self, path: &str) -> Result<()> {
+        let id = self.favorite_note_id(path)?;
+        self.db
+            .execute("DELETE FROM favorites WHERE note_id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Returns the starred notes, oldest star first. Archived ones are left out
+    /// but stay starred for when they come back.
+    pub fn get_favorites(&self) -> Result<Vec<NoteMetadata>> {
+        let mut stmt = self.db.prepare(
+            "SELECT notes.id, notes.path, notes.mtime, notes.archived
+             FROM favorites JOIN notes ON notes.id = favorites.note_id
+             WHERE notes.archived = 0
+             ORDER BY favorites.id",
+        )?;
+        let notes = stmt
+            .query_map([], metadata_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(notes)
+    }
+
+    fn favorite_note_id(&self, path: &str) -> Result<i64> {
+        self.db
+            .query_row(
+                "SELECT id FROM notes WHERE path = ?1",
+                params![path],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| Error::NotFound(path.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn favorites(api: &NotesApi) -> Vec<String> {
+        api.get_favorites()
+            .unwrap()
+            .into_iter()
+            .map(|note| note.path)
+            .collect()
+    }
+
+    #[test]
+    fn test_favorites_follow_renames() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in ["a", "b", "c"] {
+            api.create_note(path).unwrap();
+        }
+
+        api.favorite_note("b").unwrap();
+        api.favorite_note("a").unwrap();
+        api.favorite_note("b").unwrap();
+        assert_eq!(favorites(&api), ["b", "a"]);
+
+        api.rename_note("b", "c/b").unwrap();
+        assert_eq!(favorites(&api), ["c/b", "a"]);
+
+        api.unfavorite_note("a").unwrap();
+        api.delete_note("c").unwrap();
+        assert!(favorites(&api).is_empty());
+
+        assert!(matches!(
+            api.favorite_note("missing"),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
@@ -1,8 +1,11 @@
 use std::fs;
-use std::io;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+use crate::layout::{CANVAS_FILE, DEFAULT_NOTE_FILE, VaultLayout};
+use crate::trash::TRASH_DIR;
+
 #[derive(Debug, Clone)]
 pub struct FSNoteMetadata {
     pub path: String,
@@ -21,36 +24,384 @@ fn get_parent_path(path: &str) -> Option<String> {
         .map(|p| p.to_string_lossy().to_string())
 }
 
+/// Replaces `path` with `content` without ever leaving it half-written.
+///
+/// The content goes to a hidden temporary file in the same folder, is flushed to disk,
+/// and then renamed over `path`, so a crash leaves either the old file or the new one.
+/// Hidden files are never indexed, so a temporary file left by a crash is ignored.
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not a file path"))?;
+    let temp = dir.join(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let written = fs::File::create(&temp).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    let result = written.and_then(|_| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Flushes the entries of `dir` (files created or renamed in it) to disk. Only Unix
+/// can open a directory for this; elsewhere the rename is left to the OS.
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    fs::File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Where note and canvas files live, addressed by note path (`projects/rust`).
+///
+/// `NoteFilesystem` is the real store. `MemoryNoteStore` keeps everything in memory
+/// and can inject failures, for testing sync and conflict handling.
+pub trait NoteStore: Send {
+    /// Folder holding the vault's other files (database, config, checkpoints).
+    fn root_path(&self) -> &Path;
+
+    /// False while the store can't be reached (e.g. an unmounted drive).
+    fn is_available(&self) -> bool;
+
+    /// True if notes survive the process; only persistent stores journal their saves
+    /// and keep the index and config on disk.
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    fn read_note(&self, path: &str) -> io::Result<String>;
+    fn write_note(&self, path: &str, content: &str) -> io::Result<()>;
+    /// Like `write_note`, but returns only once the content has reached the disk.
+    fn write_note_durable(&self, path: &str, content: &str) -> io::Result<()>;
+    /// Creates an empty note; fails with `AlreadyExists` if there is one.
+    fn create_note(&self, path: &str) -> io::Result<()>;
+    /// Removes a note with its descendants and canvas.
+    fn delete_note(&self, path: &str) -> io::Result<()>;
+    /// Like `delete_note`, but recoverable where the platform has a trash.
+    fn trash_note(&self, path: &str) -> io::Result<()>;
+    fn read_canvas(&self, path: &str) -> io::Result<String>;
+    fn write_canvas(&self, path: &str, data: &str) -> io::Result<()>;
+    fn canvas_exists(&self, path: &str) -> bool;
+    /// Every note in the store with its modification time.
+    fn scan_all(&self) -> io::Result<Vec<FSNoteMetadata>>;
+    /// The note at `path` and every note below it, as `scan_all` lists them (the whole
+    /// store for `""`). Stores that can look at one folder should, instead of this
+    /// filtering of `scan_all`.
+    fn scan_subtree(&self, path: &str) -> io::Result<Vec<FSNoteMetadata>> {
+        let prefix = format!("{}/", path);
+        Ok(self
+            .scan_all()?
+            .into_iter()
+            .filter(|note| path.is_empty() || note.path == path || note.path.starts_with(&prefix))
+            .collect())
+    }
+    /// Modification time of one note, without scanning the rest of the store. Notes
+    /// `scan_all` skips (e.g. in hidden folders) are `NotFound`.
+    fn note_mtime(&self, path: &str) -> io::Result<SystemTime>;
+    /// Modification time and size in bytes of one note, from a single lookup, for
+    /// telling whether it changed. `NotFound` where `note_mtime` is.
+    fn note_stamp(&self, path: &str) -> io::Result<(SystemTime, u64)>;
+    /// Sets a note's modification time, e.g. to keep the one it had before an import.
+    fn set_note_mtime(&self, path: &str, mtime: SystemTime) -> io::Result<()>;
+
+    /// Stores notes under `name` from now on (see `layout`); stores without note
+    /// files ignore it.
+    fn set_note_file(&mut self, _name: &str) {}
+
+    /// Renames every existing note file to `name`, then stores notes under it.
+    /// Returns how many files were renamed.
+    fn rename_note_files(&mut self, name: &str) -> io::Result<usize> {
+        self.set_note_file(name);
+        Ok(0)
+    }
+
+    /// Stores notes in `layout` from now on; stores without folders ignore it.
+    fn set_layout(&mut self, _layout: VaultLayout) {}
+
+    /// Moves every note into or out of its folder to match `layout`, then stores
+    /// notes in it. Returns how many notes were moved.
+    fn convert_layout(&mut self, layout: VaultLayout) -> io::Result<usize> {
+        self.set_layout(layout);
+        Ok(0)
+    }
+
+    /// Gives a note kept as a plain file (see `VaultLayout::Flat`) a folder of its
+    /// own, e.g. before a child goes in. Returns true if the note was moved.
+    fn promote_note(&self, _path: &str) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    /// Turns a note back into a plain file if the layout is flat and its folder holds
+    /// nothing but the note file. Returns true if the note was moved.
+    fn demote_note(&self, _path: &str) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    fn get_ancestors(&self, path: &str) -> Vec<String> {
+        let mut ancestors = vec![path.to_string()];
+        let mut current = path.to_string();
+
+        while let Some(parent) = get_parent_path(&current) {
+            ancestors.push(parent.clone());
+            current = parent;
+        }
+
+        ancestors.reverse();
+        ancestors
+    }
+}
+
 #[derive(Debug)]
 pub struct NoteFilesystem {
     root_path: PathBuf,
+    /// File inside each note folder that holds the note
+    note_file: String,
+    /// Whether notes without children are plain files
+    layout: VaultLayout,
 }
 
 impl NoteFilesystem {
     pub fn new<P: AsRef<Path>>(root_path: P) -> io::Result<Self> {
         let root_path = root_path.as_ref().to_path_buf();
         fs::create_dir_all(&root_path)?;
-        Ok(Self { root_path })
+        Ok(Self {
+            root_path,
+            note_file: DEFAULT_NOTE_FILE.to_string(),
+            layout: VaultLayout::default(),
+        })
+    }
+
+    /// Where the note lives when it has a folder of its own.
+    fn folder_note_path(&self, path: &str) -> PathBuf {
+        if path.is_empty() {
+            self.root_path.join(&self.note_file)
+        } else {
+            self.root_path.join(path).join(&self.note_file)
+        }
+    }
+
+    /// Where the note lives in the flat layout when it has no folder; `None` for the
+    /// root note and in the folder layout.
+    fn leaf_note_path(&self, path: &str) -> Option<PathBuf> {
+        if self.layout == VaultLayout::Flat && !path.is_empty() {
+            Some(self.root_path.join(format!("{}.md", path)))
+        } else {
+            None
+        }
     }
 
-    pub fn root_path(&self) -> &Path {
+    /// The file holding the note, or that a new note would be written to.
+    fn note_to_fs_path(&self, path: &str) -> PathBuf {
+        let folder = self.folder_note_path(path);
+        match self.leaf_note_path(path) {
+            // A folder note wins over a leftover `<name>.md`, and a folder that
+            // already exists gets the note file rather than a file next to it
+            Some(leaf)
+                if !folder.exists() && (leaf.is_file() || !self.root_path.join(path).is_dir()) =>
+            {
+                leaf
+            }
+            _ => folder,
+        }
+    }
+
+    /// True for a folder without a note file of its own that has notes further down
+    /// (a plain folder in an Obsidian vault), which the flat layout reads as an empty
+    /// note.
+    fn is_bare_folder(&self, path: &str) -> bool {
+        let Some(leaf) = self.leaf_note_path(path) else {
+            return false;
+        };
+        let dir = self.root_path.join(path);
+        dir.is_dir()
+            && !leaf.exists()
+            && !self.folder_note_path(path).exists()
+            && self.holds_notes(&dir)
+    }
+
+    /// True if a Markdown file sits somewhere below `dir`, outside hidden folders.
+    fn holds_notes(&self, dir: &Path) -> bool {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return false;
+        };
+        entries.flatten().any(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                return false;
+            }
+            match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => self.holds_notes(&entry.path()),
+                Ok(_) => name.ends_with(".md"),
+                Err(_) => false,
+            }
+        })
+    }
+
+    /// Promotes the parent of a note about to be created, so the note goes inside it.
+    fn promote_parent(&self, path: &str) -> io::Result<()> {
+        if let Some(parent) = get_parent_path(path) {
+            self.promote_note(&parent)?;
+        }
+        Ok(())
+    }
+
+    /// Demotes the parent of a note that was just removed, if it was the last child.
+    fn demote_parent(&self, path: &str) -> io::Result<()> {
+        if let Some(parent) = get_parent_path(path) {
+            self.demote_note(&parent)?;
+        }
+        Ok(())
+    }
+
+    fn canvas_to_fs_path(&self, path: &str) -> PathBuf {
+        if path.is_empty() {
+            self.root_path.join(CANVAS_FILE)
+        } else {
+            self.root_path.join(path).join(CANVAS_FILE)
+        }
+    }
+
+    /// Collects the note files below `dir`, including those in the trash, as
+    /// `(current, renamed)` pairs for `rename_note_files`.
+    fn note_file_renames(
+        &self,
+        dir: &Path,
+        name: &str,
+        renames: &mut Vec<(PathBuf, PathBuf)>,
+    ) -> io::Result<()> {
+        let current = dir.join(&self.note_file);
+        if current.exists() {
+            renames.push((current, dir.join(name)));
+        }
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if !entry.metadata()?.is_dir() {
+                continue;
+            }
+            let is_trash = dir == self.root_path && entry.file_name() == TRASH_DIR;
+            if !is_trash && entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            self.note_file_renames(&entry.path(), name, renames)?;
+        }
+        Ok(())
+    }
+
+    fn scan_dir(
+        &self,
+        dir: &Path,
+        prefix: &str,
+        notes: &mut Vec<FSNoteMetadata>,
+    ) -> io::Result<()> {
+        let found_before = notes.len();
+        let index_path = dir.join(&self.note_file);
+        let has_note_file = index_path.exists();
+        if has_note_file {
+            let metadata = fs::metadata(&index_path)?;
+            let mtime = metadata.modified()?;
+            notes.push(FSNoteMetadata {
+                path: prefix.to_string(),
+                mtime,
+            });
+        }
+
+        let child_path = |name: &str| {
+            if prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", prefix, name)
+            }
+        };
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if metadata.is_dir() {
+                // Hidden folders hold the trash, checkpoints, and crash reports
+                if name.starts_with('.') {
+                    continue;
+                }
+                self.scan_dir(&entry.path(), &child_path(&name), notes)?;
+            } else if self.layout == VaultLayout::Flat
+                && name != self.note_file
+                && let Some(stem) = name.strip_suffix(".md")
+                && !stem.is_empty()
+                && !stem.starts_with('.')
+                // A folder note of the same name takes precedence
+                && !dir.join(stem).join(&self.note_file).exists()
+            {
+                notes.push(FSNoteMetadata {
+                    path: child_path(stem),
+                    mtime: metadata.modified()?,
+                });
+            }
+        }
+
+        // A plain folder with notes in it is a note too in the flat layout
+        if !has_note_file
+            && notes.len() > found_before
+            && self
+                .leaf_note_path(prefix)
+                .is_some_and(|leaf| !leaf.exists())
+        {
+            notes.push(FSNoteMetadata {
+                path: prefix.to_string(),
+                mtime: fs::metadata(dir)?.modified()?,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl NoteStore for NoteFilesystem {
+    fn root_path(&self) -> &Path {
         &self.root_path
     }
 
-    pub fn read_note(&self, path: &str) -> io::Result<String> {
+    fn is_available(&self) -> bool {
+        self.root_path.is_dir()
+    }
+
+    fn read_note(&self, path: &str) -> io::Result<String> {
+        if self.is_bare_folder(path) {
+            return Ok(String::new());
+        }
         let fs_path = self.note_to_fs_path(path);
         fs::read_to_string(fs_path)
     }
 
-    pub fn write_note(&self, path: &str, content: &str) -> io::Result<()> {
+    fn write_note(&self, path: &str, content: &str) -> io::Result<()> {
         let fs_path = self.note_to_fs_path(path);
+        if !fs_path.exists() {
+            self.promote_parent(path)?;
+        }
         if let Some(parent) = fs_path.parent() {
             fs::create_dir_all(parent)?;
         }
-        fs::write(fs_path, content)
+        write_atomic(&fs_path, content.as_bytes())
+    }
+
+    fn write_note_durable(&self, path: &str, content: &str) -> io::Result<()> {
+        self.write_note(path, content)?;
+        // The content is already on disk; make sure the rename onto it is too
+        match self.note_to_fs_path(path).parent() {
+            Some(dir) => sync_dir(dir),
+            None => Ok(()),
+        }
     }
 
-    pub fn create_note(&self, path: &str) -> io::Result<()> {
+    fn create_note(&self, path: &str) -> io::Result<()> {
         let fs_path = self.note_to_fs_path(path);
         if fs_path.exists() {
             return Err(io::Error::new(
@@ -58,84 +409,235 @@ impl NoteFilesystem {
                 "Note already exists",
             ));
         }
+        self.promote_parent(path)?;
         if let Some(parent) = fs_path.parent() {
             fs::create_dir_all(parent)?;
         }
         fs::write(fs_path, "")
     }
 
-    pub fn delete_note(&self, path: &str) -> io::Result<()> {
+    fn delete_note(&self, path: &str) -> io::Result<()> {
         let dir_path = self.root_path.join(path);
-        fs::remove_dir_all(dir_path)
+        let leaf = self.leaf_note_path(path).filter(|leaf| leaf.is_file());
+        if leaf.is_none() || dir_path.exists() {
+            fs::remove_dir_all(dir_path)?;
+        }
+        if let Some(leaf) = leaf {
+            fs::remove_file(leaf)?;
+        }
+        self.demote_parent(path)
     }
 
-    pub fn trash_note(&self, path: &str) -> io::Result<()> {
+    fn trash_note(&self, path: &str) -> io::Result<()> {
         let dir_path = self.root_path.join(path);
-        if !dir_path.exists() {
+        let leaf = self.leaf_note_path(path).filter(|leaf| leaf.is_file());
+        if !dir_path.exists() && leaf.is_none() {
             return Err(io::Error::new(
                 io::ErrorKind::NotFound,
                 "Note does not exist",
             ));
         }
-        trash::delete(&dir_path)
-            .map_err(|e| io::Error::other(format!("Failed to move note to trash: {}", e)))
+        let files = [Some(dir_path).filter(|dir| dir.exists()), leaf];
+        trash::delete_all(files.iter().flatten())
+            .map_err(|e| io::Error::other(format!("Failed to move note to trash: {}", e)))?;
+        self.demote_parent(path)
     }
 
-    pub fn scan_all(&self) -> io::Result<Vec<FSNoteMetadata>> {
-        let mut notes = Vec::new();
-        Self::scan_dir(&self.root_path, "", &mut notes)?;
-        Ok(notes)
+    fn read_canvas(&self, path: &str) -> io::Result<String> {
+        fs::read_to_string(self.canvas_to_fs_path(path))
     }
 
-    pub fn get_ancestors(&self, path: &str) -> Vec<String> {
-        let mut ancestors = vec![path.to_string()];
-        let mut current = path.to_string();
-
-        while let Some(parent) = get_parent_path(&current) {
-            ancestors.push(parent.clone());
-            current = parent;
+    fn write_canvas(&self, path: &str, data: &str) -> io::Result<()> {
+        // The canvas lives next to the note file, so the note needs a folder
+        self.promote_note(path)?;
+        let fs_path = self.canvas_to_fs_path(path);
+        if let Some(parent) = fs_path.parent() {
+            fs::create_dir_all(parent)?;
         }
+        write_atomic(&fs_path, data.as_bytes())
+    }
 
-        ancestors.reverse();
-        ancestors
+    fn canvas_exists(&self, path: &str) -> bool {
+        self.canvas_to_fs_path(path).exists()
     }
 
-    fn note_to_fs_path(&self, path: &str) -> PathBuf {
-        if path.is_empty() {
-            self.root_path.join("_index.md")
-        } else {
-            self.root_path.join(path).join("_index.md")
-        }
+    fn scan_all(&self) -> io::Result<Vec<FSNoteMetadata>> {
+        let mut notes = Vec::new();
+        self.scan_dir(&self.root_path, "", &mut notes)?;
+        Ok(notes)
     }
 
-    fn scan_dir(dir: &Path, prefix: &str, notes: &mut Vec<FSNoteMetadata>) -> io::Result<()> {
-        let index_path = dir.join("_index.md");
-        if index_path.exists() {
-            let metadata = fs::metadata(&index_path)?;
-            let mtime = metadata.modified()?;
+    fn scan_subtree(&self, path: &str) -> io::Result<Vec<FSNoteMetadata>> {
+        let mut notes = Vec::new();
+        let dir = self.root_path.join(path);
+        if dir.is_dir() {
+            self.scan_dir(&dir, path, &mut notes)?;
+        }
+        // A note kept as a plain file sits next to its folder, if it has one
+        if !notes.iter().any(|note| note.path == path)
+            && let Some(leaf) = self.leaf_note_path(path)
+            && let Ok(metadata) = fs::metadata(&leaf)
+            && metadata.is_file()
+        {
             notes.push(FSNoteMetadata {
-                path: prefix.to_string(),
-                mtime,
+                path: path.to_string(),
+                mtime: metadata.modified()?,
             });
         }
+        Ok(notes)
+    }
 
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let metadata = entry.metadata()?;
+    fn set_note_file(&mut self, name: &str) {
+        self.note_file = name.to_string();
+    }
 
-            if metadata.is_dir() {
-                let name = entry.file_name().to_string_lossy().to_string();
-                let new_prefix = if prefix.is_empty() {
-                    name.clone()
-                } else {
-                    format!("{}/{}", prefix, name)
+    fn rename_note_files(&mut self, name: &str) -> io::Result<usize> {
+        if name == self.note_file {
+            return Ok(0);
+        }
+        let mut renames = Vec::new();
+        self.note_file_renames(&self.root_path, name, &mut renames)?;
+        // Check every folder first so a clash leaves the vault as it was
+        if let Some((_, target)) = renames.iter().find(|(_, target)| target.exists()) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", target.display()),
+            ));
+        }
+        rename_all(&renames)?;
+        self.note_file = name.to_string();
+        Ok(renames.len())
+    }
+
+    fn set_layout(&mut self, layout: VaultLayout) {
+        self.layout = layout;
+    }
+
+    fn convert_layout(&mut self, layout: VaultLayout) -> io::Result<usize> {
+        if layout == self.layout {
+            return Ok(0);
+        }
+        let previous = self.layout;
+        let mut paths: Vec<String> = self.scan_all()?.into_iter().map(|n| n.path).collect();
+        // Notes moved so far, and whether they got a new empty note file; a failure
+        // moves them back (newest first) and leaves the layout as it was
+        let mut moved: Vec<(&str, bool)> = Vec::new();
+        let result = match layout {
+            VaultLayout::Flat => {
+                self.layout = layout;
+                // Deepest first, so a folder emptied of children can be demoted too
+                paths.sort_by_key(|path| std::cmp::Reverse(path.matches('/').count()));
+                paths.iter().try_for_each(|path| {
+                    if self.demote_note(path)? {
+                        moved.push((path, false));
+                    }
+                    Ok(())
+                })
+            }
+            VaultLayout::Folders => paths.iter().try_for_each(|path| {
+                if self.is_bare_folder(path) {
+                    // Plain folders get an empty note file so their children stay
+                    fs::write(self.folder_note_path(path), "")?;
+                    moved.push((path, true));
+                } else if self.promote_note(path)? {
+                    moved.push((path, false));
+                }
+                Ok(())
+            }),
+        };
+        if let Err(e) = result {
+            // Going back needs the flat layout's file names in both directions
+            for (path, created) in moved.into_iter().rev() {
+                let _ = match (layout, created) {
+                    (VaultLayout::Flat, _) => self.promote_note(path).map(drop),
+                    (VaultLayout::Folders, true) => fs::remove_file(self.folder_note_path(path)),
+                    (VaultLayout::Folders, false) => self.demote_note(path).map(drop),
                 };
-                Self::scan_dir(&entry.path(), &new_prefix, notes)?;
             }
+            self.layout = previous;
+            return Err(e);
         }
+        self.layout = layout;
+        Ok(moved.len())
+    }
 
-        Ok(())
+    fn promote_note(&self, path: &str) -> io::Result<bool> {
+        let Some(leaf) = self.leaf_note_path(path) else {
+            return Ok(false);
+        };
+        let folder = self.folder_note_path(path);
+        if !leaf.is_file() || folder.exists() {
+            return Ok(false);
+        }
+        let dir = self.root_path.join(path);
+        fs::create_dir_all(&dir)?;
+        fs::rename(&leaf, &folder)?;
+        sync_dir(&dir)?;
+        Ok(true)
     }
+
+    fn demote_note(&self, path: &str) -> io::Result<bool> {
+        let Some(leaf) = self.leaf_note_path(path) else {
+            return Ok(false);
+        };
+        let dir = self.root_path.join(path);
+        let folder = self.folder_note_path(path);
+        if leaf.exists() || !folder.is_file() {
+            return Ok(false);
+        }
+        for entry in fs::read_dir(&dir)? {
+            if entry?.file_name() != self.note_file.as_str() {
+                return Ok(false);
+            }
+        }
+        fs::rename(&folder, &leaf)?;
+        fs::remove_dir(&dir)?;
+        if let Some(parent) = leaf.parent() {
+            sync_dir(parent)?;
+        }
+        Ok(true)
+    }
+
+    fn note_mtime(&self, path: &str) -> io::Result<SystemTime> {
+        Ok(self.note_stamp(path)?.0)
+    }
+
+    fn note_stamp(&self, path: &str) -> io::Result<(SystemTime, u64)> {
+        if path.split('/').any(|segment| segment.starts_with('.')) {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        let metadata = if self.is_bare_folder(path) {
+            fs::metadata(self.root_path.join(path))?
+        } else {
+            fs::metadata(self.note_to_fs_path(path))?
+        };
+        Ok((metadata.modified()?, metadata.len()))
+    }
+
+    fn set_note_mtime(&self, path: &str, mtime: SystemTime) -> io::Result<()> {
+        fs::File::options()
+            .write(true)
+            .open(self.note_to_fs_path(path))?
+            .set_modified(mtime)
+    }
+}
+
+/// Renames each `(from, to)` pair in order. If one fails, those already done are
+/// renamed back, newest first, before the error is returned.
+fn rename_all(renames: &[(PathBuf, PathBuf)]) -> io::Result<()> {
+    for (i, (from, to)) in renames.iter().enumerate() {
+        let result = fs::rename(from, to).and_then(|()| match to.parent() {
+            Some(dir) => sync_dir(dir),
+            None => Ok(()),
+        });
+        if let Err(e) = result {
+            for (from, to) in renames[..i].iter().rev() {
+                let _ = fs::rename(to, from);
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -163,6 +665,33 @@ mod tests {
         assert_eq!(content, "Hello, World!");
     }
 
+    #[test]
+    fn test_write_replaces_note_atomically() {
+        let temp_dir = TempDir::new().unwrap();
+        let fs = NoteFilesystem::new(temp_dir.path()).unwrap();
+
+        fs.write_note("test", "first").unwrap();
+        fs.write_note_durable("test", "second").unwrap();
+        assert_eq!(fs.read_note("test").unwrap(), "second");
+
+        // A write that can't be renamed into place cleans up its temporary file
+        let blocked = temp_dir.path().join("blocked");
+        std::fs::create_dir_all(blocked.join("child")).unwrap();
+        assert!(write_atomic(&blocked, b"x").is_err());
+
+        let mut names: Vec<_> = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["blocked", "test"]);
+        let names: Vec<_> = std::fs::read_dir(temp_dir.path().join("test"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["_index.md"]);
+    }
+
     #[test]
     fn test_create_nested_note() {
         let temp_dir = TempDir::new().unwrap();
@@ -269,6 +798,23 @@ mod tests {
 
         let notes = fs.scan_all().unwrap();
         assert_eq!(notes.len(), 4);
+
+        let mut subtree: Vec<String> = fs
+            .scan_subtree("projects")
+            .unwrap()
+            .into_iter()
+            .map(|n| n.path)
+            .collect();
+        subtree.sort();
+        assert_eq!(
+            subtree,
+            vec![
+                "projects",
+                "projects/rust-app",
+                "projects/rust-app/architecture"
+            ]
+        );
+        assert_eq!(fs.scan_subtree("").unwrap().len(), 4);
     }
 
     #[test]
@@ -293,6 +839,17 @@ mod tests {
         assert_eq!(content, "Root content");
     }
 
+    #[test]
+    fn test_write_and_read_canvas() {
+        let temp_dir = TempDir::new().unwrap();
+        let fs = NoteFilesystem::new(temp_dir.path()).unwrap();
+
+        assert!(!fs.canvas_exists("board"));
+        fs.write_canvas("board", "{}").unwrap();
+        assert!(fs.canvas_exists("board"));
+        assert_eq!(fs.read_canvas("board").unwrap(), "{}");
+    }
+
     #[test]
     fn test_get_ancestors() {
         let temp_dir = TempDir::new().unwrap();
@@ -326,4 +883,103 @@ mod tests {
             vec!["a", "a/b", "a/b/c", "a/b/c/d", "a/b/c/d/e"]
         );
     }
+
+    fn flat_fs(root: &Path) -> NoteFilesystem {
+        let mut fs = NoteFilesystem::new(root).unwrap();
+        fs.set_layout(VaultLayout::Flat);
+        fs
+    }
+
+    #[test]
+    fn test_flat_layout_promotes_and_demotes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let fs = flat_fs(root);
+
+        fs.create_note("projects").unwrap();
+        fs.write_note("projects", "# Projects").unwrap();
+        assert!(root.join("projects.md").is_file());
+        assert!(!root.join("projects").exists());
+
+        // The first child moves its parent into a folder
+        fs.create_note("projects/rust").unwrap();
+        assert!(root.join("projects/_index.md").is_file());
+        assert!(root.join("projects/rust.md").is_file());
+        assert!(!root.join("projects.md").exists());
+        assert_eq!(fs.read_note("projects").unwrap(), "# Projects");
+
+        // Removing the last child moves it back out
+        fs.delete_note("projects/rust").unwrap();
+        assert!(root.join("projects.md").is_file());
+        assert!(!root.join("projects").exists());
+        assert_eq!(fs.read_note("projects").unwrap(), "# Projects");
+
+        // A canvas needs the folder too, which then stays
+        fs.write_canvas("projects", "{}").unwrap();
+        assert!(root.join("projects/_canvas.json").is_file());
+        assert_eq!(fs.read_note("projects").unwrap(), "# Projects");
+    }
+
+    #[test]
+    fn test_flat_layout_scans_obsidian_vault() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Projects/attachments")).unwrap();
+        fs::create_dir_all(root.join(".obsidian")).unwrap();
+        fs::write(root.join("Inbox.md"), "inbox").unwrap();
+        fs::write(root.join("Projects/Rust.md"), "rust").unwrap();
+        fs::write(root.join("Projects/attachments/logo.png"), [0u8; 4]).unwrap();
+        fs::write(root.join(".obsidian/workspace.md"), "").unwrap();
+
+        let fs = flat_fs(root);
+        let mut paths: Vec<String> = fs.scan_all().unwrap().into_iter().map(|n| n.path).collect();
+        paths.sort();
+        // A plain folder holding notes is an empty note; one holding only files isn't
+        assert_eq!(paths, vec!["Inbox", "Projects", "Projects/Rust"]);
+        assert_eq!(fs.read_note("Projects").unwrap(), "");
+        assert_eq!(fs.read_note("Projects/Rust").unwrap(), "rust");
+        assert!(fs.note_mtime("Projects/attachments").is_err());
+
+        // Writing to the plain folder gives it a note file
+        fs.write_note("Projects", "# Projects").unwrap();
+        assert!(root.join("Projects/_index.md").is_file());
+
+        let subtree: Vec<String> = fs
+            .scan_subtree("Projects")
+            .unwrap()
+            .into_iter()
+            .map(|n| n.path)
+            .collect();
+        assert_eq!(subtree, vec!["Projects", "Projects/Rust"]);
+        assert_eq!(fs.scan_subtree("Inbox").unwrap().len(), 1);
+        assert!(fs.scan_subtree("Missing").unwrap().is_empty());
+
+        // The folder layout only sees folder notes
+        let folders = NoteFilesystem::new(root).unwrap();
+        let paths: Vec<String> = folders
+            .scan_all()
+            .unwrap()
+            .into_iter()
+            .map(|n| n.path)
+            .collect();
+        assert_eq!(paths, vec!["Projects"]);
+    }
+
+    #[test]
+    fn test_failed_rename_all_renames_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::write(dir.join("c"), "c").unwrap();
+        let renames = [
+            (dir.join("a"), dir.join("a2")),
+            (dir.join("missing"), dir.join("b2")),
+            (dir.join("c"), dir.join("c2")),
+        ];
+
+        assert!(rename_all(&renames).is_err());
+        assert!(dir.join("a").is_file());
+        assert!(!dir.join("a2").exists());
+        assert!(dir.join("c").is_file());
+    }
 }
@@ -0,0 +1,315 @@
+//! Markdown formatting actions on plain text (bold, italic, headings, lists).
+//!
+//! Each action takes the text and a selection and returns the new text and
+//! selection, so every frontend editing raw Markdown behaves the same. Offsets
+//! are byte offsets into the text and must fall on character boundaries.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatAction {
+    Bold,
+    Italic,
+    /// Heading level 1-6; applying the line's current level removes it
+    Heading(u8),
+    BulletList,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub text: String,
+    pub selection_start: usize,
+    pub selection_end: usize,
+}
+
+impl FormatAction {
+    fn marker(self) -> Option<&'static str> {
+        match self {
+            FormatAction::Bold => Some("**"),
+            // `_` rather than `*` so it can't be mistaken for half of a bold marker
+            FormatAction::Italic => Some("_"),
+            FormatAction::Heading(_) | FormatAction::BulletList => None,
+        }
+    }
+}
+
+/// Applies `action` to the selection `start..end` of `text`.
+///
+/// Bold and italic wrap the selection (or the word under an empty selection) in
+/// markers, or remove them if it's already wrapped. Headings and lists apply to
+/// every line the selection touches.
+pub fn apply_format(text: &str, start: usize, end: usize, action: FormatAction) -> TextEdit {
+    let (start, end) = (
+        start.min(end).min(text.len()),
+        end.max(start).min(text.len()),
+    );
+    match action.marker() {
+        Some(marker) => toggle_inline(text, start, end, marker),
+        None => transform_lines(text, start, end, action),
+    }
+}
+
+fn toggle_inline(text: &str, start: usize, end: usize, marker: &str) -> TextEdit {
+    let m = marker.len();
+
+    // Markers just outside the selection: `**|word|**`
+    if start >= m && text[..start].ends_with(marker) && text[end..].starts_with(marker) {
+        let mut out = String::with_capacity(text.len());
+        out.push_str(&text[..start - m]);
+        out.push_str(&text[start..end]);
+        out.push_str(&text[end + m..]);
+        return TextEdit {
+            text: out,
+            selection_start: start - m,
+            selection_end: end - m,
+        };
+    }
+
+    // Markers inside the selection: `|**word**|`
+    let selected = &text[start..end];
+    if selected.len() >= 2 * m && selected.starts_with(marker) && selected.ends_with(marker) {
+        let inner = &selected[m..selected.len() - m];
+        return TextEdit {
+            text: format!("{}{}{}", &text[..start], inner, &text[end..]),
+            selection_start: start,
+            selection_end: start + inner.len(),
+        };
+    }
+
+    // Nothing selected: wrap the word under the cursor, if any
+    let (start, end) = if start == end {
+        word_at(text, start)
+    } else {
+        (start, end)
+    };
+    TextEdit {
+        text: format!(
+            "{}{}{}{}{}",
+            &text[..start],
+            marker,
+            &text[start..end],
+            marker,
+            &text[end..]
+        ),
+        selection_start: start + m,
+        selection_end: end + m,
+    }
+}
+
+/// Bounds of the word touching `pos`; `(pos, pos)` between spaces.
+fn word_at(text: &str, pos: usize) -> (usize, usize) {
+    let is_word = |c: char| c.is_alphanumeric() || c == '\'' || c == '-';
+    let start = text[..pos]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word(c))
+        .last()
+        .map_or(pos, |(i, _)| i);
+    let end = text[pos..]
+        .char_indices()
+        .find(|&(_, c)| !is_word(c))
+        .map_or(text.len(), |(i, _)| pos + i);
+    (start, end)
+}
+
+/// Length of a leading `#`-`######` heading marker and its space, or 0.
+fn heading_prefix_len(line: &str) -> usize {
+    let hashes = line.bytes().take_while(|&b| b == b'#').count();
+    if (1..=6).contains(&hashes) && line[hashes..].starts_with(' ') {
+        hashes + 1
+    } else {
+        0
+    }
+}
+
+/// Indentation and length of a `- `/`* `/`+ ` list marker, if the line has one.
+fn bullet_prefix(line: &str) -> Option<(usize, usize)> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+    ["- ", "* ", "+ "]
+        .iter()
+        .any(|b| rest.starts_with(b))
+        .then_some((indent, 2))
+}
+
+fn transform_lines(text: &str, start: usize, end: usize, action: FormatAction) -> TextEdit {
+    let first = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    // A selection ending right after a newline doesn't include the next line
+    let last_end = if end > start && text[..end].ends_with('\n') {
+        end - 1
+    } else {
+        end
+    };
+    let block_end = text[last_end..]
+        .find('\n')
+        .map_or(text.len(), |i| last_end + i);
+    let lines: Vec<&str> = text[first..block_end].split('\n').collect();
+
+    let all_bullets = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .all(|l| bullet_prefix(l).is_some());
+
+    // For each line: where its prefix sits, its old length, and the replacement
+    let edits: Vec<(usize, usize, String)> = lines
+        .iter()
+        .map(|line| match action {
+            FormatAction::Heading(level) => {
+                let old = heading_prefix_len(line);
+                let wanted = "#".repeat(level.clamp(1, 6) as usize) + " ";
+                if old == wanted.len() && line.starts_with(&wanted) {
+                    (0, old, String::new())
+                } else {
+                    (0, old, wanted)
+                }
+            }
+            FormatAction::BulletList if line.trim().is_empty() => (0, 0, String::new()),
+            FormatAction::BulletList => match bullet_prefix(line) {
+                Some((indent, len)) if all_bullets => (indent, len, String::new()),
+                Some(_) => (0, 0, String::new()),
+                None => {
+                    let indent = line.len() - line.trim_start().len();
+                    (indent, 0, "- ".to_string())
+                }
+            },
+            FormatAction::Bold | FormatAction::Italic => (0, 0, String::new()),
+        })
+        .collect();
+
+    let mut out = String::with_capacity(text.len() + lines.len() * 2);
+    out.push_str(&text[..first]);
+    let map = |pos: usize, line_start: usize, new_start: usize, edit: &(usize, usize, String)| {
+        let (at, old_len, ref new) = *edit;
+        let offset = pos - line_start;
+        let mapped = if offset < at {
+            offset
+        } else if offset < at + old_len {
+            at + new.len()
+        } else {
+            offset - old_len + new.len()
+        };
+        new_start + mapped
+    };
+
+    let mut line_start = first;
+    let (mut new_start_sel, mut new_end_sel) = (start, end);
+    for (i, (line, edit)) in lines.iter().zip(&edits).enumerate() {
+        let new_line_start = out.len();
+        let (at, old_len, new) = edit;
+        out.push_str(&line[..*at]);
+        out.push_str(new);
+        out.push_str(&line[at + old_len..]);
+
+        let line_end = line_start + line.len();
+        if (line_start..=line_end).contains(&start) && new_start_sel == start {
+            new_start_sel = map(start, line_start, new_line_start, edit);
+        }
+        if (line_start..=line_end).contains(&end) {
+            new_end_sel = map(end, line_start, new_line_start, edit);
+        }
+        if i + 1 < lines.len() {
+            out.push('\n');
+        }
+        line_start = line_end + 1;
+    }
+    if end > block_end {
+        new_end_sel = out.len() + (end - block_end);
+    }
+    out.push_str(&text[block_end..]);
+
+    TextEdit {
+        text: out,
+        selection_start: new_start_sel,
+        selection_end: new_end_sel,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies an action to text where `[` and `]` mark the selection.
+    fn format(marked: &str, action: FormatAction) -> String {
+        let start = marked.find('[').unwrap();
+        let end = marked.find(']').unwrap() - 1;
+        let text = marked.replace(['[', ']'], "");
+        let edit = apply_format(&text, start, end, action);
+        let mut out = edit.text.clone();
+        out.insert(edit.selection_end, ']');
+        out.insert(edit.selection_start, '[');
+        out
+    }
+
+    #[test]
+    fn test_toggle_bold() {
+        assert_eq!(format("a [word] b", FormatAction::Bold), "a **[word]** b");
+        assert_eq!(format("a **[word]** b", FormatAction::Bold), "a [word] b");
+        assert_eq!(format("a [**word**] b", FormatAction::Bold), "a [word] b");
+        // An empty selection wraps the word under the cursor
+        assert_eq!(format("a wo[]rd b", FormatAction::Bold), "a **[word]** b");
+        assert_eq!(format("a []", FormatAction::Bold), "a **[]**");
+    }
+
+    #[test]
+    fn test_italic_inside_bold() {
+        assert_eq!(format("**[word]**", FormatAction::Italic), "**_[word]_**");
+        assert_eq!(format("**_[word]_**", FormatAction::Italic), "**[word]**");
+    }
+
+    #[test]
+    fn test_heading() {
+        assert_eq!(format("Ti[]tle", FormatAction::Heading(2)), "## Ti[]tle");
+        assert_eq!(format("## Ti[]tle", FormatAction::Heading(1)), "# Ti[]tle");
+        assert_eq!(format("# [Title]", FormatAction::Heading(1)), "[Title]");
+        assert_eq!(
+            format("intro\n[one\ntwo]\nend", FormatAction::Heading(3)),
+            "intro\n### [one\n### two]\nend"
+        );
+    }
+
+    #[test]
+    fn test_bullet_list() {
+        assert_eq!(
+            format("[one\n\n  two]", FormatAction::BulletList),
+            "- [one\n\n  - two]"
+        );
+        assert_eq!(
+            format("- [one\n* two]", FormatAction::BulletList),
+            "[one\ntwo]"
+        );
+        // Mixed selections are completed rather than toggled off
+        assert_eq!(
+            format("- o[ne\ntwo]", FormatAction::BulletList),
+            "- o[ne\n- two]"
+        );
+        // A selection ending at a line break leaves the next line alone
+        assert_eq!(
+            format("[one\n]two", FormatAction::BulletList),
+            "- [one\n]two"
+        );
+    }
+
+    #[test]
+    fn test_mixed_direction_lines() {
+        // Offsets are logical, so markers land around right-to-left words in
+        // reading order however the line is displayed
+        assert_eq!(
+            format("note [שלום] here", FormatAction::Bold),
+            "note **[שלום]** here"
+        );
+        assert_eq!(
+            format("مرح[]با world", FormatAction::Italic),
+            "_[مرحبا]_ world"
+        );
+        assert_eq!(
+            format("see _[مرحبا]_ world", FormatAction::Italic),
+            "see [مرحبا] world"
+        );
+        assert_eq!(
+            format("[כותרת] title", FormatAction::Heading(2)),
+            "## [כותרת] title"
+        );
+        assert_eq!(
+            format("[שלום\nhello]", FormatAction::BulletList),
+            "- [שלום\n- hello]"
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Minimal YAML-style frontmatter support for notes.
+//!
+//! Only the subset needed for note properties is understood: `key: value` scalars,
+//! inline lists (`key: [a, b]`) and block lists (`key:` followed by `- item` lines).
+//! Anything else is preserved verbatim when the frontmatter is re-rendered.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropertyValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl PropertyValue {
+    /// Returns the value as a single string, if it is a scalar.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            PropertyValue::Text(text) => Some(text),
+            PropertyValue::List(_) => None,
+        }
+    }
+
+    /// Returns the value as a list; scalars are treated as a one-item list.
+    pub fn as_list(&self) -> Vec<String> {
+        match self {
+            PropertyValue::Text(text) if text.is_empty() => Vec::new(),
+            PropertyValue::Text(text) => vec![text.clone()],
+            PropertyValue::List(items) => items.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frontmatter {
+    entries: Vec<(String, PropertyValue)>,
+}
+
+impl Frontmatter {
+    /// Splits note content into its parsed frontmatter and the remaining body.
+    ///
+    /// Returns an empty frontmatter and the full content if there is no `---` block.
+    pub fn parse(content: &str) -> (Frontmatter, &str) {
+        let Some((block, body)) = split_block(content) else {
+            return (Frontmatter::default(), content);
+        };
+
+        let mut entries: Vec<(String, PropertyValue)> = Vec::new();
+        for line in block.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if let Some(item) = trimmed.strip_prefix("- ") {
+                // Block list item belongs to the most recent key
+                if let Some((_, value)) = entries.last_mut() {
+                    let item = unquote(item.trim()).to_string();
+                    match value {
+                        PropertyValue::List(items) => items.push(item),
+                        PropertyValue::Text(text) if text.is_empty() => {
+                            *value = PropertyValue::List(vec![item]);
+                        }
+                        PropertyValue::Text(_) => {}
+                    }
+                }
+                continue;
+            }
+
+            if let Some((key, value)) = trimmed.split_once(':') {
+                let key = key.trim().to_string();
+                if key.is_empty() {
+                    continue;
+                }
+                entries.retain(|(k, _)| k != &key);
+                entries.push((key, parse_value(value.trim())));
+            }
+        }
+
+        (Frontmatter { entries }, body)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&PropertyValue> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Returns the scalar value for `key`, if present.
+    pub fn get_text(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(|v| v.as_text())
+    }
+
+    /// Sets a property, keeping its original position if it already exists.
+    pub fn set(&mut self, key: &str, value: PropertyValue) {
+        if let Some((_, existing)) = self.entries.iter_mut().find(|(k, _)| k == key) {
+            *existing = value;
+        } else {
+            self.entries.push((key.to_string(), value));
+        }
+    }
+
+    /// Removes a property, returning its previous value.
+    pub fn remove(&mut self, key: &str) -> Option<PropertyValue> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PropertyValue)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Renders the frontmatter as a `---` delimited block (empty string if no entries).
+    pub fn render(&self) -> String {
+        if self.entries.is_empty() {
+            return String::new();
+        }
+
+        let mut out = String::from("---\n");
+        for (key, value) in &self.entries {
+            match value {
+                PropertyValue::Text(text) => out.push_str(&format!("{}: {}\n", key, text)),
+                PropertyValue::List(items) => {
+                    out.push_str(&format!("{}: [{}]\n", key, items.join(", ")))
+                }
+            }
+        }
+        out.push_str("---\n");
+        out
+    }
+}
+
+/// Returns `content` with `key` set to `value` in its frontmatter.
+///
+/// Creates the frontmatter block if the note doesn't have one yet.
+pub fn set_property_in_content(content: &str, key: &str, value: PropertyValue) -> String {
+    let (mut frontmatter, body) = Frontmatter::parse(content);
+    frontmatter.set(key, value);
+    format!("{}{}", frontmatter.render(), body)
+}
+
+/// Returns `content` with `key` removed from its frontmatter.
+///
+/// The frontmatter block is dropped entirely when its last property is removed.
+pub fn remove_property_in_content(content: &str, key: &str) -> String {
+    let (mut frontmatter, body) = Frontmatter::parse(content);
+    if frontmatter.remove(key).is_none() {
+        return content.to_string();
+    }
+    format!("{}{}", frontmatter.render(), body)
+}
+
+fn split_block(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let block = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return Some((block, body));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+fn parse_value(raw: &str) -> PropertyValue {
+    if let Some(inner) = raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+        let items = inner
+            .split(',')
+            .map(|item| unquote(item.trim()).to_string())
+            .filter(|item| !item.is_empty())
+            .collect();
+        return PropertyValue::List(items);
+    }
+    PropertyValue::Text(unquote(raw).to_string())
+}
+
+fn unquote(raw: &str) -> &str {
+    raw.strip_prefix('"')
+        .and_then(|r| r.strip_suffix('"'))
+        .or_else(|| raw.strip_prefix('\'').and_then(|r| r.strip_suffix('\'')))
+        .unwrap_or(raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_without_frontmatter() {
+        let (fm, body) = Frontmatter::parse("# Title\n");
+        assert!(fm.is_empty());
+        assert_eq!(body, "# Title\n");
+    }
+
+    #[test]
+    fn test_parse_scalars_and_lists() {
+        let content = "---\nstatus: todo\ntitle: \"Hello\"\ntags: [a, b]\nowners:\n  - alice\n  - bob\n---\n# Body\n";
+        let (fm, body) = Frontmatter::parse(content);
+
+        assert_eq!(fm.get_text("status"), Some("todo"));
+        assert_eq!(fm.get_text("title"), Some("Hello"));
+        assert_eq!(
+            fm.get("tags"),
+            Some(&PropertyValue::List(vec!["a".into(), "b".into()]))
+        );
+        assert_eq!(
+            fm.get("owners"),
+            Some(&PropertyValue::List(vec!["alice".into(), "bob".into()]))
+        );
+        assert_eq!(body, "# Body\n");
+    }
+
+    #[test]
+    fn test_unterminated_block_is_body() {
+        let (fm, body) = Frontmatter::parse("---\nstatus: todo\n");
+        assert!(fm.is_empty());
+        assert_eq!(body, "---\nstatus: todo\n");
+    }
+
+    #[test]
+    fn test_set_property_in_content() {
+        let updated =
+            set_property_in_content("# Body\n", "status", PropertyValue::Text("done".into()));
+        assert_eq!(updated, "---\nstatus: done\n---\n# Body\n");
+
+        let updated =
+            set_property_in_content(&updated, "status", PropertyValue::Text("todo".into()));
+        assert_eq!(updated, "---\nstatus: todo\n---\n# Body\n");
+    }
+
+    #[test]
+    fn test_remove_property_in_content() {
+        let content = "---\nstatus: done\n---\n# Body\n";
+        assert_eq!(remove_property_in_content(content, "status"), "# Body\n");
+        assert_eq!(remove_property_in_content(content, "missing"), content);
+    }
+}
@@ -0,0 +1,698 @@
+//! Versioning and syncing the vault with git.
+//!
+//! Opt-in per vault: `NotesApi::git_init` makes the notes root a repository (or adopts
+//! one that is already there) and turns on the `git` section of the config. Its
+//! `.gitignore` leaves out the index and other state that only makes sense on this
+//! device. While `git.auto_commit` is on, `BackgroundTask::GitCommit` (queued by
+//! `spawn_git_scheduler`) commits every change once no note has been saved for
+//! `git.commit_delay_ms`, so a burst of typing becomes one commit.
+//!
+//! Pulls fast-forward when they can and merge otherwise. A note changed on both sides
+//! is left with conflict markers in its file and listed in `GitStatus::conflicts`;
+//! once each one is edited, `git_resolve` marks it resolved and the last one finishes
+//! the merge. Nothing is committed or pulled while a merge is unfinished.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Weak};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use git2::build::CheckoutBuilder;
+use git2::{
+    AutotagOption, Commit, Cred, CredentialType, ErrorCode, FetchOptions, IndexAddOption, Oid,
+    PushOptions, RemoteCallbacks, Repository, RepositoryState, Signature, Sort, StatusOptions,
+};
+
+use crate::checkpoints::CHECKPOINTS_DIR;
+use crate::clock::Clock;
+use crate::diagnostics::CRASH_REPORT_DIR;
+use crate::journal::JOURNAL_FILE_NAME;
+use crate::layout::NoteFiles;
+use crate::notes::{Error, NotesApi, Result};
+use crate::trash::TRASH_DIR;
+use crate::write_queue::{BackgroundTask, WriteQueue};
+
+/// How often `spawn_git_scheduler` queues an auto-commit check
+const COMMIT_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Marks the start of a conflict in a note's file
+const CONFLICT_MARKER: &str = "<<<<<<< ";
+
+impl From<git2::Error> for Error {
+    fn from(err: git2::Error) -> Self {
+        Error::Git(err.message().to_string())
+    }
+}
+
+/// Where the vault stands against its last commit and the remote.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitStatus {
+    /// `None` until `git_init`
+    pub branch: Option<String>,
+    /// Notes changed since the last commit, ordered by path
+    pub changed: Vec<String>,
+    /// Local commits the remote doesn't have
+    pub ahead: usize,
+    /// Remote commits not pulled yet, as of the last fetch
+    pub behind: usize,
+    /// Notes with unresolved conflicts from a pull, ordered by path
+    pub conflicts: Vec<String>,
+}
+
+/// A commit that changed a note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitCommit {
+    pub id: String,
+    pub summary: String,
+    pub author: String,
+    pub time: SystemTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PullOutcome {
+    UpToDate,
+    /// Only the remote had new commits
+    FastForward,
+    /// Both sides had new commits and they merged cleanly
+    Merged,
+    /// The merge stopped on these notes; see `NotesApi::git_resolve`
+    Conflicts(Vec<String>),
+}
+
+/// The `.gitignore` written by `git_init`: the index, device-local state, and the
+/// `.<name>.<pid>.tmp` files of writes in progress
+fn gitignore() -> String {
+    format!(
+        ".notes.db*\n{}\n{}/\n{}/\n{}/\n.*.tmp\n",
+        JOURNAL_FILE_NAME, CHECKPOINTS_DIR, CRASH_REPORT_DIR, TRASH_DIR
+    )
+}
+
+/// The network half of a push or pull, split off so it can run without holding the
+/// `NotesApi`; get one from `NotesApi::git_remote` or `NotesApi::start_git_pull`.
+#[derive(Debug, Clone)]
+pub struct GitRemote {
+    root: PathBuf,
+    remote: String,
+}
+
+impl GitRemote {
+    fn repository(&self) -> Result<Repository> {
+        Repository::open(&self.root)
+            .map_err(|_| Error::Git("The vault is not a git repository".to_string()))
+    }
+
+    /// Pushes the current branch to the remote.
+    pub fn push(&self) -> Result<()> {
+        let repo = self.repository()?;
+        let branch = branch_name(&repo)?;
+        let mut remote = repo.find_remote(&self.remote)?;
+        let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+
+        let mut rejected = None;
+        {
+            let mut callbacks = remote_callbacks(&repo)?;
+            callbacks.push_update_reference(|_, status| {
+                if let Some(status) = status {
+                    rejected = Some(status.to_string());
+                }
+                Ok(())
+            });
+            let mut options = PushOptions::new();
+            options.remote_callbacks(callbacks);
+            remote.push(&[refspec], Some(&mut options))?;
+        }
+        match rejected {
+            Some(reason) => Err(Error::Git(format!(
+                "The remote rejected the push: {}",
+                reason
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Fetches the current branch into `FETCH_HEAD` for `NotesApi::git_merge_fetched`.
+    /// Only refs change; the notes on disk are left alone.
+    pub fn fetch(&self) -> Result<()> {
+        let repo = self.repository()?;
+        let branch = branch_name(&repo)?;
+        let mut remote = repo.find_remote(&self.remote)?;
+        let mut options = FetchOptions::new();
+        options
+            .remote_callbacks(remote_callbacks(&repo)?)
+            .download_tags(AutotagOption::None);
+        remote.fetch(&[branch.as_str()], Some(&mut options), None)?;
+        Ok(())
+    }
+}
+
+/// The note a changed file belongs to; attachments count toward their folder's note.
+fn note_of(files: &NoteFiles, relative: &str) -> Option<String> {
+    if relative.split('/').any(|segment| segment.starts_with('.')) {
+        return None;
+    }
+    files
+        .note_of_file(relative)
+        .or_else(|| relative.rsplit_once('/').map(|(dir, _)| dir.to_string()))
+        .filter(|note| !note.is_empty())
+}
+
+fn commit_message(notes: &[String]) -> String {
+    match notes {
+        [] => "Update vault".to_string(),
+        [note] => format!("Update {}", note),
+        notes => format!("Update {} notes", notes.len()),
+    }
+}
+
+fn signature(repo: &Repository) -> Result<Signature<'static>> {
+    // Fall back for machines without `user.name` and `user.email`
+    match repo.signature() {
+        Ok(signature) => Ok(signature.to_owned()),
+        Err(_) => Ok(Signature::now("Zinnia", "zinnia@localhost")?),
+    }
+}
+
+/// Authenticates with the user's git credential helper, as the git CLI would.
+fn remote_callbacks(repo: &Repository) -> Result<RemoteCallbacks<'static>> {
+    let config = repo.config()?;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| {
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            Cred::credential_helper(&config, url, username)
+        } else {
+            Cred::default()
+        }
+    });
+    Ok(callbacks)
+}
+
+fn head_commit(repo: &Repository) -> Result<Option<Commit<'_>>> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_commit()?)),
+        Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The checked-out branch's name, even before its first commit.
+fn branch_name(repo: &Repository) -> Result<String> {
+    let head = repo.find_reference("HEAD")?;
+    let target = head
+        .symbolic_target()
+        .ok_or_else(|| Error::Git("HEAD is detached".to_string()))?;
+    Ok(target
+        .strip_prefix("refs/heads/")
+        .unwrap_or(target)
+        .to_string())
+}
+
+fn conflicted_files(repo: &Repository) -> Result<Vec<String>> {
+    let index = repo.index()?;
+    if !index.has_conflicts() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+            files.push(String::from_utf8_lossy(&entry.path).into_owned());
+        }
+    }
+    Ok(files)
+}
+
+impl NotesApi {
+    fn repository(&self) -> Result<Repository> {
+        Repository::open(self.notes_root())
+            .map_err(|_| Error::Git("The vault is not a git repository".to_string()))
+    }
+
+    fn notes_of(&self, files: impl IntoIterator<Item = String>) -> Vec<String> {
+        let note_files = self.note_files();
+        let mut notes: Vec<String> = files
+            .into_iter()
+            .filter_map(|file| note_of(&note_files, &file))
+            .collect();
+        notes.sort();
+        notes.dedup();
+        notes
+    }
+
+    /// Makes the notes root a git repository, or adopts the one already there, and
+    /// turns git on in the vault config. Everything in the vault is committed.
+    pub fn git_init(&mut self) -> Result<()> {
+        self.ensure_writable()?;
+        let root = self.notes_root().to_path_buf();
+        if Repository::open(&root).is_err() {
+            Repository::init(&root)?;
+        }
+        let ignore_file = root.join(".gitignore");
+        if !ignore_file.exists() {
+            fs::write(ignore_file, gitignore())?;
+        }
+        let mut config = self.config().clone();
+        config.git.enabled = true;
+        self.set_config(config)?;
+        self.git_commit(Some("Start versioning notes"))?;
+        Ok(())
+    }
+
+    /// Commits every change in the vault. Without a `message` one is made up from the
+    /// changed notes.
+    ///
+    /// Returns the new commit's id, or `None` if nothing changed or a merge is
+    /// unfinished.
+    pub fn git_commit(&self, message: Option<&str>) -> Result<Option<String>> {
+        self.ensure_writable()?;
+        let repo = self.repository()?;
+        if repo.state() != RepositoryState::Clean {
+            return Ok(None);
+        }
+
+        let mut index = repo.index()?;
+        index.add_all(["*"], IndexAddOption::DEFAULT, None)?;
+        // Picks up deleted files, which `add_all` leaves alone
+        index.update_all(["*"], None)?;
+        index.write()?;
+
+        let parent = head_commit(&repo)?;
+        let parent_tree = parent.as_ref().map(Commit::tree).transpose()?;
+        let diff = repo.diff_tree_to_index(parent_tree.as_ref(), Some(&index), None)?;
+        if diff.deltas().len() == 0 {
+            return Ok(None);
+        }
+        let message = match message {
+            Some(message) => message.to_string(),
+            None => commit_message(&self.notes_of(diff.deltas().filter_map(|delta| {
+                let file = delta.new_file().path().or(delta.old_file().path())?;
+                Some(file.to_string_lossy().replace('\\', "/"))
+            }))),
+        };
+
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = signature(&repo)?;
+        let parents: Vec<&Commit> = parent.iter().collect();
+        let id = repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &parents,
+        )?;
+        Ok(Some(id.to_string()))
+    }
+
+    /// Commits changes for `BackgroundTask::GitCommit` when auto-commit is on and no
+    /// note has been saved for `git.commit_delay_ms`.
+    pub fn git_auto_commit(&self) -> Result<Option<String>> {
+        let git = &self.config().git;
+        if !git.enabled || !git.auto_commit || self.is_read_only() {
+            return Ok(None);
+        }
+        let delay = Duration::from_millis(git.commit_delay_ms);
+        // A clock set backwards counts as still saving
+        let saving = self.last_save.is_some_and(|saved| {
+            self.clock
+                .now()
+                .duration_since(saved)
+                .map_or(true, |idle| idle < delay)
+        });
+        if saving {
+            return Ok(None);
+        }
+        self.git_commit(None)
+    }
+
+    /// Reports uncommitted notes, unresolved conflicts, and how far the branch is from
+    /// the remote. Before `git_init` everything is empty.
+    pub fn git_status(&self) -> Result<GitStatus> {
+        if !self.config().git.enabled {
+            return Ok(GitStatus::default());
+        }
+        let repo = self.repository()?;
+        let branch = branch_name(&repo)?;
+
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let changed = self.notes_of(
+            repo.statuses(Some(&mut options))?
+                .iter()
+                .filter(|entry| !entry.status().is_conflicted())
+                .filter_map(|entry| entry.path().map(str::to_string)),
+        );
+
+        let upstream = format!("refs/remotes/{}/{}", self.config().git.remote, branch);
+        let (ahead, behind) = match (head_commit(&repo)?, repo.refname_to_id(&upstream)) {
+            (Some(local), Ok(remote)) => repo.graph_ahead_behind(local.id(), remote)?,
+            (Some(local), Err(_)) => {
+                // Nothing pushed yet: every commit is ahead
+                let mut walk = repo.revwalk()?;
+                walk.push(local.id())?;
+                (walk.count(), 0)
+            }
+            (None, _) => (0, 0),
+        };
+
+        Ok(GitStatus {
+            branch: Some(branch),
+            changed,
+            ahead,
+            behind,
+            conflicts: self.notes_of(conflicted_files(&repo)?),
+        })
+    }
+
+    /// Lists the commits that changed `path`'s files, newest first.
+    pub fn git_history(&self, path: &str) -> Result<Vec<GitCommit>> {
+        let repo = self.repository()?;
+        if head_commit(&repo)?.is_none() {
+            return Ok(Vec::new());
+        }
+        let note_files = self.note_files();
+        let mut walk = repo.revwalk()?;
+        walk.push_head()?;
+        walk.set_sorting(Sort::TIME)?;
+
+        let mut history = Vec::new();
+        for id in walk {
+            let commit = repo.find_commit(id?)?;
+            let parent_tree = match commit.parent(0) {
+                Ok(parent) => Some(parent.tree()?),
+                Err(_) => None,
+            };
+            let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+            let touches_note = diff.deltas().any(|delta| {
+                [delta.old_file().path(), delta.new_file().path()]
+                    .into_iter()
+                    .flatten()
+                    .any(|file| {
+                        let file = file.to_string_lossy().replace('\\', "/");
+                        note_of(&note_files, &file).as_deref() == Some(path)
+                    })
+            });
+            if touches_note {
+                history.push(GitCommit {
+                    id: commit.id().to_string(),
+                    summary: commit.summary().unwrap_or_default().to_string(),
+                    author: commit.author().name().unwrap_or_default().to_string(),
+                    time: UNIX_EPOCH + Duration::from_secs(commit.time().seconds().max(0) as u64),
+                });
+            }
+        }
+        Ok(history)
+    }
+
+    /// The configured remote, for pushing or fetching without holding the API.
+    pub fn git_remote(&self) -> Result<GitRemote> {
+        self.repository()?;
+        Ok(GitRemote {
+            root: self.notes_root().to_path_buf(),
+            remote: self.config().git.remote.clone(),
+        })
+    }
+
+    /// Pushes the current branch to the configured remote.
+    pub fn git_push(&self) -> Result<()> {
+        self.git_remote()?.push()
+    }
+
+    /// Commits local changes, then fetches the current branch from the remote and
+    /// merges it in. The index is rescanned when notes changed.
+    ///
+    /// Callers that shouldn't hold the API during the fetch can run the three steps
+    /// themselves: `start_git_pull`, `GitRemote::fetch`, then `git_merge_fetched`.
+    pub fn git_pull(&mut self) -> Result<PullOutcome> {
+        self.start_git_pull()?.fetch()?;
+        self.git_merge_fetched()
+    }
+
+    /// The remote to fetch from ahead of `git_merge_fetched`; fails while a merge is
+    /// unfinished.
+    pub fn start_git_pull(&self) -> Result<GitRemote> {
+        self.ensure_writable()?;
+        if self.repository()?.state() != RepositoryState::Clean {
+            return Err(Error::Git(
+                "Resolve the conflicts from the last pull first".to_string(),
+            ));
+        }
+        self.git_remote()
+    }
+
+    /// Commits local changes, including any saved during the fetch, then merges what
+    /// `GitRemote::fetch` brought in, fast-forwarding when it can. The index is
+    /// rescanned when notes changed.
+    pub fn git_merge_fetched(&mut self) -> Result<PullOutcome> {
+        let repo = self.start_git_pull()?.repository()?;
+        self.git_commit(None)?;
+        let branch = branch_name(&repo)?;
+
+        let fetched = match repo.find_reference("FETCH_HEAD") {
+            Ok(fetched) => repo.reference_to_annotated_commit(&fetched)?,
+            // The remote doesn't have the branch yet
+            Err(_) => return Ok(PullOutcome::UpToDate),
+        };
+        let (analysis, _) = repo.merge_analysis(&[&fetched])?;
+
+        let outcome = if analysis.is_up_to_date() {
+            return Ok(PullOutcome::UpToDate);
+        } else if analysis.is_unborn() || analysis.is_fast_forward() {
+            let branch_ref = format!("refs/heads/{}", branch);
+            repo.reference(&branch_ref, fetched.id(), true, "pull: fast-forward")?;
+            repo.set_head(&branch_ref)?;
+            repo.checkout_head(Some(CheckoutBuilder::new().force()))?;
+            PullOutcome::FastForward
+        } else {
+            repo.merge(
+                &[&fetched],
+                None,
+                Some(
+                    CheckoutBuilder::new()
+                        .allow_conflicts(true)
+                        .conflict_style_merge(true),
+                ),
+            )?;
+            let conflicts = conflicted_files(&repo)?;
+            if conflicts.is_empty() {
+                self.finish_merge(&repo, fetched.id())?;
+                PullOutcome::Merged
+            } else {
+                PullOutcome::Conflicts(self.notes_of(conflicts))
+            }
+        };
+        self.rescan()?;
+        Ok(outcome)
+    }
+
+    /// Commits a merge whose conflicts are all resolved.
+    fn finish_merge(&self, repo: &Repository, theirs: Oid) -> Result<()> {
+        let mut index = repo.index()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let ours =
+            head_commit(repo)?.ok_or_else(|| Error::Git("Nothing to merge into".to_string()))?;
+        let theirs = repo.find_commit(theirs)?;
+        let signature = signature(repo)?;
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &format!("Merge {}", self.config().git.remote),
+            &tree,
+            &[&ours, &theirs],
+        )?;
+        repo.cleanup_state()?;
+        Ok(())
+    }
+
+    /// Marks the conflicts in `path`'s files as resolved with what is on disk now; the
+    /// conflict markers must be gone. Returns true when this finished the merge.
+    pub fn git_resolve(&mut self, path: &str) -> Result<bool> {
+        self.ensure_writable()?;
+        let repo = self.repository()?;
+        let note_files = self.note_files();
+        let files: Vec<String> = conflicted_files(&repo)?
+            .into_iter()
+            .filter(|file| note_of(&note_files, file).as_deref() == Some(path))
+            .collect();
+        if files.is_empty() {
+            return Err(Error::InvalidInput(format!("{} has no conflicts", path)));
+        }
+
+        let mut index = repo.index()?;
+        for file in &files {
+            let on_disk = self.notes_root().join(file);
+            if !on_disk.exists() {
+                // Deleted on one side and kept deleted
+                index.remove_path(std::path::Path::new(file))?;
+                continue;
+            }
+            let content = fs::read(&on_disk)?;
+            if String::from_utf8_lossy(&content)
+                .lines()
+                .any(|line| line.starts_with(CONFLICT_MARKER))
+            {
+                return Err(Error::InvalidInput(format!(
+                    "{} still has conflict markers",
+                    path
+                )));
+            }
+            index.add_path(std::path::Path::new(file))?;
+        }
+        index.write()?;
+        self.sync_note(path)?;
+
+        if index.has_conflicts() {
+            return Ok(false);
+        }
+        let theirs = repo.refname_to_id("MERGE_HEAD")?;
+        self.finish_merge(&repo, theirs)?;
+        Ok(true)
+    }
+}
+
+/// Spawns a thread that queues `BackgroundTask::GitCommit` every few seconds; the
+/// task itself waits for saving to pause (see `NotesApi::git_auto_commit`).
+///
+/// The thread exits once the queue is dropped.
+pub fn spawn_git_scheduler(queue: Weak<WriteQueue>, clock: Arc<dyn Clock>) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        loop {
+            clock.sleep(COMMIT_CHECK_INTERVAL);
+            let Some(queue) = queue.upgrade() else {
+                break;
+            };
+            queue.enqueue(BackgroundTask::GitCommit);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_auto_commit_waits_for_saving_to_pause() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        let clock = Arc::new(ManualClock::default());
+        api.set_clock(clock.clone());
+        api.git_init().unwrap();
+
+        api.create_note("diary").unwrap();
+        api.save_note("diary", "# Monday").unwrap();
+        assert_eq!(api.git_status().unwrap().changed, vec!["diary"]);
+        assert_eq!(api.git_auto_commit().unwrap(), None);
+
+        clock.advance(Duration::from_millis(api.config().git.commit_delay_ms));
+        assert!(api.git_auto_commit().unwrap().is_some());
+        assert!(api.git_status().unwrap().changed.is_empty());
+
+        let history = api.git_history("diary").unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].summary, "Update diary");
+
+        // The index stays out of the repository
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        let tree = repo.head().unwrap().peel_to_tree().unwrap();
+        assert!(tree.get_name(".gitignore").is_some());
+        assert!(tree.get_name(".notes.db").is_none());
+    }
+
+    #[test]
+    fn test_pull_conflict_is_resolved_and_pushed() {
+        let remote = TempDir::new().unwrap();
+        Repository::init_bare(remote.path()).unwrap();
+        let dir_a = TempDir::new().unwrap();
+        let mut a = NotesApi::new(dir_a.path()).unwrap();
+        a.git_init().unwrap();
+        Repository::open(dir_a.path())
+            .unwrap()
+            .remote("origin", remote.path().to_str().unwrap())
+            .unwrap();
+        a.create_note("shared").unwrap();
+        a.save_note("shared", "one\n").unwrap();
+        a.git_commit(None).unwrap();
+        a.git_push().unwrap();
+        assert_eq!(a.git_status().unwrap().ahead, 0);
+
+        // A second device starts from a clone
+        let dir_b = TempDir::new().unwrap();
+        Repository::clone(remote.path().to_str().unwrap(), dir_b.path()).unwrap();
+        let mut b = NotesApi::new(dir_b.path()).unwrap();
+        b.rescan().unwrap();
+        b.git_init().unwrap();
+        assert_eq!(b.get_note("shared").unwrap().content, "one\n");
+        assert_eq!(b.git_pull().unwrap(), PullOutcome::UpToDate);
+
+        a.save_note("shared", "two\n").unwrap();
+        a.git_commit(None).unwrap();
+        a.git_push().unwrap();
+        b.save_note("shared", "three\n").unwrap();
+
+        assert_eq!(
+            b.git_pull().unwrap(),
+            PullOutcome::Conflicts(vec!["shared".to_string()])
+        );
+        assert_eq!(b.git_status().unwrap().conflicts, vec!["shared"]);
+        assert!(matches!(b.git_pull(), Err(Error::Git(_))));
+        assert!(matches!(
+            b.git_resolve("shared"),
+            Err(Error::InvalidInput(_))
+        ));
+
+        b.save_note("shared", "two and three\n").unwrap();
+        assert!(b.git_resolve("shared").unwrap());
+        assert!(b.git_status().unwrap().conflicts.is_empty());
+        b.git_push().unwrap();
+
+        assert_eq!(a.git_pull().unwrap(), PullOutcome::FastForward);
+        assert_eq!(a.get_note("shared").unwrap().content, "two and three\n");
+    }
+
+    #[test]
+    fn test_save_between_fetch_and_merge_is_kept() {
+        let remote = TempDir::new().unwrap();
+        Repository::init_bare(remote.path()).unwrap();
+        let dir_a = TempDir::new().unwrap();
+        let mut a = NotesApi::new(dir_a.path()).unwrap();
+        a.git_init().unwrap();
+        Repository::open(dir_a.path())
+            .unwrap()
+            .remote("origin", remote.path().to_str().unwrap())
+            .unwrap();
+        a.create_note("shared").unwrap();
+        a.git_commit(None).unwrap();
+        a.git_push().unwrap();
+
+        let dir_b = TempDir::new().unwrap();
+        Repository::clone(remote.path().to_str().unwrap(), dir_b.path()).unwrap();
+        let mut b = NotesApi::new(dir_b.path()).unwrap();
+        b.rescan().unwrap();
+        b.git_init().unwrap();
+
+        a.save_note("shared", "from a\n").unwrap();
+        a.git_commit(None).unwrap();
+        a.git_push().unwrap();
+
+        b.start_git_pull().unwrap().fetch().unwrap();
+        b.create_note("local").unwrap();
+        b.save_note("local", "from b\n").unwrap();
+        // A write left half-done by a crash stays out of the commit
+        fs::write(dir_b.path().join("local/._index.md.4242.tmp"), "partial").unwrap();
+        assert_eq!(b.git_merge_fetched().unwrap(), PullOutcome::Merged);
+
+        assert_eq!(b.get_note("shared").unwrap().content, "from a\n");
+        assert_eq!(b.get_note("local").unwrap().content, "from b\n");
+        assert!(b.git_status().unwrap().changed.is_empty());
+        let repo = Repository::open(dir_b.path()).unwrap();
+        let tree = repo.head().unwrap().peel_to_tree().unwrap();
+        let local = tree.get_path(std::path::Path::new("local")).unwrap();
+        let local = repo.find_tree(local.id()).unwrap();
+        assert!(local.get_name("_index.md").is_some());
+        assert_eq!(local.len(), 1);
+    }
+}
@@ -0,0 +1,88 @@
+//! Vault health check: a read-only pass over every note that reports problems
+//! worth the user's attention without changing anything.
+
+use std::collections::BTreeMap;
+
+use crate::notes::{NotesApi, Result};
+use crate::schema::{SchemaWarning, validate_content};
+
+#[derive(Debug, Clone, Default)]
+pub struct HealthReport {
+    pub notes_checked: usize,
+    /// Schema violations keyed by note path; conforming notes are left out
+    pub schema_warnings: BTreeMap<String, Vec<SchemaWarning>>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.schema_warnings.is_empty()
+    }
+}
+
+impl NotesApi {
+    /// Checks every non-archived note and collects what's wrong with the vault.
+    pub fn health_check(&self) -> Result<HealthReport> {
+        self.ensure_available()?;
+        let notes = self.get_all_notes()?;
+
+        let mut report = HealthReport {
+            notes_checked: notes.len(),
+            ..Default::default()
+        };
+        if self.config.schemas.is_empty() {
+            return Ok(report);
+        }
+
+        for note in notes {
+            // Notes deleted on disk since the last sync are the watcher's concern
+            let Ok(content) = self.read_content(&note.path) else {
+                continue;
+            };
+            let warnings = validate_content(&self.config.schemas, &note.path, &content);
+            if !warnings.is_empty() {
+                report.schema_warnings.insert(note.path, warnings);
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{NoteSchema, SchemaField};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_health_check_reports_schema_warnings() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        assert!(api.health_check().unwrap().is_healthy());
+
+        let mut config = api.config().clone();
+        config.schemas.push(NoteSchema {
+            name: "Person".to_string(),
+            parent: Some("people".to_string()),
+            fields: vec![SchemaField {
+                key: "email".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        api.set_config(config).unwrap();
+
+        api.create_note("people").unwrap();
+        api.create_note("people/ann").unwrap();
+        api.create_note("people/bob").unwrap();
+        api.save_note("people/bob", "---\nemail: bob@example.com\n---\n")
+            .unwrap();
+
+        let report = api.health_check().unwrap();
+        assert_eq!(report.notes_checked, 3);
+        assert!(!report.is_healthy());
+        assert_eq!(
+            report.schema_warnings.keys().collect::<Vec<_>>(),
+            vec!["people/ann"]
+        );
+    }
+}
@@ -0,0 +1,584 @@
+//! HTML to Markdown conversion for pasting rich text (e.g. from a browser).
+//!
+//! This is a forgiving converter rather than a spec-compliant HTML parser: unknown
+//! tags are unwrapped, mismatched closing tags are tolerated, and only the structure
+//! that matters for notes (headings, emphasis, links, lists, quotes, code) is kept.
+
+/// Elements that never have children or a closing tag.
+const VOID_ELEMENTS: [&str; 8] = ["br", "hr", "img", "meta", "link", "input", "col", "wbr"];
+
+/// Elements whose contents are dropped entirely.
+const SKIPPED_ELEMENTS: [&str; 6] = ["script", "style", "head", "title", "noscript", "template"];
+
+#[derive(Debug)]
+enum Node {
+    Element {
+        tag: String,
+        attrs: Vec<(String, String)>,
+        children: Vec<Node>,
+    },
+    Text(String),
+}
+
+impl Node {
+    fn attr(&self, name: &str) -> Option<&str> {
+        match self {
+            Node::Element { attrs, .. } => attrs
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.as_str()),
+            Node::Text(_) => None,
+        }
+    }
+}
+
+/// An element still being parsed: tag, attributes, and the children seen so far.
+type OpenElement = (String, Vec<(String, String)>, Vec<Node>);
+
+/// Converts an HTML fragment to Markdown.
+pub fn convert_html_to_markdown(html: &str) -> String {
+    let nodes = parse(html);
+    let rendered = render_nodes(&nodes);
+    normalize_blocks(&rendered)
+}
+
+// Parsing
+
+fn parse(html: &str) -> Vec<Node> {
+    // Stack of open elements: (tag, attrs, children)
+    let mut stack: Vec<OpenElement> = vec![(String::new(), Vec::new(), Vec::new())];
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map(|i| &after[i + 3..]).unwrap_or("");
+            continue;
+        }
+
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map(|i| &rest[i + 1..]).unwrap_or("");
+            continue;
+        }
+
+        if let Some(after) = rest.strip_prefix("</") {
+            let end = after.find('>').unwrap_or(after.len());
+            let tag = after[..end].trim().to_lowercase();
+            rest = after.get(end + 1..).unwrap_or("");
+
+            // Close up to the matching element, ignoring stray closing tags
+            if let Some(pos) = stack.iter().rposition(|(t, _, _)| *t == tag)
+                && pos > 0
+            {
+                while stack.len() > pos {
+                    close_top(&mut stack);
+                }
+            }
+            continue;
+        }
+
+        if rest.starts_with('<') && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let end = find_tag_end(rest);
+            let inner = &rest[1..end];
+            rest = rest.get(end + 1..).unwrap_or("");
+
+            let self_closing = inner.ends_with('/');
+            let inner = inner.trim_end_matches('/');
+            let (tag, attrs) = parse_tag(inner);
+
+            if SKIPPED_ELEMENTS.contains(&tag.as_str()) {
+                let closing = format!("</{}", tag);
+                rest = match find_ignore_ascii_case(rest, &closing) {
+                    Some(i) => rest[i..]
+                        .find('>')
+                        .map(|j| &rest[i + j + 1..])
+                        .unwrap_or(""),
+                    None => "",
+                };
+                continue;
+            }
+
+            if self_closing || VOID_ELEMENTS.contains(&tag.as_str()) {
+                let node = Node::Element {
+                    tag,
+                    attrs,
+                    children: Vec::new(),
+                };
+                stack.last_mut().unwrap().2.push(node);
+            } else {
+                stack.push((tag, attrs, Vec::new()));
+            }
+            continue;
+        }
+
+        // Text runs to the next tag; it starts with at least one (maybe multibyte) char
+        let first = rest.chars().next().map_or(1, char::len_utf8);
+        let end = rest[first..]
+            .find('<')
+            .map(|i| i + first)
+            .unwrap_or(rest.len());
+        let text = decode_entities(&rest[..end]);
+        stack.last_mut().unwrap().2.push(Node::Text(text));
+        rest = &rest[end..];
+    }
+
+    while stack.len() > 1 {
+        close_top(&mut stack);
+    }
+
+    stack
+        .pop()
+        .map(|(_, _, children)| children)
+        .unwrap_or_default()
+}
+
+fn close_top(stack: &mut Vec<OpenElement>) {
+    let (tag, attrs, children) = stack.pop().unwrap();
+    stack.last_mut().unwrap().2.push(Node::Element {
+        tag,
+        attrs,
+        children,
+    });
+}
+
+/// Finds ASCII `needle` in `haystack` ignoring ASCII case, as a byte offset into
+/// `haystack` itself (lowercasing first can change the length of non-ASCII text).
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Finds the `>` closing a start tag, skipping over quoted attribute values.
+fn find_tag_end(s: &str) -> usize {
+    let mut quote: Option<char> = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '>') => return i,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+fn parse_tag(inner: &str) -> (String, Vec<(String, String)>) {
+    let name_end = inner
+        .find(|c: char| c.is_whitespace())
+        .unwrap_or(inner.len());
+    let tag = inner[..name_end].to_lowercase();
+    let mut attrs = Vec::new();
+
+    let mut rest = inner[name_end..].trim_start();
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_lowercase();
+        rest = rest[key_end..].trim_start();
+
+        let value = if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            if let Some(q) = after.chars().next().filter(|c| *c == '"' || *c == '\'') {
+                let body = &after[1..];
+                let end = body.find(q).unwrap_or(body.len());
+                rest = body.get(end + 1..).unwrap_or("");
+                body[..end].to_string()
+            } else {
+                let end = after
+                    .find(|c: char| c.is_whitespace())
+                    .unwrap_or(after.len());
+                rest = &after[end..];
+                after[..end].to_string()
+            }
+        } else {
+            String::new()
+        };
+
+        if !key.is_empty() {
+            attrs.push((key, decode_entities(&value)));
+        }
+        rest = rest.trim_start();
+    }
+
+    (tag, attrs)
+}
+
+pub(crate) fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        let decoded = rest.find(';').filter(|end| *end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                    u32::from_str_radix(&entity[2..], 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                }
+                _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+                _ => None,
+            };
+            c.map(|c| (c, end))
+        });
+
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+// Rendering
+
+fn render_nodes(nodes: &[Node]) -> String {
+    nodes.iter().map(render_node).collect()
+}
+
+fn render_node(node: &Node) -> String {
+    let (tag, children) = match node {
+        Node::Text(text) => return collapse_whitespace(text),
+        Node::Element { tag, children, .. } => (tag.as_str(), children),
+    };
+
+    match tag {
+        "br" => "\n".to_string(),
+        "hr" => "\n\n---\n\n".to_string(),
+        "p" | "div" | "section" | "article" | "header" | "footer" | "main" | "figure" | "table" => {
+            block(&render_nodes(children))
+        }
+        "tr" => {
+            let cells: Vec<String> = children
+                .iter()
+                .filter(|c| matches!(c, Node::Element { tag, .. } if tag == "td" || tag == "th"))
+                .map(|c| inline(&render_node(c)))
+                .collect();
+            format!("\n{}\n", cells.join(" | "))
+        }
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = tag[1..].parse::<usize>().unwrap_or(1);
+            block(&format!(
+                "{} {}",
+                "#".repeat(level),
+                inline(&render_nodes(children))
+            ))
+        }
+        "strong" | "b" => wrap_inline(&render_nodes(children), "**"),
+        "em" | "i" => wrap_inline(&render_nodes(children), "*"),
+        "del" | "s" | "strike" => wrap_inline(&render_nodes(children), "~~"),
+        "code" => {
+            let text = text_content(children);
+            if text.is_empty() {
+                String::new()
+            } else if text.contains('`') {
+                format!("`` {} ``", text)
+            } else {
+                format!("`{}`", text)
+            }
+        }
+        "a" => {
+            let text = inline(&render_nodes(children));
+            match node.attr("href") {
+                Some(href) if !href.is_empty() && !href.starts_with("javascript:") => {
+                    if text.is_empty() {
+                        format!("<{}>", href)
+                    } else {
+                        format!("[{}]({})", text, href)
+                    }
+                }
+                _ => text,
+            }
+        }
+        "img" => match node.attr("src") {
+            Some(src) if !src.is_empty() => {
+                format!("![{}]({})", node.attr("alt").unwrap_or(""), src)
+            }
+            _ => String::new(),
+        },
+        "pre" => render_code_block(children),
+        "blockquote" => {
+            let inner = normalize_blocks(&render_nodes(children));
+            let quoted: Vec<String> = inner
+                .lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        ">".to_string()
+                    } else {
+                        format!("> {}", line)
+                    }
+                })
+                .collect();
+            block(&quoted.join("\n"))
+        }
+        "ul" | "ol" => render_list(tag == "ol", node, children),
+        _ => render_nodes(children),
+    }
+}
+
+fn render_list(ordered: bool, node: &Node, children: &[Node]) -> String {
+    let start: usize = node.attr("start").and_then(|s| s.parse().ok()).unwrap_or(1);
+    let mut lines = Vec::new();
+    let mut index = start;
+
+    for child in children {
+        let Node::Element {
+            tag,
+            children: item_children,
+            ..
+        } = child
+        else {
+            continue;
+        };
+        if tag != "li" {
+            continue;
+        }
+
+        let marker = if ordered {
+            format!("{}. ", index)
+        } else {
+            "- ".to_string()
+        };
+        index += 1;
+
+        // Items are rendered tight: paragraph breaks inside an item become line breaks
+        let body = normalize_blocks(&render_nodes(item_children)).replace("\n\n", "\n");
+        let indent = " ".repeat(marker.len());
+        for (i, line) in body.lines().enumerate() {
+            if i == 0 {
+                lines.push(format!("{}{}", marker, line));
+            } else if line.is_empty() {
+                lines.push(String::new());
+            } else {
+                lines.push(format!("{}{}", indent, line));
+            }
+        }
+        if body.is_empty() {
+            lines.push(marker.trim_end().to_string());
+        }
+    }
+
+    block(&lines.join("\n"))
+}
+
+fn render_code_block(children: &[Node]) -> String {
+    let language = children
+        .iter()
+        .find_map(|c| match c {
+            Node::Element { tag, .. } if tag == "code" => c.attr("class"),
+            _ => None,
+        })
+        .and_then(|class| {
+            class.split_whitespace().find_map(|c| {
+                c.strip_prefix("language-")
+                    .or_else(|| c.strip_prefix("lang-"))
+            })
+        })
+        .unwrap_or("");
+
+    let text = text_content(children);
+    let text = text.trim_matches('\n');
+    let fence = if text.contains("```") { "````" } else { "```" };
+    block(&format!("{}{}\n{}\n{}", fence, language, text, fence))
+}
+
+/// Raw text of a subtree, preserving whitespace (used for code).
+fn text_content(nodes: &[Node]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Element { tag, .. } if tag == "br" => out.push('\n'),
+            Node::Element { children, .. } => out.push_str(&text_content(children)),
+        }
+    }
+    out
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_space {
+                out.push(' ');
+            }
+            last_space = true;
+        } else {
+            out.push(c);
+            last_space = false;
+        }
+    }
+    out
+}
+
+fn block(content: &str) -> String {
+    format!("\n\n{}\n\n", content.trim())
+}
+
+fn inline(content: &str) -> String {
+    collapse_whitespace(content.trim()).trim().to_string()
+}
+
+/// Wraps inline content in a marker, keeping surrounding spaces outside the markers.
+fn wrap_inline(content: &str, marker: &str) -> String {
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        return content.to_string();
+    }
+    let leading = if content.starts_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let trailing = if content.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    format!("{}{}{}{}{}", leading, marker, trimmed, marker, trailing)
+}
+
+/// Trims lines and collapses runs of blank lines, leaving code blocks untouched.
+fn normalize_blocks(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_fence = false;
+
+    for line in text.lines() {
+        let is_fence = line.trim_start().starts_with("```");
+        if in_fence {
+            lines.push(line.to_string());
+            if is_fence {
+                in_fence = false;
+            }
+            continue;
+        }
+        if is_fence {
+            in_fence = true;
+        }
+
+        let line = line.trim_end();
+        let line = if line.starts_with(' ') && !starts_like_list_item(line) {
+            line.trim_start()
+        } else {
+            line
+        };
+
+        if line.is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
+
+/// Whether an indented line belongs to a nested list (and must keep its indentation).
+fn starts_like_list_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("- ")
+        || trimmed.starts_with('>')
+        || trimmed
+            .split_once(". ")
+            .is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        || line.len() - trimmed.len() >= 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paragraphs_and_inline_formatting() {
+        let html =
+            "<p>Hello <strong>bold</strong> and <em>italic</em> text</p><p>Second &amp; last</p>";
+        assert_eq!(
+            convert_html_to_markdown(html),
+            "Hello **bold** and *italic* text\n\nSecond & last"
+        );
+    }
+
+    #[test]
+    fn test_headings_and_links() {
+        let html = r#"<h2>Title</h2><p>See <a href="https://example.com">the site</a>.</p>"#;
+        assert_eq!(
+            convert_html_to_markdown(html),
+            "## Title\n\nSee [the site](https://example.com)."
+        );
+    }
+
+    #[test]
+    fn test_nested_lists() {
+        let html = "<ul><li>One</li><li>Two<ol><li>A</li><li>B</li></ol></li></ul>";
+        assert_eq!(
+            convert_html_to_markdown(html),
+            "- One\n- Two\n  1. A\n  2. B"
+        );
+    }
+
+    #[test]
+    fn test_code_blocks_preserve_whitespace() {
+        let html = "<pre><code class=\"language-rust\">fn main() {\n    println!(\"&lt;hi&gt;\");\n}</code></pre><p>Use <code>cargo run</code></p>";
+        assert_eq!(
+            convert_html_to_markdown(html),
+            "```rust\nfn main() {\n    println!(\"<hi>\");\n}\n```\n\nUse `cargo run`"
+        );
+    }
+
+    #[test]
+    fn test_blockquote_and_skipped_elements() {
+        let html = "<style>p { color: red }</style><blockquote><p>Quoted</p></blockquote><script>alert(1)</script>";
+        assert_eq!(convert_html_to_markdown(html), "> Quoted");
+    }
+
+    #[test]
+    fn test_tolerates_broken_markup() {
+        let html = "<div><p>Unclosed <b>bold</div></span><br>after";
+        assert_eq!(convert_html_to_markdown(html), "Unclosed **bold**\n\nafter");
+    }
+
+    #[test]
+    fn test_non_ascii_text() {
+        assert_eq!(
+            convert_html_to_markdown("<p>über</p><p><b>日本</b>語 &amp; é</p>"),
+            "über\n\n**日本**語 & é"
+        );
+        // "İ" grows when lowercased, which must not shift where the skipped element ends
+        assert_eq!(
+            convert_html_to_markdown("<STYLE>İİİ</Style><p>après</p>"),
+            "après"
+        );
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a&nbsp;b &#65;&#x42; &bogus; &"),
+            "a b AB &bogus; &"
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::path::Path;
+use std::time::Duration;
+
+use crate::dates::{Date, format_utc_timestamp};
+use crate::frontmatter::Frontmatter;
+use crate::notes::{NotesApi, Result};
+
+/// Frontmatter keys that turn a note into an all-day calendar event, in priority order.
+pub const DATE_KEYS: [&str; 2] = ["date", "due"];
+
+/// Length of the event created for a reminder
+const REMINDER_DURATION: Duration = Duration::from_secs(15 * 60);
+
+fn in_scope(scope: &str, path: &str) -> bool {
+    scope.is_empty() || path == scope || path.starts_with(&format!("{}/", scope))
+}
+
+/// Escapes text for an iCalendar TEXT value (RFC 5545 §3.3.11).
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn note_title(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+impl NotesApi {
+    /// Renders reminders and dated notes under `scope` as an iCalendar feed.
+    ///
+    /// `scope` is a note path; an empty string exports the whole vault. Reminders that
+    /// haven't fired yet become 15 minute events, and non-archived notes with a `date:`
+    /// or `due:` frontmatter property (`YYYY-MM-DD`) become all-day events.
+    pub fn export_ics(&self, scope: &str) -> Result<String> {
+        let stamp = format_utc_timestamp(self.clock.now());
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//Zinnia//Notes//EN".to_string(),
+            "CALSCALE:GREGORIAN".to_string(),
+        ];
+
+        for reminder in self.list_upcoming_reminders()? {
+            if !in_scope(scope, &reminder.path) {
+                continue;
+            }
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:reminder-{}@zinnia", reminder.id));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            lines.push(format!(
+                "DTSTART:{}",
+                format_utc_timestamp(reminder.remind_at)
+            ));
+            lines.push(format!(
+                "DTEND:{}",
+                format_utc_timestamp(reminder.remind_at + REMINDER_DURATION)
+            ));
+            lines.push(format!("SUMMARY:{}", escape_text(&reminder.message)));
+            lines.push(format!("DESCRIPTION:{}", escape_text(&reminder.path)));
+            lines.push("END:VEVENT".to_string());
+        }
+
+        for note in self.get_all_notes()? {
+            if !in_scope(scope, &note.path) {
+                continue;
+            }
+            let Ok(content) = self.read_content(&note.path) else {
+                continue;
+            };
+            let (frontmatter, _) = Frontmatter::parse(&content);
+            let Some(date) = DATE_KEYS
+                .iter()
+                .filter_map(|key| frontmatter.get_text(key))
+                .find_map(Date::parse)
+            else {
+                continue;
+            };
+
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:note-{}@zinnia", note.id));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            lines.push(format!("DTSTART;VALUE=DATE:{}", date.to_compact()));
+            lines.push(format!(
+                "DTEND;VALUE=DATE:{}",
+                date.add_days(1).to_compact()
+            ));
+            lines.push(format!("SUMMARY:{}", escape_text(note_title(&note.path))));
+            lines.push(format!("DESCRIPTION:{}", escape_text(&note.path)));
+            lines.push("END:VEVENT".to_string());
+        }
+
+        lines.push("END:VCALENDAR".to_string());
+
+        let mut out = lines.join("\r\n");
+        out.push_str("\r\n");
+        Ok(out)
+    }
+
+    /// Writes the iCalendar feed for `scope` to `dest`, so calendar apps can subscribe to it.
+    pub fn write_ics<P: AsRef<Path>>(&self, scope: &str, dest: P) -> Result<()> {
+        let ics = self.export_ics(scope)?;
+        std::fs::write(dest, ics)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_ics_includes_reminders_and_dated_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.set_clock(std::sync::Arc::new(crate::ManualClock::new(
+            UNIX_EPOCH + Duration::from_secs(1_600_000_000),
+        )));
+
+        api.create_note("work").unwrap();
+        api.create_note("work/launch").unwrap();
+        api.save_note("work/launch", "---\ndue: 2024-05-17\n---\nShip it")
+            .unwrap();
+        api.set_reminder(
+            "work",
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            "Call, then; email",
+        )
+        .unwrap();
+
+        let ics = api.export_ics("").unwrap();
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("SUMMARY:Call\\, then\\; email\r\n"));
+        assert!(ics.contains("DTSTART:20231114T221320Z\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20240517\r\n"));
+        assert!(ics.contains("DTEND;VALUE=DATE:20240518\r\n"));
+        assert!(ics.contains("SUMMARY:launch\r\n"));
+        assert!(ics.contains("DTSTAMP:20200913T122640Z\r\n"));
+    }
+
+    #[test]
+    fn test_export_ics_respects_scope() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("work").unwrap();
+        api.save_note("work", "---\ndate: 2024-01-01\n---\n")
+            .unwrap();
+        api.create_note("home").unwrap();
+        api.save_note("home", "---\ndate: 2024-01-02\n---\n")
+            .unwrap();
+
+        let ics = api.export_ics("home").unwrap();
+        assert!(ics.contains("DTSTART;VALUE=DATE:20240102"));
+        assert!(!ics.contains("DTSTART;VALUE=DATE:20240101"));
+    }
+
+    #[test]
+    fn test_write_ics() {
+        let temp_dir = TempDir::new().unwrap();
+        let api = NotesApi::new(temp_dir.path()).unwrap();
+        let dest = temp_dir.path().join("feed.ics");
+
+        api.write_ics("", &dest).unwrap();
+        assert!(
+            std::fs::read_to_string(dest)
+                .unwrap()
+                .contains("BEGIN:VCALENDAR")
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Hot-folder ingestion: text files dropped into a configured folder become notes.
+//!
+//! A second watcher observes the ingest folder and, once it has been quiet for a
+//! moment (so files still being copied aren't read half-written), queues a
+//! `BackgroundTask::Ingest`. The queue worker then turns every `.md` and `.txt`
+//! file in the folder into a note under the configured parent and removes the source.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::clock::Clock;
+use crate::notes::{NotesApi, Result};
+use crate::watcher::debounce;
+use crate::write_queue::{BackgroundTask, WriteQueue};
+
+/// Extensions picked up from the ingest folder.
+pub const INGEST_EXTENSIONS: [&str; 2] = ["md", "txt"];
+
+/// How long the ingest folder must be quiet before its files are ingested
+const INGEST_SETTLE: Duration = Duration::from_millis(750);
+
+fn is_ingestible(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| INGEST_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Derives a note name from a file name; `/` can't appear in a note name.
+fn title_from_file(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().replace('/', "-"))
+        .unwrap_or_default();
+    let stem = stem.trim();
+    if stem.is_empty() {
+        "untitled".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+/// Normalizes text from other tools: no byte-order mark, `\n` line endings.
+fn normalize_text(raw: &str) -> String {
+    raw.strip_prefix('\u{feff}')
+        .unwrap_or(raw)
+        .replace("\r\n", "\n")
+}
+
+impl NotesApi {
+    /// Returns the configured ingest folder, if any.
+    pub fn ingest_dir(&self) -> Option<PathBuf> {
+        self.config.ingest.dir.as_ref().map(PathBuf::from)
+    }
+
+    /// Turns a text file into a note under `parent` and deletes the file.
+    ///
+    /// The note is named after the file (without extension); a number is appended if
+    /// that name is taken. Missing parent notes are created. Returns the new note's path.
+    pub fn ingest_file(&mut self, file: &Path, parent: &str) -> Result<String> {
+        self.ensure_writable()?;
+
+        let content = normalize_text(&std::fs::read_to_string(file)?);
+        let path = self.create_unique_child(parent, &title_from_file(file))?;
+        self.save_note(&path, &content)?;
+        std::fs::remove_file(file)?;
+        Ok(path)
+    }
+
+    /// Creates an empty note at `path` along with any missing ancestors; notes that
+    /// already exist are left alone. Returns the normalized path.
+    pub(crate) fn create_missing(&mut self, path: &str) -> Result<String> {
+        let mut ancestor = String::new();
+        for segment in path.split('/').filter(|s| !s.is_empty()) {
+            if !ancestor.is_empty() {
+                ancestor.push('/');
+            }
+            ancestor.push_str(segment);
+            if !self.note_exists(&ancestor)? {
+                self.create_note(&ancestor)?;
+            }
+        }
+        Ok(ancestor)
+    }
+
+    /// Creates an empty note named `title` under `parent`, appending a number if
+    /// the name is taken. Missing parent notes are created. Returns the new path.
+    pub(crate) fn create_unique_child(&mut self, parent: &str, title: &str) -> Result<String> {
+        let ancestor = self.create_missing(parent)?;
+
+        let prefix = if ancestor.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", ancestor)
+        };
+        let mut path = format!("{}{}", prefix, title);
+        let mut n = 2;
+        while self.note_exists(&path)? {
+            path = format!("{}{} {}", prefix, title, n);
+            n += 1;
+        }
+
+        self.create_note(&path)?;
+        Ok(path)
+    }
+
+    /// Ingests every `.md` and `.txt` file in the ingest folder.
+    ///
+    /// Returns the paths of the notes created. Files that fail are left in place and
+    /// reported on stderr so one bad file doesn't block the rest.
+    pub fn ingest_pending(&mut self) -> Result<Vec<String>> {
+        let Some(dir) = self.ingest_dir() else {
+            return Ok(Vec::new());
+        };
+        let parent = self.config.ingest.parent.clone();
+
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| is_ingestible(path))
+            .collect();
+        files.sort();
+
+        let mut created = Vec::new();
+        for file in files {
+            match self.ingest_file(&file, &parent) {
+                Ok(path) => created.push(path),
+                Err(e) => eprintln!("Failed to ingest {}: {:?}", file.display(), e),
+            }
+        }
+        Ok(created)
+    }
+}
+
+/// Watches `ingest_dir` and queues `BackgroundTask::Ingest` once it settles.
+///
+/// The watcher must be kept alive; dropping it also stops the debounce thread.
+pub fn setup_ingest_watcher(
+    ingest_dir: &Path,
+    queue: Arc<WriteQueue>,
+    clock: Arc<dyn Clock>,
+) -> notify::Result<RecommendedWatcher> {
+    let (tx, rx) = mpsc::channel::<()>();
+
+    let mut watcher = RecommendedWatcher::new(
+        move |result: std::result::Result<Event, notify::Error>| match result {
+            Ok(event) if event.paths.iter().any(|p| is_ingestible(p)) => {
+                let _ = tx.send(());
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Ingest watcher error: {:?}", e),
+        },
+        Config::default(),
+    )?;
+    watcher.watch(ingest_dir, RecursiveMode::NonRecursive)?;
+
+    std::thread::spawn(move || {
+        // Each burst of events ends with one ingest after INGEST_SETTLE of quiet
+        debounce(
+            rx,
+            clock.as_ref(),
+            || INGEST_SETTLE,
+            || queue.enqueue(BackgroundTask::Ingest),
+        );
+    });
+
+    Ok(watcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::VaultConfig;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let ingest_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        let mut config = VaultConfig::default();
+        config.ingest.dir = Some(ingest_dir.path().to_string_lossy().into_owned());
+        config.ingest.parent = "inbox/dropped".to_string();
+        api.set_config(config).unwrap();
+        (temp_dir, ingest_dir, api)
+    }
+
+    #[test]
+    fn test_ingest_pending() {
+        let (_temp_dir, ingest_dir, mut api) = setup();
+        std::fs::write(ingest_dir.path().join("Meeting.md"), "# Agenda\r\n").unwrap();
+        std::fs::write(ingest_dir.path().join("list.TXT"), "\u{feff}milk").unwrap();
+        std::fs::write(ingest_dir.path().join("photo.png"), [0u8; 4]).unwrap();
+
+        let created = api.ingest_pending().unwrap();
+
+        assert_eq!(created, vec!["inbox/dropped/Meeting", "inbox/dropped/list"]);
+        assert_eq!(
+            api.get_note("inbox/dropped/Meeting").unwrap().content,
+            "# Agenda\n"
+        );
+        assert_eq!(api.get_note("inbox/dropped/list").unwrap().content, "milk");
+        assert!(api.note_exists("inbox").unwrap());
+
+        // Sources are removed; other files are left alone
+        assert!(!ingest_dir.path().join("Meeting.md").exists());
+        assert!(ingest_dir.path().join("photo.png").exists());
+    }
+
+    #[test]
+    fn test_ingest_avoids_existing_names() {
+        let (_temp_dir, ingest_dir, mut api) = setup();
+        let file = ingest_dir.path().join("todo.md");
+
+        std::fs::write(&file, "first").unwrap();
+        assert_eq!(api.ingest_file(&file, "").unwrap(), "todo");
+        std::fs::write(&file, "second").unwrap();
+        assert_eq!(api.ingest_file(&file, "").unwrap(), "todo 2");
+        assert_eq!(api.get_note("todo 2").unwrap().content, "second");
+    }
+
+    #[test]
+    fn test_ingest_without_folder_is_noop() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        assert!(api.ingest_pending().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,246 @@
+//! Write-ahead journal for note saves.
+//!
+//! Before a save touches `_index.md`, the note's path, content hash, and full content
+//! are appended to `.notes.journal` and flushed to disk; a commit record follows once
+//! the note file itself is durable. A save interrupted by a crash or power loss leaves
+//! a begin record without a commit, and the next launch writes that content again.
+//!
+//! Entries carry the whole content rather than a delta: an interrupted write can leave
+//! the file torn, and then there is no intact base a delta could be applied to.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::filesystem::NoteStore;
+use crate::notes::{NotesApi, Result, compute_hash};
+
+/// Journal file in the vault root (hidden, so it's never indexed or exported).
+pub const JOURNAL_FILE_NAME: &str = ".notes.journal";
+
+/// Once committed entries take up this much space the journal is emptied.
+const JOURNAL_COMPACT_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum Entry {
+    Begin {
+        path: String,
+        hash: String,
+        content: String,
+    },
+    Commit {
+        path: String,
+        hash: String,
+    },
+}
+
+fn journal_path(root: &Path) -> PathBuf {
+    root.join(JOURNAL_FILE_NAME)
+}
+
+fn append(root: &Path, entry: &Entry) -> io::Result<File> {
+    let mut line = serde_json::to_string(entry).map_err(io::Error::other)?;
+    line.push('\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(journal_path(root))?;
+    file.write_all(line.as_bytes())?;
+    file.sync_data()?;
+    Ok(file)
+}
+
+/// Returns begin entries without a matching commit, oldest first.
+///
+/// A torn final line (the crash hit mid-append) doesn't parse and is skipped: its save
+/// never started writing the note.
+fn incomplete_entries(root: &Path) -> io::Result<Vec<(String, String, String)>> {
+    let file = match File::open(journal_path(root)) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut pending: Vec<(String, String, String)> = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let Ok(entry) = serde_json::from_str::<Entry>(&line?) else {
+            continue;
+        };
+        match entry {
+            Entry::Begin {
+                path,
+                hash,
+                content,
+            } => pending.push((path, hash, content)),
+            Entry::Commit { path, hash } => {
+                pending.retain(|(p, h, _)| !(*p == path && *h == hash));
+            }
+        }
+    }
+    Ok(pending)
+}
+
+/// Rewrites notes whose journaled save didn't complete, then empties the journal.
+///
+/// Returns the paths that had to be written again. Notes already holding the journaled
+/// content are left alone.
+pub(crate) fn replay(fs: &dyn NoteStore) -> io::Result<Vec<String>> {
+    let root = fs.root_path();
+    let pending = incomplete_entries(root)?;
+
+    let mut replayed = Vec::new();
+    // Only the last save of each note matters
+    for (index, (path, hash, content)) in pending.iter().enumerate() {
+        if pending[index + 1..].iter().any(|(p, _, _)| p == path) {
+            continue;
+        }
+        let on_disk = fs.read_note(path).ok();
+        if on_disk.as_deref().map(compute_hash).as_ref() == Some(hash) {
+            continue;
+        }
+        fs.write_note_durable(path, content)?;
+        replayed.push(path.clone());
+    }
+
+    if journal_path(root).exists() {
+        File::create(journal_path(root))?.sync_data()?;
+    }
+    Ok(replayed)
+}
+
+impl NotesApi {
+    /// Writes a note through the journal: the content is on disk in the journal before
+    /// the note file is touched, and the save is marked complete once the note is.
+    /// Stores that aren't persistent have nothing to recover and skip the journal.
+    pub(crate) fn journaled_write(&self, path: &str, content: &str) -> Result<()> {
+        if !self.fs.is_persistent() {
+            return Ok(self.fs.write_note_durable(path, content)?);
+        }
+        let root = self.fs.root_path();
+        let hash = compute_hash(content);
+
+        append(
+            root,
+            &Entry::Begin {
+                path: path.to_string(),
+                hash: hash.clone(),
+                content: content.to_string(),
+            },
+        )?;
+        self.fs.write_note_durable(path, content)?;
+        let journal = append(
+            root,
+            &Entry::Commit {
+                path: path.to_string(),
+                hash,
+            },
+        )?;
+
+        // Saves run one at a time, so after a commit nothing in the journal is pending
+        if journal.metadata()?.len() > JOURNAL_COMPACT_BYTES {
+            journal.set_len(0)?;
+        }
+        Ok(())
+    }
+
+    /// Empties the journal, e.g. so text that was just encrypted doesn't stay behind in
+    /// earlier begin records. Only call this right after a committed write.
+    pub(crate) fn clear_journal(&self) -> Result<()> {
+        let path = journal_path(self.fs.root_path());
+        if self.fs.is_persistent() && path.exists() {
+            File::create(path)?.sync_data()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::NoteFilesystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_saves_are_committed() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.save_note("a", "one").unwrap();
+        api.save_note("a", "two").unwrap();
+
+        assert!(incomplete_entries(temp_dir.path()).unwrap().is_empty());
+        assert_eq!(
+            std::fs::read_to_string(journal_path(temp_dir.path()))
+                .unwrap()
+                .lines()
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_interrupted_save_is_replayed() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.create_note("b").unwrap();
+        api.save_note("b", "intact").unwrap();
+        drop(api);
+
+        // A save of `a` crashed after journaling, leaving a torn file, and a second
+        // journal append was cut off mid-line
+        let begin = Entry::Begin {
+            path: "a".to_string(),
+            hash: compute_hash("saved text"),
+            content: "saved text".to_string(),
+        };
+        append(temp_dir.path(), &begin).unwrap();
+        std::fs::write(temp_dir.path().join("a/_index.md"), "sav").unwrap();
+        let mut journal = OpenOptions::new()
+            .append(true)
+            .open(journal_path(temp_dir.path()))
+            .unwrap();
+        journal.write_all(b"{\"op\":\"begin\",\"pa").unwrap();
+
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        assert_eq!(api.startup_report().journal_replayed, vec!["a"]);
+        assert_eq!(api.get_note("a").unwrap().content, "saved text");
+        assert_eq!(api.get_note("b").unwrap().content, "intact");
+        assert!(incomplete_entries(temp_dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_completed_write_without_commit_is_not_rewritten() {
+        let temp_dir = TempDir::new().unwrap();
+        let fs = NoteFilesystem::new(temp_dir.path()).unwrap();
+        fs.write_note("a", "done").unwrap();
+        let begin = Entry::Begin {
+            path: "a".to_string(),
+            hash: compute_hash("done"),
+            content: "done".to_string(),
+        };
+        append(temp_dir.path(), &begin).unwrap();
+
+        assert!(replay(&fs).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_only_vault_keeps_journal() {
+        let temp_dir = TempDir::new().unwrap();
+        let fs = NoteFilesystem::new(temp_dir.path()).unwrap();
+        fs.write_note("a", "old").unwrap();
+        let begin = Entry::Begin {
+            path: "a".to_string(),
+            hash: compute_hash("new"),
+            content: "new".to_string(),
+        };
+        append(temp_dir.path(), &begin).unwrap();
+
+        let api = NotesApi::new_read_only(temp_dir.path()).unwrap();
+        assert!(api.startup_report().journal_replayed.is_empty());
+        assert_eq!(incomplete_entries(temp_dir.path()).unwrap().len(), 1);
+    }
+}
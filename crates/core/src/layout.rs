@@ -0,0 +1,309 @@
+//! How notes map to files on disk.
+//!
+//! Notes are stored as `<path>/_index.md` by default. A vault meant to be browsed on
+//! GitHub can use `README.md` (or `index.md`) instead; the name is kept in the vault
+//! config as `note_file` and checked when the vault is opened.
+//!
+//! The `flat` layout keeps notes without children as plain files, the way Obsidian
+//! does, and gives a note a folder only once it has children:
+//!
+//! ```text
+//! projects/_index.md        a note with children
+//! projects/rust.md          a note without
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::notes::{Error, NotesApi, Result};
+
+/// File name notes are stored under unless the vault config says otherwise.
+pub const DEFAULT_NOTE_FILE: &str = "_index.md";
+
+/// File name of a note's canvas, which lives next to the note file.
+pub const CANVAS_FILE: &str = "_canvas.json";
+
+/// How a vault lays its notes out on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultLayout {
+    /// Every note is a folder holding its note file
+    #[default]
+    Folders,
+    /// Notes without children are `<name>.md` files; a note is moved into a folder
+    /// when it gets its first child and back out when the last one goes
+    Flat,
+}
+
+/// The names a vault's notes are stored under, for code that maps files to notes
+/// without going through the store (the watcher, storage report, and archives).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteFiles {
+    pub note_file: String,
+    pub layout: VaultLayout,
+}
+
+impl Default for NoteFiles {
+    fn default() -> Self {
+        Self {
+            note_file: DEFAULT_NOTE_FILE.to_string(),
+            layout: VaultLayout::default(),
+        }
+    }
+}
+
+impl NoteFiles {
+    /// True if a file called `name` is part of a note rather than an attachment.
+    pub fn is_note_file(&self, name: &str) -> bool {
+        name == self.note_file || name == CANVAS_FILE || self.leaf_stem(name).is_some()
+    }
+
+    /// Returns the note the file at `relative` (to the vault root, `/`-separated)
+    /// belongs to, if it is a note or canvas file.
+    pub fn note_of_file(&self, relative: &str) -> Option<String> {
+        let (dir, name) = relative.rsplit_once('/').unwrap_or(("", relative));
+        if name == self.note_file || name == CANVAS_FILE {
+            return Some(dir.to_string());
+        }
+        let stem = self.leaf_stem(name)?;
+        Some(if dir.is_empty() {
+            stem.to_string()
+        } else {
+            format!("{}/{}", dir, stem)
+        })
+    }
+
+    /// Name of the note a flat-layout file called `name` holds, if it holds one.
+    fn leaf_stem<'a>(&self, name: &'a str) -> Option<&'a str> {
+        if self.layout != VaultLayout::Flat || name == self.note_file {
+            return None;
+        }
+        name.strip_suffix(".md")
+            .filter(|stem| !stem.is_empty() && !stem.starts_with('.'))
+    }
+}
+
+/// Checks that `name` can hold notes: a plain, visible Markdown file name that
+/// doesn't collide with the canvas file.
+pub fn validate_note_file(name: &str) -> Result<()> {
+    let invalid = |reason: &str| Err(Error::InvalidInput(format!("{}: {}", reason, name)));
+    if name.contains(['/', '\\']) {
+        return invalid("Note file name must not contain a path separator");
+    }
+    if name.starts_with('.') {
+        return invalid("Note file name must not be hidden");
+    }
+    if !name.ends_with(".md") || name.len() == ".md".len() {
+        return invalid("Note file name must be a Markdown file");
+    }
+    Ok(())
+}
+
+impl NotesApi {
+    /// File name each note is stored under (e.g. `_index.md` or `README.md`).
+    pub fn note_file(&self) -> &str {
+        &self.config().note_file
+    }
+
+    /// Returns the vault's note file name and layout.
+    pub fn note_files(&self) -> NoteFiles {
+        NoteFiles {
+            note_file: self.config().note_file.clone(),
+            layout: self.config().layout,
+        }
+    }
+
+    /// Switches the vault to storing notes as `name`, renaming every existing note
+    /// file (including those in the trash) and saving the choice in the vault config.
+    ///
+    /// Fails without renaming anything if a note folder already has a file called
+    /// `name`; if a rename or saving the config fails partway, the files already
+    /// renamed get their old name back. Returns how many files were renamed. A running
+    /// `setup_queued_watcher` needs the new `note_files`.
+    pub fn set_note_file(&mut self, name: &str) -> Result<usize> {
+        self.ensure_writable()?;
+        validate_note_file(name)?;
+
+        let previous = self.config.note_file.clone();
+        let renamed = self.fs.rename_note_files(name)?;
+        self.config.note_file = name.to_string();
+        if self.fs.is_persistent()
+            && let Err(e) = self.config.save(self.fs.root_path())
+        {
+            let _ = self.fs.rename_note_files(&previous);
+            self.config.note_file = previous;
+            return Err(e.into());
+        }
+        Ok(renamed)
+    }
+
+    /// Switches the vault to `layout`, moving notes into or out of their folders to
+    /// match, and saves the choice in the vault config.
+    ///
+    /// Going flat turns every note whose folder holds nothing but its note file into
+    /// a `<name>.md` file; going back gives each such file a folder again. Returns
+    /// how many notes were moved. Paths don't change, so the index stays valid. If a
+    /// move fails, the notes already moved go back and the layout is unchanged.
+    /// A running `setup_queued_watcher` needs the new `note_files`.
+    pub fn set_layout(&mut self, layout: VaultLayout) -> Result<usize> {
+        self.ensure_writable()?;
+
+        let moved = self.fs.convert_layout(layout)?;
+        self.config.layout = layout;
+        if self.fs.is_persistent() {
+            self.config.save(self.fs.root_path())?;
+        }
+        Ok(moved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_validate_note_file() {
+        assert!(validate_note_file("README.md").is_ok());
+        assert!(validate_note_file("index.md").is_ok());
+        for name in ["", ".md", ".hidden.md", "notes/README.md", "README.txt"] {
+            assert!(
+                matches!(validate_note_file(name), Err(Error::InvalidInput(_))),
+                "{:?} should be rejected",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_set_note_file_renames_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let mut api = NotesApi::new(root).unwrap();
+        api.create_note("projects").unwrap();
+        api.save_note("projects", "# Projects").unwrap();
+        api.create_note("projects/rust").unwrap();
+
+        assert_eq!(api.set_note_file("README.md").unwrap(), 2);
+        assert!(root.join("projects/README.md").exists());
+        assert!(!root.join("projects/_index.md").exists());
+        assert_eq!(api.get_note("projects").unwrap().content, "# Projects");
+
+        // The plain config setter can't strand the renamed files
+        let mut config = api.config().clone();
+        config.note_file = "index.md".to_string();
+        assert!(matches!(
+            api.set_config(config),
+            Err(Error::InvalidInput(_))
+        ));
+
+        // New notes and a reopened vault use the new name
+        api.create_note("inbox").unwrap();
+        assert!(root.join("inbox/README.md").exists());
+        drop(api);
+        let mut api = NotesApi::new(root).unwrap();
+        api.rescan().unwrap();
+        assert!(api.note_exists("projects/rust").unwrap());
+    }
+
+    #[test]
+    fn test_note_of_file() {
+        let flat = NoteFiles {
+            note_file: "README.md".to_string(),
+            layout: VaultLayout::Flat,
+        };
+        assert_eq!(flat.note_of_file("README.md").as_deref(), Some(""));
+        assert_eq!(flat.note_of_file("a/README.md").as_deref(), Some("a"));
+        assert_eq!(flat.note_of_file("a/_canvas.json").as_deref(), Some("a"));
+        assert_eq!(flat.note_of_file("a/b.md").as_deref(), Some("a/b"));
+        assert_eq!(flat.note_of_file("a/logo.png"), None);
+
+        let folders = NoteFiles::default();
+        assert_eq!(folders.note_of_file("a/_index.md").as_deref(), Some("a"));
+        assert_eq!(folders.note_of_file("a/b.md"), None);
+        assert!(!folders.is_note_file("b.md"));
+    }
+
+    #[test]
+    fn test_set_layout_moves_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let mut api = NotesApi::new(root).unwrap();
+        api.create_note("projects").unwrap();
+        api.create_note("projects/rust").unwrap();
+        api.save_note("projects/rust", "# Rust").unwrap();
+        api.create_note("inbox").unwrap();
+
+        assert_eq!(api.set_layout(VaultLayout::Flat).unwrap(), 2);
+        assert!(root.join("projects/rust.md").is_file());
+        assert!(root.join("inbox.md").is_file());
+        assert!(root.join("projects/_index.md").is_file());
+        assert_eq!(api.get_note("projects/rust").unwrap().content, "# Rust");
+
+        // Trashing the last child moves the parent out; restoring moves it back in
+        api.delete_note("projects/rust").unwrap();
+        assert!(root.join("projects.md").is_file());
+        api.restore_from_trash("projects/rust").unwrap();
+        assert!(root.join("projects/rust.md").is_file());
+        assert!(root.join("projects/_index.md").is_file());
+        assert_eq!(api.get_note("projects/rust").unwrap().content, "# Rust");
+
+        let mut config = api.config().clone();
+        config.layout = VaultLayout::Folders;
+        assert!(matches!(
+            api.set_config(config),
+            Err(Error::InvalidInput(_))
+        ));
+
+        // A reopened vault keeps the layout
+        drop(api);
+        let mut api = NotesApi::new(root).unwrap();
+        api.rescan().unwrap();
+        assert!(api.note_exists("inbox").unwrap());
+
+        assert_eq!(api.set_layout(VaultLayout::Folders).unwrap(), 2);
+        assert!(root.join("projects/rust/_index.md").is_file());
+        assert!(root.join("inbox/_index.md").is_file());
+        api.rescan().unwrap();
+        assert_eq!(api.get_note("projects/rust").unwrap().content, "# Rust");
+    }
+
+    #[test]
+    fn test_failed_layout_change_moves_notes_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let mut api = NotesApi::new(root).unwrap();
+        for path in ["alpha", "middle", "zulu"] {
+            api.create_note(path).unwrap();
+        }
+        api.set_layout(VaultLayout::Flat).unwrap();
+        // A stray file where one note's folder would go
+        std::fs::write(root.join("middle"), "").unwrap();
+
+        assert!(api.set_layout(VaultLayout::Folders).is_err());
+        for path in ["alpha", "middle", "zulu"] {
+            assert!(
+                root.join(format!("{}.md", path)).is_file(),
+                "{} moved",
+                path
+            );
+            assert!(!root.join(path).join("_index.md").exists());
+        }
+        assert_eq!(api.config().layout, VaultLayout::Flat);
+        api.create_note("beta").unwrap();
+        assert!(root.join("beta.md").is_file());
+    }
+
+    #[test]
+    fn test_open_rejects_invalid_note_file() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join(crate::config::CONFIG_FILE_NAME),
+            r#"{ "note_file": "../escape.md" }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            NotesApi::new(temp_dir.path()),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
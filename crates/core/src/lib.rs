@@ -1,10 +1,159 @@
+pub mod auto_title;
+pub mod backlinks;
+pub mod batch;
+pub mod board;
+pub mod canvas;
+pub mod changes;
+pub mod checkpoints;
+pub mod clock;
+pub mod compile;
+pub mod config;
+pub mod content_cache;
+pub mod covers;
+pub mod csv_import;
+pub mod daily;
+pub mod dates;
 pub mod default_paths;
+pub mod diagnostics;
+pub mod duplicate;
+pub mod encryption;
+pub mod events;
+pub mod export;
+pub mod favorites;
 pub mod filesystem;
+pub mod formatting;
+pub mod frontmatter;
+pub mod git;
+pub mod health;
+pub mod html;
+pub mod ics;
+pub mod ingest;
+pub mod journal;
+pub mod layout;
+pub mod link_check;
+pub mod link_suggestions;
+pub mod maintenance;
+pub mod memory_store;
+pub mod mentions;
+pub mod merge;
+pub mod moc;
+pub mod moves;
+pub mod note_links;
 pub mod notes;
+pub mod offline;
+pub mod ordering;
+pub mod permissions;
+pub mod privacy;
+pub mod properties;
+pub mod ranking;
+pub mod recent;
+pub mod recovery;
+pub mod reminders;
+pub mod remote_sync;
+pub mod reparent;
+pub mod revision_diff;
+pub mod schema;
+pub mod search_history;
+pub mod search_matches;
+pub mod snippets;
+pub mod stats;
+pub mod status;
+pub mod storage;
+pub mod tags;
+pub mod templates;
+pub mod trash;
+pub mod tree_diff;
+pub mod tree_state;
+pub mod typography;
+pub mod unlinked_mentions;
+pub mod url_titles;
+pub mod vault_archive;
+pub mod vault_import;
+pub mod versioning;
+pub mod views;
 pub mod watcher;
+#[cfg(feature = "webdav")]
+pub mod webdav;
+pub mod write_queue;
 
 // Re-export main types for convenience
-pub use default_paths::get_default_notes_path;
-pub use filesystem::{FSNoteMetadata, NoteFilesystem};
-pub use notes::{Error, Note, NoteMetadata, NotesApi, RankingMode, Result};
-pub use watcher::{WatcherEvent, setup_watcher};
+pub use backlinks::extract_links;
+pub use batch::{
+    BulkPropertyEdit, NoteOp, NoteSelection, PropertyChange, ReplaceChange, ReplaceEdit,
+};
+pub use board::{Board, BoardColumn};
+pub use canvas::Canvas;
+pub use changes::{Change, ChangeKind, Changes, Revision};
+pub use checkpoints::Checkpoint;
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use compile::{CompileOptions, CompiledDocument};
+pub use config::{
+    AppearanceConfig, CacheConfig, CheckpointConfig, DailyNotesConfig, DiagnosticsConfig,
+    EditorConfig, ExportConfig, FieldKind, GitConfig, IngestConfig, LinkStyle, NoteSchema,
+    SchemaField, SearchConfig, StartupConfig, TimingConfig, VaultConfig,
+};
+pub use content_cache::CacheStats;
+pub use csv_import::{CsvImport, CsvImportMode, CsvSource};
+pub use daily::DailyNote;
+pub use dates::Date;
+pub use default_paths::{get_default_notes_path, get_offline_queue_dir, get_sync_state_dir};
+pub use diagnostics::{DiagnosticsSummary, install_crash_reporter};
+pub use events::{NoteChange, NotesEvent};
+pub use export::{FormatConverter, FormatRegistry};
+pub use filesystem::{FSNoteMetadata, NoteFilesystem, NoteStore};
+pub use formatting::{FormatAction, TextEdit, apply_format};
+pub use frontmatter::{Frontmatter, PropertyValue};
+pub use git::{GitCommit, GitRemote, GitStatus, PullOutcome, spawn_git_scheduler};
+pub use health::HealthReport;
+pub use html::convert_html_to_markdown;
+pub use ingest::setup_ingest_watcher;
+pub use layout::{CANVAS_FILE, DEFAULT_NOTE_FILE, NoteFiles, VaultLayout, validate_note_file};
+#[cfg(feature = "link-check")]
+pub use link_check::check_external_links;
+pub use link_check::{
+    DeadLink, ExternalLink, LinkCheckOptions, LinkProblem, LinkReport, extract_external_links,
+};
+pub use link_suggestions::LinkSuggestion;
+pub use maintenance::{MaintenanceReport, spawn_maintenance_scheduler};
+pub use memory_store::MemoryNoteStore;
+pub use merge::{HunkChoice, HunkKind, Merge, MergeHunk, merge};
+pub use moc::{MocSource, MocUpdate};
+pub use moves::match_moves;
+pub use note_links::{NoteLink, NotePreview, note_url, parse_note_url, wiki_link};
+pub use notes::{
+    Capabilities, Error, Note, NoteMetadata, NotesApi, RankingMode, Result, SyncOutcome,
+};
+pub use offline::PendingOp;
+pub use permissions::{Access, AccessToken, ScopedNotes};
+pub use recovery::StartupReport;
+pub use reminders::Reminder;
+pub use remote_sync::{
+    LocalChange, RemoteConfig, RemoteEntry, RemoteStore, SyncAction, SyncChange, SyncReport,
+    SyncSide, VaultSync,
+};
+pub use reparent::{MoveCollision, NoteMoved};
+pub use revision_diff::{DiffKind, DiffLine, EditSummary, edit_summary, line_diff};
+pub use schema::{SchemaIssue, SchemaWarning};
+pub use search_history::{HistoryKind, SearchHistoryEntry};
+pub use search_matches::{SearchMatch, SearchResult};
+pub use snippets::Snippet;
+pub use stats::NoteStats;
+pub use status::NoteStatus;
+pub use storage::StorageReport;
+pub use tags::{TagNode, TagRename};
+pub use templates::{TemplateField, TemplateNote, TemplateSchema};
+pub use trash::TrashedNote;
+pub use tree_diff::{TreeChange, TreeDiff, TreeSubscription};
+pub use typography::{TypographyReplacement, smart_typography};
+pub use unlinked_mentions::UnlinkedMention;
+pub use vault_archive::ArchiveSummary;
+pub use vault_import::{VaultImport, VaultImportOptions};
+pub use versioning::{SaveOutcome, SavedNote, VersionedNote};
+pub use views::{ViewData, ViewLayout, ViewRow};
+pub use watcher::{
+    DiskProbe, FsProbe, WatcherConfig, event_tasks, setup_queued_watcher, setup_watcher,
+    spawn_availability_monitor,
+};
+#[cfg(feature = "webdav")]
+pub use webdav::WebDavStore;
+pub use write_queue::{BackgroundTask, QueueDepth, WriteQueue, coalesce};
@@ -0,0 +1,337 @@
+//! Finding dead external links.
+//!
+//! Extracting http(s) links from notes is always available. Checking them makes
+//! network requests, so it is behind the `link-check` feature and only runs when
+//! asked. Links are checked with `HEAD` (falling back to `GET` for servers that
+//! don't support it) on a few threads at once, each request with a timeout.
+
+use std::collections::BTreeMap;
+
+use rusqlite::params;
+
+use crate::notes::{Error, NotesApi, Result};
+use crate::tags::escape_like;
+use crate::unlinked_mentions::excluded_ranges;
+
+/// An external URL and the notes linking to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalLink {
+    pub url: String,
+    pub notes: Vec<String>,
+}
+
+/// Why a link counts as dead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkProblem {
+    /// The server answered 404, 410, or a 5xx status
+    Status(u16),
+    /// No answer: DNS failure, refused connection, timeout, TLS error, ...
+    Unreachable(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadLink {
+    pub url: String,
+    pub notes: Vec<String>,
+    pub problem: LinkProblem,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkReport {
+    /// Distinct URLs checked
+    pub checked: usize,
+    /// Ordered by URL
+    pub dead: Vec<DeadLink>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LinkCheckOptions {
+    /// Requests in flight at once
+    pub concurrency: usize,
+    /// Per request, including redirects
+    pub timeout: std::time::Duration,
+}
+
+impl Default for LinkCheckOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            timeout: std::time::Duration::from_secs(10),
+        }
+    }
+}
+
+/// `url` without trailing punctuation that belongs to the sentence around it.
+fn trim_url(url: &str) -> &str {
+    let mut url = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"', '>']);
+    // A closing parenthesis is part of the URL only if it has an opening one
+    while url.ends_with(')') && url.matches(')').count() > url.matches('(').count() {
+        url = url[..url.len() - 1].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+    }
+    url
+}
+
+/// The distinct http(s) URLs in a note's body, in order of appearance. URLs in
+/// code and the frontmatter are left out.
+pub fn extract_external_links(content: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for range in excluded_ranges(content) {
+        let text = &content[range];
+        let url = if text.starts_with("http://") || text.starts_with("https://") {
+            trim_url(text)
+        } else if let Some(mid) = text.strip_prefix('[').and_then(|t| t.find("](")) {
+            // `[text](url "title")`
+            let target = &text[mid + 3..text.len() - 1];
+            target.split_whitespace().next().unwrap_or_default()
+        } else {
+            continue;
+        };
+        let is_http = url.starts_with("http://") || url.starts_with("https://");
+        if is_http && url.len() > "https://".len() && !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+impl NotesApi {
+    /// Collects the external links in the non-archived note at `root` and the notes
+    /// below it (the whole vault for `""`), ordered by URL.
+    pub fn collect_external_links(&self, root: &str) -> Result<Vec<ExternalLink>> {
+        self.ensure_available()?;
+        if !root.is_empty() && !self.note_exists(root)? {
+            return Err(Error::NotFound(root.to_string()));
+        }
+        let paths: Vec<String> = self
+            .db
+            .prepare(
+                "SELECT path FROM notes
+                 WHERE archived = 0 AND (?1 = '' OR path = ?1 OR path LIKE ?2 ESCAPE '\\')
+                 ORDER BY path ASC",
+            )?
+            .query_map(params![root, format!("{}/%", escape_like(root))], |row| {
+                row.get(0)
+            })?
+            .collect::<std::result::Result<_, _>>()?;
+
+        let mut links: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for path in paths {
+            // Notes deleted on disk since the last sync are the watcher's concern
+            let Ok(content) = self.read_content(&path) else {
+                continue;
+            };
+            for url in extract_external_links(&content) {
+                links.entry(url).or_default().push(path.clone());
+            }
+        }
+        Ok(links
+            .into_iter()
+            .map(|(url, notes)| ExternalLink { url, notes })
+            .collect())
+    }
+}
+
+/// Requests every link and reports the dead ones. Statuses other than 404, 410,
+/// and 5xx (e.g. 403 from sites that block bots) count as alive.
+#[cfg(feature = "link-check")]
+pub fn check_external_links(
+    links: Vec<ExternalLink>,
+    options: &LinkCheckOptions,
+) -> std::io::Result<LinkReport> {
+    use std::sync::Mutex;
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(options.timeout)
+        .user_agent("Zinnia")
+        .build()
+        .map_err(std::io::Error::other)?;
+
+    let checked = links.len();
+    let queue = Mutex::new(links.into_iter());
+    let dead = Mutex::new(Vec::new());
+    std::thread::scope(|scope| {
+        for _ in 0..options.concurrency.clamp(1, checked.max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let Some(link) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+                    if let Some(problem) = probe(&client, &link.url) {
+                        dead.lock().unwrap().push(DeadLink {
+                            url: link.url,
+                            notes: link.notes,
+                            problem,
+                        });
+                    }
+                }
+            });
+        }
+    });
+
+    let mut dead = dead.into_inner().unwrap();
+    dead.sort_by(|a, b| a.url.cmp(&b.url));
+    Ok(LinkReport { checked, dead })
+}
+
+#[cfg(feature = "link-check")]
+fn probe(client: &reqwest::blocking::Client, url: &str) -> Option<LinkProblem> {
+    use reqwest::StatusCode;
+
+    let response = match client.head(url).send() {
+        Ok(response)
+            if matches!(
+                response.status(),
+                StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+            ) =>
+        {
+            client.get(url).send()
+        }
+        other => other,
+    };
+    match response {
+        Ok(response) => {
+            let status = response.status();
+            let dead = matches!(status, StatusCode::NOT_FOUND | StatusCode::GONE)
+                || status.is_server_error();
+            dead.then(|| LinkProblem::Status(status.as_u16()))
+        }
+        Err(e) => Some(LinkProblem::Unreachable(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_extract_external_links() {
+        let content = "---\nsource: https://frontmatter.example\n---\n\
+            See https://a.example/page, [docs](https://b.example/docs \"Docs\") and \
+            (https://en.wikipedia.org/wiki/Rust_(language)).\n\
+            ![img](https://c.example/x.png) <https://d.example>\n\
+            `https://code.example` [[https://not-a-url]] https://a.example/page\n\
+            ```\nhttps://fenced.example\n```\n";
+        assert_eq!(
+            extract_external_links(content),
+            [
+                "https://a.example/page",
+                "https://b.example/docs",
+                "https://en.wikipedia.org/wiki/Rust_(language)",
+                "https://c.example/x.png",
+                "https://d.example",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_external_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for (path, content) in [
+            ("reading", "https://shared.example"),
+            (
+                "reading/books",
+                "https://shared.example https://books.example",
+            ),
+            ("other", "https://other.example"),
+        ] {
+            api.create_note(path).unwrap();
+            api.save_note(path, content).unwrap();
+        }
+
+        let links = api.collect_external_links("reading").unwrap();
+        assert_eq!(
+            links,
+            [
+                ExternalLink {
+                    url: "https://books.example".to_string(),
+                    notes: vec!["reading/books".to_string()],
+                },
+                ExternalLink {
+                    url: "https://shared.example".to_string(),
+                    notes: vec!["reading".to_string(), "reading/books".to_string()],
+                },
+            ]
+        );
+        assert_eq!(api.collect_external_links("").unwrap().len(), 3);
+        assert!(matches!(
+            api.collect_external_links("missing"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[cfg(feature = "link-check")]
+    #[test]
+    fn test_check_external_links() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        // Answers /ok with 200, /gone with 404, and HEAD /no-head with 405 (GET works)
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8_lossy(&request);
+                let status = match request.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+                    [_, "/ok"] => "200 OK",
+                    ["HEAD", "/no-head"] => "405 Method Not Allowed",
+                    ["GET", "/no-head"] => "200 OK",
+                    _ => "404 Not Found",
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        let link = |url: String| ExternalLink {
+            url,
+            notes: vec!["note".to_string()],
+        };
+        let base = format!("http://127.0.0.1:{}", port);
+        let links = vec![
+            link(format!("{}/ok", base)),
+            link(format!("{}/gone", base)),
+            link(format!("{}/no-head", base)),
+            // Nothing listens on port 1
+            link("http://127.0.0.1:1/".to_string()),
+        ];
+        let options = LinkCheckOptions {
+            concurrency: 2,
+            timeout: std::time::Duration::from_secs(5),
+        };
+
+        let report = check_external_links(links, &options).unwrap();
+        assert_eq!(report.checked, 4);
+        let dead: Vec<(&str, bool)> = report
+            .dead
+            .iter()
+            .map(|d| {
+                (
+                    d.url.as_str(),
+                    matches!(d.problem, LinkProblem::Status(404)),
+                )
+            })
+            .collect();
+        assert_eq!(
+            dead,
+            [
+                ("http://127.0.0.1:1/", false),
+                (format!("{}/gone", base).as_str(), true)
+            ]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Suggestions for `[[` link autocompletion.
+//!
+//! Notes are matched by title, alias, or path. The quality of the match decides
+//! the order, and the `ranking` score adds a little on top, so among similar
+//! matches notes near the open one or opened recently come first.
+
+use rusqlite::params;
+
+use crate::notes::{NotesApi, RankingMode, Result};
+
+/// Most the ranking score can add to a match's quality (which steps by at least
+/// 0.15 between kinds of match), so a clearly better match still wins over a
+/// closer or more recent note.
+const RANKING_SHARE: f64 = 0.2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkSuggestion {
+    pub path: String,
+    /// The `title` property, or the last path segment
+    pub title: String,
+    /// Higher is better; only meaningful relative to other suggestions
+    pub score: f64,
+}
+
+/// How well `query` matches `text`, ignoring case: 1.0 for the whole text, then
+/// prefixes, word starts, substrings, and letters in order. `None` if it doesn't
+/// match at all; an empty query matches everything with 0.0.
+pub fn match_quality(query: &str, text: &str) -> Option<f64> {
+    let query = query.to_lowercase();
+    let text = text.to_lowercase();
+    if query.is_empty() {
+        return Some(0.0);
+    }
+    if text == query {
+        return Some(1.0);
+    }
+    if text.starts_with(&query) {
+        return Some(0.9);
+    }
+    let word_start = text.match_indices(&query).any(|(i, _)| {
+        text[..i]
+            .chars()
+            .next_back()
+            .is_some_and(|c| !c.is_alphanumeric())
+    });
+    if word_start {
+        return Some(0.75);
+    }
+    if text.contains(&query) {
+        return Some(0.6);
+    }
+    let mut letters = text.chars();
+    if query.chars().all(|q| letters.any(|c| c == q)) {
+        return Some(0.3);
+    }
+    None
+}
+
+impl NotesApi {
+    /// Ranks the notes `query` could be meant to link to from the note at
+    /// `context_path`, best first. Archived notes, private notes (unless
+    /// included), and the context note itself are left out.
+    pub fn suggest_links(
+        &self,
+        query: &str,
+        context_path: &str,
+        limit: usize,
+    ) -> Result<Vec<LinkSuggestion>> {
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT path, title,
+                    (SELECT group_concat(value, char(10)) FROM note_properties
+                     WHERE note_id = notes.id AND key = 'aliases')
+             FROM notes
+             WHERE archived = 0 AND path != ?1{}",
+            self.private_filter()
+        ))?;
+        let rows = stmt
+            .query_map(params![context_path], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut matched = Vec::new();
+        for (path, title, aliases) in rows {
+            let title =
+                title.unwrap_or_else(|| path.rsplit('/').next().unwrap_or(&path).to_string());
+            let best = std::iter::once(title.as_str())
+                .chain(aliases.as_deref().unwrap_or_default().lines())
+                .filter_map(|name| match_quality(query, name))
+                // A path match (`work/plan`) counts for a bit less than a name match
+                .chain(match_quality(query, &path).map(|q| q * 0.8))
+                .fold(None, |best: Option<f64>, q| {
+                    Some(best.map_or(q, |b| b.max(q)))
+                });
+            if let Some(quality) = best {
+                matched.push((path, title, quality));
+            }
+        }
+
+        let paths: Vec<&str> = matched.iter().map(|(path, _, _)| path.as_str()).collect();
+        let near = self.near_scores(&paths, RankingMode::Frecency, context_path)?;
+        let search = &self.config().search;
+        let max_near = search.ranking_weight + search.proximity_weight + search.recency_weight;
+        let mut suggestions: Vec<LinkSuggestion> = matched
+            .into_iter()
+            .zip(near)
+            .map(|((path, title, quality), near)| LinkSuggestion {
+                path,
+                title,
+                score: if max_near > 0.0 {
+                    quality + RANKING_SHARE * near / max_near
+                } else {
+                    quality
+                },
+            })
+            .collect();
+        suggestions.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.path.cmp(&b.path)));
+        suggestions.truncate(limit);
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_match_quality() {
+        assert_eq!(match_quality("plan", "Plan"), Some(1.0));
+        assert_eq!(match_quality("pla", "Planning"), Some(0.9));
+        assert_eq!(match_quality("plan", "Q3 plan draft"), Some(0.75));
+        assert_eq!(match_quality("lan", "Planning"), Some(0.6));
+        assert_eq!(match_quality("png", "Planning"), Some(0.3));
+        assert_eq!(match_quality("xyz", "Planning"), None);
+        assert_eq!(match_quality("", "Planning"), Some(0.0));
+    }
+
+    #[test]
+    fn test_suggest_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in [
+            "home",
+            "home/plan",
+            "work",
+            "work/q3",
+            "work/q3/plan",
+            "planets",
+        ] {
+            api.create_note(path).unwrap();
+        }
+        api.save_note("home", "---\naliases: [Apartment]\n---\n")
+            .unwrap();
+        api.save_note("planets", "---\ntitle: Solar system\n---\n")
+            .unwrap();
+
+        let paths = |suggestions: Vec<LinkSuggestion>| -> Vec<String> {
+            suggestions.into_iter().map(|s| s.path).collect()
+        };
+        // Exact title matches first, the nearer one ahead
+        let plan = api.suggest_links("plan", "work/q3", 10).unwrap();
+        assert_eq!(paths(plan.clone())[..2], ["work/q3/plan", "home/plan"]);
+        assert!(plan[0].score > plan[1].score);
+        // "planets" only matches by path now that its title is "Solar system"
+        assert_eq!(plan.last().unwrap().title, "Solar system");
+
+        assert_eq!(paths(api.suggest_links("apart", "", 10).unwrap()), ["home"]);
+        assert_eq!(api.suggest_links("", "work", 3).unwrap().len(), 3);
+        // The context note itself isn't suggested
+        assert!(
+            !paths(api.suggest_links("q3", "work/q3", 10).unwrap())
+                .contains(&"work/q3".to_string())
+        );
+    }
+}
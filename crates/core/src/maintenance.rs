@@ -0,0 +1,257 @@
+//! Index maintenance: compacting the full-text index and reclaiming free pages.
+//!
+//! Every save rewrites a note's FTS row, so a long-lived index accumulates segments
+//! and free pages. Maintenance merges the FTS segments, refreshes query planner
+//! statistics, and vacuums the file. It is skipped while the user is editing, since
+//! VACUUM rewrites the whole database and blocks saves until it finishes; the
+//! scheduler queues a skipped run again once editing has been idle for a while.
+
+use std::sync::{Arc, Weak};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::clock::Clock;
+use crate::notes::{NotesApi, Result};
+use crate::write_queue::{BackgroundTask, WriteQueue};
+
+/// Saves within this window count as active editing
+pub const EDITING_IDLE: Duration = Duration::from_secs(2 * 60);
+
+/// How often `spawn_maintenance_scheduler` queues maintenance
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// What a maintenance run did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    pub size_before: u64,
+    pub size_after: u64,
+    pub duration: Duration,
+}
+
+impl MaintenanceReport {
+    pub fn reclaimed(&self) -> u64 {
+        self.size_before.saturating_sub(self.size_after)
+    }
+}
+
+impl NotesApi {
+    /// Returns true if a note was saved within `EDITING_IDLE`.
+    pub fn is_editing(&self) -> bool {
+        // A clock set backwards counts as still editing
+        self.last_save.is_some_and(|saved| {
+            self.clock
+                .now()
+                .duration_since(saved)
+                .map_or(true, |idle| idle < EDITING_IDLE)
+        })
+    }
+
+    fn database_size(&self) -> Result<u64> {
+        let pages: i64 = self
+            .db
+            .pragma_query_value(None, "page_count", |row| row.get(0))?;
+        let page_size: i64 = self
+            .db
+            .pragma_query_value(None, "page_size", |row| row.get(0))?;
+        Ok((pages * page_size) as u64)
+    }
+
+    /// Optimizes the full-text index, updates planner statistics, and vacuums.
+    ///
+    /// Returns `None` without doing anything while the user is editing (see
+    /// `is_editing`).
+    pub fn run_maintenance(&mut self) -> Result<Option<MaintenanceReport>> {
+        self.ensure_writable()?;
+        if self.is_editing() {
+            return Ok(None);
+        }
+
+        let started = Instant::now();
+        let size_before = self.database_size()?;
+
+        self.db
+            .execute("INSERT INTO notes_fts(notes_fts) VALUES('optimize')", [])?;
+        self.db.execute_batch("ANALYZE; VACUUM;")?;
+        self.last_maintenance = Some(self.clock.now());
+
+        Ok(Some(MaintenanceReport {
+            size_before,
+            size_after: self.database_size()?,
+            duration: started.elapsed(),
+        }))
+    }
+}
+
+/// Spawns a thread that queues `BackgroundTask::Maintenance` every six hours.
+///
+/// A run skipped because the user was editing is queued again every `EDITING_IDLE`
+/// until one goes through. The task counts as heavy work, so a power-aware queue
+/// holds it back on battery. Read-only vaults are left alone. The thread exits once
+/// the queue is dropped.
+pub fn spawn_maintenance_scheduler(
+    queue: Weak<WriteQueue>,
+    clock: Arc<dyn Clock>,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut wait = MAINTENANCE_INTERVAL;
+        loop {
+            clock.sleep(wait);
+            let Some(queue) = queue.upgrade() else {
+                break;
+            };
+            let Ok((read_only, last_run)) =
+                queue.run(|api| Ok((api.is_read_only(), api.last_maintenance)))
+            else {
+                break;
+            };
+            if read_only {
+                wait = MAINTENANCE_INTERVAL;
+                continue;
+            }
+            let since_last_run = last_run.and_then(|ran| clock.now().duration_since(ran).ok());
+            if let Some(since) = since_last_run.filter(|since| *since < MAINTENANCE_INTERVAL) {
+                wait = MAINTENANCE_INTERVAL - since;
+                continue;
+            }
+            // Already queued tasks are deduplicated, so this is safe while one waits
+            queue.enqueue(BackgroundTask::Maintenance);
+            wait = EDITING_IDLE;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::events::NotesEvent;
+    use crate::notes::Error;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_editing_ends_after_idle_time() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        let clock = Arc::new(ManualClock::default());
+        api.set_clock(clock.clone());
+
+        api.create_note("draft").unwrap();
+        api.save_note("draft", "words").unwrap();
+        assert!(api.is_editing());
+
+        clock.advance(EDITING_IDLE - Duration::from_secs(1));
+        assert!(api.is_editing());
+        clock.advance(Duration::from_secs(1));
+        assert!(!api.is_editing());
+        assert!(api.run_maintenance().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_scheduler_queues_maintenance_each_interval() {
+        let temp_dir = TempDir::new().unwrap();
+        let api = NotesApi::new(temp_dir.path()).unwrap();
+        let clock = Arc::new(ManualClock::default());
+        let queue = Arc::new(WriteQueue::new(
+            Arc::new(Mutex::new(api)),
+            None::<fn(NotesEvent)>,
+            clock.clone(),
+        ));
+        // Held back by the gate, so the task stays visible in the queue
+        queue.set_heavy_work_gate(|| false);
+        let scheduler = spawn_maintenance_scheduler(Arc::downgrade(&queue), clock.clone());
+
+        clock.wait_for_sleepers(1);
+        assert_eq!(queue.depth().background, 0);
+        clock.advance(MAINTENANCE_INTERVAL);
+        clock.wait_for_sleepers(1);
+        assert_eq!(queue.depth().background, 1);
+
+        // Dropping the queue stops the scheduler at its next wake-up
+        drop(queue);
+        clock.advance(MAINTENANCE_INTERVAL);
+        scheduler.join().unwrap();
+    }
+
+    #[test]
+    fn test_scheduler_retries_maintenance_skipped_for_editing() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        let clock = Arc::new(ManualClock::default());
+        api.set_clock(clock.clone());
+        api.create_note("draft").unwrap();
+        let queue = Arc::new(WriteQueue::new(
+            Arc::new(Mutex::new(api)),
+            None::<fn(NotesEvent)>,
+            clock.clone(),
+        ));
+        let scheduler = spawn_maintenance_scheduler(Arc::downgrade(&queue), clock.clone());
+
+        // The user saves just before maintenance comes due, so the run is skipped
+        clock.wait_for_sleepers(1);
+        clock.advance(MAINTENANCE_INTERVAL - Duration::from_secs(1));
+        queue.run(|api| api.save_note("draft", "words")).unwrap();
+        clock.advance(Duration::from_secs(1));
+        clock.wait_for_sleepers(1);
+        // Jobs run one at a time, so this answers after the queued task has finished
+        let last_run = |queue: &WriteQueue| {
+            while queue.depth().background > 0 {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            queue.run(|api| Ok(api.last_maintenance)).unwrap()
+        };
+        assert_eq!(last_run(&queue), None);
+
+        // Once editing has gone idle the scheduler queues it again
+        clock.advance(EDITING_IDLE);
+        clock.wait_for_sleepers(1);
+        assert!(last_run(&queue).is_some());
+
+        drop(queue);
+        clock.advance(MAINTENANCE_INTERVAL);
+        scheduler.join().unwrap();
+    }
+
+    #[test]
+    fn test_maintenance_refused_when_read_only() {
+        let temp_dir = TempDir::new().unwrap();
+        NotesApi::new(temp_dir.path()).unwrap();
+        let mut api = NotesApi::new_read_only(temp_dir.path()).unwrap();
+        assert!(matches!(api.run_maintenance(), Err(Error::ReadOnly)));
+    }
+
+    #[test]
+    fn test_maintenance_reclaims_space() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for i in 0..20 {
+            let path = format!("note{}", i);
+            api.create_note(&path).unwrap();
+            api.save_note(&path, &"churn ".repeat(2000)).unwrap();
+        }
+        for i in 0..20 {
+            api.delete_note(&format!("note{}", i)).unwrap();
+        }
+
+        // Saves just happened, so maintenance waits
+        assert!(api.is_editing());
+        assert_eq!(api.run_maintenance().unwrap(), None);
+
+        api.last_save = None;
+        let report = api.run_maintenance().unwrap().unwrap();
+        assert!(report.reclaimed() > 0);
+        assert_eq!(api.search("churn").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_search_works_after_maintenance() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("kept").unwrap();
+        api.save_note("kept", "needle").unwrap();
+        api.last_save = None;
+
+        api.run_maintenance().unwrap().unwrap();
+        assert_eq!(api.search("needle").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,373 @@
+//! In-memory note store with fault injection, for tests.
+//!
+//! `MemoryNoteStore` behaves like the filesystem store (deleting a note removes its
+//! descendants, creating one that exists fails) but keeps everything in a map. Clones
+//! share their contents, so a test can hand one clone to `NotesApi::with_store` and
+//! use another to make "external" edits, fail the next write, slow down reads, or
+//! take the store offline.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use crate::clock::{Clock, SystemClock};
+use crate::filesystem::{FSNoteMetadata, NoteStore};
+
+#[derive(Debug)]
+struct StoredNote {
+    content: String,
+    mtime: SystemTime,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    notes: BTreeMap<String, StoredNote>,
+    canvases: BTreeMap<String, String>,
+    unavailable: bool,
+    fail_next_write: Option<io::ErrorKind>,
+    fail_next_read: Option<io::ErrorKind>,
+    read_delay: Duration,
+}
+
+#[derive(Clone)]
+pub struct MemoryNoteStore {
+    root: PathBuf,
+    state: Arc<Mutex<MemoryState>>,
+    clock: Arc<dyn Clock>,
+}
+
+/// Returns true if `path` is `base` or below it.
+fn is_within(path: &str, base: &str) -> bool {
+    base.is_empty()
+        || path == base
+        || path
+            .strip_prefix(base)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+impl MemoryNoteStore {
+    pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    /// A store that stamps writes with `clock`, so mtimes follow a `ManualClock`.
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            root: PathBuf::from("memory"),
+            state: Arc::default(),
+            clock,
+        }
+    }
+
+    /// Makes the next write (or create, delete, canvas write) fail with `kind`.
+    pub fn fail_next_write(&self, kind: io::ErrorKind) {
+        self.lock().fail_next_write = Some(kind);
+    }
+
+    /// Makes the next read (or scan) fail with `kind`.
+    pub fn fail_next_read(&self, kind: io::ErrorKind) {
+        self.lock().fail_next_read = Some(kind);
+    }
+
+    /// Delays every read by `delay`, to widen race windows.
+    pub fn set_read_delay(&self, delay: Duration) {
+        self.lock().read_delay = delay;
+    }
+
+    /// Simulates the vault going away (`false`) and coming back (`true`).
+    pub fn set_available(&self, available: bool) {
+        self.lock().unavailable = !available;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Locks the state for a read, applying injected delays and failures.
+    fn begin_read(&self) -> io::Result<MutexGuard<'_, MemoryState>> {
+        let delay = self.lock().read_delay;
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+        let mut state = self.lock();
+        if state.unavailable {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        if let Some(kind) = state.fail_next_read.take() {
+            return Err(io::Error::new(kind, "injected read failure"));
+        }
+        Ok(state)
+    }
+
+    /// Locks the state for a write, applying injected failures.
+    fn begin_write(&self) -> io::Result<MutexGuard<'_, MemoryState>> {
+        let mut state = self.lock();
+        if state.unavailable {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        if let Some(kind) = state.fail_next_write.take() {
+            return Err(io::Error::new(kind, "injected write failure"));
+        }
+        Ok(state)
+    }
+
+    fn remove_tree(&self, path: &str) -> io::Result<()> {
+        let mut state = self.begin_write()?;
+        let before = state.notes.len() + state.canvases.len();
+        state.notes.retain(|p, _| !is_within(p, path));
+        state.canvases.retain(|p, _| !is_within(p, path));
+        if state.notes.len() + state.canvases.len() == before {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Note does not exist",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for MemoryNoteStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NoteStore for MemoryNoteStore {
+    fn root_path(&self) -> &Path {
+        &self.root
+    }
+
+    fn is_available(&self) -> bool {
+        !self.lock().unavailable
+    }
+
+    fn is_persistent(&self) -> bool {
+        false
+    }
+
+    fn read_note(&self, path: &str) -> io::Result<String> {
+        self.begin_read()?
+            .notes
+            .get(path)
+            .map(|note| note.content.clone())
+            .ok_or_else(|| io::ErrorKind::NotFound.into())
+    }
+
+    fn write_note(&self, path: &str, content: &str) -> io::Result<()> {
+        let note = StoredNote {
+            content: content.to_string(),
+            mtime: self.clock.now(),
+        };
+        self.begin_write()?.notes.insert(path.to_string(), note);
+        Ok(())
+    }
+
+    fn write_note_durable(&self, path: &str, content: &str) -> io::Result<()> {
+        self.write_note(path, content)
+    }
+
+    fn create_note(&self, path: &str) -> io::Result<()> {
+        let mtime = self.clock.now();
+        let mut state = self.begin_write()?;
+        if state.notes.contains_key(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "Note already exists",
+            ));
+        }
+        state.notes.insert(
+            path.to_string(),
+            StoredNote {
+                content: String::new(),
+                mtime,
+            },
+        );
+        Ok(())
+    }
+
+    fn delete_note(&self, path: &str) -> io::Result<()> {
+        self.remove_tree(path)
+    }
+
+    fn trash_note(&self, path: &str) -> io::Result<()> {
+        self.remove_tree(path)
+    }
+
+    fn read_canvas(&self, path: &str) -> io::Result<String> {
+        self.begin_read()?
+            .canvases
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::ErrorKind::NotFound.into())
+    }
+
+    fn write_canvas(&self, path: &str, data: &str) -> io::Result<()> {
+        self.begin_write()?
+            .canvases
+            .insert(path.to_string(), data.to_string());
+        Ok(())
+    }
+
+    fn canvas_exists(&self, path: &str) -> bool {
+        self.lock().canvases.contains_key(path)
+    }
+
+    fn scan_all(&self) -> io::Result<Vec<FSNoteMetadata>> {
+        Ok(self
+            .begin_read()?
+            .notes
+            .iter()
+            .map(|(path, note)| FSNoteMetadata {
+                path: path.clone(),
+                mtime: note.mtime,
+            })
+            .collect())
+    }
+
+    fn note_mtime(&self, path: &str) -> io::Result<SystemTime> {
+        self.begin_read()?
+            .notes
+            .get(path)
+            .map(|note| note.mtime)
+            .ok_or_else(|| io::ErrorKind::NotFound.into())
+    }
+
+    fn note_stamp(&self, path: &str) -> io::Result<(SystemTime, u64)> {
+        self.begin_read()?
+            .notes
+            .get(path)
+            .map(|note| (note.mtime, note.content.len() as u64))
+            .ok_or_else(|| io::ErrorKind::NotFound.into())
+    }
+
+    fn set_note_mtime(&self, path: &str, mtime: SystemTime) -> io::Result<()> {
+        let mut state = self.begin_write()?;
+        let note = state
+            .notes
+            .get_mut(path)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+        note.mtime = mtime;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::{Error, NotesApi};
+    use crate::versioning::SaveOutcome;
+
+    fn setup() -> (MemoryNoteStore, NotesApi) {
+        let store = MemoryNoteStore::new();
+        let api = NotesApi::with_store(Box::new(store.clone())).unwrap();
+        (store, api)
+    }
+
+    #[test]
+    fn test_delete_removes_descendants_only() {
+        let store = MemoryNoteStore::new();
+        store.write_note("projects", "p").unwrap();
+        store.write_note("projects/rust", "r").unwrap();
+        store.write_note("projectsx", "x").unwrap();
+        store.write_canvas("projects/rust", "{}").unwrap();
+
+        // Sibling paths sharing a prefix aren't part of the subtree
+        let subtree: Vec<_> = store
+            .scan_subtree("projects")
+            .unwrap()
+            .into_iter()
+            .map(|m| m.path)
+            .collect();
+        assert_eq!(subtree, vec!["projects", "projects/rust"]);
+
+        store.delete_note("projects").unwrap();
+        let paths: Vec<_> = store
+            .scan_all()
+            .unwrap()
+            .into_iter()
+            .map(|m| m.path)
+            .collect();
+        assert_eq!(paths, vec!["projectsx"]);
+        assert!(!store.canvas_exists("projects/rust"));
+        assert_eq!(
+            store.delete_note("projects").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn test_api_runs_on_memory_store() {
+        let (store, mut api) = setup();
+        api.create_note("inbox").unwrap();
+        api.save_note("inbox", "remember the milk").unwrap();
+
+        assert_eq!(store.read_note("inbox").unwrap(), "remember the milk");
+        assert_eq!(api.search("milk").unwrap().len(), 1);
+
+        // An external edit is picked up by a sync
+        store.write_note("inbox", "remember the eggs").unwrap();
+        assert!(api.sync_note("inbox").unwrap());
+        assert_eq!(api.search("eggs").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_failed_write_leaves_note_unchanged() {
+        let (store, mut api) = setup();
+        api.create_note("draft").unwrap();
+        api.save_note("draft", "first").unwrap();
+
+        store.fail_next_write(io::ErrorKind::StorageFull);
+        assert!(matches!(
+            api.save_note("draft", "second"),
+            Err(Error::Io(_))
+        ));
+        assert_eq!(api.get_note("draft").unwrap().content, "first");
+
+        // The failure is one-shot
+        api.save_note("draft", "second").unwrap();
+        assert_eq!(store.read_note("draft").unwrap(), "second");
+    }
+
+    #[test]
+    fn test_external_edit_conflicts_with_versioned_save() {
+        let (store, mut api) = setup();
+        api.create_note("shared").unwrap();
+        api.save_note("shared", "base").unwrap();
+        let base = api.get_note_with_version("shared").unwrap().content_hash;
+
+        store.write_note("shared", "edited elsewhere").unwrap();
+        let outcome = api.save_note_versioned("shared", "mine", &base).unwrap();
+        assert!(matches!(
+            outcome,
+            SaveOutcome::Conflict { ref content, .. } if content == "edited elsewhere"
+        ));
+    }
+
+    #[test]
+    fn test_unavailable_store_blocks_writes() {
+        let (store, mut api) = setup();
+        api.create_note("note").unwrap();
+
+        store.set_available(false);
+        assert!(!api.capabilities().available);
+        assert!(matches!(
+            api.save_note("note", "lost"),
+            Err(Error::VaultUnavailable)
+        ));
+
+        store.set_available(true);
+        api.save_note("note", "kept").unwrap();
+    }
+
+    #[test]
+    fn test_failed_read_surfaces_as_error() {
+        let (store, mut api) = setup();
+        api.create_note("note").unwrap();
+
+        store.fail_next_read(io::ErrorKind::PermissionDenied);
+        assert!(api.get_note("note").is_err());
+        assert!(api.get_note("note").is_ok());
+    }
+}
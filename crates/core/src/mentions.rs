@@ -0,0 +1,196 @@
+use std::collections::BTreeSet;
+
+use rusqlite::{Connection, params};
+
+use crate::notes::{NoteMetadata, NotesApi, Result, metadata_from_row};
+
+/// Extracts `@name` mentions from note content, lowercased and deduplicated.
+///
+/// A mention must start at the beginning of a word (so e-mail addresses don't count)
+/// and may contain letters, digits, `-`, `_` and inner dots.
+pub fn extract_mentions(content: &str) -> BTreeSet<String> {
+    let mut mentions = BTreeSet::new();
+    let chars: Vec<char> = content.chars().collect();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let at_word_start = i == 0 || !(chars[i - 1].is_alphanumeric() || chars[i - 1] == '_');
+        if chars[i] == '@' && at_word_start {
+            let start = i + 1;
+            let mut end = start;
+            while end < chars.len()
+                && (chars[end].is_alphanumeric() || matches!(chars[end], '-' | '_' | '.'))
+            {
+                end += 1;
+            }
+            let name: String = chars[start..end].iter().collect();
+            let name = name.trim_end_matches('.');
+            if !name.is_empty() {
+                mentions.insert(name.to_lowercase());
+            }
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+
+    mentions
+}
+
+/// Replaces the indexed mentions for a note.
+pub(crate) fn index_mentions(db: &Connection, note_id: i64, content: &str) -> Result<()> {
+    db.execute("DELETE FROM mentions WHERE note_id = ?1", params![note_id])?;
+    for person in extract_mentions(content) {
+        db.execute(
+            "INSERT OR IGNORE INTO mentions (note_id, person) VALUES (?1, ?2)",
+            params![note_id, person],
+        )?;
+    }
+    Ok(())
+}
+
+fn person_name(person_path: &str) -> String {
+    person_path
+        .rsplit('/')
+        .next()
+        .unwrap_or(person_path)
+        .to_lowercase()
+}
+
+impl NotesApi {
+    /// Returns all non-archived notes that @mention the given person.
+    ///
+    /// `person_path` is the person's note path (e.g. `people/alice`); the mention name
+    /// is its last segment, matched case-insensitively.
+    pub fn get_mentions(&self, person_path: &str) -> Result<Vec<NoteMetadata>> {
+        let mut stmt = self.db.prepare(
+            "SELECT notes.id, notes.path, notes.mtime, notes.archived
+             FROM mentions
+             JOIN notes ON mentions.note_id = notes.id
+             WHERE mentions.person = ?1 AND notes.archived = 0 AND notes.path != ?2
+             ORDER BY notes.mtime DESC, notes.path ASC",
+        )?;
+
+        let notes = stmt
+            .query_map(
+                params![person_name(person_path), person_path],
+                metadata_from_row,
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(notes)
+    }
+
+    /// Returns the names of everyone mentioned in a note.
+    pub fn get_note_mentions(&self, path: &str) -> Result<Vec<String>> {
+        let mut stmt = self.db.prepare(
+            "SELECT mentions.person
+             FROM mentions
+             JOIN notes ON mentions.note_id = notes.id
+             WHERE notes.path = ?1
+             ORDER BY mentions.person ASC",
+        )?;
+
+        let people = stmt
+            .query_map(params![path], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<String>, _>>()?;
+
+        Ok(people)
+    }
+
+    /// Creates person notes for mentions in `path` that don't have one yet.
+    ///
+    /// Only runs when `mentions.auto_create` is enabled in the vault config.
+    /// Returns the paths of the person notes that were created.
+    pub(crate) fn create_mentioned_people(&mut self, path: &str) -> Result<Vec<String>> {
+        if !self.config.mentions.auto_create {
+            return Ok(Vec::new());
+        }
+
+        let people_root = self.config.mentions.people_root.clone();
+        let mut created = Vec::new();
+
+        for person in self.get_note_mentions(path)? {
+            let person_path = if people_root.is_empty() {
+                person
+            } else {
+                format!("{}/{}", people_root, person)
+            };
+            if person_path == path || self.note_exists(&person_path)? {
+                continue;
+            }
+
+            if !people_root.is_empty() && !self.note_exists(&people_root)? {
+                self.create_note(&people_root)?;
+            }
+            self.create_note(&person_path)?;
+            created.push(person_path);
+        }
+
+        Ok(created)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::VaultConfig;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_extract_mentions() {
+        let mentions =
+            extract_mentions("Met @Alice and @bob.smith. Mail me@example.com, @alice again");
+        let mentions: Vec<_> = mentions.into_iter().collect();
+        assert_eq!(mentions, vec!["alice", "bob.smith"]);
+    }
+
+    #[test]
+    fn test_get_mentions() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("people").unwrap();
+        api.create_note("people/alice").unwrap();
+        api.create_note("meeting").unwrap();
+        api.save_note("meeting", "Discussed roadmap with @alice")
+            .unwrap();
+        api.create_note("other").unwrap();
+        api.save_note("other", "Nothing here").unwrap();
+
+        let mentions = api.get_mentions("people/alice").unwrap();
+        assert_eq!(mentions.len(), 1);
+        assert_eq!(mentions[0].path, "meeting");
+        assert_eq!(api.get_note_mentions("meeting").unwrap(), vec!["alice"]);
+
+        api.save_note("meeting", "Rescheduled").unwrap();
+        assert!(api.get_mentions("people/alice").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_auto_create_people_disabled_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("meeting").unwrap();
+        api.save_note("meeting", "Call @carol").unwrap();
+
+        assert!(!api.note_exists("people/carol").unwrap());
+    }
+
+    #[test]
+    fn test_auto_create_people() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        let mut config = VaultConfig::default();
+        config.mentions.auto_create = true;
+        api.set_config(config).unwrap();
+
+        api.create_note("meeting").unwrap();
+        api.save_note("meeting", "Call @carol").unwrap();
+
+        assert!(api.note_exists("people").unwrap());
+        assert!(api.note_exists("people/carol").unwrap());
+        assert_eq!(api.get_mentions("people/carol").unwrap().len(), 1);
+    }
+}
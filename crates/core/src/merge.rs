@@ -0,0 +1,255 @@
+//! Three-way merges for save conflicts.
+//!
+//! When a versioned save is refused (see `versioning`), the editor holds its own
+//! edits ("mine"), the file on disk holds someone else's ("theirs"), and both
+//! started from the content the editor loaded ("base"). Lines changed on one side
+//! only merge on their own; hunks changed differently on both sides are conflicts,
+//! left for the user to settle one by one.
+
+use crate::revision_diff::{DiffKind, line_diff};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkKind {
+    /// Neither side changed these lines
+    Unchanged,
+    /// Only mine changed them
+    Mine,
+    /// Only theirs changed them
+    Theirs,
+    /// Both made the same change
+    Both,
+    /// Both changed them differently
+    Conflict,
+}
+
+/// How to settle a conflicting hunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkChoice {
+    Mine,
+    Theirs,
+    /// Mine, then theirs
+    Both,
+}
+
+/// A run of lines as they are in each version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeHunk {
+    pub kind: HunkKind,
+    pub base: Vec<String>,
+    pub mine: Vec<String>,
+    pub theirs: Vec<String>,
+}
+
+impl MergeHunk {
+    /// The hunk's lines in the merged note; `choice` only matters for conflicts.
+    pub fn lines(&self, choice: HunkChoice) -> Vec<&str> {
+        let lines: Vec<&String> = match (self.kind, choice) {
+            (HunkKind::Unchanged, _) => self.base.iter().collect(),
+            (HunkKind::Mine | HunkKind::Both, _) => self.mine.iter().collect(),
+            (HunkKind::Theirs, _) => self.theirs.iter().collect(),
+            (HunkKind::Conflict, HunkChoice::Mine) => self.mine.iter().collect(),
+            (HunkKind::Conflict, HunkChoice::Theirs) => self.theirs.iter().collect(),
+            (HunkKind::Conflict, HunkChoice::Both) => {
+                self.mine.iter().chain(&self.theirs).collect()
+            }
+        };
+        lines.into_iter().map(String::as_str).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merge {
+    pub hunks: Vec<MergeHunk>,
+    /// Whether the merged note ends with a newline
+    pub trailing_newline: bool,
+}
+
+impl Merge {
+    /// Number of conflicting hunks.
+    pub fn conflicts(&self) -> usize {
+        self.hunks
+            .iter()
+            .filter(|hunk| hunk.kind == HunkKind::Conflict)
+            .count()
+    }
+
+    /// The merged note, settling the n-th conflict with `choices[n]` (mine for
+    /// conflicts without a choice).
+    pub fn resolve(&self, choices: &[HunkChoice]) -> String {
+        let mut choices = choices.iter().copied();
+        let mut lines = Vec::new();
+        for hunk in &self.hunks {
+            let choice = if hunk.kind == HunkKind::Conflict {
+                choices.next().unwrap_or(HunkChoice::Mine)
+            } else {
+                HunkChoice::Mine
+            };
+            lines.extend(hunk.lines(choice));
+        }
+        let mut merged = lines.join("\n");
+        if self.trailing_newline && !merged.is_empty() {
+            merged.push('\n');
+        }
+        merged
+    }
+}
+
+/// For each line of `base`, the index of the line it stays as in `other`.
+fn matched_lines(base: &str, other: &str, len: usize) -> Vec<Option<usize>> {
+    let mut matched = vec![None; len];
+    for line in line_diff(base, other) {
+        if line.kind == DiffKind::Same
+            && let (Some(old), Some(new)) = (line.old_line, line.new_line)
+        {
+            matched[old - 1] = Some(new - 1);
+        }
+    }
+    matched
+}
+
+/// Merges `mine` and `theirs`, both edited from `base`, into hunks.
+///
+/// Lines both sides kept unchanged anchor the merge; between anchors, a change
+/// made on one side only is taken from that side.
+pub fn merge(base: &str, mine: &str, theirs: &str) -> Merge {
+    let base_lines: Vec<&str> = base.lines().collect();
+    let mine_lines: Vec<&str> = mine.lines().collect();
+    let theirs_lines: Vec<&str> = theirs.lines().collect();
+    let in_mine = matched_lines(base, mine, base_lines.len());
+    let in_theirs = matched_lines(base, theirs, base_lines.len());
+
+    let owned = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect();
+    let mut hunks: Vec<MergeHunk> = Vec::new();
+    let (mut b, mut m, mut t) = (0, 0, 0);
+    loop {
+        // The next base line both sides kept
+        let anchor = (b..base_lines.len()).find_map(|i| Some((i, in_mine[i]?, in_theirs[i]?)));
+        let (b_end, m_end, t_end) =
+            anchor.unwrap_or((base_lines.len(), mine_lines.len(), theirs_lines.len()));
+
+        if b_end > b || m_end > m || t_end > t {
+            let base_part = &base_lines[b..b_end];
+            let mine_part = &mine_lines[m..m_end];
+            let theirs_part = &theirs_lines[t..t_end];
+            let kind = if mine_part == base_part {
+                HunkKind::Theirs
+            } else if theirs_part == base_part {
+                HunkKind::Mine
+            } else if mine_part == theirs_part {
+                HunkKind::Both
+            } else {
+                HunkKind::Conflict
+            };
+            hunks.push(MergeHunk {
+                kind,
+                base: owned(base_part),
+                mine: owned(mine_part),
+                theirs: owned(theirs_part),
+            });
+        }
+        let Some((mut i, mut j, mut k)) = anchor else {
+            break;
+        };
+
+        // The anchor and the unchanged lines right after it
+        let start = i;
+        while i < base_lines.len() && in_mine[i] == Some(j) && in_theirs[i] == Some(k) {
+            i += 1;
+            j += 1;
+            k += 1;
+        }
+        let unchanged: Vec<String> = owned(&base_lines[start..i]);
+        match hunks.last_mut() {
+            Some(last) if last.kind == HunkKind::Unchanged => {
+                last.mine.extend(unchanged.iter().cloned());
+                last.theirs.extend(unchanged.iter().cloned());
+                last.base.extend(unchanged);
+            }
+            _ => hunks.push(MergeHunk {
+                kind: HunkKind::Unchanged,
+                base: unchanged.clone(),
+                mine: unchanged.clone(),
+                theirs: unchanged,
+            }),
+        }
+        (b, m, t) = (i, j, k);
+    }
+
+    Merge {
+        hunks,
+        trailing_newline: mine.ends_with('\n') || theirs.ends_with('\n'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_on_one_side_merge_cleanly() {
+        let base = "# Plan\none\ntwo\nthree\n";
+        let mine = "# Plan\nONE\ntwo\nthree\n";
+        let theirs = "# Plan\none\ntwo\nthree\nfour\n";
+
+        let merged = merge(base, mine, theirs);
+        let kinds: Vec<HunkKind> = merged.hunks.iter().map(|hunk| hunk.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                HunkKind::Unchanged,
+                HunkKind::Mine,
+                HunkKind::Unchanged,
+                HunkKind::Theirs
+            ]
+        );
+        assert_eq!(merged.conflicts(), 0);
+        assert_eq!(merged.resolve(&[]), "# Plan\nONE\ntwo\nthree\nfour\n");
+    }
+
+    #[test]
+    fn test_conflicting_changes() {
+        let base = "a\nb\nc\n";
+        let mine = "a\nmine\nc\n";
+        let theirs = "a\ntheirs\nc\n";
+
+        let merged = merge(base, mine, theirs);
+        assert_eq!(merged.conflicts(), 1);
+        assert_eq!(
+            merged.hunks[1],
+            MergeHunk {
+                kind: HunkKind::Conflict,
+                base: vec!["b".to_string()],
+                mine: vec!["mine".to_string()],
+                theirs: vec!["theirs".to_string()],
+            }
+        );
+        assert_eq!(merged.resolve(&[]), "a\nmine\nc\n");
+        assert_eq!(merged.resolve(&[HunkChoice::Theirs]), "a\ntheirs\nc\n");
+        assert_eq!(merged.resolve(&[HunkChoice::Both]), "a\nmine\ntheirs\nc\n");
+    }
+
+    #[test]
+    fn test_same_change_on_both_sides() {
+        let merged = merge("a\nb\n", "a\nB\n", "a\nB\n");
+        assert_eq!(merged.conflicts(), 0);
+        assert_eq!(merged.hunks[1].kind, HunkKind::Both);
+        assert_eq!(merged.resolve(&[]), "a\nB\n");
+    }
+
+    #[test]
+    fn test_conflicts_at_the_edges() {
+        // Both sides wrote into an empty note
+        let merged = merge("", "mine\n", "theirs");
+        assert_eq!(merged.hunks.len(), 1);
+        assert_eq!(
+            merged.resolve(&[HunkChoice::Theirs]),
+            "theirs\n",
+            "one side's trailing newline is kept"
+        );
+
+        // Both removed everything
+        let merged = merge("a\nb\n", "", "");
+        assert_eq!(merged.hunks[0].kind, HunkKind::Both);
+        assert_eq!(merged.resolve(&[]), "");
+    }
+}
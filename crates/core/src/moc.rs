@@ -0,0 +1,263 @@
+//! Generated "map of content" hub notes.
+//!
+//! A map lists links to every note under a subtree (nested like the tree) or
+//! carrying a tag (grouped by the most specific matching tag). It is written
+//! between two markers in the hub note, so the rest of the hub stays hand-written
+//! and regenerating only replaces the list.
+
+use std::collections::BTreeMap;
+
+use rusqlite::params;
+
+use crate::changes::Revision;
+use crate::note_links::wiki_link;
+use crate::notes::{Error, NotesApi, Result};
+use crate::tags::{escape_like, normalize_tag};
+
+const MOC_START: &str = "<!-- moc -->";
+const MOC_END: &str = "<!-- /moc -->";
+
+/// What a map of content lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MocSource {
+    /// Every note below this path
+    Subtree(String),
+    /// Every note tagged with this tag or a tag nested below it
+    Tag(String),
+}
+
+impl MocSource {
+    /// `#tag` is a tag, anything else a note path.
+    pub fn parse(source: &str) -> MocSource {
+        match source.trim().strip_prefix('#') {
+            Some(tag) => MocSource::Tag(tag.to_string()),
+            None => MocSource::Subtree(source.trim().trim_matches('/').to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MocUpdate {
+    /// The hub note holding the map
+    pub path: String,
+    /// Notes linked from the map
+    pub links: usize,
+    /// Revision after the write, `None` if the map was already up to date
+    pub revision: Option<Revision>,
+}
+
+/// Renders notes below `root` as a nested list, one level per path segment.
+fn render_subtree(root: &str, mut paths: Vec<String>) -> String {
+    // Segment order keeps children right below their parent ("a", "a/b", "a-c")
+    paths.sort_by(|a, b| a.split('/').cmp(b.split('/')));
+    let root_depth = if root.is_empty() {
+        0
+    } else {
+        root.split('/').count()
+    };
+    paths
+        .iter()
+        .map(|path| {
+            let depth = path.split('/').count() - root_depth - 1;
+            format!("{}- {}\n", "  ".repeat(depth), wiki_link(path))
+        })
+        .collect()
+}
+
+/// Renders `(tag, path)` pairs as one list per tag, under a heading naming it.
+fn render_tagged(tagged: Vec<(String, String)>) -> String {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (tag, path) in tagged {
+        groups.entry(tag).or_default().push(path);
+    }
+    groups
+        .into_iter()
+        .map(|(tag, paths)| {
+            let links: String = paths
+                .iter()
+                .map(|path| format!("- {}\n", wiki_link(path)))
+                .collect();
+            // No `#`, or the hub itself would be tagged
+            format!("### {}\n\n{}", tag, links)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Puts `list` between the map markers in `content`, appending the block when
+/// the note has none yet.
+fn replace_map(content: &str, list: &str) -> String {
+    let block = format!("{}\n{}{}", MOC_START, list, MOC_END);
+    if let Some(start) = content.find(MOC_START)
+        && let Some(end) = content[start..].find(MOC_END)
+    {
+        let end = start + end + MOC_END.len();
+        return format!("{}{}{}", &content[..start], block, &content[end..]);
+    }
+    let content = content.trim_end();
+    if content.is_empty() {
+        format!("{}\n", block)
+    } else {
+        format!("{}\n\n{}\n", content, block)
+    }
+}
+
+impl NotesApi {
+    /// Creates or refreshes the map of content in the note at `target`, creating
+    /// the note if needed. Archived notes and the hub itself are left out.
+    pub fn generate_moc(&mut self, source: &MocSource, target: &str) -> Result<MocUpdate> {
+        self.ensure_writable()?;
+
+        let (list, links) = match source {
+            MocSource::Subtree(root) => {
+                if !self.note_exists(root)? {
+                    return Err(Error::NotFound(root.clone()));
+                }
+                let pattern = if root.is_empty() {
+                    "%".to_string()
+                } else {
+                    format!("{}/%", escape_like(root))
+                };
+                let paths = self
+                    .db
+                    .prepare(
+                        "SELECT path FROM notes
+                         WHERE archived = 0 AND path <> '' AND path <> ?2
+                           AND path LIKE ?1 ESCAPE '\\'",
+                    )?
+                    .query_map(params![pattern, target], |row| row.get(0))?
+                    .collect::<std::result::Result<Vec<String>, _>>()?;
+                let links = paths.len();
+                (render_subtree(root, paths), links)
+            }
+            MocSource::Tag(tag) => {
+                let tag = normalize_tag(tag)
+                    .ok_or_else(|| Error::InvalidInput(format!("Not a valid tag: {}", tag)))?;
+                let tagged = self
+                    .db
+                    .prepare(
+                        "SELECT tags.tag, notes.path
+                         FROM tags
+                         JOIN notes ON tags.note_id = notes.id
+                         WHERE notes.archived = 0 AND notes.path <> ?3
+                           AND (tags.tag = ?1 OR tags.tag LIKE ?2 ESCAPE '\\')
+                         ORDER BY notes.path ASC",
+                    )?
+                    .query_map(
+                        params![tag, format!("{}/%", escape_like(&tag)), target],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )?
+                    .collect::<std::result::Result<Vec<(String, String)>, _>>()?;
+                let mut paths: Vec<&str> = tagged.iter().map(|(_, path)| path.as_str()).collect();
+                paths.dedup();
+                let links = paths.len();
+                (render_tagged(tagged), links)
+            }
+        };
+
+        if !self.note_exists(target)? {
+            self.create_note(target)?;
+        }
+        let content = self.read_content(target)?;
+        let updated = replace_map(&content, &list);
+        let revision = if updated == content {
+            None
+        } else {
+            Some(self.save_note(target, &updated)?)
+        };
+
+        Ok(MocUpdate {
+            path: target.to_string(),
+            links,
+            revision,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for (path, content) in [
+            ("garden", "# Garden\n\nMy plants."),
+            ("garden/roses", "#plants/flowers"),
+            ("garden/roses/pruning", "#plants"),
+            ("garden-shed", ""),
+            ("garden/tomatoes", "#plants/vegetables #plants/flowers"),
+        ] {
+            api.create_note(path).unwrap();
+            api.save_note(path, content).unwrap();
+        }
+        (temp_dir, api)
+    }
+
+    #[test]
+    fn test_subtree_map_keeps_hub_text() {
+        let (_temp_dir, mut api) = setup();
+
+        let update = api
+            .generate_moc(&MocSource::parse("garden"), "garden")
+            .unwrap();
+        assert_eq!(update.links, 3);
+        assert!(update.revision.is_some());
+        assert_eq!(
+            api.get_note("garden").unwrap().content,
+            "# Garden\n\nMy plants.\n\n<!-- moc -->\n\
+             - [[garden/roses]]\n  - [[garden/roses/pruning]]\n- [[garden/tomatoes]]\n\
+             <!-- /moc -->\n"
+        );
+
+        // Regenerating replaces only the list, and is a no-op when nothing changed
+        api.create_note("garden/basil").unwrap();
+        let update = api
+            .generate_moc(&MocSource::parse("garden"), "garden")
+            .unwrap();
+        assert_eq!(update.links, 4);
+        let content = api.get_note("garden").unwrap().content;
+        assert!(
+            content.starts_with("# Garden\n\nMy plants.\n\n<!-- moc -->\n- [[garden/basil]]\n")
+        );
+        assert_eq!(content.matches(MOC_START).count(), 1);
+        let update = api
+            .generate_moc(&MocSource::parse("garden"), "garden")
+            .unwrap();
+        assert!(update.revision.is_none());
+    }
+
+    #[test]
+    fn test_tag_map_groups_by_tag() {
+        let (_temp_dir, mut api) = setup();
+
+        let update = api
+            .generate_moc(&MocSource::parse("#Plants"), "plant index")
+            .unwrap();
+        assert_eq!(update.links, 3);
+        // The hub lists the tags without being tagged itself
+        assert_eq!(api.get_notes_with_tag("plants").unwrap().len(), 3);
+        assert_eq!(
+            api.get_note("plant index").unwrap().content,
+            "<!-- moc -->\n\
+             ### plants\n\n- [[garden/roses/pruning]]\n\n\
+             ### plants/flowers\n\n- [[garden/roses]]\n- [[garden/tomatoes]]\n\n\
+             ### plants/vegetables\n\n- [[garden/tomatoes]]\n\
+             <!-- /moc -->\n"
+        );
+    }
+
+    #[test]
+    fn test_moc_errors() {
+        let (_temp_dir, mut api) = setup();
+        assert!(matches!(
+            api.generate_moc(&MocSource::parse("missing"), "hub"),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            api.generate_moc(&MocSource::parse("#123"), "hub"),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,225 @@
+//! Following notes moved or renamed outside the app.
+//!
+//! To a rescan, a folder renamed in a file manager looks like every note in it was
+//! deleted and new ones created, which would drop their ids and everything keyed on
+//! them. `match_moves` pairs the notes that vanished from the index with the ones that
+//! appeared, by content first and then by their position under a moved folder, so the
+//! rescan can move index entries instead.
+
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::params;
+
+use crate::encryption::is_encrypted;
+use crate::notes::{NotesApi, Result, compute_hash, get_parent_path};
+
+/// Pairs notes that disappeared (`gone`, as path and indexed content hash) with notes
+/// that appeared (`new`, as path and content hash on disk). Returns `(from, to)` pairs.
+///
+/// A content hash only counts when exactly one gone and one new note share it, so
+/// empty or duplicated notes are never guessed at. Once a note is paired, descendants
+/// that kept their place under it are paired too, whatever their content.
+pub fn match_moves(gone: &[(String, String)], new: &[(String, String)]) -> Vec<(String, String)> {
+    fn unique_by_hash(notes: &[(String, String)]) -> HashMap<&str, &str> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for (_, hash) in notes {
+            *counts.entry(hash.as_str()).or_default() += 1;
+        }
+        notes
+            .iter()
+            .filter(|(_, hash)| counts[hash.as_str()] == 1)
+            .map(|(path, hash)| (hash.as_str(), path.as_str()))
+            .collect()
+    }
+
+    let new_by_hash = unique_by_hash(new);
+    let mut moves: Vec<(String, String)> = unique_by_hash(gone)
+        .into_iter()
+        .filter_map(|(hash, from)| Some((from.to_string(), new_by_hash.get(hash)?.to_string())))
+        .collect();
+    // Shallowest first, so a moved folder claims its subtree before any descendant
+    moves.sort_by_key(|(from, _)| (from.matches('/').count(), from.clone()));
+
+    let new_paths: HashSet<&str> = new.iter().map(|(path, _)| path.as_str()).collect();
+    let mut claimed_from: HashSet<String> = moves.iter().map(|(from, _)| from.clone()).collect();
+    let mut claimed_to: HashSet<String> = moves.iter().map(|(_, to)| to.clone()).collect();
+
+    let mut index = 0;
+    while index < moves.len() {
+        let (from, to) = moves[index].clone();
+        let prefix = format!("{}/", from);
+        for (path, _) in gone {
+            let Some(rest) = path.strip_prefix(&prefix) else {
+                continue;
+            };
+            let target = format!("{}/{}", to, rest);
+            if !claimed_from.contains(path)
+                && !claimed_to.contains(&target)
+                && new_paths.contains(target.as_str())
+            {
+                claimed_from.insert(path.clone());
+                claimed_to.insert(target.clone());
+                moves.push((path.clone(), target));
+            }
+        }
+        index += 1;
+    }
+
+    moves
+}
+
+impl NotesApi {
+    /// Moves the index entries of notes that were moved on disk, given the paths in
+    /// the index and on disk before a rescan, along with the links pointing at them.
+    /// Returns the old paths that were moved.
+    pub(crate) fn follow_moves(
+        &mut self,
+        db_paths: &[String],
+        fs_paths: &HashSet<&str>,
+    ) -> Result<HashSet<String>> {
+        let gone_paths: Vec<&String> = db_paths
+            .iter()
+            .filter(|path| !fs_paths.contains(path.as_str()))
+            .collect();
+        if gone_paths.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let indexed: HashSet<&str> = db_paths.iter().map(String::as_str).collect();
+        let new_paths: Vec<&str> = fs_paths
+            .iter()
+            .copied()
+            .filter(|path| !indexed.contains(path))
+            .collect();
+        if new_paths.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let mut gone = Vec::with_capacity(gone_paths.len());
+        for path in gone_paths {
+            let hash: String = self.db.query_row(
+                "SELECT content_hash FROM notes WHERE path = ?1",
+                params![path],
+                |row| row.get(0),
+            )?;
+            gone.push((path.clone(), hash));
+        }
+        let mut new = Vec::with_capacity(new_paths.len());
+        for path in new_paths {
+            // Hashed as `sync_note` hashes it: encrypted notes by their ciphertext
+            let content = self.fs.read_note(path)?;
+            let hash = if is_encrypted(&content) {
+                compute_hash(&content)
+            } else {
+                compute_hash(&self.with_canvas_labels(path, content))
+            };
+            new.push((path.to_string(), hash));
+        }
+
+        // Every moved note is its own pair, and a path is never both a source and a
+        // target, so exact matches cover the links
+        let moves = match_moves(&gone, &new);
+        for (from, to) in &moves {
+            self.db.execute(
+                "UPDATE notes SET path = ?2, parent_path = ?3 WHERE path = ?1",
+                params![from, to, get_parent_path(to)],
+            )?;
+            self.db.execute(
+                "UPDATE OR IGNORE links SET target = ?2 WHERE target = ?1",
+                params![from, to],
+            )?;
+        }
+        Ok(moves.into_iter().map(|(from, _)| from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn notes(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+        entries
+            .iter()
+            .map(|(path, hash)| (path.to_string(), hash.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_match_moves_follows_folders_and_skips_ambiguous_content() {
+        let gone = notes(&[
+            ("projects", "p"),
+            ("projects/empty", "e"),
+            ("scratch", "e"),
+            ("old", "o"),
+        ]);
+        let new = notes(&[
+            ("archive/projects", "p"),
+            ("archive/projects/empty", "e"),
+            ("elsewhere", "e"),
+            ("fresh", "f"),
+        ]);
+        assert_eq!(
+            match_moves(&gone, &new),
+            vec![
+                ("projects".to_string(), "archive/projects".to_string()),
+                (
+                    "projects/empty".to_string(),
+                    "archive/projects/empty".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rescan_keeps_ids_of_moved_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let mut api = NotesApi::new(root).unwrap();
+        api.create_note("projects").unwrap();
+        api.save_note("projects", "# Projects").unwrap();
+        api.create_note("projects/empty").unwrap();
+        api.create_note("archive").unwrap();
+        let ids = [
+            api.get_note_internal("projects").unwrap().id,
+            api.get_note_internal("projects/empty").unwrap().id,
+        ];
+
+        fs::rename(root.join("projects"), root.join("archive/projects")).unwrap();
+        api.rescan().unwrap();
+
+        assert!(!api.note_exists("projects").unwrap());
+        assert_eq!(
+            [
+                api.get_note_internal("archive/projects").unwrap().id,
+                api.get_note_internal("archive/projects/empty").unwrap().id,
+            ],
+            ids
+        );
+    }
+
+    #[test]
+    fn test_rescan_moves_links_and_encrypted_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let mut api = NotesApi::new(root).unwrap();
+        for path in ["projects", "archive", "home", "diary"] {
+            api.create_note(path).unwrap();
+        }
+        api.save_note("projects", "# Projects").unwrap();
+        api.save_note("home", "See [[projects]]").unwrap();
+        api.save_note("diary", "# Secret").unwrap();
+        api.unlock("correct horse").unwrap();
+        api.encrypt_note("diary").unwrap();
+        let diary_id = api.get_note_internal("diary").unwrap().id;
+
+        fs::rename(root.join("projects"), root.join("archive/projects")).unwrap();
+        fs::rename(root.join("diary"), root.join("archive/diary")).unwrap();
+        api.rescan().unwrap();
+
+        let backlinks = api.get_backlinks("archive/projects").unwrap();
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].path, "home");
+        assert_eq!(api.get_note_internal("archive/diary").unwrap().id, diary_id);
+    }
+}
@@ -0,0 +1,189 @@
+//! Links to notes for copying between the app and other programs.
+//!
+//! A note link has two forms: a `zinnia://note/<path>` URL that other apps can
+//! keep, and the `[[path]]` wiki-link used inside notes. Copying a note puts both
+//! on the clipboard; pasting the URL back into the editor turns it into the
+//! wiki-link. Hovering a wiki-link shows a `NotePreview` of its target.
+
+use std::time::SystemTime;
+
+use crate::frontmatter::Frontmatter;
+use crate::notes::{Error, NotesApi, Result};
+use crate::stats::compute_note_stats;
+
+/// Start of every note URL; the rest is the percent-encoded note path.
+const NOTE_URL_PREFIX: &str = "zinnia://note/";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteLink {
+    pub path: String,
+    pub url: String,
+    pub wiki_link: String,
+}
+
+/// What a link hover card shows about the linked note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotePreview {
+    pub path: String,
+    /// The `title` property, or the last path segment
+    pub title: String,
+    pub modified: SystemTime,
+    /// The start of the note as plain text
+    pub excerpt: String,
+}
+
+/// Builds the `zinnia://note/...` URL for a note, percent-encoding each path segment.
+pub fn note_url(path: &str) -> String {
+    let encoded: Vec<String> = path.split('/').map(percent_encode).collect();
+    format!("{}{}", NOTE_URL_PREFIX, encoded.join("/"))
+}
+
+/// Returns the note path a `zinnia://note/...` URL points to.
+pub fn parse_note_url(url: &str) -> Option<String> {
+    let rest = url.trim().strip_prefix(NOTE_URL_PREFIX)?;
+    let path = percent_decode(rest)?;
+    let path = path.trim_matches('/');
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+/// The `[[path]]` form used to link notes from inside other notes.
+pub fn wiki_link(path: &str) -> String {
+    format!("[[{}]]", path)
+}
+
+/// A note's display title: its `title` property, or the last path segment.
+pub(crate) fn note_title(path: &str, frontmatter: &Frontmatter) -> String {
+    frontmatter
+        .get_text("title")
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| path.rsplit('/').next().unwrap_or(path))
+        .to_string()
+}
+
+pub(crate) fn percent_encode(segment: &str) -> String {
+    let mut out = String::new();
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+pub(crate) fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+impl NotesApi {
+    /// Returns both link forms for the note at `path`, for putting on the clipboard.
+    pub fn copy_note_link(&self, path: &str) -> Result<NoteLink> {
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        Ok(NoteLink {
+            path: path.to_string(),
+            url: note_url(path),
+            wiki_link: wiki_link(path),
+        })
+    }
+
+    /// Returns the title, modified time, and opening text of the note at `path`.
+    ///
+    /// Unlike `get_note`, this doesn't count as a visit.
+    pub fn get_note_preview(&self, path: &str) -> Result<NotePreview> {
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        let note = self.get_note_internal(path)?;
+        let (frontmatter, _) = Frontmatter::parse(&note.content);
+        Ok(NotePreview {
+            title: note_title(path, &frontmatter),
+            path: note.path,
+            modified: note.modified,
+            excerpt: compute_note_stats(&note.content).preview,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_note_url_round_trip() {
+        let url = note_url("projects/Q3 plan/café");
+        assert_eq!(url, "zinnia://note/projects/Q3%20plan/caf%C3%A9");
+        assert_eq!(
+            parse_note_url(&url).as_deref(),
+            Some("projects/Q3 plan/café")
+        );
+    }
+
+    #[test]
+    fn test_parse_note_url_rejects_other_text() {
+        assert_eq!(parse_note_url("https://example.com"), None);
+        assert_eq!(parse_note_url("zinnia://note/"), None);
+        assert_eq!(parse_note_url("zinnia://note/bad%2"), None);
+        assert_eq!(
+            parse_note_url(" zinnia://note/inbox \n").as_deref(),
+            Some("inbox")
+        );
+    }
+
+    #[test]
+    fn test_copy_note_link() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("inbox").unwrap();
+
+        let link = api.copy_note_link("inbox").unwrap();
+        assert_eq!(link.url, "zinnia://note/inbox");
+        assert_eq!(link.wiki_link, "[[inbox]]");
+        assert!(matches!(
+            api.copy_note_link("missing"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_get_note_preview() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("projects").unwrap();
+        api.create_note("projects/q3").unwrap();
+        api.save_note("projects/q3", "# Plan\n\nShip the **beta**.")
+            .unwrap();
+        api.save_note("projects", "---\ntitle: All projects\n---\nIndex")
+            .unwrap();
+
+        let preview = api.get_note_preview("projects/q3").unwrap();
+        assert_eq!(preview.title, "q3");
+        assert_eq!(preview.excerpt, "Plan Ship the beta.");
+        assert_eq!(
+            api.get_note_preview("projects").unwrap().title,
+            "All projects"
+        );
+        assert!(matches!(
+            api.get_note_preview("missing"),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
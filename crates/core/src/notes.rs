@@ -1,11 +1,32 @@
-use std::path::Path;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use rusqlite::{Connection, OptionalExtension, Result as SqlResult, params};
-
-use crate::filesystem::NoteFilesystem;
+use rusqlite::{Connection, OptionalExtension, Result as SqlResult, params, params_from_iter};
+
+use crate::board::STATUS_KEY;
+use crate::changes::Revision;
+use crate::clock::{Clock, SystemClock};
+use crate::config::VaultConfig;
+use crate::content_cache::{CachedStore, ContentCache};
+use crate::encryption::{VaultKey, is_encrypted};
+use crate::export::FormatRegistry;
+use crate::filesystem::{NoteFilesystem, NoteStore};
+use crate::journal;
+use crate::offline::PendingOp;
+use crate::ordering::CHILD_ORDER;
+use crate::recovery::{self, StartupReport};
+use crate::stats::{NoteStats, compute_note_stats};
+use crate::status::NoteStatus;
+use crate::tags::escape_like;
+use crate::watcher::DiskProbe;
+
+/// The index database, in the notes root.
+pub const DATABASE_FILE_NAME: &str = ".notes.db";
+
+/// Folder archived notes are moved into, next to where they lived.
+pub const ARCHIVE_DIR: &str = "_archive";
 
 #[derive(Debug)]
 pub enum Error {
@@ -15,6 +36,24 @@ pub enum Error {
     NotFound(String),
     AlreadyExists(String),
     ParentNotFound(String),
+    InvalidInput(String),
+    /// The vault was opened read-only and the operation would modify it
+    ReadOnly,
+    /// An access token is unknown or doesn't grant the requested operation
+    PermissionDenied(String),
+    /// The notes root is missing (e.g. an external drive was unplugged)
+    VaultUnavailable,
+    /// The note changed on disk since the caller read it; `content` is what is there now
+    Conflict {
+        path: String,
+        content: String,
+    },
+    /// The note is encrypted and the vault is locked
+    Encrypted(String),
+    /// A git operation failed; see the `git` module
+    Git(String),
+    /// Talking to a sync remote failed; see the `remote_sync` module
+    Remote(String),
 }
 
 impl From<std::io::Error> for Error {
@@ -29,6 +68,66 @@ impl From<rusqlite::Error> for Error {
     }
 }
 
+impl Error {
+    /// Every key `message_key` returns, so translations can be checked for all of them.
+    pub const MESSAGE_KEYS: &[&str] = &[
+        "error-io",
+        "error-database",
+        "error-database-corrupted",
+        "error-not-found",
+        "error-already-exists",
+        "error-parent-not-found",
+        "error-invalid-input",
+        "error-read-only",
+        "error-permission-denied",
+        "error-vault-unavailable",
+        "error-conflict",
+        "error-encrypted",
+        "error-git",
+        "error-remote",
+    ];
+
+    /// Key of the user-facing message for this error in the frontend's translations.
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            Error::Io(_) => "error-io",
+            Error::Database(_) => "error-database",
+            Error::DatabaseCorrupted => "error-database-corrupted",
+            Error::NotFound(_) => "error-not-found",
+            Error::AlreadyExists(_) => "error-already-exists",
+            Error::ParentNotFound(_) => "error-parent-not-found",
+            Error::InvalidInput(_) => "error-invalid-input",
+            Error::ReadOnly => "error-read-only",
+            Error::PermissionDenied(_) => "error-permission-denied",
+            Error::VaultUnavailable => "error-vault-unavailable",
+            Error::Conflict { .. } => "error-conflict",
+            Error::Encrypted(_) => "error-encrypted",
+            Error::Git(_) => "error-git",
+            Error::Remote(_) => "error-remote",
+        }
+    }
+
+    /// Named values to substitute into the message for `message_key`.
+    pub fn message_args(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Error::Io(e) => vec![("message", e.to_string())],
+            Error::Database(e) => vec![("message", e.to_string())],
+            Error::NotFound(path)
+            | Error::AlreadyExists(path)
+            | Error::ParentNotFound(path)
+            | Error::Conflict { path, .. }
+            | Error::Encrypted(path) => vec![("path", path.clone())],
+            Error::InvalidInput(message)
+            | Error::PermissionDenied(message)
+            | Error::Git(message)
+            | Error::Remote(message) => {
+                vec![("message", message.clone())]
+            }
+            Error::DatabaseCorrupted | Error::ReadOnly | Error::VaultUnavailable => Vec::new(),
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, Clone)]
@@ -37,6 +136,8 @@ pub struct Note {
     pub path: String,
     pub content: String,
     pub modified: SystemTime,
+    /// Vault revision at the time the note was read
+    pub revision: Revision,
 }
 
 #[derive(Debug, Clone)]
@@ -45,6 +146,20 @@ pub struct NoteMetadata {
     pub path: String,
     pub modified: SystemTime,
     pub archived: bool,
+    /// Cached preview, word count, cover, and so on; only populated by the `*_with_stats`
+    /// queries
+    pub stats: Option<NoteStats>,
+}
+
+impl NoteMetadata {
+    /// The note's `title` property when its stats are loaded, otherwise its last path
+    /// segment.
+    pub fn title(&self) -> &str {
+        self.stats
+            .as_ref()
+            .and_then(|stats| stats.title.as_deref())
+            .unwrap_or_else(|| self.path.rsplit('/').next().unwrap_or(&self.path))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -55,30 +170,81 @@ pub enum RankingMode {
     Frecency,
 }
 
+/// How `NotesApi::sync_change` changed the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncOutcome {
+    /// The index already matched the filesystem
+    Unchanged,
+    /// The note was new to the index
+    Created,
+    /// The note's content changed
+    Modified,
+    /// The note is gone from the filesystem and was dropped from the index
+    Deleted,
+}
+
+/// What a frontend is allowed to do with the open vault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// False when the vault was opened read-only, or is unavailable without an offline
+    /// queue to keep edits in (see `offline`)
+    pub editable: bool,
+    /// False while the notes root is missing
+    pub available: bool,
+}
+
 pub struct NotesApi {
-    fs: NoteFilesystem,
-    db: Connection,
+    pub(crate) fs: Box<dyn NoteStore>,
+    pub(crate) db: Connection,
     /// Flag to indicate when API is performing operations (suppresses watcher)
     pub(crate) operation_in_progress: Arc<AtomicBool>,
     /// Optional callback for frecency updates
     frecency_callback: Option<Arc<dyn Fn() + Send + Sync>>,
+    /// Per-vault settings loaded from the config file
+    pub(crate) config: VaultConfig,
+    /// Rejects mutations with `Error::ReadOnly` when set
+    read_only: bool,
+    /// Recovery performed while opening the database
+    pub(crate) startup_report: StartupReport,
+    /// Formats notes can be rendered and exported in
+    pub(crate) formats: FormatRegistry,
+    /// When a note was last saved, so maintenance can stay out of the way of editing
+    pub(crate) last_save: Option<SystemTime>,
+    /// When maintenance last ran, so the scheduler can retry runs skipped for editing
+    pub(crate) last_maintenance: Option<SystemTime>,
+    /// Time source for timestamps and idle checks
+    pub(crate) clock: Arc<dyn Clock>,
+    /// Shows private notes in search, the finder, exports, and scoped access
+    pub(crate) include_private: bool,
+    /// Note contents read through `fs`, shared with the `CachedStore` wrapping it
+    pub(crate) content_cache: Arc<Mutex<ContentCache>>,
+    /// Set by `unlock`; reads and writes encrypted notes
+    pub(crate) vault_key: Option<VaultKey>,
+    /// Journal for edits made while the vault is unavailable; see `offline`
+    pub(crate) offline_queue: Option<PathBuf>,
+    /// Where this device keeps the remote sync state; see `remote_sync`
+    pub(crate) sync_state: Option<PathBuf>,
 }
 
-/// RAII guard that sets operation_in_progress flag on creation and clears it on drop
-struct OperationGuard {
+/// RAII guard that sets operation_in_progress flag on creation and restores it on drop
+///
+/// Restoring (rather than clearing) keeps the flag set while an outer operation, such
+/// as a batch, is still running.
+pub(crate) struct OperationGuard {
     flag: Arc<AtomicBool>,
+    previous: bool,
 }
 
 impl OperationGuard {
-    fn new(flag: Arc<AtomicBool>) -> Self {
-        flag.store(true, Ordering::SeqCst);
-        Self { flag }
+    pub(crate) fn new(flag: Arc<AtomicBool>) -> Self {
+        let previous = flag.swap(true, Ordering::SeqCst);
+        Self { flag, previous }
     }
 }
 
 impl Drop for OperationGuard {
     fn drop(&mut self) {
-        self.flag.store(false, Ordering::SeqCst);
+        self.flag.store(self.previous, Ordering::SeqCst);
     }
 }
 
@@ -88,11 +254,43 @@ impl NotesApi {
     /// Initializes the filesystem and database at the specified notes_root directory.
     /// Creates the database file if it doesn't exist, runs migrations, and verifies schema.
     pub fn new<P: AsRef<Path>>(notes_root: P) -> Result<Self> {
-        let fs = NoteFilesystem::new(&notes_root)?;
+        Self::open(notes_root, false)
+    }
+
+    /// Creates a NotesApi instance that rejects every mutation with `Error::ReadOnly`.
+    ///
+    /// Intended for viewing backups or shared drives. Note files are never written; the
+    /// database index is still kept in sync (e.g. by the watcher) since it's derived data.
+    pub fn new_read_only<P: AsRef<Path>>(notes_root: P) -> Result<Self> {
+        Self::open(notes_root, true)
+    }
+
+    /// Creates a NotesApi over any note store, e.g. a `MemoryNoteStore` in tests.
+    ///
+    /// A store that isn't persistent gets an in-memory index and the default config.
+    pub fn with_store(store: Box<dyn NoteStore>) -> Result<Self> {
+        Self::open_store(store, false)
+    }
+
+    fn open<P: AsRef<Path>>(notes_root: P, read_only: bool) -> Result<Self> {
+        Self::open_store(Box::new(NoteFilesystem::new(notes_root)?), read_only)
+    }
+
+    fn open_store(mut fs: Box<dyn NoteStore>, read_only: bool) -> Result<Self> {
+        let persistent = fs.is_persistent();
 
         // Create database path at notes_root/.notes.db
-        let db_path = notes_root.as_ref().join(".notes.db");
-        let db = Connection::open(db_path)?;
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let (db, mut startup_report) = if persistent {
+            recovery::open_database(&fs.root_path().join(DATABASE_FILE_NAME), clock.as_ref())?
+        } else {
+            (Connection::open_in_memory()?, StartupReport::default())
+        };
+
+        // Finish saves a crash interrupted; a read-only vault keeps its journal
+        if !read_only && persistent {
+            startup_report.journal_replayed = journal::replay(fs.as_ref())?;
+        }
 
         // Run migrations
         run_migrations(&db)?;
@@ -100,12 +298,43 @@ impl NotesApi {
         // Verify schema
         verify_schema(&db)?;
 
-        Ok(Self {
+        let config = if persistent {
+            VaultConfig::load(fs.root_path())?
+        } else {
+            VaultConfig::default()
+        };
+        crate::layout::validate_note_file(&config.note_file)?;
+        fs.set_note_file(&config.note_file);
+        fs.set_layout(config.layout);
+
+        // Stores that aren't persistent are memory already
+        let content_cache = Arc::new(Mutex::new(ContentCache::new(&config.cache)));
+        let fs: Box<dyn NoteStore> = if persistent {
+            Box::new(CachedStore::new(fs, Arc::clone(&content_cache)))
+        } else {
+            fs
+        };
+
+        let api = Self {
             fs,
             db,
             operation_in_progress: Arc::new(AtomicBool::new(false)),
             frecency_callback: None,
-        })
+            config,
+            read_only,
+            startup_report,
+            formats: FormatRegistry::default(),
+            last_save: None,
+            last_maintenance: None,
+            clock,
+            include_private: false,
+            content_cache,
+            vault_key: None,
+            offline_queue: None,
+            sync_state: None,
+        };
+        api.apply_index_cache()?;
+        Ok(api)
     }
 
     /// Creates a new NotesApi instance using platform-specific default paths.
@@ -149,6 +378,83 @@ impl NotesApi {
         self.fs.root_path()
     }
 
+    /// Returns true if the vault was opened with `new_read_only`.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Returns what frontends may do with this vault (used to disable editing UI).
+    pub fn capabilities(&self) -> Capabilities {
+        let available = self.is_available();
+        Capabilities {
+            editable: (available || self.offline_queue.is_some()) && !self.read_only,
+            available,
+        }
+    }
+
+    /// Returns true if the notes root currently exists.
+    pub fn is_available(&self) -> bool {
+        self.fs.is_available()
+    }
+
+    /// Returns `Error::VaultUnavailable` if the notes root is missing.
+    ///
+    /// Index maintenance must check this first: scanning a missing root would look like
+    /// every note was deleted.
+    pub(crate) fn ensure_available(&self) -> Result<()> {
+        if !self.is_available() {
+            return Err(Error::VaultUnavailable);
+        }
+        Ok(())
+    }
+
+    /// Returns `Error::ReadOnly` if the vault was opened read-only, or
+    /// `Error::VaultUnavailable` if the notes root is missing.
+    pub(crate) fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+        self.ensure_available()
+    }
+
+    /// Returns the time source, for background threads that should share it.
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        Arc::clone(&self.clock)
+    }
+
+    /// Replaces the time source (tests use a `ManualClock`).
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Returns the vault configuration.
+    pub fn config(&self) -> &VaultConfig {
+        &self.config
+    }
+
+    /// Replaces the vault configuration and persists it to the config file.
+    pub fn set_config(&mut self, config: VaultConfig) -> Result<()> {
+        self.ensure_writable()?;
+        // Existing note files would no longer be found under a new name
+        if config.note_file != self.config.note_file {
+            return Err(Error::InvalidInput(
+                "The note file name is changed with set_note_file".to_string(),
+            ));
+        }
+        if config.layout != self.config.layout {
+            return Err(Error::InvalidInput(
+                "The vault layout is changed with set_layout".to_string(),
+            ));
+        }
+
+        if self.fs.is_persistent() {
+            config.save(self.fs.root_path())?;
+        }
+        self.content_cache.lock().unwrap().set_limits(&config.cache);
+        self.config = config;
+        self.apply_index_cache()
+    }
+
     /// Sets a callback to be invoked when frecency scores are updated.
     /// This allows the frontend to refresh navigation when scores change.
     pub fn set_frecency_callback<F>(&mut self, callback: F)
@@ -163,7 +469,9 @@ impl NotesApi {
     /// Scans all notes in the filesystem and ensures the database is up to date.
     /// Use this after opening the database to handle external filesystem changes.
     pub fn startup_sync(&mut self) -> Result<()> {
-        self.rescan()
+        self.rescan()?;
+        self.salvage_index();
+        self.prune_changes()
     }
 
     // Core CRUD operations
@@ -173,6 +481,13 @@ impl NotesApi {
     /// Returns an error if the parent path doesn't exist (notes must be created top-down).
     /// Creates an empty note in both filesystem and database, returning the created Note.
     pub fn create_note(&mut self, path: &str) -> Result<Note> {
+        if self.defer(|| PendingOp::Create {
+            path: path.to_string(),
+        })? {
+            return Ok(self.deferred_note(path));
+        }
+        self.ensure_writable()?;
+
         let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
 
         // Check if parent exists (if not root-level)
@@ -194,12 +509,9 @@ impl NotesApi {
 
     /// Retrieves a note with its full content without tracking access.
     /// Internal method used by operations that shouldn't count as user access.
-    fn get_note_internal(&self, path: &str) -> Result<Note> {
+    pub(crate) fn get_note_internal(&self, path: &str) -> Result<Note> {
         // Read content from filesystem
-        let content = self
-            .fs
-            .read_note(path)
-            .map_err(|_| Error::NotFound(path.to_string()))?;
+        let content = self.read_content(path)?;
 
         // Get metadata from database
         let (id, mtime) = self
@@ -211,13 +523,19 @@ impl NotesApi {
             )
             .map_err(|_| Error::NotFound(path.to_string()))?;
 
-        let modified = UNIX_EPOCH + std::time::Duration::from_secs(mtime as u64);
+        // The index keeps whole seconds; the file's own time is exact, which
+        // `save_note_if_unchanged` relies on
+        let modified = self
+            .fs
+            .note_mtime(path)
+            .unwrap_or(UNIX_EPOCH + std::time::Duration::from_secs(mtime as u64));
 
         Ok(Note {
             id,
             path: path.to_string(),
             content,
             modified,
+            revision: self.revision()?,
         })
     }
 
@@ -240,40 +558,73 @@ impl NotesApi {
     /// Writes the new content to filesystem and updates the database index.
     /// Updates modification time and content hash automatically.
     /// Records an access to the note and propagates to ancestors.
-    pub fn save_note(&mut self, path: &str, content: &str) -> Result<()> {
+    pub fn save_note(&mut self, path: &str, content: &str) -> Result<Revision> {
+        if self.defer(|| PendingOp::Save {
+            path: path.to_string(),
+            content: content.to_string(),
+        })? {
+            return Ok(self.deferred_revision());
+        }
+        self.ensure_writable()?;
+
         let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
 
-        // Write to filesystem
-        self.fs.write_note(path, content)?;
+        self.write_note_content(path, content)?;
 
-        // Update database
-        self.sync_note(path)?;
+        // Create person notes for new @mentions (if enabled)
+        self.create_mentioned_people(path)?;
 
         // Record access for frecency tracking
         self.record_access(path)?;
 
+        self.revision()
+    }
+
+    /// The write half of `save_note`, shared with edits that span many notes:
+    /// encrypts if the note is encrypted, writes through the journal, and reindexes.
+    pub(crate) fn write_note_content(&mut self, path: &str, content: &str) -> Result<()> {
+        // Encrypted notes stay encrypted
+        let content = self.content_for_disk(path, content)?;
+
+        // Write to filesystem (journaled, so an interrupted save is replayed on next open)
+        self.journaled_write(path, &content)?;
+
+        // Update database
+        self.sync_note(path)?;
+
+        self.last_save = Some(self.clock.now());
         Ok(())
     }
 
     /// Deletes a note and all its descendants recursively.
     ///
-    /// Removes the note directory from filesystem and all associated entries from database.
-    /// This operation cannot be undone (unless you archive_note instead).
-    pub fn delete_note(&mut self, path: &str) -> Result<()> {
+    /// Moves the note directory into the vault's trash (see `restore_from_trash`) and
+    /// removes all associated entries from database. Stores that aren't persistent have
+    /// no trash, so their notes are removed outright.
+    pub fn delete_note(&mut self, path: &str) -> Result<Revision> {
+        self.ensure_writable()?;
+
         let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
 
-        // Delete from filesystem (recursive)
-        self.fs
-            .delete_note(path)
-            .map_err(|_| Error::NotFound(path.to_string()))?;
+        // Move to the trash (recursive)
+        if self.fs.is_persistent() {
+            self.move_to_trash(path)?;
+        } else {
+            self.fs
+                .delete_note(path)
+                .map_err(|_| Error::NotFound(path.to_string()))?;
+        }
 
         // Delete from database (note and all descendants)
         self.db.execute(
-            "DELETE FROM notes WHERE path = ?1 OR path LIKE ?2",
-            params![path, format!("{}/%", path)],
+            &format!(
+                "DELETE FROM notes WHERE path = ?2 OR {}",
+                descendants_clause(1)
+            ),
+            params_from_iter(descendants_params(path)),
         )?;
 
-        Ok(())
+        self.revision()
     }
 
     /// Moves a note and all its descendants to the system trash/recycle bin.
@@ -281,7 +632,9 @@ impl NotesApi {
     /// Sends the note directory to the OS trash (Trash on macOS, Recycle Bin on Windows).
     /// Also removes all associated entries from the database.
     /// The note can be restored from the system trash using OS file recovery.
-    pub fn trash_note(&mut self, path: &str) -> Result<()> {
+    pub fn trash_note(&mut self, path: &str) -> Result<Revision> {
+        self.ensure_writable()?;
+
         let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
 
         // Move to trash (recursive - entire directory)
@@ -291,18 +644,29 @@ impl NotesApi {
 
         // Delete from database (note and all descendants)
         self.db.execute(
-            "DELETE FROM notes WHERE path = ?1 OR path LIKE ?2",
-            params![path, format!("{}/%", path)],
+            &format!(
+                "DELETE FROM notes WHERE path = ?2 OR {}",
+                descendants_clause(1)
+            ),
+            params_from_iter(descendants_params(path)),
         )?;
 
-        Ok(())
+        self.revision()
     }
 
     /// Renames a note and updates all descendant paths.
     ///
     /// Moves the note in filesystem and updates database paths for the note and all children.
     /// Returns an error if new_path already exists or old_path doesn't exist.
-    pub fn rename_note(&mut self, old_path: &str, new_path: &str) -> Result<()> {
+    pub fn rename_note(&mut self, old_path: &str, new_path: &str) -> Result<Revision> {
+        if self.defer(|| PendingOp::Rename {
+            from: old_path.to_string(),
+            to: new_path.to_string(),
+        })? {
+            return Ok(self.deferred_revision());
+        }
+        self.ensure_writable()?;
+
         let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
 
         // Check if old path exists
@@ -325,8 +689,13 @@ impl NotesApi {
         // Get all descendants with their content
         let descendants: Vec<(String, String)> = self
             .db
-            .prepare("SELECT path FROM notes WHERE path LIKE ?1")?
-            .query_map(params![format!("{}/%", old_path)], |row| row.get(0))?
+            .prepare(&format!(
+                "SELECT path FROM notes WHERE {}",
+                descendants_clause(1)
+            ))?
+            .query_map(params_from_iter(descendants_params(old_path)), |row| {
+                row.get(0)
+            })?
             .collect::<std::result::Result<Vec<String>, _>>()?
             .into_iter()
             .map(|path| {
@@ -341,9 +710,10 @@ impl NotesApi {
             let temp_path = format!(
                 "{}_temp_{}",
                 old_path,
-                std::time::SystemTime::now()
+                self.clock
+                    .now()
                     .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
+                    .unwrap_or_default()
                     .as_nanos()
             );
 
@@ -392,9 +762,12 @@ impl NotesApi {
             self.fs.delete_note(old_path)?;
         }
 
-        // Update database: update all paths
+        // Update database: update all paths. A place in the old parent's manual
+        // order means nothing in the new one
         self.db.execute(
-            "UPDATE notes SET path = ?2, parent_path = ?3 WHERE path = ?1",
+            "UPDATE notes SET path = ?2, parent_path = ?3,
+                 sort_index = CASE WHEN parent_path IS ?3 THEN sort_index END
+             WHERE path = ?1",
             params![old_path, new_path, get_parent_path(new_path)],
         )?;
 
@@ -407,7 +780,10 @@ impl NotesApi {
             )?;
         }
 
-        Ok(())
+        // Keep links to the note and its descendants pointing at them
+        self.rewrite_links(old_path, new_path)?;
+
+        self.revision()
     }
 
     /// Checks if a note exists at the specified path.
@@ -424,15 +800,17 @@ impl NotesApi {
 
     // Navigation methods
 
-    /// Returns all direct children of a note, sorted by frecency score.
+    /// Returns all direct children of a note, pinned and manually ordered ones first.
     ///
     /// Returns metadata only (no content) for all notes whose parent is the specified path.
-    /// Children are sorted by frecency score (descending), with alphabetical fallback.
+    /// Pinned children come first, then the manual order (see `set_child_order`), then
+    /// frecency score (descending), with alphabetical fallback.
     /// Useful for displaying note hierarchies and navigation trees.
     pub fn get_children(&self, path: &str) -> Result<Vec<NoteMetadata>> {
-        let mut stmt = self
-            .db
-            .prepare("SELECT id, path, mtime, archived FROM notes WHERE parent_path = ?1 ORDER BY frecency_score DESC, path ASC")?;
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT id, path, mtime, archived FROM notes WHERE parent_path = ?1 ORDER BY {}",
+            CHILD_ORDER
+        ))?;
 
         let children = stmt
             .query_map(params![path], |row| {
@@ -443,6 +821,7 @@ impl NotesApi {
                     path: row.get(1)?,
                     modified,
                     archived: row.get::<_, i64>(3)? != 0,
+                    stats: None,
                 })
             })?
             .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -450,6 +829,71 @@ impl NotesApi {
         Ok(children)
     }
 
+    /// Like `get_children`, but includes each child's cached stats (preview, word count,
+    /// cover, title, tags) and child count, for rich list views.
+    pub fn get_children_with_stats(&self, path: &str) -> Result<Vec<NoteMetadata>> {
+        self.query_with_stats("parent_path = ?1", params![path])
+    }
+
+    /// Like `get_root_notes`, with the same stats as `get_children_with_stats`.
+    pub fn get_root_notes_with_stats(&self) -> Result<Vec<NoteMetadata>> {
+        self.query_with_stats("parent_path IS NULL", params![])
+    }
+
+    fn query_with_stats(
+        &self,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<NoteMetadata>> {
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT id, path, mtime, archived, preview, word_count, cover, private, title,
+                    icon,
+                    (SELECT COUNT(*) FROM notes AS child
+                     WHERE child.parent_path = notes.path AND child.archived = 0),
+                    (SELECT group_concat(tag, ' ') FROM tags WHERE note_id = notes.id),
+                    (SELECT value FROM note_properties
+                     WHERE note_id = notes.id AND key = '{}' AND is_list = 0),
+                    pinned, encrypted
+             FROM notes
+             WHERE {} ORDER BY {}",
+            STATUS_KEY, condition, CHILD_ORDER
+        ))?;
+
+        let notes = stmt
+            .query_map(params, |row| {
+                let mut meta = metadata_from_row(row)?;
+                // Tags never contain spaces
+                let mut tags: Vec<String> = row
+                    .get::<_, Option<String>>(11)?
+                    .unwrap_or_default()
+                    .split(' ')
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                tags.sort();
+                meta.stats = Some(NoteStats {
+                    preview: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                    word_count: row.get::<_, Option<i64>>(5)?.unwrap_or(0),
+                    cover: row.get(6)?,
+                    private: row.get::<_, i64>(7)? != 0,
+                    title: row.get(8)?,
+                    icon: row.get(9)?,
+                    tags,
+                    child_count: row.get::<_, i64>(10)? as usize,
+                    status: row
+                        .get::<_, Option<String>>(12)?
+                        .as_deref()
+                        .and_then(NoteStatus::parse),
+                    pinned: row.get::<_, i64>(13)? != 0,
+                    encrypted: row.get::<_, i64>(14)? != 0,
+                });
+                Ok(meta)
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(notes)
+    }
+
     /// Returns true if the specified path has at least one child note.
     /// Only checks non-archived notes.
     pub fn has_children(&self, path: &str) -> Result<bool> {
@@ -483,6 +927,7 @@ impl NotesApi {
                         path: row.get(1)?,
                         modified,
                         archived: row.get::<_, i64>(3)? != 0,
+                        stats: None,
                     })
                 },
             )
@@ -520,6 +965,7 @@ impl NotesApi {
                 path: row.get(1)?,
                 modified,
                 archived: row.get::<_, i64>(3)? != 0,
+                stats: None,
             })
         })?;
         ancestors.push(note_metadata);
@@ -527,15 +973,28 @@ impl NotesApi {
         Ok(ancestors)
     }
 
-    /// Returns all top-level notes (notes without a parent), sorted by frecency score.
+    /// Like `get_ancestors`, with the same stats as `get_children_with_stats`, so a
+    /// breadcrumb can show each note's title and icon.
+    pub fn get_ancestors_with_stats(&self, path: &str) -> Result<Vec<NoteMetadata>> {
+        let mut ancestors = Vec::new();
+        for ancestor in self.get_ancestors(path)? {
+            ancestors.extend(self.query_with_stats("path = ?1", params![ancestor.path])?);
+        }
+        Ok(ancestors)
+    }
+
+    /// Returns all top-level notes (notes without a parent), in the same order as
+    /// `get_children`.
     ///
     /// Returns metadata for all notes at the root of the hierarchy.
-    /// Notes are sorted by frecency score (descending), with alphabetical fallback.
+    /// Pinned notes come first, then the manual order, then frecency score
+    /// (descending), with alphabetical fallback.
     /// Useful for displaying the main navigation or note list.
     pub fn get_root_notes(&self) -> Result<Vec<NoteMetadata>> {
-        let mut stmt = self
-            .db
-            .prepare("SELECT id, path, mtime, archived FROM notes WHERE parent_path IS NULL ORDER BY frecency_score DESC, path ASC")?;
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT id, path, mtime, archived FROM notes WHERE parent_path IS NULL ORDER BY {}",
+            CHILD_ORDER
+        ))?;
 
         let roots = stmt
             .query_map([], |row| {
@@ -546,6 +1005,7 @@ impl NotesApi {
                     path: row.get(1)?,
                     modified,
                     archived: row.get::<_, i64>(3)? != 0,
+                    stats: None,
                 })
             })?
             .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -559,17 +1019,12 @@ impl NotesApi {
     ///
     /// Moves the note (and all descendants) to parent/_archive/name in filesystem
     /// and sets the archived flag in database. This is a soft delete that can be undone.
-    pub fn archive_note(&mut self, path: &str) -> Result<()> {
+    pub fn archive_note(&mut self, path: &str) -> Result<Revision> {
+        self.ensure_writable()?;
+
         let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
 
-        // Determine archive path
-        let archive_path = if let Some(parent) = get_parent_path(path) {
-            let name = path.split('/').next_back().unwrap();
-            format!("{}/_archive/{}", parent, name)
-        } else {
-            let name = path;
-            format!("_archive/{}", name)
-        };
+        let archive_path = archived_path(path);
 
         // Get content
         let content = self.fs.read_note(path)?;
@@ -577,8 +1032,11 @@ impl NotesApi {
         // Get all descendants
         let descendants: Vec<(String, String)> = self
             .db
-            .prepare("SELECT path FROM notes WHERE path LIKE ?1")?
-            .query_map(params![format!("{}/%", path)], |row| row.get(0))?
+            .prepare(&format!(
+                "SELECT path FROM notes WHERE {}",
+                descendants_clause(1)
+            ))?
+            .query_map(params_from_iter(descendants_params(path)), |row| row.get(0))?
             .collect::<std::result::Result<Vec<String>, _>>()?
             .into_iter()
             .map(|old_path| {
@@ -600,10 +1058,7 @@ impl NotesApi {
         self.fs.delete_note(path)?;
 
         // Update database
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        let now = self.clock.unix_secs();
         self.db.execute(
             "UPDATE notes SET path = ?2, parent_path = ?3, archived = 1, archived_at = ?4 WHERE path = ?1",
             params![path, archive_path, get_parent_path(&archive_path), now]
@@ -617,14 +1072,16 @@ impl NotesApi {
             )?;
         }
 
-        Ok(())
+        self.revision()
     }
 
     /// Restores an archived note to its original location.
     ///
     /// Moves the note from _archive back to its parent directory and clears the archived flag.
     /// The path parameter should be the current archived path (containing /_archive/).
-    pub fn unarchive_note(&mut self, path: &str) -> Result<()> {
+    pub fn unarchive_note(&mut self, path: &str) -> Result<Revision> {
+        self.ensure_writable()?;
+
         let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
 
         // Path should be in _archive
@@ -641,8 +1098,11 @@ impl NotesApi {
         // Get all descendants
         let descendants: Vec<(String, String)> = self
             .db
-            .prepare("SELECT path FROM notes WHERE path LIKE ?1")?
-            .query_map(params![format!("{}/%", path)], |row| row.get(0))?
+            .prepare(&format!(
+                "SELECT path FROM notes WHERE {}",
+                descendants_clause(1)
+            ))?
+            .query_map(params_from_iter(descendants_params(path)), |row| row.get(0))?
             .collect::<std::result::Result<Vec<String>, _>>()?
             .into_iter()
             .map(|old_path| {
@@ -677,7 +1137,7 @@ impl NotesApi {
             )?;
         }
 
-        Ok(())
+        self.revision()
     }
 
     // Search and sync operations
@@ -701,6 +1161,7 @@ impl NotesApi {
                     path: row.get(1)?,
                     modified,
                     archived: row.get::<_, i64>(3)? != 0,
+                    stats: None,
                 })
             })?
             .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -717,6 +1178,7 @@ impl NotesApi {
     /// 3. Alphabetical order as final tiebreaker
     ///
     /// Designed for interactive note pickers where users type partial titles.
+    /// Private notes are left out unless `set_include_private` opted in.
     pub fn fuzzy_search(
         &self,
         query: &str,
@@ -734,10 +1196,12 @@ impl NotesApi {
             let sql = format!(
                 "SELECT id, path, mtime, archived
                  FROM notes
-                 WHERE archived = 0
+                 WHERE archived = 0{}
                  ORDER BY {} DESC, path ASC
                  {}",
-                ranking_column, limit_clause
+                self.private_filter(),
+                ranking_column,
+                limit_clause
             );
 
             let mut stmt = self.db.prepare(&sql)?;
@@ -751,6 +1215,7 @@ impl NotesApi {
                         path: row.get(1)?,
                         modified,
                         archived: row.get::<_, i64>(3)? != 0,
+                        stats: None,
                     })
                 })?
                 .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -770,10 +1235,12 @@ impl NotesApi {
                         ELSE 3
                     END as match_priority
              FROM notes
-             WHERE archived = 0 AND LOWER(path) LIKE LOWER(?2)
+             WHERE archived = 0 AND LOWER(path) LIKE LOWER(?2){}
              ORDER BY match_priority ASC, {} DESC, path ASC
              {}",
-            ranking_column, limit_clause
+            self.private_filter(),
+            ranking_column,
+            limit_clause
         );
 
         let mut stmt = self.db.prepare(&sql)?;
@@ -790,6 +1257,7 @@ impl NotesApi {
                     path: row.get(1)?,
                     modified,
                     archived: row.get::<_, i64>(3)? != 0,
+                    stats: None,
                 })
             })?
             .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -801,13 +1269,15 @@ impl NotesApi {
     ///
     /// Uses FTS5 to search both note paths and content. Returns metadata for matching notes.
     /// Query syntax follows FTS5 conventions (supports phrases, AND/OR, etc.).
+    /// Private notes are left out unless `set_include_private` opted in.
     pub fn search(&self, query: &str) -> Result<Vec<NoteMetadata>> {
-        let mut stmt = self.db.prepare(
+        let mut stmt = self.db.prepare(&format!(
             "SELECT notes.id, notes.path, notes.mtime, notes.archived
              FROM notes_fts
              JOIN notes ON notes_fts.rowid = notes.id
-             WHERE notes_fts MATCH ?1",
-        )?;
+             WHERE notes_fts MATCH ?1{}",
+            self.private_filter()
+        ))?;
 
         let results = stmt
             .query_map(params![query], |row| {
@@ -818,6 +1288,7 @@ impl NotesApi {
                     path: row.get(1)?,
                     modified,
                     archived: row.get::<_, i64>(3)? != 0,
+                    stats: None,
                 })
             })?
             .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -833,23 +1304,26 @@ impl NotesApi {
     /// Returns `true` if the note content actually changed (or was newly created),
     /// `false` if the content hash was already up-to-date.
     pub fn sync_note(&mut self, path: &str) -> Result<bool> {
-        // Get file metadata from filesystem
-        let fs_metadata = self
-            .fs
-            .scan_all()?
-            .into_iter()
-            .find(|m| m.path == path)
-            .ok_or_else(|| Error::NotFound(path.to_string()))?;
+        self.ensure_available()?;
+
+        // Get the modification time from the note itself rather than a vault scan
+        let mtime = self.fs.note_mtime(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Error::NotFound(path.to_string()),
+            _ => e.into(),
+        })?;
 
         // Read content to compute hash
-        let content = self.fs.read_note(path)?;
-        let content_hash = compute_hash(&content);
+        let mut content = self.fs.read_note(path)?;
+        // Encrypted notes are indexed by path only; the ciphertext still goes into the
+        // hash so that changes to it are noticed
+        let ciphertext = is_encrypted(&content).then(|| std::mem::take(&mut content));
+        let mut stats = compute_note_stats(&content);
+        stats.encrypted = ciphertext.is_some();
+        let content = self.with_canvas_labels(path, content);
+
+        let content_hash = compute_hash(ciphertext.as_deref().unwrap_or(&content));
 
-        let mtime = fs_metadata
-            .mtime
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        let mtime = mtime.duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
         let parent_path = get_parent_path(path);
 
         // Check if note exists in database
@@ -861,18 +1335,41 @@ impl NotesApi {
 
         if exists {
             // Get existing ID and content hash
-            let (id, existing_hash): (i64, String) = self.db.query_row(
-                "SELECT id, content_hash FROM notes WHERE path = ?1",
+            let (id, existing_hash, has_stats): (i64, String, bool) = self.db.query_row(
+                "SELECT id, content_hash, word_count IS NOT NULL FROM notes WHERE path = ?1",
                 params![path],
-                |row| Ok((row.get(0)?, row.get(1)?)),
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
             )?;
 
             // Only update if content has changed
             if existing_hash != content_hash {
+                // The indexed text of an encrypted note is empty, so there's nothing to diff
+                let previous: Option<String> = self.db.query_row(
+                    "SELECT CASE WHEN n.encrypted THEN NULL ELSE f.content END
+                     FROM notes n JOIN notes_fts f ON f.rowid = n.id WHERE n.id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )?;
+
                 // Update existing note
                 self.db.execute(
-                    "UPDATE notes SET mtime = ?2, content_hash = ?3, parent_path = ?4 WHERE path = ?1",
-                    params![path, mtime, content_hash, parent_path],
+                    "UPDATE notes SET mtime = ?2, content_hash = ?3, parent_path = ?4,
+                         preview = ?5, word_count = ?6, cover = ?7, private = ?8, title = ?9,
+                         icon = ?10, encrypted = ?11
+                     WHERE path = ?1",
+                    params![
+                        path,
+                        mtime,
+                        content_hash,
+                        parent_path,
+                        stats.preview,
+                        stats.word_count,
+                        stats.cover,
+                        stats.private,
+                        stats.title,
+                        stats.icon,
+                        stats.encrypted
+                    ],
                 )?;
 
                 // Update FTS index - FTS5 requires DELETE + INSERT
@@ -883,16 +1380,56 @@ impl NotesApi {
                     params![id, path, content],
                 )?;
 
+                self.update_derived_indexes(id, &content)?;
+                if let Some(previous) = previous
+                    && !stats.encrypted
+                {
+                    self.record_edit(path, &previous, &content)?;
+                }
+
                 Ok(true) // Content changed
             } else {
+                if !has_stats {
+                    // Backfill stats and derived indexes for notes indexed before they existed
+                    self.db.execute(
+                        "UPDATE notes SET preview = ?2, word_count = ?3, cover = ?4, private = ?5,
+                                          title = ?6, icon = ?7, encrypted = ?8
+                         WHERE id = ?1",
+                        params![
+                            id,
+                            stats.preview,
+                            stats.word_count,
+                            stats.cover,
+                            stats.private,
+                            stats.title,
+                            stats.icon,
+                            stats.encrypted
+                        ],
+                    )?;
+                    self.update_derived_indexes(id, &content)?;
+                }
                 Ok(false) // Content unchanged
             }
         } else {
             // Insert new note
             self.db.execute(
-                "INSERT INTO notes (path, parent_path, mtime, content_hash, archived, archived_at)
-                 VALUES (?1, ?2, ?3, ?4, 0, NULL)",
-                params![path, parent_path, mtime, content_hash],
+                "INSERT INTO notes (path, parent_path, mtime, content_hash, archived, archived_at,
+                                    preview, word_count, cover, private, title, icon,
+                                    encrypted)
+                 VALUES (?1, ?2, ?3, ?4, 0, NULL, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    path,
+                    parent_path,
+                    mtime,
+                    content_hash,
+                    stats.preview,
+                    stats.word_count,
+                    stats.cover,
+                    stats.private,
+                    stats.title,
+                    stats.icon,
+                    stats.encrypted
+                ],
             )?;
 
             // Insert into FTS index
@@ -902,15 +1439,127 @@ impl NotesApi {
                 params![id, path, content],
             )?;
 
+            self.update_derived_indexes(id, &content)?;
+
             Ok(true) // New note created
         }
     }
 
+    /// Refreshes the indexes derived from note content (mentions, etc.) for a single note.
+    fn update_derived_indexes(&mut self, id: i64, content: &str) -> Result<()> {
+        crate::mentions::index_mentions(&self.db, id, content)?;
+        crate::tags::index_tags(&self.db, id, content)?;
+        crate::backlinks::index_links(&self.db, id, content)?;
+        crate::properties::index_properties(&self.db, id, content)?;
+        Ok(())
+    }
+
+    /// Appends the note's canvas labels, which are indexed together with the body.
+    pub(crate) fn with_canvas_labels(&self, path: &str, mut content: String) -> String {
+        if let Some(labels) = crate::canvas::canvas_search_text(self.fs.as_ref(), path) {
+            content.push('\n');
+            content.push_str(&labels);
+        }
+        content
+    }
+
+    /// Syncs a note the filesystem watcher saw change, reporting what happened to it.
+    ///
+    /// Unlike `sync_note`, a note that no longer exists on disk is removed from the
+    /// index instead of failing; its descendants get their own removal events.
+    pub fn sync_change(&mut self, path: &str) -> Result<SyncOutcome> {
+        let indexed = self.note_exists(path)?;
+        match self.sync_note(path) {
+            Ok(true) if indexed => Ok(SyncOutcome::Modified),
+            Ok(true) => Ok(SyncOutcome::Created),
+            Ok(false) => Ok(SyncOutcome::Unchanged),
+            Err(Error::NotFound(_)) if indexed => {
+                self.db
+                    .execute("DELETE FROM notes WHERE path = ?1", params![path])?;
+                Ok(SyncOutcome::Deleted)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Syncs the notes behind a set of changed filesystem paths (e.g. from a watcher of
+    /// the caller's own), instead of rescanning the vault.
+    ///
+    /// Paths are mapped to the note they belong to (`inbox/_index.md`, or whatever the
+    /// vault's `note_file` is, and the `inbox` folder are both `inbox`, as is
+    /// `inbox.md` in the flat layout); paths outside the vault or in hidden folders
+    /// are skipped. Returns each note touched, once, with what happened to it.
+    pub fn sync_paths(&mut self, paths: &[PathBuf]) -> Result<Vec<(String, SyncOutcome)>> {
+        let root = self.notes_root().to_path_buf();
+        let files = self.note_files();
+        let mut notes: Vec<String> = Vec::new();
+        for path in paths {
+            if let Some(note) = crate::watcher::path_to_note_path(&root, &files, path, &DiskProbe)
+                && !notes.contains(&note)
+            {
+                notes.push(note);
+            }
+        }
+
+        let mut outcomes = Vec::with_capacity(notes.len());
+        for note in notes {
+            let outcome = match self.sync_change(&note) {
+                // Neither on disk nor in the index, e.g. a folder that never was a note
+                Err(Error::NotFound(_)) => SyncOutcome::Unchanged,
+                result => result?,
+            };
+            outcomes.push((note, outcome));
+        }
+        Ok(outcomes)
+    }
+
+    /// Follows a note folder renamed outside the app, moving the note and its
+    /// descendants to their new paths in the index.
+    ///
+    /// Falls back to a full `rescan` when the index can't account for the rename, e.g.
+    /// `from` was never indexed or something is already indexed at `to`.
+    pub fn sync_rename(&mut self, from: &str, to: &str) -> Result<()> {
+        self.ensure_available()?;
+
+        if !self.note_exists(from)? || self.note_exists(to)? || self.fs.note_mtime(to).is_err() {
+            return self.rescan();
+        }
+
+        let descendants: Vec<String> = self
+            .db
+            .prepare(&format!(
+                "SELECT path FROM notes WHERE {}",
+                descendants_clause(1)
+            ))?
+            .query_map(params_from_iter(descendants_params(from)), |row| row.get(0))?
+            .collect::<std::result::Result<Vec<String>, _>>()?;
+
+        self.db.execute(
+            "UPDATE notes SET path = ?2, parent_path = ?3 WHERE path = ?1",
+            params![from, to, get_parent_path(to)],
+        )?;
+        for desc_old in &descendants {
+            let desc_new = desc_old.replacen(from, to, 1);
+            self.db.execute(
+                "UPDATE notes SET path = ?2, parent_path = ?3 WHERE path = ?1",
+                params![desc_old, desc_new, get_parent_path(&desc_new)],
+            )?;
+        }
+
+        // The folder may have been edited on its way
+        self.sync_note(to)?;
+        Ok(())
+    }
+
     /// Performs a full filesystem scan and rebuilds the database index.
     ///
     /// Scans all notes in the filesystem, syncs them to the database, and removes
     /// database entries for notes that no longer exist. Use after external filesystem changes.
+    /// Notes moved or renamed outside the app are followed (see `moves`), keeping their
+    /// ids and what hangs off them (frecency, reminders, tags, links).
     pub fn rescan(&mut self) -> Result<()> {
+        self.ensure_available()?;
+
         // Get all notes from filesystem
         let fs_notes = self.fs.scan_all()?;
 
@@ -921,16 +1570,19 @@ impl NotesApi {
             .query_map([], |row| row.get(0))?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
+        // Notes moved outside the app keep their ids instead of being re-created
+        let fs_paths: std::collections::HashSet<_> =
+            fs_notes.iter().map(|n| n.path.as_str()).collect();
+        let moved = self.follow_moves(&db_paths, &fs_paths)?;
+
         // Index or update all filesystem notes
         for fs_note in &fs_notes {
             self.sync_note(&fs_note.path)?;
         }
 
         // Remove notes that no longer exist in filesystem
-        let fs_paths: std::collections::HashSet<_> =
-            fs_notes.iter().map(|n| n.path.as_str()).collect();
         for db_path in db_paths {
-            if !fs_paths.contains(db_path.as_str()) {
+            if !fs_paths.contains(db_path.as_str()) && !moved.contains(&db_path) {
                 self.db
                     .execute("DELETE FROM notes WHERE path = ?1", params![db_path])?;
             }
@@ -945,15 +1597,10 @@ impl NotesApi {
     ///
     /// Formula: access_count * (100 / (days_since_access + 1))
     /// This gives higher scores to frequently accessed notes with a boost for recent access.
-    fn calculate_frecency_score(access_count: i64, last_accessed_at: Option<i64>) -> f64 {
+    fn calculate_frecency_score(access_count: i64, last_accessed_at: Option<i64>, now: i64) -> f64 {
         let access_count = access_count as f64;
 
         if let Some(last_accessed) = last_accessed_at {
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i64;
-
             let seconds_since_access = (now - last_accessed).max(0);
             let days_since_access = (seconds_since_access as f64) / 86400.0; // 86400 seconds in a day
 
@@ -968,10 +1615,7 @@ impl NotesApi {
     /// Records an access to a note and updates its frecency score.
     /// Also propagates the access to all ancestor notes.
     fn record_access(&mut self, path: &str) -> Result<()> {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        let now = self.clock.unix_secs();
 
         // Update the note itself (including direct access count)
         self.update_frecency(path, now, true)?;
@@ -1004,7 +1648,7 @@ impl NotesApi {
         )?;
 
         let new_count = access_count + 1;
-        let new_score = Self::calculate_frecency_score(new_count, Some(access_time));
+        let new_score = Self::calculate_frecency_score(new_count, Some(access_time), access_time);
 
         // Update database
         if is_direct {
@@ -1024,7 +1668,47 @@ impl NotesApi {
 }
 
 // Helper functions
-fn get_parent_path(path: &str) -> Option<String> {
+
+/// Where `archive_note` moves the note at `path`: an `_archive` folder next to it.
+pub(crate) fn archived_path(path: &str) -> String {
+    match get_parent_path(path) {
+        Some(parent) => {
+            let name = path.split('/').next_back().unwrap();
+            format!("{}/{}/{}", parent, ARCHIVE_DIR, name)
+        }
+        None => format!("{}/{}", ARCHIVE_DIR, path),
+    }
+}
+
+/// Maps a row of `id, path, mtime, archived` columns to `NoteMetadata`.
+pub(crate) fn metadata_from_row(row: &rusqlite::Row) -> SqlResult<NoteMetadata> {
+    let mtime: i64 = row.get(2)?;
+    let modified = UNIX_EPOCH + std::time::Duration::from_secs(mtime as u64);
+    Ok(NoteMetadata {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        modified,
+        archived: row.get::<_, i64>(3)? != 0,
+        stats: None,
+    })
+}
+
+/// SQL condition for the descendants of the note whose path is bound as `?n`: `LIKE`
+/// narrows them down, and the `substr` comparison is exact, since `LIKE` ignores case.
+pub(crate) fn descendants_clause(n: usize) -> String {
+    format!(
+        "(path LIKE ?{n} || '/%' ESCAPE '\\' AND substr(path, 1, length(?{m}) + 1) = ?{m} || '/')",
+        n = n,
+        m = n + 1
+    )
+}
+
+/// The parameters for `descendants_clause`: the escaped path, then the path itself.
+pub(crate) fn descendants_params(path: &str) -> [String; 2] {
+    [escape_like(path), path.to_string()]
+}
+
+pub(crate) fn get_parent_path(path: &str) -> Option<String> {
     if path.is_empty() {
         return None;
     }
@@ -1035,7 +1719,7 @@ fn get_parent_path(path: &str) -> Option<String> {
         .map(|p| p.to_string_lossy().to_string())
 }
 
-fn compute_hash(content: &str) -> String {
+pub(crate) fn compute_hash(content: &str) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
@@ -1048,7 +1732,7 @@ fn get_schema_version(conn: &Connection) -> SqlResult<i32> {
     conn.pragma_query_value(None, "user_version", |row| row.get(0))
 }
 
-fn run_migrations(conn: &Connection) -> Result<()> {
+pub(crate) fn run_migrations(conn: &Connection) -> Result<()> {
     let version = get_schema_version(conn)?;
 
     if version < 1 {
@@ -1095,13 +1779,317 @@ fn run_migrations(conn: &Connection) -> Result<()> {
         conn.pragma_update(None, "user_version", 3)?;
     }
 
+    if version < 4 {
+        // Add reminders (keyed by note id so they survive renames)
+        conn.execute_batch(
+            "CREATE TABLE reminders (
+                id INTEGER PRIMARY KEY,
+                note_id INTEGER NOT NULL,
+                remind_at INTEGER NOT NULL,
+                message TEXT NOT NULL,
+                fired INTEGER DEFAULT 0
+             );
+             CREATE INDEX idx_reminders_remind_at ON reminders(remind_at) WHERE fired = 0;",
+        )?;
+        conn.pragma_update(None, "user_version", 4)?;
+    }
+
+    if version < 5 {
+        // Add @mention index
+        conn.execute_batch(
+            "CREATE TABLE mentions (
+                note_id INTEGER NOT NULL,
+                person TEXT NOT NULL,
+                PRIMARY KEY (note_id, person)
+             );
+             CREATE INDEX idx_mentions_person ON mentions(person);
+
+             -- Drop rows keyed by note id when the note leaves the index, so a
+             -- recycled rowid never inherits another note's data
+             CREATE TRIGGER notes_delete_cleanup AFTER DELETE ON notes BEGIN
+                 DELETE FROM notes_fts WHERE rowid = OLD.id;
+                 DELETE FROM reminders WHERE note_id = OLD.id;
+                 DELETE FROM mentions WHERE note_id = OLD.id;
+             END;",
+        )?;
+        conn.pragma_update(None, "user_version", 5)?;
+    }
+
+    if version < 6 {
+        // Add scoped access tokens for external clients (HTTP API, plugins)
+        conn.execute_batch(
+            "CREATE TABLE access_tokens (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                secret TEXT UNIQUE NOT NULL,
+                access TEXT NOT NULL,
+                subtree TEXT,
+                created_at INTEGER NOT NULL
+             );",
+        )?;
+        conn.pragma_update(None, "user_version", 6)?;
+    }
+
+    if version < 7 {
+        // Add change log for tree diffs; rows are written by triggers so every code
+        // path that touches the notes table (API, watcher syncs, rescans) is covered
+        conn.execute_batch(
+            "CREATE TABLE changes (
+                revision INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                path TEXT NOT NULL,
+                old_path TEXT
+             );
+
+             CREATE TRIGGER notes_log_insert AFTER INSERT ON notes BEGIN
+                 INSERT INTO changes (kind, path) VALUES ('added', NEW.path);
+             END;
+             CREATE TRIGGER notes_log_delete AFTER DELETE ON notes BEGIN
+                 INSERT INTO changes (kind, path) VALUES ('removed', OLD.path);
+             END;
+             CREATE TRIGGER notes_log_move AFTER UPDATE OF path ON notes
+             WHEN OLD.path <> NEW.path BEGIN
+                 INSERT INTO changes (kind, path, old_path) VALUES ('moved', NEW.path, OLD.path);
+             END;",
+        )?;
+        conn.pragma_update(None, "user_version", 7)?;
+    }
+
+    if version < 8 {
+        // Log content changes too, so the change log covers every mutation
+        conn.execute_batch(
+            "CREATE TRIGGER notes_log_modify AFTER UPDATE OF content_hash ON notes
+             WHEN OLD.content_hash <> NEW.content_hash BEGIN
+                 INSERT INTO changes (kind, path) VALUES ('modified', NEW.path);
+             END;",
+        )?;
+        conn.pragma_update(None, "user_version", 8)?;
+    }
+
+    if version < 9 {
+        // Cache list-rendering stats; existing rows are backfilled on the next sync
+        conn.execute_batch(
+            "ALTER TABLE notes ADD COLUMN preview TEXT;
+             ALTER TABLE notes ADD COLUMN word_count INTEGER;",
+        )?;
+        conn.pragma_update(None, "user_version", 9)?;
+    }
+
+    if version < 10 {
+        // Persist sidebar tree expansion per note
+        conn.execute_batch(
+            "ALTER TABLE notes ADD COLUMN expanded INTEGER NOT NULL DEFAULT 0;
+             CREATE INDEX idx_notes_expanded ON notes(expanded) WHERE expanded = 1;",
+        )?;
+        conn.pragma_update(None, "user_version", 10)?;
+    }
+
+    if version < 11 {
+        // Recent searches and replaces; searches store an empty replacement
+        conn.execute_batch(
+            "CREATE TABLE search_history (
+                id INTEGER PRIMARY KEY,
+                kind TEXT NOT NULL,
+                query TEXT NOT NULL,
+                replacement TEXT NOT NULL DEFAULT '',
+                used_at INTEGER NOT NULL,
+                UNIQUE(kind, query, replacement)
+             );",
+        )?;
+        conn.pragma_update(None, "user_version", 11)?;
+    }
+
+    if version < 12 {
+        // Hierarchical tag index. Clearing the cached stats makes the next sync
+        // backfill the tags of existing notes.
+        conn.execute_batch(
+            "CREATE TABLE tags (
+                note_id INTEGER NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (note_id, tag)
+             );
+             CREATE INDEX idx_tags_tag ON tags(tag);
+
+             DROP TRIGGER notes_delete_cleanup;
+             CREATE TRIGGER notes_delete_cleanup AFTER DELETE ON notes BEGIN
+                 DELETE FROM notes_fts WHERE rowid = OLD.id;
+                 DELETE FROM reminders WHERE note_id = OLD.id;
+                 DELETE FROM mentions WHERE note_id = OLD.id;
+                 DELETE FROM tags WHERE note_id = OLD.id;
+             END;
+
+             UPDATE notes SET word_count = NULL;",
+        )?;
+        conn.pragma_update(None, "user_version", 12)?;
+    }
+
+    if version < 13 {
+        // Cover image from frontmatter, cached with the stats and backfilled the same way
+        conn.execute_batch(
+            "ALTER TABLE notes ADD COLUMN cover TEXT;
+             UPDATE notes SET word_count = NULL;",
+        )?;
+        conn.pragma_update(None, "user_version", 13)?;
+    }
+
+    if version < 14 {
+        // Backlinks index; links are backfilled with the stats on the next sync
+        conn.execute_batch(
+            "CREATE TABLE links (
+                source_id INTEGER NOT NULL,
+                target TEXT NOT NULL,
+                PRIMARY KEY (source_id, target)
+             );
+             CREATE INDEX idx_links_target ON links(target);
+
+             DROP TRIGGER notes_delete_cleanup;
+             CREATE TRIGGER notes_delete_cleanup AFTER DELETE ON notes BEGIN
+                 DELETE FROM notes_fts WHERE rowid = OLD.id;
+                 DELETE FROM reminders WHERE note_id = OLD.id;
+                 DELETE FROM mentions WHERE note_id = OLD.id;
+                 DELETE FROM tags WHERE note_id = OLD.id;
+                 DELETE FROM links WHERE source_id = OLD.id;
+             END;
+
+             UPDATE notes SET word_count = NULL;",
+        )?;
+        conn.pragma_update(None, "user_version", 14)?;
+    }
+
+    if version < 15 {
+        // Private flag from frontmatter, cached with the stats and backfilled the same way
+        conn.execute_batch(
+            "ALTER TABLE notes ADD COLUMN private INTEGER NOT NULL DEFAULT 0;
+             UPDATE notes SET word_count = NULL;",
+        )?;
+        conn.pragma_update(None, "user_version", 15)?;
+    }
+
+    if version < 16 {
+        // Frontmatter title for list views, cached with the stats and backfilled the same way
+        conn.execute_batch(
+            "ALTER TABLE notes ADD COLUMN title TEXT;
+             UPDATE notes SET word_count = NULL;",
+        )?;
+        conn.pragma_update(None, "user_version", 16)?;
+    }
+
+    if version < 17 {
+        // Frontmatter properties index; backfilled with the stats on the next sync
+        conn.execute_batch(
+            "CREATE TABLE note_properties (
+                note_id INTEGER NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT,
+                is_list INTEGER NOT NULL DEFAULT 0
+             );
+             CREATE INDEX idx_note_properties_note ON note_properties(note_id);
+             CREATE INDEX idx_note_properties_key ON note_properties(key, value COLLATE NOCASE);
+
+             DROP TRIGGER notes_delete_cleanup;
+             CREATE TRIGGER notes_delete_cleanup AFTER DELETE ON notes BEGIN
+                 DELETE FROM notes_fts WHERE rowid = OLD.id;
+                 DELETE FROM reminders WHERE note_id = OLD.id;
+                 DELETE FROM mentions WHERE note_id = OLD.id;
+                 DELETE FROM tags WHERE note_id = OLD.id;
+                 DELETE FROM links WHERE source_id = OLD.id;
+                 DELETE FROM note_properties WHERE note_id = OLD.id;
+             END;
+
+             UPDATE notes SET word_count = NULL;",
+        )?;
+        conn.pragma_update(None, "user_version", 17)?;
+    }
+
+    if version < 18 {
+        // Manual order and pins for children (see `ordering`)
+        conn.execute_batch(
+            "ALTER TABLE notes ADD COLUMN sort_index INTEGER;
+             ALTER TABLE notes ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;",
+        )?;
+        conn.pragma_update(None, "user_version", 18)?;
+    }
+
+    if version < 19 {
+        // Log of opened notes (see `recent`)
+        conn.execute_batch(
+            "CREATE TABLE recent_opens (
+                id INTEGER PRIMARY KEY,
+                note_id INTEGER NOT NULL UNIQUE,
+                opened_at INTEGER NOT NULL
+             );
+
+             DROP TRIGGER notes_delete_cleanup;
+             CREATE TRIGGER notes_delete_cleanup AFTER DELETE ON notes BEGIN
+                 DELETE FROM notes_fts WHERE rowid = OLD.id;
+                 DELETE FROM reminders WHERE note_id = OLD.id;
+                 DELETE FROM mentions WHERE note_id = OLD.id;
+                 DELETE FROM tags WHERE note_id = OLD.id;
+                 DELETE FROM links WHERE source_id = OLD.id;
+                 DELETE FROM note_properties WHERE note_id = OLD.id;
+                 DELETE FROM recent_opens WHERE note_id = OLD.id;
+             END;",
+        )?;
+        conn.pragma_update(None, "user_version", 19)?;
+    }
+
+    if version < 20 {
+        // Starred notes (see `favorites`)
+        conn.execute_batch(
+            "CREATE TABLE favorites (
+                id INTEGER PRIMARY KEY,
+                note_id INTEGER NOT NULL UNIQUE,
+                added_at INTEGER NOT NULL
+             );
+
+             DROP TRIGGER notes_delete_cleanup;
+             CREATE TRIGGER notes_delete_cleanup AFTER DELETE ON notes BEGIN
+                 DELETE FROM notes_fts WHERE rowid = OLD.id;
+                 DELETE FROM reminders WHERE note_id = OLD.id;
+                 DELETE FROM mentions WHERE note_id = OLD.id;
+                 DELETE FROM tags WHERE note_id = OLD.id;
+                 DELETE FROM links WHERE source_id = OLD.id;
+                 DELETE FROM note_properties WHERE note_id = OLD.id;
+                 DELETE FROM recent_opens WHERE note_id = OLD.id;
+                 DELETE FROM favorites WHERE note_id = OLD.id;
+             END;",
+        )?;
+        conn.pragma_update(None, "user_version", 20)?;
+    }
+
+    if version < 21 {
+        // Frontmatter icon for breadcrumbs and window titles, backfilled like the title
+        conn.execute_batch(
+            "ALTER TABLE notes ADD COLUMN icon TEXT;
+             UPDATE notes SET word_count = NULL;",
+        )?;
+        conn.pragma_update(None, "user_version", 21)?;
+    }
+
+    if version < 22 {
+        // Notes encrypted at rest (see `encryption`); no note is encrypted yet
+        conn.execute_batch("ALTER TABLE notes ADD COLUMN encrypted INTEGER NOT NULL DEFAULT 0;")?;
+        conn.pragma_update(None, "user_version", 22)?;
+    }
+
+    if version < 23 {
+        // Word-diff summaries of content changes (see `edit_summary`); older entries
+        // have none
+        conn.execute_batch(
+            "ALTER TABLE changes ADD COLUMN words_added INTEGER;
+             ALTER TABLE changes ADD COLUMN words_removed INTEGER;
+             ALTER TABLE changes ADD COLUMN headings TEXT;",
+        )?;
+        conn.pragma_update(None, "user_version", 23)?;
+    }
+
     // Future migrations go here
-    // if version < 4 { ... }
+    // if version < 24 { ... }
 
     Ok(())
 }
 
-fn verify_schema(conn: &Connection) -> Result<()> {
+pub(crate) fn verify_schema(conn: &Connection) -> Result<()> {
     // Check that notes table exists
     let notes_exists: bool = conn.query_row(
         "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='notes'",
@@ -1130,8 +2118,58 @@ fn verify_schema(conn: &Connection) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
     use tempfile::TempDir;
 
+    /// Latest schema version produced by `run_migrations`
+    const SCHEMA_VERSION: i32 = 23;
+
+    #[test]
+    fn test_message_keys_list_every_error() {
+        let errors = [
+            Error::Io(std::io::ErrorKind::Other.into()),
+            Error::Database(rusqlite::Error::QueryReturnedNoRows),
+            Error::DatabaseCorrupted,
+            Error::NotFound(String::new()),
+            Error::AlreadyExists(String::new()),
+            Error::ParentNotFound(String::new()),
+            Error::InvalidInput(String::new()),
+            Error::ReadOnly,
+            Error::PermissionDenied(String::new()),
+            Error::VaultUnavailable,
+            Error::Conflict {
+                path: String::new(),
+                content: String::new(),
+            },
+            Error::Encrypted(String::new()),
+            Error::Git(String::new()),
+            Error::Remote(String::new()),
+        ];
+        // No wildcard arm, so a new variant doesn't compile until it gets the next
+        // index here; it then goes into `errors` and `MESSAGE_KEYS` at that index
+        let variant = |error: &Error| match error {
+            Error::Io(_) => 0,
+            Error::Database(_) => 1,
+            Error::DatabaseCorrupted => 2,
+            Error::NotFound(_) => 3,
+            Error::AlreadyExists(_) => 4,
+            Error::ParentNotFound(_) => 5,
+            Error::InvalidInput(_) => 6,
+            Error::ReadOnly => 7,
+            Error::PermissionDenied(_) => 8,
+            Error::VaultUnavailable => 9,
+            Error::Conflict { .. } => 10,
+            Error::Encrypted(_) => 11,
+            Error::Git(_) => 12,
+            Error::Remote(_) => 13,
+        };
+        let variants: Vec<usize> = errors.iter().map(variant).collect();
+        assert_eq!(variants, (0..Error::MESSAGE_KEYS.len()).collect::<Vec<_>>());
+
+        let keys: Vec<&str> = errors.iter().map(Error::message_key).collect();
+        assert_eq!(keys, Error::MESSAGE_KEYS);
+    }
+
     #[test]
     fn test_create_new_database() {
         let temp_dir = TempDir::new().unwrap();
@@ -1143,7 +2181,7 @@ mod tests {
 
         // Verify schema version (should be latest)
         let version = get_schema_version(&api.db).unwrap();
-        assert_eq!(version, 3);
+        assert_eq!(version, SCHEMA_VERSION);
     }
 
     #[test]
@@ -1157,7 +2195,7 @@ mod tests {
         // Open existing database
         let api2 = NotesApi::new(temp_dir.path()).unwrap();
         let version = get_schema_version(&api2.db).unwrap();
-        assert_eq!(version, 3);
+        assert_eq!(version, SCHEMA_VERSION);
     }
 
     #[test]
@@ -1224,9 +2262,9 @@ mod tests {
         // Create a corrupted database (invalid data)
         std::fs::write(&db_path, b"corrupted data").unwrap();
 
-        // Attempt to open should fail
-        let result = NotesApi::new(temp_dir.path());
-        assert!(result.is_err());
+        // The index is derived data, so opening discards and recreates it
+        let api = NotesApi::new(temp_dir.path()).unwrap();
+        assert!(api.startup_report().index_rebuilt);
     }
 
     #[test]
@@ -1238,7 +2276,8 @@ mod tests {
         let conn = Connection::open(&db_path).unwrap();
         conn.execute("CREATE TABLE wrong_table (id INTEGER)", [])
             .unwrap();
-        conn.pragma_update(None, "user_version", 3).unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .unwrap();
         drop(conn);
 
         // Attempt to open should fail verification
@@ -1306,6 +2345,101 @@ mod tests {
         assert_eq!(note.content, "Updated");
     }
 
+    #[test]
+    fn test_read_only_rejects_mutations() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let mut api = NotesApi::new(temp_dir.path()).unwrap();
+            api.create_note("test").unwrap();
+            api.save_note("test", "Original").unwrap();
+        }
+
+        let mut api = NotesApi::new_read_only(temp_dir.path()).unwrap();
+        assert!(api.is_read_only());
+        assert!(!api.capabilities().editable);
+
+        assert!(matches!(
+            api.save_note("test", "Changed"),
+            Err(Error::ReadOnly)
+        ));
+        assert!(matches!(api.create_note("other"), Err(Error::ReadOnly)));
+        assert!(matches!(
+            api.rename_note("test", "renamed"),
+            Err(Error::ReadOnly)
+        ));
+        assert!(matches!(api.delete_note("test"), Err(Error::ReadOnly)));
+
+        assert_eq!(api.get_note("test").unwrap().content, "Original");
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("test/_index.md")).unwrap(),
+            "Original"
+        );
+    }
+
+    #[test]
+    fn test_read_only_still_indexes_external_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new_read_only(temp_dir.path()).unwrap();
+
+        std::fs::create_dir_all(temp_dir.path().join("external")).unwrap();
+        std::fs::write(temp_dir.path().join("external/_index.md"), "from disk").unwrap();
+        api.sync_note("external").unwrap();
+
+        assert_eq!(api.search("disk").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_get_children_with_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("parent").unwrap();
+        api.create_note("parent/child").unwrap();
+        api.save_note("parent/child", "# Child\nThree more words")
+            .unwrap();
+
+        assert!(api.get_children("parent").unwrap()[0].stats.is_none());
+
+        let children = api.get_children_with_stats("parent").unwrap();
+        let stats = children[0].stats.as_ref().unwrap();
+        assert_eq!(stats.preview, "Child Three more words");
+        assert_eq!(stats.word_count, 4);
+        assert_eq!(children[0].title(), "child");
+
+        api.save_note("parent", "---\ntitle: Parent note\n---\n#project")
+            .unwrap();
+        let roots = api.get_root_notes_with_stats().unwrap();
+        let stats = roots[0].stats.as_ref().unwrap();
+        assert_eq!(roots[0].title(), "Parent note");
+        assert_eq!(stats.tags, ["project"]);
+        assert_eq!(stats.child_count, 1);
+    }
+
+    #[test]
+    fn test_missing_root_blocks_writes_and_keeps_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("vault");
+        let mut api = NotesApi::new(&root).unwrap();
+        api.create_note("test").unwrap();
+
+        // Simulate the drive being unplugged and plugged back in
+        let moved = temp_dir.path().join("unplugged");
+        std::fs::rename(&root, &moved).unwrap();
+
+        assert!(!api.is_available());
+        assert!(!api.capabilities().editable);
+        assert!(matches!(
+            api.save_note("test", "x"),
+            Err(Error::VaultUnavailable)
+        ));
+        assert!(matches!(api.rescan(), Err(Error::VaultUnavailable)));
+
+        std::fs::rename(&moved, &root).unwrap();
+        assert!(api.is_available());
+        api.rescan().unwrap();
+        assert!(api.note_exists("test").unwrap());
+    }
+
     #[test]
     fn test_delete_note() {
         let temp_dir = TempDir::new().unwrap();
@@ -1331,6 +2465,38 @@ mod tests {
         assert!(!api.note_exists("parent/child").unwrap());
     }
 
+    #[test]
+    fn test_wildcards_in_paths_match_literally() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in ["a_b", "a_b/own", "axb", "axb/child", "a%", "a%/own", "ab"] {
+            api.create_note(path).unwrap();
+        }
+        api.create_note("ab/other").unwrap();
+
+        // `_` and `%` would match `axb/child` and `ab/other` as LIKE patterns
+        api.rename_note("a_b", "renamed").unwrap();
+        assert!(api.note_exists("renamed/own").unwrap());
+        assert!(api.note_exists("axb/child").unwrap());
+
+        api.create_note("p").unwrap();
+        api.rename_note("a%", "p/a%").unwrap();
+        api.rename_note("ab", "p/ab").unwrap();
+        api.archive_note("p/a%").unwrap();
+        assert!(api.note_exists("p/ab/other").unwrap());
+        assert!(api.note_exists("p/_archive/a%/own").unwrap());
+        api.unarchive_note("p/_archive/a%").unwrap();
+        api.rename_note("p/a%", "a%").unwrap();
+        api.rename_note("p/ab", "ab").unwrap();
+        assert!(api.note_exists("a%/own").unwrap());
+
+        api.create_note("a_b").unwrap();
+        api.delete_note("a_b").unwrap();
+        api.delete_note("a%").unwrap();
+        assert!(api.note_exists("axb/child").unwrap());
+        assert!(api.note_exists("ab/other").unwrap());
+    }
+
     #[test]
     fn test_trash_note() {
         let temp_dir = TempDir::new().unwrap();
@@ -1598,6 +2764,13 @@ mod tests {
         assert_eq!(ancestors[0].path, "a");
         assert_eq!(ancestors[1].path, "a/b");
         assert_eq!(ancestors[2].path, "a/b/c");
+
+        api.save_note("a", "---\nicon: 📁\n---\n").unwrap();
+        let ancestors = api.get_ancestors_with_stats("a/b/c").unwrap();
+        assert_eq!(ancestors.len(), 3);
+        let stats = ancestors[0].stats.as_ref().unwrap();
+        assert_eq!(stats.icon.as_deref(), Some("📁"));
+        assert_eq!(ancestors[2].title(), "c");
     }
 
     #[test]
@@ -1735,6 +2908,55 @@ mod tests {
         assert!(api.note_exists("note1").unwrap());
     }
 
+    #[test]
+    fn test_sync_paths_syncs_only_touched_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let mut api = NotesApi::new(root).unwrap();
+        api.create_note("inbox").unwrap();
+
+        fs::write(root.join("inbox/_index.md"), "edited elsewhere").unwrap();
+        fs::create_dir_all(root.join("drafts")).unwrap();
+        fs::write(root.join("drafts/_index.md"), "new").unwrap();
+        fs::create_dir_all(root.join(".trash/old")).unwrap();
+        fs::write(root.join(".trash/old/_index.md"), "gone").unwrap();
+
+        let outcomes = api
+            .sync_paths(&[
+                root.join("inbox/_index.md"),
+                root.join("inbox"),
+                root.join("drafts/_index.md"),
+                root.join(".trash/old/_index.md"),
+                PathBuf::from("/elsewhere/_index.md"),
+            ])
+            .unwrap();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("inbox".to_string(), SyncOutcome::Modified),
+                ("drafts".to_string(), SyncOutcome::Created),
+            ]
+        );
+        assert!(!api.note_exists(".trash/old").unwrap());
+    }
+
+    #[test]
+    fn test_sync_rename_moves_index_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let mut api = NotesApi::new(root).unwrap();
+        api.create_note("old").unwrap();
+        api.create_note("old/child").unwrap();
+        let id = api.get_note_internal("old/child").unwrap().id;
+
+        fs::rename(root.join("old"), root.join("new")).unwrap();
+        api.sync_rename("old", "new").unwrap();
+
+        assert!(!api.note_exists("old").unwrap());
+        assert_eq!(api.get_note_internal("new/child").unwrap().id, id);
+        assert_eq!(api.get_children("new").unwrap()[0].path, "new/child");
+    }
+
     #[test]
     fn test_frecency_get_note_updates_score() {
         let temp_dir = TempDir::new().unwrap();
@@ -1861,25 +3083,22 @@ mod tests {
     #[test]
     fn test_frecency_score_calculation() {
         // Test the calculation directly
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        let now = 1_700_000_000;
 
         // Recent access should have high score
-        let score_recent = NotesApi::calculate_frecency_score(10, Some(now));
+        let score_recent = NotesApi::calculate_frecency_score(10, Some(now), now);
         assert!(score_recent > 900.0); // 10 * (100 / ~1) ≈ 1000
 
         // Access from 10 days ago should have lower score
         let ten_days_ago = now - (10 * 86400);
-        let score_old = NotesApi::calculate_frecency_score(10, Some(ten_days_ago));
+        let score_old = NotesApi::calculate_frecency_score(10, Some(ten_days_ago), now);
         assert!(score_old < 100.0); // 10 * (100 / 11) ≈ 90
 
         // More accesses should increase score
         assert!(score_recent > score_old);
 
         // No access history should give zero score
-        let score_none = NotesApi::calculate_frecency_score(0, None);
+        let score_none = NotesApi::calculate_frecency_score(0, None, now);
         assert_eq!(score_none, 0.0);
     }
 
@@ -0,0 +1,409 @@
+//! Edits made while the vault is unavailable.
+//!
+//! A vault on a network or cloud-synced drive can drop away for a while. Once an
+//! offline queue is set (see `set_offline_queue`), creating, saving, and renaming notes
+//! in that time doesn't fail: each mutation is appended to a journal outside the vault
+//! and flushed to disk, and `replay_pending` applies them in order once the notes root
+//! is back (the app queues `BackgroundTask::ReplayPending` on `VaultAvailable`).
+//! `pending_operations` lists what is still waiting, so the UI can show the vault as
+//! not synced.
+//!
+//! Replay is last-writer-wins: a queued save replaces whatever the note holds by then.
+//! An edit is only queued if it would have succeeded against the index plus the edits
+//! queued before it. One that still fails for good on replay (say its parent was
+//! deleted elsewhere) is set aside in a second file instead of holding up the rest;
+//! `failed_operations` lists those so their text isn't lost. Encrypted notes are never
+//! queued, since their text would sit outside the vault unencrypted.
+
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::changes::Revision;
+use crate::notes::{Error, Note, NotesApi, Result, compute_hash, get_parent_path};
+
+/// A mutation waiting for the vault to come back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PendingOp {
+    Create { path: String },
+    Save { path: String, content: String },
+    Rename { from: String, to: String },
+}
+
+impl PendingOp {
+    fn apply(&self, api: &mut NotesApi) -> Result<()> {
+        match self {
+            PendingOp::Create { path } => match api.create_note(path) {
+                // Created elsewhere while this device was offline
+                Ok(_) | Err(Error::AlreadyExists(_)) => Ok(()),
+                Err(e) => Err(e),
+            },
+            PendingOp::Save { path, content } => {
+                // Deleted elsewhere meanwhile: the queued text wins
+                if !api.note_exists(path)? {
+                    api.create_note(path)?;
+                }
+                api.save_note(path, content).map(drop)
+            }
+            PendingOp::Rename { from, to } => api.rename_note(from, to).map(drop),
+        }
+    }
+}
+
+/// Whether retrying could help: the vault went away again, or the disk had a hiccup.
+/// Anything else (a missing parent, a name taken meanwhile) will fail the same way.
+fn is_transient(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::VaultUnavailable | Error::Io(_) | Error::Database(_) | Error::ReadOnly
+    )
+}
+
+/// Where ops that failed for good go, next to the queue.
+fn failed_path(queue: &Path) -> PathBuf {
+    queue.with_extension("failed.jsonl")
+}
+
+fn read_ops(path: &Path) -> Result<Vec<PendingOp>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut ops = Vec::new();
+    for line in BufReader::new(file).lines() {
+        // A line torn by a crash mid-append is dropped
+        if let Ok(op) = serde_json::from_str(&line?) {
+            ops.push(op);
+        }
+    }
+    Ok(ops)
+}
+
+fn append_op(path: &Path, op: &PendingOp) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(
+        file,
+        "{}",
+        serde_json::to_string(op).map_err(io::Error::other)?
+    )?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn write_ops(queue: &Path, ops: &[PendingOp]) -> Result<()> {
+    let mut lines = String::new();
+    for op in ops {
+        lines.push_str(&serde_json::to_string(op).map_err(io::Error::other)?);
+        lines.push('\n');
+    }
+    fs::write(queue, lines)?;
+    Ok(())
+}
+
+impl NotesApi {
+    /// Queues edits made while the vault is unavailable in a journal in `dir`, which
+    /// must be outside the vault. Each vault gets its own file there.
+    pub fn set_offline_queue<P: AsRef<Path>>(&mut self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let name = compute_hash(&self.notes_root().to_string_lossy());
+        self.offline_queue = Some(dir.join(format!("{}.jsonl", name)));
+        Ok(())
+    }
+
+    /// Edits waiting for the vault to come back, oldest first.
+    pub fn pending_operations(&self) -> Result<Vec<PendingOp>> {
+        match &self.offline_queue {
+            Some(queue) => read_ops(queue),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Queued edits that replay couldn't apply and set aside, oldest first.
+    pub fn failed_operations(&self) -> Result<Vec<PendingOp>> {
+        match &self.offline_queue {
+            Some(queue) => read_ops(&failed_path(queue)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Queues `op` when the vault is unavailable and an offline queue is set; returns
+    /// whether it was queued.
+    pub(crate) fn defer(&self, op: impl FnOnce() -> PendingOp) -> Result<bool> {
+        let Some(queue) = &self.offline_queue else {
+            return Ok(false);
+        };
+        if self.is_read_only() || self.is_available() {
+            return Ok(false);
+        }
+        let op = op();
+        if let PendingOp::Save { path, .. } = &op
+            && self.is_note_encrypted(path).unwrap_or(true)
+        {
+            return Ok(false);
+        }
+
+        self.check_pending(&op, &read_ops(queue)?)?;
+        append_op(queue, &op)?;
+        Ok(true)
+    }
+
+    /// Fails the way the edit itself would if it can't apply on top of the index and
+    /// the edits `queued` before it.
+    fn check_pending(&self, op: &PendingOp, queued: &[PendingOp]) -> Result<()> {
+        // What the queued edits add to and take away from the index
+        let mut added = HashSet::new();
+        let mut removed = HashSet::new();
+        for earlier in queued {
+            match earlier {
+                PendingOp::Create { path } | PendingOp::Save { path, .. } => {
+                    removed.remove(path.as_str());
+                    added.insert(path.as_str());
+                }
+                PendingOp::Rename { from, to } => {
+                    added.remove(from.as_str());
+                    removed.insert(from.as_str());
+                    removed.remove(to.as_str());
+                    added.insert(to.as_str());
+                }
+            }
+        }
+        let exists = |path: &str| -> Result<bool> {
+            Ok(added.contains(path) || (!removed.contains(path) && self.note_exists(path)?))
+        };
+        let check_parent = |path: &str| -> Result<()> {
+            match get_parent_path(path) {
+                Some(parent) if !exists(&parent)? => Err(Error::ParentNotFound(parent)),
+                _ => Ok(()),
+            }
+        };
+
+        match op {
+            PendingOp::Create { path } => check_parent(path),
+            PendingOp::Save { path, .. } if exists(path)? => Ok(()),
+            PendingOp::Save { path, .. } => Err(Error::NotFound(path.clone())),
+            PendingOp::Rename { from, .. } if !exists(from)? => Err(Error::NotFound(from.clone())),
+            PendingOp::Rename { to, .. } if exists(to)? => Err(Error::AlreadyExists(to.clone())),
+            PendingOp::Rename { to, .. } => check_parent(to),
+        }
+    }
+
+    /// What a deferred save or rename returns: the last revision the index knows.
+    pub(crate) fn deferred_revision(&self) -> Revision {
+        self.revision().unwrap_or_default()
+    }
+
+    /// What a deferred `create_note` returns; the note isn't indexed until replay.
+    pub(crate) fn deferred_note(&self, path: &str) -> Note {
+        Note {
+            id: 0,
+            path: path.to_string(),
+            content: String::new(),
+            modified: self.clock.now(),
+            revision: self.deferred_revision(),
+        }
+    }
+
+    /// Applies the queued edits in order and returns how many were applied.
+    ///
+    /// An edit that fails for good is moved to `failed_operations` and replay goes on.
+    /// One that could succeed later (the vault went away again) stops the replay,
+    /// keeping it and the rest queued for the next attempt.
+    pub fn replay_pending(&mut self) -> Result<usize> {
+        let ops = self.pending_operations()?;
+        if ops.is_empty() {
+            return Ok(0);
+        }
+        self.ensure_writable()?;
+
+        // Without a queue nothing is deferred, so an edit that meets an unavailable
+        // vault fails here instead of being queued again behind the replay's back
+        let Some(queue) = self.offline_queue.take() else {
+            return Ok(0);
+        };
+        let result = self.replay_ops(&queue, &ops);
+        self.offline_queue = Some(queue);
+        result
+    }
+
+    fn replay_ops(&mut self, queue: &Path, ops: &[PendingOp]) -> Result<usize> {
+        let mut applied = 0;
+        for (i, op) in ops.iter().enumerate() {
+            match op.apply(self) {
+                Ok(()) => applied += 1,
+                Err(e) if is_transient(&e) => {
+                    write_ops(queue, &ops[i..])?;
+                    return Err(e);
+                }
+                Err(_) => append_op(&failed_path(queue), op)?,
+            }
+        }
+        fs::remove_file(queue)?;
+        Ok(applied)
+    }
+
+    /// Drops every queued edit, and those set aside as failed, without applying them.
+    pub fn discard_pending(&mut self) -> Result<()> {
+        if let Some(queue) = &self.offline_queue {
+            for path in [queue.clone(), failed_path(queue)] {
+                match fs::remove_file(path) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_edits_are_queued_while_unavailable() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("vault");
+        let away = temp_dir.path().join("away");
+        fs::create_dir(&root).unwrap();
+        let mut api = NotesApi::new(&root).unwrap();
+        api.set_offline_queue(queue_dir.path()).unwrap();
+        api.create_note("inbox").unwrap();
+
+        fs::rename(&root, &away).unwrap();
+        assert!(api.capabilities().editable);
+        api.save_note("inbox", "written offline").unwrap();
+        api.create_note("later").unwrap();
+        api.rename_note("later", "renamed").unwrap();
+        assert_eq!(
+            api.pending_operations().unwrap(),
+            vec![
+                PendingOp::Save {
+                    path: "inbox".to_string(),
+                    content: "written offline".to_string(),
+                },
+                PendingOp::Create {
+                    path: "later".to_string(),
+                },
+                PendingOp::Rename {
+                    from: "later".to_string(),
+                    to: "renamed".to_string(),
+                },
+            ]
+        );
+
+        // Still away: nothing is lost
+        assert!(matches!(api.replay_pending(), Err(Error::VaultUnavailable)));
+        assert_eq!(api.pending_operations().unwrap().len(), 3);
+
+        fs::rename(&away, &root).unwrap();
+        assert_eq!(api.replay_pending().unwrap(), 3);
+        assert!(api.pending_operations().unwrap().is_empty());
+        assert_eq!(api.get_note("inbox").unwrap().content, "written offline");
+        assert!(api.note_exists("renamed").unwrap());
+        assert!(!api.note_exists("later").unwrap());
+    }
+
+    #[test]
+    fn test_failed_op_is_set_aside() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("vault");
+        fs::create_dir(&root).unwrap();
+        let mut api = NotesApi::new(&root).unwrap();
+        api.set_offline_queue(queue_dir.path()).unwrap();
+
+        let queue = api.offline_queue.clone().unwrap();
+        let ops = vec![
+            PendingOp::Create {
+                path: "a".to_string(),
+            },
+            PendingOp::Rename {
+                from: "missing".to_string(),
+                to: "b".to_string(),
+            },
+            PendingOp::Create {
+                path: "c".to_string(),
+            },
+        ];
+        write_ops(&queue, &ops).unwrap();
+
+        assert_eq!(api.replay_pending().unwrap(), 2);
+        assert!(api.note_exists("a").unwrap());
+        assert!(api.note_exists("c").unwrap());
+        assert!(api.pending_operations().unwrap().is_empty());
+        assert_eq!(api.failed_operations().unwrap(), ops[1..2]);
+
+        api.discard_pending().unwrap();
+        assert!(api.failed_operations().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_edits_that_cant_apply_are_not_queued() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("vault");
+        let away = temp_dir.path().join("away");
+        fs::create_dir(&root).unwrap();
+        let mut api = NotesApi::new(&root).unwrap();
+        api.set_offline_queue(queue_dir.path()).unwrap();
+        api.create_note("inbox").unwrap();
+
+        fs::rename(&root, &away).unwrap();
+        assert!(matches!(
+            api.create_note("nope/child"),
+            Err(Error::ParentNotFound(_))
+        ));
+        assert!(matches!(
+            api.rename_note("missing", "b"),
+            Err(Error::NotFound(_))
+        ));
+        // Earlier queued edits count: the new parent makes the child valid
+        api.create_note("projects").unwrap();
+        api.create_note("projects/garden").unwrap();
+        api.save_note("inbox", "important").unwrap();
+        assert_eq!(api.pending_operations().unwrap().len(), 3);
+
+        fs::rename(&away, &root).unwrap();
+        assert_eq!(api.replay_pending().unwrap(), 3);
+        assert_eq!(api.get_note("inbox").unwrap().content, "important");
+        assert!(api.note_exists("projects/garden").unwrap());
+    }
+
+    #[test]
+    fn test_vault_dropping_during_replay_keeps_edits_queued() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("vault");
+        let away = temp_dir.path().join("away");
+        fs::create_dir(&root).unwrap();
+        let mut api = NotesApi::new(&root).unwrap();
+        api.set_offline_queue(queue_dir.path()).unwrap();
+        api.create_note("inbox").unwrap();
+
+        let queue = api.offline_queue.clone().unwrap();
+        let ops = vec![PendingOp::Save {
+            path: "inbox".to_string(),
+            content: "written offline".to_string(),
+        }];
+        write_ops(&queue, &ops).unwrap();
+
+        // As replay_pending runs it, once the vault is gone again
+        fs::rename(&root, &away).unwrap();
+        api.offline_queue = None;
+        assert!(matches!(
+            api.replay_ops(&queue, &ops),
+            Err(Error::VaultUnavailable)
+        ));
+        api.offline_queue = Some(queue);
+        assert_eq!(api.pending_operations().unwrap(), ops);
+        assert!(api.failed_operations().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! Manual order and pinning of a note's children.
+//!
+//! Children list pinned notes first, then the ones placed with `set_child_order`,
+//! then the rest by frecency as before. The order lives in the index (the
+//! `sort_index` and `pinned` columns of the v18 migration) like visit counts do;
+//! moving a note to another parent drops its place but keeps its pin.
+
+use std::collections::HashSet;
+
+use rusqlite::params;
+
+use crate::notes::{Error, NotesApi, Result, get_parent_path};
+
+/// `ORDER BY` clause for listing the children of one parent (or the root notes).
+pub(crate) const CHILD_ORDER: &str =
+    "pinned DESC, sort_index IS NULL, sort_index, frecency_score DESC, path ASC";
+
+impl NotesApi {
+    /// Puts the children of `parent` (empty for the root notes) in the order of
+    /// `ordered_paths`. Children left out go after them, by frecency as before.
+    ///
+    /// Fails with `InvalidInput` if a path isn't a child of `parent` or is listed
+    /// twice. Pinned children stay first; the order applies within the pinned and
+    /// unpinned groups alike.
+    pub fn set_child_order(&mut self, parent: &str, ordered_paths: &[String]) -> Result<()> {
+        self.ensure_writable()?;
+
+        if !parent.is_empty() && !self.note_exists(parent)? {
+            return Err(Error::NotFound(parent.to_string()));
+        }
+        let mut seen = HashSet::new();
+        for path in ordered_paths {
+            if get_parent_path(path).unwrap_or_default() != parent || !self.note_exists(path)? {
+                return Err(Error::InvalidInput(format!(
+                    "{} is not a child of {}",
+                    path, parent
+                )));
+            }
+            if !seen.insert(path) {
+                return Err(Error::InvalidInput(format!("{} is listed twice", path)));
+            }
+        }
+
+        // Root notes have no parent path
+        let parent = (!parent.is_empty()).then_some(parent);
+        self.db.execute_batch("SAVEPOINT child_order")?;
+        let result = (|| -> Result<()> {
+            self.db.execute(
+                "UPDATE notes SET sort_index = NULL WHERE parent_path IS ?1",
+                params![parent],
+            )?;
+            for (index, path) in ordered_paths.iter().enumerate() {
+                self.db.execute(
+                    "UPDATE notes SET sort_index = ?2 WHERE path = ?1",
+                    params![path, index as i64],
+                )?;
+            }
+            Ok(())
+        })();
+        match result {
+            Ok(()) => self.db.execute_batch("RELEASE child_order")?,
+            Err(e) => {
+                self.db
+                    .execute_batch("ROLLBACK TO child_order; RELEASE child_order")?;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps the note at the top of its parent's children.
+    pub fn pin_note(&mut self, path: &str) -> Result<()> {
+        self.set_pinned(path, true)
+    }
+
+    /// Undoes `pin_note`; the note goes back to its manual or frecency place.
+    pub fn unpin_note(&mut self, path: &str) -> Result<()> {
+        self.set_pinned(path, false)
+    }
+
+    fn set_pinned(&mut self, path: &str, pinned: bool) -> Result<()> {
+        self.ensure_writable()?;
+        let updated = self.db.execute(
+            "UPDATE notes SET pinned = ?2 WHERE path = ?1",
+            params![path, pinned as i64],
+        )?;
+        if updated == 0 {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in ["list", "list/a", "list/b", "list/c", "list/d", "other"] {
+            api.create_note(path).unwrap();
+        }
+        (temp_dir, api)
+    }
+
+    fn children(api: &NotesApi, parent: &str) -> Vec<String> {
+        api.get_children(parent)
+            .unwrap()
+            .into_iter()
+            .map(|note| note.path)
+            .collect()
+    }
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_manual_order_and_pins() {
+        let (_temp_dir, mut api) = setup();
+
+        api.set_child_order("list", &paths(&["list/c", "list/a"]))
+            .unwrap();
+        assert_eq!(
+            children(&api, "list"),
+            ["list/c", "list/a", "list/b", "list/d"]
+        );
+
+        api.pin_note("list/d").unwrap();
+        assert_eq!(
+            children(&api, "list"),
+            ["list/d", "list/c", "list/a", "list/b"]
+        );
+        let stats = api.get_children_with_stats("list").unwrap();
+        assert_eq!(stats[0].path, "list/d");
+        assert!(stats[0].stats.as_ref().unwrap().pinned);
+
+        api.unpin_note("list/d").unwrap();
+        assert_eq!(children(&api, "list")[3], "list/d");
+    }
+
+    #[test]
+    fn test_root_order() {
+        let (_temp_dir, mut api) = setup();
+
+        api.set_child_order("", &paths(&["other", "list"])).unwrap();
+
+        let roots: Vec<String> = api
+            .get_root_notes()
+            .unwrap()
+            .into_iter()
+            .map(|note| note.path)
+            .collect();
+        assert_eq!(roots, ["other", "list"]);
+    }
+
+    #[test]
+    fn test_moving_drops_place_but_keeps_pin() {
+        let (_temp_dir, mut api) = setup();
+        api.set_child_order("list", &paths(&["list/b", "list/a"]))
+            .unwrap();
+        api.pin_note("list/a").unwrap();
+
+        // Retitling keeps the place
+        api.rename_note("list/b", "list/bee").unwrap();
+        assert_eq!(children(&api, "list")[..2], ["list/a", "list/bee"]);
+
+        api.rename_note("list/a", "other/a").unwrap();
+        let sort_index: Option<i64> = api
+            .db
+            .query_row(
+                "SELECT sort_index FROM notes WHERE path = 'other/a'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(sort_index, None);
+        assert!(
+            api.get_children_with_stats("other").unwrap()[0]
+                .stats
+                .as_ref()
+                .unwrap()
+                .pinned
+        );
+    }
+
+    #[test]
+    fn test_set_child_order_validates_paths() {
+        let (_temp_dir, mut api) = setup();
+
+        assert!(matches!(
+            api.set_child_order("list", &paths(&["other"])),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            api.set_child_order("list", &paths(&["list/a", "list/a"])),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            api.set_child_order("missing", &[]),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(api.pin_note("missing"), Err(Error::NotFound(_))));
+    }
+}
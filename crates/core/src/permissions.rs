@@ -0,0 +1,427 @@
+//! Scoped access tokens for external clients (HTTP API, plugins, clipping extensions).
+//!
+//! A token grants an access level, optionally limited to one subtree of the vault.
+//! Clients go through `ScopedNotes`, which checks every call against the token's grant
+//! before delegating to `NotesApi`, so e.g. an append-only clipper can never delete notes.
+//! Private notes look missing to clients unless the vault includes private notes.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{OptionalExtension, params};
+
+use crate::changes::Revision;
+use crate::notes::{Error, Note, NoteMetadata, NotesApi, Result};
+
+/// What a token may do, ordered from least to most privileged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Access {
+    /// Read and search notes
+    Read,
+    /// Read, create notes, and add content to the end of existing notes
+    Append,
+    /// Full access, including overwriting, renaming, and deleting notes
+    Write,
+}
+
+impl Access {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Access::Read => "read",
+            Access::Append => "append",
+            Access::Write => "write",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Access> {
+        match s {
+            "read" => Some(Access::Read),
+            "append" => Some(Access::Append),
+            "write" => Some(Access::Write),
+            _ => None,
+        }
+    }
+}
+
+/// An access token as stored in the index.
+#[derive(Debug, Clone)]
+pub struct AccessToken {
+    pub id: i64,
+    pub name: String,
+    /// The value clients present; only shown to the user when the token is created
+    pub secret: String,
+    pub access: Access,
+    /// Note path the token is limited to (the note and its descendants); `None` is the whole vault
+    pub subtree: Option<String>,
+    pub created: SystemTime,
+}
+
+impl AccessToken {
+    /// Returns true if `path` is inside this token's subtree. Paths with `.`, `..`,
+    /// or empty segments are never covered, so they can't climb out of it.
+    pub fn covers(&self, path: &str) -> bool {
+        let well_formed = path.is_empty()
+            || path
+                .split('/')
+                .all(|segment| !matches!(segment, "" | "." | ".."));
+        well_formed
+            && match &self.subtree {
+                None => true,
+                Some(root) => path == root || path.starts_with(&format!("{}/", root)),
+            }
+    }
+
+    fn check(&self, path: &str, access: Access) -> Result<()> {
+        if self.access < access {
+            return Err(Error::PermissionDenied(format!(
+                "Token '{}' has {} access, {} required",
+                self.name,
+                self.access.as_str(),
+                access.as_str()
+            )));
+        }
+        if !self.covers(path) {
+            return Err(Error::PermissionDenied(format!(
+                "Token '{}' cannot access {}",
+                self.name, path
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn generate_secret() -> Result<String> {
+    let mut bytes = [0u8; 24];
+    getrandom::fill(&mut bytes).map_err(|e| Error::Io(std::io::Error::other(e.to_string())))?;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!("zt_{}", hex))
+}
+
+fn token_from_row(row: &rusqlite::Row) -> rusqlite::Result<AccessToken> {
+    let access: String = row.get(3)?;
+    let created_at: i64 = row.get(5)?;
+    Ok(AccessToken {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        secret: row.get(2)?,
+        // Unknown levels (e.g. written by a newer version) degrade to read-only
+        access: Access::parse(&access).unwrap_or(Access::Read),
+        subtree: row.get(4)?,
+        created: UNIX_EPOCH + std::time::Duration::from_secs(created_at.max(0) as u64),
+    })
+}
+
+const TOKEN_COLUMNS: &str = "id, name, secret, access, subtree, created_at";
+
+impl NotesApi {
+    /// Creates a new access token.
+    ///
+    /// `subtree` limits the token to a note and its descendants; the note must exist.
+    pub fn create_access_token(
+        &mut self,
+        name: &str,
+        access: Access,
+        subtree: Option<&str>,
+    ) -> Result<AccessToken> {
+        self.ensure_writable()?;
+
+        if let Some(subtree) = subtree
+            && !self.note_exists(subtree)?
+        {
+            return Err(Error::NotFound(subtree.to_string()));
+        }
+
+        let secret = generate_secret()?;
+        let created_at = self.clock.unix_secs();
+        self.db.execute(
+            "INSERT INTO access_tokens (name, secret, access, subtree, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![name, secret, access.as_str(), subtree, created_at],
+        )?;
+
+        let id = self.db.last_insert_rowid();
+        Ok(self.db.query_row(
+            &format!("SELECT {} FROM access_tokens WHERE id = ?1", TOKEN_COLUMNS),
+            params![id],
+            token_from_row,
+        )?)
+    }
+
+    /// Lists all access tokens, oldest first.
+    pub fn list_access_tokens(&self) -> Result<Vec<AccessToken>> {
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT {} FROM access_tokens ORDER BY id",
+            TOKEN_COLUMNS
+        ))?;
+        let tokens = stmt
+            .query_map([], token_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(tokens)
+    }
+
+    /// Revokes an access token so it can no longer be used.
+    pub fn revoke_access_token(&mut self, id: i64) -> Result<()> {
+        self.ensure_writable()?;
+
+        let deleted = self
+            .db
+            .execute("DELETE FROM access_tokens WHERE id = ?1", params![id])?;
+        if deleted == 0 {
+            return Err(Error::NotFound(format!("token {}", id)));
+        }
+        Ok(())
+    }
+
+    /// Looks up the token for `secret` and returns a facade that enforces its grant.
+    pub fn with_token(&mut self, secret: &str) -> Result<ScopedNotes<'_>> {
+        let token = self
+            .db
+            .query_row(
+                &format!(
+                    "SELECT {} FROM access_tokens WHERE secret = ?1",
+                    TOKEN_COLUMNS
+                ),
+                params![secret],
+                token_from_row,
+            )
+            .optional()?
+            .ok_or_else(|| Error::PermissionDenied("Unknown access token".to_string()))?;
+
+        Ok(ScopedNotes { api: self, token })
+    }
+}
+
+/// A view of the vault restricted to what an access token grants.
+pub struct ScopedNotes<'a> {
+    api: &'a mut NotesApi,
+    token: AccessToken,
+}
+
+impl ScopedNotes<'_> {
+    pub fn token(&self) -> &AccessToken {
+        &self.token
+    }
+
+    /// Checks the token's grant, then hides private notes as if they didn't exist.
+    fn check(&self, path: &str, access: Access) -> Result<()> {
+        self.token.check(path, access)?;
+        match self.api.is_hidden(path) {
+            Ok(true) => Err(Error::NotFound(path.to_string())),
+            Ok(false) | Err(Error::NotFound(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn get_note(&mut self, path: &str) -> Result<Note> {
+        self.check(path, Access::Read)?;
+        self.api.get_note(path)
+    }
+
+    pub fn get_children(&self, path: &str) -> Result<Vec<NoteMetadata>> {
+        self.check(path, Access::Read)?;
+        let children = self.api.get_children(path)?;
+        Ok(children
+            .into_iter()
+            .filter(|child| !self.api.is_hidden(&child.path).unwrap_or(true))
+            .collect())
+    }
+
+    /// Full-text search, limited to notes inside the token's subtree.
+    pub fn search(&self, query: &str) -> Result<Vec<NoteMetadata>> {
+        self.token
+            .check(self.token.subtree.as_deref().unwrap_or(""), Access::Read)?;
+        let results = self.api.search(query)?;
+        Ok(results
+            .into_iter()
+            .filter(|note| self.token.covers(&note.path))
+            .collect())
+    }
+
+    pub fn create_note(&mut self, path: &str) -> Result<Note> {
+        self.check(path, Access::Append)?;
+        self.api.create_note(path)
+    }
+
+    /// Adds `text` to the end of a note (on a new line if the note doesn't end with one).
+    pub fn append_to_note(&mut self, path: &str, text: &str) -> Result<Revision> {
+        self.check(path, Access::Append)?;
+        let mut content = self.api.read_content(path)?;
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(text);
+        self.api.save_note(path, &content)
+    }
+
+    /// Replaces a note's content.
+    ///
+    /// Append-only tokens may only save content that extends the current content.
+    pub fn save_note(&mut self, path: &str, content: &str) -> Result<Revision> {
+        self.check(path, Access::Append)?;
+        if self.token.access < Access::Write {
+            let current = self.api.read_content(path)?;
+            if !content.starts_with(&current) {
+                return Err(Error::PermissionDenied(format!(
+                    "Token '{}' can only append to {}",
+                    self.token.name, path
+                )));
+            }
+        }
+        self.api.save_note(path, content)
+    }
+
+    pub fn rename_note(&mut self, old_path: &str, new_path: &str) -> Result<Revision> {
+        self.check(old_path, Access::Write)?;
+        self.check(new_path, Access::Write)?;
+        self.api.rename_note(old_path, new_path)
+    }
+
+    pub fn trash_note(&mut self, path: &str) -> Result<Revision> {
+        self.check(path, Access::Write)?;
+        self.api.trash_note(path)
+    }
+
+    pub fn delete_note(&mut self, path: &str) -> Result<Revision> {
+        self.check(path, Access::Write)?;
+        self.api.delete_note(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("inbox").unwrap();
+        api.save_note("inbox", "clippings").unwrap();
+        api.create_note("private").unwrap();
+        api.save_note("private", "secret clippings").unwrap();
+        (temp_dir, api)
+    }
+
+    #[test]
+    fn test_unknown_token_is_rejected() {
+        let (_temp_dir, mut api) = setup();
+        assert!(matches!(
+            api.with_token("zt_nope"),
+            Err(Error::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_only_token() {
+        let (_temp_dir, mut api) = setup();
+        let token = api
+            .create_access_token("viewer", Access::Read, None)
+            .unwrap();
+
+        let mut scoped = api.with_token(&token.secret).unwrap();
+        assert_eq!(scoped.get_note("inbox").unwrap().content, "clippings");
+        assert!(matches!(
+            scoped.save_note("inbox", "changed"),
+            Err(Error::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            scoped.delete_note("inbox"),
+            Err(Error::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn test_append_only_subtree_token() {
+        let (_temp_dir, mut api) = setup();
+        let token = api
+            .create_access_token("clipper", Access::Append, Some("inbox"))
+            .unwrap();
+
+        let mut scoped = api.with_token(&token.secret).unwrap();
+        scoped.append_to_note("inbox", "- new clip").unwrap();
+        scoped.create_note("inbox/article").unwrap();
+
+        assert!(matches!(
+            scoped.save_note("inbox", "overwritten"),
+            Err(Error::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            scoped.trash_note("inbox"),
+            Err(Error::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            scoped.get_note("private"),
+            Err(Error::PermissionDenied(_))
+        ));
+        for escape in [
+            "inbox/../private",
+            "inbox/./article",
+            "inbox//article",
+            "inbox/",
+        ] {
+            assert!(!token.covers(escape), "{} is covered", escape);
+        }
+        assert!(matches!(
+            scoped.get_note("inbox/../private"),
+            Err(Error::PermissionDenied(_))
+        ));
+
+        let results: Vec<_> = scoped
+            .search("clippings")
+            .unwrap()
+            .into_iter()
+            .map(|n| n.path)
+            .collect();
+        assert_eq!(results, vec!["inbox"]);
+
+        assert_eq!(
+            api.get_note("inbox").unwrap().content,
+            "clippings\n- new clip"
+        );
+    }
+
+    #[test]
+    fn test_private_notes_are_hidden_from_tokens() {
+        let (_temp_dir, mut api) = setup();
+        api.create_note("inbox/diary").unwrap();
+        api.save_note("inbox/diary", "---\nprivate: true\n---\nclippings")
+            .unwrap();
+        let token = api
+            .create_access_token("reader", Access::Write, None)
+            .unwrap();
+
+        let mut scoped = api.with_token(&token.secret).unwrap();
+        assert!(matches!(
+            scoped.get_note("inbox/diary"),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            scoped.delete_note("inbox/diary"),
+            Err(Error::NotFound(_))
+        ));
+        assert!(scoped.get_children("inbox").unwrap().is_empty());
+        assert!(
+            scoped
+                .search("clippings")
+                .unwrap()
+                .iter()
+                .all(|n| n.path != "inbox/diary")
+        );
+
+        api.set_include_private(true);
+        let mut scoped = api.with_token(&token.secret).unwrap();
+        assert!(scoped.get_note("inbox/diary").is_ok());
+    }
+
+    #[test]
+    fn test_revoke_access_token() {
+        let (_temp_dir, mut api) = setup();
+        let token = api
+            .create_access_token("temp", Access::Write, None)
+            .unwrap();
+        assert_eq!(api.list_access_tokens().unwrap().len(), 1);
+
+        api.revoke_access_token(token.id).unwrap();
+        assert!(api.list_access_tokens().unwrap().is_empty());
+        assert!(api.with_token(&token.secret).is_err());
+    }
+}
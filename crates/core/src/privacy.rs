@@ -0,0 +1,160 @@
+//! Private notes: kept out of search, the quick finder, exports, and external clients.
+//!
+//! A note is private when its frontmatter has `private: true`. The flag is cached in
+//! the index with the other note stats, and the queries that surface notes filter on
+//! it through `NotesApi::private_filter` unless the vault has opted in with
+//! `set_include_private`.
+
+use rusqlite::{OptionalExtension, params};
+
+use crate::changes::Revision;
+use crate::frontmatter::{
+    Frontmatter, PropertyValue, remove_property_in_content, set_property_in_content,
+};
+use crate::notes::{Error, NotesApi, Result};
+
+/// Frontmatter field that marks a note as private.
+pub const PRIVATE_PROPERTY: &str = "private";
+
+/// True if the frontmatter marks its note as private.
+pub fn is_private(frontmatter: &Frontmatter) -> bool {
+    frontmatter
+        .get_text(PRIVATE_PROPERTY)
+        .is_some_and(|value| matches!(value.trim().to_lowercase().as_str(), "true" | "yes"))
+}
+
+impl NotesApi {
+    /// Whether private notes are included in search, the finder, exports, and
+    /// scoped access.
+    pub fn include_private(&self) -> bool {
+        self.include_private
+    }
+
+    /// Opts in to (or back out of) seeing private notes everywhere they are hidden.
+    pub fn set_include_private(&mut self, include: bool) {
+        self.include_private = include;
+    }
+
+    /// SQL condition on the `notes` table that hides private notes unless they are
+    /// included; starts with `AND` so it can follow any `WHERE` clause.
+    pub(crate) fn private_filter(&self) -> &'static str {
+        if self.include_private {
+            ""
+        } else {
+            " AND notes.private = 0"
+        }
+    }
+
+    /// True if `path` is private and private notes are currently hidden.
+    pub(crate) fn is_hidden(&self, path: &str) -> Result<bool> {
+        if self.include_private {
+            return Ok(false);
+        }
+        self.is_note_private(path)
+    }
+
+    pub fn is_note_private(&self, path: &str) -> Result<bool> {
+        self.db
+            .query_row(
+                "SELECT private FROM notes WHERE path = ?1",
+                params![path],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+            .map(|private| private != 0)
+            .ok_or_else(|| Error::NotFound(path.to_string()))
+    }
+
+    /// Marks a note private (or public again) by editing its frontmatter.
+    pub fn set_note_private(&mut self, path: &str, private: bool) -> Result<Revision> {
+        let content = self.read_content(path)?;
+        let updated = if private {
+            set_property_in_content(
+                &content,
+                PRIVATE_PROPERTY,
+                PropertyValue::Text("true".to_string()),
+            )
+        } else {
+            remove_property_in_content(&content, PRIVATE_PROPERTY)
+        };
+        self.save_note(path, &updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::RankingMode;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("journal").unwrap();
+        api.save_note("journal", "---\nprivate: true\n---\nSecret plans")
+            .unwrap();
+        api.create_note("plans").unwrap();
+        api.save_note("plans", "Public plans").unwrap();
+        (temp_dir, api)
+    }
+
+    fn paths(notes: Vec<crate::notes::NoteMetadata>) -> Vec<String> {
+        let mut paths: Vec<String> = notes.into_iter().map(|n| n.path).collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_private_notes_are_hidden_from_search() {
+        let (_temp_dir, mut api) = setup();
+        assert!(api.is_note_private("journal").unwrap());
+
+        assert_eq!(paths(api.search("plans").unwrap()), ["plans"]);
+        assert_eq!(
+            paths(api.fuzzy_search("", None, RankingMode::Visits).unwrap()),
+            ["plans"]
+        );
+        assert!(
+            api.fuzzy_search("jour", None, RankingMode::Visits)
+                .unwrap()
+                .is_empty()
+        );
+
+        api.set_include_private(true);
+        assert_eq!(paths(api.search("plans").unwrap()), ["journal", "plans"]);
+        assert_eq!(
+            paths(api.fuzzy_search("jour", None, RankingMode::Visits).unwrap()),
+            ["journal"]
+        );
+    }
+
+    #[test]
+    fn test_set_note_private_edits_frontmatter() {
+        let (_temp_dir, mut api) = setup();
+
+        api.set_note_private("plans", true).unwrap();
+        assert!(api.is_note_private("plans").unwrap());
+        assert!(api.search("plans").unwrap().is_empty());
+
+        api.set_note_private("journal", false).unwrap();
+        assert!(!api.is_note_private("journal").unwrap());
+        assert_eq!(api.get_note("journal").unwrap().content, "Secret plans");
+    }
+
+    #[test]
+    fn test_export_skips_private_notes() {
+        let (temp_dir, api) = setup();
+        let dest = temp_dir.path().join("out");
+
+        let written = api
+            .export_notes(
+                &["journal".to_string(), "plans".to_string()],
+                "markdown",
+                &dest,
+            )
+            .unwrap();
+        assert_eq!(written, 1);
+        assert!(dest.join("plans.md").exists());
+        assert!(!dest.join("journal.md").exists());
+    }
+}
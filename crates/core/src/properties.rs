@@ -0,0 +1,244 @@
+//! Indexed frontmatter properties.
+//!
+//! Every note's frontmatter (see `frontmatter`) is indexed on sync into the
+//! `note_properties` table, one row per scalar value or list item, so notes can be
+//! looked up by property without reading their files.
+
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::changes::Revision;
+use crate::frontmatter::{
+    Frontmatter, PropertyValue, remove_property_in_content, set_property_in_content,
+};
+use crate::notes::{Error, NoteMetadata, NotesApi, Result, metadata_from_row};
+
+/// Checks that `key` can be written as a frontmatter property name.
+pub(crate) fn validate_property_key(key: &str) -> Result<()> {
+    if key.trim().is_empty() || key.contains(':') || key.contains('\n') {
+        return Err(Error::InvalidInput(format!(
+            "Invalid property name: {}",
+            key
+        )));
+    }
+    Ok(())
+}
+
+/// Replaces the indexed properties for a note.
+pub(crate) fn index_properties(db: &Connection, note_id: i64, content: &str) -> Result<()> {
+    db.execute(
+        "DELETE FROM note_properties WHERE note_id = ?1",
+        params![note_id],
+    )?;
+    let (frontmatter, _) = Frontmatter::parse(content);
+    let mut insert = db.prepare(
+        "INSERT INTO note_properties (note_id, key, value, is_list) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (key, value) in frontmatter.iter() {
+        match value {
+            PropertyValue::Text(text) => {
+                insert.execute(params![note_id, key, text, false])?;
+            }
+            // An empty list keeps a row without a value, so it reads back as a list
+            PropertyValue::List(items) if items.is_empty() => {
+                insert.execute(params![note_id, key, None::<String>, true])?;
+            }
+            PropertyValue::List(items) => {
+                for item in items {
+                    insert.execute(params![note_id, key, item, true])?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl NotesApi {
+    /// Returns a note's frontmatter properties in file order, from the index.
+    pub fn get_properties(&self, path: &str) -> Result<Vec<(String, PropertyValue)>> {
+        let id: i64 = self
+            .db
+            .query_row(
+                "SELECT id FROM notes WHERE path = ?1",
+                params![path],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| Error::NotFound(path.to_string()))?;
+
+        let mut stmt = self.db.prepare(
+            "SELECT key, value, is_list FROM note_properties WHERE note_id = ?1 ORDER BY rowid",
+        )?;
+        let rows = stmt
+            .query_map(params![id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, bool>(2)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut properties: Vec<(String, PropertyValue)> = Vec::new();
+        for (key, value, is_list) in rows {
+            if !is_list {
+                properties.push((key, PropertyValue::Text(value.unwrap_or_default())));
+                continue;
+            }
+            match properties.last_mut() {
+                Some((last, PropertyValue::List(items))) if *last == key => {
+                    items.extend(value);
+                }
+                _ => properties.push((key, PropertyValue::List(value.into_iter().collect()))),
+            }
+        }
+        Ok(properties)
+    }
+
+    /// Sets the frontmatter property `key` on a note, creating the frontmatter block
+    /// if needed, and saves it.
+    pub fn set_property(
+        &mut self,
+        path: &str,
+        key: &str,
+        value: PropertyValue,
+    ) -> Result<Revision> {
+        validate_property_key(key)?;
+        self.update_properties(path, |content| set_property_in_content(content, key, value))
+    }
+
+    /// Removes the frontmatter property `key` from a note and saves it.
+    pub fn remove_property(&mut self, path: &str, key: &str) -> Result<Revision> {
+        self.update_properties(path, |content| remove_property_in_content(content, key))
+    }
+
+    /// Returns the non-archived notes whose property `key` is `value`, or has it as a
+    /// list item. Values match case-insensitively.
+    pub fn query_by_property(&self, key: &str, value: &str) -> Result<Vec<NoteMetadata>> {
+        let mut stmt = self.db.prepare(
+            "SELECT DISTINCT notes.id, notes.path, notes.mtime, notes.archived
+             FROM note_properties
+             JOIN notes ON note_properties.note_id = notes.id
+             WHERE notes.archived = 0
+               AND note_properties.key = ?1
+               AND note_properties.value = ?2 COLLATE NOCASE
+             ORDER BY notes.path ASC",
+        )?;
+        let notes = stmt
+            .query_map(params![key, value], metadata_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(notes)
+    }
+
+    /// Rewrites a note's content with `update`, saving it only if it changed.
+    fn update_properties(
+        &mut self,
+        path: &str,
+        update: impl FnOnce(&str) -> String,
+    ) -> Result<Revision> {
+        self.ensure_writable()?;
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        let content = self.read_content(path)?;
+        let updated = update(&content);
+        if updated == content {
+            return self.revision();
+        }
+        self.save_note(path, &updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn paths(notes: Vec<NoteMetadata>) -> Vec<String> {
+        notes.into_iter().map(|note| note.path).collect()
+    }
+
+    #[test]
+    fn test_properties_are_indexed_on_sync() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("launch").unwrap();
+        api.save_note(
+            "launch",
+            "---\nstatus: Doing\nowners:\n  - ana\n  - bo\nblocked_by: []\n---\n# Launch",
+        )
+        .unwrap();
+
+        assert_eq!(
+            api.get_properties("launch").unwrap(),
+            vec![
+                (
+                    "status".to_string(),
+                    PropertyValue::Text("Doing".to_string())
+                ),
+                (
+                    "owners".to_string(),
+                    PropertyValue::List(vec!["ana".to_string(), "bo".to_string()])
+                ),
+                ("blocked_by".to_string(), PropertyValue::List(Vec::new())),
+            ]
+        );
+        assert_eq!(
+            paths(api.query_by_property("status", "doing").unwrap()),
+            vec!["launch"]
+        );
+        assert_eq!(
+            paths(api.query_by_property("owners", "bo").unwrap()),
+            vec!["launch"]
+        );
+        assert!(api.query_by_property("owner", "bo").unwrap().is_empty());
+
+        // Edits on disk are picked up by the next sync
+        std::fs::write(
+            temp_dir.path().join("launch/_index.md"),
+            "---\nstatus: done\n---\n",
+        )
+        .unwrap();
+        api.sync_note("launch").unwrap();
+        assert!(api.query_by_property("owners", "bo").unwrap().is_empty());
+        assert_eq!(
+            paths(api.query_by_property("status", "DONE").unwrap()),
+            vec!["launch"]
+        );
+    }
+
+    #[test]
+    fn test_set_and_remove_property() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("launch").unwrap();
+        api.save_note("launch", "# Launch\n").unwrap();
+
+        api.set_property(
+            "launch",
+            "due",
+            PropertyValue::Text("2026-11-01".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            api.get_note("launch").unwrap().content,
+            "---\ndue: 2026-11-01\n---\n# Launch\n"
+        );
+        assert_eq!(
+            paths(api.query_by_property("due", "2026-11-01").unwrap()),
+            vec!["launch"]
+        );
+
+        api.remove_property("launch", "due").unwrap();
+        assert_eq!(api.get_note("launch").unwrap().content, "# Launch\n");
+        assert!(api.get_properties("launch").unwrap().is_empty());
+
+        assert!(matches!(
+            api.set_property("launch", "a:b", PropertyValue::Text(String::new())),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            api.set_property("missing", "due", PropertyValue::Text(String::new())),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
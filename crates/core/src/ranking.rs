@@ -0,0 +1,201 @@
+//! Search ranking relative to the open note.
+//!
+//! The `*_near` searches take the same candidates as their plain versions but
+//! order them by a weighted score: the usual visit/frecency ranking, how close
+//! each note is to the open one in the tree, and how recently it was opened.
+//! Weights come from the vault's `search` config.
+
+use rusqlite::{OptionalExtension, params};
+
+use crate::config::SearchConfig;
+use crate::notes::{NoteMetadata, NotesApi, RankingMode, Result};
+
+/// Closeness of `path` to `current` in the tree, from 1.0 (parent or child) down
+/// towards 0 for distant notes. The current note itself scores 0.
+pub fn proximity(current: &str, path: &str) -> f64 {
+    let current: Vec<&str> = current.split('/').filter(|s| !s.is_empty()).collect();
+    let other: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let common = current
+        .iter()
+        .zip(&other)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let distance = (current.len() - common) + (other.len() - common);
+    if distance == 0 {
+        0.0
+    } else {
+        1.0 / distance as f64
+    }
+}
+
+/// 1.0 for a note opened just now, halving with every day since.
+pub fn recency(last_accessed_at: Option<i64>, now: i64) -> f64 {
+    match last_accessed_at {
+        Some(at) => {
+            let days = (now - at).max(0) as f64 / 86400.0;
+            0.5f64.powf(days)
+        }
+        None => 0.0,
+    }
+}
+
+impl NotesApi {
+    /// `fuzzy_search`, with results near `current_path` or recently opened ranked higher.
+    ///
+    /// Path prefix matches still come before other matches; the weighted score
+    /// orders results within each group.
+    pub fn fuzzy_search_near(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+        ranking_mode: RankingMode,
+        current_path: &str,
+    ) -> Result<Vec<NoteMetadata>> {
+        let candidates = self.fuzzy_search(query, None, ranking_mode)?;
+        let query = query.to_lowercase();
+        let mut results = self.rank_near(candidates, ranking_mode, current_path, |note| {
+            // Same grouping as `fuzzy_search`: prefix matches first
+            !note.path.to_lowercase().starts_with(&query)
+        })?;
+        if let Some(limit) = limit {
+            results.truncate(limit);
+        }
+        Ok(results)
+    }
+
+    /// Full-text `search`, with results near `current_path` or recently opened first.
+    pub fn search_near(&self, query: &str, current_path: &str) -> Result<Vec<NoteMetadata>> {
+        let candidates = self.search(query)?;
+        self.rank_near(candidates, RankingMode::Frecency, current_path, |_| false)
+    }
+
+    /// Sorts `notes` by `group` first, then by weighted score (highest first).
+    fn rank_near<G: Ord>(
+        &self,
+        notes: Vec<NoteMetadata>,
+        ranking_mode: RankingMode,
+        current_path: &str,
+        group: impl Fn(&NoteMetadata) -> G,
+    ) -> Result<Vec<NoteMetadata>> {
+        let paths: Vec<&str> = notes.iter().map(|note| note.path.as_str()).collect();
+        let scores = self.near_scores(&paths, ranking_mode, current_path)?;
+
+        let mut scored: Vec<(G, f64, NoteMetadata)> = notes
+            .into_iter()
+            .zip(scores)
+            .map(|(note, score)| (group(&note), score, note))
+            .collect();
+        // Stable, so equal scores keep the plain search's order
+        scored.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.total_cmp(&a.1)));
+        Ok(scored.into_iter().map(|(_, _, note)| note).collect())
+    }
+
+    /// The weighted score of each of `paths`: ranking relative to the best of them,
+    /// proximity to `current_path`, and recency, weighted by the `search` config.
+    pub(crate) fn near_scores(
+        &self,
+        paths: &[&str],
+        ranking_mode: RankingMode,
+        current_path: &str,
+    ) -> Result<Vec<f64>> {
+        let column = match ranking_mode {
+            RankingMode::Visits => "direct_access_count",
+            RankingMode::Frecency => "frecency_score",
+        };
+        let mut stmt = self.db.prepare_cached(&format!(
+            "SELECT CAST({} AS REAL), last_accessed_at FROM notes WHERE path = ?1",
+            column
+        ))?;
+        let mut signals: Vec<(f64, Option<i64>)> = Vec::with_capacity(paths.len());
+        for path in paths {
+            let row = stmt
+                .query_row(params![path], |row| {
+                    Ok((row.get::<_, Option<f64>>(0)?.unwrap_or(0.0), row.get(1)?))
+                })
+                .optional()?;
+            signals.push(row.unwrap_or((0.0, None)));
+        }
+
+        let max_rank = signals.iter().map(|(rank, _)| *rank).fold(0.0, f64::max);
+        let SearchConfig {
+            ranking_weight,
+            proximity_weight,
+            recency_weight,
+        } = self.config().search;
+        let now = self.clock.unix_secs();
+        Ok(paths
+            .iter()
+            .zip(signals)
+            .map(|(path, (rank, last_accessed_at))| {
+                let rank = if max_rank > 0.0 { rank / max_rank } else { 0.0 };
+                ranking_weight * rank
+                    + proximity_weight * proximity(current_path, path)
+                    + recency_weight * recency(last_accessed_at, now)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_proximity() {
+        assert_eq!(proximity("work/q3", "work/q3/plan"), 1.0);
+        assert_eq!(proximity("work/q3", "work"), 1.0);
+        assert_eq!(proximity("work/q3", "work/q4"), 0.5);
+        assert_eq!(proximity("work/q3", "home/plan"), 0.25);
+        assert_eq!(proximity("work/q3", "work/q3"), 0.0);
+    }
+
+    #[test]
+    fn test_recency() {
+        assert_eq!(recency(None, 1000), 0.0);
+        assert_eq!(recency(Some(1000), 1000), 1.0);
+        assert_eq!(recency(Some(0), 86400), 0.5);
+    }
+
+    #[test]
+    fn test_fuzzy_search_near_prefers_nearby_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in ["home", "home/plan", "work", "work/q3", "work/q3/plan"] {
+            api.create_note(path).unwrap();
+        }
+
+        let paths = |results: Vec<NoteMetadata>| -> Vec<String> {
+            results.into_iter().map(|n| n.path).collect()
+        };
+        let near_work = api
+            .fuzzy_search_near("plan", None, RankingMode::Visits, "work/q3")
+            .unwrap();
+        assert_eq!(paths(near_work), ["work/q3/plan", "home/plan"]);
+        let near_home = api
+            .fuzzy_search_near("plan", Some(1), RankingMode::Visits, "home")
+            .unwrap();
+        assert_eq!(paths(near_home), ["home/plan"]);
+
+        // Prefix matches still come first
+        let prefix = api
+            .fuzzy_search_near("work", None, RankingMode::Visits, "home/plan")
+            .unwrap();
+        assert_eq!(paths(prefix)[0], "work");
+    }
+
+    #[test]
+    fn test_recently_opened_notes_rank_higher() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in ["a", "a/notes", "b", "b/notes"] {
+            api.create_note(path).unwrap();
+        }
+        api.get_note("b/notes").unwrap();
+
+        let results = api
+            .fuzzy_search_near("notes", None, RankingMode::Visits, "")
+            .unwrap();
+        assert_eq!(results[0].path, "b/notes");
+    }
+}
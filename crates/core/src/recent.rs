@@ -0,0 +1,133 @@
+//! Recently modified and recently opened notes, for quick-switcher lists.
+//!
+//! Modification times come from the index. Opens are a separate log keyed by note
+//! id, so it follows renames and forgets deleted notes; re-opening a note moves it
+//! to the top, and only the newest `RECENT_OPENS_LIMIT` entries are kept.
+
+use rusqlite::{OptionalExtension, params};
+
+use crate::notes::{Error, NoteMetadata, NotesApi, Result, metadata_from_row};
+
+/// Opened notes remembered; older entries are dropped when a new one is recorded.
+pub const RECENT_OPENS_LIMIT: usize = 100;
+
+impl NotesApi {
+    /// Returns up to `limit` non-archived notes, most recently modified first.
+    pub fn get_recent_notes(&self, limit: usize) -> Result<Vec<NoteMetadata>> {
+        let mut stmt = self.db.prepare(
+            "SELECT id, path, mtime, archived FROM notes
+             WHERE archived = 0
+             ORDER BY mtime DESC, path ASC
+             LIMIT ?1",
+        )?;
+        let notes = stmt
+            .query_map(params![limit as i64], metadata_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(notes)
+    }
+
+    /// Remembers that the note was opened, for `get_recently_opened`.
+    pub fn record_open(&mut self, path: &str) -> Result<()> {
+        let now = self.clock.unix_secs();
+        let id: i64 = self
+            .db
+            .query_row(
+                "SELECT id FROM notes WHERE path = ?1",
+                params![path],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| Error::NotFound(path.to_string()))?;
+
+        // Delete and reinsert so the entry gets the newest id
+        self.db
+            .execute("DELETE FROM recent_opens WHERE note_id = ?1", params![id])?;
+        self.db.execute(
+            "INSERT INTO recent_opens (note_id, opened_at) VALUES (?1, ?2)",
+            params![id, now],
+        )?;
+        self.db.execute(
+            "DELETE FROM recent_opens WHERE id NOT IN (
+                SELECT id FROM recent_opens ORDER BY id DESC LIMIT ?1
+             )",
+            params![RECENT_OPENS_LIMIT as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Returns up to `limit` notes, most recently opened first. Archived notes are
+    /// left out but keep their place for when they come back.
+    pub fn get_recently_opened(&self, limit: usize) -> Result<Vec<NoteMetadata>> {
+        let mut stmt = self.db.prepare(
+            "SELECT notes.id, notes.path, notes.mtime, notes.archived
+             FROM recent_opens JOIN notes ON notes.id = recent_opens.note_id
+             WHERE notes.archived = 0
+             ORDER BY recent_opens.id DESC
+             LIMIT ?1",
+        )?;
+        let notes = stmt
+            .query_map(params![limit as i64], metadata_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(notes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn paths(notes: Vec<NoteMetadata>) -> Vec<String> {
+        notes.into_iter().map(|note| note.path).collect()
+    }
+
+    #[test]
+    fn test_recent_notes_by_mtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for (path, mtime) in [("old", 100), ("new", 300), ("middle", 200)] {
+            api.create_note(path).unwrap();
+            api.db
+                .execute(
+                    "UPDATE notes SET mtime = ?2 WHERE path = ?1",
+                    params![path, mtime],
+                )
+                .unwrap();
+        }
+        api.archive_note("middle").unwrap();
+
+        assert_eq!(
+            paths(api.get_recent_notes(10).unwrap())[..2],
+            ["new", "old"]
+        );
+        assert_eq!(api.get_recent_notes(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_recently_opened_follows_renames() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in ["a", "b", "c"] {
+            api.create_note(path).unwrap();
+        }
+
+        api.record_open("a").unwrap();
+        api.record_open("b").unwrap();
+        api.record_open("c").unwrap();
+        api.record_open("a").unwrap();
+        assert_eq!(paths(api.get_recently_opened(10).unwrap()), ["a", "c", "b"]);
+        assert_eq!(paths(api.get_recently_opened(2).unwrap()), ["a", "c"]);
+
+        api.rename_note("c", "renamed").unwrap();
+        api.delete_note("b").unwrap();
+        assert_eq!(
+            paths(api.get_recently_opened(10).unwrap()),
+            ["a", "renamed"]
+        );
+
+        assert!(matches!(
+            api.record_open("missing"),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+//! Startup checks for a vault whose previous session ended abruptly.
+//!
+//! The index is mostly derived from the note files, so when it can't be trusted it is
+//! set aside and `startup_sync` rebuilds it. What the files can't rebuild (reminders,
+//! tokens, favorites, visit history, pins and manual order, search history) is then
+//! copied over from the set-aside database as far as it can still be read.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use rusqlite::{Connection, ErrorCode, params};
+
+use crate::clock::Clock;
+use crate::notes::{NotesApi, Result};
+
+/// How long to wait for a lock held by another connection before failing.
+///
+/// Without a timeout a lock left by a crashed or hung process makes every query fail
+/// immediately; with an unbounded one startup would hang.
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// SQLite files that only exist while a connection has the database open.
+const SIDECAR_SUFFIXES: [&str; 3] = ["-journal", "-wal", "-shm"];
+
+/// What happened while opening the vault, for diagnostics.
+#[derive(Debug, Clone, Default)]
+pub struct StartupReport {
+    /// Sidecar files left behind by a session that didn't close cleanly
+    pub stale_files: Vec<String>,
+    /// A leftover write-ahead log was checkpointed into the database
+    pub wal_checkpointed: bool,
+    /// The index failed its integrity check and was set aside for a rebuild
+    pub index_rebuilt: bool,
+    /// Where the index that failed its check was moved to
+    pub corrupt_copy: Option<PathBuf>,
+    /// Rows copied from `corrupt_copy` into the rebuilt index, once `startup_sync` ran
+    pub salvaged: Option<usize>,
+    /// Notes whose interrupted save was completed from the journal
+    pub journal_replayed: Vec<String>,
+    pub open_duration: Duration,
+}
+
+impl StartupReport {
+    /// Returns true if the previous session appears to have ended without closing.
+    pub fn recovered(&self) -> bool {
+        !self.stale_files.is_empty() || self.index_rebuilt || !self.journal_replayed.is_empty()
+    }
+}
+
+fn sidecar_path(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = db_path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Returns the sidecar files present next to `db_path`.
+fn find_stale_files(db_path: &Path) -> Vec<PathBuf> {
+    SIDECAR_SUFFIXES
+        .iter()
+        .map(|suffix| sidecar_path(db_path, suffix))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Returns `Ok(false)` if the database is corrupt or not a database at all.
+///
+/// Lock errors are propagated so a busy database is never mistaken for a broken one.
+fn passes_quick_check(db: &Connection) -> Result<bool> {
+    match db.query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0)) {
+        Ok(result) => Ok(result == "ok"),
+        Err(rusqlite::Error::SqliteFailure(e, _))
+            if matches!(e.code, ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase) =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn connect(db_path: &Path) -> Result<Connection> {
+    let db = Connection::open(db_path)?;
+    db.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(db)
+}
+
+/// Opens the index database, recovering from an unclean shutdown.
+///
+/// A hot rollback journal is rolled back by SQLite on first access; a leftover WAL is
+/// checkpointed explicitly. A database that fails its integrity check is renamed to
+/// `.notes.db.corrupt-<unix time>` (with its sidecar files) and recreated empty.
+pub(crate) fn open_database(
+    db_path: &Path,
+    clock: &dyn Clock,
+) -> Result<(Connection, StartupReport)> {
+    let started = Instant::now();
+    let mut report = StartupReport::default();
+
+    let stale = find_stale_files(db_path);
+    report.stale_files = stale
+        .iter()
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+
+    let mut db = connect(db_path)?;
+
+    if passes_quick_check(&db)? {
+        if stale
+            .iter()
+            .any(|path| path.to_string_lossy().ends_with("-wal"))
+        {
+            db.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+            report.wal_checkpointed = true;
+        }
+    } else {
+        drop(db);
+        let stamp = clock.unix_secs();
+        let copy = sidecar_path(db_path, &format!(".corrupt-{}", stamp));
+        std::fs::rename(db_path, &copy)?;
+        for suffix in SIDECAR_SUFFIXES {
+            let sidecar = sidecar_path(db_path, suffix);
+            if sidecar.exists() {
+                std::fs::rename(sidecar, sidecar_path(&copy, suffix))?;
+            }
+        }
+        db = connect(db_path)?;
+        report.index_rebuilt = true;
+        report.corrupt_copy = Some(copy);
+    }
+
+    report.open_duration = started.elapsed();
+    Ok((db, report))
+}
+
+/// Copies what the note files can't rebuild from the database at `old` into `db`,
+/// whose notes must already be indexed; rows are matched to notes by path. Each table
+/// is copied on its own, so one that can't be read doesn't stop the others. Returns
+/// how many rows were copied.
+fn salvage(db: &Connection, old: &Path) -> usize {
+    const STATEMENTS: [&str; 6] = [
+        "UPDATE notes SET access_count = o.access_count,
+             direct_access_count = o.direct_access_count,
+             last_accessed_at = o.last_accessed_at, frecency_score = o.frecency_score,
+             expanded = o.expanded, sort_index = o.sort_index, pinned = o.pinned
+         FROM old.notes o WHERE o.path = notes.path",
+        "INSERT INTO reminders (note_id, remind_at, message, fired)
+         SELECT n.id, r.remind_at, r.message, r.fired FROM old.reminders r
+         JOIN old.notes o ON o.id = r.note_id JOIN notes n ON n.path = o.path",
+        "INSERT OR IGNORE INTO access_tokens (name, secret, access, subtree, created_at)
+         SELECT name, secret, access, subtree, created_at FROM old.access_tokens",
+        "INSERT OR IGNORE INTO favorites (note_id, added_at)
+         SELECT n.id, f.added_at FROM old.favorites f
+         JOIN old.notes o ON o.id = f.note_id JOIN notes n ON n.path = o.path",
+        "INSERT OR IGNORE INTO recent_opens (note_id, opened_at)
+         SELECT n.id, r.opened_at FROM old.recent_opens r
+         JOIN old.notes o ON o.id = r.note_id JOIN notes n ON n.path = o.path",
+        "INSERT OR IGNORE INTO search_history (kind, query, replacement, used_at)
+         SELECT kind, query, replacement, used_at FROM old.search_history",
+    ];
+
+    let uri = format!("file:{}?mode=ro", old.to_string_lossy());
+    if db
+        .execute("ATTACH DATABASE ?1 AS old", params![uri])
+        .is_err()
+    {
+        return 0;
+    }
+    let copied = STATEMENTS
+        .iter()
+        .filter_map(|sql| db.execute(sql, []).ok())
+        .sum();
+    let _ = db.execute("DETACH DATABASE old", []);
+    copied
+}
+
+impl NotesApi {
+    /// Returns what was found and repaired when this vault was opened.
+    pub fn startup_report(&self) -> &StartupReport {
+        &self.startup_report
+    }
+
+    /// After the first `startup_sync` following a rebuild, copies what it can from
+    /// the index that was set aside.
+    pub(crate) fn salvage_index(&mut self) {
+        if self.startup_report.salvaged.is_some() || self.is_read_only() {
+            return;
+        }
+        if let Some(copy) = &self.startup_report.corrupt_copy {
+            self.startup_report.salvaged = Some(salvage(&self.db, copy));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_clean_open() {
+        let temp_dir = TempDir::new().unwrap();
+        drop(NotesApi::new(temp_dir.path()).unwrap());
+
+        let api = NotesApi::new(temp_dir.path()).unwrap();
+        let report = api.startup_report();
+        assert!(report.stale_files.is_empty());
+        assert!(!report.recovered());
+    }
+
+    #[test]
+    fn test_leftover_wal_is_checkpointed() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("kept").unwrap();
+        drop(api);
+
+        // Leave a WAL behind, as a session killed mid-write would
+        let db_path = temp_dir.path().join(".notes.db");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.pragma_update(None, "journal_mode", "WAL").unwrap();
+        conn.pragma_update(None, "wal_autocheckpoint", 0).unwrap();
+        conn.execute(
+            "UPDATE notes SET frecency_score = 3 WHERE path = 'kept'",
+            [],
+        )
+        .unwrap();
+        std::fs::copy(
+            sidecar_path(&db_path, "-wal"),
+            temp_dir.path().join("saved-wal"),
+        )
+        .unwrap();
+        drop(conn);
+        std::fs::copy(
+            temp_dir.path().join("saved-wal"),
+            sidecar_path(&db_path, "-wal"),
+        )
+        .unwrap();
+
+        let api = NotesApi::new(temp_dir.path()).unwrap();
+        let report = api.startup_report();
+        assert_eq!(report.stale_files, vec![".notes.db-wal"]);
+        assert!(report.wal_checkpointed);
+        assert!(api.note_exists("kept").unwrap());
+    }
+
+    #[test]
+    fn test_corrupt_index_is_rebuilt() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("kept").unwrap();
+        drop(api);
+
+        std::fs::write(temp_dir.path().join(".notes.db"), b"not a database").unwrap();
+
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        assert!(api.startup_report().index_rebuilt);
+        assert!(api.startup_report().recovered());
+        // Set aside rather than deleted
+        let copy = api.startup_report().corrupt_copy.clone().unwrap();
+        assert_eq!(std::fs::read(&copy).unwrap(), b"not a database");
+
+        api.startup_sync().unwrap();
+        assert!(api.note_exists("kept").unwrap());
+        assert_eq!(api.startup_report().salvaged, Some(0));
+    }
+
+    #[test]
+    fn test_corrupt_copy_is_stamped_by_the_clock() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join(".notes.db");
+        std::fs::write(&db_path, b"not a database").unwrap();
+
+        let clock = crate::ManualClock::new(std::time::UNIX_EPOCH + Duration::from_secs(1_000));
+        let (_, report) = open_database(&db_path, &clock).unwrap();
+        assert_eq!(
+            report.corrupt_copy,
+            Some(temp_dir.path().join(".notes.db.corrupt-1000"))
+        );
+    }
+
+    #[test]
+    fn test_salvage_from_set_aside_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("kept").unwrap();
+        api.create_note("other").unwrap();
+        let remind_at = std::time::SystemTime::now() + Duration::from_secs(3600);
+        api.set_reminder("kept", remind_at, "check").unwrap();
+        api.favorite_note("kept").unwrap();
+        drop(api);
+
+        // As open_database leaves it after a failed check
+        let db_path = temp_dir.path().join(".notes.db");
+        let copy = temp_dir.path().join(".notes.db.corrupt-1");
+        std::fs::rename(&db_path, &copy).unwrap();
+
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.startup_report.corrupt_copy = Some(copy);
+        api.startup_sync().unwrap();
+
+        assert!(api.startup_report().salvaged.unwrap() >= 2);
+        assert_eq!(api.list_upcoming_reminders().unwrap().len(), 1);
+        assert_eq!(api.get_favorites().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,241 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::params;
+
+use crate::notes::{Error, NotesApi, Result};
+
+/// A reminder attached to a note.
+///
+/// Reminders reference the note by id, so they follow the note through renames,
+/// archiving, and moves. Reminders of deleted notes are dropped from all queries.
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    pub id: i64,
+    pub path: String,
+    pub remind_at: SystemTime,
+    pub message: String,
+    pub fired: bool,
+}
+
+fn to_unix(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn reminder_from_row(row: &rusqlite::Row) -> rusqlite::Result<Reminder> {
+    let remind_at: i64 = row.get(2)?;
+    Ok(Reminder {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        remind_at: UNIX_EPOCH + Duration::from_secs(remind_at.max(0) as u64),
+        message: row.get(3)?,
+        fired: row.get::<_, i64>(4)? != 0,
+    })
+}
+
+impl NotesApi {
+    /// Schedules a reminder for a note.
+    ///
+    /// Returns the created reminder. Multiple reminders per note are allowed.
+    pub fn set_reminder(
+        &mut self,
+        path: &str,
+        remind_at: SystemTime,
+        message: &str,
+    ) -> Result<Reminder> {
+        self.ensure_writable()?;
+
+        let note_id: i64 = self
+            .db
+            .query_row(
+                "SELECT id FROM notes WHERE path = ?1",
+                params![path],
+                |row| row.get(0),
+            )
+            .map_err(|_| Error::NotFound(path.to_string()))?;
+
+        self.db.execute(
+            "INSERT INTO reminders (note_id, remind_at, message, fired) VALUES (?1, ?2, ?3, 0)",
+            params![note_id, to_unix(remind_at), message],
+        )?;
+
+        self.get_reminder(self.db.last_insert_rowid())
+    }
+
+    /// Returns a single reminder by id.
+    pub fn get_reminder(&self, id: i64) -> Result<Reminder> {
+        self.db
+            .query_row(
+                "SELECT reminders.id, notes.path, reminders.remind_at, reminders.message, reminders.fired
+                 FROM reminders
+                 JOIN notes ON reminders.note_id = notes.id
+                 WHERE reminders.id = ?1",
+                params![id],
+                reminder_from_row,
+            )
+            .map_err(|_| Error::NotFound(format!("reminder {}", id)))
+    }
+
+    /// Returns all reminders that haven't fired yet, soonest first.
+    ///
+    /// Overdue reminders that the scheduler hasn't picked up are included.
+    pub fn list_upcoming_reminders(&self) -> Result<Vec<Reminder>> {
+        let mut stmt = self.db.prepare(
+            "SELECT reminders.id, notes.path, reminders.remind_at, reminders.message, reminders.fired
+             FROM reminders
+             JOIN notes ON reminders.note_id = notes.id
+             WHERE reminders.fired = 0
+             ORDER BY reminders.remind_at ASC, reminders.id ASC",
+        )?;
+
+        let reminders = stmt
+            .query_map([], reminder_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(reminders)
+    }
+
+    /// Returns all reminders for a single note, including fired ones.
+    pub fn get_note_reminders(&self, path: &str) -> Result<Vec<Reminder>> {
+        let mut stmt = self.db.prepare(
+            "SELECT reminders.id, notes.path, reminders.remind_at, reminders.message, reminders.fired
+             FROM reminders
+             JOIN notes ON reminders.note_id = notes.id
+             WHERE notes.path = ?1
+             ORDER BY reminders.remind_at ASC, reminders.id ASC",
+        )?;
+
+        let reminders = stmt
+            .query_map(params![path], reminder_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(reminders)
+    }
+
+    /// Marks all reminders due at or before `now` as fired and returns them.
+    ///
+    /// Intended to be polled by a scheduler; each reminder is returned exactly once.
+    pub fn take_due_reminders(&mut self, now: SystemTime) -> Result<Vec<Reminder>> {
+        let now = to_unix(now);
+
+        let due = {
+            let mut stmt = self.db.prepare(
+                "SELECT reminders.id, notes.path, reminders.remind_at, reminders.message, reminders.fired
+                 FROM reminders
+                 JOIN notes ON reminders.note_id = notes.id
+                 WHERE reminders.fired = 0 AND reminders.remind_at <= ?1
+                 ORDER BY reminders.remind_at ASC, reminders.id ASC",
+            )?;
+            stmt.query_map(params![now], reminder_from_row)?
+                .collect::<std::result::Result<Vec<_>, _>>()?
+        };
+
+        for reminder in &due {
+            self.db.execute(
+                "UPDATE reminders SET fired = 1 WHERE id = ?1",
+                params![reminder.id],
+            )?;
+        }
+
+        Ok(due
+            .into_iter()
+            .map(|r| Reminder { fired: true, ..r })
+            .collect())
+    }
+
+    /// Deletes a reminder.
+    pub fn delete_reminder(&mut self, id: i64) -> Result<()> {
+        self.ensure_writable()?;
+
+        let deleted = self
+            .db
+            .execute("DELETE FROM reminders WHERE id = ?1", params![id])?;
+        if deleted == 0 {
+            return Err(Error::NotFound(format!("reminder {}", id)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_set_and_list_reminders() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("todo").unwrap();
+
+        api.set_reminder("todo", at(2_000), "second").unwrap();
+        let first = api.set_reminder("todo", at(1_000), "first").unwrap();
+        assert_eq!(first.path, "todo");
+        assert_eq!(first.remind_at, at(1_000));
+        assert!(!first.fired);
+
+        let upcoming = api.list_upcoming_reminders().unwrap();
+        let messages: Vec<_> = upcoming.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn test_set_reminder_missing_note() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        assert!(matches!(
+            api.set_reminder("missing", at(1_000), "hi"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_take_due_reminders_fires_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("todo").unwrap();
+
+        api.set_reminder("todo", at(1_000), "due").unwrap();
+        api.set_reminder("todo", at(5_000), "later").unwrap();
+
+        let due = api.take_due_reminders(at(1_500)).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].message, "due");
+        assert!(due[0].fired);
+
+        assert!(api.take_due_reminders(at(1_500)).unwrap().is_empty());
+        assert_eq!(api.list_upcoming_reminders().unwrap().len(), 1);
+        assert_eq!(api.get_note_reminders("todo").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_reminder_follows_rename() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("old").unwrap();
+
+        let reminder = api.set_reminder("old", at(1_000), "hi").unwrap();
+        api.rename_note("old", "new").unwrap();
+
+        assert_eq!(api.get_reminder(reminder.id).unwrap().path, "new");
+    }
+
+    #[test]
+    fn test_delete_reminder() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("todo").unwrap();
+
+        let reminder = api.set_reminder("todo", at(1_000), "hi").unwrap();
+        api.delete_reminder(reminder.id).unwrap();
+
+        assert!(api.list_upcoming_reminders().unwrap().is_empty());
+        assert!(api.delete_reminder(reminder.id).is_err());
+    }
+}
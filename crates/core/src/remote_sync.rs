@@ -0,0 +1,831 @@
+//! Syncing the vault's files with a remote store such as a WebDAV server.
+//!
+//! Sync works on files, not notes: every file in the notes root except the index and
+//! other device-local state is mirrored. For each file this device remembers what both
+//! sides held after the last sync (the remote's ETag and a hash of the content), kept
+//! outside the vault (see `set_sync_state_dir`). Comparing each side against that base
+//! tells who changed what:
+//!
+//! - only this device changed a file: it is uploaded (or deleted on the remote)
+//! - only the remote changed it: it is downloaded (or deleted here)
+//! - both changed it the same way: nothing moves
+//! - both changed it differently: it is a conflict and left alone on both sides until
+//!   `resolve_sync_conflict` picks the side to keep for the next sync
+//!
+//! Uploads and deletes carry the ETag they expect, so a change that lands on the
+//! remote mid-sync is reported as a conflict instead of overwritten, and downloads and
+//! local deletes check the file here the same way. `VaultSync` runs a sync without
+//! holding the `NotesApi` through the network round trips. The WebDAV store is in the
+//! `webdav` module, behind the feature of the same name.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::checkpoints::CHECKPOINTS_DIR;
+use crate::diagnostics::CRASH_REPORT_DIR;
+use crate::filesystem::write_atomic;
+use crate::journal::JOURNAL_FILE_NAME;
+use crate::notes::{Error, NotesApi, Result, compute_hash};
+use crate::trash::TRASH_DIR;
+
+/// Where and as whom to sync. Kept on this device only, with the sync state.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteConfig {
+    /// The collection the vault is mirrored into, e.g.
+    /// `https://cloud.example/remote.php/dav/files/me/Notes/`
+    pub url: String,
+    pub username: String,
+    /// Preferably an app password, since it is stored unencrypted
+    pub password: String,
+}
+
+impl fmt::Debug for RemoteConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteConfig")
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A file on the remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEntry {
+    /// Relative to the vault root, `/`-separated
+    pub path: String,
+    /// Changes whenever the file's content does
+    pub etag: String,
+}
+
+/// Where synced files live, e.g. a WebDAV collection. Paths are relative to the vault
+/// root and `/`-separated.
+pub trait RemoteStore {
+    /// Every file on the remote, except device-local state (see `is_device_local`).
+    fn list(&self) -> Result<Vec<RemoteEntry>>;
+
+    fn get(&self, path: &str) -> Result<Vec<u8>>;
+
+    /// Writes the file if its ETag is still `expected` (`None`: if it doesn't exist)
+    /// and returns the new ETag, or `None` if the remote changed in the meantime.
+    fn put(&self, path: &str, content: &[u8], expected: Option<&str>) -> Result<Option<String>>;
+
+    /// Deletes the file if its ETag is still `expected`; `false` if it changed in the
+    /// meantime.
+    fn delete(&self, path: &str, expected: &str) -> Result<bool>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncAction {
+    Uploaded,
+    Downloaded,
+    DeletedRemote,
+    DeletedLocal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncChange {
+    pub path: String,
+    pub action: SyncAction,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// In path order
+    pub applied: Vec<SyncChange>,
+    /// Files changed differently on both sides, including ones left from earlier syncs
+    pub conflicts: Vec<String>,
+}
+
+/// Which side of a conflict `resolve_sync_conflict` keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncSide {
+    Local,
+    Remote,
+}
+
+/// What both sides held after the last sync; `None` where a side had no file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct SyncBase {
+    etag: Option<String>,
+    hash: Option<String>,
+}
+
+impl SyncBase {
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.hash.is_none()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SyncState {
+    remote: Option<RemoteConfig>,
+    files: BTreeMap<String, SyncBase>,
+    /// Conflicted files and the remote's ETag when the conflict was found
+    conflicts: BTreeMap<String, Option<String>>,
+}
+
+/// Whether a vault file belongs to this device only (the index, the journal, trash,
+/// checkpoints, crash reports, git's repository, a save's temporary file) and so is
+/// never synced.
+pub fn is_device_local(path: &str) -> bool {
+    let first = path.split('/').next().unwrap_or_default();
+    let name = path.rsplit('/').next().unwrap_or_default();
+    // `write_atomic` writes `.<name>.<pid>.tmp` next to the file
+    (name.starts_with('.') && name.ends_with(".tmp"))
+        || first.starts_with(".notes.db")
+        || [
+            ".git",
+            JOURNAL_FILE_NAME,
+            CHECKPOINTS_DIR,
+            CRASH_REPORT_DIR,
+            TRASH_DIR,
+        ]
+        .contains(&first)
+}
+
+/// Like `compute_hash`, for attachments too.
+fn hash_bytes(content: &[u8]) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+/// Every syncable file below `dir`, with the hash of its content.
+fn collect_local(root: &Path, dir: &Path, files: &mut BTreeMap<String, String>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let Some(relative) = path
+            .strip_prefix(root)
+            .ok()
+            .and_then(|relative| relative.to_str())
+            .map(|relative| relative.replace('\\', "/"))
+        else {
+            continue;
+        };
+        let file_type = entry.file_type()?;
+        if is_device_local(&relative) || file_type.is_symlink() {
+            continue;
+        }
+        if file_type.is_dir() {
+            collect_local(root, &path, files)?;
+        } else {
+            files.insert(relative, hash_bytes(&fs::read(&path)?));
+        }
+    }
+    Ok(())
+}
+
+/// Removes `dir` and its parents below `root` while they are empty.
+fn prune_empty_dirs(root: &Path, mut dir: &Path) {
+    while dir != root && dir.starts_with(root) && fs::remove_dir(dir).is_ok() {
+        match dir.parent() {
+            Some(parent) => dir = parent,
+            None => break,
+        }
+    }
+}
+
+fn read_state(path: &Path) -> Result<SyncState> {
+    match fs::read_to_string(path) {
+        Ok(raw) => serde_json::from_str(&raw)
+            .map_err(|e| Error::Io(io::Error::new(io::ErrorKind::InvalidData, e))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(SyncState::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_state(path: &Path, state: &SyncState) -> Result<()> {
+    let raw = serde_json::to_string_pretty(state).map_err(io::Error::other)?;
+    fs::write(path, raw)?;
+    Ok(())
+}
+
+/// A change a sync makes to a file on this device; see `NotesApi::apply_sync_change`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalChange {
+    pub path: String,
+    /// The downloaded content, or `None` to delete the file
+    pub content: Option<Vec<u8>>,
+    /// Hash of the file when the sync compared it; a file changed since is left alone
+    expected: Option<String>,
+}
+
+/// A sync of the vault's files with a remote that runs without the `NotesApi`, so
+/// the network round trips don't hold up other writes (see `NotesApi::start_sync`).
+///
+/// Only the changes to this device's files need the API: `run` hands each to its
+/// `apply` callback, which should run `NotesApi::apply_sync_change` wherever writes
+/// are serialized. Syncs of one vault must not overlap each other, or configuring
+/// or resolving conflicts while one runs.
+#[derive(Debug, Clone)]
+pub struct VaultSync {
+    root: PathBuf,
+    state_path: PathBuf,
+}
+
+impl VaultSync {
+    /// Syncs with `remote`, calling `progress` with the number of files done and the
+    /// total as it goes. The index is left to the caller: rescan it if
+    /// `SyncReport::changed_local` says files here changed.
+    pub fn run(
+        &self,
+        remote: &dyn RemoteStore,
+        mut apply: impl FnMut(&LocalChange) -> Result<bool>,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<SyncReport> {
+        let mut state = read_state(&self.state_path)?;
+
+        let mut local = BTreeMap::new();
+        collect_local(&self.root, &self.root, &mut local)?;
+        let remote_files: BTreeMap<String, String> = remote
+            .list()?
+            .into_iter()
+            .map(|entry| (entry.path, entry.etag))
+            .collect();
+        let paths: BTreeSet<String> = local
+            .keys()
+            .chain(remote_files.keys())
+            .chain(state.files.keys())
+            .cloned()
+            .collect();
+
+        let mut report = SyncReport::default();
+        let total = paths.len();
+        let mut result = Ok(());
+        for (done, path) in paths.into_iter().enumerate() {
+            progress(done, total);
+            let base = state.files.get(&path).cloned().unwrap_or_default();
+            let local_hash = local.get(&path).cloned();
+            let remote_etag = remote_files.get(&path).cloned();
+            let synced = match self.sync_file(
+                remote,
+                &mut apply,
+                &path,
+                base,
+                local_hash,
+                remote_etag.clone(),
+            ) {
+                Ok(synced) => synced,
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            };
+            let Some((base, action)) = synced else {
+                state.conflicts.insert(path, remote_etag);
+                continue;
+            };
+            state.conflicts.remove(&path);
+            if let Some(action) = action {
+                report.applied.push(SyncChange {
+                    path: path.clone(),
+                    action,
+                });
+            }
+            if base.is_empty() {
+                state.files.remove(&path);
+            } else {
+                state.files.insert(path, base);
+            }
+        }
+        // Keep what was synced before a failure, so the next sync picks up from there
+        write_state(&self.state_path, &state)?;
+        result?;
+        progress(total, total);
+
+        report.conflicts = state.conflicts.keys().cloned().collect();
+        Ok(report)
+    }
+
+    /// Brings one file in line on both sides. Returns the new base and what was done,
+    /// or `None` for a conflict.
+    fn sync_file(
+        &self,
+        remote: &dyn RemoteStore,
+        apply: &mut impl FnMut(&LocalChange) -> Result<bool>,
+        path: &str,
+        base: SyncBase,
+        local_hash: Option<String>,
+        remote_etag: Option<String>,
+    ) -> Result<Option<(SyncBase, Option<SyncAction>)>> {
+        let local_changed = local_hash != base.hash;
+        let remote_changed = remote_etag != base.etag;
+        let file = self.root.join(path);
+
+        let synced = match (local_changed, remote_changed) {
+            (false, false) => (base, None),
+            // Only this device changed it
+            (true, false) => match &local_hash {
+                Some(_) => match remote.put(path, &fs::read(&file)?, base.etag.as_deref())? {
+                    Some(etag) => (
+                        SyncBase {
+                            etag: Some(etag),
+                            hash: local_hash,
+                        },
+                        Some(SyncAction::Uploaded),
+                    ),
+                    None => return Ok(None),
+                },
+                None => match &remote_etag {
+                    Some(etag) if !remote.delete(path, etag)? => return Ok(None),
+                    Some(_) => (SyncBase::default(), Some(SyncAction::DeletedRemote)),
+                    None => (SyncBase::default(), None),
+                },
+            },
+            // Only the remote changed it; an edit here since the comparison makes
+            // it a conflict after all
+            (false, true) => match &remote_etag {
+                Some(_) => {
+                    let content = remote.get(path)?;
+                    let hash = hash_bytes(&content);
+                    let change = LocalChange {
+                        path: path.to_string(),
+                        content: Some(content),
+                        expected: local_hash,
+                    };
+                    if !apply(&change)? {
+                        return Ok(None);
+                    }
+                    (
+                        SyncBase {
+                            etag: remote_etag,
+                            hash: Some(hash),
+                        },
+                        Some(SyncAction::Downloaded),
+                    )
+                }
+                None if local_hash.is_none() => (SyncBase::default(), None),
+                None => {
+                    let change = LocalChange {
+                        path: path.to_string(),
+                        content: None,
+                        expected: local_hash,
+                    };
+                    if !apply(&change)? {
+                        return Ok(None);
+                    }
+                    (SyncBase::default(), Some(SyncAction::DeletedLocal))
+                }
+            },
+            // Both changed it: fine only if they ended up the same
+            (true, true) => match (&local_hash, &remote_etag) {
+                (None, None) => (SyncBase::default(), None),
+                (Some(hash), Some(_)) if hash_bytes(&remote.get(path)?) == *hash => (
+                    SyncBase {
+                        etag: remote_etag,
+                        hash: local_hash,
+                    },
+                    None,
+                ),
+                _ => return Ok(None),
+            },
+        };
+        Ok(Some(synced))
+    }
+}
+
+impl SyncReport {
+    /// Whether files on this device were written or deleted, so the index needs a
+    /// rescan.
+    pub fn changed_local(&self) -> bool {
+        self.applied.iter().any(|change| {
+            matches!(
+                change.action,
+                SyncAction::Downloaded | SyncAction::DeletedLocal
+            )
+        })
+    }
+}
+
+impl NotesApi {
+    /// Keeps the remote sync state in a file in `dir`, which must be outside the vault.
+    /// Each vault gets its own file there.
+    pub fn set_sync_state_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let name = compute_hash(&self.notes_root().to_string_lossy());
+        self.sync_state = Some(dir.join(format!("{}.json", name)));
+        Ok(())
+    }
+
+    fn sync_state_path(&self) -> Result<&PathBuf> {
+        self.sync_state
+            .as_ref()
+            .ok_or_else(|| Error::InvalidInput("Remote sync is not set up".to_string()))
+    }
+
+    fn read_sync_state(&self) -> Result<SyncState> {
+        read_state(self.sync_state_path()?)
+    }
+
+    fn write_sync_state(&self, state: &SyncState) -> Result<()> {
+        write_state(self.sync_state_path()?, state)
+    }
+
+    /// The remote this vault syncs with, if any.
+    pub fn remote_config(&self) -> Result<Option<RemoteConfig>> {
+        if self.sync_state.is_none() {
+            return Ok(None);
+        }
+        Ok(self.read_sync_state()?.remote)
+    }
+
+    /// Sets the remote to sync with, or turns sync off with `None`. Pointing the vault
+    /// at another URL forgets what was synced, so the first sync there compares every
+    /// file.
+    pub fn configure_remote(&mut self, remote: Option<RemoteConfig>) -> Result<()> {
+        if let Some(remote) = &remote
+            && !remote.url.starts_with("https://")
+            && !remote.url.starts_with("http://")
+        {
+            return Err(Error::InvalidInput(format!(
+                "Not an http(s) URL: {}",
+                remote.url
+            )));
+        }
+        let mut state = self.read_sync_state()?;
+        let same_url = match (&state.remote, &remote) {
+            (Some(old), Some(new)) => old.url == new.url,
+            _ => false,
+        };
+        if !same_url {
+            state.files.clear();
+            state.conflicts.clear();
+        }
+        state.remote = remote;
+        self.write_sync_state(&state)
+    }
+
+    /// Files left conflicted by earlier syncs, ordered by path.
+    pub fn sync_conflicts(&self) -> Result<Vec<String>> {
+        if self.sync_state.is_none() {
+            return Ok(Vec::new());
+        }
+        Ok(self.read_sync_state()?.conflicts.into_keys().collect())
+    }
+
+    /// Settles a conflict by keeping one side; the next sync copies it over the other.
+    pub fn resolve_sync_conflict(&mut self, path: &str, keep: SyncSide) -> Result<()> {
+        let mut state = self.read_sync_state()?;
+        let Some(remote_etag) = state.conflicts.remove(path) else {
+            return Err(Error::NotFound(path.to_string()));
+        };
+        let base = state.files.entry(path.to_string()).or_default();
+        // Make the kept side the only one that changed since the base
+        match keep {
+            SyncSide::Local => base.etag = remote_etag,
+            SyncSide::Remote => {
+                base.hash = match fs::read(self.notes_root().join(path)) {
+                    Ok(content) => Some(hash_bytes(&content)),
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+        if base.is_empty() {
+            state.files.remove(path);
+        }
+        self.write_sync_state(&state)
+    }
+
+    /// Syncs the vault with `remote`, calling `progress` with the number of files done
+    /// and the total as it goes. The index is rescanned if anything was downloaded or
+    /// deleted here.
+    pub fn sync_with(
+        &mut self,
+        remote: &dyn RemoteStore,
+        progress: impl FnMut(usize, usize),
+    ) -> Result<SyncReport> {
+        let sync = self.start_sync()?;
+        let report = sync.run(remote, |change| self.apply_sync_change(change), progress)?;
+        if report.changed_local() {
+            self.rescan()?;
+        }
+        Ok(report)
+    }
+
+    /// Prepares a sync that runs without the API; see `VaultSync`.
+    pub fn start_sync(&self) -> Result<VaultSync> {
+        self.ensure_writable()?;
+        Ok(VaultSync {
+            root: self.notes_root().to_path_buf(),
+            state_path: self.sync_state_path()?.clone(),
+        })
+    }
+
+    /// Writes or deletes a file for a `VaultSync`. Returns false, touching nothing, if
+    /// the file changed since the sync compared it.
+    pub fn apply_sync_change(&mut self, change: &LocalChange) -> Result<bool> {
+        self.ensure_writable()?;
+        if is_device_local(&change.path)
+            || change
+                .path
+                .split('/')
+                .any(|segment| matches!(segment, "" | "." | ".."))
+        {
+            return Err(Error::InvalidInput(format!(
+                "Not a vault file: {}",
+                change.path
+            )));
+        }
+        let root = self.notes_root().to_path_buf();
+        let file = root.join(&change.path);
+        let current = match fs::read(&file) {
+            Ok(content) => Some(hash_bytes(&content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        if current != change.expected {
+            return Ok(false);
+        }
+
+        match &change.content {
+            Some(content) => {
+                if let Some(parent) = file.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                write_atomic(&file, content)?;
+            }
+            None => {
+                match fs::remove_file(&file) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e.into()),
+                }
+                if let Some(parent) = file.parent() {
+                    prune_empty_dirs(&root, parent);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    /// A remote kept in memory; ETags count writes.
+    #[derive(Default)]
+    struct MemoryRemote {
+        files: RefCell<BTreeMap<String, (Vec<u8>, String)>>,
+        writes: RefCell<usize>,
+    }
+
+    impl MemoryRemote {
+        fn etag(&self, path: &str) -> Option<String> {
+            self.files.borrow().get(path).map(|(_, etag)| etag.clone())
+        }
+    }
+
+    impl RemoteStore for MemoryRemote {
+        fn list(&self) -> Result<Vec<RemoteEntry>> {
+            Ok(self
+                .files
+                .borrow()
+                .iter()
+                .map(|(path, (_, etag))| RemoteEntry {
+                    path: path.clone(),
+                    etag: etag.clone(),
+                })
+                .collect())
+        }
+
+        fn get(&self, path: &str) -> Result<Vec<u8>> {
+            self.files
+                .borrow()
+                .get(path)
+                .map(|(content, _)| content.clone())
+                .ok_or_else(|| Error::NotFound(path.to_string()))
+        }
+
+        fn put(
+            &self,
+            path: &str,
+            content: &[u8],
+            expected: Option<&str>,
+        ) -> Result<Option<String>> {
+            if self.etag(path).as_deref() != expected {
+                return Ok(None);
+            }
+            *self.writes.borrow_mut() += 1;
+            let etag = format!("\"{}\"", self.writes.borrow());
+            self.files
+                .borrow_mut()
+                .insert(path.to_string(), (content.to_vec(), etag.clone()));
+            Ok(Some(etag))
+        }
+
+        fn delete(&self, path: &str, expected: &str) -> Result<bool> {
+            if self.etag(path).as_deref() != Some(expected) {
+                return Ok(false);
+            }
+            self.files.borrow_mut().remove(path);
+            Ok(true)
+        }
+    }
+
+    fn device(state_dir: &TempDir) -> (TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.set_sync_state_dir(state_dir.path()).unwrap();
+        (temp_dir, api)
+    }
+
+    fn actions(report: &SyncReport) -> Vec<SyncAction> {
+        report.applied.iter().map(|change| change.action).collect()
+    }
+
+    #[test]
+    fn test_sync_copies_changes_both_ways() {
+        let state_dir = TempDir::new().unwrap();
+        let remote = MemoryRemote::default();
+        let (_a_dir, mut a) = device(&state_dir);
+        let (_b_dir, mut b) = device(&state_dir);
+
+        a.create_note("inbox").unwrap();
+        a.save_note("inbox", "from a").unwrap();
+        a.create_note("later").unwrap();
+        let mut steps = Vec::new();
+        let report = a
+            .sync_with(&remote, |done, total| steps.push((done, total)))
+            .unwrap();
+        assert!(!report.applied.is_empty());
+        assert!(
+            actions(&report)
+                .iter()
+                .all(|action| *action == SyncAction::Uploaded)
+        );
+        assert_eq!(
+            steps.last(),
+            Some(&(remote.list().unwrap().len(), steps[0].1))
+        );
+        assert!(
+            remote
+                .list()
+                .unwrap()
+                .iter()
+                .all(|e| !is_device_local(&e.path))
+        );
+
+        b.sync_with(&remote, |_, _| {}).unwrap();
+        assert_eq!(b.get_note("inbox").unwrap().content, "from a");
+        assert!(b.note_exists("later").unwrap());
+        // Nothing changed since
+        assert_eq!(
+            b.sync_with(&remote, |_, _| {}).unwrap(),
+            SyncReport::default()
+        );
+
+        b.save_note("inbox", "from b").unwrap();
+        b.delete_note("later").unwrap();
+        b.sync_with(&remote, |_, _| {}).unwrap();
+        let report = a.sync_with(&remote, |_, _| {}).unwrap();
+        assert!(actions(&report).contains(&SyncAction::DeletedLocal));
+        assert!(report.conflicts.is_empty());
+        assert_eq!(a.get_note("inbox").unwrap().content, "from b");
+        assert!(!a.note_exists("later").unwrap());
+    }
+
+    #[test]
+    fn test_conflicts_wait_for_a_side_to_be_picked() {
+        let state_dir = TempDir::new().unwrap();
+        let remote = MemoryRemote::default();
+        let (_a_dir, mut a) = device(&state_dir);
+        let (_b_dir, mut b) = device(&state_dir);
+        a.create_note("inbox").unwrap();
+        a.sync_with(&remote, |_, _| {}).unwrap();
+        b.sync_with(&remote, |_, _| {}).unwrap();
+
+        a.save_note("inbox", "from a").unwrap();
+        b.save_note("inbox", "from b").unwrap();
+        a.sync_with(&remote, |_, _| {}).unwrap();
+        let report = b.sync_with(&remote, |_, _| {}).unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+        let file = report.conflicts[0].clone();
+        assert_eq!(b.sync_conflicts().unwrap(), vec![file.clone()]);
+        // Neither side was touched
+        assert_eq!(b.get_note("inbox").unwrap().content, "from b");
+        assert_eq!(
+            String::from_utf8(remote.get(&file).unwrap()).unwrap(),
+            "from a"
+        );
+        assert_eq!(
+            b.sync_with(&remote, |_, _| {}).unwrap().conflicts,
+            vec![file.clone()]
+        );
+
+        b.resolve_sync_conflict(&file, SyncSide::Local).unwrap();
+        let report = b.sync_with(&remote, |_, _| {}).unwrap();
+        assert_eq!(actions(&report), [SyncAction::Uploaded]);
+        assert!(report.conflicts.is_empty());
+        a.sync_with(&remote, |_, _| {}).unwrap();
+        assert_eq!(a.get_note("inbox").unwrap().content, "from b");
+
+        assert!(matches!(
+            b.resolve_sync_conflict(&file, SyncSide::Remote),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_edit_during_sync_becomes_a_conflict() {
+        let state_dir = TempDir::new().unwrap();
+        let remote = MemoryRemote::default();
+        let (_a_dir, mut a) = device(&state_dir);
+        let (b_dir, mut b) = device(&state_dir);
+        a.create_note("inbox").unwrap();
+        a.sync_with(&remote, |_, _| {}).unwrap();
+        b.sync_with(&remote, |_, _| {}).unwrap();
+        a.save_note("inbox", "from a").unwrap();
+        a.sync_with(&remote, |_, _| {}).unwrap();
+
+        // b saves while its sync is on the network, after the files were compared
+        let file = b_dir.path().join("inbox/_index.md");
+        let sync = b.start_sync().unwrap();
+        let report = sync
+            .run(
+                &remote,
+                |change| {
+                    fs::write(&file, "from b").unwrap();
+                    b.apply_sync_change(change)
+                },
+                |_, _| {},
+            )
+            .unwrap();
+
+        assert_eq!(report.conflicts, vec!["inbox/_index.md".to_string()]);
+        assert!(!report.changed_local());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "from b");
+    }
+
+    #[test]
+    fn test_device_local_files() {
+        assert!(is_device_local(".notes.db-wal"));
+        assert!(is_device_local(".git/HEAD"));
+        assert!(is_device_local("inbox/._index.md.4242.tmp"));
+        assert!(!is_device_local("inbox/_index.md"));
+        assert!(!is_device_local(".obsidian/app.json"));
+    }
+
+    #[test]
+    fn test_changing_the_remote_url_forgets_the_sync_state() {
+        let state_dir = TempDir::new().unwrap();
+        let remote = MemoryRemote::default();
+        let (_dir, mut api) = device(&state_dir);
+        assert_eq!(api.remote_config().unwrap(), None);
+        let config = RemoteConfig {
+            url: "https://cloud.example/dav/".to_string(),
+            username: "me".to_string(),
+            password: "secret".to_string(),
+        };
+        api.configure_remote(Some(config.clone())).unwrap();
+        assert!(!format!("{:?}", config).contains("secret"));
+
+        api.create_note("inbox").unwrap();
+        api.sync_with(&remote, |_, _| {}).unwrap();
+        api.configure_remote(Some(RemoteConfig {
+            password: "new".to_string(),
+            ..config.clone()
+        }))
+        .unwrap();
+        assert!(
+            api.sync_with(&remote, |_, _| {})
+                .unwrap()
+                .applied
+                .is_empty()
+        );
+
+        api.configure_remote(Some(RemoteConfig {
+            url: "https://other.example/dav/".to_string(),
+            ..config
+        }))
+        .unwrap();
+        // Same content on both sides, so no conflict either
+        let report = api.sync_with(&remote, |_, _| {}).unwrap();
+        assert!(report.applied.is_empty() && report.conflicts.is_empty());
+
+        assert!(matches!(
+            api.configure_remote(Some(RemoteConfig {
+                url: "ftp://cloud.example".to_string(),
+                username: String::new(),
+                password: String::new(),
+            })),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+//! Moving a note to a different parent, keeping its name.
+//!
+//! `rename_note` takes a whole new path, so a move and a retitle look the same to
+//! it. `move_note` only changes the parent: it checks the destination, refuses to
+//! move a note into its own subtree, and settles name clashes by `MoveCollision`.
+
+use crate::changes::Revision;
+use crate::notes::{Error, NotesApi, Result, get_parent_path};
+
+/// What `move_note` does when the new parent already has a note of that name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MoveCollision {
+    /// Fail with `Error::AlreadyExists`
+    #[default]
+    Error,
+    /// Append " 2", " 3", … to the name until it is free
+    Suffix,
+}
+
+impl MoveCollision {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MoveCollision::Error => "error",
+            MoveCollision::Suffix => "suffix",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<MoveCollision> {
+        match s {
+            "error" => Some(MoveCollision::Error),
+            "suffix" => Some(MoveCollision::Suffix),
+            _ => None,
+        }
+    }
+}
+
+/// A note that `move_note` moved, with its descendants, from `from` to `to`.
+/// Frontends use it to follow an open note to its new place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteMoved {
+    pub from: String,
+    pub to: String,
+    /// Revision after the move
+    pub revision: Revision,
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
+
+impl NotesApi {
+    /// Moves the note at `path` and its descendants under `new_parent` (empty for the
+    /// vault root), keeping its name unless `collision` adds a suffix.
+    ///
+    /// Fails with `ParentNotFound` if `new_parent` doesn't exist and `InvalidInput` if
+    /// it is the note itself or one of its descendants. Moving a note to the parent
+    /// it is already under fails too, since nothing would change.
+    pub fn move_note(
+        &mut self,
+        path: &str,
+        new_parent: &str,
+        collision: MoveCollision,
+    ) -> Result<NoteMoved> {
+        self.ensure_writable()?;
+
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        if !new_parent.is_empty() && !self.note_exists(new_parent)? {
+            return Err(Error::ParentNotFound(new_parent.to_string()));
+        }
+        if new_parent == path || new_parent.starts_with(&format!("{}/", path)) {
+            return Err(Error::InvalidInput(format!(
+                "Cannot move {} into itself",
+                path
+            )));
+        }
+        if get_parent_path(path).unwrap_or_default() == new_parent {
+            return Err(Error::InvalidInput(format!("{} is already there", path)));
+        }
+
+        let name = path.rsplit('/').next().unwrap_or(path);
+        let mut target = join(new_parent, name);
+        if self.note_exists(&target)? {
+            match collision {
+                MoveCollision::Error => return Err(Error::AlreadyExists(target)),
+                MoveCollision::Suffix => {
+                    let mut n = 2;
+                    while self.note_exists(&target)? {
+                        target = join(new_parent, &format!("{} {}", name, n));
+                        n += 1;
+                    }
+                }
+            }
+        }
+
+        let revision = self.rename_note(path, &target)?;
+        Ok(NoteMoved {
+            from: path.to_string(),
+            to: target,
+            revision,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for path in [
+            "inbox",
+            "inbox/idea",
+            "inbox/idea/sketch",
+            "projects",
+            "projects/idea",
+        ] {
+            api.create_note(path).unwrap();
+        }
+        (temp_dir, api)
+    }
+
+    #[test]
+    fn test_move_note_with_subtree() {
+        let (_temp_dir, mut api) = setup();
+
+        let moved = api
+            .move_note("inbox/idea", "", MoveCollision::Error)
+            .unwrap();
+
+        assert_eq!(moved.from, "inbox/idea");
+        assert_eq!(moved.to, "idea");
+        assert!(api.note_exists("idea/sketch").unwrap());
+        assert!(!api.note_exists("inbox/idea").unwrap());
+    }
+
+    #[test]
+    fn test_move_note_collisions() {
+        let (_temp_dir, mut api) = setup();
+
+        assert!(matches!(
+            api.move_note("inbox/idea", "projects", MoveCollision::Error),
+            Err(Error::AlreadyExists(_))
+        ));
+        let moved = api
+            .move_note("inbox/idea", "projects", MoveCollision::Suffix)
+            .unwrap();
+        assert_eq!(moved.to, "projects/idea 2");
+        assert!(api.note_exists("projects/idea 2/sketch").unwrap());
+    }
+
+    #[test]
+    fn test_move_note_checks_destination() {
+        let (_temp_dir, mut api) = setup();
+
+        assert!(matches!(
+            api.move_note("inbox", "inbox/idea/sketch", MoveCollision::Suffix),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            api.move_note("inbox", "inbox", MoveCollision::Suffix),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            api.move_note("inbox/idea", "inbox", MoveCollision::Suffix),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            api.move_note("inbox", "missing", MoveCollision::Suffix),
+            Err(Error::ParentNotFound(_))
+        ));
+        assert!(matches!(
+            api.move_note("missing", "inbox", MoveCollision::Suffix),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
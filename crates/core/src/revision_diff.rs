@@ -0,0 +1,335 @@
+//! Line diffs between versions of a note.
+//!
+//! A note's earlier versions are the ones recorded in checkpoints (see
+//! `get_note_history`); the current version is the file on disk. Diffs are
+//! computed line by line, for previewing a version before restoring it.
+//! `edit_summary` condenses a diff to word counts and touched headings, small
+//! enough to keep in the change log for every save.
+
+use std::collections::HashMap;
+
+use crate::export::heading;
+use crate::notes::{Error, NotesApi, Result};
+
+/// Above this many line pairs the changed middle of two versions isn't aligned,
+/// and is reported as removed and then added as a whole.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// `MAX_DIFF_CELLS` for `edit_summary`, which runs on every save: small enough that
+/// even a large paste costs about a millisecond. Past it the counts are approximate.
+const MAX_SUMMARY_CELLS: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Same,
+    Added,
+    Removed,
+}
+
+/// One line of a diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+    /// 1-based line number in the older version; `None` for added lines
+    pub old_line: Option<usize>,
+    /// 1-based line number in the newer version; `None` for removed lines
+    pub new_line: Option<usize>,
+}
+
+/// Diffs `old` against `new` line by line, keeping as many lines unchanged as
+/// possible. Within a change, removed lines come before the lines that replace them.
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    line_diff_within(old, new, MAX_DIFF_CELLS)
+}
+
+/// `line_diff`, aligning the changed middle only if it has at most `max_cells` line
+/// pairs.
+fn line_diff_within(old: &str, new: &str, max_cells: usize) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Lines shared at the start and end need no alignment
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    let mut push = |kind: DiffKind, text: &str, i: usize, j: usize| {
+        lines.push(DiffLine {
+            kind,
+            text: text.to_string(),
+            old_line: (kind != DiffKind::Added).then_some(i + 1),
+            new_line: (kind != DiffKind::Removed).then_some(j + 1),
+        });
+    };
+
+    for (i, text) in old[..prefix].iter().enumerate() {
+        push(DiffKind::Same, text, i, i);
+    }
+
+    if old_mid.len() * new_mid.len() > max_cells {
+        for (i, text) in old_mid.iter().enumerate() {
+            push(DiffKind::Removed, text, prefix + i, prefix);
+        }
+        for (j, text) in new_mid.iter().enumerate() {
+            push(DiffKind::Added, text, prefix, prefix + j);
+        }
+    } else {
+        // lcs[i][j]: longest common subsequence of old_mid[i..] and new_mid[j..]
+        let width = new_mid.len() + 1;
+        let mut lcs = vec![0u32; (old_mid.len() + 1) * width];
+        for i in (0..old_mid.len()).rev() {
+            for j in (0..new_mid.len()).rev() {
+                lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < old_mid.len() || j < new_mid.len() {
+            if i < old_mid.len() && j < new_mid.len() && old_mid[i] == new_mid[j] {
+                push(DiffKind::Same, old_mid[i], prefix + i, prefix + j);
+                i += 1;
+                j += 1;
+            } else if j == new_mid.len()
+                || (i < old_mid.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+            {
+                push(DiffKind::Removed, old_mid[i], prefix + i, prefix + j);
+                i += 1;
+            } else {
+                push(DiffKind::Added, new_mid[j], prefix + i, prefix + j);
+                j += 1;
+            }
+        }
+    }
+
+    let old_start = old.len() - suffix;
+    let new_start = new.len() - suffix;
+    for (k, text) in old[old_start..].iter().enumerate() {
+        push(DiffKind::Same, text, old_start + k, new_start + k);
+    }
+    lines
+}
+
+/// What an edit did to a note, without the text itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditSummary {
+    pub words_added: usize,
+    pub words_removed: usize,
+    /// Headings added, then headings removed; a renamed heading is listed under its
+    /// new text and its old one
+    pub headings: Vec<String>,
+}
+
+/// How many words two runs of words share in order. Runs too long to align count
+/// the words they have in common instead.
+fn common_words(old: &[&str], new: &[&str]) -> usize {
+    if old.len() * new.len() > MAX_SUMMARY_CELLS {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for word in old {
+            *counts.entry(word).or_default() += 1;
+        }
+        return new
+            .iter()
+            .filter(|word| match counts.get_mut(*word) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false,
+            })
+            .count();
+    }
+
+    let mut previous = vec![0u32; new.len() + 1];
+    for a in old {
+        let mut row = vec![0u32; new.len() + 1];
+        for (j, b) in new.iter().enumerate() {
+            row[j + 1] = if a == b {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(row[j])
+            };
+        }
+        previous = row;
+    }
+    previous[new.len()] as usize
+}
+
+/// Summarizes the edit from `old` to `new`: words are compared within each changed
+/// run of lines, so fixing one word in a paragraph counts as one word each way.
+///
+/// Lines and words are aligned only up to a budget far below `line_diff`'s, since
+/// this runs on every save. A larger change is summarized by counting the words the
+/// two sides have in common, regardless of order.
+pub fn edit_summary(old: &str, new: &str) -> EditSummary {
+    let diff = line_diff_within(old, new, MAX_SUMMARY_CELLS);
+    let mut summary = EditSummary::default();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let (mut headings_removed, mut headings_added) = (Vec::new(), Vec::new());
+
+    // Each unchanged line (and the end) closes the run of changes before it
+    for line in diff.iter().map(Some).chain([None]) {
+        match line {
+            Some(line) if line.kind != DiffKind::Same => {
+                let (words, headings) = if line.kind == DiffKind::Added {
+                    (&mut added, &mut headings_added)
+                } else {
+                    (&mut removed, &mut headings_removed)
+                };
+                // Markdown syntax like `##` or `-` isn't a word
+                words.extend(
+                    line.text
+                        .split_whitespace()
+                        .filter(|word| word.chars().any(char::is_alphanumeric)),
+                );
+                if let Some((_, text)) = heading(line.text.trim_start()) {
+                    headings.push(text);
+                }
+            }
+            _ => {
+                let common = common_words(&removed, &added);
+                summary.words_added += added.len() - common;
+                summary.words_removed += removed.len() - common;
+                removed.clear();
+                added.clear();
+            }
+        }
+    }
+
+    // A heading removed in one place and added in another only moved
+    for text in headings_added.iter().chain(&headings_removed) {
+        let moved = headings_added.contains(text) && headings_removed.contains(text);
+        if !moved && !summary.headings.iter().any(|heading| heading == text) {
+            summary.headings.push(text.to_string());
+        }
+    }
+    summary
+}
+
+impl NotesApi {
+    /// Diffs two versions of the note at `path`, each given as a checkpoint id or
+    /// `None` for the current content. Lines are reported going from `from` to `to`.
+    pub fn get_revision_diff(
+        &self,
+        path: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<Vec<DiffLine>> {
+        let old = self.note_version(path, from)?;
+        let new = self.note_version(path, to)?;
+        Ok(line_diff(&old, &new))
+    }
+
+    fn note_version(&self, path: &str, checkpoint: Option<&str>) -> Result<String> {
+        match checkpoint {
+            Some(id) => self.get_note_at_checkpoint(path, id),
+            None if self.note_exists(path)? => self.read_content(path),
+            None => Err(Error::NotFound(path.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn render(lines: &[DiffLine]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                let sign = match line.kind {
+                    DiffKind::Same => ' ',
+                    DiffKind::Added => '+',
+                    DiffKind::Removed => '-',
+                };
+                format!("{}{}", sign, line.text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("a\nb\nc\nd\n", "a\nB\nc\nd\ne\n");
+        assert_eq!(render(&diff), vec![" a", "-b", "+B", " c", " d", "+e"]);
+        // Line numbers follow each side
+        assert_eq!((diff[1].old_line, diff[1].new_line), (Some(2), None));
+        assert_eq!((diff[2].old_line, diff[2].new_line), (None, Some(2)));
+        assert_eq!((diff[4].old_line, diff[4].new_line), (Some(4), Some(4)));
+        assert_eq!((diff[5].old_line, diff[5].new_line), (None, Some(5)));
+
+        assert!(line_diff("", "").is_empty());
+        assert_eq!(render(&line_diff("", "x")), vec!["+x"]);
+        assert_eq!(render(&line_diff("x\ny", "y")), vec!["-x", " y"]);
+    }
+
+    #[test]
+    fn test_edit_summary() {
+        let old = "# Plan\nShip the first version soon.\n\n## Risks\nNone yet.\n";
+        let new =
+            "# Plan\nShip the second version soon, then rest.\n\n## Open questions\nNone yet.\n";
+        assert_eq!(
+            edit_summary(old, new),
+            EditSummary {
+                // "second", "soon,", "then", "rest.", "Open", "questions" for
+                // "first", "soon.", "Risks"
+                words_added: 6,
+                words_removed: 3,
+                headings: vec!["Open questions".to_string(), "Risks".to_string()],
+            }
+        );
+
+        // Moving a section touches no heading; its words count both ways
+        let moved = edit_summary("## A\nx\n## B\ny\n", "## B\ny\n## A\nx\n");
+        assert_eq!(
+            (moved.words_added, moved.words_removed, moved.headings.len()),
+            (2, 2, 0)
+        );
+        assert_eq!(edit_summary("", "").words_added, 0);
+    }
+
+    #[test]
+    fn test_large_edit_summary_stays_within_budget() {
+        let old: String = (0..2000).map(|i| format!("line {}\n", i)).collect();
+        let new: String = (0..2000)
+            .map(|i| format!("line {}\n", if i % 2 == 0 { i } else { i + 5000 }))
+            .collect();
+        // Too many line pairs to align, so the whole middle is compared as words:
+        // every "line" and the even numbers are shared, the odd numbers changed
+        let summary = edit_summary(&old, &new);
+        assert_eq!((summary.words_added, summary.words_removed), (1000, 1000));
+        let same = |diff: Vec<DiffLine>| diff.iter().filter(|l| l.kind == DiffKind::Same).count();
+        assert_eq!(same(line_diff_within(&old, &new, MAX_SUMMARY_CELLS)), 1);
+        assert_eq!(same(line_diff(&old, &new)), 1000);
+    }
+
+    #[test]
+    fn test_revision_diff_against_current() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("plan").unwrap();
+        api.save_note("plan", "# Plan\nship it").unwrap();
+        let checkpoint = api.create_checkpoint("before").unwrap();
+        api.save_note("plan", "# Plan\nship it\ncelebrate").unwrap();
+
+        let diff = api
+            .get_revision_diff("plan", Some(&checkpoint.id), None)
+            .unwrap();
+        assert_eq!(render(&diff), vec![" # Plan", " ship it", "+celebrate"]);
+        assert!(matches!(
+            api.get_revision_diff("plan", Some("0"), None),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+//! Frontmatter schemas for structured notes.
+//!
+//! Schemas come from the vault config and describe which properties a kind of
+//! note should carry. Violations are warnings, never errors: a note that breaks
+//! its schema still saves, and the warnings are reported back to the user.
+
+use crate::config::{FieldKind, NoteSchema, SchemaField};
+use crate::dates::Date;
+use crate::frontmatter::{Frontmatter, PropertyValue};
+use crate::notes::{Error, NotesApi, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaIssue {
+    /// A required field is absent or empty
+    Missing,
+    /// The value doesn't have the expected kind
+    WrongKind(FieldKind),
+    /// The value isn't one of the field's allowed values
+    NotAllowed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaWarning {
+    /// Name of the schema the note was checked against
+    pub schema: String,
+    pub field: String,
+    pub issue: SchemaIssue,
+}
+
+impl NoteSchema {
+    /// Whether this schema covers the note at `path` with the given frontmatter.
+    pub fn applies_to(&self, path: &str, frontmatter: &Frontmatter) -> bool {
+        let parent = path
+            .rsplit_once('/')
+            .map(|(parent, _)| parent)
+            .unwrap_or("");
+        let by_parent = self.parent.as_deref() == Some(parent);
+        let by_type = self
+            .note_type
+            .as_deref()
+            .is_some_and(|note_type| frontmatter.get_text("type") == Some(note_type));
+        by_parent || by_type
+    }
+}
+
+/// Checks note content against every schema that applies to it.
+pub fn validate_content(schemas: &[NoteSchema], path: &str, content: &str) -> Vec<SchemaWarning> {
+    let (frontmatter, _) = Frontmatter::parse(content);
+
+    let mut warnings = Vec::new();
+    for schema in schemas.iter().filter(|s| s.applies_to(path, &frontmatter)) {
+        for field in &schema.fields {
+            if let Some(issue) = check_field(field, frontmatter.get(&field.key)) {
+                warnings.push(SchemaWarning {
+                    schema: schema.name.clone(),
+                    field: field.key.clone(),
+                    issue,
+                });
+            }
+        }
+    }
+    warnings
+}
+
+fn check_field(field: &SchemaField, value: Option<&PropertyValue>) -> Option<SchemaIssue> {
+    let values = value.map(PropertyValue::as_list).unwrap_or_default();
+    if values.is_empty() {
+        return field.required.then_some(SchemaIssue::Missing);
+    }
+
+    let kind_ok = match field.kind {
+        FieldKind::Any | FieldKind::List => true,
+        FieldKind::Text => matches!(value, Some(PropertyValue::Text(_))),
+        FieldKind::Date => values.iter().all(|v| Date::parse(v).is_some()),
+        FieldKind::Number => values.iter().all(|v| v.parse::<f64>().is_ok()),
+    };
+    if !kind_ok {
+        return Some(SchemaIssue::WrongKind(field.kind));
+    }
+
+    if field.allowed.is_empty() {
+        return None;
+    }
+    values
+        .into_iter()
+        .find(|v| !field.allowed.contains(v))
+        .map(SchemaIssue::NotAllowed)
+}
+
+impl NotesApi {
+    /// Checks a note's frontmatter against the vault's schemas.
+    ///
+    /// Returns an empty list when the note conforms or no schema applies to it.
+    pub fn validate_note(&self, path: &str) -> Result<Vec<SchemaWarning>> {
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        if self.config.schemas.is_empty() {
+            return Ok(Vec::new());
+        }
+        let content = self.read_content(path)?;
+        Ok(validate_content(&self.config.schemas, path, &content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project_schema() -> NoteSchema {
+        NoteSchema {
+            name: "Project".to_string(),
+            parent: Some("projects".to_string()),
+            note_type: Some("project".to_string()),
+            fields: vec![
+                SchemaField {
+                    key: "status".to_string(),
+                    allowed: vec!["active".to_string(), "done".to_string()],
+                    ..Default::default()
+                },
+                SchemaField {
+                    key: "owner".to_string(),
+                    ..Default::default()
+                },
+                SchemaField {
+                    key: "due".to_string(),
+                    required: false,
+                    kind: FieldKind::Date,
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+
+    fn issues(content: &str, path: &str) -> Vec<(String, SchemaIssue)> {
+        validate_content(&[project_schema()], path, content)
+            .into_iter()
+            .map(|w| (w.field, w.issue))
+            .collect()
+    }
+
+    #[test]
+    fn test_conforming_note_has_no_warnings() {
+        let content = "---\nstatus: active\nowner: Ann\ndue: 2026-03-01\n---\n# Plan\n";
+        assert!(issues(content, "projects/plan").is_empty());
+    }
+
+    #[test]
+    fn test_missing_and_invalid_fields() {
+        let content = "---\nstatus: someday\ndue: next week\n---\n";
+        assert_eq!(
+            issues(content, "projects/plan"),
+            vec![
+                (
+                    "status".to_string(),
+                    SchemaIssue::NotAllowed("someday".to_string())
+                ),
+                ("owner".to_string(), SchemaIssue::Missing),
+                ("due".to_string(), SchemaIssue::WrongKind(FieldKind::Date)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_scope() {
+        // Only direct children of the parent are covered
+        assert!(issues("# Deep\n", "projects/plan/notes").is_empty());
+        assert!(issues("# Other\n", "areas/home").is_empty());
+        // A matching type property opts any note in
+        assert_eq!(
+            issues("---\ntype: project\nstatus: done\n---\n", "areas/home").len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_validate_note() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        let mut config = api.config().clone();
+        config.schemas.push(project_schema());
+        api.set_config(config).unwrap();
+
+        api.create_note("projects").unwrap();
+        api.create_note("projects/plan").unwrap();
+        api.save_note("projects/plan", "---\nstatus: active\n---\n# Plan\n")
+            .unwrap();
+
+        let warnings = api.validate_note("projects/plan").unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].schema, "Project");
+        assert_eq!(warnings[0].field, "owner");
+        assert!(api.validate_note("projects").unwrap().is_empty());
+        assert!(api.validate_note("missing").is_err());
+    }
+}
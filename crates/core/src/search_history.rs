@@ -0,0 +1,203 @@
+//! Recent search queries and replace operations, kept per vault so the search UI
+//! can recall them. Re-recording an entry moves it to the top instead of adding a
+//! duplicate; only the newest entries of each kind are kept.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{OptionalExtension, params};
+
+use crate::batch::ReplaceEdit;
+use crate::notes::{Error, NotesApi, Result};
+
+/// Entries kept per kind; older ones are dropped when a new one is recorded.
+pub const HISTORY_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryKind {
+    Search,
+    Replace,
+}
+
+impl HistoryKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HistoryKind::Search => "search",
+            HistoryKind::Replace => "replace",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<HistoryKind> {
+        match s {
+            "search" => Some(HistoryKind::Search),
+            "replace" => Some(HistoryKind::Replace),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHistoryEntry {
+    pub id: i64,
+    pub kind: HistoryKind,
+    pub query: String,
+    /// Only set for replaces
+    pub replacement: Option<String>,
+    pub used_at: SystemTime,
+}
+
+impl NotesApi {
+    /// Remembers a search the user ran (e.g. picked a result for).
+    pub fn record_search(&mut self, query: &str) -> Result<()> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(());
+        }
+        self.record_history(HistoryKind::Search, query, "")
+    }
+
+    pub(crate) fn record_replace(&mut self, find: &str, replacement: &str) -> Result<()> {
+        self.record_history(HistoryKind::Replace, find, replacement)
+    }
+
+    fn record_history(&mut self, kind: HistoryKind, query: &str, replacement: &str) -> Result<()> {
+        let now = self.clock.unix_secs();
+
+        // Delete and reinsert so the entry gets the newest id
+        self.db.execute(
+            "DELETE FROM search_history WHERE kind = ?1 AND query = ?2 AND replacement = ?3",
+            params![kind.as_str(), query, replacement],
+        )?;
+        self.db.execute(
+            "INSERT INTO search_history (kind, query, replacement, used_at) VALUES (?1, ?2, ?3, ?4)",
+            params![kind.as_str(), query, replacement, now],
+        )?;
+        self.db.execute(
+            "DELETE FROM search_history WHERE kind = ?1 AND id NOT IN (
+                SELECT id FROM search_history WHERE kind = ?1 ORDER BY id DESC LIMIT ?2
+             )",
+            params![kind.as_str(), HISTORY_LIMIT as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Returns recent entries, newest first; pass a kind to only get searches or replaces.
+    pub fn get_search_history(&self, kind: Option<HistoryKind>) -> Result<Vec<SearchHistoryEntry>> {
+        let mut stmt = self.db.prepare(
+            "SELECT id, kind, query, replacement, used_at FROM search_history
+             WHERE ?1 IS NULL OR kind = ?1
+             ORDER BY id DESC",
+        )?;
+        let entries = stmt
+            .query_map(params![kind.map(HistoryKind::as_str)], |row| {
+                let kind: String = row.get(1)?;
+                let replacement: String = row.get(3)?;
+                let used_at: i64 = row.get(4)?;
+                let kind = HistoryKind::parse(&kind).unwrap_or(HistoryKind::Search);
+                Ok(SearchHistoryEntry {
+                    id: row.get(0)?,
+                    kind,
+                    query: row.get(2)?,
+                    replacement: (kind == HistoryKind::Replace).then_some(replacement),
+                    used_at: UNIX_EPOCH + Duration::from_secs(used_at as u64),
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    pub fn clear_search_history(&mut self) -> Result<()> {
+        self.db.execute("DELETE FROM search_history", [])?;
+        Ok(())
+    }
+
+    /// Runs a replace from the history again; see `replace_in_notes`. Use `dry_run`
+    /// to review the affected notes before applying it.
+    pub fn rerun_replace(&mut self, id: i64, dry_run: bool) -> Result<ReplaceEdit> {
+        let entry: Option<(String, String)> = self
+            .db
+            .query_row(
+                "SELECT query, replacement FROM search_history WHERE id = ?1 AND kind = 'replace'",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let (find, replacement) =
+            entry.ok_or_else(|| Error::InvalidInput(format!("No replace with id {}", id)))?;
+        self.replace_in_notes(&find, &replacement, dry_run)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn queries(api: &NotesApi, kind: Option<HistoryKind>) -> Vec<String> {
+        api.get_search_history(kind)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.query)
+            .collect()
+    }
+
+    #[test]
+    fn test_recording_moves_entries_to_top() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.record_search("alpha").unwrap();
+        api.record_search("beta").unwrap();
+        api.record_search(" alpha ").unwrap();
+        api.record_search("").unwrap();
+
+        assert_eq!(queries(&api, None), vec!["alpha", "beta"]);
+
+        api.clear_search_history().unwrap();
+        assert!(queries(&api, None).is_empty());
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        for i in 0..HISTORY_LIMIT + 5 {
+            api.record_search(&format!("query {}", i)).unwrap();
+        }
+        let history = queries(&api, Some(HistoryKind::Search));
+        assert_eq!(history.len(), HISTORY_LIMIT);
+        assert_eq!(history[0], format!("query {}", HISTORY_LIMIT + 4));
+    }
+
+    #[test]
+    fn test_replace_is_recorded_and_rerun() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.save_note("a", "teh cat and teh dog").unwrap();
+        api.create_note("b").unwrap();
+        api.save_note("b", "nothing here").unwrap();
+
+        // Previews aren't recorded
+        let preview = api.replace_in_notes("teh", "the", true).unwrap();
+        assert_eq!(preview.changes.len(), 1);
+        assert_eq!(preview.changes[0].matches, 2);
+        assert!(queries(&api, None).is_empty());
+
+        let edit = api.replace_in_notes("teh", "the", false).unwrap();
+        assert!(edit.revision.is_some());
+        assert_eq!(api.get_note("a").unwrap().content, "the cat and the dog");
+
+        let history = api.get_search_history(Some(HistoryKind::Replace)).unwrap();
+        assert_eq!(history[0].query, "teh");
+        assert_eq!(history[0].replacement.as_deref(), Some("the"));
+
+        // The text is back, so re-running the saved replace finds it again
+        api.save_note("b", "teh end").unwrap();
+        let review = api.rerun_replace(history[0].id, true).unwrap();
+        assert_eq!(review.changes[0].path, "b");
+        api.rerun_replace(history[0].id, false).unwrap();
+        assert_eq!(api.get_note("b").unwrap().content, "the end");
+        assert!(api.rerun_replace(-1, true).is_err());
+    }
+}
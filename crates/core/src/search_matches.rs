@@ -0,0 +1,248 @@
+//! Full-text search results with the lines that matched, so a results list can
+//! show each hit in context and jump to it.
+
+use std::collections::HashMap;
+
+use crate::notes::{NoteMetadata, NotesApi, Result};
+
+/// Matching lines listed per note; `SearchResult::match_count` has the total.
+pub const MAX_MATCHES_PER_NOTE: usize = 5;
+
+/// Characters of context kept before a match on long lines.
+const CONTEXT_BEFORE: usize = 40;
+
+/// Characters a context line is cut to.
+const CONTEXT_LEN: usize = 160;
+
+/// One line of a note that contains a query term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// 1-based line number in the note's Markdown.
+    pub line: usize,
+    /// The line, cut down to the text around the match on long lines.
+    pub text: String,
+    /// Byte range of the first matched term within `text`.
+    pub start: usize,
+    pub end: usize,
+    /// How many times the matched text occurs earlier in the note (ignoring
+    /// case), so an editor can select this occurrence rather than the first.
+    pub occurrence: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub note: NoteMetadata,
+    /// The first `MAX_MATCHES_PER_NOTE` matching lines. Empty when only the path
+    /// matched.
+    pub matches: Vec<SearchMatch>,
+    /// Number of matching lines in the whole note.
+    pub match_count: usize,
+}
+
+/// Extracts the words and phrases of an FTS5 query: operators, column filters,
+/// grouping, and prefix stars are dropped; quoted phrases stay whole.
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut rest = query;
+    while let Some(c) = rest.chars().next() {
+        if c == '"' {
+            let phrase = &rest[1..];
+            let end = phrase.find('"').unwrap_or(phrase.len());
+            terms.push(phrase[..end].trim().to_string());
+            rest = phrase.get(end + 1..).unwrap_or("");
+        } else if c.is_whitespace() || matches!(c, '(' | ')' | '+' | '^') {
+            rest = &rest[c.len_utf8()..];
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || matches!(c, '"' | '(' | ')'))
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            rest = &rest[end..];
+            if matches!(word, "AND" | "OR" | "NOT" | "NEAR") {
+                continue;
+            }
+            // `title:word` filters a column; only the word is looked for
+            let word = word.rsplit_once(':').map_or(word, |(_, w)| w);
+            terms.push(word.trim_end_matches('*').to_string());
+        }
+    }
+    terms.retain(|term| !term.is_empty());
+    terms
+}
+
+/// Finds `needle` in `haystack` ignoring case, returning its byte range.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<(usize, usize)> {
+    haystack.char_indices().find_map(|(start, _)| {
+        let mut rest = haystack[start..].char_indices();
+        for n in needle.chars() {
+            let (_, h) = rest.next()?;
+            if !h.to_lowercase().eq(n.to_lowercase()) {
+                return None;
+            }
+        }
+        let end = rest.next().map_or(haystack.len(), |(i, _)| start + i);
+        Some((start, end))
+    })
+}
+
+/// Counts the non-overlapping occurrences of `needle` in `haystack`, ignoring case.
+fn count_ignore_case(mut haystack: &str, needle: &str) -> usize {
+    let mut count = 0;
+    while let Some((_, end)) = find_ignore_case(haystack, needle) {
+        count += 1;
+        haystack = &haystack[end..];
+    }
+    count
+}
+
+/// Cuts `line` to the text around `start..end`, shifting the range to match.
+fn context(line: &str, start: usize, end: usize) -> (String, usize, usize) {
+    let line = line.trim_end();
+    if line.chars().count() <= CONTEXT_LEN {
+        return (line.to_string(), start, end);
+    }
+    let from = line[..start]
+        .char_indices()
+        .rev()
+        .nth(CONTEXT_BEFORE - 1)
+        .map_or(0, |(i, _)| i);
+    let to = line[from..]
+        .char_indices()
+        .nth(CONTEXT_LEN)
+        .map_or(line.len(), |(i, _)| from + i)
+        .max(end);
+    let prefix = if from > 0 { "…" } else { "" };
+    let suffix = if to < line.len() { "…" } else { "" };
+    let shift = prefix.len() as isize - from as isize;
+    (
+        format!("{}{}{}", prefix, &line[from..to], suffix),
+        (start as isize + shift) as usize,
+        (end as isize + shift) as usize,
+    )
+}
+
+/// Lists the lines of `content` that contain any of `terms`.
+pub fn matching_lines(content: &str, terms: &[String]) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    let mut earlier: HashMap<String, usize> = HashMap::new();
+    for (i, line) in content.lines().enumerate() {
+        if let Some((start, end)) = terms
+            .iter()
+            .filter_map(|term| find_ignore_case(line, term))
+            .min()
+        {
+            let matched = line[start..end].to_lowercase();
+            let occurrence = earlier.get(&matched).copied().unwrap_or(0);
+            let (text, start, end) = context(line, start, end);
+            matches.push(SearchMatch {
+                line: i + 1,
+                text,
+                start,
+                end,
+                occurrence,
+            });
+        }
+        for term in terms {
+            let key = term.to_lowercase();
+            let count = count_ignore_case(line, term);
+            *earlier.entry(key).or_default() += count;
+        }
+    }
+    matches
+}
+
+impl NotesApi {
+    /// Full-text search like `search` (or `search_near` given `current_path`),
+    /// with each note's matching lines for showing results in context.
+    pub fn search_with_matches(
+        &self,
+        query: &str,
+        current_path: Option<&str>,
+    ) -> Result<Vec<SearchResult>> {
+        let notes = match current_path {
+            Some(current) => self.search_near(query, current)?,
+            None => self.search(query)?,
+        };
+        let terms = query_terms(query);
+        let mut results = Vec::with_capacity(notes.len());
+        for note in notes {
+            // The index can be briefly ahead of a note deleted on disk
+            let Ok(content) = self.read_content(&note.path) else {
+                continue;
+            };
+            let mut matches = matching_lines(&content, &terms);
+            let match_count = matches.len();
+            matches.truncate(MAX_MATCHES_PER_NOTE);
+            results.push(SearchResult {
+                note,
+                matches,
+                match_count,
+            });
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_query_terms() {
+        assert_eq!(query_terms("rust AND tauri"), vec!["rust", "tauri"]);
+        assert_eq!(
+            query_terms("\"write queue\" OR sync*"),
+            vec!["write queue", "sync"]
+        );
+        assert_eq!(
+            query_terms("path:inbox NOT (draft)"),
+            vec!["inbox", "draft"]
+        );
+    }
+
+    #[test]
+    fn test_matching_lines() {
+        let content = "# Plans\nShip the Search panel\n\nsearch again, SEARCH!\n";
+        let matches = matching_lines(content, &["search".to_string()]);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].line, 2);
+        assert_eq!(&matches[0].text[matches[0].start..matches[0].end], "Search");
+        assert_eq!(matches[1].line, 4);
+        assert_eq!(matches[1].start, 0);
+        // "search" on line 4 follows the one on line 2
+        assert_eq!(matches[0].occurrence, 0);
+        assert_eq!(matches[1].occurrence, 1);
+    }
+
+    #[test]
+    fn test_long_lines_keep_context_around_match() {
+        let line = format!("{}needle{}", "a".repeat(300), "b".repeat(300));
+        let matches = matching_lines(&line, &["needle".to_string()]);
+
+        let m = &matches[0];
+        assert_eq!(&m.text[m.start..m.end], "needle");
+        assert!(m.text.starts_with('…') && m.text.ends_with('…'));
+        assert!(m.text.chars().count() <= CONTEXT_LEN + 2);
+    }
+
+    #[test]
+    fn test_search_with_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("garden").unwrap();
+        let lines: Vec<String> = (0..8).map(|i| format!("tomato row {}", i)).collect();
+        api.save_note("garden", &lines.join("\n")).unwrap();
+        api.create_note("kitchen").unwrap();
+        api.save_note("kitchen", "Nothing here").unwrap();
+
+        let results = api.search_with_matches("tomato", None).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].note.path, "garden");
+        assert_eq!(results[0].match_count, 8);
+        assert_eq!(results[0].matches.len(), MAX_MATCHES_PER_NOTE);
+        assert_eq!(results[0].matches[1].line, 2);
+    }
+}
@@ -0,0 +1,181 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dates::Date;
+use crate::notes::{Error, NotesApi, Result};
+
+/// A user-defined abbreviation, e.g. `;date` → `{{date}}`.
+///
+/// Snippets live in the vault config so every frontend shares the same definitions.
+/// Expansions may contain `{{date}}` (YYYY-MM-DD) and `{{time}}` (HH:MM, UTC) variables.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub trigger: String,
+    pub expansion: String,
+}
+
+/// Snippets every vault starts with.
+pub fn default_snippets() -> Vec<Snippet> {
+    vec![
+        Snippet {
+            trigger: ";date".to_string(),
+            expansion: "{{date}}".to_string(),
+        },
+        Snippet {
+            trigger: ";time".to_string(),
+            expansion: "{{time}}".to_string(),
+        },
+    ]
+}
+
+/// Substitutes snippet variables using the given time.
+pub fn render_snippet(expansion: &str, now: SystemTime) -> String {
+    let secs = now
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let time = format!("{:02}:{:02}", (secs % 86_400) / 3600, (secs % 3600) / 60);
+
+    expansion
+        .replace("{{date}}", &Date::from_system_time(now).to_string())
+        .replace("{{time}}", &time)
+}
+
+/// Finds a snippet trigger that ends exactly at `cursor` (a byte offset into `text`).
+///
+/// The trigger must be preceded by whitespace or the start of the text. Returns the byte
+/// range to replace together with the matching snippet.
+pub fn find_trigger_at<'a>(
+    snippets: &'a [Snippet],
+    text: &str,
+    cursor: usize,
+) -> Option<(std::ops::Range<usize>, &'a Snippet)> {
+    let before = text.get(..cursor)?;
+    let word_start = before
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    let word = &before[word_start..];
+
+    snippets
+        .iter()
+        .find(|s| !s.trigger.is_empty() && s.trigger == word)
+        .map(|s| (word_start..cursor, s))
+}
+
+impl NotesApi {
+    /// Returns all snippets defined for this vault.
+    pub fn get_snippets(&self) -> &[Snippet] {
+        &self.config.snippets
+    }
+
+    /// Adds or replaces the snippet with the given trigger.
+    pub fn set_snippet(&mut self, trigger: &str, expansion: &str) -> Result<()> {
+        self.ensure_writable()?;
+
+        if trigger.is_empty() || trigger.chars().any(char::is_whitespace) {
+            return Err(Error::InvalidInput(format!(
+                "Invalid snippet trigger: {:?}",
+                trigger
+            )));
+        }
+
+        let mut config = self.config.clone();
+        let snippet = Snippet {
+            trigger: trigger.to_string(),
+            expansion: expansion.to_string(),
+        };
+        match config.snippets.iter_mut().find(|s| s.trigger == trigger) {
+            Some(existing) => *existing = snippet,
+            None => config.snippets.push(snippet),
+        }
+        self.set_config(config)
+    }
+
+    /// Removes the snippet with the given trigger.
+    pub fn remove_snippet(&mut self, trigger: &str) -> Result<()> {
+        self.ensure_writable()?;
+
+        let mut config = self.config.clone();
+        let before = config.snippets.len();
+        config.snippets.retain(|s| s.trigger != trigger);
+        if config.snippets.len() == before {
+            return Err(Error::NotFound(trigger.to_string()));
+        }
+        self.set_config(config)
+    }
+
+    /// Returns the rendered expansion for a trigger, or `None` if no snippet matches.
+    pub fn expand_snippet(&self, trigger: &str) -> Option<String> {
+        self.config
+            .snippets
+            .iter()
+            .find(|s| s.trigger == trigger)
+            .map(|s| render_snippet(&s.expansion, self.clock.now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_snippet() {
+        let now = Date::new(2024, 5, 17).unwrap().to_system_time() + Duration::from_secs(3_660);
+        assert_eq!(render_snippet("{{date}} {{time}}", now), "2024-05-17 01:01");
+    }
+
+    #[test]
+    fn test_find_trigger_at() {
+        let snippets = default_snippets();
+        let text = "Today is ;date";
+        let (range, snippet) = find_trigger_at(&snippets, text, text.len()).unwrap();
+        assert_eq!(range, 9..14);
+        assert_eq!(snippet.trigger, ";date");
+
+        assert!(find_trigger_at(&snippets, "x;date", 6).is_none());
+        assert!(find_trigger_at(&snippets, ";dat", 4).is_none());
+    }
+
+    #[test]
+    fn test_set_and_expand_snippet() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.set_snippet(";mtg", "## Meeting\n- Attendees:\n")
+            .unwrap();
+        assert_eq!(
+            api.expand_snippet(";mtg").as_deref(),
+            Some("## Meeting\n- Attendees:\n")
+        );
+        assert!(api.expand_snippet(";date").is_some());
+        assert!(api.expand_snippet(";nope").is_none());
+
+        // Persisted in the vault config
+        let reopened = NotesApi::new(temp_dir.path()).unwrap();
+        assert!(reopened.get_snippets().iter().any(|s| s.trigger == ";mtg"));
+    }
+
+    #[test]
+    fn test_invalid_and_missing_snippets() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        assert!(matches!(
+            api.set_snippet("two words", "x"),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            api.remove_snippet(";nope"),
+            Err(Error::NotFound(_))
+        ));
+
+        api.remove_snippet(";date").unwrap();
+        assert!(api.expand_snippet(";date").is_none());
+    }
+}
@@ -0,0 +1,219 @@
+//! Cached note statistics (preview text, word count, cover, title, icon, tags, privacy,
+//! and status) for list rendering.
+
+use crate::board::STATUS_KEY;
+use crate::covers::COVER_PROPERTY;
+use crate::frontmatter::Frontmatter;
+use crate::privacy::is_private;
+use crate::status::NoteStatus;
+use crate::tags::extract_tags;
+
+/// Maximum number of characters kept in a note preview.
+pub const PREVIEW_LENGTH: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteStats {
+    /// The start of the note as plain text (markdown and frontmatter removed)
+    pub preview: String,
+    pub word_count: i64,
+    /// The `cover` frontmatter field: an image path relative to the note's folder
+    pub cover: Option<String>,
+    /// Set by `private: true` in the frontmatter
+    pub private: bool,
+    /// The `title` frontmatter field; see `NoteMetadata::title` for the display title
+    pub title: Option<String>,
+    /// The `icon` frontmatter field, usually an emoji shown before the title
+    pub icon: Option<String>,
+    /// Tags used in the note, sorted
+    pub tags: Vec<String>,
+    /// Non-archived child notes; only known to the index, so 0 when computed from content
+    pub child_count: usize,
+    /// The `status` frontmatter field, if it is a known status
+    pub status: Option<NoteStatus>,
+    /// Kept at the top of its parent's children (see `ordering`); only known to the
+    /// index, so false when computed from content
+    pub pinned: bool,
+    /// Stored encrypted (see `encryption`); the other stats are then empty
+    pub encrypted: bool,
+}
+
+/// Computes the preview, word count, cover, title, tags, and privacy for raw note
+/// content.
+pub fn compute_note_stats(content: &str) -> NoteStats {
+    let (frontmatter, body) = Frontmatter::parse(content);
+    let plain = strip_markdown(body);
+
+    let word_count = plain
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count() as i64;
+
+    NoteStats {
+        preview: truncate_preview(&plain),
+        word_count,
+        cover: frontmatter
+            .get_text(COVER_PROPERTY)
+            .map(str::trim)
+            .filter(|cover| !cover.is_empty())
+            .map(str::to_string),
+        private: is_private(&frontmatter),
+        title: frontmatter
+            .get_text("title")
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .map(str::to_string),
+        icon: frontmatter
+            .get_text("icon")
+            .map(str::trim)
+            .filter(|icon| !icon.is_empty())
+            .map(str::to_string),
+        tags: extract_tags(content).into_iter().collect(),
+        child_count: 0,
+        status: frontmatter.get_text(STATUS_KEY).and_then(NoteStatus::parse),
+        pinned: false,
+        encrypted: false,
+    }
+}
+
+/// Reduces markdown to its visible text, joined into a single line.
+pub(crate) fn strip_markdown(body: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+
+    for line in body.lines() {
+        let mut line = line.trim();
+        if line.starts_with("```") || is_rule(line) {
+            continue;
+        }
+
+        line = line.trim_start_matches('#').trim_start();
+        line = line.trim_start_matches('>').trim_start();
+        line = strip_list_marker(line);
+
+        words.extend(strip_inline(line).split_whitespace().map(str::to_string));
+    }
+
+    words.join(" ")
+}
+
+fn is_rule(line: &str) -> bool {
+    line.len() >= 3 && (line.chars().all(|c| c == '-') || line.chars().all(|c| c == '*'))
+}
+
+fn strip_list_marker(line: &str) -> &str {
+    let line = if let Some(rest) = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))
+    {
+        rest
+    } else {
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        match line[digits..].strip_prefix(". ") {
+            Some(rest) if digits > 0 => rest,
+            _ => line,
+        }
+    };
+
+    line.strip_prefix("[ ] ")
+        .or_else(|| line.strip_prefix("[x] "))
+        .or_else(|| line.strip_prefix("[X] "))
+        .unwrap_or(line)
+}
+
+/// Replaces links and wikilinks with their text and drops emphasis/code markers.
+fn strip_inline(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(inner) = rest.strip_prefix("[[")
+            && let Some(end) = inner.find("]]")
+        {
+            let target = &inner[..end];
+            out.push_str(target.rsplit('|').next().unwrap_or(target));
+            rest = &inner[end + 2..];
+            continue;
+        }
+
+        let link_start = rest.strip_prefix("![").or_else(|| rest.strip_prefix('['));
+        if let Some(inner) = link_start
+            && let Some(end) = inner.find("](")
+            && let Some(close) = inner[end + 2..].find(')')
+        {
+            out.push_str(&inner[..end]);
+            rest = &inner[end + 2 + close + 1..];
+            continue;
+        }
+
+        if !matches!(c, '*' | '`' | '~') {
+            out.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out
+}
+
+fn truncate_preview(text: &str) -> String {
+    if text.chars().count() <= PREVIEW_LENGTH {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(PREVIEW_LENGTH).collect();
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_strip_markdown() {
+        let content = "---\nstatus: todo\n---\n# Title\n\nSome **bold** and `code` with a [link](https://example.com) to [[notes/other|Other]].\n\n- [ ] task item\n";
+        let stats = compute_note_stats(content);
+        assert_eq!(
+            stats.preview,
+            "Title Some bold and code with a link to Other. task item"
+        );
+        assert_eq!(stats.word_count, 12);
+        assert_eq!(stats.cover, None);
+        assert_eq!(stats.title, None);
+    }
+
+    #[test]
+    fn test_title_and_tags() {
+        let stats = compute_note_stats(
+            "---\ntitle: Trip plan\nicon: \"🧭\"\ntags: [travel]\n---\nSee #todo",
+        );
+        assert_eq!(stats.title.as_deref(), Some("Trip plan"));
+        assert_eq!(stats.icon.as_deref(), Some("🧭"));
+        assert_eq!(stats.tags, ["todo", "travel"]);
+    }
+
+    #[test]
+    fn test_cover_from_frontmatter() {
+        let stats = compute_note_stats(
+            "---
+cover: images/header.jpg
+---
+Body",
+        );
+        assert_eq!(stats.cover.as_deref(), Some("images/header.jpg"));
+        assert_eq!(stats.preview, "Body");
+    }
+
+    #[test]
+    fn test_preview_is_truncated() {
+        let content = "word ".repeat(100);
+        let stats = compute_note_stats(&content);
+        assert_eq!(stats.word_count, 100);
+        assert!(stats.preview.ends_with('…'));
+        assert!(stats.preview.chars().count() <= PREVIEW_LENGTH + 1);
+    }
+
+    #[test]
+    fn test_empty_note() {
+        let stats = compute_note_stats("");
+        assert_eq!(stats.preview, "");
+        assert_eq!(stats.word_count, 0);
+    }
+}
@@ -0,0 +1,149 @@
+//! Workflow status labels.
+//!
+//! A note's `status` frontmatter field marks where it stands: `todo`, `doing`, or
+//! `done`. That's lighter than a full property schema but enough to run a project
+//! from the note hierarchy. The field is cached with the note's stats so the tree can
+//! show it. `cycle_status` steps through the labels and back to none.
+
+use rusqlite::{OptionalExtension, params};
+
+use crate::board::STATUS_KEY;
+use crate::frontmatter::PropertyValue;
+use crate::notes::{Error, NotesApi, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteStatus {
+    Todo,
+    Doing,
+    Done,
+}
+
+impl NoteStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoteStatus::Todo => "todo",
+            NoteStatus::Doing => "doing",
+            NoteStatus::Done => "done",
+        }
+    }
+
+    /// Reads a `status` value, ignoring case and surrounding whitespace. Other
+    /// values (e.g. `blocked`) aren't a status this module knows about.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "todo" => Some(NoteStatus::Todo),
+            "doing" => Some(NoteStatus::Doing),
+            "done" => Some(NoteStatus::Done),
+            _ => None,
+        }
+    }
+
+    /// The status after `status` when cycling: none, todo, doing, done, none.
+    pub fn next(status: Option<Self>) -> Option<Self> {
+        match status {
+            None => Some(NoteStatus::Todo),
+            Some(NoteStatus::Todo) => Some(NoteStatus::Doing),
+            Some(NoteStatus::Doing) => Some(NoteStatus::Done),
+            Some(NoteStatus::Done) => None,
+        }
+    }
+}
+
+impl NotesApi {
+    /// Returns the status of the note at `path`, from the index.
+    pub fn get_status(&self, path: &str) -> Result<Option<NoteStatus>> {
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        let value: Option<String> = self
+            .db
+            .query_row(
+                "SELECT note_properties.value FROM note_properties
+                 JOIN notes ON note_properties.note_id = notes.id
+                 WHERE notes.path = ?1 AND note_properties.key = ?2
+                   AND note_properties.is_list = 0",
+                params![path, STATUS_KEY],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(value.as_deref().and_then(NoteStatus::parse))
+    }
+
+    /// Sets the status of the note at `path`; `None` removes the field.
+    pub fn set_status(&mut self, path: &str, status: Option<NoteStatus>) -> Result<()> {
+        match status {
+            Some(status) => self.set_property(
+                path,
+                STATUS_KEY,
+                PropertyValue::Text(status.as_str().to_string()),
+            )?,
+            None => self.remove_property(path, STATUS_KEY)?,
+        };
+        Ok(())
+    }
+
+    /// Moves the note at `path` to the next status and returns it. A value that
+    /// isn't a known status starts over at `todo`.
+    pub fn cycle_status(&mut self, path: &str) -> Result<Option<NoteStatus>> {
+        let status = NoteStatus::next(self.get_status(path)?);
+        self.set_status(path, status)?;
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cycle_status() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("launch").unwrap();
+        api.save_note("launch", "# Launch\n").unwrap();
+
+        assert_eq!(api.get_status("launch").unwrap(), None);
+        assert_eq!(api.cycle_status("launch").unwrap(), Some(NoteStatus::Todo));
+        assert_eq!(
+            api.get_note("launch").unwrap().content,
+            "---\nstatus: todo\n---\n# Launch\n"
+        );
+        assert_eq!(api.cycle_status("launch").unwrap(), Some(NoteStatus::Doing));
+        assert_eq!(api.cycle_status("launch").unwrap(), Some(NoteStatus::Done));
+        assert_eq!(api.get_status("launch").unwrap(), Some(NoteStatus::Done));
+        assert_eq!(api.cycle_status("launch").unwrap(), None);
+        assert_eq!(api.get_note("launch").unwrap().content, "# Launch\n");
+    }
+
+    #[test]
+    fn test_status_in_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("project").unwrap();
+        api.create_note("project/a").unwrap();
+        api.create_note("project/b").unwrap();
+        api.save_note("project/a", "---\nstatus: Doing\n---\n")
+            .unwrap();
+        api.save_note("project/b", "---\nstatus: blocked\n---\n")
+            .unwrap();
+
+        let statuses: Vec<Option<NoteStatus>> = api
+            .get_children_with_stats("project")
+            .unwrap()
+            .into_iter()
+            .map(|note| note.stats.unwrap().status)
+            .collect();
+        assert!(statuses.contains(&Some(NoteStatus::Doing)));
+        assert!(statuses.contains(&None));
+
+        // Unknown values start over
+        assert_eq!(api.get_status("project/b").unwrap(), None);
+        assert_eq!(
+            api.cycle_status("project/b").unwrap(),
+            Some(NoteStatus::Todo)
+        );
+        assert!(matches!(api.get_status("missing"), Err(Error::NotFound(_))));
+    }
+}
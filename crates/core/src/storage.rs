@@ -0,0 +1,244 @@
+//! Storage accounting: what the vault's disk space is spent on.
+//!
+//! Files are grouped by what they hold: note documents, archived notes (anything
+//! under an `_archive` folder), attachments (other files next to notes),
+//! checkpoint history, the vault's trash, and the index database. Notes sent to the
+//! OS trash leave the vault, so they aren't counted.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rusqlite::params;
+
+use crate::changes::Revision;
+use crate::checkpoints::CHECKPOINTS_DIR;
+use crate::layout::NoteFiles;
+use crate::notes::{ARCHIVE_DIR, DATABASE_FILE_NAME, Error, NotesApi, Result};
+use crate::trash::TRASH_DIR;
+
+/// Bytes used per category.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageReport {
+    pub notes: u64,
+    pub archive: u64,
+    pub attachments: u64,
+    /// Checkpoints, including their database snapshots
+    pub history: u64,
+    /// Deleted notes waiting in the trash
+    pub trash: u64,
+    /// The index database with its write-ahead log
+    pub database: u64,
+}
+
+impl StorageReport {
+    pub fn total(&self) -> u64 {
+        self.notes + self.archive + self.attachments + self.history + self.trash + self.database
+    }
+}
+
+fn dir_size(dir: &Path) -> std::io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
+
+/// Adds the files below `dir` to `report`; `archived` once inside an `_archive` folder.
+/// Note files (see `NoteFiles::is_note_file`) make up a note; anything else is an
+/// attachment.
+fn tally(
+    dir: &Path,
+    files: &NoteFiles,
+    archived: bool,
+    report: &mut StorageReport,
+) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            tally(
+                &entry.path(),
+                files,
+                archived || name == ARCHIVE_DIR,
+                report,
+            )?;
+        } else if archived {
+            report.archive += metadata.len();
+        } else if files.is_note_file(&name) {
+            report.notes += metadata.len();
+        } else {
+            report.attachments += metadata.len();
+        }
+    }
+    Ok(())
+}
+
+impl NotesApi {
+    /// Measures how much space notes, the archive, attachments, history, the trash,
+    /// and the index take up.
+    pub fn get_storage_report(&self) -> Result<StorageReport> {
+        self.ensure_available()?;
+        let root = self.fs.root_path();
+        let mut report = StorageReport::default();
+        tally(root, &self.note_files(), false, &mut report)?;
+
+        let checkpoints = root.join(CHECKPOINTS_DIR);
+        if checkpoints.is_dir() {
+            report.history = dir_size(&checkpoints)?;
+        }
+        let trash = root.join(TRASH_DIR);
+        if trash.is_dir() {
+            report.trash = dir_size(&trash)?;
+        }
+        for suffix in ["", "-wal", "-shm"] {
+            if let Ok(metadata) =
+                fs::metadata(root.join(format!("{}{}", DATABASE_FILE_NAME, suffix)))
+            {
+                report.database += metadata.len();
+            }
+        }
+        Ok(report)
+    }
+
+    /// Moves every archived note to the OS trash, after creating a checkpoint.
+    ///
+    /// Returns `None` if nothing was archived.
+    pub fn empty_archive(&mut self) -> Result<Option<Revision>> {
+        self.ensure_writable()?;
+
+        // The outermost `_archive` folders holding archived notes
+        let mut stmt = self
+            .db
+            .prepare("SELECT path FROM notes WHERE archived = 1")?;
+        let mut folders: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter_map(|path| {
+                let path = path.ok()?;
+                let segments: Vec<&str> = path.split('/').collect();
+                let end = segments.iter().position(|s| *s == ARCHIVE_DIR)?;
+                Some(segments[..=end].join("/"))
+            })
+            .collect();
+        drop(stmt);
+        folders.sort();
+        folders.dedup();
+        if folders.is_empty() {
+            return Ok(None);
+        }
+
+        // The OS trash can't be undone from here if a later folder fails
+        self.create_checkpoint("Before emptying the archive")?;
+        let revision = self.run_batch(|api| {
+            for folder in &folders {
+                api.fs.trash_note(folder).map_err(|e| match e.kind() {
+                    io::ErrorKind::NotFound => Error::NotFound(folder.clone()),
+                    _ => Error::Io(e),
+                })?;
+                let prefix = format!("{}/", folder);
+                api.db.execute(
+                    "DELETE FROM notes WHERE substr(path, 1, ?2) = ?1",
+                    params![prefix, prefix.chars().count() as i64],
+                )?;
+            }
+            Ok(())
+        })?;
+        Ok(Some(revision))
+    }
+
+    /// Deletes every checkpoint. Returns how many there were.
+    pub fn delete_all_checkpoints(&mut self) -> Result<usize> {
+        let checkpoints = self.list_checkpoints()?;
+        for checkpoint in &checkpoints {
+            self.delete_checkpoint(&checkpoint.id)?;
+        }
+        Ok(checkpoints.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_store::MemoryNoteStore;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_storage_report_categories() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("keep").unwrap();
+        api.save_note("keep", "12345").unwrap();
+        api.create_note("old").unwrap();
+        api.save_note("old", "1234567").unwrap();
+        api.archive_note("old").unwrap();
+        fs::write(temp_dir.path().join("keep/photo.png"), [0u8; 100]).unwrap();
+
+        let report = api.get_storage_report().unwrap();
+        assert_eq!(report.notes, 5);
+        assert_eq!(report.archive, 7);
+        assert_eq!(report.attachments, 100);
+        assert_eq!(report.history, 0);
+        assert!(report.database > 0);
+
+        api.create_checkpoint("before").unwrap();
+        assert!(api.get_storage_report().unwrap().history > 0);
+        assert_eq!(api.delete_all_checkpoints().unwrap(), 1);
+        assert!(api.list_checkpoints().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_empty_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        assert_eq!(api.empty_archive().unwrap(), None);
+
+        api.create_note("projects").unwrap();
+        api.create_note("projects/done").unwrap();
+        api.create_note("projects/done/notes").unwrap();
+        api.archive_note("projects/done").unwrap();
+
+        assert!(api.empty_archive().unwrap().is_some());
+        assert!(!temp_dir.path().join("projects/_archive").exists());
+        assert_eq!(
+            api.list_checkpoints().unwrap()[0].label,
+            "Before emptying the archive"
+        );
+        let paths: Vec<String> = api
+            .db
+            .prepare("SELECT path FROM notes")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        assert_eq!(paths, ["projects"]);
+        assert_eq!(api.get_storage_report().unwrap().archive, 0);
+    }
+
+    #[test]
+    fn test_empty_archive_reports_io_errors() {
+        let store = MemoryNoteStore::new();
+        let mut api = NotesApi::with_store(Box::new(store.clone())).unwrap();
+        api.create_note("projects").unwrap();
+        api.create_note("projects/done").unwrap();
+        api.archive_note("projects/done").unwrap();
+
+        store.fail_next_write(io::ErrorKind::PermissionDenied);
+        assert!(matches!(
+            api.empty_archive(),
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::PermissionDenied
+        ));
+        assert!(api.empty_archive().unwrap().is_some());
+    }
+}
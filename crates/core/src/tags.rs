@@ -0,0 +1,414 @@
+//! Hierarchical `#tags`.
+//!
+//! Tags come from inline `#tag` tokens in the body (outside code and links) and
+//! from the `tags` frontmatter property. A `/` nests them: `#project/rust/app`
+//! is filed under `project` and `project/rust`. Tags are indexed lowercase with
+//! their full path, so a parent tag matches every note tagged below it.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ops::Range;
+
+use rusqlite::{Connection, params};
+
+use crate::changes::Revision;
+use crate::frontmatter::{Frontmatter, PropertyValue, set_property_in_content};
+use crate::notes::{Error, NoteMetadata, NotesApi, Result, metadata_from_row};
+use crate::unlinked_mentions::excluded_ranges;
+
+/// Frontmatter property listing a note's tags.
+const TAGS_PROPERTY: &str = "tags";
+
+/// A tag and its nested tags, with how many notes use it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagNode {
+    /// Last segment (`app` for `project/rust/app`)
+    pub name: String,
+    /// Full tag path
+    pub tag: String,
+    /// Notes tagged with this tag or any tag below it
+    pub count: usize,
+    /// Notes tagged with exactly this tag
+    pub direct_count: usize,
+    pub children: Vec<TagNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagRename {
+    /// Notes whose tags were rewritten
+    pub notes: Vec<String>,
+    /// Revision after the rewrite; `None` for a dry run or if no note used the tag
+    pub revision: Option<Revision>,
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// Lowercase tag path without `#` and empty segments, or `None` if it isn't a tag.
+///
+/// Purely numeric tags are rejected so `#1` in "issue #1" isn't one.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_start_matches('#');
+    if tag.is_empty() || !tag.chars().all(is_tag_char) {
+        return None;
+    }
+    let segments: Vec<String> = tag
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let has_letter = segments
+        .iter()
+        .any(|segment| segment.chars().any(|c| !c.is_ascii_digit()));
+    (has_letter && !segments.is_empty()).then(|| segments.join("/"))
+}
+
+/// Inline `#tag` tokens in the body: the range of each (including `#`) and its tag.
+fn inline_tags(content: &str) -> Vec<(Range<usize>, String)> {
+    let excluded = excluded_ranges(content);
+    let mut tags = Vec::new();
+    let mut prev: Option<char> = None;
+    for (start, c) in content.char_indices() {
+        let after_space = prev.is_none_or(|p| p.is_whitespace() || p == '(');
+        prev = Some(c);
+        if c != '#' || !after_space || excluded.iter().any(|ex| ex.contains(&start)) {
+            continue;
+        }
+        let rest = &content[start + 1..];
+        let len = rest.find(|c: char| !is_tag_char(c)).unwrap_or(rest.len());
+        let token = rest[..len].trim_end_matches('/');
+        if let Some(tag) = normalize_tag(token) {
+            tags.push((start..start + 1 + token.len(), tag));
+        }
+    }
+    tags
+}
+
+/// All tags of a note, from its body and its `tags` property.
+pub fn extract_tags(content: &str) -> BTreeSet<String> {
+    let (frontmatter, _) = Frontmatter::parse(content);
+    let mut tags: BTreeSet<String> = frontmatter
+        .get(TAGS_PROPERTY)
+        .map(PropertyValue::as_list)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|tag| normalize_tag(tag))
+        .collect();
+    tags.extend(inline_tags(content).into_iter().map(|(_, tag)| tag));
+    tags
+}
+
+/// Replaces the indexed tags for a note.
+pub(crate) fn index_tags(db: &Connection, note_id: i64, content: &str) -> Result<()> {
+    db.execute("DELETE FROM tags WHERE note_id = ?1", params![note_id])?;
+    for tag in extract_tags(content) {
+        db.execute(
+            "INSERT OR IGNORE INTO tags (note_id, tag) VALUES (?1, ?2)",
+            params![note_id, tag],
+        )?;
+    }
+    Ok(())
+}
+
+/// `tag` renamed from `old` to `new` if it is `old` or nested below it.
+fn renamed(tag: &str, old: &str, new: &str) -> Option<String> {
+    if tag == old {
+        Some(new.to_string())
+    } else {
+        tag.strip_prefix(old)
+            .filter(|rest| rest.starts_with('/'))
+            .map(|rest| format!("{}{}", new, rest))
+    }
+}
+
+/// `content` with `old` and its nested tags renamed, or `None` if it has none of them.
+fn rename_in_content(content: &str, old: &str, new: &str) -> Option<String> {
+    let mut updated = content.to_string();
+    let mut changed = false;
+
+    // Back to front, so earlier ranges stay valid
+    for (range, tag) in inline_tags(content).into_iter().rev() {
+        if let Some(tag) = renamed(&tag, old, new) {
+            updated.replace_range(range, &format!("#{}", tag));
+            changed = true;
+        }
+    }
+
+    let (frontmatter, _) = Frontmatter::parse(&updated);
+    if let Some(value) = frontmatter.get(TAGS_PROPERTY) {
+        let items = value.as_list();
+        let renamed_items: Vec<String> = items
+            .iter()
+            .map(|item| {
+                normalize_tag(item)
+                    .and_then(|tag| renamed(&tag, old, new))
+                    .unwrap_or_else(|| item.clone())
+            })
+            .collect();
+        if renamed_items != items {
+            let value = match value {
+                PropertyValue::Text(_) => PropertyValue::Text(renamed_items.join(", ")),
+                PropertyValue::List(_) => PropertyValue::List(renamed_items),
+            };
+            updated = set_property_in_content(&updated, TAGS_PROPERTY, value);
+            changed = true;
+        }
+    }
+
+    changed.then_some(updated)
+}
+
+pub(crate) fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+impl NotesApi {
+    /// Returns every tag in use as a tree, with note counts. Archived notes aren't counted.
+    pub fn get_tag_tree(&self) -> Result<Vec<TagNode>> {
+        let mut stmt = self.db.prepare(
+            "SELECT tags.tag, tags.note_id
+             FROM tags
+             JOIN notes ON tags.note_id = notes.id
+             WHERE notes.archived = 0",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        // Notes per tag path, counting each note once under every ancestor
+        let mut all: BTreeMap<String, HashSet<i64>> = BTreeMap::new();
+        let mut direct: BTreeMap<String, usize> = BTreeMap::new();
+        for (tag, note_id) in rows {
+            *direct.entry(tag.clone()).or_default() += 1;
+            let mut end = 0;
+            for segment in tag.split('/') {
+                end += segment.len();
+                all.entry(tag[..end].to_string())
+                    .or_default()
+                    .insert(note_id);
+                end += 1;
+            }
+        }
+
+        fn build(
+            parent: Option<&str>,
+            all: &BTreeMap<String, HashSet<i64>>,
+            direct: &BTreeMap<String, usize>,
+        ) -> Vec<TagNode> {
+            all.iter()
+                .filter(|(tag, _)| match parent {
+                    None => !tag.contains('/'),
+                    Some(parent) => tag
+                        .strip_prefix(parent)
+                        .and_then(|rest| rest.strip_prefix('/'))
+                        .is_some_and(|rest| !rest.contains('/')),
+                })
+                .map(|(tag, notes)| TagNode {
+                    name: tag.rsplit('/').next().unwrap_or(tag).to_string(),
+                    tag: tag.clone(),
+                    count: notes.len(),
+                    direct_count: direct.get(tag).copied().unwrap_or(0),
+                    children: build(Some(tag), all, direct),
+                })
+                .collect()
+        }
+
+        Ok(build(None, &all, &direct))
+    }
+
+    /// Returns every tag path in use, parents included, in sorted order (e.g. for
+    /// completing tags or a flat tag list). Counts come with `get_tag_tree`.
+    pub fn get_tags(&self) -> Result<Vec<String>> {
+        fn flatten(nodes: Vec<TagNode>, tags: &mut Vec<String>) {
+            for node in nodes {
+                tags.push(node.tag);
+                flatten(node.children, tags);
+            }
+        }
+        let mut tags = Vec::new();
+        flatten(self.get_tag_tree()?, &mut tags);
+        Ok(tags)
+    }
+
+    /// Returns the non-archived notes tagged with `tag` or any tag nested below it.
+    pub fn get_notes_with_tag(&self, tag: &str) -> Result<Vec<NoteMetadata>> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Ok(Vec::new());
+        };
+        let mut stmt = self.db.prepare(
+            "SELECT DISTINCT notes.id, notes.path, notes.mtime, notes.archived
+             FROM tags
+             JOIN notes ON tags.note_id = notes.id
+             WHERE notes.archived = 0 AND (tags.tag = ?1 OR tags.tag LIKE ?2 ESCAPE '\\')
+             ORDER BY notes.path ASC",
+        )?;
+        let notes = stmt
+            .query_map(
+                params![tag, format!("{}/%", escape_like(&tag))],
+                metadata_from_row,
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(notes)
+    }
+
+    /// Renames a tag everywhere it is used, along with the tags nested below it
+    /// (`#project/rust` becomes `#work/rust` when renaming `project` to `work`).
+    ///
+    /// The new name is normalized like any tag (`#Work//Rust` becomes `work/rust`).
+    /// With `dry_run` nothing is written and the result lists the notes that would
+    /// change. Otherwise they are rewritten as one batch.
+    pub fn rename_tag(&mut self, old: &str, new: &str, dry_run: bool) -> Result<TagRename> {
+        if !dry_run {
+            self.ensure_writable()?;
+        }
+        let invalid = |tag: &str| Error::InvalidInput(format!("Not a valid tag: {}", tag));
+        let old = normalize_tag(old).ok_or_else(|| invalid(old))?;
+        let new_tag = normalize_tag(new).ok_or_else(|| invalid(new))?;
+
+        let mut planned: Vec<(String, String, String)> = Vec::new();
+        for note in self.get_notes_with_tag(&old)? {
+            let content = self.read_content(&note.path)?;
+            if let Some(updated) = rename_in_content(&content, &old, &new_tag) {
+                planned.push((note.path, content, updated));
+            }
+        }
+
+        let revision = if dry_run || planned.is_empty() {
+            None
+        } else {
+            let writes: Vec<(&str, &str, &str)> = planned
+                .iter()
+                .map(|(path, original, updated)| {
+                    (path.as_str(), original.as_str(), updated.as_str())
+                })
+                .collect();
+            Some(self.write_batch(&writes)?)
+        };
+
+        Ok(TagRename {
+            notes: planned.into_iter().map(|(path, _, _)| path).collect(),
+            revision,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tags(content: &str) -> Vec<String> {
+        extract_tags(content).into_iter().collect()
+    }
+
+    #[test]
+    fn test_extract_tags() {
+        assert_eq!(
+            tags(
+                "---\ntags: [Reading, '#area/home']\n---\n# Title\nSee #Project/Rust/app and #todo/."
+            ),
+            ["area/home", "project/rust/app", "reading", "todo"]
+        );
+        // Headings, issue numbers, URL fragments, and code aren't tags
+        assert!(
+            tags("## Heading\nissue #12\nhttps://x.io/#frag\n`#code`\n```\n#no\n```").is_empty()
+        );
+    }
+
+    #[test]
+    fn test_tag_tree_counts_descendants() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        for (path, content) in [
+            ("a", "#project/rust/app"),
+            ("b", "#project/rust #project/rust/cli"),
+            ("c", "#project #reading"),
+        ] {
+            api.create_note(path).unwrap();
+            api.save_note(path, content).unwrap();
+        }
+
+        let tree = api.get_tag_tree().unwrap();
+        let names: Vec<&str> = tree.iter().map(|n| n.tag.as_str()).collect();
+        assert_eq!(names, ["project", "reading"]);
+        let project = &tree[0];
+        assert_eq!((project.count, project.direct_count), (3, 1));
+        let rust = &project.children[0];
+        assert_eq!(
+            (rust.tag.as_str(), rust.count, rust.direct_count),
+            ("project/rust", 2, 1)
+        );
+        let leaves: Vec<&str> = rust.children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(leaves, ["app", "cli"]);
+        assert_eq!(
+            api.get_tags().unwrap(),
+            [
+                "project",
+                "project/rust",
+                "project/rust/app",
+                "project/rust/cli",
+                "reading"
+            ]
+        );
+
+        let paths = |notes: Vec<NoteMetadata>| -> Vec<String> {
+            notes.into_iter().map(|n| n.path).collect()
+        };
+        assert_eq!(
+            paths(api.get_notes_with_tag("#Project/rust").unwrap()),
+            ["a", "b"]
+        );
+        assert_eq!(
+            paths(api.get_notes_with_tag("project").unwrap()),
+            ["a", "b", "c"]
+        );
+        // A tag that only shares a prefix isn't nested
+        assert!(api.get_notes_with_tag("proj").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rename_tag_cascades_to_children() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.save_note(
+            "a",
+            "---\ntags: [project/rust]\n---\n#Project and #project/rust/app, #projects",
+        )
+        .unwrap();
+
+        let preview = api.rename_tag("project", "work", true).unwrap();
+        assert_eq!(preview.notes, ["a"]);
+        assert!(preview.revision.is_none());
+        assert_eq!(api.get_notes_with_tag("project").unwrap().len(), 1);
+
+        let rename = api.rename_tag("project", "work", false).unwrap();
+        assert_eq!(rename.notes, ["a"]);
+        assert!(rename.revision.is_some());
+        assert_eq!(
+            api.get_note("a").unwrap().content,
+            "---\ntags: [work/rust]\n---\n#work and #work/rust/app, #projects"
+        );
+        assert!(api.get_notes_with_tag("project").unwrap().is_empty());
+        assert_eq!(api.get_notes_with_tag("work/rust").unwrap().len(), 1);
+
+        assert!(matches!(
+            api.rename_tag("work", "not a tag", false),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            api.rename_tag("work", "2024", false),
+            Err(Error::InvalidInput(_))
+        ));
+
+        api.rename_tag("work", "#Jobs//Current/", false).unwrap();
+        assert_eq!(
+            api.get_note("a").unwrap().content,
+            "---\ntags: [jobs/current/rust]\n---\n#jobs/current and #jobs/current/rust/app, #projects"
+        );
+    }
+}
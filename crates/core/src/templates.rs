@@ -0,0 +1,225 @@
+//! Note templates: any note's content used as the start of a new note.
+//!
+//! Besides the snippet variables (`{{date}}`, `{{time}}`), a template can mark where
+//! the caret goes with `${cursor}` and ask for values with numbered variables like
+//! `${1:project name}`. A number used more than once gets the same value everywhere;
+//! the text after the colon is what the prompt shows and what's filled in when no
+//! value is given. Anything else that looks like `${...}` is left as written.
+
+use std::collections::HashMap;
+
+use crate::changes::Revision;
+use crate::notes::{Error, NotesApi, Result};
+use crate::snippets::render_snippet;
+
+const CURSOR: &str = "cursor";
+
+/// A value a template asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateField {
+    pub index: u32,
+    /// The first non-empty label the variable has in the template
+    pub label: String,
+}
+
+/// What to prompt for before instantiating a template.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateSchema {
+    /// Ordered by number
+    pub fields: Vec<TemplateField>,
+    pub has_cursor: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedTemplate {
+    pub content: String,
+    /// Byte offset of `${cursor}` in `content`, if the template has one
+    pub cursor: Option<usize>,
+}
+
+/// A note created from a template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateNote {
+    pub revision: Revision,
+    pub content: String,
+    /// Byte offset in `content` to put the caret at
+    pub cursor: Option<usize>,
+}
+
+enum Placeholder<'a> {
+    Cursor,
+    Variable { index: u32, label: &'a str },
+}
+
+/// Splits a template into literal text and placeholders, in order.
+fn placeholders(template: &str) -> Vec<(std::ops::Range<usize>, Placeholder<'_>)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(start) = template[from..].find("${").map(|i| from + i) {
+        let Some(end) = template[start..].find('}').map(|i| start + i) else {
+            break;
+        };
+        let inner = &template[start + 2..end];
+        let (number, label) = inner.split_once(':').unwrap_or((inner, ""));
+        let placeholder = if inner == CURSOR {
+            Some(Placeholder::Cursor)
+        } else if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) {
+            number
+                .parse()
+                .ok()
+                .map(|index| Placeholder::Variable { index, label })
+        } else {
+            None
+        };
+        match placeholder {
+            Some(placeholder) => {
+                found.push((start..end + 1, placeholder));
+                from = end + 1;
+            }
+            None => from = start + 2,
+        }
+    }
+    found
+}
+
+/// Lists the variables a template asks for and whether it places the caret.
+pub fn template_schema(template: &str) -> TemplateSchema {
+    let mut schema = TemplateSchema::default();
+    for (_, placeholder) in placeholders(template) {
+        match placeholder {
+            Placeholder::Cursor => schema.has_cursor = true,
+            Placeholder::Variable { index, label } => {
+                match schema.fields.iter_mut().find(|f| f.index == index) {
+                    Some(field) if field.label.is_empty() => field.label = label.to_string(),
+                    Some(_) => {}
+                    None => schema.fields.push(TemplateField {
+                        index,
+                        label: label.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+    schema.fields.sort_by_key(|field| field.index);
+    schema
+}
+
+/// Fills in the variables (falling back to their labels) and takes out `${cursor}`,
+/// remembering where it was. Only the first `${cursor}` counts; others are dropped.
+pub fn render_template(template: &str, values: &HashMap<u32, String>) -> RenderedTemplate {
+    let schema = template_schema(template);
+    let mut content = String::with_capacity(template.len());
+    let mut cursor = None;
+    let mut last = 0;
+    for (range, placeholder) in placeholders(template) {
+        content.push_str(&template[last..range.start]);
+        match placeholder {
+            Placeholder::Cursor => {
+                cursor.get_or_insert(content.len());
+            }
+            Placeholder::Variable { index, .. } => {
+                let value = values.get(&index).map(String::as_str);
+                let label = schema.fields.iter().find(|f| f.index == index);
+                let label = label.map(|f| f.label.as_str());
+                content.push_str(value.filter(|v| !v.is_empty()).or(label).unwrap_or(""));
+            }
+        }
+        last = range.end;
+    }
+    content.push_str(&template[last..]);
+    RenderedTemplate { content, cursor }
+}
+
+impl NotesApi {
+    /// What to ask for before `create_from_template` with this template note.
+    pub fn get_template_schema(&self, template_path: &str) -> Result<TemplateSchema> {
+        let template = self.get_note_internal(template_path)?;
+        Ok(template_schema(&template.content))
+    }
+
+    /// Creates the note at `path` with the content of the template note, its
+    /// variables filled in from `values` (by number).
+    pub fn create_from_template(
+        &mut self,
+        path: &str,
+        template_path: &str,
+        values: &HashMap<u32, String>,
+    ) -> Result<TemplateNote> {
+        if self.note_exists(path)? {
+            return Err(Error::AlreadyExists(path.to_string()));
+        }
+        let template = self.get_note_internal(template_path)?.content;
+        let rendered = render_template(&render_snippet(&template, self.clock.now()), values);
+
+        self.create_note(path)?;
+        let revision = self.save_note(path, &rendered.content)?;
+        Ok(TemplateNote {
+            revision,
+            content: rendered.content,
+            cursor: rendered.cursor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_template_schema() {
+        let schema = template_schema("# ${1}\n\n${2:owner} for ${1:project name}\n${cursor}");
+        assert!(schema.has_cursor);
+        assert_eq!(
+            schema.fields,
+            [
+                TemplateField {
+                    index: 1,
+                    label: "project name".to_string()
+                },
+                TemplateField {
+                    index: 2,
+                    label: "owner".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            template_schema("costs ${x} and $5"),
+            TemplateSchema::default()
+        );
+    }
+
+    #[test]
+    fn test_render_template() {
+        let values = HashMap::from([(1, "Zinnia".to_string())]);
+        let rendered =
+            render_template("# ${1:name}\n\n${cursor}\n\n${2:owner}, ${1} ${x}", &values);
+        assert_eq!(rendered.content, "# Zinnia\n\n\n\nowner, Zinnia ${x}");
+        assert_eq!(rendered.cursor, Some("# Zinnia\n\n".len()));
+
+        assert_eq!(render_template("no caret", &values).cursor, None);
+    }
+
+    #[test]
+    fn test_create_from_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("meeting").unwrap();
+        api.save_note("meeting", "# ${1:topic}\n\n- ${cursor}")
+            .unwrap();
+
+        let values = HashMap::from([(1, "Roadmap".to_string())]);
+        let note = api
+            .create_from_template("roadmap", "meeting", &values)
+            .unwrap();
+        let content = api.get_note("roadmap").unwrap().content;
+        assert_eq!(content, "# Roadmap\n\n- ");
+        assert_eq!(note.content, content);
+        assert_eq!(note.cursor, Some(content.len()));
+
+        assert!(matches!(
+            api.create_from_template("roadmap", "meeting", &values),
+            Err(Error::AlreadyExists(_))
+        ));
+    }
+}
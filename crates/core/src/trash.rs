@@ -0,0 +1,261 @@
+//! The vault's own trash, so deleting a note can be undone.
+//!
+//! Deleted notes are moved, with their descendants and attachments, into `.trash/`
+//! in the vault. Each entry keeps the path it was deleted from:
+//!
+//! ```text
+//! .trash/
+//!   <id>/                   the note's folder
+//!   <id>.json               original path and deletion time
+//! ```
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::changes::Revision;
+use crate::notes::{Error, NotesApi, OperationGuard, Result};
+
+/// Folder in the vault root holding deleted notes (hidden, so it's never indexed).
+pub const TRASH_DIR: &str = ".trash";
+
+/// A deleted note waiting in the trash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashedNote {
+    pub id: String,
+    /// Where the note lived before it was deleted
+    pub original_path: String,
+    /// Unix timestamp in seconds
+    pub deleted_at: i64,
+}
+
+fn invalid_data(err: serde_json::Error) -> Error {
+    Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+}
+
+impl NotesApi {
+    fn trash_dir(&self) -> PathBuf {
+        self.fs.root_path().join(TRASH_DIR)
+    }
+
+    /// Moves a note's folder into the trash; the caller updates the index.
+    pub(crate) fn move_to_trash(&self, path: &str) -> Result<()> {
+        if path.is_empty() {
+            return Err(Error::InvalidInput(
+                "The vault root can't be moved to the trash".to_string(),
+            ));
+        }
+        // Hidden folders (the trash itself, checkpoints) never hold notes
+        if path.split('/').any(|segment| segment.starts_with('.')) {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        // A note kept as a plain file goes into the trash as a folder like any other
+        self.fs.promote_note(path)?;
+        let source = self.fs.root_path().join(path);
+        if !source.is_dir() {
+            return Err(Error::NotFound(path.to_string()));
+        }
+
+        let dir = self.trash_dir();
+        fs::create_dir_all(&dir)?;
+
+        let now = self.clock.now();
+        let millis = now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let mut id = format!("{:013}", millis);
+        let mut n = 1;
+        while dir.join(&id).exists() || dir.join(format!("{}.json", id)).exists() {
+            id = format!("{:013}-{}", millis, n);
+            n += 1;
+        }
+
+        let entry = TrashedNote {
+            id: id.clone(),
+            original_path: path.to_string(),
+            deleted_at: now
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0),
+        };
+        let record = dir.join(format!("{}.json", id));
+        let raw = serde_json::to_string_pretty(&entry).map_err(invalid_data)?;
+        fs::write(&record, raw)?;
+        if let Err(err) = fs::rename(&source, dir.join(&id)) {
+            let _ = fs::remove_file(&record);
+            return Err(err.into());
+        }
+        if let Some((parent, _)) = path.rsplit_once('/') {
+            self.fs.demote_note(parent)?;
+        }
+        Ok(())
+    }
+
+    /// Lists the notes in the trash, most recently deleted first.
+    pub fn list_trash(&self) -> Result<Vec<TrashedNote>> {
+        let dir = self.trash_dir();
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            let Some(id) = name.strip_suffix(".json") else {
+                continue;
+            };
+            // A record whose folder is gone has nothing left to restore
+            if !dir.join(id).is_dir() {
+                continue;
+            }
+            let raw = fs::read_to_string(dir.join(&name))?;
+            entries.push(serde_json::from_str::<TrashedNote>(&raw).map_err(invalid_data)?);
+        }
+        // Ids sort by deletion time
+        entries.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(entries)
+    }
+
+    /// Puts the most recently deleted note at `path` back where it was, descendants
+    /// included.
+    ///
+    /// Fails with `AlreadyExists` if a note has since been created at that path.
+    pub fn restore_from_trash(&mut self, path: &str) -> Result<Revision> {
+        self.ensure_writable()?;
+
+        let entry = self
+            .list_trash()?
+            .into_iter()
+            .find(|entry| entry.original_path == path)
+            .ok_or_else(|| Error::NotFound(path.to_string()))?;
+        let dest = self.fs.root_path().join(path);
+        if dest.exists() || self.note_exists(path)? {
+            return Err(Error::AlreadyExists(path.to_string()));
+        }
+
+        {
+            let _guard = OperationGuard::new(Arc::clone(&self.operation_in_progress));
+
+            let dir = self.trash_dir();
+            if let Some((parent, _)) = path.rsplit_once('/') {
+                self.fs.promote_note(parent)?;
+            }
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(dir.join(&entry.id), &dest)?;
+            fs::remove_file(dir.join(format!("{}.json", entry.id)))?;
+            self.fs.demote_note(path)?;
+
+            let restored: Vec<String> = self
+                .fs
+                .scan_subtree(path)?
+                .into_iter()
+                .map(|note| note.path)
+                .collect();
+            for note in &restored {
+                self.sync_note(note)?;
+            }
+        }
+
+        self.revision()
+    }
+
+    /// Permanently deletes notes that have been in the trash for longer than
+    /// `older_than`, or everything in it when `None`.
+    ///
+    /// Returns how many trashed notes were removed.
+    pub fn empty_trash(&mut self, older_than: Option<Duration>) -> Result<usize> {
+        self.ensure_writable()?;
+
+        let now = self.clock.now();
+        let dir = self.trash_dir();
+        let mut removed = 0;
+        for entry in self.list_trash()? {
+            let deleted_at = UNIX_EPOCH + Duration::from_secs(entry.deleted_at.max(0) as u64);
+            let age = now.duration_since(deleted_at).unwrap_or_default();
+            if older_than.is_some_and(|limit| age <= limit) {
+                continue;
+            }
+            fs::remove_dir_all(dir.join(&entry.id))?;
+            fs::remove_file(dir.join(format!("{}.json", entry.id)))?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_deleted_notes_can_be_restored() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("projects").unwrap();
+        api.save_note("projects", "All projects").unwrap();
+        api.create_note("projects/rust").unwrap();
+        api.save_note("projects/rust", "Rust notes").unwrap();
+
+        api.delete_note("projects").unwrap();
+        assert!(!api.note_exists("projects/rust").unwrap());
+        api.rescan().unwrap();
+        assert!(!api.note_exists("projects").unwrap());
+
+        let trash = api.list_trash().unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].original_path, "projects");
+
+        api.restore_from_trash("projects").unwrap();
+        assert_eq!(api.get_note("projects/rust").unwrap().content, "Rust notes");
+        assert!(api.list_trash().unwrap().is_empty());
+        assert!(matches!(
+            api.restore_from_trash("projects"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_restore_refuses_to_overwrite() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("inbox").unwrap();
+        api.delete_note("inbox").unwrap();
+        api.create_note("inbox").unwrap();
+
+        assert!(matches!(
+            api.restore_from_trash("inbox"),
+            Err(Error::AlreadyExists(_))
+        ));
+        assert_eq!(api.list_trash().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_empty_trash_keeps_recent_deletions() {
+        let temp_dir = TempDir::new().unwrap();
+        let clock = Arc::new(ManualClock::default());
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.set_clock(clock.clone());
+        api.create_note("old").unwrap();
+        api.create_note("new").unwrap();
+
+        api.delete_note("old").unwrap();
+        clock.advance(Duration::from_secs(10 * 86400));
+        api.delete_note("new").unwrap();
+
+        let removed = api.empty_trash(Some(Duration::from_secs(86400))).unwrap();
+        assert_eq!(removed, 1);
+        let trash = api.list_trash().unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].original_path, "new");
+
+        assert_eq!(api.empty_trash(None).unwrap(), 1);
+        assert!(api.list_trash().unwrap().is_empty());
+    }
+}
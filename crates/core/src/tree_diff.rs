@@ -0,0 +1,277 @@
+//! Structural diffs of the note tree since a revision.
+//!
+//! Built on the change log (see `changes`): structural entries are collapsed into net
+//! changes, so frontends can apply O(change) updates to their tree instead of
+//! refetching roots and children after each event.
+
+use std::collections::BTreeMap;
+
+use rusqlite::{OptionalExtension, params};
+
+use crate::changes::Revision;
+use crate::notes::{NoteMetadata, NotesApi, Result, get_parent_path, metadata_from_row};
+
+/// A single structural change to the tree.
+///
+/// `note` carries the current metadata for nodes that still exist, so a frontend can
+/// insert them without another round trip.
+#[derive(Debug, Clone)]
+pub enum TreeChange {
+    Added {
+        path: String,
+        note: Option<NoteMetadata>,
+    },
+    Removed {
+        path: String,
+    },
+    /// The node moved to a different parent
+    Moved {
+        from: String,
+        to: String,
+        note: Option<NoteMetadata>,
+    },
+    /// The node was renamed within the same parent
+    Retitled {
+        from: String,
+        to: String,
+        note: Option<NoteMetadata>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeDiff {
+    /// Revision the diff starts from (exclusive)
+    pub from_revision: Revision,
+    /// Revision the diff brings the caller up to
+    pub revision: Revision,
+    /// True if `from_revision` is older than the retained log; the caller must refetch
+    pub reset: bool,
+    pub changes: Vec<TreeChange>,
+}
+
+/// Where a node at a given current path came from within the diff window.
+enum Origin {
+    Existing(String),
+    New,
+}
+
+impl NotesApi {
+    /// Computes the net tree changes after `since`.
+    ///
+    /// Intermediate steps are collapsed: a note created and deleted within the window
+    /// doesn't appear, and a note moved twice shows a single move.
+    pub fn tree_diff_since(&self, since: Revision) -> Result<TreeDiff> {
+        let revision = self.revision()?;
+        if self.changes_pruned_after(since, revision)? {
+            return Ok(TreeDiff {
+                from_revision: since,
+                revision,
+                reset: true,
+                changes: Vec::new(),
+            });
+        }
+
+        let mut current: BTreeMap<String, Origin> = BTreeMap::new();
+        let mut removed: Vec<String> = Vec::new();
+
+        let mut stmt = self.db.prepare(
+            "SELECT kind, path, old_path FROM changes WHERE revision > ?1 ORDER BY revision",
+        )?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (kind, path, old_path) = row?;
+            match (kind.as_str(), old_path) {
+                ("added", _) => {
+                    current.insert(path, Origin::New);
+                }
+                ("removed", _) => match current.remove(&path) {
+                    Some(Origin::New) => {}
+                    Some(Origin::Existing(original)) => removed.push(original),
+                    None => removed.push(path),
+                },
+                ("moved", Some(from)) => {
+                    let origin = current.remove(&from).unwrap_or(Origin::Existing(from));
+                    current.insert(path, origin);
+                }
+                _ => {}
+            }
+        }
+
+        let mut changes: Vec<TreeChange> = removed
+            .into_iter()
+            .map(|path| TreeChange::Removed { path })
+            .collect();
+
+        for (path, origin) in current {
+            let note = self.metadata_for_path(&path)?;
+            match origin {
+                Origin::New => changes.push(TreeChange::Added { path, note }),
+                Origin::Existing(from) if from == path => {}
+                Origin::Existing(from) => {
+                    if get_parent_path(&from) == get_parent_path(&path) {
+                        changes.push(TreeChange::Retitled {
+                            from,
+                            to: path,
+                            note,
+                        });
+                    } else {
+                        changes.push(TreeChange::Moved {
+                            from,
+                            to: path,
+                            note,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(TreeDiff {
+            from_revision: since,
+            revision,
+            reset: false,
+            changes,
+        })
+    }
+
+    fn metadata_for_path(&self, path: &str) -> Result<Option<NoteMetadata>> {
+        Ok(self
+            .db
+            .query_row(
+                "SELECT id, path, mtime, archived FROM notes WHERE path = ?1",
+                params![path],
+                metadata_from_row,
+            )
+            .optional()?)
+    }
+}
+
+/// Tracks a consumer's position in the change log.
+///
+/// Call `poll` after writes (or on watcher events) to get what changed since the
+/// previous poll; the subscription advances automatically.
+#[derive(Debug, Clone, Copy)]
+pub struct TreeSubscription {
+    revision: Revision,
+}
+
+impl TreeSubscription {
+    /// Starts a subscription at the vault's current revision.
+    pub fn new(api: &NotesApi) -> Result<Self> {
+        Ok(Self {
+            revision: api.revision()?,
+        })
+    }
+
+    pub fn revision(&self) -> Revision {
+        self.revision
+    }
+
+    /// Returns the changes since the last poll, or `None` if the tree is unchanged.
+    pub fn poll(&mut self, api: &NotesApi) -> Result<Option<TreeDiff>> {
+        let diff = api.tree_diff_since(self.revision)?;
+        if diff.revision == self.revision {
+            return Ok(None);
+        }
+        self.revision = diff.revision;
+        if !diff.reset && diff.changes.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(diff))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn describe(diff: &TreeDiff) -> Vec<String> {
+        diff.changes
+            .iter()
+            .map(|change| match change {
+                TreeChange::Added { path, .. } => format!("+{}", path),
+                TreeChange::Removed { path } => format!("-{}", path),
+                TreeChange::Moved { from, to, .. } => format!("{}->{}", from, to),
+                TreeChange::Retitled { from, to, .. } => format!("{}=>{}", from, to),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_reports_structural_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.create_note("b").unwrap();
+        api.create_note("c").unwrap();
+        let start = api.revision().unwrap();
+
+        api.create_note("a/new").unwrap();
+        api.rename_note("b", "bee").unwrap();
+        api.delete_note("c").unwrap();
+        api.save_note("a", "content changes aren't structural")
+            .unwrap();
+
+        let diff = api.tree_diff_since(start).unwrap();
+        assert!(!diff.reset);
+        assert_eq!(describe(&diff), vec!["-c", "+a/new", "b=>bee"]);
+    }
+
+    #[test]
+    fn test_diff_collapses_intermediate_steps() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.create_note("b").unwrap();
+        api.create_note("note").unwrap();
+        let start = api.revision().unwrap();
+
+        api.create_note("temp").unwrap();
+        api.delete_note("temp").unwrap();
+        api.rename_note("note", "renamed").unwrap();
+        api.rename_note("renamed", "a/renamed").unwrap();
+
+        let diff = api.tree_diff_since(start).unwrap();
+        assert_eq!(describe(&diff), vec!["note->a/renamed"]);
+        match &diff.changes[0] {
+            TreeChange::Moved { note, .. } => {
+                assert_eq!(note.as_ref().unwrap().path, "a/renamed")
+            }
+            other => panic!("unexpected change {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_subscription_poll() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        let mut subscription = TreeSubscription::new(&api).unwrap();
+        assert!(subscription.poll(&api).unwrap().is_none());
+
+        api.create_note("inbox").unwrap();
+        let diff = subscription.poll(&api).unwrap().unwrap();
+        assert_eq!(describe(&diff), vec!["+inbox"]);
+        assert!(subscription.poll(&api).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_diff_reset_when_log_pruned() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("a").unwrap();
+        api.create_note("b").unwrap();
+        api.db.execute("DELETE FROM changes", []).unwrap();
+        api.create_note("c").unwrap();
+
+        assert!(api.tree_diff_since(0).unwrap().reset);
+        let latest = api.revision().unwrap();
+        assert!(!api.tree_diff_since(latest - 1).unwrap().reset);
+    }
+}
@@ -0,0 +1,112 @@
+//! Which notes are expanded in the note tree.
+//!
+//! The navigation menus save the submenus open when they close as the expanded notes,
+//! and reopen them the next time, so the menus keep their shape across restarts.
+//!
+//! The flag lives on the note's index row, so it follows renames, moves, and archiving
+//! without extra bookkeeping and disappears when the note is deleted. It is UI state
+//! rather than note content, so it can be changed in read-only vaults too.
+
+use rusqlite::params;
+
+use crate::notes::{Error, NotesApi, Result};
+
+impl NotesApi {
+    /// Returns the paths of all expanded notes, sorted by path.
+    pub fn get_expanded_paths(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .db
+            .prepare("SELECT path FROM notes WHERE expanded = 1 ORDER BY path")?;
+        let paths = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<String>, _>>()?;
+        Ok(paths)
+    }
+
+    /// Marks a note as expanded or collapsed in the tree.
+    pub fn set_expanded(&mut self, path: &str, expanded: bool) -> Result<()> {
+        let updated = self.db.execute(
+            "UPDATE notes SET expanded = ?2 WHERE path = ?1",
+            params![path, expanded],
+        )?;
+        if updated == 0 {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Collapses every note.
+    pub fn collapse_all(&mut self) -> Result<()> {
+        self.db
+            .execute("UPDATE notes SET expanded = 0 WHERE expanded = 1", [])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_expanded_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("b").unwrap();
+        api.create_note("a").unwrap();
+        api.create_note("a/child").unwrap();
+
+        api.set_expanded("b", true).unwrap();
+        api.set_expanded("a", true).unwrap();
+        assert_eq!(api.get_expanded_paths().unwrap(), vec!["a", "b"]);
+
+        api.set_expanded("b", false).unwrap();
+        assert_eq!(api.get_expanded_paths().unwrap(), vec!["a"]);
+
+        api.collapse_all().unwrap();
+        assert!(api.get_expanded_paths().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_expansion_follows_rename_and_persists() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let mut api = NotesApi::new(temp_dir.path()).unwrap();
+            api.create_note("a").unwrap();
+            api.create_note("a/child").unwrap();
+            api.set_expanded("a", true).unwrap();
+            api.set_expanded("a/child", true).unwrap();
+            api.rename_note("a", "renamed").unwrap();
+        }
+
+        let api = NotesApi::new(temp_dir.path()).unwrap();
+        assert_eq!(
+            api.get_expanded_paths().unwrap(),
+            vec!["renamed", "renamed/child"]
+        );
+    }
+
+    #[test]
+    fn test_set_expanded_missing_note() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        assert!(matches!(
+            api.set_expanded("missing", true),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_only_vault_can_expand() {
+        let temp_dir = TempDir::new().unwrap();
+        NotesApi::new(temp_dir.path())
+            .unwrap()
+            .create_note("a")
+            .unwrap();
+
+        let mut api = NotesApi::new_read_only(temp_dir.path()).unwrap();
+        api.set_expanded("a", true).unwrap();
+        assert_eq!(api.get_expanded_paths().unwrap(), vec!["a"]);
+    }
+}
@@ -0,0 +1,136 @@
+//! Smart typography applied as the user types.
+//!
+//! Straight quotes become curly quotes, `--` an en dash, `---` an em dash, and
+//! `...` an ellipsis. Text inside code spans and fenced code blocks is left
+//! alone, so code samples keep their ASCII punctuation.
+
+/// Replaces the last `delete` characters before the cursor (and the typed
+/// character itself) with `insert`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypographyReplacement {
+    pub delete: usize,
+    pub insert: String,
+}
+
+impl TypographyReplacement {
+    fn new(delete: usize, insert: &str) -> Self {
+        Self {
+            delete,
+            insert: insert.to_string(),
+        }
+    }
+}
+
+/// What typing `typed` after `before` (the text up to the cursor) should
+/// insert instead, or `None` to insert it as typed.
+pub fn smart_typography(before: &str, typed: char) -> Option<TypographyReplacement> {
+    if !matches!(typed, '"' | '\'' | '-' | '.') || in_code(before) {
+        return None;
+    }
+
+    let replacement = match typed {
+        '"' if opens_quote(before) => TypographyReplacement::new(0, "\u{201C}"),
+        '"' => TypographyReplacement::new(0, "\u{201D}"),
+        '\'' if opens_quote(before) => TypographyReplacement::new(0, "\u{2018}"),
+        // Also the apostrophe in "don't"
+        '\'' => TypographyReplacement::new(0, "\u{2019}"),
+        '-' if before.ends_with('\u{2013}') => TypographyReplacement::new(1, "\u{2014}"),
+        // `---` at the start of a line is a thematic break or frontmatter fence
+        '-' if before.ends_with('-') && !line_of(before).starts_with('-') => {
+            TypographyReplacement::new(1, "\u{2013}")
+        }
+        '.' if before.ends_with("..") => TypographyReplacement::new(2, "\u{2026}"),
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+fn line_of(before: &str) -> &str {
+    before.rsplit('\n').next().unwrap_or(before)
+}
+
+/// A quote opens after whitespace, an opening bracket, or another opening
+/// quote, and at the start of the text.
+fn opens_quote(before: &str) -> bool {
+    before.chars().next_back().is_none_or(|c| {
+        c.is_whitespace()
+            || matches!(
+                c,
+                '(' | '[' | '{' | '\u{201C}' | '\u{2018}' | '\u{2014}' | '\u{2013}'
+            )
+    })
+}
+
+/// Whether the cursor sits in a fenced code block or an unclosed code span.
+fn in_code(before: &str) -> bool {
+    let fences = before
+        .lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            line.starts_with("```") || line.starts_with("~~~")
+        })
+        .count();
+    if fences % 2 == 1 {
+        return true;
+    }
+    line_of(before).matches('`').count() % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Types `input` one character at a time, applying replacements.
+    fn type_text(input: &str) -> String {
+        let mut text = String::new();
+        for c in input.chars() {
+            match smart_typography(&text, c) {
+                Some(replacement) => {
+                    for _ in 0..replacement.delete {
+                        text.pop();
+                    }
+                    text.push_str(&replacement.insert);
+                }
+                None => text.push(c),
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn test_quotes() {
+        assert_eq!(type_text(r#"She said "hi""#), "She said \u{201C}hi\u{201D}");
+        assert_eq!(type_text("'twas (don't)"), "\u{2018}twas (don\u{2019}t)");
+    }
+
+    #[test]
+    fn test_dashes_and_ellipsis() {
+        assert_eq!(type_text("a -- b"), "a \u{2013} b");
+        assert_eq!(type_text("a---b"), "a\u{2014}b");
+        assert_eq!(type_text("wait..."), "wait\u{2026}");
+        // Thematic breaks and list markers stay ASCII
+        assert_eq!(type_text("---"), "---");
+        assert_eq!(type_text("- item"), "- item");
+    }
+
+    #[test]
+    fn test_right_to_left_text() {
+        assert_eq!(
+            type_text("הוא אמר \"שלום\""),
+            "הוא אמר \u{201C}שלום\u{201D}"
+        );
+        assert_eq!(
+            type_text("قال 'مرحبا' -- ثم..."),
+            "قال \u{2018}مرحبا\u{2019} \u{2013} ثم\u{2026}"
+        );
+    }
+
+    #[test]
+    fn test_code_is_left_alone() {
+        assert_eq!(type_text("`a--b \"c\"`"), "`a--b \"c\"`");
+        assert_eq!(
+            type_text("```\nx = \"y\"...\n```\n\"z\""),
+            "```\nx = \"y\"...\n```\n\u{201C}z\u{201D}"
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Plain-text mentions of a note in other notes that aren't links yet.
+//!
+//! A note is mentioned by its title or any of its `aliases`, matched as whole
+//! words regardless of case. Text that is already a link, a URL, or code is
+//! skipped. `link_mention` turns a single mention into a `[[wiki-link]]`.
+
+use std::ops::Range;
+
+use crate::changes::Revision;
+use crate::frontmatter::Frontmatter;
+use crate::note_links::note_title;
+use crate::notes::{Error, NotesApi, Result};
+
+/// Names shorter than this (in characters) match too much to be useful.
+const MIN_NAME_LENGTH: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnlinkedMention {
+    /// Note containing the mention
+    pub source: String,
+    /// Byte range of the mention in the source note's content
+    pub range: Range<usize>,
+    /// The mention as written
+    pub text: String,
+    /// The line the mention is on, for showing it in context
+    pub context: String,
+}
+
+/// The title and aliases a note can be mentioned by, longest first.
+fn mention_names(path: &str, frontmatter: &Frontmatter) -> Vec<String> {
+    let mut names = vec![note_title(path, frontmatter)];
+    if let Some(aliases) = frontmatter.get("aliases") {
+        names.extend(aliases.as_list());
+    }
+    let mut names: Vec<String> = names
+        .into_iter()
+        .map(|name| name.trim().to_string())
+        .filter(|name| name.chars().count() >= MIN_NAME_LENGTH)
+        .collect();
+    names.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    names.dedup_by(|a, b| a.to_lowercase() == b.to_lowercase());
+    names
+}
+
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+/// End of `name` if it occurs at `start` in `text`, ignoring case.
+fn match_at(text: &str, start: usize, name: &str) -> Option<usize> {
+    let mut chars = text[start..].char_indices();
+    for expected in name.chars() {
+        let (_, actual) = chars.next()?;
+        if !actual.to_lowercase().eq(expected.to_lowercase()) {
+            return None;
+        }
+    }
+    Some(chars.next().map_or(text.len(), |(i, _)| start + i))
+}
+
+/// Byte ranges that must not be turned into links: frontmatter, code, links, and URLs.
+pub(crate) fn excluded_ranges(content: &str) -> Vec<Range<usize>> {
+    let (_, body) = Frontmatter::parse(content);
+    let body_start = content.len() - body.len();
+    let mut excluded = Vec::new();
+    // The frontmatter
+    excluded.push(0..body_start);
+
+    let mut offset = body_start;
+    let mut fence_start: Option<usize> = None;
+    for line in body.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            match fence_start.take() {
+                Some(start) => excluded.push(start..offset),
+                None => fence_start = Some(line_start),
+            }
+            continue;
+        }
+        if fence_start.is_none() {
+            excluded.extend(inline_exclusions(line, line_start));
+        }
+    }
+    if let Some(start) = fence_start {
+        excluded.push(start..content.len());
+    }
+    excluded
+}
+
+fn inline_exclusions(line: &str, line_start: usize) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < line.len() {
+        let rest = &line[i..];
+        let end = if let Some(code) = rest.strip_prefix('`') {
+            code.find('`').map(|end| end + 2)
+        } else if rest.starts_with("[[") {
+            rest.find("]]").map(|end| end + 2)
+        } else if rest.starts_with('[') {
+            rest.find("](")
+                .and_then(|mid| rest[mid..].find(')').map(|close| mid + close + 1))
+        } else if rest.starts_with("http://") || rest.starts_with("https://") {
+            Some(rest.find(char::is_whitespace).unwrap_or(rest.len()))
+        } else {
+            None
+        };
+        match end {
+            Some(end) => {
+                ranges.push(line_start + i..line_start + i + end);
+                i += end;
+            }
+            None => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    ranges
+}
+
+/// Whole-word, case-insensitive occurrences of `names` outside excluded text.
+fn find_mentions(content: &str, names: &[String]) -> Vec<Range<usize>> {
+    let excluded = excluded_ranges(content);
+    let is_excluded = |range: &Range<usize>| {
+        excluded
+            .iter()
+            .any(|ex| range.start < ex.end && ex.start < range.end)
+    };
+
+    let mut found = Vec::new();
+    let mut prev: Option<char> = None;
+    let mut skip_until = 0;
+    for (start, c) in content.char_indices() {
+        let at_word_start = prev.is_none_or(|p| !p.is_alphanumeric());
+        prev = Some(c);
+        if start < skip_until || !at_word_start {
+            continue;
+        }
+        // Longest name first, so "Project Alpha" wins over "Project"
+        for name in names {
+            let Some(end) = match_at(content, start, name) else {
+                continue;
+            };
+            let at_word_end = content[end..]
+                .chars()
+                .next()
+                .is_none_or(|next| !next.is_alphanumeric());
+            if at_word_end && !is_excluded(&(start..end)) {
+                found.push(start..end);
+                skip_until = end;
+                break;
+            }
+        }
+    }
+    found
+}
+
+fn line_around(content: &str, range: &Range<usize>) -> String {
+    let start = content[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let end = content[range.end..]
+        .find('\n')
+        .map_or(content.len(), |i| range.end + i);
+    content[start..end].trim().to_string()
+}
+
+impl NotesApi {
+    /// Finds plain-text mentions of the note at `path` (by title or alias) in other notes.
+    pub fn get_unlinked_mentions(&self, path: &str) -> Result<Vec<UnlinkedMention>> {
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        let content = self.read_content(path)?;
+        let (frontmatter, _) = Frontmatter::parse(&content);
+        let names = mention_names(path, &frontmatter);
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut mentions = Vec::new();
+        let mut sources = self.get_all_notes()?;
+        sources.sort_by(|a, b| a.path.cmp(&b.path));
+        for source in sources {
+            if source.path == path {
+                continue;
+            }
+            let Ok(content) = self.read_content(&source.path) else {
+                continue;
+            };
+            for range in find_mentions(&content, &names) {
+                mentions.push(UnlinkedMention {
+                    source: source.path.clone(),
+                    text: content[range.clone()].to_string(),
+                    context: line_around(&content, &range),
+                    range,
+                });
+            }
+        }
+        Ok(mentions)
+    }
+
+    /// Replaces the mention at `range` in `source` with a wiki-link to `target`.
+    ///
+    /// Fails with `InvalidInput` if the text at `range` is no longer a name of
+    /// `target`, e.g. because `source` was edited after the mentions were listed.
+    pub fn link_mention(
+        &mut self,
+        source: &str,
+        range: Range<usize>,
+        target: &str,
+    ) -> Result<Revision> {
+        self.ensure_writable()?;
+        if !self.note_exists(target)? {
+            return Err(Error::NotFound(target.to_string()));
+        }
+        let target_content = self.read_content(target)?;
+        let (frontmatter, _) = Frontmatter::parse(&target_content);
+        let names = mention_names(target, &frontmatter);
+
+        let content = self.read_content(source)?;
+        let stale = || Error::InvalidInput("The mention has changed since it was found".into());
+        let text = content.get(range.clone()).ok_or_else(stale)?;
+        if !names.iter().any(|name| eq_ignore_case(name, text)) {
+            return Err(stale());
+        }
+
+        let link = if text == target {
+            format!("[[{}]]", target)
+        } else {
+            format!("[[{}|{}]]", target, text)
+        };
+        let mut updated = content.clone();
+        updated.replace_range(range, &link);
+        self.write_batch(&[(source, &content, &updated)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("projects").unwrap();
+        api.create_note("projects/apollo").unwrap();
+        api.save_note(
+            "projects/apollo",
+            "---\naliases:\n  - Moonshot\n---\nThe moon project.",
+        )
+        .unwrap();
+        api.create_note("log").unwrap();
+        (temp_dir, api)
+    }
+
+    #[test]
+    fn test_finds_title_and_alias_mentions() {
+        let (_temp, mut api) = setup();
+        api.save_note(
+            "log",
+            "Talked about APOLLO today.\nThe moonshot slipped; apollonian calm.",
+        )
+        .unwrap();
+
+        let mentions = api.get_unlinked_mentions("projects/apollo").unwrap();
+        let texts: Vec<&str> = mentions.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, ["APOLLO", "moonshot"]);
+        assert_eq!(mentions[0].source, "log");
+        assert_eq!(mentions[0].context, "Talked about APOLLO today.");
+    }
+
+    #[test]
+    fn test_skips_links_code_and_frontmatter() {
+        let (_temp, mut api) = setup();
+        api.save_note(
+            "log",
+            "---\nsee: apollo\n---\n[[projects/apollo]] and [Apollo](https://x.io/apollo)\n`apollo`\n```\napollo\n```\nhttps://apollo.dev",
+        )
+        .unwrap();
+
+        assert!(
+            api.get_unlinked_mentions("projects/apollo")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_link_mention() {
+        let (_temp, mut api) = setup();
+        api.save_note("log", "Ship Apollo soon").unwrap();
+
+        let mention = api
+            .get_unlinked_mentions("projects/apollo")
+            .unwrap()
+            .remove(0);
+        api.link_mention("log", mention.range.clone(), "projects/apollo")
+            .unwrap();
+        assert_eq!(
+            api.get_note("log").unwrap().content,
+            "Ship [[projects/apollo|Apollo]] soon"
+        );
+        assert!(
+            api.get_unlinked_mentions("projects/apollo")
+                .unwrap()
+                .is_empty()
+        );
+
+        // The same range now points into the link, not at the mention
+        assert!(matches!(
+            api.link_mention("log", mention.range, "projects/apollo"),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
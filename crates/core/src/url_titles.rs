@@ -0,0 +1,132 @@
+//! Turning pasted URLs into titled Markdown links.
+//!
+//! Parsing and formatting are always available; the network fetch is behind the
+//! `url-titles` feature and is additionally opt-in per vault (`paste.fetch_url_titles`),
+//! so offline and privacy-conscious setups never make requests.
+
+use crate::html::decode_entities;
+
+/// Returns true if `text` is a single bare http(s) URL.
+pub fn is_bare_url(text: &str) -> bool {
+    let text = text.trim();
+    (text.starts_with("http://") || text.starts_with("https://"))
+        && text.len() > "https://".len()
+        && !text.chars().any(char::is_whitespace)
+}
+
+/// Extracts the page title from an HTML document.
+///
+/// Prefers `<title>`, falling back to the `og:title` meta tag.
+pub fn extract_html_title(html: &str) -> Option<String> {
+    let lower = html.to_lowercase();
+
+    let from_title = lower.find("<title").and_then(|start| {
+        let open_end = start + lower[start..].find('>')? + 1;
+        let close = open_end + lower[open_end..].find("</title")?;
+        Some(&html[open_end..close])
+    });
+
+    let from_og = || {
+        let meta = lower.find("property=\"og:title\"")?;
+        let tag_start = lower[..meta].rfind('<')?;
+        let tag_end = meta + lower[meta..].find('>')?;
+        let tag = &html[tag_start..tag_end];
+        let content = tag.to_lowercase().find("content=\"")? + "content=\"".len();
+        let len = tag[content..].find('"')?;
+        Some(&tag[content..content + len])
+    };
+
+    let raw = from_title
+        .filter(|t| !t.trim().is_empty())
+        .or_else(from_og)?;
+    let title = decode_entities(raw)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if title.is_empty() { None } else { Some(title) }
+}
+
+/// Formats a Markdown link for a URL, falling back to the bare URL without a title.
+pub fn format_link(url: &str, title: Option<&str>) -> String {
+    match title {
+        Some(title) if !title.is_empty() => {
+            let title = title.replace('[', "\\[").replace(']', "\\]");
+            format!("[{}]({})", title, url)
+        }
+        _ => url.to_string(),
+    }
+}
+
+/// Fetches a page and returns its title, giving up after `timeout`.
+///
+/// Only the first 512 KiB of HTML responses are read. Non-HTML responses yield `None`.
+#[cfg(feature = "url-titles")]
+pub fn fetch_url_title(url: &str, timeout: std::time::Duration) -> std::io::Result<Option<String>> {
+    use std::io::Read;
+
+    const MAX_BYTES: u64 = 512 * 1024;
+
+    if !is_bare_url(url) {
+        return Ok(None);
+    }
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .user_agent("Zinnia")
+        .build()
+        .map_err(std::io::Error::other)?;
+
+    let response = client
+        .get(url.trim())
+        .send()
+        .and_then(|r| r.error_for_status())
+        .map_err(std::io::Error::other)?;
+
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("html"));
+    if !is_html {
+        return Ok(None);
+    }
+
+    let mut body = Vec::new();
+    response.take(MAX_BYTES).read_to_end(&mut body)?;
+    Ok(extract_html_title(&String::from_utf8_lossy(&body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_bare_url() {
+        assert!(is_bare_url("https://example.com/page"));
+        assert!(is_bare_url("  http://example.com  "));
+        assert!(!is_bare_url("see https://example.com"));
+        assert!(!is_bare_url("ftp://example.com"));
+        assert!(!is_bare_url("https://"));
+    }
+
+    #[test]
+    fn test_extract_html_title() {
+        let html = "<html><head><TITLE>\n  Rust &amp; You\n</TITLE></head></html>";
+        assert_eq!(extract_html_title(html).as_deref(), Some("Rust & You"));
+
+        let html = r#"<head><title></title><meta property="og:title" content="Fallback"></head>"#;
+        assert_eq!(extract_html_title(html).as_deref(), Some("Fallback"));
+
+        assert_eq!(extract_html_title("<p>no title</p>"), None);
+    }
+
+    #[test]
+    fn test_format_link() {
+        assert_eq!(
+            format_link("https://a.b", Some("A [b]")),
+            "[A \\[b\\]](https://a.b)"
+        );
+        assert_eq!(format_link("https://a.b", None), "https://a.b");
+    }
+}
@@ -0,0 +1,589 @@
+//! Single-file vault export/import (`.folio` archives).
+//!
+//! An archive is a zip file containing every vault file under `files/` plus a
+//! `manifest.json` with the index-only data (frecency, archive timestamps, reminders,
+//! config) that can't be rebuilt from the files alone. The vault's key file goes along
+//! too: without its salt the archive's encrypted notes could never be opened again.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+
+use crate::config::{CONFIG_FILE_NAME, VaultConfig};
+use crate::encryption::{ENCRYPTED_HEADER, KEY_FILE_NAME, parse_key_file, read_key_file};
+use crate::layout::{NoteFiles, VaultLayout};
+use crate::notes::{Error, NotesApi, Result};
+
+/// File extension used for vault archives.
+pub const ARCHIVE_EXTENSION: &str = "folio";
+
+/// Bumped whenever the manifest layout changes incompatibly.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const FILES_PREFIX: &str = "files/";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    format_version: u32,
+    app_version: String,
+    exported_at: i64,
+    files: Vec<ManifestFile>,
+    notes: Vec<ManifestNote>,
+    reminders: Vec<ManifestReminder>,
+    config: Option<VaultConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManifestFile {
+    path: String,
+    mtime: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManifestNote {
+    path: String,
+    archived_at: Option<i64>,
+    access_count: i64,
+    direct_access_count: i64,
+    last_accessed_at: Option<i64>,
+    frecency_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManifestReminder {
+    path: String,
+    remind_at: i64,
+    message: String,
+    fired: bool,
+}
+
+/// Counts of what was written to or read from an archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveSummary {
+    pub notes: usize,
+    pub files: usize,
+}
+
+fn archive_error(e: impl std::fmt::Display) -> Error {
+    Error::Io(io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+fn unix_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Lists vault files relative to `root`, skipping hidden files and directories
+/// (the database, config, and tool folders like `.git`).
+pub(crate) fn collect_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Converts a relative path to the `/`-separated form used inside archives.
+pub(crate) fn archive_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl NotesApi {
+    /// Exports the whole vault into a single archive file at `dest`.
+    pub fn export_archive<P: AsRef<Path>>(&self, dest: P) -> Result<ArchiveSummary> {
+        let root = self.fs.root_path().to_path_buf();
+        let mut files = Vec::new();
+        collect_files(&root, &root, &mut files)?;
+        files.sort();
+
+        let notes = self
+            .db
+            .prepare(
+                "SELECT path, archived_at, access_count, direct_access_count, last_accessed_at, frecency_score
+                 FROM notes ORDER BY path",
+            )?
+            .query_map([], |row| {
+                Ok(ManifestNote {
+                    path: row.get(0)?,
+                    archived_at: row.get(1)?,
+                    access_count: row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+                    direct_access_count: row.get::<_, Option<i64>>(3)?.unwrap_or(0),
+                    last_accessed_at: row.get(4)?,
+                    frecency_score: row.get::<_, Option<f64>>(5)?.unwrap_or(0.0),
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let reminders = self
+            .db
+            .prepare(
+                "SELECT notes.path, reminders.remind_at, reminders.message, reminders.fired
+                 FROM reminders JOIN notes ON reminders.note_id = notes.id
+                 ORDER BY reminders.id",
+            )?
+            .query_map([], |row| {
+                Ok(ManifestReminder {
+                    path: row.get(0)?,
+                    remind_at: row.get(1)?,
+                    message: row.get(2)?,
+                    fired: row.get::<_, i64>(3)? != 0,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut zip = zip::ZipWriter::new(File::create(dest)?);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+        let mut manifest_files = Vec::new();
+        for relative in &files {
+            let name = archive_path(relative);
+            let full = root.join(relative);
+            let mtime = fs::metadata(&full)?.modified().map(unix_secs).unwrap_or(0);
+
+            zip.start_file(format!("{}{}", FILES_PREFIX, name), options)
+                .map_err(archive_error)?;
+            zip.write_all(&fs::read(&full)?)?;
+
+            manifest_files.push(ManifestFile { path: name, mtime });
+        }
+
+        match fs::read(root.join(KEY_FILE_NAME)) {
+            Ok(key_file) => {
+                zip.start_file(KEY_FILE_NAME, options)
+                    .map_err(archive_error)?;
+                zip.write_all(&key_file)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let manifest = Manifest {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: self.clock.unix_secs(),
+            files: manifest_files,
+            notes,
+            reminders,
+            config: Some(self.config.clone()),
+        };
+        zip.start_file(MANIFEST_NAME, options)
+            .map_err(archive_error)?;
+        zip.write_all(
+            serde_json::to_string_pretty(&manifest)
+                .map_err(archive_error)?
+                .as_bytes(),
+        )?;
+        zip.finish().map_err(archive_error)?;
+
+        Ok(ArchiveSummary {
+            notes: manifest.notes.len(),
+            files: manifest.files.len(),
+        })
+    }
+
+    /// Imports an archive created by `export_archive` into this vault.
+    ///
+    /// Fails with `AlreadyExists` (before writing anything) if any archived note or
+    /// file already exists here, indexed or not. File mtimes, frecency data, and reminders are restored;
+    /// the archived config is applied only if this vault has no config file yet. The
+    /// archived key file is kept if this vault has none; if the archive has encrypted
+    /// notes and this vault's key is a different one, the import fails with
+    /// `InvalidInput`. A checkpoint is created before anything is written.
+    pub fn import_archive<P: AsRef<Path>>(&mut self, src: P) -> Result<ArchiveSummary> {
+        self.ensure_writable()?;
+
+        let mut zip = zip::ZipArchive::new(File::open(src)?).map_err(archive_error)?;
+
+        let manifest: Manifest = {
+            let mut raw = String::new();
+            zip.by_name(MANIFEST_NAME)
+                .map_err(archive_error)?
+                .read_to_string(&mut raw)?;
+            serde_json::from_str(&raw).map_err(archive_error)?
+        };
+        if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+            return Err(archive_error(format!(
+                "Unsupported archive format version {}",
+                manifest.format_version
+            )));
+        }
+
+        // Note files are renamed to this vault's note file name on the way in
+        let archived = manifest
+            .config
+            .as_ref()
+            .map_or_else(NoteFiles::default, |config| NoteFiles {
+                note_file: config.note_file.clone(),
+                layout: config.layout,
+            });
+        let archived_note_file = archived.note_file.clone();
+        let note_file = self.note_file().to_string();
+        // Folder notes read fine in a flat vault, but not the other way around
+        if archived.layout == VaultLayout::Flat && self.config().layout != VaultLayout::Flat {
+            return Err(Error::InvalidInput(
+                "The archive uses the flat layout; switch this vault to it first".to_string(),
+            ));
+        }
+
+        for file in &manifest.files {
+            if let Some(note_path) = archived.note_of_file(&file.path)
+                && self.note_exists(&note_path)?
+            {
+                return Err(Error::AlreadyExists(note_path));
+            }
+        }
+
+        let root = self.fs.root_path().to_path_buf();
+        let mut dests = Vec::with_capacity(manifest.files.len());
+        let mut has_encrypted = false;
+        for file in &manifest.files {
+            let mut entry = zip
+                .by_name(&format!("{}{}", FILES_PREFIX, file.path))
+                .map_err(archive_error)?;
+            // Reject entries that would escape the vault (e.g. `../`)
+            let relative = entry
+                .enclosed_name()
+                .and_then(|p| p.strip_prefix(FILES_PREFIX).ok().map(Path::to_path_buf))
+                .ok_or_else(|| archive_error(format!("Invalid path in archive: {}", file.path)))?;
+
+            let mut header = Vec::new();
+            (&mut entry)
+                .take(ENCRYPTED_HEADER.len() as u64)
+                .read_to_end(&mut header)?;
+            has_encrypted |= header == ENCRYPTED_HEADER.as_bytes();
+
+            let relative = if relative
+                .file_name()
+                .is_some_and(|name| name == archived_note_file.as_str())
+            {
+                relative.with_file_name(&note_file)
+            } else {
+                relative
+            };
+            // Attachments and files the watcher hasn't indexed yet are kept too
+            if root.join(&relative).exists() {
+                return Err(Error::AlreadyExists(archive_path(&relative)));
+            }
+            dests.push(root.join(relative));
+        }
+
+        let archived_key = match zip.by_name(KEY_FILE_NAME) {
+            Ok(mut entry) => {
+                let mut raw = String::new();
+                entry.read_to_string(&mut raw)?;
+                let key_file = parse_key_file(&raw)?;
+                Some((raw, key_file))
+            }
+            Err(zip::result::ZipError::FileNotFound) => None,
+            Err(e) => return Err(archive_error(e)),
+        };
+        if has_encrypted {
+            let Some((_, archived_key)) = &archived_key else {
+                return Err(Error::InvalidInput(
+                    "The archive has encrypted notes but no key file".to_string(),
+                ));
+            };
+            if read_key_file(&root)?.is_some_and(|key_file| key_file != *archived_key) {
+                return Err(Error::InvalidInput(
+                    "The archive's notes are encrypted with another passphrase than this vault's"
+                        .to_string(),
+                ));
+            }
+        }
+
+        self.create_checkpoint("Before import")?;
+
+        for (file, dest) in manifest.files.iter().zip(&dests) {
+            let mut entry = zip
+                .by_name(&format!("{}{}", FILES_PREFIX, file.path))
+                .map_err(archive_error)?;
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            let out = File::options().write(true).create_new(true).open(dest)?;
+            (&out).write_all(&contents)?;
+            out.set_modified(UNIX_EPOCH + Duration::from_secs(file.mtime.max(0) as u64))?;
+        }
+
+        if let Some((raw, _)) = &archived_key
+            && !root.join(KEY_FILE_NAME).exists()
+        {
+            fs::write(root.join(KEY_FILE_NAME), raw)?;
+        }
+
+        self.rescan()?;
+
+        for note in &manifest.notes {
+            self.db.execute(
+                "UPDATE notes SET archived_at = ?2, access_count = ?3, direct_access_count = ?4,
+                     last_accessed_at = ?5, frecency_score = ?6
+                 WHERE path = ?1",
+                params![
+                    note.path,
+                    note.archived_at,
+                    note.access_count,
+                    note.direct_access_count,
+                    note.last_accessed_at,
+                    note.frecency_score
+                ],
+            )?;
+        }
+
+        for reminder in &manifest.reminders {
+            if !self.note_exists(&reminder.path)? {
+                continue;
+            }
+            let created = self.set_reminder(
+                &reminder.path,
+                UNIX_EPOCH + Duration::from_secs(reminder.remind_at.max(0) as u64),
+                &reminder.message,
+            )?;
+            if reminder.fired {
+                self.db.execute(
+                    "UPDATE reminders SET fired = 1 WHERE id = ?1",
+                    params![created.id],
+                )?;
+            }
+        }
+
+        if let Some(mut config) = manifest.config
+            && !root.join(CONFIG_FILE_NAME).exists()
+        {
+            config.note_file = note_file;
+            config.layout = self.config().layout;
+            self.set_config(config)?;
+        }
+
+        Ok(ArchiveSummary {
+            notes: manifest.notes.len(),
+            files: manifest.files.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::DEFAULT_NOTE_FILE;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let source_dir = TempDir::new().unwrap();
+        let mut source = NotesApi::new(source_dir.path()).unwrap();
+        source.create_note("projects").unwrap();
+        source.create_note("projects/app").unwrap();
+        source
+            .save_note("projects/app", "# App\nSee [[projects]]")
+            .unwrap();
+        source.get_note("projects/app").unwrap();
+        fs::write(source_dir.path().join("projects/app/diagram.png"), b"png").unwrap();
+        source
+            .set_reminder(
+                "projects/app",
+                UNIX_EPOCH + Duration::from_secs(1_000),
+                "ship",
+            )
+            .unwrap();
+
+        let direct_access_count = |api: &NotesApi| -> i64 {
+            api.db
+                .query_row(
+                    "SELECT direct_access_count FROM notes WHERE path = 'projects/app'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        let source_count = direct_access_count(&source);
+
+        let archive = source_dir.path().join("vault.folio");
+        let summary = source.export_archive(&archive).unwrap();
+        assert_eq!(summary.notes, 2);
+        assert_eq!(summary.files, 3);
+
+        let target_dir = TempDir::new().unwrap();
+        let mut target = NotesApi::new(target_dir.path()).unwrap();
+        assert_eq!(target.import_archive(&archive).unwrap(), summary);
+
+        assert_eq!(
+            target.get_note("projects/app").unwrap().content,
+            "# App\nSee [[projects]]"
+        );
+        assert_eq!(
+            fs::read(target_dir.path().join("projects/app/diagram.png")).unwrap(),
+            b"png"
+        );
+        assert_eq!(target.list_upcoming_reminders().unwrap().len(), 1);
+
+        // The source vault's accesses carry over, plus the get_note above
+        assert_eq!(direct_access_count(&target), source_count + 1);
+    }
+
+    #[test]
+    fn test_import_renames_note_files_to_this_vaults_layout() {
+        let source_dir = TempDir::new().unwrap();
+        let mut source = NotesApi::new(source_dir.path()).unwrap();
+        source.set_note_file("README.md").unwrap();
+        source.create_note("docs").unwrap();
+        source.save_note("docs", "# Docs").unwrap();
+        let archive = source_dir.path().join("vault.folio");
+        source.export_archive(&archive).unwrap();
+
+        let target_dir = TempDir::new().unwrap();
+        let mut target = NotesApi::new(target_dir.path()).unwrap();
+        target.import_archive(&archive).unwrap();
+
+        assert!(target_dir.path().join("docs/_index.md").exists());
+        assert_eq!(target.get_note("docs").unwrap().content, "# Docs");
+        assert_eq!(target.note_file(), DEFAULT_NOTE_FILE);
+    }
+
+    #[test]
+    fn test_flat_archive_needs_a_flat_vault() {
+        let source_dir = TempDir::new().unwrap();
+        let mut source = NotesApi::new(source_dir.path()).unwrap();
+        source.set_layout(VaultLayout::Flat).unwrap();
+        source.create_note("inbox").unwrap();
+        source.save_note("inbox", "# Inbox").unwrap();
+        let archive = source_dir.path().join("vault.folio");
+        source.export_archive(&archive).unwrap();
+
+        let target_dir = TempDir::new().unwrap();
+        let mut target = NotesApi::new(target_dir.path()).unwrap();
+        assert!(matches!(
+            target.import_archive(&archive),
+            Err(Error::InvalidInput(_))
+        ));
+
+        target.set_layout(VaultLayout::Flat).unwrap();
+        target.import_archive(&archive).unwrap();
+        assert!(target_dir.path().join("inbox.md").is_file());
+        assert_eq!(target.get_note("inbox").unwrap().content, "# Inbox");
+    }
+
+    #[test]
+    fn test_import_refuses_existing_notes() {
+        let source_dir = TempDir::new().unwrap();
+        let mut source = NotesApi::new(source_dir.path()).unwrap();
+        source.create_note("inbox").unwrap();
+        let archive = source_dir.path().join("vault.folio");
+        source.export_archive(&archive).unwrap();
+
+        let target_dir = TempDir::new().unwrap();
+        let mut target = NotesApi::new(target_dir.path()).unwrap();
+        target.create_note("inbox").unwrap();
+        target.save_note("inbox", "keep me").unwrap();
+
+        assert!(matches!(
+            target.import_archive(&archive),
+            Err(Error::AlreadyExists(_))
+        ));
+        assert_eq!(target.get_note("inbox").unwrap().content, "keep me");
+    }
+
+    #[test]
+    fn test_import_refuses_existing_files() {
+        let source_dir = TempDir::new().unwrap();
+        let mut source = NotesApi::new(source_dir.path()).unwrap();
+        source.create_note("inbox").unwrap();
+        source.create_note("docs").unwrap();
+        fs::write(source_dir.path().join("docs/plan.pdf"), b"theirs").unwrap();
+        let archive = source_dir.path().join("vault.folio");
+        source.export_archive(&archive).unwrap();
+
+        // An attachment with the same name, and a note the index hasn't seen yet
+        let target_dir = TempDir::new().unwrap();
+        let mut target = NotesApi::new(target_dir.path()).unwrap();
+        fs::create_dir_all(target_dir.path().join("docs")).unwrap();
+        fs::write(target_dir.path().join("docs/plan.pdf"), b"mine").unwrap();
+        match target.import_archive(&archive) {
+            Err(Error::AlreadyExists(path)) => assert_eq!(path, "docs/plan.pdf"),
+            other => panic!("expected AlreadyExists, got {:?}", other),
+        }
+        assert_eq!(
+            fs::read(target_dir.path().join("docs/plan.pdf")).unwrap(),
+            b"mine"
+        );
+        assert!(!target_dir.path().join("inbox").exists());
+    }
+
+    #[test]
+    fn test_encrypted_notes_round_trip() {
+        let source_dir = TempDir::new().unwrap();
+        let mut source = NotesApi::new(source_dir.path()).unwrap();
+        source.create_note("diary").unwrap();
+        source.save_note("diary", "# Secret").unwrap();
+        source.unlock("correct horse").unwrap();
+        source.encrypt_note("diary").unwrap();
+        let archive = source_dir.path().join("vault.folio");
+        source.export_archive(&archive).unwrap();
+
+        let target_dir = TempDir::new().unwrap();
+        let mut target = NotesApi::new(target_dir.path()).unwrap();
+        target.import_archive(&archive).unwrap();
+
+        assert!(matches!(target.get_note("diary"), Err(Error::Encrypted(_))));
+        assert!(matches!(
+            target.unlock("wrong"),
+            Err(Error::PermissionDenied(_))
+        ));
+        target.unlock("correct horse").unwrap();
+        assert_eq!(target.get_note("diary").unwrap().content, "# Secret");
+    }
+
+    #[test]
+    fn test_import_refuses_encrypted_notes_under_another_key() {
+        let source_dir = TempDir::new().unwrap();
+        let mut source = NotesApi::new(source_dir.path()).unwrap();
+        source.create_note("diary").unwrap();
+        source.unlock("correct horse").unwrap();
+        source.encrypt_note("diary").unwrap();
+        let archive = source_dir.path().join("vault.folio");
+        source.export_archive(&archive).unwrap();
+
+        let target_dir = TempDir::new().unwrap();
+        let mut target = NotesApi::new(target_dir.path()).unwrap();
+        target.unlock("battery staple").unwrap();
+        assert!(matches!(
+            target.import_archive(&archive),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(!target_dir.path().join("diary").exists());
+    }
+
+    #[test]
+    fn test_export_skips_hidden_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("inbox").unwrap();
+        fs::create_dir_all(temp_dir.path().join(".git")).unwrap();
+        fs::write(temp_dir.path().join(".git/HEAD"), "ref").unwrap();
+
+        let archive = temp_dir.path().join("out.folio");
+        let summary = api.export_archive(&archive).unwrap();
+        assert_eq!(summary.files, 1);
+    }
+}
@@ -0,0 +1,519 @@
+//! Importing a folder of loose Markdown files, such as an Obsidian vault.
+//!
+//! `Alpha.md` becomes the note `Alpha`, and a folder holding Markdown files becomes
+//! a note of its own, with the content of a sibling `Folder.md` if there is one.
+//! Other files are copied along as attachments. Obsidian-style `[[Alpha]]` links,
+//! which name a note by file name alone, and relative `[text](Alpha.md)` links are
+//! rewritten to the imported notes' paths.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::backlinks::{LinkForm, link_refs};
+use crate::note_links::percent_decode;
+use crate::notes::{Error, NotesApi, Result};
+use crate::unlinked_mentions::excluded_ranges;
+
+#[derive(Debug, Clone, Default)]
+pub struct VaultImportOptions {
+    /// Note the imported notes go below; empty for the vault root
+    pub target: String,
+    /// Report what an import would do without writing anything
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VaultImport {
+    /// Notes created (or that would be), parents first
+    pub created: Vec<String>,
+    /// Notes and files skipped because they already exist
+    pub conflicts: Vec<String>,
+    /// Other files copied into the vault, relative to its root
+    pub attachments: Vec<String>,
+    /// Links pointed at the imported notes
+    pub links_rewritten: usize,
+}
+
+/// A note found in the source folder.
+#[derive(Default)]
+struct SourceNote {
+    /// The Markdown file, if any; a folder without one imports as an empty note
+    file: Option<PathBuf>,
+    mtime: Option<SystemTime>,
+}
+
+/// Collects the notes and attachments below `dir`, keyed by their path relative to
+/// the source folder (`prefix` is `dir`'s). Returns true if `dir` holds any
+/// Markdown file. Hidden entries, like `.obsidian`, are skipped.
+fn walk(
+    dir: &Path,
+    prefix: &str,
+    notes: &mut BTreeMap<String, SourceNote>,
+    attachments: &mut Vec<(String, PathBuf)>,
+) -> io::Result<bool> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut found = false;
+    for entry in entries {
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+        let relative = join(prefix, &name);
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if walk(&path, &relative, notes, attachments)? {
+                notes.entry(relative).or_default();
+                found = true;
+            }
+        } else if let Some(stem) = markdown_stem(&relative) {
+            let note = notes.entry(stem.to_string()).or_default();
+            note.mtime = entry.metadata()?.modified().ok();
+            note.file = Some(path);
+            found = true;
+        } else {
+            attachments.push((relative, path));
+        }
+    }
+    Ok(found)
+}
+
+/// `path` without its `.md` extension, if it has one (in any case).
+fn markdown_stem(path: &str) -> Option<&str> {
+    let stem = path.len().checked_sub(3)?;
+    (path.is_char_boundary(stem) && path[stem..].eq_ignore_ascii_case(".md")).then(|| &path[..stem])
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
+
+/// Resolves `relative` against the folder `base`, both relative to the source
+/// folder; `None` if it points outside it.
+fn resolve_relative(base: &str, relative: &str) -> Option<String> {
+    let mut segments: Vec<&str> = if relative.starts_with('/') {
+        Vec::new()
+    } else {
+        base.split('/').filter(|s| !s.is_empty()).collect()
+    };
+    for segment in relative.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+/// Maps link targets written in the source folder to the imported notes.
+struct LinkResolver {
+    /// Lowercased source path to note path
+    by_path: HashMap<String, String>,
+    /// Lowercased file name to the source paths with it, shortest first
+    by_name: HashMap<String, Vec<String>>,
+}
+
+impl LinkResolver {
+    fn new<'a>(notes: impl Iterator<Item = (&'a str, String)>) -> Self {
+        let mut by_path = HashMap::new();
+        let mut by_name: HashMap<String, Vec<String>> = HashMap::new();
+        for (source, path) in notes {
+            let key = source.to_lowercase();
+            let name = key.rsplit('/').next().unwrap_or(&key).to_string();
+            by_name.entry(name).or_default().push(key.clone());
+            by_path.insert(key, path);
+        }
+        for sources in by_name.values_mut() {
+            sources.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        }
+        LinkResolver { by_path, by_name }
+    }
+
+    /// The note a source path (without `.md`) was imported as.
+    fn by_path(&self, source: &str) -> Option<&str> {
+        self.by_path.get(&source.to_lowercase()).map(String::as_str)
+    }
+
+    /// The note a wiki link target names: a full source path, or the end of one as
+    /// Obsidian allows, preferring the shortest path when several match.
+    fn wiki_target(&self, target: &str) -> Option<&str> {
+        let target = target.trim().trim_matches('/');
+        let key = markdown_stem(target).unwrap_or(target).to_lowercase();
+        if let Some(path) = self.by_path.get(&key) {
+            return Some(path);
+        }
+        let name = key.rsplit('/').next()?;
+        let suffix = format!("/{}", key);
+        self.by_name
+            .get(name)?
+            .iter()
+            .find(|source| source.ends_with(&suffix))
+            .and_then(|source| self.by_path.get(source))
+            .map(String::as_str)
+    }
+}
+
+/// `content` of the note imported from `source` with its links pointed at the
+/// imported notes, and how many links changed.
+fn rewrite_links(content: &str, source: &str, resolver: &LinkResolver) -> (String, usize) {
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+
+    // `[[Alpha]]`, `[[Alpha|text]]`, and `[[Alpha#Heading]]`
+    for link in link_refs(content) {
+        if link.form != LinkForm::Wiki {
+            continue;
+        }
+        let (target, anchor) = match link.path.split_once('#') {
+            Some((target, _)) => (target, true),
+            None => (link.path.as_str(), false),
+        };
+        let Some(path) = resolver.wiki_target(target) else {
+            continue;
+        };
+        // Folio links don't go to headings, so those stay visible as the text
+        let replacement = match (&link.text, anchor) {
+            (Some(text), _) => format!("[[{}|{}]]", path, text),
+            (None, true) => format!("[[{}|{}]]", path, link.path),
+            (None, false) => format!("[[{}]]", path),
+        };
+        edits.push((link.span, replacement));
+    }
+
+    // `[text](Alpha.md)`, relative to the linking note's folder
+    let base = source.rsplit_once('/').map_or("", |(parent, _)| parent);
+    for range in excluded_ranges(content) {
+        let text = &content[range.clone()];
+        if !text.starts_with('[') || text.starts_with("[[") {
+            continue;
+        }
+        let Some(mid) = text.find("](") else {
+            continue;
+        };
+        let url = &text[mid + 2..text.len() - 1];
+        let url = url.split('#').next().unwrap_or(url).trim();
+        if url.contains(':') {
+            continue;
+        }
+        let Some(path) = percent_decode(url)
+            .as_deref()
+            .and_then(markdown_stem)
+            .and_then(|stem| resolve_relative(base, stem))
+            .and_then(|stem| resolver.by_path(&stem))
+        else {
+            continue;
+        };
+        let label = text[1..mid].trim();
+        let replacement = if label.is_empty() {
+            format!("[[{}]]", path)
+        } else {
+            format!("[[{}|{}]]", path, label)
+        };
+        edits.push((range, replacement));
+    }
+
+    edits.retain(|(range, replacement)| content[range.clone()] != *replacement);
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut updated = content.to_string();
+    // Back to front, so earlier ranges stay valid
+    for (range, replacement) in edits.iter().rev() {
+        updated.replace_range(range.clone(), replacement);
+    }
+    (updated, edits.len())
+}
+
+impl NotesApi {
+    /// Imports the Markdown files below `src_dir` as notes below `options.target`.
+    ///
+    /// Notes and attachments that already exist are left alone and reported as
+    /// conflicts; everything else keeps its modification time. With `dry_run` set
+    /// nothing is written and the report says what an import would do. Otherwise a
+    /// checkpoint is created before anything is written.
+    pub fn import_vault<P: AsRef<Path>>(
+        &mut self,
+        src_dir: P,
+        options: &VaultImportOptions,
+    ) -> Result<VaultImport> {
+        let src_dir = src_dir.as_ref();
+        if !src_dir.is_dir() {
+            return Err(Error::InvalidInput(format!(
+                "Not a folder: {}",
+                src_dir.display()
+            )));
+        }
+        let target = options.target.trim_matches('/');
+        if !target.is_empty() && !self.note_exists(target)? {
+            return Err(Error::NotFound(target.to_string()));
+        }
+        if !options.dry_run {
+            self.ensure_writable()?;
+        }
+
+        let mut notes = BTreeMap::new();
+        let mut files = Vec::new();
+        walk(src_dir, "", &mut notes, &mut files)?;
+        let resolver = LinkResolver::new(
+            notes
+                .keys()
+                .map(|source| (source.as_str(), join(target, source))),
+        );
+
+        let mut import = VaultImport::default();
+        // Parents come before their children, since a path sorts after its prefixes
+        let mut writes = Vec::new();
+        for (source, note) in &notes {
+            let path = join(target, source);
+            // The file may be on disk before the watcher has indexed it
+            if self.note_exists(&path)? || self.fs.note_mtime(&path).is_ok() {
+                import.conflicts.push(path);
+                continue;
+            }
+            let content = match &note.file {
+                Some(file) => fs::read_to_string(file)?,
+                None => String::new(),
+            };
+            let (content, rewritten) = rewrite_links(&content, source, &resolver);
+            import.links_rewritten += rewritten;
+            import.created.push(path.clone());
+            writes.push((path, content, note.mtime));
+        }
+
+        let root = self.fs.root_path().to_path_buf();
+        let mut copies = Vec::new();
+        for (relative, src) in files {
+            let dest = join(target, &relative);
+            if root.join(&dest).exists() {
+                import.conflicts.push(dest);
+                continue;
+            }
+            import.attachments.push(dest.clone());
+            copies.push((src, dest));
+        }
+
+        if options.dry_run || (writes.is_empty() && copies.is_empty()) {
+            return Ok(import);
+        }
+        self.create_checkpoint("Before import")?;
+
+        for (path, content, mtime) in &writes {
+            self.fs.write_note(path, content)?;
+            if let Some(mtime) = mtime {
+                self.fs.set_note_mtime(path, *mtime)?;
+            }
+        }
+        for (src, dest) in &copies {
+            // Attachments live in their note's folder
+            let owner = dest.rsplit_once('/').map_or("", |(parent, _)| parent);
+            self.fs.promote_note(owner)?;
+            let full = root.join(dest);
+            if let Some(parent) = full.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            // A file that appeared since the check above is kept, not overwritten
+            let mut out = match File::options().write(true).create_new(true).open(&full) {
+                Ok(out) => out,
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    import.attachments.retain(|attachment| attachment != dest);
+                    import.conflicts.push(dest.clone());
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            io::copy(&mut File::open(src)?, &mut out)?;
+            if let Ok(mtime) = fs::metadata(src).and_then(|meta| meta.modified()) {
+                out.set_modified(mtime)?;
+            }
+        }
+
+        self.rescan()?;
+        Ok(import)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+    use tempfile::TempDir;
+
+    /// An Obsidian-style vault: loose notes, a folder with a sibling note, a folder
+    /// without one, an attachment, and settings to skip.
+    fn obsidian_vault() -> TempDir {
+        let src = TempDir::new().unwrap();
+        let root = src.path();
+        fs::create_dir_all(root.join(".obsidian")).unwrap();
+        fs::create_dir_all(root.join("Projects/Alpha")).unwrap();
+        fs::create_dir_all(root.join("Areas")).unwrap();
+        fs::write(root.join(".obsidian/app.json"), "{}").unwrap();
+        fs::write(
+            root.join("Home.md"),
+            "See [[Beta]], [[Projects/Alpha|the alpha]], [[Beta#Plan]], and [notes](Areas/Health%20Log.md).\n",
+        )
+        .unwrap();
+        fs::write(root.join("Projects.md"), "# Projects\n").unwrap();
+        fs::write(root.join("Projects/Alpha.md"), "Back to [[Home]]\n").unwrap();
+        fs::write(root.join("Projects/Alpha/Beta.md"), "[up](../../Home.md)\n").unwrap();
+        fs::write(root.join("Projects/Alpha/diagram.png"), [0u8; 4]).unwrap();
+        fs::write(root.join("Areas/Health Log.md"), "[[Missing]]\n").unwrap();
+        src
+    }
+
+    #[test]
+    fn test_import_obsidian_vault() {
+        let src = obsidian_vault();
+        let mtime = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        File::options()
+            .write(true)
+            .open(src.path().join("Projects/Alpha.md"))
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("imported").unwrap();
+
+        let options = VaultImportOptions {
+            target: "imported".to_string(),
+            dry_run: false,
+        };
+        let import = api.import_vault(src.path(), &options).unwrap();
+
+        assert_eq!(
+            import.created,
+            vec![
+                "imported/Areas",
+                "imported/Areas/Health Log",
+                "imported/Home",
+                "imported/Projects",
+                "imported/Projects/Alpha",
+                "imported/Projects/Alpha/Beta",
+            ]
+        );
+        assert!(import.conflicts.is_empty());
+        assert_eq!(
+            import.attachments,
+            vec!["imported/Projects/Alpha/diagram.png"]
+        );
+        assert_eq!(import.links_rewritten, 6);
+
+        assert_eq!(
+            api.get_note("imported/Home").unwrap().content,
+            "See [[imported/Projects/Alpha/Beta]], [[imported/Projects/Alpha|the alpha]], \
+             [[imported/Projects/Alpha/Beta|Beta#Plan]], and [[imported/Areas/Health Log|notes]].\n"
+        );
+        assert_eq!(
+            api.get_note("imported/Projects/Alpha/Beta")
+                .unwrap()
+                .content,
+            "[[imported/Home|up]]\n"
+        );
+        // Unresolved links are left alone, and folders without a note are empty notes
+        assert_eq!(
+            api.get_note("imported/Areas/Health Log").unwrap().content,
+            "[[Missing]]\n"
+        );
+        assert_eq!(api.get_note("imported/Areas").unwrap().content, "");
+        assert_eq!(
+            api.get_note("imported/Projects").unwrap().content,
+            "# Projects\n"
+        );
+
+        assert_eq!(api.fs.note_mtime("imported/Projects/Alpha").unwrap(), mtime);
+        assert!(
+            temp_dir
+                .path()
+                .join("imported/Projects/Alpha/diagram.png")
+                .exists()
+        );
+        assert!(!api.note_exists("imported/.obsidian").unwrap());
+        assert_eq!(api.list_checkpoints().unwrap()[0].label, "Before import");
+    }
+
+    #[test]
+    fn test_import_dry_run_and_conflicts() {
+        let src = obsidian_vault();
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        api.create_note("Home").unwrap();
+        api.save_note("Home", "Already here\n").unwrap();
+
+        let options = VaultImportOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let preview = api.import_vault(src.path(), &options).unwrap();
+        assert_eq!(preview.conflicts, vec!["Home"]);
+        assert_eq!(preview.created.len(), 5);
+        assert!(!api.note_exists("Projects").unwrap());
+        assert!(!temp_dir.path().join("Projects").exists());
+
+        let import = api
+            .import_vault(src.path(), &VaultImportOptions::default())
+            .unwrap();
+        assert_eq!(import, preview);
+        assert_eq!(api.get_note("Home").unwrap().content, "Already here\n");
+        // Links to the conflicting note still point at it
+        assert_eq!(
+            api.get_note("Projects/Alpha").unwrap().content,
+            "Back to [[Home]]\n"
+        );
+    }
+
+    #[test]
+    fn test_import_keeps_notes_not_indexed_yet() {
+        let src = obsidian_vault();
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+        // Written by another program, before the watcher picked it up
+        fs::create_dir_all(temp_dir.path().join("Areas")).unwrap();
+        fs::write(temp_dir.path().join("Areas/_index.md"), "Mine\n").unwrap();
+
+        let import = api
+            .import_vault(src.path(), &VaultImportOptions::default())
+            .unwrap();
+        assert!(import.conflicts.contains(&"Areas".to_string()));
+        assert_eq!(api.get_note("Areas").unwrap().content, "Mine\n");
+    }
+
+    #[test]
+    fn test_import_requires_target_and_folder() {
+        let src = obsidian_vault();
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        let options = VaultImportOptions {
+            target: "missing".to_string(),
+            dry_run: true,
+        };
+        assert!(matches!(
+            api.import_vault(src.path(), &options),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            api.import_vault(src.path().join("Home.md"), &VaultImportOptions::default()),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_relative() {
+        assert_eq!(resolve_relative("a/b", "../c").as_deref(), Some("a/c"));
+        assert_eq!(resolve_relative("a", "./b/c").as_deref(), Some("a/b/c"));
+        assert_eq!(resolve_relative("a", "/c").as_deref(), Some("c"));
+        assert_eq!(resolve_relative("a", "../../c"), None);
+    }
+}
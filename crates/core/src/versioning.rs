@@ -0,0 +1,271 @@
+//! Optimistic concurrency for note saves.
+//!
+//! A client reads a note together with the hash of its content, then saves with that
+//! hash as the base. If the file changed in the meantime (another window, a sync tool,
+//! an editor outside the app) the save is refused and the current content is returned
+//! so the client can merge or reload instead of overwriting it.
+//!
+//! Clients that only keep the note's modification time can use
+//! `save_note_if_unchanged` instead, which fails with `Error::Conflict`.
+
+use std::time::SystemTime;
+
+use crate::changes::Revision;
+use crate::notes::{Error, Note, NotesApi, Result, compute_hash};
+
+/// A note together with the hash of the content that was read.
+#[derive(Debug, Clone)]
+pub struct VersionedNote {
+    pub note: Note,
+    pub content_hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveOutcome {
+    /// The content was written; `content_hash` is the base for the next save
+    Saved {
+        revision: Revision,
+        content_hash: String,
+    },
+    /// The note changed since the base was read; nothing was written
+    Conflict {
+        content: String,
+        content_hash: String,
+        revision: Revision,
+    },
+}
+
+/// A note written by `save_note_if_unchanged`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedNote {
+    pub revision: Revision,
+    /// The `expected_modified` for the next save
+    pub modified: SystemTime,
+}
+
+impl NotesApi {
+    /// Retrieves a note along with the hash to pass to `save_note_versioned`.
+    ///
+    /// Records an access like `get_note`.
+    pub fn get_note_with_version(&mut self, path: &str) -> Result<VersionedNote> {
+        let note = self.get_note(path)?;
+        let content_hash = compute_hash(&note.content);
+        Ok(VersionedNote { note, content_hash })
+    }
+
+    /// Saves a note only if its content on disk still matches `base_hash`.
+    ///
+    /// The check reads the file rather than the index, so edits the watcher hasn't
+    /// synced yet are still detected. Saving content identical to what is already on
+    /// disk succeeds regardless of the base.
+    pub fn save_note_versioned(
+        &mut self,
+        path: &str,
+        content: &str,
+        base_hash: &str,
+    ) -> Result<SaveOutcome> {
+        // Offline there is no file to check the base against; `save_note` queues the
+        // edit if it can and fails otherwise
+        if !self.is_available() {
+            let revision = self.save_note(path, content)?;
+            return Ok(SaveOutcome::Saved {
+                revision,
+                content_hash: compute_hash(content),
+            });
+        }
+        self.ensure_writable()?;
+
+        let current = self.read_content(path)?;
+        let current_hash = compute_hash(&current);
+
+        if current_hash != base_hash && current != content {
+            return Ok(SaveOutcome::Conflict {
+                content: current,
+                content_hash: current_hash,
+                revision: self.revision()?,
+            });
+        }
+
+        let revision = self.save_note(path, content)?;
+        Ok(SaveOutcome::Saved {
+            revision,
+            content_hash: compute_hash(content),
+        })
+    }
+
+    /// Saves a note only if its file was last modified at `expected_modified`, the
+    /// `modified` of the note as the caller read it.
+    ///
+    /// Times are compared exactly, so an edit within the same second as the read is
+    /// still caught. Fails with `Error::Conflict` carrying the content on disk when
+    /// the file changed since; saving content identical to it succeeds regardless.
+    pub fn save_note_if_unchanged(
+        &mut self,
+        path: &str,
+        content: &str,
+        expected_modified: SystemTime,
+    ) -> Result<SavedNote> {
+        self.ensure_writable()?;
+
+        let current = self.read_content(path)?;
+        let modified = self.fs.note_mtime(path)?;
+
+        if modified != expected_modified && current != content {
+            return Err(Error::Conflict {
+                path: path.to_string(),
+                content: current,
+            });
+        }
+
+        let revision = self.save_note(path, content)?;
+        Ok(SavedNote {
+            revision,
+            modified: self.fs.note_mtime(path)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_with_current_base() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("a").unwrap();
+        let versioned = api.get_note_with_version("a").unwrap();
+
+        let outcome = api
+            .save_note_versioned("a", "# A\n", &versioned.content_hash)
+            .unwrap();
+        let SaveOutcome::Saved { content_hash, .. } = outcome else {
+            panic!("expected save, got {:?}", outcome);
+        };
+
+        let reread = api.get_note_with_version("a").unwrap();
+        assert_eq!(reread.note.content, "# A\n");
+        assert_eq!(reread.content_hash, content_hash);
+    }
+
+    #[test]
+    fn test_external_edit_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("a").unwrap();
+        let versioned = api.get_note_with_version("a").unwrap();
+
+        // Edited outside the app, not yet seen by the watcher
+        std::fs::write(temp_dir.path().join("a/_index.md"), "# Theirs\n").unwrap();
+
+        let outcome = api
+            .save_note_versioned("a", "# Mine\n", &versioned.content_hash)
+            .unwrap();
+        match outcome {
+            SaveOutcome::Conflict {
+                content,
+                content_hash,
+                ..
+            } => {
+                assert_eq!(content, "# Theirs\n");
+                assert_eq!(content_hash, compute_hash("# Theirs\n"));
+            }
+            other => panic!("expected conflict, got {:?}", other),
+        }
+
+        let on_disk = std::fs::read_to_string(temp_dir.path().join("a/_index.md")).unwrap();
+        assert_eq!(on_disk, "# Theirs\n");
+    }
+
+    #[test]
+    fn test_identical_content_is_not_a_conflict() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("a").unwrap();
+        let versioned = api.get_note_with_version("a").unwrap();
+        std::fs::write(temp_dir.path().join("a/_index.md"), "# Same\n").unwrap();
+
+        let outcome = api
+            .save_note_versioned("a", "# Same\n", &versioned.content_hash)
+            .unwrap();
+        assert!(matches!(outcome, SaveOutcome::Saved { .. }));
+    }
+
+    #[test]
+    fn test_save_if_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("a").unwrap();
+        let read = api.get_note("a").unwrap();
+        let saved = api
+            .save_note_if_unchanged("a", "# Mine\n", read.modified)
+            .unwrap();
+
+        // Edited outside the app a little later
+        let file = temp_dir.path().join("a/_index.md");
+        std::fs::write(&file, "# Theirs\n").unwrap();
+        let later = saved.modified + std::time::Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        match api.save_note_if_unchanged("a", "# Mine again\n", saved.modified) {
+            Err(Error::Conflict { path, content }) => {
+                assert_eq!(path, "a");
+                assert_eq!(content, "# Theirs\n");
+            }
+            other => panic!("expected conflict, got {:?}", other),
+        }
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "# Theirs\n");
+
+        assert!(api.save_note_if_unchanged("a", "# Mine\n", later).is_ok());
+    }
+
+    #[test]
+    fn test_edit_within_the_same_second_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("a").unwrap();
+        let file = temp_dir.path().join("a/_index.md");
+        let read_at = std::time::UNIX_EPOCH + std::time::Duration::from_millis(1_700_000_000_100);
+        let set_mtime = |time| {
+            std::fs::File::options()
+                .write(true)
+                .open(&file)
+                .unwrap()
+                .set_modified(time)
+                .unwrap()
+        };
+        set_mtime(read_at);
+        let read = api.get_note("a").unwrap();
+        assert_eq!(read.modified, read_at);
+
+        std::fs::write(&file, "# Theirs\n").unwrap();
+        set_mtime(read_at + std::time::Duration::from_millis(300));
+
+        assert!(matches!(
+            api.save_note_if_unchanged("a", "# Mine\n", read.modified),
+            Err(Error::Conflict { .. })
+        ));
+    }
+
+    #[test]
+    fn test_save_versioned_missing_note() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        assert!(matches!(
+            api.save_note_versioned("missing", "x", "0"),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
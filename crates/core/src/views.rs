@@ -0,0 +1,284 @@
+//! Database-style views of a note's children.
+//!
+//! A parent note declares how its children should be shown in its frontmatter:
+//!
+//! ```text
+//! view: table
+//! view_properties: [status, owner, due]
+//! view_sort: -due
+//! view_filter: [status!=done, owner]
+//! ```
+//!
+//! `view_sort` takes a property name, prefixed with `-` for descending order.
+//! Each `view_filter` entry is `key=value`, `key!=value`, `key` (has a value),
+//! or `!key` (has no value); a child must match all of them.
+
+use std::cmp::Ordering;
+
+use crate::board::Board;
+use crate::board::{COLUMNS_KEY, STATUS_KEY, group_cards};
+use crate::frontmatter::Frontmatter;
+use crate::notes::{Error, NoteMetadata, NotesApi, Result};
+
+/// Frontmatter key on the parent note selecting the view kind.
+pub const VIEW_KEY: &str = "view";
+
+/// Frontmatter key listing the properties shown as table columns.
+pub const VIEW_PROPERTIES_KEY: &str = "view_properties";
+
+/// Frontmatter key naming the property children are sorted by.
+pub const VIEW_SORT_KEY: &str = "view_sort";
+
+/// Frontmatter key holding the filter conditions.
+pub const VIEW_FILTER_KEY: &str = "view_filter";
+
+#[derive(Debug, Clone)]
+pub enum ViewLayout {
+    List,
+    /// One row per child with a column for each property
+    Table {
+        properties: Vec<String>,
+    },
+    /// Children grouped by `status`, like `get_board`
+    Board(Board),
+}
+
+#[derive(Debug, Clone)]
+pub struct ViewRow {
+    pub note: NoteMetadata,
+    pub frontmatter: Frontmatter,
+}
+
+#[derive(Debug, Clone)]
+pub struct ViewData {
+    pub path: String,
+    pub layout: ViewLayout,
+    /// Matching children in display order
+    pub rows: Vec<ViewRow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Equals(String, String),
+    NotEquals(String, String),
+    Present(String),
+    Absent(String),
+}
+
+impl Condition {
+    fn parse(raw: &str) -> Option<Condition> {
+        let raw = raw.trim();
+        if let Some((key, value)) = raw.split_once("!=") {
+            return Some(Condition::NotEquals(key.trim().into(), value.trim().into()));
+        }
+        if let Some((key, value)) = raw.split_once('=') {
+            return Some(Condition::Equals(key.trim().into(), value.trim().into()));
+        }
+        match raw.strip_prefix('!') {
+            Some(key) if !key.trim().is_empty() => Some(Condition::Absent(key.trim().into())),
+            None if !raw.is_empty() => Some(Condition::Present(raw.into())),
+            _ => None,
+        }
+    }
+
+    fn matches(&self, frontmatter: &Frontmatter) -> bool {
+        let values = |key: &str| {
+            frontmatter
+                .get(key)
+                .map(|v| v.as_list())
+                .unwrap_or_default()
+        };
+        match self {
+            Condition::Equals(key, value) => values(key).contains(value),
+            Condition::NotEquals(key, value) => !values(key).contains(value),
+            Condition::Present(key) => !values(key).is_empty(),
+            Condition::Absent(key) => values(key).is_empty(),
+        }
+    }
+}
+
+/// Orders two property values, numerically when both are numbers. Missing values
+/// sort last regardless of direction.
+fn compare_values(a: Option<&str>, b: Option<&str>, descending: bool) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => {
+            let ordering = match (a.parse::<f64>(), b.parse::<f64>()) {
+                (Ok(a), Ok(b)) => a.total_cmp(&b),
+                _ => a.to_lowercase().cmp(&b.to_lowercase()),
+            };
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        }
+    }
+}
+
+impl NotesApi {
+    /// Evaluates the view declared on the note at `path` over its non-archived
+    /// children. Notes without a `view` property get a plain list.
+    pub fn get_view_data(&self, path: &str) -> Result<ViewData> {
+        if !self.note_exists(path)? {
+            return Err(Error::NotFound(path.to_string()));
+        }
+        let content = self.read_content(path)?;
+        let (parent_fm, _) = Frontmatter::parse(&content);
+        let list = |key: &str| parent_fm.get(key).map(|v| v.as_list()).unwrap_or_default();
+
+        let conditions: Vec<Condition> = list(VIEW_FILTER_KEY)
+            .iter()
+            .filter_map(|raw| Condition::parse(raw))
+            .collect();
+
+        let mut rows = Vec::new();
+        for child in self.get_children(path)? {
+            if child.archived {
+                continue;
+            }
+            let content = self.read_content(&child.path).unwrap_or_default();
+            let (frontmatter, _) = Frontmatter::parse(&content);
+            if conditions.iter().all(|c| c.matches(&frontmatter)) {
+                rows.push(ViewRow {
+                    note: child,
+                    frontmatter,
+                });
+            }
+        }
+
+        if let Some(sort) = parent_fm.get_text(VIEW_SORT_KEY).filter(|s| !s.is_empty()) {
+            let (key, descending) = match sort.strip_prefix('-') {
+                Some(key) => (key.trim(), true),
+                None => (sort, false),
+            };
+            // Stable, so ties keep the children's path order
+            rows.sort_by(|a, b| {
+                compare_values(
+                    a.frontmatter.get_text(key),
+                    b.frontmatter.get_text(key),
+                    descending,
+                )
+            });
+        }
+
+        let layout = match parent_fm.get_text(VIEW_KEY).unwrap_or("list") {
+            "list" | "" => ViewLayout::List,
+            "table" => ViewLayout::Table {
+                properties: list(VIEW_PROPERTIES_KEY),
+            },
+            "board" => {
+                let cards = rows
+                    .iter()
+                    .map(|row| {
+                        let status = row.frontmatter.get_text(STATUS_KEY).map(str::to_string);
+                        (row.note.clone(), status)
+                    })
+                    .collect();
+                ViewLayout::Board(group_cards(path, list(COLUMNS_KEY), cards))
+            }
+            other => {
+                return Err(Error::InvalidInput(format!("Unknown view: {}", other)));
+            }
+        };
+
+        Ok(ViewData {
+            path: path.to_string(),
+            layout,
+            rows,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup(parent_frontmatter: &str) -> (TempDir, NotesApi) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut api = NotesApi::new(temp_dir.path()).unwrap();
+
+        api.create_note("projects").unwrap();
+        api.save_note("projects", &format!("---\n{}---\n", parent_frontmatter))
+            .unwrap();
+        for (name, fm) in [
+            ("alpha", "status: active\npriority: 2\n"),
+            ("beta", "status: done\npriority: 10\n"),
+            ("gamma", "status: active\n"),
+        ] {
+            let path = format!("projects/{}", name);
+            api.create_note(&path).unwrap();
+            api.save_note(&path, &format!("---\n{}---\n", fm)).unwrap();
+        }
+        (temp_dir, api)
+    }
+
+    fn row_paths(view: &ViewData) -> Vec<&str> {
+        view.rows.iter().map(|r| r.note.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_default_list_view() {
+        let (_temp_dir, api) = setup("title: Projects\n");
+        let view = api.get_view_data("projects").unwrap();
+        assert!(matches!(view.layout, ViewLayout::List));
+        assert_eq!(
+            row_paths(&view),
+            vec!["projects/alpha", "projects/beta", "projects/gamma"]
+        );
+    }
+
+    #[test]
+    fn test_table_view_sorts_and_filters() {
+        let (_temp_dir, api) =
+            setup("view: table\nview_properties: [status, priority]\nview_sort: -priority\n");
+        let view = api.get_view_data("projects").unwrap();
+        let ViewLayout::Table { properties } = &view.layout else {
+            panic!("expected a table");
+        };
+        assert_eq!(properties, &vec!["status", "priority"]);
+        // Numeric order, with the note lacking a priority last
+        assert_eq!(
+            row_paths(&view),
+            vec!["projects/beta", "projects/alpha", "projects/gamma"]
+        );
+        assert_eq!(view.rows[0].frontmatter.get_text("status"), Some("done"));
+
+        let (_temp_dir, api) = setup("view: list\nview_filter: [status!=done, priority]\n");
+        let view = api.get_view_data("projects").unwrap();
+        assert_eq!(row_paths(&view), vec!["projects/alpha"]);
+    }
+
+    #[test]
+    fn test_board_view() {
+        let (_temp_dir, api) = setup("view: board\ncolumns: [active, done]\nview_sort: priority\n");
+        let view = api.get_view_data("projects").unwrap();
+        let ViewLayout::Board(board) = view.layout else {
+            panic!("expected a board");
+        };
+        assert_eq!(board.columns[0].name, "active");
+        let active: Vec<_> = board.columns[0]
+            .cards
+            .iter()
+            .map(|c| c.path.as_str())
+            .collect();
+        assert_eq!(active, vec!["projects/alpha", "projects/gamma"]);
+        assert_eq!(board.columns[1].cards.len(), 1);
+    }
+
+    #[test]
+    fn test_unknown_view() {
+        let (_temp_dir, api) = setup("view: gallery\n");
+        assert!(matches!(
+            api.get_view_data("projects"),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            api.get_view_data("missing"),
+            Err(Error::NotFound(_))
+        ));
+    }
+}
@@ -1,18 +1,80 @@
-use std::sync::{Arc, Mutex};
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::JoinHandle;
+use std::time::Duration;
 
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 
 use crate::NotesApi;
+use crate::clock::Clock;
+use crate::config::TimingConfig;
+use crate::events::NotesEvent;
+use crate::layout::NoteFiles;
+use crate::write_queue::{BackgroundTask, WriteQueue, coalesce, run_task};
 
-/// Event type emitted by the filesystem watcher
-#[derive(Debug, Clone)]
-pub enum WatcherEvent {
-    /// Notes were modified (created, updated, or deleted)
-    NotesChanged,
-    /// Notes were renamed or moved
-    NotesRenamed,
-    /// Frecency scores were updated (navigation should refresh)
-    FrecencyUpdated,
+/// How often `spawn_availability_monitor` checks whether the notes root exists
+const AVAILABILITY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The filesystem lookups `event_tasks` makes, so tests can answer them from a
+/// description of the tree instead of a real directory.
+pub trait FsProbe {
+    fn is_dir(&self, path: &Path) -> bool;
+    fn exists(&self, path: &Path) -> bool;
+}
+
+/// Answers `FsProbe` lookups from the real filesystem.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiskProbe;
+
+impl FsProbe for DiskProbe {
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+}
+
+/// How `setup_watcher` batches filesystem events.
+///
+/// The timings come from a vault's `TimingConfig` (see `from_timing`); the default
+/// uses the default timings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatcherConfig {
+    /// How long the vault must be quiet before the burst of changes is synced
+    pub debounce: Duration,
+    /// Whether duplicate tasks in a burst run once (see `batch_tasks`)
+    pub coalesce: bool,
+    /// A coalesced burst touching more notes than this is handled with one rescan
+    pub rescan_threshold: usize,
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self::from_timing(&TimingConfig::default())
+    }
+}
+
+impl WatcherConfig {
+    /// The watcher settings in a vault's `timing` config, with coalescing on.
+    pub fn from_timing(timing: &TimingConfig) -> Self {
+        Self {
+            debounce: Duration::from_millis(timing.watcher_debounce_ms),
+            coalesce: true,
+            rescan_threshold: timing.rescan_threshold,
+        }
+    }
+
+    /// The tasks to run for a settled burst, in order.
+    fn settled_tasks(&self, tasks: Vec<BackgroundTask>) -> Vec<BackgroundTask> {
+        if self.coalesce {
+            batch_tasks(tasks, self.rescan_threshold)
+        } else {
+            tasks
+        }
+    }
 }
 
 /// Sets up a filesystem watcher for the notes directory.
@@ -23,12 +85,15 @@ pub enum WatcherEvent {
 /// - Note folder creation and deletion
 /// - Note folder renames and moves
 ///
-/// The watcher uses debouncing to avoid excessive rescans during bulk operations.
+/// The watcher uses debouncing to avoid excessive rescans during bulk operations. Work
+/// runs on the trailing edge: once `config.debounce` passes without another event,
+/// every task collected during the burst is run, so the final state is always synced.
 ///
 /// # Arguments
 ///
 /// * `notes_api` - Arc-wrapped NotesApi instance to sync when changes are detected
 /// * `on_change` - Optional callback function that will be called when changes are detected
+/// * `config` - Debounce window and whether to coalesce duplicate work
 ///
 /// # Returns
 ///
@@ -38,135 +103,674 @@ pub enum WatcherEvent {
 /// # Example
 ///
 /// ```no_run
-/// use zinnia_core::{NotesApi, setup_watcher, WatcherEvent};
+/// use zinnia_core::{NotesApi, setup_watcher, WatcherConfig, NotesEvent};
 /// use std::sync::{Arc, Mutex};
 ///
 /// let api = NotesApi::new("/path/to/notes").unwrap();
 /// let api = Arc::new(Mutex::new(api));
-/// let _watcher = setup_watcher(Arc::clone(&api), None::<fn(WatcherEvent)>);
+/// let _watcher = setup_watcher(
+///     Arc::clone(&api),
+///     None::<fn(NotesEvent)>,
+///     WatcherConfig::default(),
+/// );
 /// // Keep _watcher alive while you want to monitor filesystem changes
 /// ```
-pub fn setup_watcher<F>(notes_api: Arc<Mutex<NotesApi>>, on_change: Option<F>) -> RecommendedWatcher
+pub fn setup_watcher<F>(
+    notes_api: Arc<Mutex<NotesApi>>,
+    on_change: Option<F>,
+    config: WatcherConfig,
+) -> RecommendedWatcher
 where
-    F: Fn(WatcherEvent) + Send + 'static,
+    F: Fn(NotesEvent) + Send + 'static,
 {
-    let notes_root = {
+    let (notes_root, files, clock) = {
         let api = notes_api.lock().unwrap();
-        api.notes_root().to_path_buf()
+        (
+            api.notes_root().to_path_buf(),
+            api.note_files(),
+            api.clock(),
+        )
     };
 
     let notes_root_clone = notes_root.clone();
+    let pending = Arc::new(Mutex::new(Vec::new()));
+    let (tx, rx) = mpsc::channel::<()>();
 
-    // Helper function to convert filesystem path to note path
-    let path_to_note_path = move |fs_path: &std::path::Path| -> Option<String> {
-        // Get the path relative to notes_root
-        let relative = fs_path.strip_prefix(&notes_root_clone).ok()?;
-
-        // Convert to string
-        let path_str = relative.to_str()?;
-
-        // Remove /_index.md suffix if present
-        if path_str.ends_with("/_index.md") {
-            Some(path_str.trim_end_matches("/_index.md").to_string())
-        } else if path_str == "_index.md" {
-            Some(String::new()) // Root note
-        } else if relative.is_dir() {
-            // Directory itself - use as-is
-            Some(path_str.to_string())
-        } else {
-            None
-        }
-    };
-
+    let watcher_pending = Arc::clone(&pending);
     let mut watcher = RecommendedWatcher::new(
-        move |result: Result<Event, notify::Error>| {
-            match result {
-                Ok(event) => {
-                    // Ignore changes to the database file itself to prevent loops
-                    let is_db_change = event.paths.iter().any(|p| {
-                        p.file_name().and_then(|n| n.to_str()).is_some_and(|name| {
-                            name == ".notes.db" || name.starts_with(".notes.db-")
-                        })
-                    });
-
-                    if is_db_change {
-                        return;
-                    }
+        move |result: Result<Event, notify::Error>| match result {
+            Ok(event) => {
+                let tasks = event_tasks(&event, &notes_root_clone, &files, &DiskProbe);
+                if !tasks.is_empty() {
+                    watcher_pending.lock().unwrap().extend(tasks);
+                    let _ = tx.send(());
+                }
+            }
+            Err(e) => eprintln!("Filesystem watcher error: {:?}", e),
+        },
+        Config::default(),
+    )
+    .expect("Failed to create filesystem watcher");
 
-                    // Check if this is a note-related change (involves _index.md or note directories)
-                    let is_note_related = event.paths.iter().any(|p| {
-                        // Check if it's an _index.md file
-                        if p.file_name().and_then(|n| n.to_str()) == Some("_index.md") {
-                            return true;
-                        }
-
-                        // Check if it's a directory that might contain notes
-                        if p.is_dir() {
-                            // Check if it contains _index.md
-                            let index_path = p.join("_index.md");
-                            return index_path.exists();
-                        }
-
-                        false
-                    });
-
-                    if !is_note_related {
-                        return;
+    // Stops once the watcher (holding the sender) is dropped
+    std::thread::spawn(move || {
+        debounce(
+            rx,
+            clock.as_ref(),
+            || config.debounce,
+            || {
+                // Events arriving from here on start the next burst
+                let tasks = std::mem::take(&mut *pending.lock().unwrap());
+                let Ok(mut api) = notes_api.lock() else {
+                    return;
+                };
+                for task in config.settled_tasks(tasks) {
+                    // Only notify if the index actually changed
+                    if let Some(event) = run_task(&mut api, &task)
+                        && let Some(ref callback) = on_change
+                    {
+                        callback(event);
                     }
+                }
+            },
+        );
+    });
 
-                    use notify::EventKind;
-                    match event.kind {
-                        // Handle rename/move events - need full rescan
-                        EventKind::Modify(notify::event::ModifyKind::Name(_)) => {
-                            if let Ok(mut api) = notes_api.lock() {
-                                if let Err(e) = api.rescan() {
-                                    eprintln!("Failed to rescan after rename: {:?}", e);
-                                } else if let Some(ref callback) = on_change {
-                                    callback(WatcherEvent::NotesRenamed);
-                                }
-                            }
-                        }
-                        // Handle create, modify, and delete events for specific notes
-                        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
-                            // Extract note paths from the event
-                            for path in &event.paths {
-                                // Convert filesystem path to note path
-                                if let Some(note_path) = path_to_note_path(path)
-                                    && let Ok(mut api) = notes_api.lock()
-                                {
-                                    // Use sync_note which returns true only if content changed
-                                    match api.sync_note(&note_path) {
-                                        Ok(true) => {
-                                            // Only notify if content actually changed
-                                            if let Some(ref callback) = on_change {
-                                                callback(WatcherEvent::NotesChanged);
-                                            }
-                                        }
-                                        Ok(false) => {
-                                            // Don't notify - content is identical
-                                        }
-                                        Err(e) => {
-                                            eprintln!("Failed to sync note {}: {:?}", note_path, e);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        _ => {
-                            // Ignore other event types
-                        }
-                    }
+    watcher
+        .watch(&notes_root, RecursiveMode::Recursive)
+        .expect("Failed to start watching notes directory");
+
+    watcher
+}
+
+/// Calls `on_settle` once per burst of messages on `rx`, after `settle()` passes on
+/// `clock` without another one arriving. Returns when the sender is dropped.
+///
+/// `settle` is asked again for every wait, so a changed setting applies to the next one.
+pub(crate) fn debounce(
+    rx: mpsc::Receiver<()>,
+    clock: &dyn Clock,
+    settle: impl Fn() -> Duration,
+    mut on_settle: impl FnMut(),
+) {
+    while rx.recv().is_ok() {
+        loop {
+            clock.sleep(settle());
+            match rx.try_recv() {
+                Ok(()) => while rx.try_recv().is_ok() {},
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => return,
+            }
+        }
+        on_settle();
+    }
+}
+
+/// Coalesces a settled burst of tasks, replacing them with one rescan when they
+/// touch more than `rescan_threshold` notes (e.g. a `git pull` or a sync client
+/// catching up), since one pass over the vault beats hundreds of single syncs.
+pub fn batch_tasks(tasks: Vec<BackgroundTask>, rescan_threshold: usize) -> Vec<BackgroundTask> {
+    let tasks = coalesce(tasks);
+    let syncs = tasks
+        .iter()
+        .filter(|task| matches!(task, BackgroundTask::Sync(_)))
+        .count();
+    if syncs > rescan_threshold {
+        coalesce(tasks.into_iter().chain([BackgroundTask::Rescan]))
+    } else {
+        tasks
+    }
+}
+
+/// Sets up a filesystem watcher that hands its work to a `WriteQueue`.
+///
+/// Behaves like `setup_watcher`, but tasks are held until the vault has been quiet for
+/// `timing.watcher_debounce_ms`, batched (see `batch_tasks`), and queued as background
+/// tasks that run after any pending interactive writes. Change events are reported
+/// through the queue's `on_change` callback. Updates to `timing` apply to the next burst.
+/// `files` are the vault's note file name and layout (see `NotesApi::note_files`);
+/// update them after changing either, and the next event is read with them.
+pub fn setup_queued_watcher(
+    notes_root: &Path,
+    files: Arc<Mutex<NoteFiles>>,
+    queue: Arc<WriteQueue>,
+    timing: Arc<Mutex<TimingConfig>>,
+    clock: Arc<dyn Clock>,
+) -> RecommendedWatcher {
+    let notes_root_clone = notes_root.to_path_buf();
+    let pending = Arc::new(Mutex::new(Vec::new()));
+    let (tx, rx) = mpsc::channel::<()>();
+
+    let watcher_pending = Arc::clone(&pending);
+    let mut watcher = RecommendedWatcher::new(
+        move |result: Result<Event, notify::Error>| match result {
+            Ok(event) => {
+                let files = files.lock().unwrap().clone();
+                let tasks = event_tasks(&event, &notes_root_clone, &files, &DiskProbe);
+                if !tasks.is_empty() {
+                    watcher_pending.lock().unwrap().extend(tasks);
+                    let _ = tx.send(());
                 }
-                Err(e) => eprintln!("Filesystem watcher error: {:?}", e),
             }
+            Err(e) => eprintln!("Filesystem watcher error: {:?}", e),
         },
         Config::default(),
     )
     .expect("Failed to create filesystem watcher");
 
+    // Stops once the watcher (holding the sender) is dropped
+    std::thread::spawn(move || {
+        let settle = || Duration::from_millis(timing.lock().unwrap().watcher_debounce_ms);
+        debounce(rx, clock.as_ref(), settle, || {
+            let tasks = std::mem::take(&mut *pending.lock().unwrap());
+            let threshold = timing.lock().unwrap().rescan_threshold;
+            for task in batch_tasks(tasks, threshold) {
+                queue.enqueue(task);
+            }
+        });
+    });
+
     watcher
-        .watch(&notes_root, RecursiveMode::Recursive)
+        .watch(notes_root, RecursiveMode::Recursive)
         .expect("Failed to start watching notes directory");
 
     watcher
 }
+
+/// Spawns a thread that watches for the notes root disappearing and coming back.
+///
+/// A filesystem watch dies with its directory, so when the root returns it is watched
+/// again before `VaultAvailable` is reported. Callers should rescan on `VaultAvailable`
+/// to pick up changes made while the vault was away. The thread exits once the watcher
+/// is dropped.
+pub fn spawn_availability_monitor<F>(
+    notes_root: &Path,
+    watcher: Weak<Mutex<RecommendedWatcher>>,
+    on_change: F,
+) -> JoinHandle<()>
+where
+    F: Fn(NotesEvent) + Send + 'static,
+{
+    let notes_root = notes_root.to_path_buf();
+
+    std::thread::spawn(move || {
+        let mut available = notes_root.is_dir();
+        loop {
+            std::thread::sleep(AVAILABILITY_POLL_INTERVAL);
+            let Some(watcher) = watcher.upgrade() else {
+                break;
+            };
+
+            let now_available = notes_root.is_dir();
+            if now_available == available {
+                continue;
+            }
+
+            let Ok(mut watcher) = watcher.lock() else {
+                break;
+            };
+            if now_available {
+                if let Err(e) = watcher.watch(&notes_root, RecursiveMode::Recursive) {
+                    // Try again on the next poll
+                    eprintln!("Failed to re-watch notes directory: {:?}", e);
+                    continue;
+                }
+                on_change(NotesEvent::VaultAvailable);
+            } else {
+                let _ = watcher.unwatch(&notes_root);
+                on_change(NotesEvent::VaultUnavailable);
+            }
+            available = now_available;
+        }
+    })
+}
+
+/// Converts a filesystem path to the note path it belongs to.
+pub(crate) fn path_to_note_path(
+    notes_root: &Path,
+    files: &NoteFiles,
+    fs_path: &Path,
+    probe: &impl FsProbe,
+) -> Option<String> {
+    // Get the path relative to notes_root
+    let relative = fs_path.strip_prefix(notes_root).ok()?;
+
+    // Convert to string
+    let path_str = relative.to_str()?;
+
+    // Hidden folders (e.g. the trash) are never indexed
+    if path_str.split('/').any(|segment| segment.starts_with('.')) {
+        return None;
+    }
+
+    // A note or canvas file belongs to its note (a folder's, or a flat `<name>.md`)
+    if let Some(note) = files.note_of_file(path_str) {
+        Some(note)
+    } else if probe.is_dir(fs_path) {
+        // Directory itself - use as-is
+        Some(path_str.to_string())
+    } else {
+        None
+    }
+}
+
+/// Maps both ends of a rename to note paths when a note folder was renamed. The source
+/// no longer exists, so it is taken to be a folder because the destination is one.
+fn renamed_note_paths(
+    notes_root: &Path,
+    files: &NoteFiles,
+    from: &Path,
+    to: &Path,
+    probe: &impl FsProbe,
+) -> Option<(String, String)> {
+    if !probe.exists(&to.join(&files.note_file)) {
+        return None;
+    }
+    let to = path_to_note_path(notes_root, files, to, probe)?;
+    let from = from.strip_prefix(notes_root).ok()?.to_str()?;
+    if from.is_empty() || from.split('/').any(|segment| segment.starts_with('.')) {
+        return None;
+    }
+    Some((from.to_string(), to))
+}
+
+/// Decides what index maintenance a filesystem event requires.
+///
+/// The only side effects are the lookups made through `probe`; feed the result of a
+/// burst of events to `write_queue::coalesce` to see what would actually run.
+/// `files` are the vault's note file name and layout (see `NotesApi::note_files`).
+pub fn event_tasks(
+    event: &Event,
+    notes_root: &Path,
+    files: &NoteFiles,
+    probe: &impl FsProbe,
+) -> Vec<BackgroundTask> {
+    // A vanished root is reported by the availability monitor, not as note deletions
+    if !probe.is_dir(notes_root) {
+        return Vec::new();
+    }
+
+    // Ignore changes to the database file itself to prevent loops
+    let is_db_change = event.paths.iter().any(|p| {
+        p.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| name == ".notes.db" || name.starts_with(".notes.db-"))
+    });
+
+    if is_db_change {
+        return Vec::new();
+    }
+
+    // Check if this is a note-related change (involves note files or note directories)
+    let is_note_related = event.paths.iter().any(|p| {
+        // Check if it's a note or canvas file
+        if p.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| files.is_note_file(name))
+        {
+            return true;
+        }
+
+        // Check if it's a directory that might contain notes
+        if probe.is_dir(p) {
+            // Check if it contains a note file
+            return probe.exists(&p.join(&files.note_file));
+        }
+
+        false
+    });
+
+    if !is_note_related {
+        return Vec::new();
+    }
+
+    use notify::EventKind;
+    match event.kind {
+        // A folder renamed in one step is reported as such, and a file renamed over a
+        // note file (how saves are written, see `NoteFilesystem`) is a content change;
+        // anything else rescans
+        EventKind::Modify(notify::event::ModifyKind::Name(mode)) => {
+            use notify::event::RenameMode;
+            let saved_over = |path: &Path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|name| files.is_note_file(name))
+                    && probe.exists(path)
+            };
+            match (mode, event.paths.as_slice()) {
+                (RenameMode::Both, [_, to]) | (_, [to]) if saved_over(to) => {
+                    path_to_note_path(notes_root, files, to, probe)
+                        .map(BackgroundTask::Sync)
+                        .into_iter()
+                        .collect()
+                }
+                (RenameMode::Both, [from, to]) => {
+                    match renamed_note_paths(notes_root, files, from, to, probe) {
+                        Some((from, to)) => vec![BackgroundTask::Rename { from, to }],
+                        None => vec![BackgroundTask::Rescan],
+                    }
+                }
+                _ => vec![BackgroundTask::Rescan],
+            }
+        }
+        // Handle create, modify, and delete events for specific notes
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => event
+            .paths
+            .iter()
+            .filter_map(|path| path_to_note_path(notes_root, files, path, probe))
+            .map(BackgroundTask::Sync)
+            .collect(),
+        // Ignore other event types
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::filesystem::NoteStore;
+    use crate::memory_store::MemoryNoteStore;
+    use notify::EventKind;
+    use notify::event::{CreateKind, DataChange, ModifyKind, RemoveKind, RenameMode};
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    const ROOT: &str = "/vault";
+
+    /// A vault described by its directories and files
+    #[derive(Default)]
+    struct FakeTree {
+        dirs: HashSet<PathBuf>,
+        files: HashSet<PathBuf>,
+    }
+
+    impl FakeTree {
+        fn with_notes(notes: &[&str]) -> Self {
+            let mut tree = FakeTree::default();
+            tree.dirs.insert(PathBuf::from(ROOT));
+            for note in notes {
+                let dir = Path::new(ROOT).join(note);
+                tree.files.insert(dir.join("_index.md"));
+                tree.dirs.insert(dir);
+            }
+            tree
+        }
+    }
+
+    impl FsProbe for FakeTree {
+        fn is_dir(&self, path: &Path) -> bool {
+            self.dirs.contains(path)
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            self.dirs.contains(path) || self.files.contains(path)
+        }
+    }
+
+    fn event(kind: EventKind, paths: &[&str]) -> Event {
+        paths.iter().fold(Event::new(kind), |event, path| {
+            event.add_path(Path::new(ROOT).join(path))
+        })
+    }
+
+    fn tasks(tree: &FakeTree, events: &[Event]) -> Vec<BackgroundTask> {
+        coalesce(
+            events
+                .iter()
+                .flat_map(|event| event_tasks(event, Path::new(ROOT), &NoteFiles::default(), tree)),
+        )
+    }
+
+    fn sync(path: &str) -> BackgroundTask {
+        BackgroundTask::Sync(path.to_string())
+    }
+
+    #[test]
+    fn test_note_file_events_sync_their_note() {
+        let tree = FakeTree::with_notes(&["inbox", "projects/rust"]);
+        let events = [
+            event(
+                EventKind::Create(CreateKind::File),
+                &["projects/rust/_index.md"],
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                &["inbox/_canvas.json"],
+            ),
+            event(EventKind::Modify(ModifyKind::Any), &["_index.md"]),
+        ];
+        assert_eq!(
+            tasks(&tree, &events),
+            vec![sync("projects/rust"), sync("inbox"), sync("")]
+        );
+    }
+
+    #[test]
+    fn test_burst_of_writes_syncs_once() {
+        let tree = FakeTree::with_notes(&["draft"]);
+        let write = event(
+            EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            &["draft/_index.md"],
+        );
+        let events = [
+            event(EventKind::Create(CreateKind::File), &["draft/_index.md"]),
+            write.clone(),
+            write.clone(),
+            write,
+        ];
+        assert_eq!(tasks(&tree, &events), vec![sync("draft")]);
+    }
+
+    #[test]
+    fn test_database_and_unrelated_files_are_ignored() {
+        let tree = FakeTree::with_notes(&["inbox"]);
+        let events = [
+            event(EventKind::Modify(ModifyKind::Any), &[".notes.db"]),
+            event(EventKind::Create(CreateKind::File), &[".notes.db-wal"]),
+            event(EventKind::Modify(ModifyKind::Any), &[".notes.db-shm"]),
+            event(EventKind::Create(CreateKind::File), &["inbox/photo.png"]),
+            event(EventKind::Modify(ModifyKind::Any), &[".notes.journal"]),
+        ];
+        assert!(tasks(&tree, &events).is_empty());
+    }
+
+    #[test]
+    fn test_renames_rescan_and_absorb_syncs() {
+        let tree = FakeTree::with_notes(&["new", "other"]);
+        let events = [
+            event(EventKind::Modify(ModifyKind::Any), &["other/_index.md"]),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)),
+                &["old"],
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::To)),
+                &["new"],
+            ),
+            event(EventKind::Modify(ModifyKind::Any), &["new/_index.md"]),
+        ];
+        assert_eq!(tasks(&tree, &events), vec![BackgroundTask::Rescan]);
+    }
+
+    #[test]
+    fn test_paired_folder_rename_names_both_notes() {
+        let tree = FakeTree::with_notes(&["projects/new", "other"]);
+        let events = [
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &["old", "projects/new"],
+            ),
+            event(EventKind::Modify(ModifyKind::Any), &["other/_index.md"]),
+        ];
+        assert_eq!(
+            tasks(&tree, &events),
+            vec![
+                BackgroundTask::Rename {
+                    from: "old".into(),
+                    to: "projects/new".into()
+                },
+                sync("other"),
+            ]
+        );
+
+        // Saving through a temporary file is not a note rename, just a change
+        let events = [event(
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+            &["other/_index.md.tmp", "other/_index.md"],
+        )];
+        assert_eq!(tasks(&tree, &events), vec![sync("other")]);
+
+        // Some platforms report each end of that rename on its own
+        let events = [
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Any)),
+                &["other/._index.md.tmp"],
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Any)),
+                &["other/_index.md"],
+            ),
+        ];
+        assert_eq!(tasks(&tree, &events), vec![sync("other")]);
+    }
+
+    #[test]
+    fn test_removed_note_folder_syncs_note() {
+        let mut tree = FakeTree::with_notes(&["gone"]);
+        // The folder is already gone when the event arrives; the index file still names it
+        tree.dirs.remove(&Path::new(ROOT).join("gone"));
+        let events = [event(
+            EventKind::Remove(RemoveKind::File),
+            &["gone/_index.md"],
+        )];
+        assert_eq!(tasks(&tree, &events), vec![sync("gone")]);
+    }
+
+    #[test]
+    fn test_events_ignored_while_root_missing() {
+        let mut tree = FakeTree::with_notes(&["inbox"]);
+        tree.dirs.remove(Path::new(ROOT));
+        let events = [event(
+            EventKind::Remove(RemoveKind::File),
+            &["inbox/_index.md"],
+        )];
+        assert!(tasks(&tree, &events).is_empty());
+    }
+
+    #[test]
+    fn test_large_bursts_become_one_rescan() {
+        let burst = || {
+            (0..5)
+                .map(|i| sync(&format!("note-{}", i)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(batch_tasks(burst(), 5).len(), 5);
+        assert_eq!(batch_tasks(burst(), 4), vec![BackgroundTask::Rescan]);
+    }
+
+    #[test]
+    fn test_debounce_waits_for_quiet() {
+        let clock = Arc::new(ManualClock::default());
+        let window = Arc::new(Mutex::new(Duration::from_millis(500)));
+        let (tx, rx) = mpsc::channel();
+        let (settled_tx, settled) = mpsc::channel();
+        let debounce_clock = clock.clone();
+        let debounce_window = window.clone();
+        let handle = std::thread::spawn(move || {
+            let settle = || *debounce_window.lock().unwrap();
+            debounce(rx, debounce_clock.as_ref(), settle, || {
+                settled_tx.send(()).unwrap()
+            });
+        });
+
+        // An event inside the window restarts it
+        tx.send(()).unwrap();
+        clock.wait_for_sleepers(1);
+        tx.send(()).unwrap();
+        clock.advance(Duration::from_millis(500));
+        clock.wait_for_sleepers(1);
+        assert!(settled.try_recv().is_err());
+        clock.advance(Duration::from_millis(500));
+        settled.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // A longer window applies from the next burst on
+        *window.lock().unwrap() = Duration::from_secs(5);
+        tx.send(()).unwrap();
+        clock.wait_for_sleepers(1);
+        clock.advance(Duration::from_millis(500));
+        clock.wait_for_sleepers(1);
+        assert!(settled.try_recv().is_err());
+        clock.advance(Duration::from_millis(4500));
+        settled.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        drop(tx);
+        handle.join().unwrap();
+        assert!(settled.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_watcher_config_coalesces_only_when_asked() {
+        let burst = vec![sync("inbox"), sync("inbox"), sync("drafts")];
+        assert_eq!(
+            WatcherConfig::default().settled_tasks(burst.clone()),
+            vec![sync("inbox"), sync("drafts")]
+        );
+
+        let config = WatcherConfig {
+            coalesce: false,
+            ..WatcherConfig::default()
+        };
+        assert_eq!(config.settled_tasks(burst.clone()), burst);
+
+        let config = WatcherConfig::from_timing(&TimingConfig {
+            watcher_debounce_ms: 2000,
+            rescan_threshold: 1,
+            ..TimingConfig::default()
+        });
+        assert_eq!(config.debounce, Duration::from_secs(2));
+        assert_eq!(config.settled_tasks(burst), vec![BackgroundTask::Rescan]);
+    }
+
+    #[test]
+    fn test_tasks_report_only_real_changes() {
+        let store = MemoryNoteStore::new();
+        let mut api = NotesApi::with_store(Box::new(store.clone())).unwrap();
+        api.create_note("inbox").unwrap();
+
+        // Content written outside the app is synced and reported once
+        store.write_note("inbox", "from another editor").unwrap();
+        assert!(matches!(
+            run_task(&mut api, &sync("inbox")),
+            Some(NotesEvent::NoteModified { path }) if path == "inbox"
+        ));
+        assert!(run_task(&mut api, &sync("inbox")).is_none());
+
+        store.create_note("drafts").unwrap();
+        assert!(matches!(
+            run_task(&mut api, &sync("drafts")),
+            Some(NotesEvent::NoteCreated { path }) if path == "drafts"
+        ));
+
+        store.delete_note("drafts").unwrap();
+        assert!(matches!(
+            run_task(&mut api, &sync("drafts")),
+            Some(NotesEvent::NoteDeleted { path }) if path == "drafts"
+        ));
+        assert!(!api.note_exists("drafts").unwrap());
+        assert!(run_task(&mut api, &sync("drafts")).is_none());
+
+        assert!(matches!(
+            run_task(&mut api, &BackgroundTask::Rescan),
+            Some(NotesEvent::NotesRenamed)
+        ));
+
+        // An index opened without syncing catches up in the background
+        store.create_note("later").unwrap();
+        assert!(matches!(
+            run_task(&mut api, &BackgroundTask::StartupSync),
+            Some(NotesEvent::IndexRefreshed)
+        ));
+        assert!(api.note_exists("later").unwrap());
+    }
+}
@@ -0,0 +1,364 @@
+//! A WebDAV collection (Nextcloud, ownCloud, a plain Apache or nginx share) as a
+//! `RemoteStore`.
+//!
+//! The collection is walked one level per `PROPFIND`, since many servers refuse
+//! `Depth: infinity`. Writes are conditional (`If-Match` / `If-None-Match: *`), so a
+//! file changed on the server after it was listed comes back as a conflict instead of
+//! being overwritten. Missing parent collections are created before uploading.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use percent_encoding::percent_decode_str;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{ETAG, IF_MATCH, IF_NONE_MATCH};
+use reqwest::{Method, StatusCode, Url};
+
+use crate::notes::{Error, NotesApi, Result};
+use crate::remote_sync::{RemoteConfig, RemoteEntry, RemoteStore, SyncReport, is_device_local};
+
+/// Per request, so a large upload on a slow link still has to make progress
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/><d:getetag/></d:prop></d:propfind>"#;
+
+/// One `<d:response>` from a multistatus body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DavResource {
+    href: String,
+    collection: bool,
+    etag: Option<String>,
+}
+
+fn parse_multistatus(xml: &str) -> Result<Vec<DavResource>> {
+    let doc = roxmltree::Document::parse(xml)
+        .map_err(|e| Error::Remote(format!("Unreadable server response: {}", e)))?;
+    let mut resources = Vec::new();
+    for response in doc
+        .descendants()
+        .filter(|node| node.has_tag_name(("DAV:", "response")))
+    {
+        let text = |name: &str| {
+            response
+                .descendants()
+                .find(|node| node.has_tag_name(("DAV:", name)))
+                .and_then(|node| node.text())
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+        };
+        let Some(href) = text("href") else {
+            continue;
+        };
+        resources.push(DavResource {
+            href,
+            collection: response
+                .descendants()
+                .any(|node| node.has_tag_name(("DAV:", "collection"))),
+            etag: text("getetag"),
+        });
+    }
+    Ok(resources)
+}
+
+fn request_error(e: reqwest::Error) -> Error {
+    Error::Remote(e.to_string())
+}
+
+fn status_error(method: &Method, path: &str, status: StatusCode) -> Error {
+    match status {
+        StatusCode::UNAUTHORIZED => {
+            Error::Remote("The server refused the username or password".to_string())
+        }
+        status => Error::Remote(format!("{} {} failed: {}", method, path, status)),
+    }
+}
+
+pub struct WebDavStore {
+    client: Client,
+    /// Always ends in `/`
+    base: Url,
+    username: String,
+    password: String,
+    /// Collections known to exist, so uploads only create the missing ones
+    collections: RefCell<HashSet<String>>,
+}
+
+impl WebDavStore {
+    pub fn new(config: &RemoteConfig) -> Result<Self> {
+        let mut base = Url::parse(&config.url)
+            .map_err(|e| Error::InvalidInput(format!("{}: {}", config.url, e)))?;
+        if base.cannot_be_a_base() {
+            return Err(Error::InvalidInput(config.url.clone()));
+        }
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent("Zinnia")
+            .build()
+            .map_err(request_error)?;
+        Ok(Self {
+            client,
+            base,
+            username: config.username.clone(),
+            password: config.password.clone(),
+            collections: RefCell::new(HashSet::from([String::new()])),
+        })
+    }
+
+    /// The URL of the file (or with `collection`, the collection) at `path`.
+    fn url(&self, path: &str, collection: bool) -> Url {
+        let mut url = self.base.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty();
+            segments.extend(path.split('/').filter(|segment| !segment.is_empty()));
+            if collection {
+                segments.push("");
+            }
+        }
+        url
+    }
+
+    /// The path relative to the base of an `href` from a multistatus response.
+    fn relative_path(&self, href: &str) -> Option<String> {
+        let url = self.base.join(href).ok()?;
+        let relative = url.path().strip_prefix(self.base.path())?;
+        let segments: Option<Vec<String>> = relative
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| {
+                percent_decode_str(segment)
+                    .decode_utf8()
+                    .ok()
+                    .map(|segment| segment.into_owned())
+            })
+            .collect();
+        Some(segments?.join("/"))
+    }
+
+    fn request(&self, method: Method, url: Url) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .basic_auth(&self.username, Some(&self.password))
+    }
+
+    fn send(&self, request: RequestBuilder) -> Result<Response> {
+        request.send().map_err(request_error)
+    }
+
+    fn propfind(&self, path: &str, collection: bool, depth: &str) -> Result<Vec<DavResource>> {
+        let method = Method::from_bytes(b"PROPFIND").expect("valid method");
+        let response = self.send(
+            self.request(method.clone(), self.url(path, collection))
+                .header("Depth", depth)
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .body(PROPFIND_BODY),
+        )?;
+        let status = response.status();
+        if status != StatusCode::MULTI_STATUS {
+            return Err(status_error(&method, path, status));
+        }
+        parse_multistatus(&response.text().map_err(request_error)?)
+    }
+
+    /// Creates the collections above `path` that don't exist yet.
+    fn create_parents(&self, path: &str) -> Result<()> {
+        let method = Method::from_bytes(b"MKCOL").expect("valid method");
+        let segments: Vec<&str> = path.split('/').collect();
+        for end in 1..segments.len() {
+            let parent = segments[..end].join("/");
+            if self.collections.borrow().contains(&parent) {
+                continue;
+            }
+            let status = self
+                .send(self.request(method.clone(), self.url(&parent, true)))?
+                .status();
+            // 405: it already exists
+            if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                return Err(status_error(&method, &parent, status));
+            }
+            self.collections.borrow_mut().insert(parent);
+        }
+        Ok(())
+    }
+}
+
+impl RemoteStore for WebDavStore {
+    fn list(&self) -> Result<Vec<RemoteEntry>> {
+        let mut entries = Vec::new();
+        let mut pending = vec![String::new()];
+        while let Some(dir) = pending.pop() {
+            for resource in self.propfind(&dir, true, "1")? {
+                let Some(path) = self.relative_path(&resource.href) else {
+                    continue;
+                };
+                if path == dir || is_device_local(&path) {
+                    continue;
+                }
+                if resource.collection {
+                    self.collections.borrow_mut().insert(path.clone());
+                    pending.push(path);
+                } else {
+                    let etag = resource.etag.ok_or_else(|| {
+                        Error::Remote(format!("The server gave no ETag for {}", path))
+                    })?;
+                    entries.push(RemoteEntry { path, etag });
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    fn get(&self, path: &str) -> Result<Vec<u8>> {
+        let response = self.send(self.request(Method::GET, self.url(path, false)))?;
+        if !response.status().is_success() {
+            return Err(status_error(&Method::GET, path, response.status()));
+        }
+        Ok(response.bytes().map_err(request_error)?.to_vec())
+    }
+
+    fn put(&self, path: &str, content: &[u8], expected: Option<&str>) -> Result<Option<String>> {
+        self.create_parents(path)?;
+        let request = self
+            .request(Method::PUT, self.url(path, false))
+            .body(content.to_vec());
+        let request = match expected {
+            Some(etag) => request.header(IF_MATCH, etag),
+            None => request.header(IF_NONE_MATCH, "*"),
+        };
+        let response = self.send(request)?;
+        match response.status() {
+            StatusCode::PRECONDITION_FAILED => Ok(None),
+            status if status.is_success() => {
+                let etag = response
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|etag| etag.to_str().ok())
+                    .map(str::to_string);
+                match etag {
+                    Some(etag) => Ok(Some(etag)),
+                    // Not every server returns the new ETag; ask for it
+                    None => Ok(self
+                        .propfind(path, false, "0")?
+                        .into_iter()
+                        .find_map(|resource| resource.etag)),
+                }
+            }
+            status => Err(status_error(&Method::PUT, path, status)),
+        }
+    }
+
+    fn delete(&self, path: &str, expected: &str) -> Result<bool> {
+        let response = self.send(
+            self.request(Method::DELETE, self.url(path, false))
+                .header(IF_MATCH, expected),
+        )?;
+        match response.status() {
+            StatusCode::PRECONDITION_FAILED => Ok(false),
+            // Already gone
+            StatusCode::NOT_FOUND => Ok(true),
+            status if status.is_success() => Ok(true),
+            status => Err(status_error(&Method::DELETE, path, status)),
+        }
+    }
+}
+
+impl NotesApi {
+    /// Syncs the vault with its WebDAV remote (see `configure_remote`).
+    pub fn sync_now(&mut self, progress: impl FnMut(usize, usize)) -> Result<SyncReport> {
+        let store = self.webdav_store()?;
+        self.sync_with(&store, progress)
+    }
+
+    /// The vault's WebDAV remote, for a `VaultSync` run without the API.
+    pub fn webdav_store(&self) -> Result<WebDavStore> {
+        let config = self
+            .remote_config()?
+            .ok_or_else(|| Error::InvalidInput("No sync remote is set up".to_string()))?;
+        WebDavStore::new(&config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(url: &str) -> WebDavStore {
+        WebDavStore::new(&RemoteConfig {
+            url: url.to_string(),
+            username: "me".to_string(),
+            password: "secret".to_string(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_multistatus() {
+        let xml = r#"<?xml version="1.0"?>
+            <d:multistatus xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+              <d:response>
+                <d:href>/dav/files/me/Notes/</d:href>
+                <d:propstat>
+                  <d:prop><d:resourcetype><d:collection/></d:resourcetype>
+                  <d:getetag>"dir"</d:getetag></d:prop>
+                  <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+              </d:response>
+              <d:response>
+                <d:href>/dav/files/me/Notes/Reading%20list.md</d:href>
+                <d:propstat>
+                  <d:prop><d:resourcetype/><d:getetag>"abc"</d:getetag></d:prop>
+                  <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+              </d:response>
+            </d:multistatus>"#;
+        assert_eq!(
+            parse_multistatus(xml).unwrap(),
+            [
+                DavResource {
+                    href: "/dav/files/me/Notes/".to_string(),
+                    collection: true,
+                    etag: Some("\"dir\"".to_string()),
+                },
+                DavResource {
+                    href: "/dav/files/me/Notes/Reading%20list.md".to_string(),
+                    collection: false,
+                    etag: Some("\"abc\"".to_string()),
+                },
+            ]
+        );
+        assert!(matches!(parse_multistatus("<d:"), Err(Error::Remote(_))));
+    }
+
+    #[test]
+    fn test_paths_map_to_urls_and_back() {
+        let store = store("https://cloud.example/dav/files/me/Notes");
+        let url = store.url("Reading list/café.md", false);
+        assert_eq!(
+            url.as_str(),
+            "https://cloud.example/dav/files/me/Notes/Reading%20list/caf%C3%A9.md"
+        );
+        assert_eq!(
+            store.url("Reading list", true).as_str(),
+            "https://cloud.example/dav/files/me/Notes/Reading%20list/"
+        );
+        assert_eq!(
+            store.relative_path(url.path()).as_deref(),
+            Some("Reading list/café.md")
+        );
+        assert_eq!(
+            store.relative_path(url.as_str()).as_deref(),
+            Some("Reading list/café.md")
+        );
+        assert_eq!(
+            store
+                .relative_path("/dav/files/me/Notes/Reading%20list/")
+                .as_deref(),
+            Some("Reading list")
+        );
+        assert_eq!(store.relative_path("/elsewhere/"), None);
+    }
+}
@@ -0,0 +1,681 @@
+//! Serialized, prioritized writes against a shared `NotesApi`.
+//!
+//! The UI and the filesystem watcher both mutate the same connection. When a bulk
+//! change (e.g. a `git pull`) drops hundreds of file events, running each sync as it
+//! arrives starves interactive saves. `WriteQueue` runs every mutation on a single
+//! worker thread, always draining interactive jobs before background ones, and
+//! coalesces background work: duplicate syncs of one note run once, and a pending
+//! rescan supersedes any queued syncs. Background tasks are also rate-limited, so
+//! a burst leaves the `NotesApi` lock free between tasks for reads that bypass the
+//! queue.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+use crate::NotesApi;
+use crate::clock::Clock;
+use crate::events::NotesEvent;
+use crate::notes::{Error, Result, SyncOutcome};
+
+/// An interactive job; returns the event to report, if any
+type Job = Box<dyn FnOnce(&mut NotesApi) -> Option<NotesEvent> + Send>;
+type ChangeCallback = Box<dyn Fn(NotesEvent) + Send + Sync>;
+type HeavyWorkGate = Box<dyn Fn() -> bool + Send + Sync>;
+
+/// How often deferred heavy work asks the gate again
+const HEAVY_WORK_RECHECK: Duration = Duration::from_secs(30);
+
+/// How long heavy work is held back before it runs regardless of the gate, so a
+/// rescan the index depends on isn't put off for a whole afternoon on battery
+const MAX_HEAVY_DEFERRAL: Duration = Duration::from_secs(15 * 60);
+
+/// Background tasks that may run back to back before the rate limit applies
+const BACKGROUND_BURST: u32 = 50;
+
+/// Background tasks per second once a burst is used up
+const BACKGROUND_RATE: u32 = 200;
+
+/// Background index maintenance queued by the watcher.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BackgroundTask {
+    /// Re-index a single note
+    Sync(String),
+    /// Re-index the whole vault (e.g. after a rename/move)
+    Rescan,
+    /// Follow a note folder renamed or moved on disk; see `NotesApi::sync_rename`
+    Rename { from: String, to: String },
+    /// Bring an index opened without syncing up to date; see `NotesApi::startup_sync`
+    StartupSync,
+    /// Turn files in the ingest folder into notes
+    Ingest,
+    /// Compact the index; see `NotesApi::run_maintenance`
+    Maintenance,
+    /// Commit changes if saving has paused; see `NotesApi::git_auto_commit`
+    GitCommit,
+    /// Apply edits queued while the vault was unavailable; see `NotesApi::replay_pending`
+    ReplayPending,
+}
+
+impl BackgroundTask {
+    /// Whether the task may be deferred by `WriteQueue::set_heavy_work_gate`.
+    pub fn is_heavy(&self) -> bool {
+        matches!(self, BackgroundTask::Rescan | BackgroundTask::Maintenance)
+    }
+
+    /// Whether a pending rescan makes the task redundant.
+    fn is_covered_by_rescan(&self) -> bool {
+        matches!(
+            self,
+            BackgroundTask::Sync(_) | BackgroundTask::Rename { .. }
+        )
+    }
+}
+
+/// Number of jobs waiting in each lane, for diagnostics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueDepth {
+    pub interactive: usize,
+    pub background: usize,
+}
+
+#[derive(Default)]
+struct QueueState {
+    interactive: VecDeque<Job>,
+    background: VecDeque<BackgroundTask>,
+    /// Mirrors `background` for O(1) de-duplication
+    queued: HashSet<BackgroundTask>,
+    /// When the oldest heavy task still queued was queued
+    heavy_since: Option<SystemTime>,
+    shutdown: bool,
+}
+
+impl QueueState {
+    /// Adds a background task unless a pending task already covers it.
+    fn push_background(&mut self, task: BackgroundTask) -> bool {
+        if self.queued.contains(&task)
+            || (task.is_covered_by_rescan() && self.queued.contains(&BackgroundTask::Rescan))
+        {
+            return false;
+        }
+        if task == BackgroundTask::Rescan {
+            // A rescan re-indexes every note, so pending syncs and renames are redundant
+            self.background
+                .retain(|queued| !queued.is_covered_by_rescan());
+            self.queued.retain(|queued| !queued.is_covered_by_rescan());
+        }
+        self.queued.insert(task.clone());
+        self.background.push_back(task);
+        true
+    }
+
+    /// Takes the next job to run, always preferring the interactive lane. Heavy
+    /// background tasks are skipped (left queued) unless `heavy_allowed`.
+    fn pop_next(&mut self, heavy_allowed: bool) -> Option<Next> {
+        if let Some(job) = self.interactive.pop_front() {
+            return Some(Next::Interactive(job));
+        }
+        let index = self
+            .background
+            .iter()
+            .position(|task| heavy_allowed || !task.is_heavy())?;
+        let task = self.background.remove(index)?;
+        self.queued.remove(&task);
+        Some(Next::Background(task))
+    }
+}
+
+/// Token bucket pacing background tasks: `burst` may run at once, then `per_sec`.
+struct RateLimit {
+    burst: f64,
+    per_sec: f64,
+    tokens: f64,
+    refilled: SystemTime,
+}
+
+impl RateLimit {
+    fn new(burst: u32, per_sec: u32, now: SystemTime) -> Self {
+        Self {
+            burst: burst as f64,
+            per_sec: per_sec as f64,
+            tokens: burst as f64,
+            refilled: now,
+        }
+    }
+
+    fn refill(&mut self, now: SystemTime) {
+        let elapsed = now
+            .duration_since(self.refilled)
+            .unwrap_or_default()
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.burst);
+        self.refilled = now;
+    }
+
+    /// How long until a task may run; zero if one may run now.
+    fn wait_time(&mut self, now: SystemTime) -> Duration {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.per_sec)
+        }
+    }
+
+    /// Uses up one task's worth of budget.
+    fn take(&mut self, now: SystemTime) {
+        self.refill(now);
+        self.tokens = (self.tokens - 1.0).max(0.0);
+    }
+}
+
+/// Reduces a burst of background tasks to what the queue would actually run:
+/// duplicates are dropped, and a rescan replaces every sync.
+pub fn coalesce(tasks: impl IntoIterator<Item = BackgroundTask>) -> Vec<BackgroundTask> {
+    let mut state = QueueState::default();
+    for task in tasks {
+        state.push_background(task);
+    }
+    state.background.into()
+}
+
+/// Runs one background task, returning the event to report if the index changed.
+pub(crate) fn run_task(api: &mut NotesApi, task: &BackgroundTask) -> Option<NotesEvent> {
+    match task {
+        BackgroundTask::Sync(path) => match api.sync_change(path) {
+            Ok(SyncOutcome::Created) => Some(NotesEvent::NoteCreated { path: path.clone() }),
+            Ok(SyncOutcome::Modified) => Some(NotesEvent::NoteModified { path: path.clone() }),
+            Ok(SyncOutcome::Deleted) => Some(NotesEvent::NoteDeleted { path: path.clone() }),
+            Ok(SyncOutcome::Unchanged) => None,
+            Err(e) => {
+                eprintln!("Failed to sync note {}: {:?}", path, e);
+                None
+            }
+        },
+        BackgroundTask::Rescan => match api.rescan() {
+            Ok(()) => Some(NotesEvent::NotesRenamed),
+            Err(e) => {
+                eprintln!("Failed to rescan after rename: {:?}", e);
+                None
+            }
+        },
+        BackgroundTask::Rename { from, to } => match api.sync_rename(from, to) {
+            Ok(()) => Some(NotesEvent::NoteRenamed {
+                from: from.clone(),
+                to: to.clone(),
+            }),
+            Err(e) => {
+                eprintln!("Failed to sync rename of {}: {:?}", from, e);
+                None
+            }
+        },
+        BackgroundTask::StartupSync => match api.startup_sync() {
+            Ok(()) => Some(NotesEvent::IndexRefreshed),
+            Err(e) => {
+                eprintln!("Failed to sync notes database: {:?}", e);
+                None
+            }
+        },
+        BackgroundTask::Ingest => match api.ingest_pending() {
+            Ok(created) if !created.is_empty() => Some(NotesEvent::NotesChanged),
+            Ok(_) => None,
+            Err(e) => {
+                eprintln!("Failed to ingest files: {:?}", e);
+                None
+            }
+        },
+        BackgroundTask::Maintenance => {
+            if let Err(e) = api.run_maintenance() {
+                eprintln!("Index maintenance failed: {:?}", e);
+            }
+            None
+        }
+        BackgroundTask::ReplayPending => match api.replay_pending() {
+            Ok(0) => None,
+            Ok(_) => Some(NotesEvent::NotesChanged),
+            Err(e) => {
+                eprintln!("Failed to replay offline edits: {:?}", e);
+                None
+            }
+        },
+        BackgroundTask::GitCommit => {
+            if let Err(e) = api.git_auto_commit() {
+                eprintln!("Auto-commit failed: {:?}", e);
+            }
+            None
+        }
+    }
+}
+
+struct Shared {
+    state: Mutex<QueueState>,
+    wake: Condvar,
+    heavy_work_gate: Mutex<Option<HeavyWorkGate>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Shared {
+    fn heavy_work_allowed(&self) -> bool {
+        self.heavy_work_gate
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_none_or(|gate| gate())
+    }
+}
+
+pub struct WriteQueue {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl WriteQueue {
+    /// Starts the worker thread for `notes_api`.
+    ///
+    /// `on_change` is called after background tasks that changed the index, with the
+    /// same events `setup_watcher` emits, and with `NotesEvent::NoteConflicted` when
+    /// a `run` job fails with `Error::Conflict`. `clock` paces background work and
+    /// times how long heavy work has been held back.
+    pub fn new<F>(
+        notes_api: Arc<Mutex<NotesApi>>,
+        on_change: Option<F>,
+        clock: Arc<dyn Clock>,
+    ) -> Self
+    where
+        F: Fn(NotesEvent) + Send + Sync + 'static,
+    {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(QueueState::default()),
+            wake: Condvar::new(),
+            heavy_work_gate: Mutex::new(None),
+            clock,
+        });
+
+        let worker_shared = Arc::clone(&shared);
+        let on_change: Option<ChangeCallback> = on_change.map(|f| Box::new(f) as ChangeCallback);
+        let worker = std::thread::spawn(move || run_worker(worker_shared, notes_api, on_change));
+
+        Self {
+            shared,
+            worker: Some(worker),
+        }
+    }
+
+    /// Runs `job` on the worker ahead of any background work and waits for its result.
+    pub fn run<T, F>(&self, job: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut NotesApi) -> Result<T> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            state.interactive.push_back(Box::new(move |api| {
+                let result = job(api);
+                let event = match &result {
+                    Err(Error::Conflict { path, .. }) => {
+                        Some(NotesEvent::NoteConflicted { path: path.clone() })
+                    }
+                    _ => None,
+                };
+                let _ = tx.send(result);
+                event
+            }));
+        }
+        self.shared.wake.notify_one();
+
+        rx.recv().unwrap_or_else(|_| {
+            Err(crate::Error::Io(std::io::Error::other(
+                "Write queue worker stopped",
+            )))
+        })
+    }
+
+    /// Queues background index maintenance. Returns immediately.
+    pub fn enqueue(&self, task: BackgroundTask) {
+        let heavy = task.is_heavy();
+        let mut state = self.shared.state.lock().unwrap();
+        let added = state.push_background(task);
+        if added && heavy {
+            state
+                .heavy_since
+                .get_or_insert_with(|| self.shared.clock.now());
+        }
+        drop(state);
+        if added {
+            self.shared.wake.notify_one();
+        }
+    }
+
+    /// Lets the app hold back heavy background tasks (see `BackgroundTask::is_heavy`),
+    /// e.g. while on battery. `gate` is asked before each heavy task and again every
+    /// 30 seconds while one is held back; it should be cheap. Work queued for longer
+    /// than 15 minutes runs anyway.
+    pub fn set_heavy_work_gate<F>(&self, gate: F)
+    where
+        F: Fn() -> bool + Send + Sync + 'static,
+    {
+        *self.shared.heavy_work_gate.lock().unwrap() = Some(Box::new(gate));
+        self.shared.wake.notify_one();
+    }
+
+    /// Returns how many jobs are waiting in each lane.
+    pub fn depth(&self) -> QueueDepth {
+        let state = self.shared.state.lock().unwrap();
+        QueueDepth {
+            interactive: state.interactive.len(),
+            background: state.background.len(),
+        }
+    }
+}
+
+impl Drop for WriteQueue {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.wake.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+enum Next {
+    Interactive(Job),
+    Background(BackgroundTask),
+}
+
+fn run_worker(
+    shared: Arc<Shared>,
+    notes_api: Arc<Mutex<NotesApi>>,
+    on_change: Option<ChangeCallback>,
+) {
+    let clock = Arc::clone(&shared.clock);
+    let mut rate_limit = RateLimit::new(BACKGROUND_BURST, BACKGROUND_RATE, clock.now());
+    loop {
+        let now = clock.now();
+        // A clock set backwards counts as just queued
+        let held_for = shared
+            .state
+            .lock()
+            .unwrap()
+            .heavy_since
+            .map(|since| now.duration_since(since).unwrap_or_default());
+        let overdue = held_for.is_some_and(|held| held >= MAX_HEAVY_DEFERRAL);
+        let heavy_allowed = overdue || shared.heavy_work_allowed();
+        let throttled = rate_limit.wait_time(now);
+        let next = {
+            let mut state = shared.state.lock().unwrap();
+            let next = if throttled.is_zero() {
+                state.pop_next(heavy_allowed)
+            } else {
+                state.interactive.pop_front().map(Next::Interactive)
+            };
+            if let Some(next) = next {
+                if let Next::Background(task) = &next
+                    && task.is_heavy()
+                {
+                    // The next heavy task's wait starts now
+                    state.heavy_since = state
+                        .background
+                        .iter()
+                        .any(BackgroundTask::is_heavy)
+                        .then_some(now);
+                }
+                next
+            } else if state.shutdown {
+                return;
+            } else {
+                // Every wakeup starts over, so the gate is asked again; it may have
+                // changed while the queue was idle
+                if state.background.is_empty() {
+                    drop(shared.wake.wait(state).unwrap());
+                } else if !throttled.is_zero() {
+                    // Out of background budget; interactive jobs still wake the worker
+                    drop(shared.wake.wait_timeout(state, throttled).unwrap());
+                } else {
+                    // Only deferred heavy work is left; ask the gate again later
+                    let remaining = MAX_HEAVY_DEFERRAL.saturating_sub(held_for.unwrap_or_default());
+                    drop(
+                        shared
+                            .wake
+                            .wait_timeout(state, HEAVY_WORK_RECHECK.min(remaining))
+                            .unwrap(),
+                    );
+                }
+                continue;
+            }
+        };
+        if matches!(next, Next::Background(_)) {
+            rate_limit.take(now);
+        }
+
+        let Ok(mut api) = notes_api.lock() else {
+            return;
+        };
+        let event = match next {
+            Next::Interactive(job) => job(&mut api),
+            Next::Background(task) => run_task(&mut api, &task),
+        };
+        if let Some(event) = event
+            && let Some(ref callback) = on_change
+        {
+            callback(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{ManualClock, SystemClock};
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, Arc<Mutex<NotesApi>>) {
+        let temp_dir = TempDir::new().unwrap();
+        let api = NotesApi::new(temp_dir.path()).unwrap();
+        (temp_dir, Arc::new(Mutex::new(api)))
+    }
+
+    #[test]
+    fn test_run_returns_result() {
+        let (_temp_dir, api) = setup();
+        let queue = WriteQueue::new(
+            Arc::clone(&api),
+            None::<fn(NotesEvent)>,
+            Arc::new(SystemClock),
+        );
+
+        let note = queue.run(|api| api.create_note("inbox")).unwrap();
+        assert_eq!(note.path, "inbox");
+        queue.run(|api| api.save_note("inbox", "hello")).unwrap();
+        assert_eq!(
+            api.lock().unwrap().get_note("inbox").unwrap().content,
+            "hello"
+        );
+    }
+
+    #[test]
+    fn test_background_tasks_are_coalesced() {
+        let mut state = QueueState::default();
+        assert!(state.push_background(BackgroundTask::Sync("a".into())));
+        assert!(!state.push_background(BackgroundTask::Sync("a".into())));
+        assert!(state.push_background(BackgroundTask::Sync("b".into())));
+        assert_eq!(state.background.len(), 2);
+
+        // A rescan replaces pending syncs and absorbs new ones
+        assert!(state.push_background(BackgroundTask::Rescan));
+        assert!(!state.push_background(BackgroundTask::Sync("c".into())));
+        assert_eq!(
+            state.background.iter().collect::<Vec<_>>(),
+            vec![&BackgroundTask::Rescan]
+        );
+
+        // Ingesting isn't covered by a rescan
+        assert!(state.push_background(BackgroundTask::Ingest));
+        assert!(!state.push_background(BackgroundTask::Ingest));
+        assert_eq!(state.background.len(), 2);
+    }
+
+    #[test]
+    fn test_interactive_jobs_run_before_background() {
+        let mut state = QueueState::default();
+        state.push_background(BackgroundTask::Sync("a".into()));
+        state.interactive.push_back(Box::new(|_| None));
+
+        assert!(matches!(state.pop_next(true), Some(Next::Interactive(_))));
+        assert!(matches!(
+            state.pop_next(true),
+            Some(Next::Background(BackgroundTask::Sync(_)))
+        ));
+        assert!(state.pop_next(true).is_none());
+        assert!(state.queued.is_empty());
+    }
+
+    #[test]
+    fn test_rate_limit_paces_bursts() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut limit = RateLimit::new(3, 10, start);
+        for _ in 0..3 {
+            assert_eq!(limit.wait_time(start), Duration::ZERO);
+            limit.take(start);
+        }
+        assert_eq!(limit.wait_time(start), Duration::from_millis(100));
+
+        // Budget comes back at the sustained rate, up to the burst size
+        assert_eq!(
+            limit.wait_time(start + Duration::from_millis(100)),
+            Duration::ZERO
+        );
+        limit.take(start + Duration::from_millis(100));
+        assert!(limit.wait_time(start + Duration::from_millis(150)) > Duration::ZERO);
+        let later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert_eq!(limit.wait_time(later), Duration::ZERO);
+            limit.take(later);
+        }
+        assert!(limit.wait_time(later) > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_bursts_of_syncs_all_run() {
+        let (temp_dir, api) = setup();
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let queue = WriteQueue::new(
+            Arc::clone(&api),
+            Some(move |event: NotesEvent| {
+                let _ = tx.lock().unwrap().send(event);
+            }),
+            Arc::new(SystemClock),
+        );
+
+        let count = BACKGROUND_BURST as usize + 20;
+        for i in 0..count {
+            let dir = temp_dir.path().join(format!("pulled{}", i));
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("_index.md"), "from a pull").unwrap();
+            queue.enqueue(BackgroundTask::Sync(format!("pulled{}", i)));
+        }
+        // A save still goes through while the burst is being paced
+        queue.run(|api| api.create_note("mine")).unwrap();
+
+        for _ in 0..count {
+            let event = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert!(matches!(event, NotesEvent::NoteCreated { .. }));
+        }
+        assert_eq!(queue.depth().background, 0);
+    }
+
+    #[test]
+    fn test_heavy_tasks_wait_for_gate() {
+        let mut state = QueueState::default();
+        state.push_background(BackgroundTask::Rescan);
+        state.push_background(BackgroundTask::Ingest);
+
+        assert!(matches!(
+            state.pop_next(false),
+            Some(Next::Background(BackgroundTask::Ingest))
+        ));
+        assert!(state.pop_next(false).is_none());
+        assert!(matches!(
+            state.pop_next(true),
+            Some(Next::Background(BackgroundTask::Rescan))
+        ));
+    }
+
+    #[test]
+    fn test_heavy_work_runs_once_held_back_too_long() {
+        let (_temp_dir, api) = setup();
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let clock = Arc::new(ManualClock::default());
+        let queue = WriteQueue::new(
+            Arc::clone(&api),
+            Some(move |event: NotesEvent| {
+                let _ = tx.lock().unwrap().send(event);
+            }),
+            clock.clone(),
+        );
+        queue.set_heavy_work_gate(|| false);
+        queue.enqueue(BackgroundTask::Rescan);
+
+        // Every job wakes the worker, which then looks at the clock again
+        clock.advance(MAX_HEAVY_DEFERRAL - Duration::from_secs(1));
+        queue.run(|_| Ok(())).unwrap();
+        assert_eq!(queue.depth().background, 1);
+        assert!(rx.try_recv().is_err());
+
+        clock.advance(Duration::from_secs(1));
+        queue.run(|_| Ok(())).unwrap();
+        let event = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(event, NotesEvent::NotesRenamed));
+        assert_eq!(queue.depth().background, 0);
+    }
+
+    #[test]
+    fn test_background_sync_reports_changes() {
+        let (temp_dir, api) = setup();
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let queue = WriteQueue::new(
+            Arc::clone(&api),
+            Some(move |event: NotesEvent| {
+                let _ = tx.lock().unwrap().send(event);
+            }),
+            Arc::new(SystemClock),
+        );
+
+        std::fs::create_dir_all(temp_dir.path().join("external")).unwrap();
+        std::fs::write(temp_dir.path().join("external/_index.md"), "from disk").unwrap();
+        queue.enqueue(BackgroundTask::Sync("external".into()));
+
+        let event = rx.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+        assert!(matches!(event, NotesEvent::NoteCreated { path } if path == "external"));
+        assert!(api.lock().unwrap().note_exists("external").unwrap());
+    }
+
+    #[test]
+    fn test_conflicting_save_reports_conflict() {
+        let (_temp_dir, api) = setup();
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let queue = WriteQueue::new(
+            Arc::clone(&api),
+            Some(move |event: NotesEvent| {
+                let _ = tx.lock().unwrap().send(event);
+            }),
+            Arc::new(SystemClock),
+        );
+
+        let result = queue.run(|api| {
+            api.create_note("a")?;
+            api.save_note_if_unchanged("a", "mine", std::time::UNIX_EPOCH)
+        });
+        assert!(matches!(result, Err(Error::Conflict { .. })));
+
+        let event = rx.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+        assert!(matches!(event, NotesEvent::NoteConflicted { path } if path == "a"));
+    }
+}
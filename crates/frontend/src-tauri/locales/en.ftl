@@ -0,0 +1,19 @@
+# Strings produced on the Rust side of the app.
+# Placeables use Fluent syntax: { $name }
+
+error-io = Could not read or write a file: { $message }
+error-database = The notes index reported an error: { $message }
+error-database-corrupted = The notes index is damaged. Restart the app to rebuild it.
+error-not-found = Note not found: { $path }
+error-already-exists = A note already exists at { $path }
+error-parent-not-found = The parent note { $path } does not exist
+error-invalid-input = { $message }
+error-read-only = This vault is open read-only
+error-permission-denied = Permission denied: { $message }
+error-vault-unavailable = The notes folder is unavailable
+error-conflict = { $path } was changed elsewhere since it was opened
+error-encrypted = { $path } is encrypted. Unlock the vault to open it.
+error-git = Git: { $message }
+error-remote = Sync: { $message }
+
+reminder-title = Reminder
@@ -0,0 +1,19 @@
+# Strings produced on the Rust side of the app.
+# Placeables use Fluent syntax: { $name }
+
+error-io = Kon een bestand niet lezen of schrijven: { $message }
+error-database = De notitie-index gaf een fout: { $message }
+error-database-corrupted = De notitie-index is beschadigd. Herstart de app om hem opnieuw op te bouwen.
+error-not-found = Notitie niet gevonden: { $path }
+error-already-exists = Er bestaat al een notitie op { $path }
+error-parent-not-found = De bovenliggende notitie { $path } bestaat niet
+error-invalid-input = { $message }
+error-read-only = Deze kluis is alleen-lezen geopend
+error-permission-denied = Toegang geweigerd: { $message }
+error-vault-unavailable = De notitiemap is niet beschikbaar
+error-conflict = { $path } is elders gewijzigd sinds het werd geopend
+error-encrypted = { $path } is versleuteld. Ontgrendel de kluis om het te openen.
+error-git = Git: { $message }
+error-remote = Synchronisatie: { $message }
+
+reminder-title = Herinnering
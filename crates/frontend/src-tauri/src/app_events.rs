@@ -0,0 +1,19 @@
+//! Names of the events only the app emits.
+//!
+//! Changes to the vault itself go out as `zinnia_core::NotesEvent` under its
+//! `name()`. The events here belong to the app, not the core: each carries a payload
+//! built from a DTO in this crate (a tree diff, a reminder, sync progress), so they
+//! stay out of `NotesEvent`. The webview listens for the same strings.
+
+/// A note was moved by a command; payload `NoteMovedDTO`
+pub const MOVED: &str = "notes:moved";
+/// The favorites list changed; no payload
+pub const FAVORITES: &str = "notes:favorites";
+/// The note tree changed; payload `TreeDiffDTO`
+pub const TREE: &str = "notes:tree";
+/// A reminder fired; payload `ReminderFiredPayload`
+pub const REMINDER: &str = "notes:reminder";
+/// Schema warnings for the note just saved; payload `NoteSchemaWarningsDTO`
+pub const SCHEMA_WARNINGS: &str = "notes:schema-warnings";
+/// A sync made progress; payload `SyncProgressDTO`
+pub const SYNC_PROGRESS: &str = "notes:sync-progress";
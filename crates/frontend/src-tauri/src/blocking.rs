@@ -0,0 +1,63 @@
+//! Runs vault work on the blocking thread pool, for async commands.
+//!
+//! Synchronous commands run on the main thread, so one that waits for the vault
+//! lock (held during a large rescan) or for a slow disk freezes every other command
+//! and the window with it. The async commands hand that work to these helpers and
+//! await the result instead.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use zinnia_core::{NotesApi, WriteQueue};
+
+use crate::i18n;
+
+/// Locks the vault and runs `read` on the blocking pool.
+pub async fn read<T, F>(api: &Arc<Mutex<NotesApi>>, read: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut NotesApi) -> zinnia_core::Result<T> + Send + 'static,
+{
+    let api = Arc::clone(api);
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut api = api.lock().unwrap();
+        read(&mut api).map_err(i18n::error_message)
+    })
+    .await
+    .map_err(|e| format!("{:?}", e))?
+}
+
+/// The newest save applied to each note, by the sequence number the frontend gave it.
+///
+/// Each async command waits for the queue on its own task, so two saves of a note can
+/// reach the queue in the opposite order to the one they were sent in. The frontend
+/// numbers its saves, and one older than a save already applied is dropped.
+#[derive(Default)]
+pub struct SaveOrder(Mutex<HashMap<String, u64>>);
+
+impl SaveOrder {
+    /// Records save `seq` of `path` unless a newer one was applied; returns whether
+    /// the save should go ahead. Called from the queued job, so checks don't race.
+    pub fn admit(&self, path: &str, seq: u64) -> bool {
+        let mut applied = self.0.lock().unwrap();
+        match applied.get(path) {
+            Some(&last) if last > seq => false,
+            _ => {
+                applied.insert(path.to_string(), seq);
+                true
+            }
+        }
+    }
+}
+
+/// Runs `job` through the write queue, waiting for it on the blocking pool.
+pub async fn write<T, F>(queue: &Arc<WriteQueue>, job: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut NotesApi) -> zinnia_core::Result<T> + Send + 'static,
+{
+    let queue = Arc::clone(queue);
+    tauri::async_runtime::spawn_blocking(move || queue.run(job).map_err(i18n::error_message))
+        .await
+        .map_err(|e| format!("{:?}", e))?
+}
@@ -0,0 +1,145 @@
+//! Localized strings for text produced on the Rust side (command errors, notifications).
+//!
+//! Translations live in `locales/<lang>.ftl` and are embedded at build time. Only the
+//! subset of Fluent we need is supported: `key = value` messages with `{ $name }`
+//! placeables. Missing keys fall back to English, then to the key itself.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use zinnia_core::Error;
+
+const FALLBACK_LOCALE: &str = "en";
+
+/// Embedded translation files, by language tag
+const LOCALES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.ftl")),
+    ("nl", include_str!("../locales/nl.ftl")),
+];
+
+static LOCALIZER: OnceLock<Localizer> = OnceLock::new();
+
+pub struct Localizer {
+    locale: String,
+    messages: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Localizer {
+    /// Picks the best available translation for `requested` (e.g. `nl-BE` → `nl`).
+    pub fn new(requested: &str) -> Self {
+        let locale = resolve_locale(requested);
+        Self {
+            messages: parse_ftl(source_for(&locale)),
+            fallback: parse_ftl(source_for(FALLBACK_LOCALE)),
+            locale,
+        }
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Looks up `key` and substitutes `{ $name }` placeables from `args`.
+    pub fn format(&self, key: &str, args: &[(&str, String)]) -> String {
+        let Some(template) = self.messages.get(key).or_else(|| self.fallback.get(key)) else {
+            return key.to_string();
+        };
+
+        let mut message = template.clone();
+        for (name, value) in args {
+            message = message.replace(&format!("{{ ${} }}", name), value);
+        }
+        message
+    }
+}
+
+/// Sets the UI locale: the vault config override if given, otherwise the OS locale.
+///
+/// Only the first call has an effect, so a changed config applies on the next launch.
+pub fn init(config_locale: Option<&str>) {
+    let requested = config_locale
+        .map(|locale| locale.to_string())
+        .or_else(sys_locale::get_locale)
+        .unwrap_or_else(|| FALLBACK_LOCALE.to_string());
+    let _ = LOCALIZER.set(Localizer::new(&requested));
+}
+
+fn localizer() -> &'static Localizer {
+    LOCALIZER.get_or_init(|| Localizer::new(FALLBACK_LOCALE))
+}
+
+/// The locale strings are being shown in.
+pub fn current_locale() -> &'static str {
+    localizer().locale()
+}
+
+/// Translates `key` in the current locale.
+pub fn tr(key: &str, args: &[(&str, String)]) -> String {
+    localizer().format(key, args)
+}
+
+/// User-facing message for an error returned to the webview.
+pub fn error_message(error: Error) -> String {
+    tr(error.message_key(), &error.message_args())
+}
+
+fn source_for(locale: &str) -> &'static str {
+    LOCALES
+        .iter()
+        .find(|(tag, _)| *tag == locale)
+        .map(|(_, source)| *source)
+        .unwrap_or("")
+}
+
+fn resolve_locale(requested: &str) -> String {
+    let requested = requested.replace('_', "-").to_lowercase();
+    let language = requested.split('-').next().unwrap_or_default();
+    LOCALES
+        .iter()
+        .map(|(tag, _)| *tag)
+        .find(|tag| *tag == requested || *tag == language)
+        .unwrap_or(FALLBACK_LOCALE)
+        .to_string()
+}
+
+fn parse_ftl(source: &str) -> HashMap<String, String> {
+    source
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_locale() {
+        assert_eq!(resolve_locale("nl-BE"), "nl");
+        assert_eq!(resolve_locale("nl_NL"), "nl");
+        assert_eq!(resolve_locale("fr-FR"), "en");
+    }
+
+    #[test]
+    fn test_format_with_args_and_fallback() {
+        let localizer = Localizer::new("nl");
+        let message = localizer.format("error-not-found", &[("path", "inbox/a".to_string())]);
+        assert_eq!(message, "Notitie niet gevonden: inbox/a");
+        assert_eq!(localizer.format("missing-key", &[]), "missing-key");
+    }
+
+    /// `Error::MESSAGE_KEYS` lists the key of every variant; core's tests keep it complete.
+    #[test]
+    fn test_every_error_key_is_translated() {
+        for (tag, source) in LOCALES {
+            let messages = parse_ftl(source);
+            for key in Error::MESSAGE_KEYS {
+                assert!(messages.contains_key(*key), "{} is missing {}", tag, key);
+            }
+        }
+    }
+}
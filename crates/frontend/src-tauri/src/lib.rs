@@ -1,203 +1,3284 @@
+mod app_events;
+mod blocking;
+mod i18n;
+mod perf;
+mod power;
+mod reminders;
+mod tree;
+pub mod versioning;
+
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use tauri::{Emitter, Manager, State};
-use zinnia_core::{Note, NoteMetadata, NotesApi, RankingMode, WatcherEvent, setup_watcher};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tree::TreeEvents;
+use versioning::{MergeDTO, SaveIfUnchangedDTO, SaveOutcomeDTO, VersionedNoteDTO};
+use zinnia_core::{
+    Access, AccessToken, BackgroundTask, Board, BoardColumn, BulkPropertyEdit, Canvas, Checkpoint,
+    CompileOptions, CsvImportMode, CsvSource, DailyNote, Date, DiffKind, DiffLine, EditSummary,
+    FormatAction, GitCommit, GitStatus, HealthReport, HistoryKind, LinkCheckOptions, LinkProblem,
+    LinkReport, LinkSuggestion, MocSource, MocUpdate, MoveCollision, Note, NoteFiles, NoteLink,
+    NoteMetadata, NoteMoved, NoteOp, NotePreview, NoteSelection, NotesApi, NotesEvent, PendingOp,
+    PropertyValue, PullOutcome, RankingMode, Reminder, RemoteConfig, ReplaceEdit, SchemaIssue,
+    SchemaWarning, SearchHistoryEntry, SearchMatch, SearchResult, StorageReport, SyncAction,
+    SyncReport, SyncSide, TagNode, TagRename, TemplateNote, TemplateSchema, TimingConfig,
+    TrashedNote, UnlinkedMention, VaultImportOptions, VaultLayout, ViewData, ViewLayout,
+    WriteQueue, convert_html_to_markdown, get_default_notes_path, get_offline_queue_dir,
+    get_sync_state_dir, install_crash_reporter, parse_note_url, setup_ingest_watcher,
+    setup_queued_watcher, spawn_availability_monitor, spawn_git_scheduler,
+    spawn_maintenance_scheduler, wiki_link,
+};
+
+// Application state holding the NotesApi instance
+pub struct AppState {
+    notes_api: Arc<Mutex<NotesApi>>,
+    safe_mode: bool,
+    /// Watcher timing shared with the running watcher, so settings apply without a restart
+    timing: Arc<Mutex<TimingConfig>>,
+    /// Note file name and layout shared with the running watcher
+    note_files: Arc<Mutex<NoteFiles>>,
+}
+
+// Serializable versions of the core types for Tauri/JSON
+#[derive(Serialize, Deserialize)]
+pub struct NoteDTO {
+    id: i64,
+    path: String,
+    content: String,
+    modified: u64,       // Unix timestamp
+    modified_nanos: u32, // Sub-second part, for save_note_if_unchanged
+    revision: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NoteMetadataDTO {
+    id: i64,
+    path: String,
+    modified: u64, // Unix timestamp
+    archived: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<NoteStatsDTO>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NoteStatsDTO {
+    preview: String,
+    word_count: i64,
+    /// Image path relative to the note's folder; fetch it with `get_note_cover`
+    cover: Option<String>,
+    private: bool,
+    /// The `title` property, or the last path segment
+    title: String,
+    /// The `icon` property, usually an emoji
+    icon: Option<String>,
+    /// Stored encrypted; the other stats are empty
+    encrypted: bool,
+    tags: Vec<String>,
+    child_count: usize,
+    /// "todo", "doing", or "done"
+    status: Option<&'static str>,
+    pinned: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CanvasDTO {
+    path: String,
+    data: serde_json::Value,
+    modified: u64, // Unix timestamp
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BoardColumnDTO {
+    name: String,
+    cards: Vec<NoteMetadataDTO>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BoardDTO {
+    path: String,
+    columns: Vec<BoardColumnDTO>,
+    uncategorized: Vec<NoteMetadataDTO>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PropertyValueDTO {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl From<PropertyValue> for PropertyValueDTO {
+    fn from(value: PropertyValue) -> Self {
+        match value {
+            PropertyValue::Text(text) => PropertyValueDTO::Text(text),
+            PropertyValue::List(items) => PropertyValueDTO::List(items),
+        }
+    }
+}
+
+impl From<PropertyValueDTO> for PropertyValue {
+    fn from(dto: PropertyValueDTO) -> Self {
+        match dto {
+            PropertyValueDTO::Text(text) => PropertyValue::Text(text),
+            PropertyValueDTO::List(items) => PropertyValue::List(items),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NoteSelectionDTO {
+    Paths { paths: Vec<String> },
+    ChildrenOf { path: String },
+    Search { query: String },
+}
+
+impl From<NoteSelectionDTO> for NoteSelection {
+    fn from(dto: NoteSelectionDTO) -> Self {
+        match dto {
+            NoteSelectionDTO::Paths { paths } => NoteSelection::Paths(paths),
+            NoteSelectionDTO::ChildrenOf { path } => NoteSelection::ChildrenOf(path),
+            NoteSelectionDTO::Search { query } => NoteSelection::Search(query),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NoteOpDTO {
+    Create { path: String },
+    Save { path: String, content: String },
+    Rename { from: String, to: String },
+    Delete { path: String },
+    Archive { path: String },
+}
+
+impl From<NoteOpDTO> for NoteOp {
+    fn from(dto: NoteOpDTO) -> Self {
+        match dto {
+            NoteOpDTO::Create { path } => NoteOp::Create { path },
+            NoteOpDTO::Save { path, content } => NoteOp::Save { path, content },
+            NoteOpDTO::Rename { from, to } => NoteOp::Rename { from, to },
+            NoteOpDTO::Delete { path } => NoteOp::Delete { path },
+            NoteOpDTO::Archive { path } => NoteOp::Archive { path },
+        }
+    }
+}
+
+/// Payload of `notes:moved`, emitted after `move_note`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NoteMovedDTO {
+    from: String,
+    to: String,
+    revision: i64,
+}
+
+impl From<NoteMoved> for NoteMovedDTO {
+    fn from(moved: NoteMoved) -> Self {
+        NoteMovedDTO {
+            from: moved.from,
+            to: moved.to,
+            revision: moved.revision,
+        }
+    }
+}
+
+/// One note's value of the edited property before and after a bulk edit
+#[derive(Serialize, Deserialize)]
+pub struct PropertyChangeDTO {
+    path: String,
+    before: Option<PropertyValueDTO>,
+    after: Option<PropertyValueDTO>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TagNodeDTO {
+    name: String,
+    tag: String,
+    count: usize,
+    direct_count: usize,
+    children: Vec<TagNodeDTO>,
+}
+
+impl From<TagNode> for TagNodeDTO {
+    fn from(node: TagNode) -> Self {
+        TagNodeDTO {
+            name: node.name,
+            tag: node.tag,
+            count: node.count,
+            direct_count: node.direct_count,
+            children: node.children.into_iter().map(TagNodeDTO::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TagRenameDTO {
+    notes: Vec<String>,
+    /// Null when no note used the tag
+    revision: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MocUpdateDTO {
+    path: String,
+    links: usize,
+    /// Null when the map was already up to date
+    revision: Option<i64>,
+}
+
+impl From<MocUpdate> for MocUpdateDTO {
+    fn from(update: MocUpdate) -> Self {
+        MocUpdateDTO {
+            path: update.path,
+            links: update.links,
+            revision: update.revision,
+        }
+    }
+}
+
+impl From<TagRename> for TagRenameDTO {
+    fn from(rename: TagRename) -> Self {
+        TagRenameDTO {
+            notes: rename.notes,
+            revision: rename.revision,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BulkPropertyEditDTO {
+    changes: Vec<PropertyChangeDTO>,
+    /// Null for a dry run or when nothing changed
+    revision: Option<i64>,
+}
+
+impl From<BulkPropertyEdit> for BulkPropertyEditDTO {
+    fn from(edit: BulkPropertyEdit) -> Self {
+        BulkPropertyEditDTO {
+            changes: edit
+                .changes
+                .into_iter()
+                .map(|change| PropertyChangeDTO {
+                    path: change.path,
+                    before: change.before.map(PropertyValueDTO::from),
+                    after: change.after.map(PropertyValueDTO::from),
+                })
+                .collect(),
+            revision: edit.revision,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReplaceChangeDTO {
+    path: String,
+    matches: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReplaceEditDTO {
+    changes: Vec<ReplaceChangeDTO>,
+    /// Null for a dry run or when nothing matched
+    revision: Option<i64>,
+}
+
+impl From<ReplaceEdit> for ReplaceEditDTO {
+    fn from(edit: ReplaceEdit) -> Self {
+        ReplaceEditDTO {
+            changes: edit
+                .changes
+                .into_iter()
+                .map(|change| ReplaceChangeDTO {
+                    path: change.path,
+                    matches: change.matches,
+                })
+                .collect(),
+            revision: edit.revision,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SearchHistoryEntryDTO {
+    id: i64,
+    /// "search" or "replace"
+    kind: String,
+    query: String,
+    replacement: Option<String>,
+    used_at: u64, // Unix timestamp
+}
+
+impl From<SearchHistoryEntry> for SearchHistoryEntryDTO {
+    fn from(entry: SearchHistoryEntry) -> Self {
+        SearchHistoryEntryDTO {
+            id: entry.id,
+            kind: entry.kind.as_str().to_string(),
+            query: entry.query,
+            replacement: entry.replacement,
+            used_at: entry
+                .used_at
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SearchMatchDTO {
+    line: usize,
+    text: String,
+    /// Range of the match in `text`, in UTF-16 units for slicing in JS
+    start: usize,
+    end: usize,
+    occurrence: usize,
+}
+
+impl From<SearchMatch> for SearchMatchDTO {
+    fn from(m: SearchMatch) -> Self {
+        let utf16 = |byte: usize| m.text[..byte].encode_utf16().count();
+        SearchMatchDTO {
+            line: m.line,
+            start: utf16(m.start),
+            end: utf16(m.end),
+            occurrence: m.occurrence,
+            text: m.text,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TemplateFieldDTO {
+    index: u32,
+    label: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TemplateSchemaDTO {
+    fields: Vec<TemplateFieldDTO>,
+    has_cursor: bool,
+}
+
+impl From<TemplateSchema> for TemplateSchemaDTO {
+    fn from(schema: TemplateSchema) -> Self {
+        TemplateSchemaDTO {
+            fields: schema
+                .fields
+                .into_iter()
+                .map(|field| TemplateFieldDTO {
+                    index: field.index,
+                    label: field.label,
+                })
+                .collect(),
+            has_cursor: schema.has_cursor,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TemplateNoteDTO {
+    revision: i64,
+    /// Caret position in the note's content, in UTF-16 units for JS
+    cursor: Option<usize>,
+}
+
+impl From<TemplateNote> for TemplateNoteDTO {
+    fn from(note: TemplateNote) -> Self {
+        TemplateNoteDTO {
+            revision: note.revision,
+            cursor: note
+                .cursor
+                .map(|byte| note.content[..byte].encode_utf16().count()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SearchResultDTO {
+    note: NoteMetadataDTO,
+    matches: Vec<SearchMatchDTO>,
+    match_count: usize,
+}
+
+impl From<SearchResult> for SearchResultDTO {
+    fn from(result: SearchResult) -> Self {
+        SearchResultDTO {
+            note: result.note.into(),
+            matches: result.matches.into_iter().map(Into::into).collect(),
+            match_count: result.match_count,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LinkSuggestionDTO {
+    path: String,
+    title: String,
+    score: f64,
+}
+
+impl From<LinkSuggestion> for LinkSuggestionDTO {
+    fn from(suggestion: LinkSuggestion) -> Self {
+        LinkSuggestionDTO {
+            path: suggestion.path,
+            title: suggestion.title,
+            score: suggestion.score,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ViewRowDTO {
+    note: NoteMetadataDTO,
+    /// The child's frontmatter properties in file order
+    properties: Vec<(String, PropertyValueDTO)>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ViewDataDTO {
+    path: String,
+    /// "list", "table", or "board"
+    kind: String,
+    /// Table columns; empty for other kinds
+    properties: Vec<String>,
+    /// Only set for board views
+    board: Option<BoardDTO>,
+    rows: Vec<ViewRowDTO>,
+}
+
+impl From<ViewData> for ViewDataDTO {
+    fn from(view: ViewData) -> Self {
+        let (kind, properties, board) = match view.layout {
+            ViewLayout::List => ("list", Vec::new(), None),
+            ViewLayout::Table { properties } => ("table", properties, None),
+            ViewLayout::Board(board) => ("board", Vec::new(), Some(board.into())),
+        };
+        ViewDataDTO {
+            path: view.path,
+            kind: kind.to_string(),
+            properties,
+            board,
+            rows: view
+                .rows
+                .into_iter()
+                .map(|row| ViewRowDTO {
+                    properties: row
+                        .frontmatter
+                        .iter()
+                        .map(|(key, value)| (key.to_string(), value.clone().into()))
+                        .collect(),
+                    note: row.note.into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReminderDTO {
+    id: i64,
+    path: String,
+    remind_at: u64, // Unix timestamp
+    message: String,
+    fired: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AccessTokenDTO {
+    id: i64,
+    name: String,
+    secret: String,
+    access: String,
+    subtree: Option<String>,
+    created: u64, // Unix timestamp
+}
+
+impl From<AccessToken> for AccessTokenDTO {
+    fn from(token: AccessToken) -> Self {
+        AccessTokenDTO {
+            id: token.id,
+            name: token.name,
+            secret: token.secret,
+            access: token.access.as_str().to_string(),
+            subtree: token.subtree,
+            created: token
+                .created
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChangeDTO {
+    revision: i64,
+    kind: String,
+    path: String,
+    old_path: Option<String>,
+    edit: Option<EditSummaryDTO>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EditSummaryDTO {
+    words_added: usize,
+    words_removed: usize,
+    headings: Vec<String>,
+}
+
+impl From<EditSummary> for EditSummaryDTO {
+    fn from(summary: EditSummary) -> Self {
+        EditSummaryDTO {
+            words_added: summary.words_added,
+            words_removed: summary.words_removed,
+            headings: summary.headings,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChangesDTO {
+    revision: i64,
+    reset: bool,
+    changes: Vec<ChangeDTO>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct QueueDepthDTO {
+    interactive: usize,
+    background: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CapabilitiesDTO {
+    editable: bool,
+    available: bool,
+    safe_mode: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CheckpointDTO {
+    id: String,
+    label: String,
+    created_at: i64,
+    files: usize,
+}
+
+impl From<Checkpoint> for CheckpointDTO {
+    fn from(checkpoint: Checkpoint) -> Self {
+        Self {
+            id: checkpoint.id,
+            label: checkpoint.label,
+            created_at: checkpoint.created_at,
+            files: checkpoint.files,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DiffLineDTO {
+    /// "same", "added", or "removed"
+    kind: &'static str,
+    text: String,
+    old_line: Option<usize>,
+    new_line: Option<usize>,
+}
+
+impl From<DiffLine> for DiffLineDTO {
+    fn from(line: DiffLine) -> Self {
+        Self {
+            kind: match line.kind {
+                DiffKind::Same => "same",
+                DiffKind::Added => "added",
+                DiffKind::Removed => "removed",
+            },
+            text: line.text,
+            old_line: line.old_line,
+            new_line: line.new_line,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DailyNoteDTO {
+    /// YYYY-MM-DD
+    date: String,
+    path: String,
+}
+
+impl From<DailyNote> for DailyNoteDTO {
+    fn from(note: DailyNote) -> Self {
+        Self {
+            date: note.date.to_string(),
+            path: note.path,
+        }
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date sent by the frontend.
+fn parse_date(date: &str) -> Result<Date, String> {
+    Date::parse(date).ok_or_else(|| {
+        i18n::error_message(zinnia_core::Error::InvalidInput(format!(
+            "Invalid date: {}",
+            date
+        )))
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TrashedNoteDTO {
+    id: String,
+    original_path: String,
+    deleted_at: i64,
+}
+
+impl From<TrashedNote> for TrashedNoteDTO {
+    fn from(entry: TrashedNote) -> Self {
+        Self {
+            id: entry.id,
+            original_path: entry.original_path,
+            deleted_at: entry.deleted_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportFormatDTO {
+    id: String,
+    name: String,
+    extension: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MaintenanceReportDTO {
+    size_before: u64,
+    size_after: u64,
+    reclaimed: u64,
+    duration_ms: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StorageReportDTO {
+    notes: u64,
+    archive: u64,
+    attachments: u64,
+    history: u64,
+    trash: u64,
+    database: u64,
+    total: u64,
+}
+
+impl From<StorageReport> for StorageReportDTO {
+    fn from(report: StorageReport) -> Self {
+        StorageReportDTO {
+            notes: report.notes,
+            archive: report.archive,
+            attachments: report.attachments,
+            history: report.history,
+            trash: report.trash,
+            database: report.database,
+            total: report.total(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StartupReportDTO {
+    stale_files: Vec<String>,
+    wal_checkpointed: bool,
+    index_rebuilt: bool,
+    /// Rows recovered from the index that was set aside, when it was rebuilt
+    salvaged: Option<usize>,
+    journal_replayed: Vec<String>,
+    open_duration_ms: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SchemaWarningDTO {
+    schema: String,
+    field: String,
+    /// "missing", "wrong_kind", or "not_allowed"
+    issue: String,
+    /// Expected kind for "wrong_kind", offending value for "not_allowed"
+    detail: Option<String>,
+}
+
+impl From<SchemaWarning> for SchemaWarningDTO {
+    fn from(warning: SchemaWarning) -> Self {
+        let (issue, detail) = match warning.issue {
+            SchemaIssue::Missing => ("missing", None),
+            SchemaIssue::WrongKind(kind) => {
+                ("wrong_kind", Some(format!("{:?}", kind).to_lowercase()))
+            }
+            SchemaIssue::NotAllowed(value) => ("not_allowed", Some(value)),
+        };
+        SchemaWarningDTO {
+            schema: warning.schema,
+            field: warning.field,
+            issue: issue.to_string(),
+            detail,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NoteSchemaWarningsDTO {
+    path: String,
+    warnings: Vec<SchemaWarningDTO>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HealthReportDTO {
+    notes_checked: usize,
+    schema_warnings: Vec<NoteSchemaWarningsDTO>,
+}
+
+impl From<HealthReport> for HealthReportDTO {
+    fn from(report: HealthReport) -> Self {
+        HealthReportDTO {
+            notes_checked: report.notes_checked,
+            schema_warnings: report
+                .schema_warnings
+                .into_iter()
+                .map(|(path, warnings)| NoteSchemaWarningsDTO {
+                    path,
+                    warnings: warnings.into_iter().map(SchemaWarningDTO::from).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LinkProblemDTO {
+    Status { code: u16 },
+    Unreachable { message: String },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DeadLinkDTO {
+    url: String,
+    notes: Vec<String>,
+    problem: LinkProblemDTO,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LinkReportDTO {
+    checked: usize,
+    dead: Vec<DeadLinkDTO>,
+}
+
+impl From<LinkReport> for LinkReportDTO {
+    fn from(report: LinkReport) -> Self {
+        LinkReportDTO {
+            checked: report.checked,
+            dead: report
+                .dead
+                .into_iter()
+                .map(|link| DeadLinkDTO {
+                    url: link.url,
+                    notes: link.notes,
+                    problem: match link.problem {
+                        LinkProblem::Status(code) => LinkProblemDTO::Status { code },
+                        LinkProblem::Unreachable(message) => {
+                            LinkProblemDTO::Unreachable { message }
+                        }
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvImportModeDTO {
+    ChildNotes,
+    Table,
+}
+
+impl From<CsvImportModeDTO> for CsvImportMode {
+    fn from(dto: CsvImportModeDTO) -> Self {
+        match dto {
+            CsvImportModeDTO::ChildNotes => CsvImportMode::ChildNotes,
+            CsvImportModeDTO::Table => CsvImportMode::Table,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FormatActionDTO {
+    Bold,
+    Italic,
+    Heading { level: u8 },
+    BulletList,
+}
+
+impl From<FormatActionDTO> for FormatAction {
+    fn from(dto: FormatActionDTO) -> Self {
+        match dto {
+            FormatActionDTO::Bold => FormatAction::Bold,
+            FormatActionDTO::Italic => FormatAction::Italic,
+            FormatActionDTO::Heading { level } => FormatAction::Heading(level),
+            FormatActionDTO::BulletList => FormatAction::BulletList,
+        }
+    }
+}
+
+/// Selection offsets are UTF-16 code units, matching the frontend's
+/// `selectionStart`/`selectionEnd`.
+#[derive(Serialize, Deserialize)]
+pub struct TextEditDTO {
+    text: String,
+    selection_start: usize,
+    selection_end: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TypographyReplacementDTO {
+    delete: usize,
+    insert: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CsvImportDTO {
+    rows: usize,
+    created: Vec<String>,
+    delimiter: String,
+    encoding: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VaultImportDTO {
+    created: Vec<String>,
+    conflicts: Vec<String>,
+    attachments: Vec<String>,
+    links_rewritten: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NoteLinkDTO {
+    path: String,
+    url: String,
+    wiki_link: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NotePreviewDTO {
+    path: String,
+    title: String,
+    modified: u64, // Unix timestamp
+    excerpt: String,
+}
+
+impl From<NotePreview> for NotePreviewDTO {
+    fn from(preview: NotePreview) -> Self {
+        NotePreviewDTO {
+            path: preview.path,
+            title: preview.title,
+            modified: preview
+                .modified
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            excerpt: preview.excerpt,
+        }
+    }
+}
+
+/// `start`/`end` are byte offsets into the source note, passed back to `link_mention`.
+#[derive(Serialize, Deserialize)]
+pub struct UnlinkedMentionDTO {
+    source: String,
+    start: usize,
+    end: usize,
+    text: String,
+    context: String,
+}
+
+impl From<UnlinkedMention> for UnlinkedMentionDTO {
+    fn from(mention: UnlinkedMention) -> Self {
+        UnlinkedMentionDTO {
+            source: mention.source,
+            start: mention.range.start,
+            end: mention.range.end,
+            text: mention.text,
+            context: mention.context,
+        }
+    }
+}
+
+impl From<NoteLink> for NoteLinkDTO {
+    fn from(link: NoteLink) -> Self {
+        NoteLinkDTO {
+            path: link.path,
+            url: link.url,
+            wiki_link: link.wiki_link,
+        }
+    }
+}
+
+/// A command round trip timed by the frontend; see `perf`.
+#[derive(Serialize, Deserialize)]
+pub struct IpcSampleDTO {
+    command: String,
+    duration_ms: f64,
+    /// Left out when the frontend didn't measure sizes for this call
+    request_bytes: Option<u64>,
+    response_bytes: Option<u64>,
+    ok: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CommandPerfDTO {
+    command: String,
+    count: u64,
+    errors: u64,
+    p50_ms: f64,
+    p95_ms: f64,
+    mean_request_bytes: u64,
+    mean_response_bytes: u64,
+}
+
+impl From<perf::CommandStats> for CommandPerfDTO {
+    fn from(stats: perf::CommandStats) -> Self {
+        CommandPerfDTO {
+            command: stats.command,
+            count: stats.count,
+            errors: stats.errors,
+            p50_ms: stats.p50.as_secs_f64() * 1000.0,
+            p95_ms: stats.p95.as_secs_f64() * 1000.0,
+            mean_request_bytes: stats.mean_request_bytes,
+            mean_response_bytes: stats.mean_response_bytes,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveSummaryDTO {
+    notes: usize,
+    files: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankingModeDTO {
+    Visits,
+    Frecency,
+}
+
+impl From<RankingModeDTO> for RankingMode {
+    fn from(dto: RankingModeDTO) -> Self {
+        match dto {
+            RankingModeDTO::Visits => RankingMode::Visits,
+            RankingModeDTO::Frecency => RankingMode::Frecency,
+        }
+    }
+}
+
+// Convert core types to DTOs
+impl From<Note> for NoteDTO {
+    fn from(note: Note) -> Self {
+        let modified = note.modified.duration_since(std::time::UNIX_EPOCH).unwrap();
+        NoteDTO {
+            id: note.id,
+            path: note.path,
+            content: note.content,
+            modified: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+            revision: note.revision,
+        }
+    }
+}
+
+impl From<NoteMetadata> for NoteMetadataDTO {
+    fn from(meta: NoteMetadata) -> Self {
+        let title = meta.title().to_string();
+        NoteMetadataDTO {
+            id: meta.id,
+            path: meta.path,
+            modified: meta
+                .modified
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            archived: meta.archived,
+            stats: meta.stats.map(|stats| NoteStatsDTO {
+                preview: stats.preview,
+                word_count: stats.word_count,
+                cover: stats.cover,
+                private: stats.private,
+                title,
+                icon: stats.icon,
+                encrypted: stats.encrypted,
+                tags: stats.tags,
+                child_count: stats.child_count,
+                status: stats.status.map(|status| status.as_str()),
+                pinned: stats.pinned,
+            }),
+        }
+    }
+}
+
+impl From<Canvas> for CanvasDTO {
+    fn from(canvas: Canvas) -> Self {
+        CanvasDTO {
+            path: canvas.path,
+            data: canvas.data,
+            modified: canvas
+                .modified
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        }
+    }
+}
+
+impl From<BoardColumn> for BoardColumnDTO {
+    fn from(column: BoardColumn) -> Self {
+        BoardColumnDTO {
+            name: column.name,
+            cards: column.cards.into_iter().map(|c| c.into()).collect(),
+        }
+    }
+}
+
+impl From<Board> for BoardDTO {
+    fn from(board: Board) -> Self {
+        BoardDTO {
+            path: board.path,
+            columns: board.columns.into_iter().map(|c| c.into()).collect(),
+            uncategorized: board.uncategorized.into_iter().map(|c| c.into()).collect(),
+        }
+    }
+}
+
+impl From<Reminder> for ReminderDTO {
+    fn from(reminder: Reminder) -> Self {
+        ReminderDTO {
+            id: reminder.id,
+            path: reminder.path,
+            remind_at: reminder
+                .remind_at
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            message: reminder.message,
+            fired: reminder.fired,
+        }
+    }
+}
+
+// Tauri Commands
+
+#[tauri::command]
+async fn create_note(
+    path: String,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<NoteDTO, String> {
+    let note = blocking::write(&queue, move |api| api.create_note(&path)).await?;
+    tree.emit(&app);
+    Ok(note.into())
+}
+
+#[tauri::command]
+async fn get_note(path: String, state: State<'_, AppState>) -> Result<NoteDTO, String> {
+    blocking::read(&state.notes_api, move |api| api.get_note(&path))
+        .await
+        .map(|note| note.into())
+}
+
+#[tauri::command]
+async fn save_note(
+    path: String,
+    content: String,
+    seq: u64,
+    queue: State<'_, Arc<WriteQueue>>,
+    order: State<'_, Arc<blocking::SaveOrder>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let order = Arc::clone(&order);
+    let (revision, warnings) = blocking::write(&queue, move |api| {
+        // A newer save of this note got to the queue first; this one is stale
+        if !order.admit(&path, seq) {
+            return Ok((api.revision()?, None));
+        }
+        let revision = api.save_note(&path, &content)?;
+        let warnings = api.validate_note(&path).unwrap_or_default();
+        Ok((revision, Some((path, warnings))))
+    })
+    .await?;
+    let Some(warnings) = warnings else {
+        return Ok(revision);
+    };
+    // Saving can auto-create person notes for new @mentions
+    tree.emit(&app);
+    emit_schema_warnings(&app, warnings);
+    Ok(revision)
+}
+
+/// Schema warnings for each note a bulk edit wrote, to emit like a save's.
+fn validate_notes<'a>(
+    api: &NotesApi,
+    paths: impl IntoIterator<Item = &'a str>,
+) -> Vec<(String, Vec<SchemaWarning>)> {
+    paths
+        .into_iter()
+        .map(|path| {
+            (
+                path.to_string(),
+                api.validate_note(path).unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+/// Tells the editor whether the note it just saved breaks its schema. Sent on
+/// every save so a fixed note clears its warnings.
+fn emit_schema_warnings(app: &AppHandle, (path, warnings): (String, Vec<SchemaWarning>)) {
+    let payload = NoteSchemaWarningsDTO {
+        path,
+        warnings: warnings.into_iter().map(SchemaWarningDTO::from).collect(),
+    };
+    if let Err(e) = app.emit(app_events::SCHEMA_WARNINGS, payload) {
+        eprintln!("Failed to emit schema warnings: {}", e);
+    }
+}
+
+#[tauri::command]
+fn get_note_with_version(path: String, state: State<AppState>) -> Result<VersionedNoteDTO, String> {
+    let mut api = state.notes_api.lock().unwrap();
+    versioning::get_note_with_version(&mut api, &path).map_err(i18n::error_message)
+}
+
+/// Merges the editor's edits (`mine`) with a conflicting version on disk
+/// (`theirs`), both made from `base`, for the merge editor.
+#[tauri::command]
+fn merge_conflict(base: String, mine: String, theirs: String) -> MergeDTO {
+    zinnia_core::merge(&base, &mine, &theirs).into()
+}
+
+#[tauri::command]
+fn save_note_versioned(
+    path: String,
+    content: String,
+    base_hash: String,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<SaveOutcomeDTO, String> {
+    let (outcome, warnings) = queue
+        .run(move |api| {
+            let outcome = versioning::save_note_versioned(api, &path, &content, &base_hash)?;
+            let warnings = outcome
+                .is_saved()
+                .then(|| api.validate_note(&path).unwrap_or_default());
+            Ok((outcome, warnings.map(|warnings| (path, warnings))))
+        })
+        .map_err(i18n::error_message)?;
+    if let Some(warnings) = warnings {
+        tree.emit(&app);
+        emit_schema_warnings(&app, warnings);
+    }
+    Ok(outcome)
+}
+
+/// Saves unless the note's modified time is no longer `expected_modified` (Unix
+/// seconds) plus `expected_modified_nanos`, as read. A refused save also emits
+/// `notes:conflicted` from the write queue.
+#[tauri::command]
+fn save_note_if_unchanged(
+    path: String,
+    content: String,
+    expected_modified: u64,
+    expected_modified_nanos: u32,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<SaveIfUnchangedDTO, String> {
+    let expected = std::time::UNIX_EPOCH
+        + std::time::Duration::new(expected_modified, expected_modified_nanos);
+    let saved = queue.run(move |api| {
+        let saved = api.save_note_if_unchanged(&path, &content, expected)?;
+        let warnings = api.validate_note(&path).unwrap_or_default();
+        Ok((saved, (path, warnings)))
+    });
+    match saved {
+        Ok((saved, warnings)) => {
+            tree.emit(&app);
+            emit_schema_warnings(&app, warnings);
+            Ok(saved.into())
+        }
+        Err(zinnia_core::Error::Conflict { content, .. }) => {
+            Ok(SaveIfUnchangedDTO::Conflict { content })
+        }
+        Err(e) => Err(i18n::error_message(e)),
+    }
+}
+
+#[tauri::command]
+async fn delete_note(
+    path: String,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = blocking::write(&queue, move |api| api.delete_note(&path)).await?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+#[tauri::command]
+fn delete_notes(
+    paths: Vec<String>,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = queue
+        .run(move |api| api.delete_notes(&paths))
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+#[tauri::command]
+async fn batch(
+    ops: Vec<NoteOpDTO>,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let ops = ops.into_iter().map(NoteOp::from).collect();
+    let result = blocking::write(&queue, move |api| api.batch(ops)).await;
+    // A failed batch is taken back, so the tree only changes on success
+    if result.is_ok() {
+        tree.emit(&app);
+    }
+    result
+}
+
+#[tauri::command]
+async fn move_notes(
+    paths: Vec<String>,
+    dest_parent: String,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = blocking::write(&queue, move |api| api.move_notes(&paths, &dest_parent)).await?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+/// Renames an untitled note after its first H1 or line; `None` if the name is already
+/// right. The editor follows the rename itself, so no move event is sent.
+#[tauri::command]
+async fn title_from_first_line(
+    path: String,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<Option<NoteMovedDTO>, String> {
+    let moved = blocking::write(&queue, move |api| api.title_from_first_line(&path)).await?;
+    if moved.is_some() {
+        tree.emit(&app);
+    }
+    Ok(moved.map(NoteMovedDTO::from))
+}
+
+/// Moves a note under `new_parent`, keeping its name. `collision` is "error" (the
+/// default) or "suffix" to pick a free name like "Idea 2".
+#[tauri::command]
+async fn move_note(
+    path: String,
+    new_parent: String,
+    collision: Option<String>,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<NoteMovedDTO, String> {
+    let collision = match collision.as_deref() {
+        Some(s) => {
+            MoveCollision::parse(s).ok_or_else(|| format!("Unknown collision policy: {}", s))?
+        }
+        None => MoveCollision::default(),
+    };
+    let moved: NoteMovedDTO = blocking::write(&queue, move |api| {
+        api.move_note(&path, &new_parent, collision)
+    })
+    .await?
+    .into();
+    if let Err(e) = app.emit(app_events::MOVED, moved.clone()) {
+        eprintln!("Failed to emit move event: {:?}", e);
+    }
+    tree.emit(&app);
+    Ok(moved)
+}
+
+#[tauri::command]
+async fn rename_note(
+    old_path: String,
+    new_path: String,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision =
+        blocking::write(&queue, move |api| api.rename_note(&old_path, &new_path)).await?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+/// Copies a note to `new_path`, with its whole subtree if `recursive` is set.
+#[tauri::command]
+async fn duplicate_note(
+    path: String,
+    new_path: String,
+    recursive: bool,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = blocking::write(&queue, move |api| {
+        api.duplicate_note(&path, &new_path, recursive)
+    })
+    .await?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+/// The variables to prompt for before `create_from_template`.
+#[tauri::command]
+async fn get_template_schema(
+    template_path: String,
+    state: State<'_, AppState>,
+) -> Result<TemplateSchemaDTO, String> {
+    blocking::read(&state.notes_api, move |api| {
+        api.get_template_schema(&template_path)
+    })
+    .await
+    .map(TemplateSchemaDTO::from)
+}
+
+/// `values` are keyed by variable number; missing ones get their label.
+#[tauri::command]
+async fn create_from_template(
+    path: String,
+    template_path: String,
+    values: HashMap<u32, String>,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<TemplateNoteDTO, String> {
+    let note = blocking::write(&queue, move |api| {
+        api.create_from_template(&path, &template_path, &values)
+    })
+    .await?;
+    tree.emit(&app);
+    Ok(note.into())
+}
+
+/// Puts the children of `parent` (empty for the root notes) in the given order,
+/// as dropped in the sidebar.
+#[tauri::command]
+async fn reorder_children(
+    parent: String,
+    ordered_paths: Vec<String>,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<(), String> {
+    blocking::write(&queue, move |api| {
+        api.set_child_order(&parent, &ordered_paths)
+    })
+    .await?;
+    tree.emit(&app);
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct VaultLockDTO {
+    /// False until the first unlock sets the passphrase
+    has_passphrase: bool,
+    unlocked: bool,
+}
+
+#[tauri::command]
+fn get_vault_lock(state: State<AppState>) -> VaultLockDTO {
+    let api = state.notes_api.lock().unwrap();
+    VaultLockDTO {
+        has_passphrase: api.has_passphrase(),
+        unlocked: api.is_unlocked(),
+    }
+}
+
+/// Edits made while the vault was unavailable that haven't been written to it yet.
+#[tauri::command]
+async fn get_pending_operations(state: State<'_, AppState>) -> Result<Vec<PendingOp>, String> {
+    blocking::read(&state.notes_api, |api| api.pending_operations()).await
+}
+
+/// Queued edits that couldn't be applied once the vault was back (e.g. their parent
+/// note was deleted meanwhile).
+#[tauri::command]
+async fn get_failed_operations(state: State<'_, AppState>) -> Result<Vec<PendingOp>, String> {
+    blocking::read(&state.notes_api, |api| api.failed_operations()).await
+}
+
+/// Drops the queued edits, and those that couldn't be applied, without writing them.
+#[tauri::command]
+async fn discard_pending_operations(queue: State<'_, Arc<WriteQueue>>) -> Result<(), String> {
+    blocking::write(&queue, |api| api.discard_pending()).await
+}
+
+/// The first unlock sets the vault's passphrase.
+#[tauri::command]
+async fn unlock_vault(passphrase: String, queue: State<'_, Arc<WriteQueue>>) -> Result<(), String> {
+    blocking::write(&queue, move |api| api.unlock(&passphrase)).await
+}
+
+#[tauri::command]
+async fn lock_vault(queue: State<'_, Arc<WriteQueue>>) -> Result<(), String> {
+    blocking::write(&queue, |api| {
+        api.lock();
+        Ok(())
+    })
+    .await
+}
+
+/// Encrypts the note at rest, or stores it as plain text again.
+#[tauri::command]
+async fn set_note_encrypted(
+    path: String,
+    encrypted: bool,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = blocking::write(&queue, move |api| {
+        if encrypted {
+            api.encrypt_note(&path)
+        } else {
+            api.decrypt_note(&path)
+        }
+    })
+    .await?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+#[derive(Serialize)]
+pub struct GitStatusDTO {
+    branch: Option<String>,
+    changed: Vec<String>,
+    ahead: usize,
+    behind: usize,
+    conflicts: Vec<String>,
+}
+
+impl From<GitStatus> for GitStatusDTO {
+    fn from(status: GitStatus) -> Self {
+        GitStatusDTO {
+            branch: status.branch,
+            changed: status.changed,
+            ahead: status.ahead,
+            behind: status.behind,
+            conflicts: status.conflicts,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct GitCommitDTO {
+    id: String,
+    summary: String,
+    author: String,
+    time: u64,
+}
+
+impl From<GitCommit> for GitCommitDTO {
+    fn from(commit: GitCommit) -> Self {
+        GitCommitDTO {
+            id: commit.id,
+            summary: commit.summary,
+            author: commit.author,
+            time: commit
+                .time
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PullOutcomeDTO {
+    UpToDate,
+    FastForward,
+    Merged,
+    Conflicts { notes: Vec<String> },
+}
+
+impl From<PullOutcome> for PullOutcomeDTO {
+    fn from(outcome: PullOutcome) -> Self {
+        match outcome {
+            PullOutcome::UpToDate => PullOutcomeDTO::UpToDate,
+            PullOutcome::FastForward => PullOutcomeDTO::FastForward,
+            PullOutcome::Merged => PullOutcomeDTO::Merged,
+            PullOutcome::Conflicts(notes) => PullOutcomeDTO::Conflicts { notes },
+        }
+    }
+}
+
+/// Makes the vault a git repository and commits everything in it.
+#[tauri::command]
+async fn git_init(queue: State<'_, Arc<WriteQueue>>) -> Result<(), String> {
+    blocking::write(&queue, |api| api.git_init()).await
+}
+
+#[tauri::command]
+async fn get_git_status(state: State<'_, AppState>) -> Result<GitStatusDTO, String> {
+    blocking::read(&state.notes_api, |api| api.git_status())
+        .await
+        .map(GitStatusDTO::from)
+}
+
+#[tauri::command]
+async fn get_git_history(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<GitCommitDTO>, String> {
+    let history = blocking::read(&state.notes_api, move |api| api.git_history(&path)).await?;
+    Ok(history.into_iter().map(GitCommitDTO::from).collect())
+}
+
+/// Commits now instead of waiting for the auto-commit; returns the commit's id.
+#[tauri::command]
+async fn git_commit(
+    message: Option<String>,
+    queue: State<'_, Arc<WriteQueue>>,
+) -> Result<Option<String>, String> {
+    blocking::write(&queue, move |api| api.git_commit(message.as_deref())).await
+}
+
+#[tauri::command]
+async fn git_push(state: State<'_, AppState>) -> Result<(), String> {
+    let remote = blocking::read(&state.notes_api, |api| api.git_remote()).await?;
+    // The upload runs without the API so saves aren't held up by the network
+    tauri::async_runtime::spawn_blocking(move || remote.push().map_err(i18n::error_message))
+        .await
+        .map_err(|e| format!("{:?}", e))?
+}
+
+#[tauri::command]
+async fn git_pull(
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<PullOutcomeDTO, String> {
+    let remote = blocking::write(&queue, |api| api.start_git_pull()).await?;
+    // Fetching only moves refs, so it runs outside the queue; the merge goes back
+    // through it and commits anything saved in the meantime first
+    tauri::async_runtime::spawn_blocking(move || remote.fetch().map_err(i18n::error_message))
+        .await
+        .map_err(|e| format!("{:?}", e))??;
+    let outcome = blocking::write(&queue, |api| api.git_merge_fetched()).await?;
+    tree.emit(&app);
+    Ok(outcome.into())
+}
+
+/// Marks a conflicted note resolved once its conflict markers are edited away;
+/// returns true when that finished the merge.
+#[tauri::command]
+async fn git_resolve(
+    path: String,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    let finished = blocking::write(&queue, move |api| api.git_resolve(&path)).await?;
+    tree.emit(&app);
+    Ok(finished)
+}
+
+#[derive(Serialize)]
+pub struct RemoteStatusDTO {
+    url: Option<String>,
+    username: Option<String>,
+    conflicts: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct SyncChangeDTO {
+    path: String,
+    action: SyncAction,
+}
+
+#[derive(Serialize)]
+pub struct SyncReportDTO {
+    applied: Vec<SyncChangeDTO>,
+    conflicts: Vec<String>,
+}
+
+impl From<SyncReport> for SyncReportDTO {
+    fn from(report: SyncReport) -> Self {
+        SyncReportDTO {
+            applied: report
+                .applied
+                .into_iter()
+                .map(|change| SyncChangeDTO {
+                    path: change.path,
+                    action: change.action,
+                })
+                .collect(),
+            conflicts: report.conflicts,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct SyncProgressDTO {
+    done: usize,
+    total: usize,
+}
+
+/// Held while a sync runs and by the commands that change its state: a sync only
+/// goes through the write queue to change files here, so the queue doesn't keep it
+/// from overlapping them.
+#[derive(Default)]
+pub struct SyncGate(tauri::async_runtime::Mutex<()>);
+
+/// The WebDAV remote, without its password.
+#[tauri::command]
+async fn get_remote_status(state: State<'_, AppState>) -> Result<RemoteStatusDTO, String> {
+    blocking::read(&state.notes_api, |api| {
+        let remote = api.remote_config()?;
+        Ok(RemoteStatusDTO {
+            url: remote.as_ref().map(|remote| remote.url.clone()),
+            username: remote.map(|remote| remote.username),
+            conflicts: api.sync_conflicts()?,
+        })
+    })
+    .await
+}
+
+/// Sets the WebDAV remote, or turns sync off without a URL. Without a password the
+/// stored one is kept.
+#[tauri::command]
+async fn configure_remote(
+    url: Option<String>,
+    username: String,
+    password: Option<String>,
+    queue: State<'_, Arc<WriteQueue>>,
+    gate: State<'_, SyncGate>,
+) -> Result<(), String> {
+    let _idle = gate.0.lock().await;
+    blocking::write(&queue, move |api| {
+        let remote = match url.filter(|url| !url.trim().is_empty()) {
+            Some(url) => {
+                let password = match password {
+                    Some(password) => password,
+                    None => api
+                        .remote_config()?
+                        .map(|remote| remote.password)
+                        .unwrap_or_default(),
+                };
+                Some(RemoteConfig {
+                    url: url.trim().to_string(),
+                    username,
+                    password,
+                })
+            }
+            None => None,
+        };
+        api.configure_remote(remote)
+    })
+    .await
+}
+
+/// Syncs with the WebDAV remote, reporting progress as `notes:sync-progress`.
+#[tauri::command]
+async fn sync_now(
+    queue: State<'_, Arc<WriteQueue>>,
+    gate: State<'_, SyncGate>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<SyncReportDTO, String> {
+    let _syncing = gate.0.lock().await;
+    // The network round trips run outside the queue so saves aren't held up; only
+    // the changes to files here and the rescan after them go through it
+    let (sync, store) =
+        blocking::write(&queue, |api| Ok((api.start_sync()?, api.webdav_store()?))).await?;
+    let apply_queue = Arc::clone(&queue);
+    let progress_app = app.clone();
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        sync.run(
+            &store,
+            |change| {
+                let change = change.clone();
+                apply_queue.run(move |api| api.apply_sync_change(&change))
+            },
+            |done, total| {
+                let _ =
+                    progress_app.emit(app_events::SYNC_PROGRESS, SyncProgressDTO { done, total });
+            },
+        )
+        .map_err(i18n::error_message)
+    })
+    .await
+    .map_err(|e| format!("{:?}", e))??;
+    if report.changed_local() {
+        blocking::write(&queue, |api| api.rescan()).await?;
+        // Open notes may have been replaced
+        let event = NotesEvent::NotesChanged;
+        let _ = app.emit(event.name(), event.change());
+        tree.emit(&app);
+    }
+    Ok(report.into())
+}
+
+/// Keeps one side of a sync conflict; the next sync copies it over the other.
+#[tauri::command]
+async fn resolve_sync_conflict(
+    path: String,
+    keep: SyncSide,
+    queue: State<'_, Arc<WriteQueue>>,
+    gate: State<'_, SyncGate>,
+) -> Result<(), String> {
+    let _idle = gate.0.lock().await;
+    blocking::write(&queue, move |api| api.resolve_sync_conflict(&path, keep)).await
+}
+
+/// Pins the note to the top of its parent's children, or unpins it.
+#[tauri::command]
+async fn pin_note(
+    path: String,
+    pinned: bool,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<(), String> {
+    blocking::write(&queue, move |api| {
+        if pinned {
+            api.pin_note(&path)
+        } else {
+            api.unpin_note(&path)
+        }
+    })
+    .await?;
+    tree.emit(&app);
+    Ok(())
+}
+
+/// With `detail`, each child carries its stats, so rich lists need no further calls.
+#[tauri::command]
+async fn get_children(
+    path: String,
+    detail: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<NoteMetadataDTO>, String> {
+    blocking::read(&state.notes_api, move |api| {
+        if detail.unwrap_or(false) {
+            api.get_children_with_stats(&path)
+        } else {
+            api.get_children(&path)
+        }
+    })
+    .await
+    .map(|children| children.into_iter().map(|c| c.into()).collect())
+}
+
+#[tauri::command]
+async fn get_children_with_stats(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<NoteMetadataDTO>, String> {
+    blocking::read(&state.notes_api, move |api| {
+        api.get_children_with_stats(&path)
+    })
+    .await
+    .map(|children| children.into_iter().map(|c| c.into()).collect())
+}
+
+/// Notes linking to `path` with a wiki-link or note URL.
+#[tauri::command]
+fn get_backlinks(path: String, state: State<AppState>) -> Result<Vec<NoteMetadataDTO>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_backlinks(&path)
+        .map(|notes| notes.into_iter().map(|n| n.into()).collect())
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn get_outgoing_links(path: String, state: State<AppState>) -> Result<Vec<String>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_outgoing_links(&path).map_err(i18n::error_message)
+}
+
+/// Raw bytes of a note's cover image, sent as binary rather than JSON.
+#[tauri::command]
+fn get_note_cover(path: String, state: State<AppState>) -> Result<tauri::ipc::Response, String> {
+    let cover = {
+        let api = state.notes_api.lock().unwrap();
+        api.get_cover_path(&path).map_err(i18n::error_message)?
+    };
+    let cover = cover.ok_or_else(|| format!("No cover image for {}", path))?;
+    let bytes = std::fs::read(cover).map_err(|e| format!("{:?}", e))?;
+    Ok(tauri::ipc::Response::new(bytes))
+}
+
+#[tauri::command]
+fn has_children(path: String, state: State<AppState>) -> Result<bool, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.has_children(&path).map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn get_expanded_paths(state: State<AppState>) -> Result<Vec<String>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_expanded_paths().map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn compile_subtree(
+    path: String,
+    max_depth: Option<usize>,
+    include_archived: Option<bool>,
+    state: State<AppState>,
+) -> Result<String, String> {
+    let api = state.notes_api.lock().unwrap();
+    let options = CompileOptions {
+        max_depth,
+        include_archived: include_archived.unwrap_or(false),
+        ..Default::default()
+    };
+    api.compile_subtree(&path, &options)
+        .map(|doc| doc.content)
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn set_expanded(path: String, expanded: bool, state: State<AppState>) -> Result<(), String> {
+    let mut api = state.notes_api.lock().unwrap();
+    api.set_expanded(&path, expanded)
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn set_note_private(
+    path: String,
+    private: bool,
+    queue: State<Arc<WriteQueue>>,
+) -> Result<i64, String> {
+    queue
+        .run(move |api| api.set_note_private(&path, private))
+        .map_err(i18n::error_message)
+}
+
+/// Shows or hides private notes in search, the finder, and exports.
+#[tauri::command]
+fn set_include_private(include: bool, state: State<AppState>) {
+    state.notes_api.lock().unwrap().set_include_private(include);
+}
+
+#[tauri::command]
+fn collapse_all(state: State<AppState>) -> Result<(), String> {
+    let mut api = state.notes_api.lock().unwrap();
+    api.collapse_all().map_err(i18n::error_message)
+}
+
+/// With `detail`, each note carries its stats, like `get_children`.
+#[tauri::command]
+fn get_ancestors(
+    path: String,
+    detail: Option<bool>,
+    state: State<AppState>,
+) -> Result<Vec<NoteMetadataDTO>, String> {
+    let api = state.notes_api.lock().unwrap();
+    let ancestors = if detail.unwrap_or(false) {
+        api.get_ancestors_with_stats(&path)
+    } else {
+        api.get_ancestors(&path)
+    };
+    ancestors
+        .map(|ancestors| ancestors.into_iter().map(|a| a.into()).collect())
+        .map_err(i18n::error_message)
+}
+
+/// With `detail`, each note carries its stats, like `get_children`.
+#[tauri::command]
+fn get_root_notes(
+    detail: Option<bool>,
+    state: State<AppState>,
+) -> Result<Vec<NoteMetadataDTO>, String> {
+    let api = state.notes_api.lock().unwrap();
+    let notes = if detail.unwrap_or(false) {
+        api.get_root_notes_with_stats()
+    } else {
+        api.get_root_notes()
+    };
+    notes
+        .map(|notes| notes.into_iter().map(|n| n.into()).collect())
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+async fn get_all_notes(state: State<'_, AppState>) -> Result<Vec<NoteMetadataDTO>, String> {
+    blocking::read(&state.notes_api, |api| api.get_all_notes())
+        .await
+        .map(|notes| notes.into_iter().map(|n| n.into()).collect())
+}
+
+/// Most recently modified notes first.
+#[tauri::command]
+async fn get_recent_notes(
+    limit: usize,
+    state: State<'_, AppState>,
+) -> Result<Vec<NoteMetadataDTO>, String> {
+    blocking::read(&state.notes_api, move |api| api.get_recent_notes(limit))
+        .await
+        .map(|notes| notes.into_iter().map(|n| n.into()).collect())
+}
+
+/// Most recently opened notes first, as logged by `record_note_open`.
+#[tauri::command]
+async fn get_recently_opened(
+    limit: usize,
+    state: State<'_, AppState>,
+) -> Result<Vec<NoteMetadataDTO>, String> {
+    blocking::read(&state.notes_api, move |api| api.get_recently_opened(limit))
+        .await
+        .map(|notes| notes.into_iter().map(|n| n.into()).collect())
+}
+
+#[tauri::command]
+fn record_note_open(path: String, state: State<AppState>) -> Result<(), String> {
+    let mut api = state.notes_api.lock().unwrap();
+    api.record_open(&path).map_err(i18n::error_message)
+}
+
+/// Starred notes, oldest star first.
+#[tauri::command]
+async fn get_favorites(state: State<'_, AppState>) -> Result<Vec<NoteMetadataDTO>, String> {
+    blocking::read(&state.notes_api, |api| api.get_favorites())
+        .await
+        .map(|notes| notes.into_iter().map(|n| n.into()).collect())
+}
+
+#[tauri::command]
+fn favorite_note(path: String, state: State<AppState>, app: AppHandle) -> Result<(), String> {
+    let mut api = state.notes_api.lock().unwrap();
+    api.favorite_note(&path).map_err(i18n::error_message)?;
+    emit_favorites_changed(&app);
+    Ok(())
+}
+
+#[tauri::command]
+fn unfavorite_note(path: String, state: State<AppState>, app: AppHandle) -> Result<(), String> {
+    let mut api = state.notes_api.lock().unwrap();
+    api.unfavorite_note(&path).map_err(i18n::error_message)?;
+    emit_favorites_changed(&app);
+    Ok(())
+}
+
+/// Lets every open note list refresh its starred section.
+fn emit_favorites_changed(app: &AppHandle) {
+    if let Err(e) = app.emit(app_events::FAVORITES, ()) {
+        eprintln!("Failed to emit favorites event: {:?}", e);
+    }
+}
+
+#[tauri::command]
+async fn fuzzy_search_notes(
+    query: String,
+    limit: Option<usize>,
+    ranking_mode: RankingModeDTO,
+    current_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<NoteMetadataDTO>, String> {
+    let ranking_mode: RankingMode = ranking_mode.into();
+    blocking::read(&state.notes_api, move |api| {
+        // Results near the open note rank higher
+        match current_path {
+            Some(current) => api.fuzzy_search_near(&query, limit, ranking_mode, &current),
+            None => api.fuzzy_search(&query, limit, ranking_mode),
+        }
+    })
+    .await
+    .map(|results| results.into_iter().map(|r| r.into()).collect())
+}
+
+#[tauri::command]
+async fn search_notes(
+    query: String,
+    current_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<NoteMetadataDTO>, String> {
+    blocking::read(&state.notes_api, move |api| match current_path {
+        Some(current) => api.search_near(&query, &current),
+        None => api.search(&query),
+    })
+    .await
+    .map(|results| results.into_iter().map(|r| r.into()).collect())
+}
+
+/// Loads the given notes into the content cache ahead of opening them, e.g. the
+/// visible part of the tree. Returns how many were loaded.
+#[tauri::command]
+async fn warm_cache(paths: Vec<String>, state: State<'_, AppState>) -> Result<usize, String> {
+    blocking::read(&state.notes_api, move |api| api.warm_cache(&paths)).await
+}
+
+/// Like `search_notes`, with each note's matching lines for showing results in context.
+#[tauri::command]
+async fn search_with_matches(
+    query: String,
+    current_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SearchResultDTO>, String> {
+    blocking::read(&state.notes_api, move |api| {
+        api.search_with_matches(&query, current_path.as_deref())
+    })
+    .await
+    .map(|results| results.into_iter().map(Into::into).collect())
+}
+
+/// Notes to offer for `[[` autocompletion in the note at `context_path`, best first.
+#[tauri::command]
+async fn suggest_links(
+    query: String,
+    context_path: String,
+    limit: usize,
+    state: State<'_, AppState>,
+) -> Result<Vec<LinkSuggestionDTO>, String> {
+    blocking::read(&state.notes_api, move |api| {
+        api.suggest_links(&query, &context_path, limit)
+    })
+    .await
+    .map(|suggestions| suggestions.into_iter().map(Into::into).collect())
+}
+
+#[tauri::command]
+fn archive_notes(
+    paths: Vec<String>,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = queue
+        .run(move |api| api.archive_notes(&paths))
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+/// Sets (or with a null `value`, removes) a property on many notes at once.
+/// With `dry_run` nothing is written and the changes are only previewed.
+#[tauri::command]
+fn bulk_set_property(
+    selection: NoteSelectionDTO,
+    key: String,
+    value: Option<PropertyValueDTO>,
+    dry_run: bool,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<BulkPropertyEditDTO, String> {
+    let (edit, warnings) = queue
+        .run(move |api| {
+            let edit =
+                api.bulk_set_property(&selection.into(), &key, value.map(Into::into), dry_run)?;
+            let warnings = match edit.revision {
+                Some(_) => validate_notes(api, edit.changes.iter().map(|c| c.path.as_str())),
+                None => Vec::new(),
+            };
+            Ok((edit, warnings))
+        })
+        .map_err(i18n::error_message)?;
+    if edit.revision.is_some() {
+        tree.emit(&app);
+    }
+    for warnings in warnings {
+        emit_schema_warnings(&app, warnings);
+    }
+    Ok(edit.into())
+}
+
+/// A note's frontmatter properties in file order.
+#[tauri::command]
+fn get_properties(
+    path: String,
+    state: State<AppState>,
+) -> Result<Vec<(String, PropertyValueDTO)>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_properties(&path)
+        .map(|properties| {
+            properties
+                .into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect()
+        })
+        .map_err(i18n::error_message)
+}
+
+/// Sets (or with a null `value`, removes) a property on one note.
+#[tauri::command]
+fn set_property(
+    path: String,
+    key: String,
+    value: Option<PropertyValueDTO>,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = queue
+        .run(move |api| match value {
+            Some(value) => api.set_property(&path, &key, value.into()),
+            None => api.remove_property(&path, &key),
+        })
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+/// A note's workflow status ("todo", "doing", or "done"), if any.
+#[tauri::command]
+fn get_status(path: String, state: State<AppState>) -> Result<Option<&'static str>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_status(&path)
+        .map(|status| status.map(|status| status.as_str()))
+        .map_err(i18n::error_message)
+}
+
+/// Moves a note to its next status and returns it; after "done" it has none.
+#[tauri::command]
+fn cycle_status(
+    path: String,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<Option<&'static str>, String> {
+    let status = queue
+        .run(move |api| api.cycle_status(&path))
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(status.map(|status| status.as_str()))
+}
+
+/// Notes whose property `key` is `value` or has it as a list item.
+#[tauri::command]
+fn query_by_property(
+    key: String,
+    value: String,
+    state: State<AppState>,
+) -> Result<Vec<NoteMetadataDTO>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.query_by_property(&key, &value)
+        .map(|notes| notes.into_iter().map(|n| n.into()).collect())
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn get_tag_tree(state: State<AppState>) -> Result<Vec<TagNodeDTO>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_tag_tree()
+        .map(|tree| tree.into_iter().map(TagNodeDTO::from).collect())
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn get_tags(state: State<AppState>) -> Result<Vec<String>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_tags().map_err(i18n::error_message)
+}
+
+/// Notes tagged with `tag` or any tag nested below it.
+#[tauri::command]
+fn get_notes_with_tag(tag: String, state: State<AppState>) -> Result<Vec<NoteMetadataDTO>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_notes_with_tag(&tag)
+        .map(|notes| notes.into_iter().map(|n| n.into()).collect())
+        .map_err(i18n::error_message)
+}
+
+/// Writes a map of content into `target`: links to every note below `source`,
+/// or tagged with it when `source` starts with `#`.
+#[tauri::command]
+fn generate_moc(
+    source: String,
+    target: String,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<MocUpdateDTO, String> {
+    let update = queue
+        .run(move |api| api.generate_moc(&MocSource::parse(&source), &target))
+        .map_err(i18n::error_message)?;
+    if update.revision.is_some() {
+        tree.emit(&app);
+    }
+    Ok(update.into())
+}
+
+#[tauri::command]
+fn rename_tag(
+    old: String,
+    new: String,
+    dry_run: bool,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<TagRenameDTO, String> {
+    let rename = queue
+        .run(move |api| api.rename_tag(&old, &new, dry_run))
+        .map_err(i18n::error_message)?;
+    if rename.revision.is_some() {
+        tree.emit(&app);
+    }
+    Ok(rename.into())
+}
+
+/// With `dry_run` nothing is written and the changes are only previewed.
+#[tauri::command]
+fn rename_property_value(
+    key: String,
+    old: String,
+    new: String,
+    dry_run: bool,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<BulkPropertyEditDTO, String> {
+    let (edit, warnings) = queue
+        .run(move |api| {
+            let edit = api.rename_property_value(&key, &old, &new, dry_run)?;
+            let warnings = match edit.revision {
+                Some(_) => validate_notes(api, edit.changes.iter().map(|c| c.path.as_str())),
+                None => Vec::new(),
+            };
+            Ok((edit, warnings))
+        })
+        .map_err(i18n::error_message)?;
+    if edit.revision.is_some() {
+        tree.emit(&app);
+    }
+    for warnings in warnings {
+        emit_schema_warnings(&app, warnings);
+    }
+    Ok(edit.into())
+}
+
+#[tauri::command]
+fn replace_in_notes(
+    find: String,
+    replacement: String,
+    dry_run: bool,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<ReplaceEditDTO, String> {
+    let (edit, warnings) = queue
+        .run(move |api| {
+            let edit = api.replace_in_notes(&find, &replacement, dry_run)?;
+            let warnings = match edit.revision {
+                Some(_) => validate_notes(api, edit.changes.iter().map(|c| c.path.as_str())),
+                None => Vec::new(),
+            };
+            Ok((edit, warnings))
+        })
+        .map_err(i18n::error_message)?;
+    if edit.revision.is_some() {
+        tree.emit(&app);
+    }
+    for warnings in warnings {
+        emit_schema_warnings(&app, warnings);
+    }
+    Ok(edit.into())
+}
+
+#[tauri::command]
+fn rerun_replace(
+    id: i64,
+    dry_run: bool,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<ReplaceEditDTO, String> {
+    let (edit, warnings) = queue
+        .run(move |api| {
+            let edit = api.rerun_replace(id, dry_run)?;
+            let warnings = match edit.revision {
+                Some(_) => validate_notes(api, edit.changes.iter().map(|c| c.path.as_str())),
+                None => Vec::new(),
+            };
+            Ok((edit, warnings))
+        })
+        .map_err(i18n::error_message)?;
+    if edit.revision.is_some() {
+        tree.emit(&app);
+    }
+    for warnings in warnings {
+        emit_schema_warnings(&app, warnings);
+    }
+    Ok(edit.into())
+}
+
+#[tauri::command]
+fn record_search(query: String, state: State<AppState>) -> Result<(), String> {
+    let mut api = state.notes_api.lock().unwrap();
+    api.record_search(&query).map_err(i18n::error_message)
+}
+
+/// `kind` is "search" or "replace"; all entries are returned when it's omitted.
+#[tauri::command]
+fn get_search_history(
+    kind: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<SearchHistoryEntryDTO>, String> {
+    let kind = kind.as_deref().and_then(HistoryKind::parse);
+    let api = state.notes_api.lock().unwrap();
+    api.get_search_history(kind)
+        .map(|entries| {
+            entries
+                .into_iter()
+                .map(SearchHistoryEntryDTO::from)
+                .collect()
+        })
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn clear_search_history(state: State<AppState>) -> Result<(), String> {
+    let mut api = state.notes_api.lock().unwrap();
+    api.clear_search_history().map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn archive_note(
+    path: String,
+    state: State<AppState>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = state
+        .notes_api
+        .lock()
+        .unwrap()
+        .archive_note(&path)
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+#[tauri::command]
+fn unarchive_note(
+    path: String,
+    state: State<AppState>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = state
+        .notes_api
+        .lock()
+        .unwrap()
+        .unarchive_note(&path)
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+#[tauri::command]
+fn trash_note(
+    path: String,
+    state: State<AppState>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = state
+        .notes_api
+        .lock()
+        .unwrap()
+        .trash_note(&path)
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+#[tauri::command]
+fn create_canvas(
+    path: String,
+    state: State<AppState>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<CanvasDTO, String> {
+    let canvas = state
+        .notes_api
+        .lock()
+        .unwrap()
+        .create_canvas(&path)
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(canvas.into())
+}
+
+#[tauri::command]
+fn get_canvas(path: String, state: State<AppState>) -> Result<CanvasDTO, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_canvas(&path)
+        .map(|canvas| canvas.into())
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn save_canvas(
+    path: String,
+    data: serde_json::Value,
+    state: State<AppState>,
+) -> Result<i64, String> {
+    let mut api = state.notes_api.lock().unwrap();
+    api.save_canvas(&path, &data).map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn get_board(path: String, state: State<AppState>) -> Result<BoardDTO, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_board(&path)
+        .map(|board| board.into())
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn get_view_data(path: String, state: State<AppState>) -> Result<ViewDataDTO, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_view_data(&path)
+        .map(ViewDataDTO::from)
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn move_card(path: String, to_column: String, state: State<AppState>) -> Result<i64, String> {
+    let mut api = state.notes_api.lock().unwrap();
+    api.move_card(&path, &to_column)
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn set_reminder(
+    path: String,
+    remind_at: u64,
+    message: String,
+    state: State<AppState>,
+) -> Result<ReminderDTO, String> {
+    let mut api = state.notes_api.lock().unwrap();
+    let remind_at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(remind_at);
+    api.set_reminder(&path, remind_at, &message)
+        .map(|reminder| reminder.into())
+        .map_err(i18n::error_message)
+}
 
-// Application state holding the NotesApi instance
-pub struct AppState {
-    notes_api: Arc<Mutex<NotesApi>>,
+#[tauri::command]
+fn list_upcoming_reminders(state: State<AppState>) -> Result<Vec<ReminderDTO>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.list_upcoming_reminders()
+        .map(|reminders| reminders.into_iter().map(|r| r.into()).collect())
+        .map_err(i18n::error_message)
 }
 
-// Serializable versions of the core types for Tauri/JSON
-#[derive(Serialize, Deserialize)]
-pub struct NoteDTO {
-    id: i64,
-    path: String,
-    content: String,
-    modified: u64, // Unix timestamp
+#[tauri::command]
+fn delete_reminder(id: i64, state: State<AppState>) -> Result<(), String> {
+    let mut api = state.notes_api.lock().unwrap();
+    api.delete_reminder(id).map_err(i18n::error_message)
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct NoteMetadataDTO {
-    id: i64,
-    path: String,
-    modified: u64, // Unix timestamp
-    archived: bool,
+#[tauri::command]
+fn export_ics(
+    scope: String,
+    dest: Option<String>,
+    state: State<AppState>,
+) -> Result<String, String> {
+    let api = state.notes_api.lock().unwrap();
+    let ics = api.export_ics(&scope).map_err(i18n::error_message)?;
+    if let Some(dest) = dest {
+        std::fs::write(dest, &ics).map_err(|e| format!("{:?}", e))?;
+    }
+    Ok(ics)
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum RankingModeDTO {
-    Visits,
-    Frecency,
+#[tauri::command]
+fn get_export_formats(state: State<AppState>) -> Vec<ExportFormatDTO> {
+    let api = state.notes_api.lock().unwrap();
+    api.export_formats()
+        .formats()
+        .map(|format| ExportFormatDTO {
+            id: format.id().to_string(),
+            name: format.name().to_string(),
+            extension: format.extension().to_string(),
+        })
+        .collect()
 }
 
-impl From<RankingModeDTO> for RankingMode {
-    fn from(dto: RankingModeDTO) -> Self {
-        match dto {
-            RankingModeDTO::Visits => RankingMode::Visits,
-            RankingModeDTO::Frecency => RankingMode::Frecency,
-        }
+#[tauri::command]
+fn render_note(path: String, format: String, state: State<AppState>) -> Result<String, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.render_note(&path, &format).map_err(i18n::error_message)
+}
+
+#[tauri::command]
+async fn export_notes(
+    paths: Vec<String>,
+    format: String,
+    dest: String,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    blocking::read(&state.notes_api, move |api| {
+        api.export_notes(&paths, &format, dest)
+    })
+    .await
+}
+
+/// Imports a table from a file (`file`) or from its text (`content`).
+#[tauri::command]
+fn import_csv(
+    file: Option<String>,
+    content: Option<String>,
+    target: String,
+    mode: CsvImportModeDTO,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<CsvImportDTO, String> {
+    let import = queue
+        .run(move |api| {
+            let source = match (&file, &content) {
+                (Some(file), _) => CsvSource::Path(std::path::Path::new(file)),
+                (None, Some(content)) => CsvSource::Content(content.as_bytes()),
+                (None, None) => {
+                    return Err(zinnia_core::Error::InvalidInput(
+                        "Nothing to import".to_string(),
+                    ));
+                }
+            };
+            api.import_csv(source, &target, mode.into())
+        })
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(CsvImportDTO {
+        rows: import.rows,
+        created: import.created,
+        delimiter: import.delimiter.to_string(),
+        encoding: import.encoding.to_string(),
+    })
+}
+
+/// Imports a folder of Markdown files (e.g. an Obsidian vault) below `target`.
+/// With `dry_run` set, only reports what an import would do.
+#[tauri::command]
+fn import_vault(
+    src_dir: String,
+    target: String,
+    dry_run: bool,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<VaultImportDTO, String> {
+    let import = queue
+        .run(move |api| api.import_vault(&src_dir, &VaultImportOptions { target, dry_run }))
+        .map_err(i18n::error_message)?;
+    if !dry_run {
+        tree.emit(&app);
     }
+    Ok(VaultImportDTO {
+        created: import.created,
+        conflicts: import.conflicts,
+        attachments: import.attachments,
+        links_rewritten: import.links_rewritten,
+    })
 }
 
-// Convert core types to DTOs
-impl From<Note> for NoteDTO {
-    fn from(note: Note) -> Self {
-        NoteDTO {
-            id: note.id,
-            path: note.path,
-            content: note.content,
-            modified: note
-                .modified
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+/// Byte offset of the `units`-th UTF-16 code unit of `text`.
+fn utf16_to_byte(text: &str, units: usize) -> usize {
+    let mut seen = 0;
+    for (i, c) in text.char_indices() {
+        if seen >= units {
+            return i;
         }
+        seen += c.len_utf16();
     }
+    text.len()
 }
 
-impl From<NoteMetadata> for NoteMetadataDTO {
-    fn from(meta: NoteMetadata) -> Self {
-        NoteMetadataDTO {
-            id: meta.id,
-            path: meta.path,
-            modified: meta
-                .modified
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-            archived: meta.archived,
-        }
+fn byte_to_utf16(text: &str, byte: usize) -> usize {
+    text[..byte].encode_utf16().count()
+}
+
+/// Applies a Markdown formatting action to a selection of raw note text.
+#[tauri::command]
+fn format_text(
+    text: String,
+    selection_start: usize,
+    selection_end: usize,
+    action: FormatActionDTO,
+) -> TextEditDTO {
+    let start = utf16_to_byte(&text, selection_start);
+    let end = utf16_to_byte(&text, selection_end);
+    let edit = zinnia_core::apply_format(&text, start, end, action.into());
+    TextEditDTO {
+        selection_start: byte_to_utf16(&edit.text, edit.selection_start),
+        selection_end: byte_to_utf16(&edit.text, edit.selection_end),
+        text: edit.text,
     }
 }
 
-// Tauri Commands
+/// Smart-typography replacement for typing `typed` after `before`, if any.
+#[tauri::command]
+fn smart_typography(before: String, typed: char) -> Option<TypographyReplacementDTO> {
+    zinnia_core::smart_typography(&before, typed).map(|replacement| TypographyReplacementDTO {
+        delete: replacement.delete,
+        insert: replacement.insert,
+    })
+}
 
 #[tauri::command]
-fn create_note(path: String, state: State<AppState>) -> Result<NoteDTO, String> {
-    let mut api = state.notes_api.lock().unwrap();
-    api.create_note(&path)
-        .map(|note| note.into())
-        .map_err(|e| format!("{:?}", e))
+fn get_changes_since(since: i64, state: State<AppState>) -> Result<ChangesDTO, String> {
+    let api = state.notes_api.lock().unwrap();
+    let changes = api.get_changes_since(since).map_err(i18n::error_message)?;
+    Ok(ChangesDTO {
+        revision: changes.revision,
+        reset: changes.reset,
+        changes: changes
+            .changes
+            .into_iter()
+            .map(|change| ChangeDTO {
+                revision: change.revision,
+                kind: change.kind.as_str().to_string(),
+                path: change.path,
+                old_path: change.old_path,
+                edit: change.edit.map(EditSummaryDTO::from),
+            })
+            .collect(),
+    })
 }
 
 #[tauri::command]
-fn get_note(path: String, state: State<AppState>) -> Result<NoteDTO, String> {
-    let mut api = state.notes_api.lock().unwrap();
-    api.get_note(&path)
-        .map(|note| note.into())
-        .map_err(|e| format!("{:?}", e))
+fn get_write_queue_depth(queue: State<Arc<WriteQueue>>) -> QueueDepthDTO {
+    let depth = queue.depth();
+    QueueDepthDTO {
+        interactive: depth.interactive,
+        background: depth.background,
+    }
 }
 
 #[tauri::command]
-fn save_note(path: String, content: String, state: State<AppState>) -> Result<(), String> {
-    let mut api = state.notes_api.lock().unwrap();
-    api.save_note(&path, &content)
-        .map_err(|e| format!("{:?}", e))
+fn get_locale() -> String {
+    i18n::current_locale().to_string()
+}
+
+#[tauri::command]
+fn get_startup_report(state: State<AppState>) -> StartupReportDTO {
+    let api = state.notes_api.lock().unwrap();
+    let report = api.startup_report();
+    StartupReportDTO {
+        stale_files: report.stale_files.clone(),
+        wal_checkpointed: report.wal_checkpointed,
+        index_rebuilt: report.index_rebuilt,
+        salvaged: report.salvaged,
+        journal_replayed: report.journal_replayed.clone(),
+        open_duration_ms: report.open_duration.as_millis() as u64,
+    }
+}
+
+#[tauri::command]
+fn validate_note(path: String, state: State<AppState>) -> Result<Vec<SchemaWarningDTO>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.validate_note(&path)
+        .map(|warnings| warnings.into_iter().map(SchemaWarningDTO::from).collect())
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+async fn health_check(state: State<'_, AppState>) -> Result<HealthReportDTO, String> {
+    blocking::read(&state.notes_api, |api| api.health_check())
+        .await
+        .map(HealthReportDTO::from)
+}
+
+/// Requests every http(s) link in `scope` (a note and the notes below it, or the
+/// whole vault for "") and reports the dead ones. The vault stays unlocked while
+/// the requests run.
+#[tauri::command]
+async fn check_external_links(
+    scope: String,
+    state: State<'_, AppState>,
+) -> Result<LinkReportDTO, String> {
+    let links = {
+        let api = state.notes_api.lock().unwrap();
+        api.collect_external_links(&scope)
+            .map_err(i18n::error_message)?
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        zinnia_core::check_external_links(links, &LinkCheckOptions::default())
+    })
+    .await
+    .map_err(|e| format!("{:?}", e))?
+    .map(LinkReportDTO::from)
+    .map_err(|e| format!("{:?}", e))
+}
+
+#[tauri::command]
+fn run_maintenance(queue: State<Arc<WriteQueue>>) -> Result<Option<MaintenanceReportDTO>, String> {
+    let report = queue
+        .run(|api| api.run_maintenance())
+        .map_err(i18n::error_message)?;
+    Ok(report.map(|report| MaintenanceReportDTO {
+        size_before: report.size_before,
+        size_after: report.size_after,
+        reclaimed: report.reclaimed(),
+        duration_ms: report.duration.as_millis() as u64,
+    }))
+}
+
+#[tauri::command]
+async fn get_storage_report(state: State<'_, AppState>) -> Result<StorageReportDTO, String> {
+    blocking::read(&state.notes_api, |api| api.get_storage_report())
+        .await
+        .map(StorageReportDTO::from)
+}
+
+#[tauri::command]
+fn empty_archive(
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<Option<i64>, String> {
+    let revision = queue
+        .run(|api| api.empty_archive())
+        .map_err(i18n::error_message)?;
+    if revision.is_some() {
+        tree.emit(&app);
+    }
+    Ok(revision)
+}
+
+#[tauri::command]
+fn delete_all_checkpoints(queue: State<Arc<WriteQueue>>) -> Result<usize, String> {
+    queue
+        .run(|api| api.delete_all_checkpoints())
+        .map_err(i18n::error_message)
+}
+
+/// Takes a batch of command timings from the frontend.
+#[tauri::command]
+fn record_ipc_timings(samples: Vec<IpcSampleDTO>, perf: State<perf::PerfStats>) {
+    for sample in samples {
+        perf.record(
+            &sample.command,
+            perf::Sample {
+                duration: std::time::Duration::from_secs_f64(sample.duration_ms.max(0.0) / 1000.0),
+                request_bytes: sample.request_bytes,
+                response_bytes: sample.response_bytes,
+                ok: sample.ok,
+            },
+        );
+    }
+}
+
+/// p50/p95 round trip per command since launch, slowest first.
+#[tauri::command]
+fn get_perf_stats(perf: State<perf::PerfStats>) -> Vec<CommandPerfDTO> {
+    perf.stats().into_iter().map(CommandPerfDTO::from).collect()
+}
+
+#[tauri::command]
+fn create_diagnostics_bundle(dest: String, state: State<AppState>) -> Result<usize, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.create_diagnostics_bundle(dest)
+        .map(|summary| summary.crash_reports)
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn get_capabilities(state: State<AppState>) -> CapabilitiesDTO {
+    let api = state.notes_api.lock().unwrap();
+    let capabilities = api.capabilities();
+    CapabilitiesDTO {
+        editable: capabilities.editable,
+        available: capabilities.available,
+        safe_mode: state.safe_mode,
+    }
 }
 
 #[tauri::command]
-fn delete_note(path: String, state: State<AppState>) -> Result<(), String> {
+fn create_access_token(
+    name: String,
+    access: String,
+    subtree: Option<String>,
+    state: State<AppState>,
+) -> Result<AccessTokenDTO, String> {
+    let access =
+        Access::parse(&access).ok_or_else(|| format!("Unknown access level: {}", access))?;
     let mut api = state.notes_api.lock().unwrap();
-    api.delete_note(&path).map_err(|e| format!("{:?}", e))
+    api.create_access_token(&name, access, subtree.as_deref())
+        .map(AccessTokenDTO::from)
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn list_access_tokens(state: State<AppState>) -> Result<Vec<AccessTokenDTO>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.list_access_tokens()
+        .map(|tokens| tokens.into_iter().map(AccessTokenDTO::from).collect())
+        .map_err(i18n::error_message)
 }
 
 #[tauri::command]
-fn rename_note(old_path: String, new_path: String, state: State<AppState>) -> Result<(), String> {
+fn revoke_access_token(id: i64, state: State<AppState>) -> Result<(), String> {
     let mut api = state.notes_api.lock().unwrap();
-    api.rename_note(&old_path, &new_path)
-        .map_err(|e| format!("{:?}", e))
+    api.revoke_access_token(id).map_err(i18n::error_message)
 }
 
 #[tauri::command]
-fn get_children(path: String, state: State<AppState>) -> Result<Vec<NoteMetadataDTO>, String> {
+fn export_vault_archive(dest: String, state: State<AppState>) -> Result<ArchiveSummaryDTO, String> {
     let api = state.notes_api.lock().unwrap();
-    api.get_children(&path)
-        .map(|children| children.into_iter().map(|c| c.into()).collect())
-        .map_err(|e| format!("{:?}", e))
+    let summary = api.export_archive(dest).map_err(i18n::error_message)?;
+    Ok(ArchiveSummaryDTO {
+        notes: summary.notes,
+        files: summary.files,
+    })
 }
 
 #[tauri::command]
-fn has_children(path: String, state: State<AppState>) -> Result<bool, String> {
+fn create_checkpoint(
+    label: String,
+    queue: State<Arc<WriteQueue>>,
+) -> Result<CheckpointDTO, String> {
+    queue
+        .run(move |api| api.create_checkpoint(&label))
+        .map(CheckpointDTO::from)
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn list_checkpoints(state: State<AppState>) -> Result<Vec<CheckpointDTO>, String> {
     let api = state.notes_api.lock().unwrap();
-    api.has_children(&path).map_err(|e| format!("{:?}", e))
+    api.list_checkpoints()
+        .map(|checkpoints| checkpoints.into_iter().map(CheckpointDTO::from).collect())
+        .map_err(i18n::error_message)
 }
 
+/// Checkpoints holding a new version of the note, newest first.
 #[tauri::command]
-fn get_ancestors(path: String, state: State<AppState>) -> Result<Vec<NoteMetadataDTO>, String> {
+fn get_note_history(path: String, state: State<AppState>) -> Result<Vec<CheckpointDTO>, String> {
     let api = state.notes_api.lock().unwrap();
-    api.get_ancestors(&path)
-        .map(|ancestors| ancestors.into_iter().map(|a| a.into()).collect())
-        .map_err(|e| format!("{:?}", e))
+    api.get_note_history(&path)
+        .map(|checkpoints| checkpoints.into_iter().map(CheckpointDTO::from).collect())
+        .map_err(i18n::error_message)
 }
 
 #[tauri::command]
-fn get_root_notes(state: State<AppState>) -> Result<Vec<NoteMetadataDTO>, String> {
+fn get_note_at_checkpoint(
+    path: String,
+    id: String,
+    state: State<AppState>,
+) -> Result<String, String> {
     let api = state.notes_api.lock().unwrap();
-    api.get_root_notes()
-        .map(|notes| notes.into_iter().map(|n| n.into()).collect())
-        .map_err(|e| format!("{:?}", e))
+    api.get_note_at_checkpoint(&path, &id)
+        .map_err(i18n::error_message)
 }
 
+/// Line diff between two versions of a note; a null checkpoint id is the current
+/// content.
 #[tauri::command]
-fn get_all_notes(state: State<AppState>) -> Result<Vec<NoteMetadataDTO>, String> {
+fn get_revision_diff(
+    path: String,
+    from: Option<String>,
+    to: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<DiffLineDTO>, String> {
     let api = state.notes_api.lock().unwrap();
-    api.get_all_notes()
-        .map(|notes| notes.into_iter().map(|n| n.into()).collect())
-        .map_err(|e| format!("{:?}", e))
+    api.get_revision_diff(&path, from.as_deref(), to.as_deref())
+        .map(|lines| lines.into_iter().map(DiffLineDTO::from).collect())
+        .map_err(i18n::error_message)
 }
 
 #[tauri::command]
-fn fuzzy_search_notes(
-    query: String,
-    limit: Option<usize>,
-    ranking_mode: RankingModeDTO,
+fn restore_checkpoint(
+    id: String,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<(), String> {
+    queue
+        .run(move |api| api.restore_checkpoint(&id))
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(())
+}
+
+#[tauri::command]
+fn delete_checkpoint(id: String, queue: State<Arc<WriteQueue>>) -> Result<(), String> {
+    queue
+        .run(move |api| api.delete_checkpoint(&id))
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn list_trash(state: State<AppState>) -> Result<Vec<TrashedNoteDTO>, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.list_trash()
+        .map(|entries| entries.into_iter().map(TrashedNoteDTO::from).collect())
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn restore_from_trash(
+    path: String,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = queue
+        .run(move |api| api.restore_from_trash(&path))
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+/// Permanently deletes trashed notes older than `older_than_secs`, or all of them.
+#[tauri::command]
+fn empty_trash(
+    older_than_secs: Option<u64>,
+    queue: State<Arc<WriteQueue>>,
+) -> Result<usize, String> {
+    queue
+        .run(move |api| api.empty_trash(older_than_secs.map(std::time::Duration::from_secs)))
+        .map_err(i18n::error_message)
+}
+
+/// Opens the daily note for `date` (YYYY-MM-DD, today when omitted), creating it if needed.
+#[tauri::command]
+async fn open_daily_note(
+    date: Option<String>,
+    queue: State<'_, Arc<WriteQueue>>,
+    tree: State<'_, Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<NoteDTO, String> {
+    let date = date.as_deref().map(parse_date).transpose()?;
+    let note = blocking::write(&queue, move |api| {
+        let date = date.unwrap_or_else(|| Date::from_system_time(api.clock().now()));
+        api.get_or_create_daily_note(date)
+    })
+    .await?;
+    tree.emit(&app);
+    Ok(note.into())
+}
+
+/// Days between `from` and `to` (inclusive, YYYY-MM-DD) that have a daily note.
+#[tauri::command]
+async fn list_daily_notes(
+    from: String,
+    to: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<DailyNoteDTO>, String> {
+    let (from, to) = (parse_date(&from)?, parse_date(&to)?);
+    blocking::read(&state.notes_api, move |api| {
+        api.get_daily_notes_range(from, to)
+    })
+    .await
+    .map(|notes| notes.into_iter().map(DailyNoteDTO::from).collect())
+}
+
+#[tauri::command]
+fn import_vault_archive(
+    src: String,
+    state: State<AppState>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<ArchiveSummaryDTO, String> {
+    let summary = state
+        .notes_api
+        .lock()
+        .unwrap()
+        .import_archive(src)
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(ArchiveSummaryDTO {
+        notes: summary.notes,
+        files: summary.files,
+    })
+}
+
+#[tauri::command]
+fn get_mentions(
+    person_path: String,
     state: State<AppState>,
 ) -> Result<Vec<NoteMetadataDTO>, String> {
     let api = state.notes_api.lock().unwrap();
-    api.fuzzy_search(&query, limit, ranking_mode.into())
-        .map(|results| results.into_iter().map(|r| r.into()).collect())
-        .map_err(|e| format!("{:?}", e))
+    api.get_mentions(&person_path)
+        .map(|notes| notes.into_iter().map(|n| n.into()).collect())
+        .map_err(i18n::error_message)
 }
 
 #[tauri::command]
-fn search_notes(query: String, state: State<AppState>) -> Result<Vec<NoteMetadataDTO>, String> {
+fn get_vault_config(state: State<AppState>) -> VaultConfig {
     let api = state.notes_api.lock().unwrap();
-    api.search(&query)
-        .map(|results| results.into_iter().map(|r| r.into()).collect())
-        .map_err(|e| format!("{:?}", e))
+    api.config().clone()
 }
 
 #[tauri::command]
-fn archive_note(path: String, state: State<AppState>) -> Result<(), String> {
+fn set_vault_config(
+    config: VaultConfig,
+    webview: tauri::Webview,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let mut api = state.notes_api.lock().unwrap();
+    let timing = config.timing.clone();
+    let ui_scale = config.appearance.ui_scale;
+    api.set_config(config).map_err(i18n::error_message)?;
+    *state.timing.lock().unwrap() = timing;
+    webview.set_zoom(ui_scale).map_err(|e| e.to_string())
+}
+
+/// Steps the vault's UI scale and zooms the calling window to it; the webview doesn't
+/// remember its own zoom across launches.
+fn step_zoom(
+    direction: i32,
+    webview: tauri::Webview,
+    state: State<AppState>,
+) -> Result<f64, String> {
     let mut api = state.notes_api.lock().unwrap();
-    api.archive_note(&path).map_err(|e| format!("{:?}", e))
+    let mut config = api.config().clone();
+    let ui_scale = config.appearance.step_ui_scale(direction);
+    api.set_config(config).map_err(i18n::error_message)?;
+    webview.set_zoom(ui_scale).map_err(|e| e.to_string())?;
+    Ok(ui_scale)
+}
+
+#[tauri::command]
+fn zoom_in(webview: tauri::Webview, state: State<AppState>) -> Result<f64, String> {
+    step_zoom(1, webview, state)
+}
+
+#[tauri::command]
+fn zoom_out(webview: tauri::Webview, state: State<AppState>) -> Result<f64, String> {
+    step_zoom(-1, webview, state)
+}
+
+#[tauri::command]
+fn zoom_reset(webview: tauri::Webview, state: State<AppState>) -> Result<f64, String> {
+    step_zoom(0, webview, state)
 }
 
+/// Renames every note file and hands the watcher the new name.
 #[tauri::command]
-fn unarchive_note(path: String, state: State<AppState>) -> Result<(), String> {
+async fn set_note_file(
+    name: String,
+    queue: State<'_, Arc<WriteQueue>>,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let note_files = Arc::clone(&state.note_files);
+    blocking::write(&queue, move |api| {
+        let renamed = api.set_note_file(&name)?;
+        *note_files.lock().unwrap() = api.note_files();
+        Ok(renamed)
+    })
+    .await
+}
+
+/// Moves notes into or out of their folders and hands the watcher the new layout.
+#[tauri::command]
+async fn set_vault_layout(
+    layout: VaultLayout,
+    queue: State<'_, Arc<WriteQueue>>,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let note_files = Arc::clone(&state.note_files);
+    blocking::write(&queue, move |api| {
+        let moved = api.set_layout(layout)?;
+        *note_files.lock().unwrap() = api.note_files();
+        Ok(moved)
+    })
+    .await
+}
+
+#[tauri::command]
+fn get_snippets(state: State<AppState>) -> Vec<Snippet> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_snippets().to_vec()
+}
+
+#[tauri::command]
+fn set_snippet(trigger: String, expansion: String, state: State<AppState>) -> Result<(), String> {
     let mut api = state.notes_api.lock().unwrap();
-    api.unarchive_note(&path).map_err(|e| format!("{:?}", e))
+    api.set_snippet(&trigger, &expansion)
+        .map_err(i18n::error_message)
 }
 
 #[tauri::command]
-fn trash_note(path: String, state: State<AppState>) -> Result<(), String> {
+fn remove_snippet(trigger: String, state: State<AppState>) -> Result<(), String> {
     let mut api = state.notes_api.lock().unwrap();
-    api.trash_note(&path).map_err(|e| format!("{:?}", e))
+    api.remove_snippet(&trigger).map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn expand_snippet(trigger: String, state: State<AppState>) -> Option<String> {
+    let api = state.notes_api.lock().unwrap();
+    api.expand_snippet(&trigger)
+}
+
+#[tauri::command]
+fn convert_html(html: String) -> String {
+    convert_html_to_markdown(&html)
+}
+
+#[tauri::command]
+fn copy_note_link(path: String, state: State<AppState>) -> Result<NoteLinkDTO, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.copy_note_link(&path)
+        .map(NoteLinkDTO::from)
+        .map_err(i18n::error_message)
+}
+
+/// Title, modified time, and opening text of a linked note, for hover cards.
+#[tauri::command]
+fn get_note_preview(path: String, state: State<AppState>) -> Result<NotePreviewDTO, String> {
+    let api = state.notes_api.lock().unwrap();
+    api.get_note_preview(&path)
+        .map(NotePreviewDTO::from)
+        .map_err(i18n::error_message)
+}
+
+#[tauri::command]
+fn get_unlinked_mentions(
+    path: String,
+    state: State<AppState>,
+) -> Result<Vec<UnlinkedMentionDTO>, String> {
+    let api = state.notes_api.lock().unwrap();
+    let mentions = api
+        .get_unlinked_mentions(&path)
+        .map_err(i18n::error_message)?;
+    Ok(mentions.into_iter().map(UnlinkedMentionDTO::from).collect())
+}
+
+/// Turns one unlinked mention in `source` into a wiki-link to `target`.
+#[tauri::command]
+fn link_mention(
+    source: String,
+    start: usize,
+    end: usize,
+    target: String,
+    queue: State<Arc<WriteQueue>>,
+    tree: State<Arc<TreeEvents>>,
+    app: AppHandle,
+) -> Result<i64, String> {
+    let revision = queue
+        .run(move |api| api.link_mention(&source, start..end, &target))
+        .map_err(i18n::error_message)?;
+    tree.emit(&app);
+    Ok(revision)
+}
+
+/// Returns a `[Title](url)` link for a pasted URL, or the bare URL if title fetching
+/// is disabled in the vault config or the page couldn't be fetched in time. Note URLs
+/// copied from the app become `[[wiki-links]]`.
+#[tauri::command]
+async fn link_for_pasted_url(url: String, state: State<'_, AppState>) -> Result<String, String> {
+    if let Some(path) = parse_note_url(&url) {
+        return Ok(wiki_link(&path));
+    }
+
+    let paste_config = {
+        let api = state.notes_api.lock().unwrap();
+        api.config().paste.clone()
+    };
+
+    if !paste_config.fetch_url_titles || !url_titles::is_bare_url(&url) {
+        return Ok(url);
+    }
+
+    let timeout = std::time::Duration::from_millis(paste_config.url_title_timeout_ms);
+    let fetch_url = url.clone();
+    let title = tauri::async_runtime::spawn_blocking(move || {
+        url_titles::fetch_url_title(&fetch_url, timeout)
+    })
+    .await
+    .map_err(|e| format!("{:?}", e))?
+    .unwrap_or_else(|e| {
+        eprintln!("Failed to fetch title for {}: {:?}", url, e);
+        None
+    });
+
+    Ok(url_titles::format_link(url.trim(), title.as_deref()))
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let mut api =
-        NotesApi::with_default_path(cfg!(debug_assertions)).expect("Failed to initialize NotesApi");
-    api.startup_sync().expect("Failed to sync notes database");
+    let debug = cfg!(debug_assertions);
+    // `--read-only` opens the vault for viewing only (e.g. a backup or shared drive)
+    let read_only = std::env::args().any(|arg| arg == "--read-only");
+    // `--safe-mode` (or ZINNIA_SAFE_MODE) opens read-only with the watcher and background
+    // jobs off and skips the startup sync, to get past a vault that crashes on launch
+    let safe_mode = std::env::args().any(|arg| arg == "--safe-mode")
+        || std::env::var_os("ZINNIA_SAFE_MODE").is_some_and(|value| value != "0");
+    let notes_root = get_default_notes_path(debug).expect("Could not determine default notes path");
+    let mut api = if read_only || safe_mode {
+        NotesApi::new_read_only(notes_root)
+    } else {
+        NotesApi::new(notes_root)
+    }
+    .expect("Failed to initialize NotesApi");
+    if api.config().diagnostics.crash_reports {
+        install_crash_reporter(api.crash_report_dir(), api.clock());
+    }
+    // Keep edits made while a synced vault's drive is away instead of failing them
+    if !read_only
+        && !safe_mode
+        && let Some(dir) = get_offline_queue_dir()
+        && let Err(e) = api.set_offline_queue(dir)
+    {
+        eprintln!("Failed to set up the offline queue: {:?}", e);
+    }
+    if !read_only
+        && !safe_mode
+        && let Some(dir) = get_sync_state_dir()
+        && let Err(e) = api.set_sync_state_dir(dir)
+    {
+        eprintln!("Failed to set up remote sync: {:?}", e);
+    }
+    i18n::init(api.config().locale.as_deref());
+    if api.startup_report().recovered() {
+        eprintln!(
+            "Recovered from unclean shutdown: {:?}",
+            api.startup_report()
+        );
+    }
+    // With a background sync the UI opens on the existing index, which the write
+    // queue brings up to date once it is running
+    let background_sync = !safe_mode && api.config().startup.background_sync;
+    if safe_mode {
+        eprintln!("Starting in safe mode");
+    } else if !background_sync {
+        api.startup_sync().expect("Failed to sync notes database");
+    }
 
+    let timing = Arc::new(Mutex::new(api.config().timing.clone()));
+    let note_files = Arc::new(Mutex::new(api.note_files()));
     let notes_api = Arc::new(Mutex::new(api));
 
     let state = AppState {
         notes_api: Arc::clone(&notes_api),
+        safe_mode,
+        timing: Arc::clone(&timing),
+        note_files: Arc::clone(&note_files),
     };
+    let tree_events = Arc::new(TreeEvents::new(Arc::clone(&notes_api)));
 
     tauri::Builder::default()
         .plugin(tauri_plugin_updater::Builder::new().build())
@@ -206,23 +3287,164 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(state)
+        .manage(Arc::clone(&tree_events))
+        .manage(Arc::new(blocking::SaveOrder::default()))
+        .manage(SyncGate::default())
+        .manage(perf::PerfStats::default())
         .invoke_handler(tauri::generate_handler![
             create_note,
             get_note,
             save_note,
+            get_note_with_version,
+            save_note_versioned,
+            save_note_if_unchanged,
+            merge_conflict,
             delete_note,
+            delete_notes,
+            move_notes,
+            batch,
             rename_note,
+            move_note,
+            title_from_first_line,
+            duplicate_note,
+            get_template_schema,
+            create_from_template,
+            reorder_children,
+            pin_note,
+            get_vault_lock,
+            get_pending_operations,
+            get_failed_operations,
+            discard_pending_operations,
+            unlock_vault,
+            git_init,
+            get_git_status,
+            get_git_history,
+            git_commit,
+            git_push,
+            git_pull,
+            git_resolve,
+            get_remote_status,
+            configure_remote,
+            sync_now,
+            resolve_sync_conflict,
+            lock_vault,
+            set_note_encrypted,
             get_children,
+            get_children_with_stats,
+            get_note_cover,
+            get_backlinks,
+            get_outgoing_links,
             has_children,
+            get_expanded_paths,
+            compile_subtree,
+            get_startup_report,
+            run_maintenance,
+            get_storage_report,
+            empty_archive,
+            delete_all_checkpoints,
+            create_diagnostics_bundle,
+            record_ipc_timings,
+            get_perf_stats,
+            validate_note,
+            health_check,
+            check_external_links,
+            get_export_formats,
+            render_note,
+            export_notes,
+            import_csv,
+            import_vault,
+            format_text,
+            smart_typography,
+            create_checkpoint,
+            list_checkpoints,
+            get_note_history,
+            get_note_at_checkpoint,
+            get_revision_diff,
+            restore_checkpoint,
+            delete_checkpoint,
+            list_trash,
+            restore_from_trash,
+            empty_trash,
+            open_daily_note,
+            list_daily_notes,
+            set_expanded,
+            set_note_private,
+            set_include_private,
+            collapse_all,
             get_ancestors,
             get_root_notes,
             get_all_notes,
+            get_recent_notes,
+            get_recently_opened,
+            record_note_open,
+            get_favorites,
+            favorite_note,
+            unfavorite_note,
             fuzzy_search_notes,
             search_notes,
+            search_with_matches,
+            suggest_links,
+            warm_cache,
             archive_note,
+            archive_notes,
+            bulk_set_property,
+            get_properties,
+            set_property,
+            query_by_property,
+            get_status,
+            cycle_status,
+            get_tag_tree,
+            get_tags,
+            get_notes_with_tag,
+            rename_tag,
+            rename_property_value,
+            generate_moc,
+            replace_in_notes,
+            rerun_replace,
+            record_search,
+            get_search_history,
+            clear_search_history,
             unarchive_note,
             trash_note,
+            create_canvas,
+            get_canvas,
+            save_canvas,
+            get_board,
+            get_view_data,
+            move_card,
+            set_reminder,
+            list_upcoming_reminders,
+            delete_reminder,
+            export_ics,
+            get_capabilities,
+            get_locale,
+            get_changes_since,
+            get_write_queue_depth,
+            create_access_token,
+            list_access_tokens,
+            revoke_access_token,
+            export_vault_archive,
+            import_vault_archive,
+            get_mentions,
+            get_vault_config,
+            set_vault_config,
+            zoom_in,
+            zoom_out,
+            zoom_reset,
+            set_note_file,
+            set_vault_layout,
+            get_snippets,
+            set_snippet,
+            remove_snippet,
+            expand_snippet,
+            convert_html,
+            link_for_pasted_url,
+            copy_note_link,
+            get_note_preview,
+            get_unlinked_mentions,
+            link_mention,
         ])
         .setup(move |app| {
             let app_handle = app.handle().clone();
@@ -232,31 +3454,108 @@ pub fn run() {
             {
                 let mut api = notes_api.lock().unwrap();
                 api.set_frecency_callback(move || {
-                    if let Err(e) = app_handle_frecency.emit("notes:frecency", ()) {
+                    let event = NotesEvent::FrecencyUpdated;
+                    if let Err(e) = app_handle_frecency.emit(event.name(), ()) {
                         eprintln!("Failed to emit frecency event: {:?}", e);
                     }
                 });
             }
 
-            // Setup filesystem watcher with event emission
-            let _watcher = setup_watcher(
+            // Open at the vault's saved zoom
+            let ui_scale = notes_api.lock().unwrap().config().appearance.ui_scale;
+            if let Some(window) = app.get_webview_window("main")
+                && let Err(e) = window.set_zoom(ui_scale)
+            {
+                eprintln!("Failed to apply the UI scale: {:?}", e);
+            }
+
+            // Serialize writes: UI saves run ahead of watcher syncs and rescans
+            let (notes_root, ingest_dir, clock) = {
+                let api = notes_api.lock().unwrap();
+                (
+                    api.notes_root().to_path_buf(),
+                    api.ingest_dir(),
+                    api.clock(),
+                )
+            };
+            let reminder_api = Arc::clone(&notes_api);
+            let write_queue = Arc::new(WriteQueue::new(
                 notes_api,
                 Some(move |event| {
-                    let event_name = match event {
-                        WatcherEvent::NotesChanged => "notes:changed",
-                        WatcherEvent::NotesRenamed => "notes:renamed",
-                        WatcherEvent::FrecencyUpdated => "notes:frecency",
-                    };
-
-                    // Emit event to frontend
-                    if let Err(e) = app_handle.emit(event_name, ()) {
+                    // Bulk events carry no change: the frontend reloads everything
+                    if let Err(e) = app_handle.emit(event.name(), event.change()) {
                         eprintln!("Failed to emit watcher event: {:?}", e);
                     }
+
+                    // This runs on the queue worker while it holds the API lock, so the
+                    // tree diff is computed on another thread
+                    let tree_events = Arc::clone(&tree_events);
+                    let app_handle = app_handle.clone();
+                    std::thread::spawn(move || tree_events.emit(&app_handle));
                 }),
-            );
+                Arc::clone(&clock),
+            ));
+            app.manage(Arc::clone(&write_queue));
+            if background_sync {
+                write_queue.enqueue(BackgroundTask::StartupSync);
+            }
+            // Edits queued before the app last quit with the vault away
+            write_queue.enqueue(BackgroundTask::ReplayPending);
+
+            // Safe mode runs nothing in the background
+            if safe_mode {
+                return Ok(());
+            }
+
+            // Fire OS notifications for due reminders
+            reminders::spawn_reminder_scheduler(app.handle().clone(), Arc::clone(&reminder_api));
+
+            // Hold back full rescans while on battery or under load
+            let power = power::PowerPolicy::default();
+            write_queue.set_heavy_work_gate(move || power.allows_heavy_work());
+            spawn_maintenance_scheduler(Arc::downgrade(&write_queue), Arc::clone(&clock));
+            // Does nothing until git is turned on for the vault
+            spawn_git_scheduler(Arc::downgrade(&write_queue), Arc::clone(&clock));
+
+            // Setup filesystem watcher feeding the write queue
+            let watcher = Arc::new(Mutex::new(setup_queued_watcher(
+                &notes_root,
+                note_files,
+                Arc::clone(&write_queue),
+                timing,
+                Arc::clone(&clock),
+            )));
+
+            // Report when the notes root goes away (unmounted drive, deleted folder)
+            // and rescan once it is back
+            let app_handle_vault = app.handle().clone();
+            let vault_queue = Arc::clone(&write_queue);
+            spawn_availability_monitor(&notes_root, Arc::downgrade(&watcher), move |event| {
+                if event == NotesEvent::VaultAvailable {
+                    // Offline edits go first so the rescan indexes them
+                    vault_queue.enqueue(BackgroundTask::ReplayPending);
+                    vault_queue.enqueue(BackgroundTask::Rescan);
+                }
+                if let Err(e) = app_handle_vault.emit(event.name(), ()) {
+                    eprintln!("Failed to emit vault event: {:?}", e);
+                }
+            });
 
             // Keep watcher alive for app lifetime
-            app.manage(_watcher);
+            app.manage(watcher);
+
+            // Hot folder: ingest whatever is already waiting, then watch for more.
+            // Changing the folder takes effect on the next launch.
+            if let Some(ingest_dir) = ingest_dir {
+                write_queue.enqueue(BackgroundTask::Ingest);
+                match setup_ingest_watcher(&ingest_dir, Arc::clone(&write_queue), clock) {
+                    // Kept alive for the app lifetime, like the notes watcher
+                    Ok(ingest_watcher) => {
+                        app.manage(Mutex::new(ingest_watcher));
+                    }
+                    Err(e) => eprintln!("Failed to watch ingest folder: {:?}", e),
+                }
+            }
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -0,0 +1,176 @@
+//! Timings of IPC commands since launch, for diagnosing "the app feels slow" reports.
+//!
+//! The frontend times each command from `invoke` to its reply and sends the samples
+//! here in batches. Timing on this side would only see async commands being
+//! dispatched, not finished, and the round trip is what the user waits for anyway.
+//! Commands slower than `SLOW_COMMAND` are logged as their samples come in.
+//! Payload sizes cost the frontend a `JSON.stringify`, so only a share of samples
+//! (and every slow one) carry them.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Round trips at least this long are logged
+pub const SLOW_COMMAND: Duration = Duration::from_millis(250);
+
+/// Durations kept per command for percentiles; older ones are dropped
+const MAX_SAMPLES: usize = 1000;
+
+/// One command round trip, as measured by the frontend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub duration: Duration,
+    /// JSON size of the arguments, if measured
+    pub request_bytes: Option<u64>,
+    /// JSON size of the reply (or the error), if measured
+    pub response_bytes: Option<u64>,
+    pub ok: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandStats {
+    pub command: String,
+    pub count: u64,
+    pub errors: u64,
+    /// Over the newest `MAX_SAMPLES` calls
+    pub p50: Duration,
+    pub p95: Duration,
+    /// Over the samples that carried sizes; zero if none did
+    pub mean_request_bytes: u64,
+    pub mean_response_bytes: u64,
+}
+
+#[derive(Default)]
+struct CommandTimings {
+    count: u64,
+    errors: u64,
+    /// Samples that carried sizes
+    sized: u64,
+    request_bytes: u64,
+    response_bytes: u64,
+    recent: VecDeque<Duration>,
+}
+
+#[derive(Default)]
+pub struct PerfStats {
+    commands: Mutex<HashMap<String, CommandTimings>>,
+}
+
+impl PerfStats {
+    pub fn record(&self, command: &str, sample: Sample) {
+        let sizes = sample.request_bytes.zip(sample.response_bytes);
+        if sample.duration >= SLOW_COMMAND {
+            let sizes = sizes.map_or_else(String::new, |(request, response)| {
+                format!(" ({} bytes in, {} bytes out)", request, response)
+            });
+            eprintln!(
+                "Slow command {}: {} ms{}",
+                command,
+                sample.duration.as_millis(),
+                sizes
+            );
+        }
+
+        let mut commands = self.commands.lock().unwrap();
+        let timings = commands.entry(command.to_string()).or_default();
+        timings.count += 1;
+        timings.errors += u64::from(!sample.ok);
+        if let Some((request, response)) = sizes {
+            timings.sized += 1;
+            timings.request_bytes += request;
+            timings.response_bytes += response;
+        }
+        if timings.recent.len() == MAX_SAMPLES {
+            timings.recent.pop_front();
+        }
+        timings.recent.push_back(sample.duration);
+    }
+
+    /// Per-command stats, slowest (by p95) first.
+    pub fn stats(&self) -> Vec<CommandStats> {
+        let commands = self.commands.lock().unwrap();
+        let mut stats: Vec<CommandStats> = commands
+            .iter()
+            .map(|(command, timings)| {
+                let mut sorted: Vec<Duration> = timings.recent.iter().copied().collect();
+                sorted.sort();
+                CommandStats {
+                    command: command.clone(),
+                    count: timings.count,
+                    errors: timings.errors,
+                    p50: percentile(&sorted, 0.5),
+                    p95: percentile(&sorted, 0.95),
+                    mean_request_bytes: timings
+                        .request_bytes
+                        .checked_div(timings.sized)
+                        .unwrap_or(0),
+                    mean_response_bytes: timings
+                        .response_bytes
+                        .checked_div(timings.sized)
+                        .unwrap_or(0),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| b.p95.cmp(&a.p95).then_with(|| a.command.cmp(&b.command)));
+        stats
+    }
+}
+
+/// Nearest-rank percentile of sorted durations; zero when there are none.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(ms: u64) -> Sample {
+        Sample {
+            duration: Duration::from_millis(ms),
+            request_bytes: Some(10),
+            response_bytes: Some(100),
+            ok: ms != 0,
+        }
+    }
+
+    #[test]
+    fn test_percentile() {
+        let sorted: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&sorted, 0.5), Duration::from_millis(50));
+        assert_eq!(percentile(&sorted, 0.95), Duration::from_millis(95));
+        assert_eq!(percentile(&sorted[..1], 0.95), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 0.5), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_stats_per_command() {
+        let perf = PerfStats::default();
+        for ms in [0, 2, 4, 6] {
+            perf.record("get_note", sample(ms));
+        }
+        perf.record("search", sample(300));
+        perf.record(
+            "get_note",
+            Sample {
+                request_bytes: None,
+                response_bytes: None,
+                ..sample(1)
+            },
+        );
+
+        let stats = perf.stats();
+        assert_eq!(stats[0].command, "search");
+        assert_eq!(stats[1].command, "get_note");
+        assert_eq!(stats[1].count, 5);
+        assert_eq!(stats[1].errors, 1);
+        assert_eq!(stats[1].p50, Duration::from_millis(2));
+        assert_eq!(stats[1].p95, Duration::from_millis(6));
+        assert_eq!(stats[1].mean_response_bytes, 100);
+    }
+}
@@ -0,0 +1,245 @@
+//! Power and load awareness for background work.
+//!
+//! Heavy background work (full rescans) is held back while the machine runs on
+//! battery or is busy; the write queue asks `PowerPolicy` before starting it. Status
+//! comes from the OS without extra dependencies: sysfs and `/proc/loadavg` on Linux,
+//! `pmset`/`sysctl` on macOS, and `GetSystemPowerStatus` on Windows. Anything that
+//! can't be determined counts as "plugged in, idle". Work is held back for at most
+//! 15 minutes (see `WriteQueue::set_heavy_work_gate`), so the index doesn't stay
+//! stale for a whole session on battery.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a power reading is reused before asking the OS again
+const STATUS_TTL: Duration = Duration::from_secs(60);
+
+/// One-minute load average per CPU above which the system counts as busy
+const HIGH_LOAD_PER_CPU: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PowerStatus {
+    /// `None` if the power source is unknown (e.g. a desktop without a battery)
+    pub on_battery: Option<bool>,
+    /// One-minute load average divided by the CPU count
+    pub load_per_cpu: Option<f64>,
+}
+
+impl PowerStatus {
+    pub fn read() -> Self {
+        Self {
+            on_battery: platform::on_battery(),
+            load_per_cpu: platform::load_average().map(|load| {
+                let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+                load / cpus as f64
+            }),
+        }
+    }
+
+    pub fn allows_heavy_work(&self) -> bool {
+        self.on_battery != Some(true)
+            && self
+                .load_per_cpu
+                .is_none_or(|load| load < HIGH_LOAD_PER_CPU)
+    }
+}
+
+/// Decides whether heavy background work may run now, caching the OS reading.
+#[derive(Default)]
+pub struct PowerPolicy {
+    cached: Mutex<Option<(Instant, PowerStatus)>>,
+}
+
+impl PowerPolicy {
+    pub fn status(&self) -> PowerStatus {
+        let mut cached = self.cached.lock().unwrap();
+        match *cached {
+            Some((read_at, status)) if read_at.elapsed() < STATUS_TTL => status,
+            _ => {
+                let status = PowerStatus::read();
+                *cached = Some((Instant::now(), status));
+                status
+            }
+        }
+    }
+
+    pub fn allows_heavy_work(&self) -> bool {
+        self.status().allows_heavy_work()
+    }
+}
+
+/// Parses the first field of `/proc/loadavg` or `sysctl -n vm.loadavg` output.
+fn parse_load_average(raw: &str) -> Option<f64> {
+    raw.split_whitespace()
+        .find(|field| *field != "{")
+        .and_then(|field| field.parse().ok())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs;
+    use std::path::Path;
+
+    pub fn on_battery() -> Option<bool> {
+        on_battery_in(Path::new("/sys/class/power_supply"))
+    }
+
+    /// Reads power supplies under a sysfs directory: on battery when a battery is
+    /// discharging and no mains supply is online.
+    pub(super) fn on_battery_in(dir: &Path) -> Option<bool> {
+        let mut has_battery = false;
+        let mut discharging = false;
+        for entry in fs::read_dir(dir).ok()?.flatten() {
+            let read = |name: &str| {
+                fs::read_to_string(entry.path().join(name))
+                    .map(|s| s.trim().to_string())
+                    .unwrap_or_default()
+            };
+            match read("type").as_str() {
+                "Mains" if read("online") == "1" => return Some(false),
+                "Battery" => {
+                    has_battery = true;
+                    discharging |= read("status") == "Discharging";
+                }
+                _ => {}
+            }
+        }
+        has_battery.then_some(discharging)
+    }
+
+    pub fn load_average() -> Option<f64> {
+        super::parse_load_average(&fs::read_to_string("/proc/loadavg").ok()?)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::process::Command;
+
+    fn output(program: &str, args: &[&str]) -> Option<String> {
+        let output = Command::new(program).args(args).output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    pub fn on_battery() -> Option<bool> {
+        // First line: "Now drawing from 'Battery Power'" or "'AC Power'"
+        let raw = output("pmset", &["-g", "batt"])?;
+        let first = raw.lines().next()?;
+        if first.contains("'Battery Power'") {
+            Some(true)
+        } else if first.contains("'AC Power'") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub fn load_average() -> Option<f64> {
+        super::parse_load_average(&output("sysctl", &["-n", "vm.loadavg"])?)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemPowerStatus {
+        ac_line_status: u8,
+        battery_flag: u8,
+        battery_life_percent: u8,
+        system_status_flag: u8,
+        battery_life_time: u32,
+        battery_full_life_time: u32,
+    }
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+
+    pub fn on_battery() -> Option<bool> {
+        let mut status = SystemPowerStatus::default();
+        // SAFETY: the pointer is to a live, correctly laid out SYSTEM_POWER_STATUS
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+            return None;
+        }
+        match status.ac_line_status {
+            0 => Some(true),
+            1 => Some(false),
+            _ => None,
+        }
+    }
+
+    pub fn load_average() -> Option<f64> {
+        // Windows has no load average
+        None
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod platform {
+    pub fn on_battery() -> Option<bool> {
+        None
+    }
+
+    pub fn load_average() -> Option<f64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_load_average() {
+        assert_eq!(
+            parse_load_average("0.52 0.58 0.59 1/467 12345\n"),
+            Some(0.52)
+        );
+        assert_eq!(parse_load_average("{ 2.10 1.95 1.80 }\n"), Some(2.10));
+        assert_eq!(parse_load_average(""), None);
+    }
+
+    #[test]
+    fn test_allows_heavy_work() {
+        let unknown = PowerStatus::default();
+        assert!(unknown.allows_heavy_work());
+
+        let on_battery = PowerStatus {
+            on_battery: Some(true),
+            load_per_cpu: Some(0.1),
+        };
+        assert!(!on_battery.allows_heavy_work());
+
+        let busy = PowerStatus {
+            on_battery: Some(false),
+            load_per_cpu: Some(1.5),
+        };
+        assert!(!busy.allows_heavy_work());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_linux_power_supplies() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let supply = |name: &str, files: &[(&str, &str)]| {
+            let path = dir.path().join(name);
+            std::fs::create_dir(&path).unwrap();
+            for (file, value) in files {
+                std::fs::write(path.join(file), format!("{}\n", value)).unwrap();
+            }
+        };
+
+        assert_eq!(platform::on_battery_in(dir.path()), None);
+
+        supply("BAT0", &[("type", "Battery"), ("status", "Discharging")]);
+        assert_eq!(platform::on_battery_in(dir.path()), Some(true));
+
+        supply("AC", &[("type", "Mains"), ("online", "1")]);
+        assert_eq!(platform::on_battery_in(dir.path()), Some(false));
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+use zinnia_core::NotesApi;
+
+use crate::{app_events, i18n};
+
+/// How often the scheduler checks for due reminders
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Payload of the `notes:reminder` event, used by the webview to offer opening the note
+#[derive(Clone, Serialize)]
+struct ReminderFiredPayload {
+    id: i64,
+    path: String,
+    message: String,
+}
+
+/// Spawns a background thread that fires OS notifications for due reminders.
+///
+/// Each fired reminder is also emitted as a `notes:reminder` event carrying the note
+/// path, so the frontend can offer to open the note without leaving the one being edited.
+pub fn spawn_reminder_scheduler(app_handle: AppHandle, notes_api: Arc<Mutex<NotesApi>>) {
+    let clock = notes_api.lock().unwrap().clock();
+    std::thread::spawn(move || {
+        loop {
+            let due = match notes_api.lock() {
+                Ok(mut api) => api.take_due_reminders(clock.now()),
+                Err(_) => break,
+            };
+
+            match due {
+                Ok(reminders) => {
+                    for reminder in reminders {
+                        let title = if reminder.path.is_empty() {
+                            i18n::tr("reminder-title", &[])
+                        } else {
+                            reminder.path.clone()
+                        };
+
+                        if let Err(e) = app_handle
+                            .notification()
+                            .builder()
+                            .title(title)
+                            .body(&reminder.message)
+                            .show()
+                        {
+                            eprintln!("Failed to show reminder notification: {:?}", e);
+                        }
+
+                        let payload = ReminderFiredPayload {
+                            id: reminder.id,
+                            path: reminder.path,
+                            message: reminder.message,
+                        };
+                        if let Err(e) = app_handle.emit(app_events::REMINDER, payload) {
+                            eprintln!("Failed to emit reminder event: {:?}", e);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Failed to check reminders: {:?}", e),
+            }
+
+            clock.sleep(POLL_INTERVAL);
+        }
+    });
+}
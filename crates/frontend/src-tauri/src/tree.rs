@@ -0,0 +1,109 @@
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use zinnia_core::{NotesApi, TreeChange, TreeDiff, TreeSubscription};
+
+use crate::{NoteMetadataDTO, app_events};
+
+/// Payload of the `notes:tree` event
+#[derive(Clone, Serialize)]
+pub struct TreeDiffDTO {
+    from_revision: i64,
+    revision: i64,
+    reset: bool,
+    changes: Vec<TreeChangeDTO>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum TreeChangeDTO {
+    Added {
+        path: String,
+        note: Option<NoteMetadataDTO>,
+    },
+    Removed {
+        path: String,
+    },
+    Moved {
+        from: String,
+        to: String,
+        note: Option<NoteMetadataDTO>,
+    },
+    Retitled {
+        from: String,
+        to: String,
+        note: Option<NoteMetadataDTO>,
+    },
+}
+
+impl From<TreeChange> for TreeChangeDTO {
+    fn from(change: TreeChange) -> Self {
+        match change {
+            TreeChange::Added { path, note } => TreeChangeDTO::Added {
+                path,
+                note: note.map(|n| n.into()),
+            },
+            TreeChange::Removed { path } => TreeChangeDTO::Removed { path },
+            TreeChange::Moved { from, to, note } => TreeChangeDTO::Moved {
+                from,
+                to,
+                note: note.map(|n| n.into()),
+            },
+            TreeChange::Retitled { from, to, note } => TreeChangeDTO::Retitled {
+                from,
+                to,
+                note: note.map(|n| n.into()),
+            },
+        }
+    }
+}
+
+impl From<TreeDiff> for TreeDiffDTO {
+    fn from(diff: TreeDiff) -> Self {
+        TreeDiffDTO {
+            from_revision: diff.from_revision,
+            revision: diff.revision,
+            reset: diff.reset,
+            changes: diff.changes.into_iter().map(|c| c.into()).collect(),
+        }
+    }
+}
+
+/// Emits `notes:tree` events carrying what changed in the note tree since the last emit.
+pub struct TreeEvents {
+    notes_api: Arc<Mutex<NotesApi>>,
+    subscription: Mutex<TreeSubscription>,
+}
+
+impl TreeEvents {
+    pub fn new(notes_api: Arc<Mutex<NotesApi>>) -> Self {
+        let subscription = {
+            let api = notes_api.lock().unwrap();
+            TreeSubscription::new(&api).expect("Failed to read tree revision")
+        };
+        Self {
+            notes_api,
+            subscription: Mutex::new(subscription),
+        }
+    }
+
+    /// Emits the pending tree diff, if any. Must not be called while holding the API lock.
+    pub fn emit(&self, app_handle: &AppHandle) {
+        let diff = {
+            let api = self.notes_api.lock().unwrap();
+            let mut subscription = self.subscription.lock().unwrap();
+            subscription.poll(&api)
+        };
+
+        match diff {
+            Ok(Some(diff)) => {
+                if let Err(e) = app_handle.emit(app_events::TREE, TreeDiffDTO::from(diff)) {
+                    eprintln!("Failed to emit tree diff: {:?}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to compute tree diff: {:?}", e),
+        }
+    }
+}
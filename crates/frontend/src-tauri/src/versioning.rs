@@ -0,0 +1,156 @@
+//! Conflict-aware loading and saving for the editor.
+//!
+//! The webview loads a note with `get_note_with_version`, keeps the returned hash, and
+//! saves through `save_note_versioned`. A `conflict` outcome carries the content that
+//! is on disk now, so the editor can offer to reload or keep its own version instead
+//! of silently overwriting an external edit, or to merge the two in the merge editor
+//! (see `merge_conflict`).
+
+use serde::Serialize;
+use std::time::UNIX_EPOCH;
+use zinnia_core::{
+    HunkKind, Merge, MergeHunk, NotesApi, Result, SaveOutcome, SavedNote, VersionedNote,
+};
+
+use crate::NoteDTO;
+
+#[derive(Serialize)]
+pub struct VersionedNoteDTO {
+    note: NoteDTO,
+    content_hash: String,
+}
+
+impl From<VersionedNote> for VersionedNoteDTO {
+    fn from(versioned: VersionedNote) -> Self {
+        VersionedNoteDTO {
+            note: versioned.note.into(),
+            content_hash: versioned.content_hash,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum SaveOutcomeDTO {
+    Saved {
+        revision: i64,
+        content_hash: String,
+    },
+    Conflict {
+        revision: i64,
+        content: String,
+        content_hash: String,
+    },
+}
+
+impl SaveOutcomeDTO {
+    pub fn is_saved(&self) -> bool {
+        matches!(self, SaveOutcomeDTO::Saved { .. })
+    }
+}
+
+impl From<SaveOutcome> for SaveOutcomeDTO {
+    fn from(outcome: SaveOutcome) -> Self {
+        match outcome {
+            SaveOutcome::Saved {
+                revision,
+                content_hash,
+            } => SaveOutcomeDTO::Saved {
+                revision,
+                content_hash,
+            },
+            SaveOutcome::Conflict {
+                content,
+                content_hash,
+                revision,
+            } => SaveOutcomeDTO::Conflict {
+                revision,
+                content,
+                content_hash,
+            },
+        }
+    }
+}
+
+/// Result of `save_note_if_unchanged`; the error's disk content becomes `conflict`
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum SaveIfUnchangedDTO {
+    Saved {
+        revision: i64,
+        modified: u64,
+        modified_nanos: u32,
+    },
+    Conflict {
+        content: String,
+    },
+}
+
+impl From<SavedNote> for SaveIfUnchangedDTO {
+    fn from(saved: SavedNote) -> Self {
+        let modified = saved
+            .modified
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        SaveIfUnchangedDTO::Saved {
+            revision: saved.revision,
+            modified: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct MergeHunkDTO {
+    kind: &'static str,
+    base: Vec<String>,
+    mine: Vec<String>,
+    theirs: Vec<String>,
+}
+
+impl From<MergeHunk> for MergeHunkDTO {
+    fn from(hunk: MergeHunk) -> Self {
+        MergeHunkDTO {
+            kind: match hunk.kind {
+                HunkKind::Unchanged => "unchanged",
+                HunkKind::Mine => "mine",
+                HunkKind::Theirs => "theirs",
+                HunkKind::Both => "both",
+                HunkKind::Conflict => "conflict",
+            },
+            base: hunk.base,
+            mine: hunk.mine,
+            theirs: hunk.theirs,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct MergeDTO {
+    hunks: Vec<MergeHunkDTO>,
+    trailing_newline: bool,
+}
+
+impl From<Merge> for MergeDTO {
+    fn from(merge: Merge) -> Self {
+        MergeDTO {
+            hunks: merge.hunks.into_iter().map(|hunk| hunk.into()).collect(),
+            trailing_newline: merge.trailing_newline,
+        }
+    }
+}
+
+pub fn get_note_with_version(api: &mut NotesApi, path: &str) -> Result<VersionedNoteDTO> {
+    api.get_note_with_version(path)
+        .map(|versioned| versioned.into())
+}
+
+pub fn save_note_versioned(
+    api: &mut NotesApi,
+    path: &str,
+    content: &str,
+    base_hash: &str,
+) -> Result<SaveOutcomeDTO> {
+    api.save_note_versioned(path, content, base_hash)
+        .map(|outcome| outcome.into())
+}
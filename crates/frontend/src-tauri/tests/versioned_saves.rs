@@ -0,0 +1,73 @@
+//! The load → edit → save flow the editor uses to avoid overwriting external edits.
+
+use serde_json::{Value, json};
+use tempfile::TempDir;
+use zinnia_core::NotesApi;
+use zinnia_frontend_lib::versioning::{get_note_with_version, save_note_versioned};
+
+fn setup() -> (TempDir, NotesApi) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut api = NotesApi::new(temp_dir.path()).unwrap();
+    api.create_note("journal").unwrap();
+    api.save_note("journal", "# Journal\n").unwrap();
+    (temp_dir, api)
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> Value {
+    serde_json::to_value(value).unwrap()
+}
+
+#[test]
+fn save_with_loaded_hash_succeeds() {
+    let (_temp_dir, mut api) = setup();
+
+    let loaded = to_json(get_note_with_version(&mut api, "journal").unwrap());
+    assert_eq!(loaded["note"]["content"], "# Journal\n");
+    let base = loaded["content_hash"].as_str().unwrap().to_string();
+
+    let saved =
+        to_json(save_note_versioned(&mut api, "journal", "# Journal\nDay one\n", &base).unwrap());
+    assert_eq!(saved["status"], "saved");
+
+    // The returned hash is the base for the next autosave
+    let next_base = saved["content_hash"].as_str().unwrap().to_string();
+    let saved = to_json(
+        save_note_versioned(&mut api, "journal", "# Journal\nDay two\n", &next_base).unwrap(),
+    );
+    assert_eq!(saved["status"], "saved");
+}
+
+#[test]
+fn external_edit_returns_conflict_then_reload_and_keep_mine() {
+    let (temp_dir, mut api) = setup();
+
+    let loaded = to_json(get_note_with_version(&mut api, "journal").unwrap());
+    let base = loaded["content_hash"].as_str().unwrap().to_string();
+
+    std::fs::write(
+        temp_dir.path().join("journal/_index.md"),
+        "# Journal\nFrom my phone\n",
+    )
+    .unwrap();
+
+    let outcome =
+        to_json(save_note_versioned(&mut api, "journal", "# Journal\nMine\n", &base).unwrap());
+    assert_eq!(outcome["status"], "conflict");
+    assert_eq!(outcome["content"], json!("# Journal\nFrom my phone\n"));
+
+    // Retrying with the stale base keeps conflicting
+    let server_hash = outcome["content_hash"].as_str().unwrap().to_string();
+    let stale =
+        to_json(save_note_versioned(&mut api, "journal", "# Journal\nMine\n", &base).unwrap());
+    assert_eq!(stale["status"], "conflict");
+
+    // Keep mine: save again on top of the content the user has now seen
+    let kept = to_json(
+        save_note_versioned(&mut api, "journal", "# Journal\nMine\n", &server_hash).unwrap(),
+    );
+    assert_eq!(kept["status"], "saved");
+    assert_eq!(
+        std::fs::read_to_string(temp_dir.path().join("journal/_index.md")).unwrap(),
+        "# Journal\nMine\n"
+    );
+}
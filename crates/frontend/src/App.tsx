@@ -1,16 +1,48 @@
-import { onMount, createSignal, onCleanup } from "solid-js";
+import {
+  onMount,
+  createSignal,
+  createEffect,
+  on,
+  onCleanup,
+  Show,
+} from "solid-js";
 import { NotesProvider, useNotes } from "./api";
 import { Navigation } from "./components/Navigation";
 import EditorManager from "./components/editor/EditorManager";
 import { ToastProvider, useToast } from "./components/ui/Toast";
 import { NoteFinder } from "./components/ui/NoteFinder";
+import { ReplaceModal } from "./components/ui/ReplaceModal";
+import { SearchPanel } from "./components/ui/SearchPanel";
 import { Settings } from "./components/ui/Settings";
+import { LiveRegion } from "./components/ui/LiveRegion";
 import { checkForUpdates } from "./utils/updater";
 import { downloadAndInstallUpdate, restartApp } from "./utils/updater";
 import { getVersion } from "@tauri-apps/api/app";
-import { loadSettings } from "./utils/settings";
+import { getCurrentWebview } from "@tauri-apps/api/webview";
+import { listen } from "@tauri-apps/api/event";
+import { ask } from "@tauri-apps/plugin-dialog";
+import { loadSettings, takeLegacyFontSize } from "./utils/settings";
+import {
+  applyAppearance,
+  stepUiScale,
+  updateAppearance,
+} from "./utils/appearance";
+import { applyEditorConfig } from "./utils/editorConfig";
+import { applyTimingConfig } from "./utils/timing";
+import { commands } from "./api/commands";
 import { getAppState, setAppState } from "./utils/appState";
-import type { NoteMetadata } from "./types";
+import type { NoteMetadata, SchemaWarning } from "./types";
+
+function describeSchemaWarning(warning: SchemaWarning): string {
+  switch (warning.issue) {
+    case "missing":
+      return `${warning.schema}: "${warning.field}" is missing`;
+    case "wrong_kind":
+      return `${warning.schema}: "${warning.field}" should be a ${warning.detail}`;
+    case "not_allowed":
+      return `${warning.schema}: "${warning.detail}" is not allowed for "${warning.field}"`;
+  }
+}
 
 function AppContent() {
   const isDev = import.meta.env.DEV;
@@ -18,6 +50,8 @@ function AppContent() {
   const notes = useNotes();
   const [showNoteFinder, setShowNoteFinder] = createSignal(false);
   const [showSettings, setShowSettings] = createSignal(false);
+  const [showReplace, setShowReplace] = createSignal(false);
+  const [showSearch, setShowSearch] = createSignal(false);
 
   // Global keyboard shortcut for Command+K / Control+K
   const handleKeyDown = (e: KeyboardEvent) => {
@@ -26,11 +60,46 @@ function AppContent() {
       e.preventDefault();
       setShowNoteFinder(true);
     }
+    // Cmd/Ctrl + Shift + F searches inside all notes, listing every match
+    if ((e.metaKey || e.ctrlKey) && e.shiftKey && e.key.toLowerCase() === "f") {
+      e.preventDefault();
+      setShowSearch(true);
+    }
+    // Cmd/Ctrl + Shift + H opens find and replace across all notes
+    if ((e.metaKey || e.ctrlKey) && e.shiftKey && e.key.toLowerCase() === "h") {
+      e.preventDefault();
+      setShowReplace(true);
+    }
+    // Cmd/Ctrl + Shift + D opens today's daily note
+    if ((e.metaKey || e.ctrlKey) && e.shiftKey && e.key.toLowerCase() === "d") {
+      e.preventDefault();
+      commands
+        .openDailyNote()
+        .then((note) => notes.setCurrentPath(note.path))
+        .catch((err) => toast.error(`Failed to open daily note: ${err}`));
+    }
     // Check for Cmd+, (Mac) or Ctrl+, (Windows/Linux)
     if ((e.metaKey || e.ctrlKey) && e.key === ",") {
       e.preventDefault();
       setShowSettings(true);
     }
+    // Cmd/Ctrl + "+", "-", "0" zoom the interface
+    if (e.metaKey || e.ctrlKey) {
+      const direction =
+        e.key === "=" || e.key === "+"
+          ? 1
+          : e.key === "-"
+            ? -1
+            : e.key === "0"
+              ? 0
+              : null;
+      if (direction !== null) {
+        e.preventDefault();
+        stepUiScale(direction).catch((err) =>
+          console.error("Failed to update UI scale:", err),
+        );
+      }
+    }
   };
 
   onMount(async () => {
@@ -43,14 +112,33 @@ function AppContent() {
     const settings = await loadSettings();
     console.log("Loaded settings:", settings);
 
-    // Apply font size setting
-    document.documentElement.style.setProperty(
-      "--text-base",
-      `${settings.fontSize}px`,
-    );
+    const { safe_mode: safeMode } = await commands.getCapabilities();
+    if (safeMode) {
+      toast.info(
+        "Safe mode: the vault is read-only and background sync is off. " +
+          "Restart normally to resume.",
+        { duration: "persistent" },
+      );
+    }
+
+    // Apply the vault's appearance, editor, and timing settings
+    const config = await commands.getVaultConfig();
+    applyAppearance(config.appearance);
+    applyEditorConfig(config.editor);
+    applyTimingConfig(config.timing);
+
+    // Font size used to be an app-wide setting; carry it over to the vault
+    const legacyFontSize = await takeLegacyFontSize();
+    if (legacyFontSize !== null) {
+      await updateAppearance((current) => ({
+        ...current,
+        editor_font_size: legacyFontSize,
+      }));
+    }
 
-    // Open last opened note if setting is enabled and note exists
-    if (settings.openLastNote) {
+    // Open last opened note if setting is enabled and note exists. Skipped in
+    // safe mode in case that note is what keeps crashing the app
+    if (settings.openLastNote && !safeMode) {
       const lastOpenedNote = await getAppState("lastOpenedNote");
       if (lastOpenedNote) {
         notes.setCurrentPath(lastOpenedNote);
@@ -81,7 +169,7 @@ function AppContent() {
 
     // Function to check for updates and show toast if available
     const performUpdateCheck = async () => {
-      if (!settings.autoCheckUpdates) return;
+      if (!settings.autoCheckUpdates || safeMode) return;
 
       const update = await checkForUpdates();
       if (update) {
@@ -115,6 +203,90 @@ function AppContent() {
     });
   });
 
+  // Tell the user when the notes folder disappears or comes back
+  createEffect(
+    on(
+      notes.available,
+      (available, wasAvailable) => {
+        if (!available) {
+          toast.error(
+            notes.editable()
+              ? "Notes folder is unavailable. Edits are kept until it returns."
+              : "Notes folder is unavailable. Editing is paused.",
+          );
+        } else if (wasAvailable === false) {
+          toast.success("Notes folder is available again");
+        }
+      },
+      { defer: true },
+    ),
+  );
+
+  // CSV/TSV files dropped on the window are imported into the current note
+  const importDroppedTables = async (paths: string[]) => {
+    const tables = paths.filter((p) => /\.(csv|tsv)$/i.test(p));
+    const target = notes.currentPath();
+    if (tables.length === 0 || !target || !notes.editable()) return;
+
+    const asNotes = await ask(
+      "Create a child note for each row, or insert the data as a table?",
+      { okLabel: "Child notes", cancelLabel: "Table" },
+    );
+    for (const file of tables) {
+      try {
+        const result = await commands.importCsv(
+          { file },
+          target,
+          asNotes ? "child_notes" : "table",
+        );
+        toast.success(`${result.rows} rows imported`, { duration: "short" });
+      } catch (err) {
+        console.error("Failed to import table:", err);
+        toast.error(`Failed to import: ${err}`);
+      }
+    }
+    if (!asNotes) notes.refetchCurrent();
+  };
+
+  onMount(async () => {
+    const unlisten = await getCurrentWebview().onDragDropEvent((event) => {
+      if (event.payload.type === "drop") {
+        importDroppedTables(event.payload.paths);
+      }
+    });
+    onCleanup(unlisten);
+  });
+
+  // Offer to open a reminder's note rather than switching away from what's being
+  // edited
+  onMount(async () => {
+    const unlisten = await listen<{ path: string; message: string }>(
+      "notes:reminder",
+      (event) => {
+        const { path, message } = event.payload;
+        toast.info(message || `Reminder for ${path}`, {
+          actionLabel: "Open",
+          onAction: () => notes.setCurrentPath(path),
+          duration: "persistent",
+        });
+      },
+    );
+    onCleanup(unlisten);
+  });
+
+  // Point out frontmatter that breaks the vault's schema after a save
+  createEffect(
+    on(
+      () => notes.schemaWarnings().map(describeSchemaWarning).join("; "),
+      (message, previous) => {
+        if (message && message !== previous) {
+          toast.info(message, { duration: "long" });
+        }
+      },
+      { defer: true },
+    ),
+  );
+
   const handleNoteSelect = (note: NoteMetadata) => {
     notes.setCurrentPath(note.path);
   };
@@ -125,6 +297,43 @@ function AppContent() {
       <div class="mt-14 flex flex-1">
         <EditorManager />
       </div>
+      <Show when={notes.pendingEdits() > 0 || notes.failedEdits() > 0}>
+        <div
+          role="status"
+          class="fixed right-3 bottom-2 z-50 flex gap-2 text-xs opacity-60"
+        >
+          <Show when={notes.pendingEdits() > 0}>
+            <span>
+              {notes.pendingEdits() === 1
+                ? "1 edit waiting for the notes folder"
+                : `${notes.pendingEdits()} edits waiting for the notes folder`}
+            </span>
+          </Show>
+          <Show when={notes.failedEdits() > 0}>
+            <span>
+              {notes.failedEdits() === 1
+                ? "1 queued edit couldn't be applied"
+                : `${notes.failedEdits()} queued edits couldn't be applied`}
+            </span>
+          </Show>
+          <button
+            type="button"
+            class="hover:underline"
+            onClick={async () => {
+              if (!(await ask("Discard these edits? Their text is lost."))) {
+                return;
+              }
+              try {
+                await notes.discardPendingEdits();
+              } catch (error) {
+                toast.error(String(error));
+              }
+            }}
+          >
+            Discard
+          </button>
+        </div>
+      </Show>
       {isDev && (
         <div class="pointer-events-none fixed top-[9.5px] left-[80px] z-50 text-xs opacity-30">
           DEV BUILD
@@ -135,8 +344,15 @@ function AppContent() {
         onSelect={handleNoteSelect}
         onClose={() => setShowNoteFinder(false)}
         placeholder="Search notes..."
+        recordHistory
+      />
+      <ReplaceModal
+        open={showReplace()}
+        onClose={() => setShowReplace(false)}
       />
+      <SearchPanel open={showSearch()} onClose={() => setShowSearch(false)} />
       <Settings open={showSettings()} onClose={() => setShowSettings(false)} />
+      <LiveRegion />
     </div>
   );
 }
@@ -5,21 +5,43 @@ import {
   createSignal,
   createResource,
   createMemo,
+  createEffect,
   onCleanup,
+  untrack,
   type ParentProps,
   type Resource,
   type Accessor,
 } from "solid-js";
 import { listen } from "@tauri-apps/api/event";
 import { commands } from "./commands";
-import type { Note, NoteMetadata } from "../types";
+import type {
+  Note,
+  NoteChange,
+  NoteMoved,
+  NoteMetadata,
+  SchemaWarning,
+  TreeDiff,
+} from "../types";
+import { applyTreeDiff } from "../utils/tree";
 import { setAppState } from "../utils/appState";
 
 interface NotesContextValue {
+  // Vault capabilities (false while a read-only vault is open)
+  editable: Accessor<boolean>;
+  // False while the notes folder is missing (e.g. an unmounted drive)
+  available: Accessor<boolean>;
+  // Edits made while the notes folder was missing, not written to it yet
+  pendingEdits: Accessor<number>;
+  // Queued edits that couldn't be applied once the folder was back
+  failedEdits: Accessor<number>;
+  discardPendingEdits: () => Promise<void>;
+
   // Current note state
   currentNote: Resource<Note | undefined>;
   currentPath: Accessor<string>;
   setCurrentPath: (path: string) => void;
+  // Frontmatter schema problems in the current note, updated on every save
+  schemaWarnings: Accessor<SchemaWarning[]>;
 
   // Navigation
   children: Resource<NoteMetadata[]>;
@@ -85,6 +107,9 @@ export function NotesProvider(props: ParentProps) {
       setAppState("lastOpenedNote", path).catch((err) =>
         console.error("Failed to save last opened note:", err),
       );
+      commands
+        .recordNoteOpen(path)
+        .catch((err) => console.error("Failed to record note open:", err));
     }
   };
 
@@ -110,6 +135,34 @@ export function NotesProvider(props: ParentProps) {
     }
   };
 
+  // Read-only vaults disable editing affordances across the UI
+  const [capabilities, { refetch: refetchCapabilities }] = createResource(
+    commands.getCapabilities,
+  );
+  const editable = () => capabilities()?.editable ?? true;
+  const available = () => capabilities()?.available ?? true;
+
+  const [pendingOperations, { refetch: refetchPending }] = createResource(
+    commands.getPendingOperations,
+  );
+  const pendingEdits = () => pendingOperations()?.length ?? 0;
+  const [failedOperations, { refetch: refetchFailed }] = createResource(
+    commands.getFailedOperations,
+  );
+  const failedEdits = () => failedOperations()?.length ?? 0;
+  const discardPendingEdits = async () => {
+    await commands.discardPendingOperations();
+    refetchPending();
+    refetchFailed();
+  };
+
+  // Queued edits aren't announced, so check on them while the folder is away
+  createEffect(() => {
+    if (available()) return;
+    const timer = setInterval(refetchPending, 2000);
+    onCleanup(() => clearInterval(timer));
+  });
+
   // Search state
   const [searchQuery, setSearchQuery] = createSignal("");
 
@@ -119,7 +172,8 @@ export function NotesProvider(props: ParentProps) {
     commands.getNote,
   );
 
-  const [children, { refetch: refetchChildren }] = createResource(
+  const [children, { refetch: refetchChildren, mutate: mutateChildren }] =
+    createResource(
     currentPath,
     commands.getChildren,
   );
@@ -129,9 +183,8 @@ export function NotesProvider(props: ParentProps) {
     commands.getAncestors,
   );
 
-  const [rootNotes, { refetch: refetchRootNotes }] = createResource(
-    commands.getRootNotes,
-  );
+  const [rootNotes, { refetch: refetchRootNotes, mutate: mutateRootNotes }] =
+    createResource(commands.getRootNotes);
 
   const [searchResults] = createResource(
     // Only fetch when query is not empty
@@ -139,15 +192,17 @@ export function NotesProvider(props: ParentProps) {
       const query = searchQuery();
       return query.trim() ? query : null;
     }),
-    (query) => commands.searchNotes(query),
+    // A lone #tag lists the notes tagged with it or below it
+    (query) =>
+      /^#[\p{L}\p{N}_\-/]+$/u.test(query.trim())
+        ? commands.getNotesWithTag(query.trim())
+        : commands.searchNotes(query, untrack(currentPath)),
   );
 
   // Mutation functions with automatic refetching
+  // Tree lists are kept up to date by notes:tree events
   const createNote = async (path: string) => {
-    const note = await commands.createNote(path);
-    refetchRootNotes();
-    refetchChildren();
-    return note;
+    return commands.createNote(path);
   };
 
   const saveNote = async (path: string, content: string) => {
@@ -162,8 +217,6 @@ export function NotesProvider(props: ParentProps) {
     if (path === currentPath()) {
       setCurrentPath("");
     }
-    refetchChildren();
-    refetchRootNotes();
   };
 
   const renameNote = async (oldPath: string, newPath: string) => {
@@ -171,51 +224,93 @@ export function NotesProvider(props: ParentProps) {
     if (currentPath() === oldPath) {
       setCurrentPath(newPath);
     }
-    refetchChildren();
     refetchAncestors();
   };
 
   const archiveNote = async (path: string) => {
     await commands.archiveNote(path);
-    refetchChildren();
     refetchCurrent();
   };
 
   const unarchiveNote = async (path: string) => {
     await commands.unarchiveNote(path);
-    refetchChildren();
     refetchCurrent();
   };
 
+  // Schema warnings by note path, as reported by the last save of each note
+  const [warningsByPath, setWarningsByPath] = createSignal<
+    Record<string, SchemaWarning[]>
+  >({});
+  const schemaWarnings = createMemo(
+    () => warningsByPath()[currentPath()] ?? [],
+  );
+
   // Listen for filesystem watcher events from Tauri backend
   const setupWatcherListeners = async () => {
-    // Listen for note changes (create, update, delete)
-    // The backend now uses content hash comparison, so this event only fires
-    // when content actually changes (not on our own saves with identical content)
-    const unlistenChanged = await listen("notes:changed", () => {
-      console.log(
-        "File watcher detected external changes, reloading current note...",
-      );
-      // Force reload by toggling the path
+    // Force reload by toggling the path
+    const reloadCurrent = () => {
       const path = currentPath();
       if (path) {
         setCurrentPath("");
         // Use setTimeout to ensure the effect runs twice
         setTimeout(() => setCurrentPath(path), 0);
       }
-    });
+    };
+
+    // Listen for note changes (create, update, delete)
+    // The backend now uses content hash comparison, so this event only fires
+    // when content actually changes (not on our own saves with identical content)
+    const unlistenChanged = await listen<NoteChange | null>(
+      "notes:changed",
+      (event) => {
+        const change = event.payload;
+        // Without a payload any note may have changed
+        if (!change) {
+          // Replaying offline edits reports this way too
+          refetchPending();
+          refetchFailed();
+          reloadCurrent();
+          return;
+        }
+        // Other notes only affect the tree, which notes:tree keeps current
+        if (change.kind === "renamed" || change.path !== currentPath()) {
+          return;
+        }
+        if (change.kind === "deleted") {
+          setCurrentPath("");
+        } else if (change.kind === "modified") {
+          console.log("File watcher detected external changes, reloading...");
+          reloadCurrent();
+        }
+      },
+    );
 
     // Listen for note renames/moves
-    const unlistenRenamed = await listen("notes:renamed", () => {
-      console.log(
-        "File watcher detected external rename/move, reloading current note...",
-      );
-      // Force reload by toggling the path
+    const unlistenRenamed = await listen<NoteChange | null>(
+      "notes:renamed",
+      (event) => {
+        const change = event.payload;
+        if (change?.kind !== "renamed") {
+          reloadCurrent();
+          return;
+        }
+        // Follow the open note (or its ancestor) to its new location
+        const path = currentPath();
+        if (path === change.from || path.startsWith(`${change.from}/`)) {
+          setCurrentPath(change.to + path.slice(change.from.length));
+        }
+        refetchAncestors();
+      },
+    );
+
+    // Follow the open note (or its ancestor) when moveNote moves it
+    const unlistenMoved = await listen<NoteMoved>("notes:moved", (event) => {
+      const { from, to } = event.payload;
       const path = currentPath();
-      if (path) {
-        setCurrentPath("");
-        setTimeout(() => setCurrentPath(path), 0);
+      if (path === from || path.startsWith(`${from}/`)) {
+        setCurrentPath(to + path.slice(from.length));
       }
+      refetchAncestors();
     });
 
     // Listen for frecency updates
@@ -225,11 +320,68 @@ export function NotesProvider(props: ParentProps) {
       refetchRootNotes();
     });
 
+    // Apply structural changes to the loaded lists instead of refetching them
+    const unlistenTree = await listen<TreeDiff>("notes:tree", (event) => {
+      const diff = event.payload;
+      if (diff.reset) {
+        refetchChildren();
+        refetchRootNotes();
+        return;
+      }
+      const current = children();
+      if (current) {
+        mutateChildren(applyTreeDiff(current, currentPath(), diff));
+      }
+      const roots = rootNotes();
+      if (roots) {
+        mutateRootNotes(applyTreeDiff(roots, "", diff));
+      }
+    });
+
+    // The notes folder went away; stop editing until it comes back
+    const unlistenVaultUnavailable = await listen(
+      "notes:vault-unavailable",
+      () => {
+        refetchCapabilities();
+      },
+    );
+
+    // The notes folder is back and being rescanned
+    const unlistenVaultAvailable = await listen("notes:vault-available", () => {
+      refetchCapabilities();
+      refetchPending();
+      refetchFailed();
+      refetchCurrent();
+      refetchChildren();
+      refetchRootNotes();
+    });
+
+    // The background startup sync finished; reload what came from the old index
+    const unlistenIndexRefreshed = await listen("notes:index-refreshed", () => {
+      refetchCurrent();
+      refetchChildren();
+      refetchRootNotes();
+    });
+
+    const unlistenSchemaWarnings = await listen<{
+      path: string;
+      warnings: SchemaWarning[];
+    }>("notes:schema-warnings", (event) => {
+      const { path, warnings } = event.payload;
+      setWarningsByPath((current) => ({ ...current, [path]: warnings }));
+    });
+
     // Cleanup listeners when component unmounts
     onCleanup(() => {
       unlistenChanged();
       unlistenRenamed();
+      unlistenMoved();
       unlistenFrecency();
+      unlistenTree();
+      unlistenSchemaWarnings();
+      unlistenVaultUnavailable();
+      unlistenVaultAvailable();
+      unlistenIndexRefreshed();
     });
   };
 
@@ -237,9 +389,15 @@ export function NotesProvider(props: ParentProps) {
   setupWatcherListeners();
 
   const value: NotesContextValue = {
+    editable,
+    available,
+    pendingEdits,
+    failedEdits,
+    discardPendingEdits,
     currentNote,
     currentPath,
     setCurrentPath,
+    schemaWarnings,
     children,
     ancestors,
     rootNotes,
@@ -1,51 +1,618 @@
 // Tauri command bindings for notes API
-import { invoke } from "@tauri-apps/api/core";
-import type { Note, NoteMetadata } from "../types";
+import { invoke } from "./ipc";
+import type {
+  AccessLevel,
+  AccessToken,
+  ArchiveSummary,
+  Board,
+  BulkPropertyEdit,
+  Canvas,
+  Capabilities,
+  Changes,
+  Checkpoint,
+  CommandPerf,
+  CsvImport,
+  CsvImportMode,
+  DailyNote,
+  DiffLine,
+  ExportFormat,
+  FormatAction,
+  GitCommit,
+  GitStatus,
+  HealthReport,
+  HistoryKind,
+  LinkReport,
+  LinkSuggestion,
+  MaintenanceReport,
+  Merge,
+  MocUpdate,
+  MoveCollision,
+  Note,
+  NoteLink,
+  NoteMetadata,
+  NoteMoved,
+  NoteOp,
+  NotePreview,
+  NoteSelection,
+  NoteStatus,
+  PendingOp,
+  PropertyValue,
+  PullOutcome,
+  QueueDepth,
+  Reminder,
+  RemoteStatus,
+  ReplaceEdit,
+  SaveIfUnchanged,
+  SaveOutcome,
+  SchemaWarning,
+  SearchHistoryEntry,
+  SearchResult,
+  Snippet,
+  StartupReport,
+  StorageReport,
+  SyncReport,
+  TagNode,
+  TagRename,
+  TemplateNote,
+  TemplateSchema,
+  TextEdit,
+  TrashedNote,
+  TypographyReplacement,
+  UnlinkedMention,
+  VaultConfig,
+  VaultImport,
+  VaultLayout,
+  VaultLock,
+  VersionedNote,
+  ViewData,
+} from "../types";
 
 export type RankingMode = "visits" | "frecency";
 
+// Starts from the clock so numbers keep growing across reloads of the window
+let saveSeq = Date.now();
+const nextSaveSeq = () => ++saveSeq;
+
 export const commands = {
+  getCapabilities: () => invoke<Capabilities>("get_capabilities"),
+
+  getLocale: () => invoke<string>("get_locale"),
+
+  getStartupReport: () => invoke<StartupReport>("get_startup_report"),
+
+  // Resolves to null when skipped because a note was saved recently
+  runMaintenance: () => invoke<MaintenanceReport | null>("run_maintenance"),
+
+  getStorageReport: () => invoke<StorageReport>("get_storage_report"),
+
+  // Null when nothing was archived
+  emptyArchive: () => invoke<number | null>("empty_archive"),
+
+  deleteAllCheckpoints: () => invoke<number>("delete_all_checkpoints"),
+
+  // Returns how many crash reports were included
+  createDiagnosticsBundle: (dest: string) =>
+    invoke<number>("create_diagnostics_bundle", { dest }),
+
+  // Round trip per command since launch, slowest first
+  getPerfStats: () => invoke<CommandPerf[]>("get_perf_stats"),
+
+  // Empty when the note conforms or no schema applies to it
+  validateNote: (path: string) =>
+    invoke<SchemaWarning[]>("validate_note", { path }),
+
+  healthCheck: () => invoke<HealthReport>("health_check"),
+
+  // Requests every http(s) link below `scope` ("" for the whole vault)
+  checkExternalLinks: (scope: string) =>
+    invoke<LinkReport>("check_external_links", { scope }),
+
+  createAccessToken: (name: string, access: AccessLevel, subtree?: string) =>
+    invoke<AccessToken>("create_access_token", { name, access, subtree }),
+
+  listAccessTokens: () => invoke<AccessToken[]>("list_access_tokens"),
+
+  revokeAccessToken: (id: number) =>
+    invoke<void>("revoke_access_token", { id }),
+
+  getChangesSince: (since: number) =>
+    invoke<Changes>("get_changes_since", { since }),
+
+  getWriteQueueDepth: () => invoke<QueueDepth>("get_write_queue_depth"),
+
   createNote: (path: string) => invoke<Note>("create_note", { path }),
 
   getNote: (path: string) => invoke<Note>("get_note", { path }),
 
+  // Numbered so the backend can drop a save that overtakes a newer one
   saveNote: (path: string, content: string) =>
-    invoke<void>("save_note", { path, content }),
+    invoke<number>("save_note", { path, content, seq: nextSaveSeq() }),
 
-  deleteNote: (path: string) => invoke<void>("delete_note", { path }),
+  getNoteWithVersion: (path: string) =>
+    invoke<VersionedNote>("get_note_with_version", { path }),
+
+  saveNoteVersioned: (path: string, content: string, baseHash: string) =>
+    invoke<SaveOutcome>("save_note_versioned", { path, content, baseHash }),
+
+  // Saves unless the note changed on disk since it was read; pass the note's
+  // `modified` and `modified_nanos`
+  saveNoteIfUnchanged: (
+    path: string,
+    content: string,
+    expectedModified: number,
+    expectedModifiedNanos: number,
+  ) =>
+    invoke<SaveIfUnchanged>("save_note_if_unchanged", {
+      path,
+      content,
+      expectedModified,
+      expectedModifiedNanos,
+    }),
+
+  // Three-way merge for the merge editor after a versioned save conflicts
+  mergeConflict: (base: string, mine: string, theirs: string) =>
+    invoke<Merge>("merge_conflict", { base, mine, theirs }),
+
+  deleteNote: (path: string) => invoke<number>("delete_note", { path }),
 
   renameNote: (oldPath: string, newPath: string) =>
-    invoke<void>("rename_note", { oldPath, newPath }),
+    invoke<number>("rename_note", { oldPath, newPath }),
+
+  // Moves the note under newParent ("" for the root), keeping its name
+  moveNote: (path: string, newParent: string, collision?: MoveCollision) =>
+    invoke<NoteMoved>("move_note", { path, newParent, collision }),
+
+  // Names an untitled note after its first H1 or line; null if nothing changed
+  titleFromFirstLine: (path: string) =>
+    invoke<NoteMoved | null>("title_from_first_line", { path }),
+
+  // Copies the note, and its descendants if recursive, in one go
+  duplicateNote: (path: string, newPath: string, recursive: boolean) =>
+    invoke<number>("duplicate_note", { path, newPath, recursive }),
+
+  getTemplateSchema: (templatePath: string) =>
+    invoke<TemplateSchema>("get_template_schema", { templatePath }),
+
+  // Values are keyed by variable number; missing ones get their label
+  createFromTemplate: (
+    path: string,
+    templatePath: string,
+    values: Record<number, string>,
+  ) =>
+    invoke<TemplateNote>("create_from_template", {
+      path,
+      templatePath,
+      values,
+    }),
+
+  // Manual order of a parent's children ("" for the root notes)
+  reorderChildren: (parent: string, orderedPaths: string[]) =>
+    invoke<void>("reorder_children", { parent, orderedPaths }),
+
+  pinNote: (path: string, pinned: boolean) =>
+    invoke<void>("pin_note", { path, pinned }),
+
+  getVaultLock: () => invoke<VaultLock>("get_vault_lock"),
+
+  // Edits waiting for the notes folder to come back, oldest first
+  getPendingOperations: () =>
+    invoke<PendingOp[]>("get_pending_operations"),
+
+  // Queued edits that couldn't be applied once the folder was back
+  getFailedOperations: () => invoke<PendingOp[]>("get_failed_operations"),
+
+  // Drops queued and failed edits without writing them
+  discardPendingOperations: () => invoke<void>("discard_pending_operations"),
+
+  // The first unlock sets the vault's passphrase
+  unlockVault: (passphrase: string) =>
+    invoke<void>("unlock_vault", { passphrase }),
+
+  lockVault: () => invoke<void>("lock_vault"),
 
-  getChildren: (path: string) =>
-    invoke<NoteMetadata[]>("get_children", { path }),
+  // Encrypts the note at rest, or stores it as plain text again
+  setNoteEncrypted: (path: string, encrypted: boolean) =>
+    invoke<number>("set_note_encrypted", { path, encrypted }),
+
+  // Makes the vault a git repository and commits everything in it
+  gitInit: () => invoke<void>("git_init"),
+
+  getGitStatus: () => invoke<GitStatus>("get_git_status"),
+
+  getGitHistory: (path: string) =>
+    invoke<GitCommit[]>("get_git_history", { path }),
+
+  // Commits now; returns the commit id, or null if nothing changed
+  gitCommit: (message?: string) =>
+    invoke<string | null>("git_commit", { message: message ?? null }),
+
+  gitPush: () => invoke<void>("git_push"),
+
+  gitPull: () => invoke<PullOutcome>("git_pull"),
+
+  // Once its conflict markers are edited away; true when the merge finished
+  gitResolve: (path: string) => invoke<boolean>("git_resolve", { path }),
+
+  getRemoteStatus: () => invoke<RemoteStatus>("get_remote_status"),
+
+  // A null url turns sync off; a null password keeps the stored one
+  configureRemote: (
+    url: string | null,
+    username: string,
+    password: string | null,
+  ) => invoke<void>("configure_remote", { url, username, password }),
+
+  // Progress is reported as notes:sync-progress events
+  syncNow: () => invoke<SyncReport>("sync_now"),
+
+  // The next sync copies the kept side over the other
+  resolveSyncConflict: (path: string, keep: "local" | "remote") =>
+    invoke<void>("resolve_sync_conflict", { path, keep }),
+
+  // With detail, each child includes its stats (same as getChildrenWithStats)
+  getChildren: (path: string, detail = false) =>
+    invoke<NoteMetadata[]>("get_children", { path, detail }),
+
+  getChildrenWithStats: (path: string) =>
+    invoke<NoteMetadata[]>("get_children_with_stats", { path }),
+
+  getBacklinks: (path: string) =>
+    invoke<NoteMetadata[]>("get_backlinks", { path }),
+
+  // Includes links to notes that don't exist yet
+  getOutgoingLinks: (path: string) =>
+    invoke<string[]>("get_outgoing_links", { path }),
+
+  // Cover image bytes; wrap in a Blob for an object URL
+  getNoteCover: (path: string) =>
+    invoke<ArrayBuffer>("get_note_cover", { path }),
 
   hasChildren: (path: string) => invoke<boolean>("has_children", { path }),
 
+  getExpandedPaths: () => invoke<string[]>("get_expanded_paths"),
+
+  compileSubtree: (
+    path: string,
+    options?: { maxDepth?: number; includeArchived?: boolean },
+  ) =>
+    invoke<string>("compile_subtree", {
+      path,
+      maxDepth: options?.maxDepth ?? null,
+      includeArchived: options?.includeArchived ?? null,
+    }),
+
+  setExpanded: (path: string, expanded: boolean) =>
+    invoke<void>("set_expanded", { path, expanded }),
+
+  setNotePrivate: (path: string, isPrivate: boolean) =>
+    invoke<number>("set_note_private", { path, private: isPrivate }),
+
+  // Private notes are hidden from search, the finder, and exports until set
+  setIncludePrivate: (include: boolean) =>
+    invoke<void>("set_include_private", { include }),
+
+  collapseAll: () => invoke<void>("collapse_all"),
+
   getAncestors: (path: string) =>
     invoke<NoteMetadata[]>("get_ancestors", { path }),
 
+  getAncestorsWithStats: (path: string) =>
+    invoke<NoteMetadata[]>("get_ancestors", { path, detail: true }),
+
   getRootNotes: () => invoke<NoteMetadata[]>("get_root_notes"),
 
+  getRootNotesWithStats: () =>
+    invoke<NoteMetadata[]>("get_root_notes", { detail: true }),
+
   getAllNotes: () => invoke<NoteMetadata[]>("get_all_notes"),
 
+  // Most recently modified first
+  getRecentNotes: (limit = 20) =>
+    invoke<NoteMetadata[]>("get_recent_notes", { limit }),
+
+  // Most recently opened first, for quick-switcher lists
+  getRecentlyOpened: (limit = 20) =>
+    invoke<NoteMetadata[]>("get_recently_opened", { limit }),
+
+  recordNoteOpen: (path: string) => invoke<void>("record_note_open", { path }),
+
+  // Starred notes, oldest star first
+  getFavorites: () => invoke<NoteMetadata[]>("get_favorites"),
+
+  favoriteNote: (path: string) => invoke<void>("favorite_note", { path }),
+
+  unfavoriteNote: (path: string) => invoke<void>("unfavorite_note", { path }),
+
+  // With currentPath, notes near it and recently opened ones rank higher
   fuzzySearchNotes: (
     query: string,
     limit?: number,
     rankingMode?: RankingMode,
+    currentPath?: string,
   ) =>
     invoke<NoteMetadata[]>("fuzzy_search_notes", {
       query,
       limit,
       rankingMode: rankingMode || "visits",
+      currentPath: currentPath || null,
+    }),
+
+  searchNotes: (query: string, currentPath?: string) =>
+    invoke<NoteMetadata[]>("search_notes", {
+      query,
+      currentPath: currentPath || null,
+    }),
+
+  // Notes for [[ autocompletion in the note at contextPath, best first:
+  // title/alias matches, then nearby and recently opened notes
+  suggestLinks: (query: string, contextPath: string, limit = 10) =>
+    invoke<LinkSuggestion[]>("suggest_links", { query, contextPath, limit }),
+
+  // Preloads notes about to be shown (e.g. an opened panel) into memory
+  warmCache: (paths: string[]) => invoke<number>("warm_cache", { paths }),
+
+  // Like searchNotes, with each note's matching lines in context
+  searchWithMatches: (query: string, currentPath?: string) =>
+    invoke<SearchResult[]>("search_with_matches", {
+      query,
+      currentPath: currentPath || null,
     }),
 
-  searchNotes: (query: string) =>
-    invoke<NoteMetadata[]>("search_notes", { query }),
+  archiveNote: (path: string) => invoke<number>("archive_note", { path }),
+
+  unarchiveNote: (path: string) =>
+    invoke<number>("unarchive_note", { path }),
+
+  trashNote: (path: string) => invoke<number>("trash_note", { path }),
+
+  archiveNotes: (paths: string[]) =>
+    invoke<number>("archive_notes", { paths }),
+
+  deleteNotes: (paths: string[]) => invoke<number>("delete_notes", { paths }),
+
+  moveNotes: (paths: string[], destParent: string) =>
+    invoke<number>("move_notes", { paths, destParent }),
+
+  // Create/save/rename/delete/archive steps applied in order as one batch; if a
+  // step fails, the ones before it are taken back
+  batch: (ops: NoteOp[]) => invoke<number>("batch", { ops }),
+
+  createCanvas: (path: string) => invoke<Canvas>("create_canvas", { path }),
+
+  getCanvas: (path: string) => invoke<Canvas>("get_canvas", { path }),
+
+  saveCanvas: (path: string, data: unknown) =>
+    invoke<number>("save_canvas", { path, data }),
+
+  getBoard: (path: string) => invoke<Board>("get_board", { path }),
+
+  getViewData: (path: string) => invoke<ViewData>("get_view_data", { path }),
+
+  moveCard: (path: string, toColumn: string) =>
+    invoke<number>("move_card", { path, toColumn }),
+
+  setReminder: (path: string, remindAt: number, message: string) =>
+    invoke<Reminder>("set_reminder", { path, remindAt, message }),
+
+  listUpcomingReminders: () => invoke<Reminder[]>("list_upcoming_reminders"),
+
+  deleteReminder: (id: number) => invoke<void>("delete_reminder", { id }),
+
+  exportIcs: (scope: string, dest?: string) =>
+    invoke<string>("export_ics", { scope, dest }),
+
+  getExportFormats: () => invoke<ExportFormat[]>("get_export_formats"),
+
+  renderNote: (path: string, format: string) =>
+    invoke<string>("render_note", { path, format }),
 
-  archiveNote: (path: string) => invoke<void>("archive_note", { path }),
+  // Imports a CSV/TSV file or pasted table text into `target`
+  importCsv: (
+    source: { file: string } | { content: string },
+    target: string,
+    mode: CsvImportMode,
+  ) => invoke<CsvImport>("import_csv", { ...source, target, mode }),
 
-  unarchiveNote: (path: string) => invoke<void>("unarchive_note", { path }),
+  // Markdown folders such as Obsidian vaults; a dry run only reports
+  importVault: (srcDir: string, target: string, dryRun: boolean) =>
+    invoke<VaultImport>("import_vault", { srcDir, target, dryRun }),
+
+  // Same Markdown transforms for every editor working on raw text
+  formatText: (
+    text: string,
+    selectionStart: number,
+    selectionEnd: number,
+    action: FormatAction,
+  ) =>
+    invoke<TextEdit>("format_text", {
+      text,
+      selectionStart,
+      selectionEnd,
+      action,
+    }),
+
+  smartTypography: (before: string, typed: string) =>
+    invoke<TypographyReplacement | null>("smart_typography", {
+      before,
+      typed,
+    }),
+
+  // A null value removes the property; dryRun only previews the changes
+  bulkSetProperty: (
+    selection: NoteSelection,
+    key: string,
+    value: PropertyValue | null,
+    dryRun: boolean,
+  ) =>
+    invoke<BulkPropertyEdit>("bulk_set_property", {
+      selection,
+      key,
+      value,
+      dryRun,
+    }),
+
+  // Frontmatter as [key, value] pairs in file order
+  getProperties: (path: string) =>
+    invoke<[string, PropertyValue][]>("get_properties", { path }),
+
+  // A null value removes the property; returns the new revision
+  setProperty: (path: string, key: string, value: PropertyValue | null) =>
+    invoke<number>("set_property", { path, key, value }),
+
+  getStatus: (path: string) =>
+    invoke<NoteStatus | null>("get_status", { path }),
+
+  // None, todo, doing, done, and back to none; returns the new status
+  cycleStatus: (path: string) =>
+    invoke<NoteStatus | null>("cycle_status", { path }),
+
+  // Matches list items too, ignoring case
+  queryByProperty: (key: string, value: string) =>
+    invoke<NoteMetadata[]>("query_by_property", { key, value }),
+
+  getTagTree: () => invoke<TagNode[]>("get_tag_tree"),
+
+  // Every tag path in use, parents included, sorted
+  getTags: () => invoke<string[]>("get_tags"),
+
+  // Includes notes tagged below `tag`
+  getNotesWithTag: (tag: string) =>
+    invoke<NoteMetadata[]>("get_notes_with_tag", { tag }),
+
+  // Nested tags are renamed along with it; a dry run only lists the notes
+  renameTag: (oldTag: string, newTag: string, dryRun: boolean) =>
+    invoke<TagRename>("rename_tag", { old: oldTag, new: newTag, dryRun }),
+
+  // Renames a frontmatter value in every note, including inside lists
+  renamePropertyValue: (
+    key: string,
+    oldValue: string,
+    newValue: string,
+    dryRun: boolean,
+  ) =>
+    invoke<BulkPropertyEdit>("rename_property_value", {
+      key,
+      old: oldValue,
+      new: newValue,
+      dryRun,
+    }),
+
+  // Links every note below `source` (or tagged with it, for "#tag") from a
+  // generated list in `target`; the rest of `target` is left alone
+  generateMoc: (source: string, target: string) =>
+    invoke<MocUpdate>("generate_moc", { source, target }),
+
+  // Literal, case-sensitive replace across all notes; dryRun only previews
+  replaceInNotes: (find: string, replacement: string, dryRun: boolean) =>
+    invoke<ReplaceEdit>("replace_in_notes", { find, replacement, dryRun }),
+
+  rerunReplace: (id: number, dryRun: boolean) =>
+    invoke<ReplaceEdit>("rerun_replace", { id, dryRun }),
+
+  recordSearch: (query: string) => invoke<void>("record_search", { query }),
+
+  // Newest first; all kinds when `kind` is omitted
+  getSearchHistory: (kind?: HistoryKind) =>
+    invoke<SearchHistoryEntry[]>("get_search_history", { kind }),
+
+  clearSearchHistory: () => invoke<void>("clear_search_history"),
+
+  exportNotes: (paths: string[], format: string, dest: string) =>
+    invoke<number>("export_notes", { paths, format, dest }),
+
+  createCheckpoint: (label: string) =>
+    invoke<Checkpoint>("create_checkpoint", { label }),
+
+  listCheckpoints: () => invoke<Checkpoint[]>("list_checkpoints"),
+
+  restoreCheckpoint: (id: string) =>
+    invoke<void>("restore_checkpoint", { id }),
+
+  deleteCheckpoint: (id: string) => invoke<void>("delete_checkpoint", { id }),
+
+  // Checkpoints holding a new version of the note, newest first
+  getNoteHistory: (path: string) =>
+    invoke<Checkpoint[]>("get_note_history", { path }),
+
+  getNoteAtCheckpoint: (path: string, id: string) =>
+    invoke<string>("get_note_at_checkpoint", { path, id }),
+
+  // A null checkpoint id stands for the note's current content
+  getRevisionDiff: (path: string, from: string | null, to: string | null) =>
+    invoke<DiffLine[]>("get_revision_diff", { path, from, to }),
+
+  listTrash: () => invoke<TrashedNote[]>("list_trash"),
+
+  restoreFromTrash: (path: string) =>
+    invoke<number>("restore_from_trash", { path }),
+
+  // Permanently deletes trashed notes older than this, or all when omitted
+  emptyTrash: (olderThanSecs?: number) =>
+    invoke<number>("empty_trash", { olderThanSecs }),
+
+  // The daily note for a YYYY-MM-DD date (default today), created if needed
+  openDailyNote: (date?: string) => invoke<Note>("open_daily_note", { date }),
+
+  // Days between two YYYY-MM-DD dates (inclusive) that have a daily note
+  listDailyNotes: (from: string, to: string) =>
+    invoke<DailyNote[]>("list_daily_notes", { from, to }),
+
+  exportVaultArchive: (dest: string) =>
+    invoke<ArchiveSummary>("export_vault_archive", { dest }),
+
+  importVaultArchive: (src: string) =>
+    invoke<ArchiveSummary>("import_vault_archive", { src }),
+
+  getMentions: (personPath: string) =>
+    invoke<NoteMetadata[]>("get_mentions", { personPath }),
+
+  getVaultConfig: () => invoke<VaultConfig>("get_vault_config"),
+
+  setVaultConfig: (config: VaultConfig) =>
+    invoke<void>("set_vault_config", { config }),
+
+  // Step the vault's UI scale and zoom the window; return the new scale
+  zoomIn: () => invoke<number>("zoom_in"),
+  zoomOut: () => invoke<number>("zoom_out"),
+  zoomReset: () => invoke<number>("zoom_reset"),
+
+  // Renames every note file (e.g. to README.md); returns how many were renamed
+  setNoteFile: (name: string) => invoke<number>("set_note_file", { name }),
+
+  // Moves notes into or out of their folders; returns how many were moved
+  setVaultLayout: (layout: VaultLayout) =>
+    invoke<number>("set_vault_layout", { layout }),
+
+  getSnippets: () => invoke<Snippet[]>("get_snippets"),
+
+  setSnippet: (trigger: string, expansion: string) =>
+    invoke<void>("set_snippet", { trigger, expansion }),
+
+  removeSnippet: (trigger: string) =>
+    invoke<void>("remove_snippet", { trigger }),
+
+  expandSnippet: (trigger: string) =>
+    invoke<string | null>("expand_snippet", { trigger }),
+
+  convertHtml: (html: string) => invoke<string>("convert_html", { html }),
+
+  copyNoteLink: (path: string) =>
+    invoke<NoteLink>("copy_note_link", { path }),
+
+  getNotePreview: (path: string) =>
+    invoke<NotePreview>("get_note_preview", { path }),
+
+  getUnlinkedMentions: (path: string) =>
+    invoke<UnlinkedMention[]>("get_unlinked_mentions", { path }),
+
+  linkMention: (mention: UnlinkedMention, target: string) =>
+    invoke<number>("link_mention", {
+      source: mention.source,
+      start: mention.start,
+      end: mention.end,
+      target,
+    }),
 
-  trashNote: (path: string) => invoke<void>("trash_note", { path }),
+  linkForPastedUrl: (url: string) =>
+    invoke<string>("link_for_pasted_url", { url }),
 };
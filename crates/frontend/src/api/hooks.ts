@@ -9,9 +9,13 @@ import {
   type Accessor,
   Setter,
 } from "solid-js";
+import { listen } from "@tauri-apps/api/event";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { commands } from "./commands";
-import type { Note, NoteMetadata } from "../types";
+import { announce } from "../utils/announce";
+import type { Note, NoteChange, NoteMetadata, SaveOutcome } from "../types";
+
+type SaveConflict = Extract<SaveOutcome, { status: "conflict" }>;
 
 /**
  * Hook to fetch a specific note by path
@@ -23,17 +27,20 @@ export function useNote(path: () => string): Resource<Note | undefined> {
 
 /**
  * Hook to load and track note content
- * Returns the note content, loading state, and any errors
+ * Returns the note content, its version hash, loading state, and any errors
  */
 export type NoteContent = {
   content: Accessor<string>;
   setContent: Setter<string>;
+  contentHash: Accessor<string | null>;
+  setContentHash: Setter<string | null>;
   isLoading: Accessor<boolean>;
   error: Accessor<Error | null>;
 };
 
 export function useNoteContent(path: Accessor<string | null>): NoteContent {
   const [content, setContent] = createSignal("");
+  const [contentHash, setContentHash] = createSignal<string | null>(null);
   const [isLoading, setIsLoading] = createSignal(false);
   const [error, setError] = createSignal<Error | null>(null);
 
@@ -42,6 +49,7 @@ export function useNoteContent(path: Accessor<string | null>): NoteContent {
 
     if (!notePath) {
       setContent("");
+      setContentHash(null);
       setError(null);
       return;
     }
@@ -50,8 +58,10 @@ export function useNoteContent(path: Accessor<string | null>): NoteContent {
     setError(null);
 
     try {
-      const note = await commands.getNote(notePath);
+      const { note, content_hash } =
+        await commands.getNoteWithVersion(notePath);
       setContent(note.content);
+      setContentHash(content_hash);
     } catch (err) {
       console.error("Failed to load note:", err);
       setError(err as Error);
@@ -60,34 +70,71 @@ export function useNoteContent(path: Accessor<string | null>): NoteContent {
     }
   });
 
-  return { content, setContent, isLoading, error };
+  return {
+    content,
+    setContent,
+    contentHash,
+    setContentHash,
+    isLoading,
+    error,
+  };
 }
 
 /**
  * Hook to handle autosaving with debounce
  * Tracks save state and provides manual save function
+ *
+ * When a base hash is provided, saves are versioned: if the note changed on
+ * disk since it was loaded, nothing is written and `conflict` holds the disk
+ * content until the user reloads or keeps their version.
  */
 export function useAutoSave(options: {
   getPath: Accessor<string | null>;
   getContent: Accessor<string>;
-  delay?: number;
+  getBaseHash?: Accessor<string | null>;
+  setBaseHash?: (hash: string) => void;
+  // Read each time a save is scheduled, so setting changes apply right away
+  delay?: Accessor<number>;
 }) {
-  const { getPath, getContent, delay = 1000 } = options;
+  const {
+    getPath,
+    getContent,
+    getBaseHash,
+    setBaseHash,
+    delay = () => 1000,
+  } = options;
 
   const [isSaving, setIsSaving] = createSignal(false);
   const [lastSavedContent, setLastSavedContent] = createSignal("");
+  const [conflict, setConflict] = createSignal<SaveConflict | null>(null);
 
   let debounceTimer: number | undefined;
 
+  // Returns whether the content was written
   const performSave = async (path: string, content: string) => {
-    if (!path) return;
+    if (!path) return false;
 
     setIsSaving(true);
     try {
-      await commands.saveNote(path, content);
+      const baseHash = getBaseHash?.();
+      if (baseHash == null || !setBaseHash) {
+        await commands.saveNote(path, content);
+        setLastSavedContent(content);
+        return true;
+      }
+
+      const outcome = await commands.saveNoteVersioned(path, content, baseHash);
+      if (outcome.status === "conflict") {
+        setConflict(outcome);
+        announce("This note was changed outside the editor");
+        return false;
+      }
+      setBaseHash(outcome.content_hash);
       setLastSavedContent(content);
+      return true;
     } catch (err) {
       console.error("Failed to save:", err);
+      return false;
     } finally {
       setIsSaving(false);
     }
@@ -102,7 +149,10 @@ export function useAutoSave(options: {
       debounceTimer = undefined;
     }
 
-    await performSave(path, getContent());
+    // Explicit saves are announced; autosaves would be too chatty
+    if (await performSave(path, getContent())) {
+      announce("Note saved");
+    }
   };
 
   const scheduleAutoSave = (content: string) => {
@@ -110,16 +160,74 @@ export function useAutoSave(options: {
       clearTimeout(debounceTimer);
     }
 
+    // Hold edits back until the conflict is resolved
+    if (conflict()) return;
+
     const path = getPath();
     if (path && content !== lastSavedContent()) {
       debounceTimer = setTimeout(() => {
-        performSave(path, content);
-      }, delay) as unknown as number;
+        // The note may have been renamed in the meantime
+        performSave(getPath() ?? path, content);
+      }, delay()) as unknown as number;
+    }
+  };
+
+  // Saves pending edits now, without announcing it; returns whether
+  // everything is saved
+  const flush = async () => {
+    clearTimeout(debounceTimer);
+    debounceTimer = undefined;
+    const path = getPath();
+    if (!path || conflict() || !hasUnsavedChanges()) {
+      return !conflict();
     }
+    return performSave(path, getContent());
   };
 
   const hasUnsavedChanges = () => getContent() !== lastSavedContent();
 
+  // Drop local edits in favour of the content on disk; returns that content
+  const reloadFromConflict = () => {
+    const current = conflict();
+    if (!current) return null;
+
+    setBaseHash?.(current.content_hash);
+    setLastSavedContent(current.content);
+    setConflict(null);
+    return current.content;
+  };
+
+  // Overwrite the content on disk with the local edits
+  const keepMine = async () => {
+    const current = conflict();
+    if (!current) return;
+
+    setBaseHash?.(current.content_hash);
+    setConflict(null);
+    await forceSave();
+  };
+
+  // Another writer's save of this note was refused, so it changed on disk;
+  // check our version against it to bring up the conflict here too
+  onMount(async () => {
+    const unlisten = await listen<NoteChange>("notes:conflicted", (event) => {
+      const path = getPath();
+      if (
+        !path ||
+        event.payload.kind !== "conflicted" ||
+        event.payload.path !== path ||
+        conflict() ||
+        getBaseHash?.() == null
+      ) {
+        return;
+      }
+      clearTimeout(debounceTimer);
+      debounceTimer = undefined;
+      performSave(path, getContent());
+    });
+    onCleanup(unlisten);
+  });
+
   // Update lastSavedContent when path changes and content loads
   createEffect(() => {
     const path = getPath();
@@ -137,7 +245,13 @@ export function useAutoSave(options: {
   return {
     isSaving,
     hasUnsavedChanges,
+    conflict,
+    // What both sides of a conflict started from
+    lastSavedContent,
+    reloadFromConflict,
+    keepMine,
     forceSave,
+    flush,
     scheduleAutoSave,
     setLastSavedContent,
   };
@@ -0,0 +1,73 @@
+// Tauri's invoke, timing each command round trip for `getPerfStats`. Samples
+// are sent in batches; the backend keeps the stats and logs slow commands.
+// Measuring payload sizes means serializing them again, so only every
+// SIZE_SAMPLE_EVERY-th call and slow calls are measured.
+import { invoke as tauriInvoke, type InvokeArgs } from "@tauri-apps/api/core";
+
+const FLUSH_DELAY = 5000;
+const RECORD_COMMAND = "record_ipc_timings";
+const SIZE_SAMPLE_EVERY = 20;
+// Matches the backend's SLOW_COMMAND, so logged slow commands have sizes
+const SLOW_COMMAND_MS = 250;
+
+interface IpcSample {
+  command: string;
+  duration_ms: number;
+  request_bytes?: number;
+  response_bytes?: number;
+  ok: boolean;
+}
+
+let pending: IpcSample[] = [];
+let flushTimer: number | undefined;
+let calls = 0;
+
+// Approximate: UTF-16 length of the JSON the payload travels as
+const jsonSize = (value: unknown) =>
+  value === undefined ? 0 : (JSON.stringify(value)?.length ?? 0);
+
+const flush = () => {
+  flushTimer = undefined;
+  const samples = pending;
+  pending = [];
+  tauriInvoke(RECORD_COMMAND, { samples }).catch((err) =>
+    console.error("Failed to record command timings:", err),
+  );
+};
+
+const record = (
+  command: string,
+  start: number,
+  args: InvokeArgs | undefined,
+  response: unknown,
+  ok: boolean,
+) => {
+  const duration_ms = performance.now() - start;
+  calls += 1;
+  const measure =
+    calls % SIZE_SAMPLE_EVERY === 0 || duration_ms >= SLOW_COMMAND_MS;
+  pending.push({
+    command,
+    duration_ms,
+    ...(measure
+      ? { request_bytes: jsonSize(args), response_bytes: jsonSize(response) }
+      : {}),
+    ok,
+  });
+  flushTimer ??= window.setTimeout(flush, FLUSH_DELAY);
+};
+
+export async function invoke<T>(
+  command: string,
+  args?: InvokeArgs,
+): Promise<T> {
+  const start = performance.now();
+  try {
+    const result = await tauriInvoke<T>(command, args);
+    record(command, start, args, result, true);
+    return result;
+  } catch (err) {
+    record(command, start, args, err, false);
+    throw err;
+  }
+}
@@ -7,15 +7,28 @@ import {
   onCleanup,
 } from "solid-js";
 import { listen } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import { useNotes } from "../api";
 import { commands } from "../api/commands";
+import { announce } from "../utils/announce";
 import { getPathTitle } from "../utils/paths";
 import { expandMacros } from "../utils/macros";
+import {
+  isCycleStatusKey,
+  STATUS_LABELS,
+  STATUS_MARKS,
+  statusAnnouncement,
+} from "../utils/status";
 import { DropdownMenu } from "./ui/DropdownMenu";
 import { useToast } from "./ui/Toast";
-import type { NoteMetadata } from "../types";
+import type { NoteMetadata, NoteStatus } from "../types";
 
-function Breadcrumb(props: { item: NoteMetadata; isActive: boolean }) {
+// `version` changes when a status shown in the lists changed
+function Breadcrumb(props: {
+  item: NoteMetadata;
+  isActive: boolean;
+  version: number;
+}) {
   const notes = useNotes();
   const toast = useToast();
   const [children, setChildren] = createSignal<NoteMetadata[]>([]);
@@ -33,7 +46,8 @@ function Breadcrumb(props: { item: NoteMetadata; isActive: boolean }) {
 
   createEffect(() => {
     refreshKey(); // Track refresh key
-    commands.getChildren(props.item.path).then(setChildren);
+    props.version; // Track status changes
+    commands.getChildren(props.item.path, true).then(setChildren);
   });
 
   const handleCreateNote = async () => {
@@ -51,7 +65,7 @@ function Breadcrumb(props: { item: NoteMetadata; isActive: boolean }) {
   const [inputRef, setInputRef] = createSignal<HTMLInputElement | null>(null);
 
   const handleClick = () => {
-    if (props.isActive) {
+    if (props.isActive && notes.editable()) {
       // Enter edit mode
       setEditTitle(getPathTitle(props.item.path));
       setIsEditing(true);
@@ -90,13 +104,22 @@ function Breadcrumb(props: { item: NoteMetadata; isActive: boolean }) {
           <button
             class={`hover:bg-button-hover rounded px-2 ${props.isActive ? "" : "opacity-60"}`}
             onClick={handleClick}
+            aria-current={props.isActive ? "page" : undefined}
           >
+            <Show when={props.item.stats?.icon}>
+              {(icon) => (
+                <span class="mr-1" aria-hidden="true">
+                  {icon()}
+                </span>
+              )}
+            </Show>
             {getPathTitle(props.item.path)}
           </button>
         }
       >
         <input
           type="text"
+          dir="auto"
           value={editTitle()}
           onInput={(e) => setEditTitle(e.currentTarget.value)}
           onBlur={handleRename}
@@ -105,6 +128,7 @@ function Breadcrumb(props: { item: NoteMetadata; isActive: boolean }) {
             if (e.key === "Escape") setIsEditing(false);
           }}
           class="bg-transparent px-2 outline-none"
+          aria-label="Note title"
           ref={setInputRef}
         />
       </Show>
@@ -113,8 +137,10 @@ function Breadcrumb(props: { item: NoteMetadata; isActive: boolean }) {
           when={children().length > 0}
           fallback={
             <button
-              class="hover:bg-button-hover rounded px-2 opacity-60"
+              class="hover:bg-button-hover rounded px-2 opacity-60 disabled:cursor-not-allowed disabled:opacity-30"
               onClick={handleCreateNote}
+              disabled={!notes.editable()}
+              aria-label={`New note in ${getPathTitle(props.item.path)}`}
             >
               +
             </button>
@@ -132,7 +158,7 @@ function Breadcrumb(props: { item: NoteMetadata; isActive: boolean }) {
   );
 }
 
-function RootCrumb() {
+function RootCrumb(props: { version: number }) {
   const [rootNotes, setRootNotes] = createSignal<NoteMetadata[]>([]);
   const [refreshKey, setRefreshKey] = createSignal(0);
 
@@ -146,7 +172,8 @@ function RootCrumb() {
 
   createEffect(() => {
     refreshKey(); // Track refresh key
-    commands.getRootNotes().then(setRootNotes);
+    props.version; // Track status changes
+    commands.getRootNotesWithStats().then(setRootNotes);
   });
 
   return (
@@ -160,29 +187,111 @@ function RootCrumb() {
 
 export function Navigation() {
   const notes = useNotes();
+  const toast = useToast();
   const [items, setItems] = createSignal<NoteMetadata[]>([]);
+  const [status, setStatus] = createSignal<NoteStatus | null>(null);
+  const [statusVersion, setStatusVersion] = createSignal(0);
+  // Bumped when the tree changes, since an edit may change a title or icon
+  const [treeVersion, setTreeVersion] = createSignal(0);
+
+  onMount(async () => {
+    const unlisten = await listen("notes:tree", () => {
+      setTreeVersion((v) => v + 1);
+    });
+    onCleanup(unlisten);
+  });
 
   createEffect(() => {
     const path = notes.currentPath();
+    treeVersion();
     if (path) {
-      commands.getAncestors(path).then(setItems);
+      commands.getAncestorsWithStats(path).then(setItems);
+      commands
+        .getStatus(path)
+        .then(setStatus)
+        .catch(() => setStatus(null));
     } else {
       setItems([]);
+      setStatus(null);
+    }
+  });
+
+  // Name the window after the open note, so windows can be told apart in the
+  // task switcher
+  createEffect(() => {
+    const current = items().at(-1);
+    const stats = current?.stats;
+    const title = stats
+      ? [stats.icon, stats.title].filter(Boolean).join(" ")
+      : "Zinnia";
+    getCurrentWindow()
+      .setTitle(title)
+      .catch((err) => console.error("Failed to set window title:", err));
+  });
+
+  const cycleStatus = async () => {
+    const path = notes.currentPath();
+    if (!path || !notes.editable()) return;
+    try {
+      const next = await commands.cycleStatus(path);
+      setStatus(next);
+      setStatusVersion((v) => v + 1);
+      notes.refetchCurrent();
+      announce(statusAnnouncement(next));
+    } catch (err) {
+      console.error("Failed to change status:", err);
+      toast.error(`Failed to change status: ${err}`);
     }
+  };
+
+  // Rows in the tree handle the shortcut for their own note first
+  const handleKeyDown = (e: KeyboardEvent) => {
+    if (isCycleStatusKey(e)) {
+      e.preventDefault();
+      cycleStatus();
+    }
+  };
+
+  onMount(() => {
+    document.addEventListener("keydown", handleKeyDown);
+    onCleanup(() => document.removeEventListener("keydown", handleKeyDown));
   });
 
   return (
     <div class="bg-background fixed top-0 left-0 z-10 w-full">
       <div class="h-6 w-full" data-tauri-drag-region></div>
-      <nav class="flex h-8 w-full items-center px-4 pb-2 font-sans select-none">
+      <nav
+        class="flex h-8 w-full items-center px-4 pb-2 font-sans select-none"
+        aria-label="Breadcrumb"
+      >
         <div class="flex flex-1">
-          <RootCrumb />
+          <RootCrumb version={statusVersion()} />
           <For each={items()}>
             {(item, index) => {
               const isActive = () => index() === items().length - 1;
-              return <Breadcrumb item={item} isActive={isActive()} />;
+              return (
+                <Breadcrumb
+                  item={item}
+                  isActive={isActive()}
+                  version={statusVersion()}
+                />
+              );
             }}
           </For>
+          <Show when={status()}>
+            {(current) => (
+              <button
+                class="hover:bg-button-hover rounded px-2 text-xs opacity-60 disabled:cursor-not-allowed"
+                onClick={cycleStatus}
+                disabled={!notes.editable()}
+                title="Change status"
+                aria-label={`Status: ${STATUS_LABELS[current()]}. Change status`}
+                aria-keyshortcuts="Control+Shift+S Meta+Shift+S"
+              >
+                {STATUS_MARKS[current()]} {STATUS_LABELS[current()]}
+              </button>
+            )}
+          </Show>
         </div>
         <div class="ml-4 flex gap-1">
           <button
@@ -190,6 +299,7 @@ export function Navigation() {
             onClick={() => notes.goBack()}
             disabled={!notes.canGoBack()}
             title="Go back"
+            aria-label="Go back"
           >
             ←
           </button>
@@ -198,6 +308,7 @@ export function Navigation() {
             onClick={() => notes.goForward()}
             disabled={!notes.canGoForward()}
             title="Go forward"
+            aria-label="Go forward"
           >
             →
           </button>
@@ -1,23 +1,67 @@
-import { defaultValueCtx, Editor, rootCtx } from "@milkdown/kit/core";
+import {
+  defaultValueCtx,
+  Editor,
+  editorViewCtx,
+  editorViewOptionsCtx,
+  parserCtx,
+  rootCtx,
+} from "@milkdown/kit/core";
 import { commonmark } from "@milkdown/kit/preset/commonmark";
 import { createSignal, createEffect, onCleanup, onMount, Show } from "solid-js";
 import { history } from "@milkdown/kit/plugin/history";
 import { listener, listenerCtx } from "@milkdown/kit/plugin/listener";
 import { gfm } from "@milkdown/kit/preset/gfm";
+import { replaceAll } from "@milkdown/kit/utils";
+import { TextSelection } from "@milkdown/kit/prose/state";
 import "prosemirror-view/style/prosemirror.css";
-import { useNoteContent, useAutoSave } from "../../api";
+import { useNoteContent, useAutoSave, useNotes } from "../../api";
+import { commands } from "../../api/commands";
 import { NoteContent } from "../../api/hooks";
+import {
+  getNoteFolds,
+  getNoteViewState,
+  setNoteFolds,
+  setNoteViewState,
+  type NoteViewState,
+} from "../../utils/appState";
+import { editorConfig } from "../../utils/editorConfig";
+import { isUntitled } from "../../utils/paths";
+import {
+  caretRequest,
+  clearCaret,
+  clearReveal,
+  revealRequest,
+} from "../../utils/reveal";
+import { autosaveDelay } from "../../utils/timing";
+import { caretSelection, findOccurrence } from "./findText";
+import { folding } from "./folding";
+import { formattingKeymap } from "./formattingKeymap";
+import { noteLinkPaste } from "./noteLinkPaste";
+import { smartTypography } from "./smartTypography";
+import { wikiLinkHover, type HoveredLink } from "./wikiLinkHover";
+import { LinkPreview } from "../ui/LinkPreview";
+import { MergeEditor } from "../ui/MergeEditor";
 import "./MdEditor.css";
 
-const AUTOSAVE_DELAY = 400;
+const VIEW_STATE_DELAY = 500;
+// Pause in typing before an untitled note is named after its first line
+const AUTO_TITLE_DELAY = 2000;
+
+// Notes named from their first line keep following it while the app runs
+const autoTitled = new Set<string>();
+// Grace period for moving the pointer from a link onto its preview card
+const LINK_PREVIEW_HIDE_DELAY = 300;
 
 function MdEditor({ path, content }: { path: string; content: NoteContent }) {
-  const [pathSignal, _] = createSignal(path);
+  // Follows renames from auto-titling, so saves go to the new path
+  const [pathSignal, setPathSignal] = createSignal(path);
 
   const autoSave = useAutoSave({
     getPath: pathSignal,
     getContent: content.content,
-    delay: AUTOSAVE_DELAY,
+    getBaseHash: content.contentHash,
+    setBaseHash: content.setContentHash,
+    delay: autosaveDelay,
   });
 
   // Set initial content as last saved to avoid triggering autosave on load
@@ -26,34 +70,268 @@ function MdEditor({ path, content }: { path: string; content: NoteContent }) {
   let ref: HTMLDivElement | null = null;
   let editor: Editor;
 
+  // [[wiki-link]] under the pointer, shown as a preview card
+  const notes = useNotes();
+  const [hoveredLink, setHoveredLink] = createSignal<HoveredLink | null>(null);
+  const [merging, setMerging] = createSignal(false);
+  const [editorReady, setEditorReady] = createSignal(false);
+  let hideLinkTimer: number | undefined;
+  const hideLinkPreview = () => {
+    clearTimeout(hideLinkTimer);
+    hideLinkTimer = window.setTimeout(
+      () => setHoveredLink(null),
+      LINK_PREVIEW_HIDE_DELAY,
+    );
+  };
+  const handleLinkHover = (link: HoveredLink | null) => {
+    if (!link) return hideLinkPreview();
+    clearTimeout(hideLinkTimer);
+    setHoveredLink(link);
+  };
+
+  // Cursor and scroll position, saved shortly after they settle
+  let viewState: NoteViewState | null = null;
+  let viewStateTimer: number | undefined;
+  const persistViewState = () => {
+    clearTimeout(viewStateTimer);
+    if (!viewState) return;
+    setNoteViewState(path, viewState).catch((err) =>
+      console.error("Failed to save view state:", err),
+    );
+  };
+  const updateViewState = (update: Partial<NoteViewState>) => {
+    viewState = {
+      cursor: 0,
+      scrollTop: document.scrollingElement?.scrollTop ?? 0,
+      ...viewState,
+      ...update,
+    };
+    clearTimeout(viewStateTimer);
+    viewStateTimer = window.setTimeout(persistViewState, VIEW_STATE_DELAY);
+  };
+  let autoTitleTimer: number | undefined;
+  const scheduleAutoTitle = () => {
+    clearTimeout(autoTitleTimer);
+    const current = pathSignal();
+    if (!editorConfig().auto_title) return;
+    if (!isUntitled(current) && !autoTitled.has(current)) return;
+    autoTitleTimer = window.setTimeout(applyAutoTitle, AUTO_TITLE_DELAY);
+  };
+  // Saves, renames, and reopens the note under its new name with the caret
+  // where it was
+  const applyAutoTitle = async () => {
+    const current = pathSignal();
+    if (!(await autoSave.flush())) return;
+    try {
+      const moved = await commands.titleFromFirstLine(current);
+      if (!moved) return;
+      autoTitled.delete(current);
+      autoTitled.add(moved.to);
+      setPathSignal(moved.to);
+      // Keep anything typed while the rename ran
+      await autoSave.flush();
+      if (viewState) await setNoteViewState(moved.to, viewState);
+      notes.setCurrentPath(moved.to);
+    } catch (err) {
+      console.error("Failed to name note:", err);
+    }
+  };
+
+  const handleScroll = () => {
+    // The preview card is placed in viewport coordinates
+    setHoveredLink(null);
+    updateViewState({ scrollTop: document.scrollingElement?.scrollTop ?? 0 });
+  };
+
   onMount(async () => {
+    const folds = await getNoteFolds(path).catch(() => []);
+    const savedView = await getNoteViewState(path).catch(() => null);
+    const persistFolds = (folded: string[]) =>
+      setNoteFolds(path, folded).catch((err) =>
+        console.error("Failed to save folds:", err),
+      );
+
     editor = await Editor.make()
       .config((ctx) => {
         ctx.set(rootCtx, ref);
         ctx.set(defaultValueCtx, content.content() || "");
+        ctx.update(editorViewOptionsCtx, (prev) => ({
+          ...prev,
+          attributes: {
+            role: "textbox",
+            "aria-multiline": "true",
+            "aria-label": "Note content",
+          },
+        }));
         ctx
           .get(listenerCtx)
           .markdownUpdated((_ctx, markdown, _prevMarkdown) => {
             content.setContent(markdown);
             let processedMarkdown = markdown;
             autoSave.scheduleAutoSave(processedMarkdown);
+            scheduleAutoTitle();
+          })
+          .selectionUpdated((_ctx, selection) => {
+            updateViewState({ cursor: selection.head });
           });
       })
       .use(commonmark)
       .use(gfm)
       .use(history)
       .use(listener)
+      .use(folding(folds, persistFolds))
+      .use(noteLinkPaste)
+      .use(formattingKeymap)
+      .use(smartTypography)
+      .use(wikiLinkHover(handleLinkHover))
       .create();
+
+    setEditorReady(true);
+    // A pending search match or caret takes precedence over where the note
+    // was left
+    const pending = revealRequest()?.path === path;
+    if (!pending && caretRequest()?.path !== path && savedView) {
+      restoreViewState(savedView);
+    }
+    window.addEventListener("scroll", handleScroll, { passive: true });
+  });
+
+  // Select a requested match, whether the note just opened or was showing
+  createEffect(() => {
+    const request = revealRequest();
+    if (!editorReady() || request?.path !== path) return;
+    clearReveal();
+    editor.action((ctx) => {
+      const view = ctx.get(editorViewCtx);
+      const range = findOccurrence(view.state.doc, request);
+      if (!range) return;
+      const selection = TextSelection.create(
+        view.state.doc,
+        range.from,
+        range.to,
+      );
+      view.dispatch(view.state.tr.setSelection(selection).scrollIntoView());
+      view.focus();
+    });
   });
 
+  // Put the caret where a new note asked for it, e.g. a template's ${cursor}
+  createEffect(() => {
+    const request = caretRequest();
+    if (!editorReady() || request?.path !== path) return;
+    clearCaret();
+    editor.action((ctx) => {
+      const view = ctx.get(editorViewCtx);
+      const markdown = content.content().slice(0, request.offset);
+      const prefix = ctx.get(parserCtx)(markdown);
+      const selection = caretSelection(view.state.doc, prefix, markdown);
+      view.dispatch(view.state.tr.setSelection(selection).scrollIntoView());
+      view.focus();
+    });
+  });
+
+  const restoreViewState = (saved: NoteViewState) => {
+    viewState = saved;
+    editor.action((ctx) => {
+      const view = ctx.get(editorViewCtx);
+      const cursor = Math.min(saved.cursor, view.state.doc.content.size);
+      const selection = TextSelection.near(view.state.doc.resolve(cursor));
+      view.dispatch(view.state.tr.setSelection(selection));
+      view.focus();
+    });
+    // Wait for layout so the saved offset is reachable
+    requestAnimationFrame(() => {
+      document.scrollingElement?.scrollTo({ top: saved.scrollTop });
+    });
+  };
+
   onCleanup(() => {
+    window.removeEventListener("scroll", handleScroll);
+    clearTimeout(hideLinkTimer);
+    clearTimeout(autoTitleTimer);
+    persistViewState();
     editor.destroy();
   });
 
+  // Replace the local edits with what is on disk now
+  const reload = () => {
+    const diskContent = autoSave.reloadFromConflict();
+    if (diskContent === null) return;
+    content.setContent(diskContent);
+    editor.action(replaceAll(diskContent));
+  };
+
+  // Replace the local edits with a merge of both versions and save it over
+  // the content on disk
+  const saveMerged = (merged: string) => {
+    setMerging(false);
+    content.setContent(merged);
+    editor.action(replaceAll(merged));
+    autoSave.keepMine();
+  };
+
   return (
     <>
+      <Show when={autoSave.conflict()}>
+        <div
+          class="bg-paper text-text fixed bottom-3 left-3 z-[100] flex items-center gap-4 rounded border px-3 py-2 shadow-md"
+          role="alert"
+        >
+          <span class="text-xs select-none">
+            This note was changed outside the editor.
+          </span>
+          <div class="flex items-center gap-1.5">
+            <button
+              class="text-text-muted text-xs underline hover:opacity-80"
+              onClick={reload}
+            >
+              Reload
+            </button>
+            <button
+              class="text-text-muted text-xs underline hover:opacity-80"
+              onClick={() => autoSave.keepMine()}
+            >
+              Keep mine
+            </button>
+            <button
+              class="text-text-muted text-xs underline hover:opacity-80"
+              onClick={() => setMerging(true)}
+            >
+              Merge…
+            </button>
+          </div>
+        </div>
+      </Show>
+
+      <Show when={merging() && autoSave.conflict()}>
+        {(conflict) => (
+          <MergeEditor
+            base={autoSave.lastSavedContent()}
+            mine={content.content()}
+            theirs={conflict().content}
+            onSave={saveMerged}
+            onClose={() => setMerging(false)}
+          />
+        )}
+      </Show>
+
       <div ref={ref!} class="flex w-full flex-col" />
 
+      <Show when={hoveredLink()}>
+        {(link) => (
+          <LinkPreview
+            link={link()}
+            onOpen={(target) => {
+              setHoveredLink(null);
+              notes.setCurrentPath(target);
+            }}
+            onClose={() => setHoveredLink(null)}
+            onMouseEnter={() => clearTimeout(hideLinkTimer)}
+            onMouseLeave={hideLinkPreview}
+          />
+        )}
+      </Show>
+
       {/*<div class="text-text-muted pointer-events-none fixed bottom-2 left-2 text-xs opacity-40">
         {(autoSave.isSaving() && "Saving...") ||
           (autoSave.hasUnsavedChanges() && "Unsaved changes")}
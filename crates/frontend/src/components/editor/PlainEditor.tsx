@@ -2,8 +2,11 @@ import { createSignal, createEffect, Show, onCleanup, onMount } from "solid-js";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { useNotes } from "../../api";
 import { commands } from "../../api/commands";
+import { NOTE_URL } from "../../utils/noteLinks";
+import type { FormatAction } from "../../types";
+import { isTypographyTrigger } from "../../utils/editorConfig";
+import { autosaveDelay } from "../../utils/timing";
 
-const AUTOSAVE_DELAY = 1000;
 
 export function Editor() {
   const notes = useNotes();
@@ -13,6 +16,16 @@ export function Editor() {
 
   let debounceTimer: number | undefined;
   let lastSavedContent = "";
+  let snippetTriggers = new Set<string>();
+
+  onMount(async () => {
+    try {
+      const snippets = await commands.getSnippets();
+      snippetTriggers = new Set(snippets.map((s) => s.trigger));
+    } catch (err) {
+      console.error("Failed to load snippets:", err);
+    }
+  });
 
   createEffect(async () => {
     const path = notes.currentPath();
@@ -49,7 +62,7 @@ export function Editor() {
 
   const handleSave = async () => {
     const path = notes.currentPath();
-    if (!path) return;
+    if (!path || !notes.editable()) return;
     if (debounceTimer) {
       clearTimeout(debounceTimer);
       debounceTimer = undefined;
@@ -66,8 +79,141 @@ export function Editor() {
     if (path && newContent !== lastSavedContent) {
       debounceTimer = setTimeout(() => {
         performSave(path, newContent);
-      }, AUTOSAVE_DELAY) as unknown as number;
+      }, autosaveDelay()) as unknown as number;
+    }
+  };
+
+  // Expands a snippet trigger that ends at the cursor (trigger + space)
+  const expandSnippetAtCursor = async (textarea: HTMLTextAreaElement) => {
+    const cursor = textarea.selectionStart;
+    const before = content().slice(0, cursor);
+    const trigger = before.split(/\s/).pop() ?? "";
+    if (!snippetTriggers.has(trigger)) return false;
+
+    const expansion = await commands.expandSnippet(trigger);
+    if (expansion === null) return false;
+
+    const start = cursor - trigger.length;
+    const next =
+      content().slice(0, start) + expansion + " " + content().slice(cursor);
+    handleContentChange(next);
+    textarea.value = next;
+    const caret = start + expansion.length + 1;
+    textarea.setSelectionRange(caret, caret);
+    return true;
+  };
+
+  // cmd-b, cmd-i, cmd-1/2/3 and cmd-shift-8
+  const formatActionFor = (e: KeyboardEvent): FormatAction | null => {
+    if (!(e.metaKey || e.ctrlKey) || e.altKey) return null;
+    if (e.shiftKey) {
+      return e.code === "Digit8" ? { kind: "bullet_list" } : null;
+    }
+    switch (e.key.toLowerCase()) {
+      case "b":
+        return { kind: "bold" };
+      case "i":
+        return { kind: "italic" };
+      case "1":
+      case "2":
+      case "3":
+        return { kind: "heading", level: Number(e.key) };
+      default:
+        return null;
+    }
+  };
+
+  const applyFormat = async (
+    textarea: HTMLTextAreaElement,
+    action: FormatAction,
+  ) => {
+    const edit = await commands.formatText(
+      textarea.value,
+      textarea.selectionStart,
+      textarea.selectionEnd,
+      action,
+    );
+    handleContentChange(edit.text);
+    textarea.value = edit.text;
+    textarea.setSelectionRange(edit.selection_start, edit.selection_end);
+  };
+
+  // Inserts the typed character right away, then swaps in its smart-typography
+  // replacement once it arrives. Anything typed in between makes the positions stale,
+  // so the plain character is kept then.
+  const typeSmart = async (textarea: HTMLTextAreaElement, typed: string) => {
+    const start = textarea.selectionStart;
+    const before = textarea.value.slice(0, start);
+    textarea.setRangeText(typed, start, textarea.selectionEnd, "end");
+    handleContentChange(textarea.value);
+    const typedValue = textarea.value;
+    const typedEnd = start + typed.length;
+
+    const replacement = await commands
+      .smartTypography(before, typed)
+      .catch(() => null);
+    if (!replacement || textarea.value !== typedValue) return;
+    const caretUnmoved =
+      textarea.selectionStart === typedEnd && textarea.selectionEnd === typedEnd;
+    textarea.setRangeText(
+      replacement.insert,
+      start - replacement.delete,
+      typedEnd,
+      caretUnmoved ? "end" : "preserve",
+    );
+    handleContentChange(textarea.value);
+  };
+
+  // Set by cmd/ctrl-shift-v so the next paste keeps the clipboard's plain text
+  let pasteAsPlainText = false;
+
+  // Converts rich text (HTML) on the clipboard to Markdown before inserting it
+  const handlePaste = async (e: ClipboardEvent) => {
+    const plain = pasteAsPlainText;
+    pasteAsPlainText = false;
+    if (!notes.editable()) return;
+
+    const text = e.clipboardData?.getData("text/plain") ?? "";
+    const trimmed = text.trim();
+    const isLink = /^https?:\/\/\S+$/.test(trimmed) || NOTE_URL.test(trimmed);
+    if (!plain && isLink) {
+      // Insert the URL right away, then swap in a titled link (or, for a note
+      // URL, its wiki-link) once it's resolved
+      e.preventDefault();
+      const textarea = e.currentTarget as HTMLTextAreaElement;
+      const url = trimmed;
+      const start = textarea.selectionStart;
+      textarea.setRangeText(url, start, textarea.selectionEnd, "end");
+      handleContentChange(textarea.value);
+
+      const link = await commands.linkForPastedUrl(url).catch(() => url);
+      if (link !== url && content().slice(start, start + url.length) === url) {
+        const next =
+          content().slice(0, start) +
+          link +
+          content().slice(start + url.length);
+        textarea.value = next;
+        handleContentChange(next);
+      }
+      return;
+    }
+
+    const html = e.clipboardData?.getData("text/html");
+    if (plain || !html) return;
+
+    e.preventDefault();
+    const textarea = e.currentTarget as HTMLTextAreaElement;
+    const start = textarea.selectionStart;
+    const end = textarea.selectionEnd;
+    try {
+      const markdown = await commands.convertHtml(html);
+      textarea.setRangeText(markdown, start, end, "end");
+    } catch (err) {
+      console.error("Failed to convert pasted HTML:", err);
+      const text = e.clipboardData?.getData("text/plain") ?? "";
+      textarea.setRangeText(text, start, end, "end");
     }
+    handleContentChange(textarea.value);
   };
 
   const handleKeyDown = (e: KeyboardEvent) => {
@@ -75,6 +221,44 @@ export function Editor() {
       e.preventDefault();
       handleSave();
     }
+
+    const action = formatActionFor(e);
+    if (action && notes.editable()) {
+      e.preventDefault();
+      applyFormat(e.currentTarget as HTMLTextAreaElement, action);
+      return;
+    }
+
+    if ((e.metaKey || e.ctrlKey) && e.shiftKey && e.key.toLowerCase() === "v") {
+      pasteAsPlainText = true;
+    }
+
+    const modified = e.metaKey || e.ctrlKey || e.altKey;
+    if (!modified && isTypographyTrigger(e.key) && notes.editable()) {
+      e.preventDefault();
+      typeSmart(e.currentTarget as HTMLTextAreaElement, e.key);
+      return;
+    }
+
+    if (e.key === " " && snippetTriggers.size > 0) {
+      const textarea = e.currentTarget as HTMLTextAreaElement;
+      const before = content().slice(0, textarea.selectionStart);
+      if (snippetTriggers.has(before.split(/\s/).pop() ?? "")) {
+        e.preventDefault();
+        expandSnippetAtCursor(textarea).then((expanded) => {
+          if (!expanded) {
+            // Snippet disappeared in the meantime; keep the typed space
+            textarea.setRangeText(
+              " ",
+              textarea.selectionStart,
+              textarea.selectionEnd,
+              "end",
+            );
+            handleContentChange(textarea.value);
+          }
+        });
+      }
+    }
   };
 
   const hasUnsavedChanges = () => content() !== lastSavedContent;
@@ -127,9 +311,13 @@ export function Editor() {
             value={content()}
             onInput={(e) => handleContentChange(e.currentTarget.value)}
             onKeyDown={handleKeyDown}
+            onPaste={handlePaste}
             class="w-full flex-1 resize-none px-1 outline-none"
+            style={{ "unicode-bidi": "plaintext" }}
+            dir="auto"
             placeholder="Write your note here..."
             disabled={isSaving()}
+            readOnly={!notes.editable()}
           />
           <div class="px-4 pb-4">
             <div class="opacity-40">
@@ -0,0 +1,39 @@
+import type { Node } from "@milkdown/kit/prose/model";
+import { Selection } from "@milkdown/kit/prose/state";
+
+// Finds the `occurrence`th match of `text` (ignoring case, counting from 0) in
+// the document's text. The count comes from the Markdown, which can hold
+// matches that don't render (link targets, say); past the last rendered match
+// the last one is used.
+export function findOccurrence(
+  doc: Node,
+  { text, occurrence }: { text: string; occurrence: number },
+): { from: number; to: number } | null {
+  const needle = text.toLowerCase();
+  if (!needle) return null;
+  let found: { from: number; to: number } | null = null;
+  let seen = 0;
+  doc.descendants((node, pos) => {
+    if (seen > occurrence) return false;
+    if (!node.isText || !node.text) return;
+    const haystack = node.text.toLowerCase();
+    let index = haystack.indexOf(needle);
+    while (index !== -1 && seen <= occurrence) {
+      found = { from: pos + index, to: pos + index + needle.length };
+      seen++;
+      index = haystack.indexOf(needle, index + needle.length);
+    }
+  });
+  return found;
+}
+
+// Where an offset into the Markdown lands in the document, given the Markdown
+// up to it parsed on its own: at the end of that text, or at the start of the
+// next block when it ends with a line break.
+export function caretSelection(doc: Node, prefix: Node, markdown: string) {
+  const end = Math.min(Selection.atEnd(prefix).from, doc.content.size);
+  const next = markdown.endsWith("\n")
+    ? Selection.findFrom(doc.resolve(Math.min(end + 1, doc.content.size)), 1)
+    : null;
+  return next ?? Selection.near(doc.resolve(end));
+}
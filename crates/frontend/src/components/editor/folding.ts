@@ -0,0 +1,142 @@
+import { $prose } from "@milkdown/kit/utils";
+import { Plugin, PluginKey } from "@milkdown/kit/prose/state";
+import { Decoration, DecorationSet } from "@milkdown/kit/prose/view";
+import type { Node } from "@milkdown/kit/prose/model";
+import type { EditorView } from "@milkdown/kit/prose/view";
+
+// Code blocks with at least this many lines can be folded
+export const LONG_CODE_LINES = 15;
+
+type FoldMeta = { toggle: string } | { set: string[] };
+
+const foldKey = new PluginKey<Set<string>>("folding");
+
+interface Foldable {
+  key: string;
+  pos: number;
+  node: Node;
+  // Heading level; code blocks fold only themselves
+  level: number | null;
+}
+
+// Folds are keyed by content rather than position so they survive edits
+// elsewhere in the note and can be restored when it is reopened
+function foldables(doc: Node): Foldable[] {
+  const seen = new Map<string, number>();
+  const result: Foldable[] = [];
+
+  doc.forEach((node, pos) => {
+    let base: string;
+    let level: number | null = null;
+    if (node.type.name === "heading") {
+      level = node.attrs.level as number;
+      base = `${"#".repeat(level)} ${node.textContent}`;
+    } else if (
+      node.type.name === "code_block" &&
+      node.textContent.split("\n").length >= LONG_CODE_LINES
+    ) {
+      base = "```";
+    } else {
+      return;
+    }
+    const occurrence = seen.get(base) ?? 0;
+    seen.set(base, occurrence + 1);
+    result.push({ key: `${base}#${occurrence}`, pos, node, level });
+  });
+
+  return result;
+}
+
+function foldIndicator(view: EditorView, key: string, folded: boolean) {
+  const button = document.createElement("button");
+  button.className = "fold-indicator";
+  button.contentEditable = "false";
+  button.textContent = folded ? "▸" : "▾";
+  button.setAttribute("aria-label", folded ? "Expand section" : "Fold section");
+  button.setAttribute("aria-expanded", String(!folded));
+  button.addEventListener("mousedown", (e) => e.preventDefault());
+  button.addEventListener("click", () => {
+    view.dispatch(view.state.tr.setMeta(foldKey, { toggle: key }));
+  });
+  return button;
+}
+
+function foldDecorations(doc: Node, folded: Set<string>): DecorationSet {
+  const decorations: Decoration[] = [];
+
+  for (const item of foldables(doc)) {
+    const isFolded = folded.has(item.key);
+    decorations.push(
+      Decoration.widget(
+        item.pos + 1,
+        (view) => foldIndicator(view, item.key, isFolded),
+        { side: -1, ignoreSelection: true, key: `${item.key}:${isFolded}` },
+      ),
+    );
+    if (!isFolded) continue;
+
+    const end = item.pos + item.node.nodeSize;
+    decorations.push(
+      Decoration.node(item.pos, end, {
+        class: item.level === null ? "fold-collapsed" : "fold-heading",
+      }),
+    );
+    if (item.level === null) continue;
+
+    // Hide everything up to the next heading of the same or higher level
+    const level = item.level;
+    let done = false;
+    doc.forEach((node, pos) => {
+      if (done || pos < end) return;
+      if (node.type.name === "heading" && node.attrs.level <= level) {
+        done = true;
+        return;
+      }
+      decorations.push(
+        Decoration.node(pos, pos + node.nodeSize, { class: "fold-hidden" }),
+      );
+    });
+  }
+
+  return DecorationSet.create(doc, decorations);
+}
+
+// Folding for headings and long code blocks. `initial` restores saved folds;
+// `onChange` receives the folded keys whenever they change.
+export function folding(
+  initial: string[],
+  onChange: (folded: string[]) => void,
+) {
+  return $prose(
+    () =>
+      new Plugin<Set<string>>({
+        key: foldKey,
+        state: {
+          init: () => new Set(initial),
+          apply(tr, folded) {
+            const meta = tr.getMeta(foldKey) as FoldMeta | undefined;
+            if (!meta) return folded;
+            if ("set" in meta) return new Set(meta.set);
+            const next = new Set(folded);
+            if (!next.delete(meta.toggle)) next.add(meta.toggle);
+            return next;
+          },
+        },
+        props: {
+          decorations(state) {
+            return foldDecorations(state.doc, foldKey.getState(state)!);
+          },
+        },
+        view: () => ({
+          update(view, prevState) {
+            const folded = foldKey.getState(view.state)!;
+            if (folded !== foldKey.getState(prevState)) {
+              // Drop folds whose heading no longer exists
+              const keys = new Set(foldables(view.state.doc).map((f) => f.key));
+              onChange([...folded].filter((key) => keys.has(key)));
+            }
+          },
+        }),
+      }),
+  );
+}
@@ -0,0 +1,43 @@
+import { commandsCtx } from "@milkdown/kit/core";
+import { keymap } from "@milkdown/kit/prose/keymap";
+import type { EditorState } from "@milkdown/kit/prose/state";
+import {
+  liftListItemCommand,
+  turnIntoTextCommand,
+  wrapInBulletListCommand,
+  wrapInHeadingCommand,
+} from "@milkdown/kit/preset/commonmark";
+import { $prose } from "@milkdown/kit/utils";
+
+const inBulletList = (state: EditorState) => {
+  const { $from } = state.selection;
+  for (let depth = $from.depth; depth > 0; depth--) {
+    if ($from.node(depth).type.name === "bullet_list") return true;
+  }
+  return false;
+};
+
+// cmd-1/2/3 toggle headings and cmd-shift-8 toggles a bullet list, matching
+// the raw-text editor; cmd-b and cmd-i come with the commonmark preset
+export const formattingKeymap = $prose((ctx) => {
+  const toggleHeading = (level: number) => (state: EditorState) => {
+    const parent = state.selection.$from.parent;
+    const commands = ctx.get(commandsCtx);
+    if (parent.type.name === "heading" && parent.attrs.level === level) {
+      return commands.call(turnIntoTextCommand.key);
+    }
+    return commands.call(wrapInHeadingCommand.key, level);
+  };
+
+  return keymap({
+    "Mod-1": toggleHeading(1),
+    "Mod-2": toggleHeading(2),
+    "Mod-3": toggleHeading(3),
+    "Mod-Shift-8": (state) => {
+      const commands = ctx.get(commandsCtx);
+      return inBulletList(state)
+        ? commands.call(liftListItemCommand.key)
+        : commands.call(wrapInBulletListCommand.key);
+    },
+  });
+});
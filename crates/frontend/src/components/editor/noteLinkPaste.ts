@@ -0,0 +1,27 @@
+import { $prose } from "@milkdown/kit/utils";
+import { Plugin, PluginKey } from "@milkdown/kit/prose/state";
+import { commands } from "../../api/commands";
+import { NOTE_URL } from "../../utils/noteLinks";
+
+// Pasting a note URL copied from the app inserts the [[wiki-link]] form
+export const noteLinkPaste = $prose(
+  () =>
+    new Plugin({
+      key: new PluginKey("note-link-paste"),
+      props: {
+        handlePaste(view, event) {
+          const text = event.clipboardData?.getData("text/plain").trim() ?? "";
+          if (!NOTE_URL.test(text)) return false;
+
+          const { from, to } = view.state.selection;
+          commands
+            .linkForPastedUrl(text)
+            .catch(() => text)
+            .then((link) => {
+              view.dispatch(view.state.tr.insertText(link, from, to));
+            });
+          return true;
+        },
+      },
+    }),
+);
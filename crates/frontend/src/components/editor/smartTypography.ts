@@ -0,0 +1,43 @@
+import { $prose } from "@milkdown/kit/utils";
+import { Plugin, PluginKey } from "@milkdown/kit/prose/state";
+import { commands } from "../../api/commands";
+import { isTypographyTrigger } from "../../utils/editorConfig";
+
+// Curly quotes, dashes, and ellipses as you type, when enabled in the config
+export const smartTypography = $prose(
+  () =>
+    new Plugin({
+      key: new PluginKey("smart-typography"),
+      props: {
+        handleTextInput(view, from, to, text) {
+          if (!isTypographyTrigger(text)) return false;
+
+          // Code blocks and inline code keep their ASCII punctuation
+          const $from = view.state.doc.resolve(from);
+          if ($from.parent.type.spec.code) return false;
+          if ($from.marks().some((mark) => mark.type.spec.code)) return false;
+
+          // The typed character goes in now so later keystrokes land after it; the
+          // replacement is only applied if nothing changed the document meanwhile,
+          // since its positions would be stale
+          const before = $from.parent.textBetween(0, $from.parentOffset);
+          view.dispatch(view.state.tr.insertText(text, from, to));
+          const typed = view.state.doc;
+          commands
+            .smartTypography(before, text)
+            .catch(() => null)
+            .then((replacement) => {
+              if (!replacement || view.state.doc !== typed) return;
+              view.dispatch(
+                view.state.tr.insertText(
+                  replacement.insert,
+                  from - replacement.delete,
+                  from + text.length,
+                ),
+              );
+            });
+          return true;
+        },
+      },
+    }),
+);
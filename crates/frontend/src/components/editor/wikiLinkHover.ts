@@ -0,0 +1,67 @@
+import { $prose } from "@milkdown/kit/utils";
+import { Plugin, PluginKey } from "@milkdown/kit/prose/state";
+import type { EditorView } from "@milkdown/kit/prose/view";
+
+export interface HoveredLink {
+  // Note path from `[[path]]`, `[[path|label]]` or `[[path#heading]]`
+  path: string;
+  // Viewport position of the link text, for placing the preview card
+  left: number;
+  top: number;
+  bottom: number;
+}
+
+const WIKI_LINK = /\[\[([^\]\n]+)\]\]/g;
+
+function linkAt(view: EditorView, event: MouseEvent): HoveredLink | null {
+  const hit = view.posAtCoords({ left: event.clientX, top: event.clientY });
+  if (!hit) return null;
+  const $pos = view.state.doc.resolve(hit.pos);
+  const block = $pos.parent;
+  if (!block.isTextblock || block.type.spec.code) return null;
+
+  // Leaf nodes count as one character so offsets line up with positions
+  const text = block.textBetween(0, block.content.size, undefined, "\ufffc");
+  const offset = $pos.parentOffset;
+  for (const match of text.matchAll(WIKI_LINK)) {
+    const start = match.index;
+    const end = start + match[0].length;
+    if (offset < start || offset > end) continue;
+
+    const path = match[1].split("|")[0].split("#")[0].trim();
+    if (!path) return null;
+    const blockStart = $pos.start();
+    const from = view.coordsAtPos(blockStart + start);
+    const to = view.coordsAtPos(blockStart + end);
+    return { path, left: from.left, top: from.top, bottom: to.bottom };
+  }
+  return null;
+}
+
+// Reports the [[wiki-link]] under the mouse (or null) as the pointer moves
+export const wikiLinkHover = (onHover: (link: HoveredLink | null) => void) =>
+  $prose(() => {
+    let current: string | null = null;
+    const report = (link: HoveredLink | null) => {
+      const key = link ? `${link.path}@${link.left},${link.top}` : null;
+      if (key === current) return;
+      current = key;
+      onHover(link);
+    };
+
+    return new Plugin({
+      key: new PluginKey("wiki-link-hover"),
+      props: {
+        handleDOMEvents: {
+          mousemove(view, event) {
+            report(linkAt(view, event));
+            return false;
+          },
+          mouseleave() {
+            report(null);
+            return false;
+          },
+        },
+      },
+    });
+  });
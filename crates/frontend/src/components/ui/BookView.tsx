@@ -0,0 +1,38 @@
+import { createResource, Show } from "solid-js";
+import { commands } from "../../api/commands";
+import { getPathTitle } from "../../utils/paths";
+import { Modal } from "../primitives/Modal";
+
+// Read-only view of a note and all its descendants as one document
+export function BookView(props: {
+  path: string | null;
+  onClose: () => void;
+}) {
+  const [content] = createResource(
+    () => props.path,
+    (path) => commands.compileSubtree(path),
+  );
+
+  return (
+    <Modal
+      open={props.path !== null}
+      onClose={props.onClose}
+      class="bg-paper text-text w-[640px] rounded-md border outline-none"
+    >
+      <div
+        role="document"
+        aria-label={props.path ? getPathTitle(props.path) : undefined}
+        dir="auto"
+        class="max-h-[70vh] overflow-y-auto px-6 py-4 whitespace-pre-wrap"
+        style={{ "unicode-bidi": "plaintext" }}
+      >
+        <Show
+          when={!content.error}
+          fallback={<span class="opacity-60">Could not load notes</span>}
+        >
+          {content() ?? "Loading..."}
+        </Show>
+      </div>
+    </Modal>
+  );
+}
@@ -1,4 +1,4 @@
-import { createSignal, Show, For, JSX, onCleanup } from "solid-js";
+import { createSignal, Show, For, JSX, onCleanup, onMount } from "solid-js";
 import { Portal } from "solid-js/web";
 
 export type MenuItem =
@@ -12,6 +12,22 @@ export type MenuItem =
       separator: true;
     };
 
+// Moves focus between the items of a menu with the arrow keys
+export function handleMenuKeyDown(e: KeyboardEvent) {
+  if (e.key !== "ArrowDown" && e.key !== "ArrowUp") return;
+  e.preventDefault();
+
+  const menu = e.currentTarget as HTMLElement;
+  const items = Array.from(
+    menu.querySelectorAll<HTMLButtonElement>(
+      '[role="menuitem"]:not(:disabled)',
+    ),
+  );
+  const index = items.indexOf(document.activeElement as HTMLButtonElement);
+  const next = e.key === "ArrowDown" ? index + 1 : index - 1;
+  items[(next + items.length) % items.length]?.focus();
+}
+
 interface ContextMenuContentProps {
   items: MenuItem[];
   onItemClick: (item: Extract<MenuItem, { label: string }>) => void;
@@ -30,6 +46,7 @@ export function ContextMenuContent(props: ContextMenuContentProps) {
             return (
               <div class="px-1">
                 <button
+                  role="menuitem"
                   onClick={() => props.onItemClick(item)}
                   disabled={item.disabled}
                   class="hover:bg-button-hover w-full rounded px-1 py-0.5 text-left text-xs outline-none select-none disabled:cursor-not-allowed disabled:opacity-40"
@@ -53,8 +70,20 @@ interface ContextMenuContainerProps {
 }
 
 export function ContextMenuContainer(props: ContextMenuContainerProps) {
+  let containerRef: HTMLDivElement | undefined;
+
+  // Focus the first action so menus opened from the keyboard are usable
+  onMount(() => {
+    containerRef
+      ?.querySelector<HTMLButtonElement>('[role="menuitem"]:not(:disabled)')
+      ?.focus();
+  });
+
   return (
     <div
+      ref={containerRef}
+      role="menu"
+      onKeyDown={handleMenuKeyDown}
       class="text-text-muted fixed min-w-[140px] rounded-md py-1 shadow-lg"
       style={{
         left: `${props.x}px`,
@@ -0,0 +1,63 @@
+import { createSignal, onMount, Show } from "solid-js";
+import { save } from "@tauri-apps/plugin-dialog";
+import { commands } from "../../api/commands";
+import { Checkbox } from "../primitives/form";
+import { useToast } from "./Toast";
+
+export function DiagnosticsSettings() {
+  const toast = useToast();
+  const [crashReports, setCrashReports] = createSignal<boolean | null>(null);
+
+  onMount(async () => {
+    const config = await commands.getVaultConfig();
+    setCrashReports(config.diagnostics.crash_reports);
+  });
+
+  const updateCrashReports = async (value: boolean) => {
+    const config = await commands.getVaultConfig();
+    await commands.setVaultConfig({
+      ...config,
+      diagnostics: { ...config.diagnostics, crash_reports: value },
+    });
+    setCrashReports(value);
+    toast.info("Takes effect after a restart", { duration: "short" });
+  };
+
+  const createBundle = async () => {
+    const dest = await save({
+      defaultPath: "zinnia-diagnostics.zip",
+      filters: [{ name: "Zip archive", extensions: ["zip"] }],
+    });
+    if (!dest) return;
+
+    try {
+      const reports = await commands.createDiagnosticsBundle(dest);
+      const plural = reports === 1 ? "" : "s";
+      toast.success(`Diagnostics saved with ${reports} crash report${plural}`);
+    } catch (err) {
+      console.error("Failed to create diagnostics bundle:", err);
+      toast.error(`Failed to create diagnostics: ${err}`);
+    }
+  };
+
+  return (
+    <div class="space-y-4">
+      <Show when={crashReports() !== null}>
+        <div class="flex items-center justify-between">
+          <label>Save crash reports</label>
+          <Checkbox checked={crashReports()!} onChange={updateCrashReports} />
+        </div>
+      </Show>
+      <div class="flex items-center justify-between">
+        <label>Diagnostics for bug reports</label>
+        <button
+          type="button"
+          onClick={createBundle}
+          class="bg-button-bg hover:bg-button-hover rounded border px-2 py-1 text-sm"
+        >
+          Save…
+        </button>
+      </div>
+    </div>
+  );
+}
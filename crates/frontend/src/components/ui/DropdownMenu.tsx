@@ -1,12 +1,37 @@
-import { createSignal, For, onMount, onCleanup, Show } from "solid-js";
+import {
+  createEffect,
+  createResource,
+  createSignal,
+  For,
+  onMount,
+  onCleanup,
+  Show,
+} from "solid-js";
 import { listen } from "@tauri-apps/api/event";
+import { ask, open as openDialog } from "@tauri-apps/plugin-dialog";
 import { useNotes } from "../../api";
 import { commands } from "../../api/commands";
+import { announce } from "../../utils/announce";
+import { copyNoteLink } from "../../utils/noteLinks";
+import { getParentPath, getPathTitle } from "../../utils/paths";
+import { requestCaret } from "../../utils/reveal";
+import { isCycleStatusKey, statusAnnouncement } from "../../utils/status";
 import { InputModal } from "./InputModal";
 import { NoteFinder } from "./NoteFinder";
+import { BookView } from "./BookView";
+import { NoteHistory } from "./NoteHistory";
+import { UnlinkedMentions } from "./UnlinkedMentions";
 import { MenuPanel } from "./MenuPanel";
+import { PassphrasePrompt } from "./PassphrasePrompt";
+import { TemplatePrompt } from "./TemplatePrompt";
 import { useToast } from "./Toast";
-import type { NoteMetadata } from "../../types";
+import type {
+  CsvImportMode,
+  ExportFormat,
+  NoteMetadata,
+  NoteSelection,
+  TemplateField,
+} from "../../types";
 import type { MenuItem } from "./ContextMenu";
 import { ContextMenuContainer } from "./ContextMenu";
 
@@ -34,6 +59,40 @@ export function DropdownMenu(props: DropdownMenuProps) {
   const [createAtPath, setCreateAtPath] = createSignal("");
   const [showNoteFinder, setShowNoteFinder] = createSignal(false);
   const [noteToMove, setNoteToMove] = createSignal<string | null>(null);
+  // Shift-click builds a multi-selection for batch move/archive
+  const [selection, setSelection] = createSignal<string[]>([]);
+  const [movingSelection, setMovingSelection] = createSignal(false);
+  const [bookPath, setBookPath] = createSignal<string | null>(null);
+  const [mentionsPath, setMentionsPath] = createSignal<string | null>(null);
+  const [historyPath, setHistoryPath] = createSignal<string | null>(null);
+  // "New from template…": the parent while a template is picked, then both
+  // and the template's variables while they're filled in
+  const [templateParent, setTemplateParent] = createSignal<string | null>(
+    null,
+  );
+  const [templateTarget, setTemplateTarget] = createSignal<{
+    parent: string;
+    template: string;
+  } | null>(null);
+  const [templateFields, setTemplateFields] = createSignal<
+    TemplateField[] | null
+  >(null);
+  // An action waiting for the vault to be unlocked
+  const [unlockRequest, setUnlockRequest] = createSignal<{
+    mode: "unlock" | "set";
+    then: () => Promise<void>;
+  } | null>(null);
+  // Notes a "Set property" prompt applies to, while it's open
+  const [propertyTarget, setPropertyTarget] =
+    createSignal<NoteSelection | null>(null);
+  // Markdown is what's on disk already, so only other formats are offered
+  const [exportFormats] = createResource(async () =>
+    (await commands.getExportFormats()).filter((f) => f.id !== "markdown"),
+  );
+  // Shown at the top of the "All notes" panel
+  const [favorites, { refetch: refetchFavorites }] = createResource(
+    commands.getFavorites,
+  );
   const [openPanels, setOpenPanels] = createSignal<PanelState[]>([]);
   const [childrenCache, setChildrenCache] = createSignal(
     new Map<string, NoteMetadata[]>(),
@@ -41,6 +100,9 @@ export function DropdownMenu(props: DropdownMenuProps) {
   const [hasChildrenMap, setHasChildrenMap] = createSignal<
     Record<string, boolean>
   >({});
+  // Notes whose submenus were open last, outermost first; saved as the
+  // expanded notes when the menu closes and reopened when it opens again
+  let shape: string[] = [];
   const [contextMenu, setContextMenu] = createSignal<{
     items: MenuItem[];
     x: number;
@@ -61,6 +123,11 @@ export function DropdownMenu(props: DropdownMenuProps) {
     onCleanup(unlisten);
   });
 
+  onMount(async () => {
+    const unlisten = await listen("notes:favorites", () => refetchFavorites());
+    onCleanup(unlisten);
+  });
+
   // Track refs for positioning
   const panelRefs = new Map<number, HTMLDivElement>();
   const rowRefs = new Map<string, HTMLButtonElement>();
@@ -100,6 +167,52 @@ export function DropdownMenu(props: DropdownMenuProps) {
 
       // Preload hasChildren for root items
       loadHasChildrenForItems(props.content);
+      focusFirstRow(0);
+      restoreShape();
+    }
+  };
+
+  createEffect(() => {
+    const panels = openPanels();
+    if (panels.length > 0) {
+      shape = panels.slice(1).map((panel) => panel.parentPath);
+    }
+  });
+
+  // Reopens the submenus of the expanded notes, one level at a time
+  const restoreShape = async () => {
+    try {
+      const expanded = new Set(await commands.getExpandedPaths());
+      let items = props.content;
+      for (let level = 0; dialogRef?.open; level++) {
+        const next = items.find((item) => expanded.has(item.path));
+        if (!next || !(await openSubmenu(level, next.path))) break;
+        items = childrenCache().get(next.path) ?? [];
+      }
+    } catch (err) {
+      console.error("Failed to restore open submenus:", err);
+    }
+  };
+
+  // Stores the submenus open at close as this menu's expanded notes
+  const saveShape = async () => {
+    const open = new Set(shape);
+    const prefix = props.path ? `${props.path}/` : "";
+    try {
+      const previous = (await commands.getExpandedPaths()).filter((path) =>
+        path.startsWith(prefix),
+      );
+      const changes = [
+        ...previous
+          .filter((path) => !open.has(path))
+          .map((path) => commands.setExpanded(path, false)),
+        ...shape
+          .filter((path) => !previous.includes(path))
+          .map((path) => commands.setExpanded(path, true)),
+      ];
+      await Promise.all(changes);
+    } catch (err) {
+      console.error("Failed to save open submenus:", err);
     }
   };
 
@@ -107,6 +220,7 @@ export function DropdownMenu(props: DropdownMenuProps) {
     if (e.target === dialogRef) {
       dialogRef?.close();
       setOpenPanels([]);
+      setSelection([]);
     }
   };
 
@@ -130,11 +244,15 @@ export function DropdownMenu(props: DropdownMenuProps) {
     const cache = childrenCache();
     if (!cache.has(parentPath)) {
       try {
-        const items = await commands.getChildren(parentPath);
+        const items = await commands.getChildren(parentPath, true);
         cache.set(parentPath, items);
         setChildrenCache(new Map(cache));
         // Preload hasChildren for these items
         loadHasChildrenForItems(items);
+        // Shown notes are likely to be opened next
+        commands
+          .warmCache(items.map((item) => item.path))
+          .catch((err) => console.error("Failed to warm cache:", err));
       } catch (err) {
         console.error("Failed to load children:", err);
       }
@@ -192,38 +310,497 @@ export function DropdownMenu(props: DropdownMenuProps) {
       return;
     }
 
+    await openSubmenu(level, note.path);
+  };
+
+  // Opens the children of `path` (a row in panel `level`) as the next panel.
+  // Returns false if it has none.
+  const openSubmenu = async (level: number, path: string) => {
     // Load children if needed
-    await ensureChildrenLoaded(note.path);
+    await ensureChildrenLoaded(path);
 
     const cache = childrenCache();
-    const items = cache.get(note.path);
+    const items = cache.get(path);
 
     if (!items || items.length === 0) {
       // No children after all, trim panels
       setOpenPanels((panels) => panels.slice(0, level + 1));
-      return;
+      return false;
     }
 
     // Compute position for submenu
-    const pos = computeSubmenuPosition(level, note.path);
+    const pos = computeSubmenuPosition(level, path);
 
     // Add/replace submenu panel
     setOpenPanels((panels) => {
       const next = panels.slice(0, level + 1);
       next.push({
-        parentPath: note.path,
+        parentPath: path,
         items,
         left: pos.left,
         top: pos.top,
       });
       return next;
     });
+    return true;
   };
 
-  const handleClickItem = (item: NoteMetadata) => {
-    notes.setCurrentPath(item.path);
-    dialogRef?.close();
-    setOpenPanels([]);
+  const handleClickItem = (item: NoteMetadata, e: MouseEvent) => {
+    if (e.shiftKey && notes.editable()) {
+      setSelection((selected) =>
+        selected.includes(item.path)
+          ? selected.filter((path) => path !== item.path)
+          : [...selected, item.path],
+      );
+      return;
+    }
+
+    const open = async () => {
+      notes.setCurrentPath(item.path);
+      dialogRef?.close();
+      setOpenPanels([]);
+      setSelection([]);
+    };
+    if (item.stats?.encrypted) {
+      withUnlockedVault(open);
+    } else {
+      open();
+    }
+  };
+
+  // Returns the selected note containing `path` (or `path` itself), if any
+  const selectedAncestor = (paths: string[], path: string) =>
+    paths.find((p) => path === p || path.startsWith(p + "/"));
+
+  const focusFirstRow = (level: number) => {
+    requestAnimationFrame(() => {
+      panelRefs
+        .get(level)
+        ?.querySelector<HTMLButtonElement>('[role="menuitem"]')
+        ?.focus();
+    });
+  };
+
+  // Keyboard equivalents for hovering, the archive button, and right-click
+  const handleRowKeyDown = async (
+    e: KeyboardEvent,
+    level: number,
+    note: NoteMetadata,
+  ) => {
+    if (e.key === "ContextMenu" || (e.key === "F10" && e.shiftKey)) {
+      e.preventDefault();
+      const rect = (e.currentTarget as HTMLElement).getBoundingClientRect();
+      setContextMenu({
+        items: createContextMenuItems(note),
+        x: rect.left,
+        y: rect.bottom,
+        notePath: note.path,
+      });
+      return;
+    }
+
+    if (isCycleStatusKey(e)) {
+      e.preventDefault();
+      e.stopPropagation();
+      if (notes.editable()) {
+        handleCycleStatus(note);
+      }
+      return;
+    }
+
+    switch (e.key) {
+      case "ArrowRight":
+        e.preventDefault();
+        await handleHoverItem(level, note);
+        if (openPanels().length > level + 1) {
+          focusFirstRow(level + 1);
+        }
+        break;
+      case "ArrowLeft": {
+        e.preventDefault();
+        if (level === 0) break;
+        const parentPath = openPanels()[level]?.parentPath;
+        setOpenPanels((panels) => panels.slice(0, level));
+        if (parentPath !== undefined) {
+          rowRefs.get(parentPath)?.focus();
+        }
+        break;
+      }
+      case "Delete":
+      case "Backspace":
+        e.preventDefault();
+        if (notes.editable()) {
+          handleArchiveItem(note);
+        }
+        break;
+    }
+  };
+
+  // Updates the row in place; the tree isn't refetched for content changes
+  const handleCycleStatus = async (note: NoteMetadata) => {
+    try {
+      const status = await commands.cycleStatus(note.path);
+      const update = (item: NoteMetadata) =>
+        item.path === note.path && item.stats
+          ? { ...item, stats: { ...item.stats, status } }
+          : item;
+      setOpenPanels((panels) =>
+        panels.map((panel) => ({ ...panel, items: panel.items.map(update) })),
+      );
+      setChildrenCache(
+        (cache) =>
+          new Map(
+            [...cache].map(([path, items]) => [path, items.map(update)]),
+          ),
+      );
+      if (note.path === notes.currentPath()) {
+        notes.refetchCurrent();
+      }
+      announce(statusAnnouncement(status));
+    } catch (err) {
+      console.error("Failed to change status:", err);
+      toast.error(`Failed to change status: ${err}`);
+    }
+  };
+
+  const handleArchiveSelection = async () => {
+    const paths = selection();
+    if (paths.length === 0) return;
+
+    try {
+      await commands.archiveNotes(paths);
+
+      const current = notes.currentPath();
+      const archived = selectedAncestor(paths, current);
+      if (archived) {
+        notes.setCurrentPath(archived.split("/").slice(0, -1).join("/"));
+      }
+
+      setSelection([]);
+      setChildrenCache(new Map());
+      setHasChildrenMap({});
+      props.onRefresh?.();
+
+      toast.success(`${paths.length} notes archived`, { duration: "short" });
+    } catch (err) {
+      console.error("Failed to archive notes:", err);
+      toast.error(`Failed to archive: ${err}`);
+    }
+  };
+
+  // Lists the note's descendants in a generated block inside the note itself
+  const handleGenerateMoc = async (path: string) => {
+    try {
+      const update = await commands.generateMoc(path, path);
+      toast.success(
+        update.revision === null
+          ? "Map of content is up to date"
+          : `Map of content lists ${update.links} notes`,
+        { duration: "short" },
+      );
+    } catch (err) {
+      console.error("Failed to generate map of content:", err);
+      toast.error(`Failed to generate map of content: ${err}`);
+    }
+  };
+
+  const handleCopyLink = async (path: string) => {
+    try {
+      await copyNoteLink(path);
+      toast.success("Link copied", { duration: "short" });
+    } catch (err) {
+      console.error("Failed to copy link:", err);
+      toast.error(`Failed to copy link: ${err}`);
+    }
+  };
+
+  const handleCopyAs = async (path: string, format: ExportFormat) => {
+    try {
+      const rendered = await commands.renderNote(path, format.id);
+      await navigator.clipboard.writeText(rendered);
+      toast.success(`Copied as ${format.name}`, { duration: "short" });
+    } catch (err) {
+      console.error("Failed to copy note:", err);
+      toast.error(`Failed to copy: ${err}`);
+    }
+  };
+
+  const handleExportSelection = async (format: ExportFormat) => {
+    const paths = selection();
+    if (paths.length === 0) return;
+
+    const dest = await openDialog({ directory: true });
+    if (typeof dest !== "string") return;
+
+    try {
+      const count = await commands.exportNotes(paths, format.id, dest);
+      toast.success(`${count} notes exported`, { duration: "short" });
+    } catch (err) {
+      console.error("Failed to export notes:", err);
+      toast.error(`Failed to export: ${err}`);
+    }
+  };
+
+  const handleImportCsv = async (path: string, mode: CsvImportMode) => {
+    const file = await openDialog({
+      filters: [{ name: "Tables", extensions: ["csv", "tsv", "txt"] }],
+    });
+    if (typeof file !== "string") return;
+
+    try {
+      const result = await commands.importCsv({ file }, path, mode);
+      setChildrenCache(new Map());
+      setHasChildrenMap({});
+      props.onRefresh?.();
+      toast.success(`${result.rows} rows imported`, { duration: "short" });
+    } catch (err) {
+      console.error("Failed to import table:", err);
+      toast.error(`Failed to import: ${err}`);
+    }
+  };
+
+  // Refetches a parent's children so open panels show the new order
+  const reloadPanel = async (parentPath: string) => {
+    const items = await commands.getChildren(parentPath, true);
+    setChildrenCache((cache) => new Map(cache).set(parentPath, items));
+    setOpenPanels((panels) =>
+      panels.map((panel) =>
+        panel.parentPath === parentPath ? { ...panel, items } : panel,
+      ),
+    );
+    props.onRefresh?.();
+  };
+
+  // Rows dropped in a panel; the order sticks until notes move elsewhere
+  const handleReorder = async (parentPath: string, orderedPaths: string[]) => {
+    try {
+      await commands.reorderChildren(parentPath, orderedPaths);
+      await reloadPanel(parentPath);
+    } catch (err) {
+      console.error("Failed to reorder notes:", err);
+      toast.error(`Failed to reorder: ${err}`);
+    }
+  };
+
+  const handleTogglePin = async (note: NoteMetadata) => {
+    const pinned = !note.stats?.pinned;
+    try {
+      await commands.pinNote(note.path, pinned);
+      await reloadPanel(getParentPath(note.path));
+      announce(pinned ? "Pinned" : "Unpinned");
+    } catch (err) {
+      console.error("Failed to pin note:", err);
+      toast.error(`Failed to ${pinned ? "pin" : "unpin"}: ${err}`);
+    }
+  };
+
+  // Runs `action` once encrypted notes can be read, asking for the
+  // passphrase first if needed
+  const withUnlockedVault = async (action: () => Promise<void>) => {
+    try {
+      const lock = await commands.getVaultLock();
+      if (lock.unlocked) {
+        await action();
+      } else {
+        const mode = lock.has_passphrase ? "unlock" : "set";
+        setUnlockRequest({ mode, then: action });
+      }
+    } catch (err) {
+      console.error("Failed to check the vault lock:", err);
+      toast.error(`Failed to unlock: ${err}`);
+    }
+  };
+
+  const handleUnlock = async (passphrase: string) => {
+    const request = unlockRequest();
+    if (!request) return;
+    try {
+      await commands.unlockVault(passphrase);
+    } catch (err) {
+      console.error("Failed to unlock:", err);
+      toast.error(`Failed to unlock: ${err}`);
+      return;
+    }
+    setUnlockRequest(null);
+    announce("Vault unlocked");
+    await request.then();
+  };
+
+  const handleLockVault = async () => {
+    try {
+      await commands.lockVault();
+      announce("Vault locked");
+    } catch (err) {
+      console.error("Failed to lock:", err);
+      toast.error(`Failed to lock: ${err}`);
+    }
+  };
+
+  const handleToggleEncryption = (note: NoteMetadata) => {
+    const encrypted = !note.stats?.encrypted;
+    withUnlockedVault(async () => {
+      try {
+        await commands.setNoteEncrypted(note.path, encrypted);
+        await reloadPanel(getParentPath(note.path));
+        announce(encrypted ? "Encrypted" : "Decrypted");
+      } catch (err) {
+        console.error("Failed to change encryption:", err);
+        toast.error(`Failed to ${encrypted ? "encrypt" : "decrypt"}: ${err}`);
+      }
+    });
+  };
+
+  const isFavorite = (path: string) =>
+    favorites()?.some((note) => note.path === path) ?? false;
+
+  const handleToggleFavorite = async (path: string) => {
+    const starred = isFavorite(path);
+    try {
+      await (starred
+        ? commands.unfavoriteNote(path)
+        : commands.favoriteNote(path));
+      announce(starred ? "Removed from starred" : "Starred");
+    } catch (err) {
+      console.error("Failed to star note:", err);
+      toast.error(`Failed to ${starred ? "unstar" : "star"}: ${err}`);
+    }
+  };
+
+  // Asks for the template's variables, if it has any, before creating the note
+  const handlePickTemplate = async (template: NoteMetadata) => {
+    const parent = templateParent();
+    if (parent === null) return;
+    setTemplateTarget({ parent, template: template.path });
+    try {
+      const schema = await commands.getTemplateSchema(template.path);
+      if (schema.fields.length > 0) {
+        setTemplateFields(schema.fields);
+      } else {
+        await handleCreateFromTemplate({});
+      }
+    } catch (err) {
+      console.error("Failed to read template:", err);
+      toast.error(`Failed to read template: ${err}`);
+    }
+  };
+
+  // Creates "untitled" (or untitled 2, …) in the chosen note and opens it with
+  // the caret where the template put it
+  const handleCreateFromTemplate = async (values: Record<number, string>) => {
+    const target = templateTarget();
+    setTemplateFields(null);
+    setTemplateTarget(null);
+    if (!target) return;
+    const { parent, template } = target;
+    const prefix = parent ? `${parent}/` : "";
+    try {
+      const siblings = await commands.getChildren(parent);
+      const taken = new Set(siblings.map((note) => note.path));
+      let newPath = `${prefix}untitled`;
+      for (let n = 2; taken.has(newPath); n++) {
+        newPath = `${prefix}untitled ${n}`;
+      }
+      const note = await commands.createFromTemplate(newPath, template, values);
+      setChildrenCache(new Map());
+      setHasChildrenMap({});
+      props.onRefresh?.();
+      if (note.cursor !== null) {
+        requestCaret({ path: newPath, offset: note.cursor });
+      }
+      notes.setCurrentPath(newPath);
+      dialogRef?.close();
+      setOpenPanels([]);
+    } catch (err) {
+      console.error("Failed to create note from template:", err);
+      toast.error(`Failed to create note: ${err}`);
+    }
+  };
+
+  // Copies the note and its children next to it as "<name> copy" (or copy 2, …)
+  const handleDuplicate = async (path: string) => {
+    const parent = getParentPath(path);
+    const prefix = parent ? `${parent}/` : "";
+    try {
+      // Root notes have no parent to list children of
+      const siblings = parent
+        ? await commands.getChildren(parent)
+        : await commands.getRootNotes();
+      const taken = new Set(siblings.map((note) => note.path));
+      let newPath = `${path} copy`;
+      for (let n = 2; taken.has(newPath); n++) {
+        newPath = `${path} copy ${n}`;
+      }
+      await commands.duplicateNote(path, newPath, true);
+      setChildrenCache(new Map());
+      setHasChildrenMap({});
+      props.onRefresh?.();
+      toast.success(`Duplicated as ${newPath.slice(prefix.length)}`, {
+        duration: "short",
+      });
+    } catch (err) {
+      console.error("Failed to duplicate note:", err);
+      toast.error(`Failed to duplicate: ${err}`);
+    }
+  };
+
+  // Confirms with a dry run before importing
+  const handleImportVault = async (path: string) => {
+    const srcDir = await openDialog({ directory: true });
+    if (typeof srcDir !== "string") return;
+
+    try {
+      const preview = await commands.importVault(srcDir, path, true);
+      const count = preview.created.length;
+      if (count === 0) {
+        toast.info("Nothing new to import", { duration: "short" });
+        return;
+      }
+      const skipped = preview.conflicts.length
+        ? ` ${preview.conflicts.length} existing items will be skipped.`
+        : "";
+      if (!(await ask(`Import ${count} notes?${skipped}`))) return;
+
+      await commands.importVault(srcDir, path, false);
+      setChildrenCache(new Map());
+      setHasChildrenMap({});
+      props.onRefresh?.();
+      toast.success(`${count} notes imported`, { duration: "short" });
+    } catch (err) {
+      console.error("Failed to import folder:", err);
+      toast.error(`Failed to import: ${err}`);
+    }
+  };
+
+  // Takes "key: value" (an empty value removes the property) and confirms
+  // with a dry run before writing
+  const handleSetProperty = async (input: string) => {
+    const target = propertyTarget();
+    setPropertyTarget(null);
+    if (!target) return;
+
+    const [rawKey, ...rest] = input.split(":");
+    const key = rawKey.trim();
+    const value = rest.join(":").trim() || null;
+    if (!key) return;
+
+    try {
+      const preview = await commands.bulkSetProperty(target, key, value, true);
+      const count = preview.changes.length;
+      if (count === 0) {
+        toast.info("No notes need changing", { duration: "short" });
+        return;
+      }
+      const action = value ? `Set ${key} to "${value}"` : `Remove ${key}`;
+      if (!(await ask(`${action} on ${count} notes?`))) return;
+
+      await commands.bulkSetProperty(target, key, value, false);
+      setSelection([]);
+      props.onRefresh?.();
+      toast.success(`${count} notes updated`, { duration: "short" });
+    } catch (err) {
+      console.error("Failed to set property:", err);
+      toast.error(`Failed to set property: ${err}`);
+    }
   };
 
   const handleArchiveItem = async (item: NoteMetadata) => {
@@ -322,6 +899,7 @@ export function DropdownMenu(props: DropdownMenuProps) {
   };
 
   const handleCreateChild = async (parentPath: string) => {
+    if (!notes.editable()) return;
     setCreateAtPath(parentPath);
     setShowModal(true);
     dialogRef?.close();
@@ -344,9 +922,157 @@ export function DropdownMenu(props: DropdownMenuProps) {
   };
 
   const createContextMenuItems = (note: NoteMetadata): MenuItem[] => {
+    const selected = selection();
+    if (selected.length > 1 && selected.includes(note.path)) {
+      return [
+        {
+          label: `Move ${selected.length} notes`,
+          disabled: !notes.editable(),
+          onClick: () => {
+            handleMoveSelection();
+          },
+        },
+        { separator: true },
+        {
+          label: "Set property…",
+          disabled: !notes.editable(),
+          onClick: () => {
+            setPropertyTarget({ kind: "paths", paths: selected });
+          },
+        },
+        ...(exportFormats() ?? []).map((format) => ({
+          label: `Export ${selected.length} notes as ${format.name}…`,
+          onClick: () => {
+            handleExportSelection(format);
+          },
+        })),
+        { separator: true },
+        {
+          label: `Archive ${selected.length} notes`,
+          disabled: !notes.editable(),
+          onClick: () => {
+            handleArchiveSelection();
+          },
+        },
+      ];
+    }
+
     return [
+      {
+        label: "Read whole project",
+        disabled: !hasChildrenMap()[note.path],
+        onClick: () => {
+          setBookPath(note.path);
+        },
+      },
+      {
+        label: "Cycle status",
+        disabled: !notes.editable(),
+        onClick: () => {
+          handleCycleStatus(note);
+        },
+      },
+      {
+        label: isFavorite(note.path) ? "Unstar" : "Star",
+        onClick: () => {
+          handleToggleFavorite(note.path);
+        },
+      },
+      {
+        label: "Copy link",
+        onClick: () => {
+          handleCopyLink(note.path);
+        },
+      },
+      {
+        label: "Unlinked mentions…",
+        onClick: () => {
+          setMentionsPath(note.path);
+        },
+      },
+      {
+        label: "History…",
+        onClick: () => {
+          setHistoryPath(note.path);
+        },
+      },
+      ...(exportFormats() ?? []).map((format) => ({
+        label: `Copy as ${format.name}`,
+        onClick: () => {
+          handleCopyAs(note.path, format);
+        },
+      })),
+      {
+        label: "Set property on children…",
+        disabled: !notes.editable() || !hasChildrenMap()[note.path],
+        onClick: () => {
+          setPropertyTarget({ kind: "children_of", path: note.path });
+        },
+      },
+      {
+        label: "Update map of content",
+        disabled: !notes.editable() || !hasChildrenMap()[note.path],
+        onClick: () => {
+          handleGenerateMoc(note.path);
+        },
+      },
+      { separator: true },
+      {
+        label: "New from template…",
+        disabled: !notes.editable(),
+        onClick: () => {
+          setTemplateParent(note.path);
+        },
+      },
+      {
+        label: "Import CSV as notes…",
+        disabled: !notes.editable(),
+        onClick: () => {
+          handleImportCsv(note.path, "child_notes");
+        },
+      },
+      {
+        label: "Import CSV as table…",
+        disabled: !notes.editable(),
+        onClick: () => {
+          handleImportCsv(note.path, "table");
+        },
+      },
+      {
+        label: "Import Markdown folder…",
+        disabled: !notes.editable(),
+        onClick: () => {
+          handleImportVault(note.path);
+        },
+      },
+      { separator: true },
+      {
+        label: note.stats?.pinned ? "Unpin" : "Pin",
+        disabled: !notes.editable(),
+        onClick: () => {
+          handleTogglePin(note);
+        },
+      },
+      {
+        label: note.stats?.encrypted ? "Decrypt" : "Encrypt",
+        disabled: !notes.editable(),
+        onClick: () => {
+          handleToggleEncryption(note);
+        },
+      },
+      ...(note.stats?.encrypted
+        ? [{ label: "Lock vault", onClick: handleLockVault }]
+        : []),
+      {
+        label: "Duplicate",
+        disabled: !notes.editable(),
+        onClick: () => {
+          handleDuplicate(note.path);
+        },
+      },
       {
         label: "Move",
+        disabled: !notes.editable(),
         onClick: () => {
           handleMoveNote(note.path);
         },
@@ -354,12 +1080,14 @@ export function DropdownMenu(props: DropdownMenuProps) {
       { separator: true },
       {
         label: "Archive",
+        disabled: !notes.editable(),
         onClick: () => {
           handleArchiveItem(note);
         },
       },
       {
         label: "Trash",
+        disabled: !notes.editable(),
         onClick: () => {
           handleTrashItem(note);
         },
@@ -373,52 +1101,80 @@ export function DropdownMenu(props: DropdownMenuProps) {
 
   const handleMoveNote = (notePath: string) => {
     setNoteToMove(notePath);
+    setMovingSelection(false);
     setShowNoteFinder(true);
     dialogRef?.close();
     setOpenPanels([]);
   };
 
-  const handleMoveToDestination = async (destination: NoteMetadata) => {
-    const sourceNotePath = noteToMove();
-    if (!sourceNotePath) return;
+  const handleMoveSelection = () => {
+    setNoteToMove(null);
+    setMovingSelection(true);
+    setShowNoteFinder(true);
+    dialogRef?.close();
+    setOpenPanels([]);
+  };
 
-    const noteTitle = sourceNotePath.split("/").pop();
-    if (!noteTitle) return;
+  const moveSelectionTo = async (destination: NoteMetadata) => {
+    const paths = selection();
+    if (paths.length === 0) return;
 
-    const newPath = destination.path
-      ? `${destination.path}/${noteTitle}`
-      : noteTitle;
+    try {
+      await commands.moveNotes(paths, destination.path);
+
+      // Follow the current note if it was moved along
+      const current = notes.currentPath();
+      const moved = selectedAncestor(paths, current);
+      if (moved) {
+        const name = moved.split("/").pop();
+        const newRoot = destination.path ? `${destination.path}/${name}` : name;
+        notes.setCurrentPath(newRoot + current.slice(moved.length));
+      }
 
-    // Prevent moving to itself
-    if (sourceNotePath === newPath) {
-      toast.error("Cannot move note to itself");
-      return;
+      setSelection([]);
+      setChildrenCache(new Map());
+      setHasChildrenMap({});
+      props.onRefresh?.();
+
+      toast.success(`${paths.length} notes moved`, { duration: "short" });
+    } catch (err) {
+      console.error("Failed to move notes:", err);
+      toast.error(`Failed to move: ${err}`);
     }
+  };
 
-    // Prevent moving to a descendant
-    if (newPath.startsWith(sourceNotePath + "/")) {
-      toast.error("Cannot move note to its own descendant");
+  const handleMoveToDestination = async (destination: NoteMetadata) => {
+    if (movingSelection()) {
+      setMovingSelection(false);
+      await moveSelectionTo(destination);
       return;
     }
 
+    const sourceNotePath = noteToMove();
+    if (!sourceNotePath) return;
+
     try {
-      await commands.renameNote(sourceNotePath, newPath);
+      // A clashing name gets a suffix; notes:moved follows the open note
+      const moved = await commands.moveNote(
+        sourceNotePath,
+        destination.path,
+        "suffix",
+      );
 
       // Invalidate cache for both old and new parents
       const cache = childrenCache();
-      const oldParent = sourceNotePath.split("/").slice(0, -1).join("/");
-      const newParent = destination.path;
-      cache.delete(oldParent);
-      cache.delete(newParent);
+      cache.delete(getParentPath(sourceNotePath));
+      cache.delete(destination.path);
       setChildrenCache(new Map(cache));
 
-      // Update current path if the moved note was selected
-      if (notes.currentPath() === sourceNotePath) {
-        notes.setCurrentPath(newPath);
-      }
-
       props.onRefresh?.();
-      toast.success("Note moved", { duration: "short" });
+      const name = getPathTitle(moved.to);
+      toast.success(
+        name === getPathTitle(sourceNotePath)
+          ? "Note moved"
+          : `Note moved as ${name}`,
+        { duration: "short" },
+      );
     } catch (err) {
       console.error("Failed to move note:", err);
       toast.error(`Failed to move: ${err}`);
@@ -453,6 +1209,12 @@ export function DropdownMenu(props: DropdownMenuProps) {
         placeholder="untitled"
         onClose={() => setShowModal(false)}
       />
+      <InputModal
+        open={propertyTarget() !== null}
+        onSubmit={handleSetProperty}
+        placeholder="status: done"
+        onClose={() => setPropertyTarget(null)}
+      />
       <NoteFinder
         open={showNoteFinder()}
         onSelect={handleMoveToDestination}
@@ -461,9 +1223,41 @@ export function DropdownMenu(props: DropdownMenuProps) {
         excludePath={noteToMove()}
         rankBy="frecency"
       />
+      <NoteFinder
+        open={templateParent() !== null}
+        onSelect={handlePickTemplate}
+        onClose={() => setTemplateParent(null)}
+        placeholder="Template..."
+        rankBy="frecency"
+      />
+      <TemplatePrompt
+        fields={templateFields()}
+        onSubmit={handleCreateFromTemplate}
+        onClose={() => {
+          setTemplateFields(null);
+          setTemplateTarget(null);
+        }}
+      />
+      <PassphrasePrompt
+        mode={unlockRequest()?.mode ?? null}
+        onSubmit={handleUnlock}
+        onClose={() => setUnlockRequest(null)}
+      />
+      <BookView path={bookPath()} onClose={() => setBookPath(null)} />
+      <UnlinkedMentions
+        path={mentionsPath()}
+        onClose={() => setMentionsPath(null)}
+      />
+      <NoteHistory
+        path={historyPath()}
+        onClose={() => setHistoryPath(null)}
+      />
       <button
         ref={buttonRef}
         class={`hover:bg-button-hover rounded px-0.5 font-mono ${props.isActive ? "" : "opacity-60"}`}
+        aria-label={props.path ? "Notes inside this note" : "All notes"}
+        aria-haspopup="menu"
+        aria-expanded={openPanels().length > 0}
         onClick={handleClick}
         onMouseDown={() => {
           // Close context menu when clicking outside
@@ -492,6 +1286,7 @@ export function DropdownMenu(props: DropdownMenuProps) {
           handleDialogClick(e);
           handleCloseContextMenu();
         }}
+        onClose={saveShape}
         onMouseDown={(e) => {
           if (contextMenu() && e.target === dialogRef) {
             handleCloseContextMenu();
@@ -503,6 +1298,7 @@ export function DropdownMenu(props: DropdownMenuProps) {
             <MenuPanel
               parentPath={panel.parentPath}
               items={panel.items}
+              favorites={panel.parentPath ? undefined : favorites()}
               left={panel.left}
               top={panel.top}
               level={index()}
@@ -511,10 +1307,13 @@ export function DropdownMenu(props: DropdownMenuProps) {
               onClickItem={handleClickItem}
               onArchiveItem={handleArchiveItem}
               onCreateChild={handleCreateChild}
+              onReorder={notes.editable() ? handleReorder : undefined}
               setPanelRef={setPanelRef}
               setRowRef={setRowRef}
               onContextMenu={handleContextMenu}
+              onRowKeyDown={handleRowKeyDown}
               contextMenuNotePath={contextMenu()?.notePath}
+              selectedPaths={selection()}
               createContextMenuItems={createContextMenuItems}
             />
           )}
@@ -0,0 +1,65 @@
+import { createSignal, onMount, Show } from "solid-js";
+import { commands } from "../../api/commands";
+import type { ExportConfig, LinkStyle } from "../../types";
+
+const LINK_STYLES: { value: LinkStyle; label: string }[] = [
+  { value: "wiki", label: "Keep [[wiki links]]" },
+  { value: "relative", label: "Links to exported files" },
+  { value: "published", label: "Links to published site" },
+];
+
+export function ExportSettings() {
+  const [exportConfig, setExportConfig] = createSignal<ExportConfig | null>(
+    null,
+  );
+
+  onMount(async () => {
+    setExportConfig((await commands.getVaultConfig()).export);
+  });
+
+  const updateExport = async (update: Partial<ExportConfig>) => {
+    const config = await commands.getVaultConfig();
+    const updated = { ...config.export, ...update };
+    await commands.setVaultConfig({ ...config, export: updated });
+    setExportConfig(updated);
+  };
+
+  return (
+    <Show when={exportConfig()}>
+      {(current) => (
+        <div class="space-y-4">
+          <div class="flex items-center justify-between">
+            <label for="export-link-style">Links in exported notes</label>
+            <select
+              id="export-link-style"
+              value={current().links}
+              onChange={(e) =>
+                updateExport({ links: e.currentTarget.value as LinkStyle })
+              }
+              class="text-text rounded border bg-transparent px-2 py-1"
+            >
+              {LINK_STYLES.map((style) => (
+                <option value={style.value}>{style.label}</option>
+              ))}
+            </select>
+          </div>
+          <Show when={current().links === "published"}>
+            <div class="flex items-center justify-between">
+              <label for="export-base-url">Published site URL</label>
+              <input
+                id="export-base-url"
+                type="url"
+                value={current().base_url}
+                onChange={(e) =>
+                  updateExport({ base_url: e.currentTarget.value.trim() })
+                }
+                class="text-text w-56 rounded border bg-transparent px-2 py-1 outline-none"
+                placeholder="https://notes.example.com"
+              />
+            </div>
+          </Show>
+        </div>
+      )}
+    </Show>
+  );
+}
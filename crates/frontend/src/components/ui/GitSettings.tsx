@@ -0,0 +1,164 @@
+import { createResource, For, Show } from "solid-js";
+import { commands } from "../../api/commands";
+import type { PullOutcome } from "../../types";
+import { Checkbox } from "../primitives/form";
+import { useToast } from "./Toast";
+
+const PULL_MESSAGES: Record<PullOutcome["status"], string> = {
+  up_to_date: "Already up to date",
+  fast_forward: "Pulled remote changes",
+  merged: "Merged remote changes",
+  conflicts: "Some notes changed on both sides",
+};
+
+export function GitSettings() {
+  const toast = useToast();
+  const [status, { refetch }] = createResource(commands.getGitStatus);
+  const [config, { mutate: setConfig }] = createResource(
+    async () => (await commands.getVaultConfig()).git,
+  );
+
+  const run = async (action: () => Promise<string>) => {
+    try {
+      toast.success(await action());
+    } catch (error) {
+      toast.error(String(error));
+    }
+    refetch();
+  };
+
+  const setAutoCommit = async (auto_commit: boolean) => {
+    const current = await commands.getVaultConfig();
+    const git = { ...current.git, auto_commit };
+    await commands.setVaultConfig({ ...current, git });
+    setConfig(git);
+  };
+
+  const init = () =>
+    run(async () => {
+      await commands.gitInit();
+      setConfig((await commands.getVaultConfig()).git);
+      return "The vault is now versioned with git";
+    });
+
+  const commit = () =>
+    run(async () =>
+      (await commands.gitCommit()) === null
+        ? "Nothing to commit"
+        : "Committed changes",
+    );
+
+  const pull = () =>
+    run(async () => PULL_MESSAGES[(await commands.gitPull()).status]);
+
+  const push = () =>
+    run(async () => {
+      await commands.gitPush();
+      return "Pushed";
+    });
+
+  const resolve = (path: string) =>
+    run(async () =>
+      (await commands.gitResolve(path))
+        ? "Merge finished"
+        : `Resolved ${path}`,
+    );
+
+  return (
+    <div class="space-y-2">
+      <label>Git</label>
+      <Show
+        when={status()?.branch}
+        fallback={
+          <div class="flex items-center justify-between">
+            <span class="text-text-muted text-sm">
+              Keep the vault's history in a git repository
+            </span>
+            <button
+              type="button"
+              onClick={init}
+              class="bg-button-bg hover:bg-button-hover rounded border px-2 py-1 text-sm"
+            >
+              Turn on
+            </button>
+          </div>
+        }
+      >
+        {(branch) => (
+          <>
+            <div class="text-text-muted space-y-1 text-sm">
+              <div class="flex justify-between">
+                <span>Branch</span>
+                <span>{branch()}</span>
+              </div>
+              <div class="flex justify-between">
+                <span>Uncommitted notes</span>
+                <span>{status()!.changed.length}</span>
+              </div>
+              <div class="flex justify-between">
+                <span>To push / to pull</span>
+                <span>
+                  {status()!.ahead} / {status()!.behind}
+                </span>
+              </div>
+            </div>
+            <div class="flex items-center justify-between">
+              <label>Commit automatically</label>
+              <Checkbox
+                checked={config()?.auto_commit ?? true}
+                onChange={setAutoCommit}
+              />
+            </div>
+            <Show when={status()!.conflicts.length > 0}>
+              <div class="space-y-1 text-sm">
+                <p class="text-text-muted">
+                  Edit away the conflict markers in each note, then mark it
+                  resolved
+                </p>
+                <For each={status()!.conflicts}>
+                  {(path) => (
+                    <div class="flex items-center justify-between">
+                      <span>{path}</span>
+                      <button
+                        type="button"
+                        onClick={() => resolve(path)}
+                        class="text-text-muted hover:text-text text-xs hover:underline"
+                      >
+                        Resolved
+                      </button>
+                    </div>
+                  )}
+                </For>
+              </div>
+            </Show>
+            <div class="flex flex-wrap gap-2 pt-1">
+              <button
+                type="button"
+                onClick={commit}
+                disabled={status()!.conflicts.length > 0}
+                class="bg-button-bg hover:bg-button-hover rounded border px-2 py-1 text-sm disabled:opacity-50"
+              >
+                Commit now
+              </button>
+              <button
+                type="button"
+                onClick={pull}
+                disabled={status()!.conflicts.length > 0}
+                class="bg-button-bg hover:bg-button-hover rounded border px-2 py-1 text-sm disabled:opacity-50"
+              >
+                Pull
+              </button>
+              <button
+                type="button"
+                onClick={push}
+                class="bg-button-bg hover:bg-button-hover rounded border px-2 py-1 text-sm"
+              >
+                Push
+              </button>
+            </div>
+          </>
+        )}
+      </Show>
+    </div>
+  );
+}
@@ -39,6 +39,7 @@ export function InputModal(props: {
         <input
           ref={inputRef}
           type="text"
+          dir="auto"
           class="text-text w-full bg-transparent outline-none"
           placeholder={props.placeholder}
           value={value()}
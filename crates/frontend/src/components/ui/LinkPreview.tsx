@@ -0,0 +1,73 @@
+import { createResource, onCleanup, onMount, Show } from "solid-js";
+import { commands } from "../../api/commands";
+import type { HoveredLink } from "../editor/wikiLinkHover";
+
+// Hover card for a [[wiki-link]]: title, last change, and the note's opening
+export function LinkPreview(props: {
+  link: HoveredLink;
+  onOpen: (path: string) => void;
+  onClose: () => void;
+  onMouseEnter: () => void;
+  onMouseLeave: () => void;
+}) {
+  const [preview] = createResource(
+    () => props.link.path,
+    (path) => commands.getNotePreview(path),
+  );
+
+  const handleKeyDown = (e: KeyboardEvent) => {
+    if (e.key === "Escape") props.onClose();
+  };
+
+  onMount(() => {
+    document.addEventListener("keydown", handleKeyDown);
+    onCleanup(() => document.removeEventListener("keydown", handleKeyDown));
+  });
+
+  // Below the link, or above it when there's no room
+  const style = () => {
+    const left = `${props.link.left}px`;
+    const below = props.link.bottom + 4;
+    if (below + 160 <= window.innerHeight) return { left, top: `${below}px` };
+    return { left, bottom: `${window.innerHeight - props.link.top + 4}px` };
+  };
+
+  return (
+    <div
+      role="tooltip"
+      class="bg-paper text-text fixed z-[90] w-72 rounded-md border px-3 py-2 text-sm shadow-md"
+      style={style()}
+      onMouseEnter={() => props.onMouseEnter()}
+      onMouseLeave={() => props.onMouseLeave()}
+    >
+      <Show
+        when={!preview.error}
+        fallback={
+          <span class="opacity-60">No note at {props.link.path}</span>
+        }
+      >
+        <Show when={preview()} fallback={<span class="opacity-60">...</span>}>
+          {(note) => (
+            <>
+              <button
+                type="button"
+                class="block w-full truncate text-left font-medium hover:underline"
+                onClick={() => props.onOpen(note().path)}
+              >
+                {note().title}
+              </button>
+              <div class="text-text-muted text-xs">
+                {new Date(note().modified * 1000).toLocaleString()}
+              </div>
+              <Show when={note().excerpt}>
+                <p class="text-text-muted mt-1 line-clamp-4" dir="auto">
+                  {note().excerpt}
+                </p>
+              </Show>
+            </>
+          )}
+        </Show>
+      </Show>
+    </div>
+  );
+}
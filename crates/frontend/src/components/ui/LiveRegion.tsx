@@ -0,0 +1,9 @@
+import { announcement } from "../../utils/announce";
+
+export function LiveRegion() {
+  return (
+    <div class="sr-only" role="status" aria-live="polite" aria-atomic="true">
+      {announcement()}
+    </div>
+  );
+}
@@ -1,7 +1,8 @@
-import { For } from "solid-js";
+import { createSignal, For, Show } from "solid-js";
 import { getPathTitle } from "../../utils/paths";
+import { STATUS_LABELS, STATUS_MARKS } from "../../utils/status";
 import type { NoteMetadata } from "../../types";
-import type { MenuItem } from "./ContextMenu";
+import { handleMenuKeyDown, type MenuItem } from "./ContextMenu";
 import { Card } from "../primitives/Card";
 
 const MAX_TITLE_LENGTH = 18;
@@ -19,18 +20,24 @@ function truncateTitle(
 interface MenuPanelProps {
   parentPath: string;
   items: NoteMetadata[];
+  // Starred notes, listed above the items
+  favorites?: NoteMetadata[];
   left: number;
   top: number;
   level: number;
   hasChildrenMap?: Record<string, boolean>;
   contextMenuNotePath?: string;
+  selectedPaths?: string[];
 
   // Callbacks
   onHoverItem: (level: number, item: NoteMetadata) => void;
-  onClickItem: (item: NoteMetadata) => void;
+  onClickItem: (item: NoteMetadata, e: MouseEvent) => void;
   onArchiveItem: (item: NoteMetadata) => void;
   onCreateChild: (parentPath: string) => void;
+  // Rows can be dragged into a new order when set
+  onReorder?: (parentPath: string, orderedPaths: string[]) => void;
   onContextMenu: (e: MouseEvent, note: NoteMetadata, items: MenuItem[]) => void;
+  onRowKeyDown: (e: KeyboardEvent, level: number, note: NoteMetadata) => void;
   createContextMenuItems: (note: NoteMetadata) => MenuItem[];
 
   // Ref callbacks for positioning
@@ -39,9 +46,28 @@ interface MenuPanelProps {
 }
 
 export function MenuPanel(props: MenuPanelProps) {
+  const [dragged, setDragged] = createSignal<string | null>(null);
+
+  // Puts the dragged row before the one it was dropped on
+  const handleDrop = (target: string) => {
+    const source = dragged();
+    setDragged(null);
+    if (!source || source === target || !props.onReorder) return;
+    const paths = props.items
+      .map((item) => item.path)
+      .filter((path) => path !== source);
+    paths.splice(paths.indexOf(target), 0, source);
+    props.onReorder(props.parentPath, paths);
+  };
+
   return (
     <Card
       ref={(el) => props.setPanelRef(props.level, el)}
+      role="menu"
+      aria-label={
+        props.parentPath ? getPathTitle(props.parentPath) : "All notes"
+      }
+      onKeyDown={handleMenuKeyDown}
       class="absolute w-fit min-w-[140px] outline-none"
       style={{
         left: `${props.left}px`,
@@ -50,11 +76,50 @@ export function MenuPanel(props: MenuPanelProps) {
       }}
       data-level={props.level}
     >
+      <Show when={props.favorites?.length}>
+        <div role="group" aria-label="Starred" class="border-b pb-1">
+          <div class="px-2 pt-1.5 text-xs opacity-50 select-none">Starred</div>
+          <For each={props.favorites}>
+            {(note) => (
+              <button
+                role="menuitem"
+                onClick={(e) => props.onClickItem(note, e)}
+                onContextMenu={(e) =>
+                  props.onContextMenu(
+                    e,
+                    note,
+                    props.createContextMenuItems(note),
+                  )
+                }
+                class="block w-full px-2 py-1.5 text-left whitespace-nowrap outline-none select-none hover:underline"
+              >
+                {truncateTitle(getPathTitle(note.path))}
+              </button>
+            )}
+          </For>
+        </div>
+      </Show>
       <For each={props.items}>
         {(note) => {
           return (
             <div
               class="group flex items-center"
+              classList={{
+                "opacity-40": dragged() === note.path,
+              }}
+              draggable={!!props.onReorder}
+              onDragStart={(e) => {
+                setDragged(note.path);
+                e.dataTransfer?.setData("text/plain", note.path);
+              }}
+              onDragEnd={() => setDragged(null)}
+              onDragOver={(e) => {
+                if (dragged()) e.preventDefault();
+              }}
+              onDrop={(e) => {
+                e.preventDefault();
+                handleDrop(note.path);
+              }}
               onMouseEnter={() => props.onHoverItem(props.level, note)}
               onContextMenu={(e) =>
                 props.onContextMenu(e, note, props.createContextMenuItems(note))
@@ -62,19 +127,58 @@ export function MenuPanel(props: MenuPanelProps) {
             >
               <button
                 ref={(el) => props.setRowRef(note.path, el)}
-                onClick={() => props.onClickItem(note)}
+                role="menuitem"
+                aria-haspopup={
+                  props.hasChildrenMap?.[note.path] ? "menu" : undefined
+                }
+                aria-keyshortcuts="ArrowRight Delete Shift+F10 Control+Shift+S Meta+Shift+S"
+                onClick={(e) => props.onClickItem(note, e)}
+                onKeyDown={(e) => props.onRowKeyDown(e, props.level, note)}
                 class="px-2 py-1.5 pr-0 text-left whitespace-nowrap outline-none select-none hover:underline"
                 classList={{
                   underline: props.contextMenuNotePath === note.path,
+                  "bg-button-hover": props.selectedPaths?.includes(note.path),
                 }}
               >
+                {note.stats?.pinned && (
+                  <span
+                    role="img"
+                    aria-label="Pinned"
+                    title="Pinned"
+                    class="mr-1 text-xs opacity-60"
+                  >
+                    •
+                  </span>
+                )}
+                {note.stats?.encrypted && (
+                  <span
+                    role="img"
+                    aria-label="Encrypted"
+                    title="Encrypted"
+                    class="mr-1 text-xs opacity-60"
+                  >
+                    🔒
+                  </span>
+                )}
                 {truncateTitle(getPathTitle(note.path))}
+                {note.stats?.status && (
+                  <span
+                    role="img"
+                    aria-label={STATUS_LABELS[note.stats.status]}
+                    title={STATUS_LABELS[note.stats.status]}
+                    class="ml-1.5 text-xs opacity-60"
+                  >
+                    {STATUS_MARKS[note.stats.status]}
+                  </span>
+                )}
               </button>
               <span class="relative ml-1 inline-flex w-2 items-center">
                 {props.hasChildrenMap?.[note.path] && (
                   <span class="text-xs opacity-50 group-hover:hidden">›</span>
                 )}
+                {/* Keyboard users archive with Delete on the row */}
                 <span
+                  aria-hidden="true"
                   class="hover:text-red hidden -translate-x-0.5 cursor-pointer p-0.5 opacity-50 group-hover:inline-flex hover:opacity-80"
                   onClick={(e) => {
                     e.stopPropagation();
@@ -89,6 +193,7 @@ export function MenuPanel(props: MenuPanelProps) {
         }}
       </For>
       <button
+        role="menuitem"
         onClick={() => props.onCreateChild(props.parentPath)}
         class="w-full px-2 py-2 text-left opacity-60 outline-none select-none hover:underline"
       >
@@ -0,0 +1,145 @@
+import { createResource, createSignal, For, Show } from "solid-js";
+import { commands } from "../../api/commands";
+import type { HunkChoice, Merge, MergeHunk } from "../../types";
+import { Modal } from "../primitives/Modal";
+
+const CHOICES: { choice: HunkChoice; label: string }[] = [
+  { choice: "mine", label: "Use mine" },
+  { choice: "theirs", label: "Use theirs" },
+  { choice: "both", label: "Use both" },
+];
+
+// A hunk's lines in the merged note; the choice only matters for conflicts
+function hunkLines(hunk: MergeHunk, choice: HunkChoice | undefined) {
+  switch (hunk.kind) {
+    case "unchanged":
+      return hunk.base;
+    case "mine":
+    case "both":
+      return hunk.mine;
+    case "theirs":
+      return hunk.theirs;
+    case "conflict":
+      if (choice === "theirs") return hunk.theirs;
+      if (choice === "both") return [...hunk.mine, ...hunk.theirs];
+      return hunk.mine;
+  }
+}
+
+function resolve(merge: Merge, choices: Record<number, HunkChoice>) {
+  const text = merge.hunks
+    .flatMap((hunk, i) => hunkLines(hunk, choices[i]))
+    .join("\n");
+  return merge.trailing_newline && text ? `${text}\n` : text;
+}
+
+// Three panes for settling a save conflict: the editor's version, the one on
+// disk, and the merge. Changes made on one side only are taken as they are;
+// each conflicting hunk is settled with its own buttons.
+export function MergeEditor(props: {
+  base: string;
+  mine: string;
+  theirs: string;
+  onSave: (merged: string) => void;
+  onClose: () => void;
+}) {
+  const [choices, setChoices] = createSignal<Record<number, HunkChoice>>({});
+  const [merge] = createResource(() =>
+    commands.mergeConflict(props.base, props.mine, props.theirs),
+  );
+
+  const unresolved = () =>
+    merge()?.hunks.filter(
+      (hunk, i) => hunk.kind === "conflict" && !(i in choices()),
+    ).length ?? 0;
+
+  const choose = (index: number, choice: HunkChoice) =>
+    setChoices((current) => ({ ...current, [index]: choice }));
+
+  const handleSave = () => {
+    const current = merge();
+    if (current) props.onSave(resolve(current, choices()));
+  };
+
+  const pane = (lines: string[], highlight: boolean) => (
+    <div
+      class={`min-w-0 flex-1 px-2 py-0.5 whitespace-pre-wrap ${highlight ? "bg-selection" : ""}`}
+    >
+      {lines.join("\n")}
+    </div>
+  );
+
+  return (
+    <Modal
+      open
+      onClose={props.onClose}
+      class="bg-paper text-text flex h-[75vh] w-[1100px] max-w-[95vw] flex-col rounded-md border outline-none"
+    >
+      <div class="flex border-b px-4 py-3">
+        <div class="flex-1">Mine</div>
+        <div class="flex-1">Theirs (on disk)</div>
+        <div class="flex-1">Result</div>
+      </div>
+      <div class="min-h-0 flex-1 overflow-auto font-mono text-xs">
+        <Show
+          when={!merge.error}
+          fallback={<div class="p-3 opacity-60">Could not merge</div>}
+        >
+          <For each={merge()?.hunks}>
+            {(hunk, i) => (
+              <div class="border-b">
+                <Show when={hunk.kind === "conflict"}>
+                  <div
+                    role="group"
+                    aria-label="Conflicting change"
+                    class="bg-red/10 flex gap-2 px-2 py-1"
+                  >
+                    <For each={CHOICES}>
+                      {({ choice, label }) => (
+                        <button
+                          type="button"
+                          aria-pressed={choices()[i()] === choice}
+                          class={`hover:bg-button-hover rounded px-1.5 ${choices()[i()] === choice ? "bg-button-bg" : ""}`}
+                          onClick={() => choose(i(), choice)}
+                        >
+                          {label}
+                        </button>
+                      )}
+                    </For>
+                  </div>
+                </Show>
+                <div class="flex">
+                  {pane(hunk.mine, hunk.kind === "mine")}
+                  {pane(hunk.theirs, hunk.kind === "theirs")}
+                  {pane(hunkLines(hunk, choices()[i()]), false)}
+                </div>
+              </div>
+            )}
+          </For>
+        </Show>
+      </div>
+      <div class="flex items-center justify-end gap-2 border-t px-4 py-2">
+        <Show when={unresolved() > 0}>
+          <span class="text-text-muted mr-auto text-xs">
+            {unresolved()} conflicts left; unsettled ones keep your version
+          </span>
+        </Show>
+        <button
+          type="button"
+          class="hover:bg-button-hover rounded px-2 py-1"
+          onClick={props.onClose}
+        >
+          Cancel
+        </button>
+        <button
+          type="button"
+          class="bg-button-bg hover:bg-button-hover rounded px-2 py-1 disabled:opacity-50"
+          disabled={!merge()}
+          onClick={handleSave}
+        >
+          Save merge
+        </button>
+      </div>
+    </Modal>
+  );
+}
@@ -1,7 +1,16 @@
-import { createSignal, createEffect, For, onMount, onCleanup } from "solid-js";
+import {
+  createSignal,
+  createEffect,
+  createUniqueId,
+  For,
+  onMount,
+  onCleanup,
+} from "solid-js";
+import { useNotes } from "../../api";
 import { commands, type RankingMode } from "../../api/commands";
 import type { NoteMetadata } from "../../types";
 import { Modal } from "../primitives/Modal";
+import { announce } from "../../utils/announce";
 
 export function NoteFinder(props: {
   open: boolean;
@@ -10,13 +19,21 @@ export function NoteFinder(props: {
   placeholder?: string;
   excludePath?: string | null;
   rankBy?: RankingMode;
+  // Remember picked queries and recall them with Alt+Up/Down
+  recordHistory?: boolean;
 }) {
+  const notes = useNotes();
   const [query, setQuery] = createSignal("");
   const [results, setResults] = createSignal<NoteMetadata[]>([]);
   const [selectedIndex, setSelectedIndex] = createSignal(0);
   const [isLoading, setIsLoading] = createSignal(false);
   let inputRef: HTMLInputElement | undefined;
   let resultsContainerRef: HTMLDivElement | undefined;
+  // Recent searches, newest first; historyIndex is -1 while not recalling
+  const [history, setHistory] = createSignal<string[]>([]);
+  const [historyIndex, setHistoryIndex] = createSignal(-1);
+  const listboxId = createUniqueId();
+  const optionId = (index: number) => `${listboxId}-${index}`;
 
   const handleClose = () => {
     setQuery("");
@@ -31,6 +48,33 @@ export function NoteFinder(props: {
     }
   });
 
+  createEffect(() => {
+    if (!props.open || !props.recordHistory) return;
+    setHistoryIndex(-1);
+    commands
+      .getSearchHistory("search")
+      .then((entries) => setHistory(entries.map((entry) => entry.query)))
+      .catch((err) => console.error("Failed to load search history:", err));
+  });
+
+  const recall = (step: number) => {
+    const next = historyIndex() + step;
+    if (next < -1 || next >= history().length) return;
+    setHistoryIndex(next);
+    setQuery(next === -1 ? "" : history()[next]);
+  };
+
+  const selectNote = (note: NoteMetadata) => {
+    const picked = query().trim();
+    if (props.recordHistory && picked) {
+      commands
+        .recordSearch(picked)
+        .catch((err) => console.error("Failed to record search:", err));
+    }
+    props.onSelect(note);
+    handleClose();
+  };
+
   // Fuzzy search as user types, or show top 6 notes if empty
   createEffect(async () => {
     if (!props.open) return;
@@ -44,12 +88,18 @@ export function NoteFinder(props: {
         searchQuery,
         6,
         rankingMode,
+        notes.currentPath(),
       );
       const filtered = props.excludePath
         ? searchResults.filter((note) => note.path !== props.excludePath)
         : searchResults;
       setResults(filtered);
       setSelectedIndex(0);
+      if (searchQuery.trim()) {
+        announce(
+          filtered.length === 1 ? "1 result" : `${filtered.length} results`,
+        );
+      }
     } catch (err) {
       console.error("Search failed:", err);
       setResults([]);
@@ -75,6 +125,14 @@ export function NoteFinder(props: {
 
     const resultCount = results().length;
 
+    if (props.recordHistory && e.altKey) {
+      if (e.key === "ArrowUp" || e.key === "ArrowDown") {
+        e.preventDefault();
+        recall(e.key === "ArrowUp" ? 1 : -1);
+        return;
+      }
+    }
+
     switch (e.key) {
       case "ArrowDown":
         e.preventDefault();
@@ -93,8 +151,7 @@ export function NoteFinder(props: {
         if (resultCount > 0) {
           const selected = results()[selectedIndex()];
           if (selected) {
-            props.onSelect(selected);
-            handleClose();
+            selectNote(selected);
           }
         }
         break;
@@ -107,8 +164,7 @@ export function NoteFinder(props: {
   });
 
   const handleResultClick = (note: NoteMetadata) => {
-    props.onSelect(note);
-    handleClose();
+    selectNote(note);
   };
 
   return (
@@ -121,15 +177,28 @@ export function NoteFinder(props: {
         <input
           ref={inputRef}
           type="text"
+          dir="auto"
           class="w-full bg-transparent px-2 py-1.5 outline-none"
           placeholder={props.placeholder || "Search notes..."}
+          role="combobox"
+          aria-label={props.placeholder || "Search notes"}
+          aria-expanded={results().length > 0}
+          aria-controls={listboxId}
+          aria-activedescendant={
+            results().length > 0 ? optionId(selectedIndex()) : undefined
+          }
           value={query()}
-          onInput={(e) => setQuery(e.currentTarget.value)}
+          onInput={(e) => {
+            setHistoryIndex(-1);
+            setQuery(e.currentTarget.value);
+          }}
           autofocus
         />
       </div>
       <div
         ref={resultsContainerRef}
+        id={listboxId}
+        role="listbox"
         class="max-h-[400px] overflow-y-auto px-2.5 py-1 pr-1"
         onMouseLeave={() => setSelectedIndex(0)}
       >
@@ -157,6 +226,10 @@ export function NoteFinder(props: {
 
             return (
               <button
+                id={optionId(index())}
+                role="option"
+                aria-selected={index() === selectedIndex()}
+                tabIndex={-1}
                 class="w-full px-2 py-1.5 text-left whitespace-nowrap outline-none select-none hover:underline"
                 classList={{
                   underline: index() === selectedIndex(),
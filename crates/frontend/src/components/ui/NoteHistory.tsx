@@ -0,0 +1,157 @@
+import { createResource, createSignal, For, Show } from "solid-js";
+import { commands } from "../../api/commands";
+import { useNotes } from "../../api";
+import type { DiffLine } from "../../types";
+import { getPathTitle } from "../../utils/paths";
+import { Modal } from "../primitives/Modal";
+import { useToast } from "./Toast";
+
+const LINE_STYLES: Record<DiffLine["kind"], string> = {
+  same: "",
+  added: "bg-green/10 text-green",
+  removed: "bg-red/10 text-red",
+};
+
+const LINE_SIGNS: Record<DiffLine["kind"], string> = {
+  same: " ",
+  added: "+",
+  removed: "-",
+};
+
+// Earlier versions of a note from the vault's checkpoints, each shown as a
+// read-only diff against the current content before it is restored
+export function NoteHistory(props: {
+  path: string | null;
+  onClose: () => void;
+}) {
+  const notes = useNotes();
+  const toast = useToast();
+  const [selected, setSelected] = createSignal<string | null>(null);
+
+  const [history] = createResource(
+    () => props.path,
+    async (path) => {
+      const checkpoints = await commands.getNoteHistory(path);
+      setSelected(checkpoints[0]?.id ?? null);
+      return checkpoints;
+    },
+  );
+  const [diff] = createResource(
+    () => {
+      const path = props.path;
+      const id = selected();
+      return path && id ? { path, id } : null;
+    },
+    ({ path, id }) => commands.getRevisionDiff(path, id, null),
+  );
+
+  const changed = () => diff()?.some((line) => line.kind !== "same");
+
+  const handleRestore = async () => {
+    const path = props.path;
+    const id = selected();
+    if (!path || !id) return;
+    try {
+      const content = await commands.getNoteAtCheckpoint(path, id);
+      await notes.saveNote(path, content);
+      toast.success("Version restored");
+      props.onClose();
+    } catch (err) {
+      console.error("Failed to restore version:", err);
+      toast.error(`Failed to restore: ${err}`);
+    }
+  };
+
+  return (
+    <Modal
+      open={props.path !== null}
+      onClose={props.onClose}
+      class="bg-paper text-text flex h-[70vh] w-[860px] flex-col rounded-md border outline-none"
+    >
+      <div class="border-b px-4 py-3">
+        History of {props.path ? getPathTitle(props.path) : ""}
+      </div>
+      <div class="flex min-h-0 flex-1">
+        <div
+          role="listbox"
+          aria-label="Versions"
+          class="w-56 shrink-0 overflow-y-auto border-r py-1"
+        >
+          <For
+            each={history()}
+            fallback={
+              <div class="px-3 py-1 opacity-60">
+                {history.loading ? "Loading..." : "No earlier versions"}
+              </div>
+            }
+          >
+            {(checkpoint) => (
+              <button
+                type="button"
+                role="option"
+                aria-selected={selected() === checkpoint.id}
+                class={`hover:bg-button-hover block w-full px-3 py-1 text-left ${selected() === checkpoint.id ? "bg-selection" : ""}`}
+                onClick={() => setSelected(checkpoint.id)}
+              >
+                <div class="truncate">{checkpoint.label}</div>
+                <div class="text-text-muted text-xs">
+                  {new Date(checkpoint.created_at * 1000).toLocaleString()}
+                </div>
+              </button>
+            )}
+          </For>
+        </div>
+        <div
+          aria-label="Changes since this version"
+          class="min-w-0 flex-1 overflow-auto font-mono text-xs"
+        >
+          <Show
+            when={!diff.error}
+            fallback={<div class="p-3 opacity-60">Could not load changes</div>}
+          >
+            <Show
+              when={diff() === undefined || changed()}
+              fallback={
+                <div class="p-3 opacity-60">Same as the current version</div>
+              }
+            >
+              <For each={diff()}>
+                {(line) => (
+                  <div class={`flex whitespace-pre ${LINE_STYLES[line.kind]}`}>
+                    <span class="w-10 shrink-0 pr-2 text-right opacity-50">
+                      {line.old_line ?? ""}
+                    </span>
+                    <span class="w-10 shrink-0 pr-2 text-right opacity-50">
+                      {line.new_line ?? ""}
+                    </span>
+                    <span aria-hidden="true" class="w-4 shrink-0">
+                      {LINE_SIGNS[line.kind]}
+                    </span>
+                    <span>{line.text}</span>
+                  </div>
+                )}
+              </For>
+            </Show>
+          </Show>
+        </div>
+      </div>
+      <div class="flex justify-end gap-2 border-t px-4 py-2">
+        <button
+          type="button"
+          class="hover:bg-button-hover rounded px-2 py-1"
+          onClick={props.onClose}
+        >
+          Close
+        </button>
+        <button
+          type="button"
+          class="bg-button-bg hover:bg-button-hover rounded px-2 py-1 disabled:opacity-50"
+          disabled={!notes.editable() || !changed()}
+          onClick={handleRestore}
+        >
+          Restore this version
+        </button>
+      </div>
+    </Modal>
+  );
+}
@@ -0,0 +1,69 @@
+import { createEffect, createSignal, Show } from "solid-js";
+import { Modal } from "../primitives/Modal";
+
+// Asks for the vault passphrase. The first time ("set") it is typed twice,
+// since encrypted notes can't be recovered without it.
+export function PassphrasePrompt(props: {
+  mode: "unlock" | "set" | null;
+  onSubmit: (passphrase: string) => void;
+  onClose: () => void;
+}) {
+  const [passphrase, setPassphrase] = createSignal("");
+  const [confirmation, setConfirmation] = createSignal("");
+  let inputRef: HTMLInputElement | undefined;
+
+  createEffect(() => {
+    if (props.mode) {
+      setPassphrase("");
+      setConfirmation("");
+      setTimeout(() => inputRef?.focus(), 0);
+    }
+  });
+
+  const mismatch = () =>
+    props.mode === "set" && confirmation() !== passphrase();
+
+  const handleSubmit = (e: Event) => {
+    e.preventDefault();
+    if (!passphrase() || mismatch()) return;
+    props.onSubmit(passphrase());
+  };
+
+  return (
+    <Modal
+      open={props.mode !== null}
+      onClose={props.onClose}
+      class="bg-button-bg w-[400px] rounded border p-4"
+    >
+      <form onSubmit={handleSubmit} class="flex flex-col gap-2">
+        <input
+          ref={inputRef}
+          type="password"
+          class="text-text w-full bg-transparent outline-none"
+          placeholder={
+            props.mode === "set" ? "Choose a passphrase" : "Passphrase"
+          }
+          aria-label="Passphrase"
+          value={passphrase()}
+          onInput={(e) => setPassphrase(e.currentTarget.value)}
+        />
+        <Show when={props.mode === "set"}>
+          <input
+            type="password"
+            class="text-text w-full bg-transparent outline-none"
+            placeholder="Repeat the passphrase"
+            aria-label="Repeat the passphrase"
+            aria-invalid={mismatch() && confirmation() !== ""}
+            value={confirmation()}
+            onInput={(e) => setConfirmation(e.currentTarget.value)}
+          />
+          <p class="text-xs opacity-60">
+            Encrypted notes can't be opened without it, so keep it safe
+          </p>
+        </Show>
+        {/* Enter submits from either field */}
+        <button type="submit" hidden />
+      </form>
+    </Modal>
+  );
+}
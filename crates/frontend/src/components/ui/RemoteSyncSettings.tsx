@@ -0,0 +1,176 @@
+import {
+  createResource,
+  createSignal,
+  For,
+  onCleanup,
+  onMount,
+  Show,
+} from "solid-js";
+import { listen } from "@tauri-apps/api/event";
+import { commands } from "../../api/commands";
+import type { SyncProgress, SyncReport } from "../../types";
+import { useToast } from "./Toast";
+
+const INPUT_CLASS =
+  "text-text w-56 rounded border bg-transparent px-2 py-1 outline-none";
+const BUTTON_CLASS =
+  "bg-button-bg hover:bg-button-hover rounded border px-2 py-1 text-sm disabled:opacity-50";
+
+function reportMessage(report: SyncReport): string {
+  const applied = report.applied.length;
+  const synced =
+    applied === 0
+      ? "Everything was up to date"
+      : `Synced ${applied} ${applied === 1 ? "file" : "files"}`;
+  return report.conflicts.length > 0
+    ? `${synced}; ${report.conflicts.length} changed on both sides`
+    : synced;
+}
+
+export function RemoteSyncSettings() {
+  const toast = useToast();
+  const [status, { refetch }] = createResource(commands.getRemoteStatus);
+  const [url, setUrl] = createSignal<string>();
+  const [username, setUsername] = createSignal<string>();
+  const [password, setPassword] = createSignal("");
+  const [progress, setProgress] = createSignal<SyncProgress | null>(null);
+
+  onMount(async () => {
+    const unlisten = await listen<SyncProgress>("notes:sync-progress", (e) =>
+      setProgress(e.payload),
+    );
+    onCleanup(unlisten);
+  });
+
+  const currentUrl = () => url() ?? status()?.url ?? "";
+  const currentUsername = () => username() ?? status()?.username ?? "";
+
+  const save = async () => {
+    try {
+      await commands.configureRemote(
+        currentUrl().trim() || null,
+        currentUsername(),
+        password() || null,
+      );
+      setPassword("");
+      toast.success(currentUrl().trim() ? "Remote saved" : "Sync turned off");
+    } catch (error) {
+      toast.error(String(error));
+    }
+    refetch();
+  };
+
+  const sync = async () => {
+    setProgress({ done: 0, total: 0 });
+    try {
+      toast.success(reportMessage(await commands.syncNow()));
+    } catch (error) {
+      toast.error(String(error));
+    }
+    setProgress(null);
+    refetch();
+  };
+
+  const keep = async (path: string, side: "local" | "remote") => {
+    try {
+      await commands.resolveSyncConflict(path, side);
+    } catch (error) {
+      toast.error(String(error));
+    }
+    refetch();
+  };
+
+  return (
+    <div class="space-y-2">
+      <label>Sync with WebDAV</label>
+      <p class="text-text-muted text-sm">
+        Nextcloud, ownCloud, or any WebDAV folder. Use an app password: it is
+        stored on this device unencrypted.
+      </p>
+      <div class="flex items-center justify-between">
+        <label for="remote-url">Folder URL</label>
+        <input
+          id="remote-url"
+          type="url"
+          value={currentUrl()}
+          onInput={(e) => setUrl(e.currentTarget.value)}
+          class={INPUT_CLASS}
+          placeholder="https://cloud.example.com/remote.php/dav/files/me/Notes"
+        />
+      </div>
+      <div class="flex items-center justify-between">
+        <label for="remote-username">Username</label>
+        <input
+          id="remote-username"
+          type="text"
+          autocomplete="username"
+          value={currentUsername()}
+          onInput={(e) => setUsername(e.currentTarget.value)}
+          class={INPUT_CLASS}
+        />
+      </div>
+      <div class="flex items-center justify-between">
+        <label for="remote-password">Password</label>
+        <input
+          id="remote-password"
+          type="password"
+          autocomplete="current-password"
+          value={password()}
+          onInput={(e) => setPassword(e.currentTarget.value)}
+          class={INPUT_CLASS}
+          placeholder={status()?.url ? "Unchanged" : ""}
+        />
+      </div>
+      <Show when={status()?.conflicts.length}>
+        <div class="space-y-1 text-sm">
+          <p class="text-text-muted">
+            Changed both here and on the server; keep one side and sync again
+          </p>
+          <For each={status()!.conflicts}>
+            {(path) => (
+              <div class="flex items-center justify-between gap-2">
+                <span class="truncate">{path}</span>
+                <span class="flex shrink-0 gap-2">
+                  <button
+                    type="button"
+                    onClick={() => keep(path, "local")}
+                    class="text-text-muted hover:text-text text-xs hover:underline"
+                  >
+                    Keep mine
+                  </button>
+                  <button
+                    type="button"
+                    onClick={() => keep(path, "remote")}
+                    class="text-text-muted hover:text-text text-xs hover:underline"
+                  >
+                    Keep server's
+                  </button>
+                </span>
+              </div>
+            )}
+          </For>
+        </div>
+      </Show>
+      <div class="flex flex-wrap items-center gap-2 pt-1">
+        <button type="button" onClick={save} class={BUTTON_CLASS}>
+          Save
+        </button>
+        <button
+          type="button"
+          onClick={sync}
+          disabled={!status()?.url || progress() !== null}
+          class={BUTTON_CLASS}
+        >
+          Sync now
+        </button>
+        <Show when={progress()}>
+          {(progress) => (
+            <span class="text-text-muted text-sm" aria-live="polite">
+              Syncing {progress().done} / {progress().total}
+            </span>
+          )}
+        </Show>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,167 @@
+import { createSignal, createEffect, For, Show } from "solid-js";
+import { commands } from "../../api/commands";
+import type { ReplaceEdit, SearchHistoryEntry } from "../../types";
+import { Modal } from "../primitives/Modal";
+import { useToast } from "./Toast";
+
+// Find and replace across all notes. Changes are previewed as a dry run and
+// only written once reviewed; Alt+Up/Down recalls earlier replaces.
+export function ReplaceModal(props: { open: boolean; onClose: () => void }) {
+  const toast = useToast();
+  const [find, setFind] = createSignal("");
+  const [replacement, setReplacement] = createSignal("");
+  const [preview, setPreview] = createSignal<ReplaceEdit | null>(null);
+  const [history, setHistory] = createSignal<SearchHistoryEntry[]>([]);
+  const [historyIndex, setHistoryIndex] = createSignal(-1);
+  let findRef: HTMLInputElement | undefined;
+
+  // The history entry shown in the inputs, while it's unedited
+  const recalled = () => history()[historyIndex()] ?? null;
+
+  const handleClose = () => {
+    setFind("");
+    setReplacement("");
+    setPreview(null);
+    props.onClose();
+  };
+
+  createEffect(() => {
+    if (!props.open) return;
+    setTimeout(() => findRef?.focus(), 0);
+    setHistoryIndex(-1);
+    commands
+      .getSearchHistory("replace")
+      .then(setHistory)
+      .catch((err) => console.error("Failed to load replace history:", err));
+  });
+
+  const edit = (setter: (value: string) => void, value: string) => {
+    setter(value);
+    setHistoryIndex(-1);
+    setPreview(null);
+  };
+
+  const recall = (step: number) => {
+    const next = historyIndex() + step;
+    if (next < -1 || next >= history().length) return;
+    const entry = history()[next];
+    setFind(entry?.query ?? "");
+    setReplacement(entry?.replacement ?? "");
+    setHistoryIndex(next);
+    setPreview(null);
+  };
+
+  const run = (dryRun: boolean) => {
+    const entry = recalled();
+    return entry
+      ? commands.rerunReplace(entry.id, dryRun)
+      : commands.replaceInNotes(find(), replacement(), dryRun);
+  };
+
+  const handlePreview = async () => {
+    if (!find()) return;
+    try {
+      setPreview(await run(true));
+    } catch (err) {
+      console.error("Failed to preview replace:", err);
+      toast.error(`Failed to preview: ${err}`);
+    }
+  };
+
+  const handleApply = async () => {
+    try {
+      const result = await run(false);
+      toast.success(`Replaced in ${result.changes.length} notes`, {
+        duration: "short",
+      });
+      handleClose();
+    } catch (err) {
+      console.error("Failed to replace:", err);
+      toast.error(`Failed to replace: ${err}`);
+    }
+  };
+
+  const handleKeyDown = (e: KeyboardEvent) => {
+    if (e.altKey && (e.key === "ArrowUp" || e.key === "ArrowDown")) {
+      e.preventDefault();
+      recall(e.key === "ArrowUp" ? 1 : -1);
+    } else if (e.key === "Enter") {
+      e.preventDefault();
+      handlePreview();
+    }
+  };
+
+  return (
+    <Modal
+      open={props.open}
+      onClose={handleClose}
+      class="bg-paper text-text w-[480px] rounded-md border outline-none"
+    >
+      <div class="flex flex-col gap-2 border-b px-4 py-3">
+        <input
+          ref={findRef}
+          type="text"
+          dir="auto"
+          class="w-full rounded border bg-transparent px-2 py-1 outline-none"
+          placeholder="Find"
+          aria-label="Find"
+          value={find()}
+          onInput={(e) => edit(setFind, e.currentTarget.value)}
+          onKeyDown={handleKeyDown}
+        />
+        <input
+          type="text"
+          dir="auto"
+          class="w-full rounded border bg-transparent px-2 py-1 outline-none"
+          placeholder="Replace with"
+          aria-label="Replace with"
+          value={replacement()}
+          onInput={(e) => edit(setReplacement, e.currentTarget.value)}
+          onKeyDown={handleKeyDown}
+        />
+      </div>
+      <Show
+        when={preview()}
+        fallback={
+          <div class="px-4 py-2 opacity-60">
+            Press Enter to preview the changes
+          </div>
+        }
+      >
+        {(result) => (
+          <div class="px-4 py-2">
+            <div
+              role="list"
+              aria-label="Notes that will change"
+              class="max-h-[300px] overflow-y-auto"
+            >
+              <For
+                each={result().changes}
+                fallback={<div class="opacity-60">No matches</div>}
+              >
+                {(change) => (
+                  <div role="listitem" class="py-0.5">
+                    {change.path}{" "}
+                    <span class="opacity-50">
+                      ({change.matches}{" "}
+                      {change.matches === 1 ? "match" : "matches"})
+                    </span>
+                  </div>
+                )}
+              </For>
+            </div>
+            <Show when={result().changes.length > 0}>
+              <button
+                type="button"
+                onClick={handleApply}
+                class="bg-button-bg hover:bg-button-hover mt-2 rounded border px-2 py-1"
+              >
+                Replace in {result().changes.length} notes
+              </button>
+            </Show>
+          </div>
+        )}
+      </Show>
+    </Modal>
+  );
+}
@@ -0,0 +1,234 @@
+import {
+  createEffect,
+  createMemo,
+  createSignal,
+  createUniqueId,
+  For,
+  onCleanup,
+  Show,
+} from "solid-js";
+import { useNotes } from "../../api";
+import { commands } from "../../api/commands";
+import type { SearchMatch, SearchResult } from "../../types";
+import { announce } from "../../utils/announce";
+import { requestReveal } from "../../utils/reveal";
+import { Modal } from "../primitives/Modal";
+
+// Wait for a pause in typing before searching again
+const SEARCH_DELAY = 150;
+
+// One selectable row: a matching line, or a note that only matched by path
+interface Entry {
+  path: string;
+  match: SearchMatch | null;
+}
+
+// Full-text search with every hit shown in context. Arrow keys move between
+// hits and Enter opens the note with the hit selected.
+export function SearchPanel(props: { open: boolean; onClose: () => void }) {
+  const notes = useNotes();
+  const [query, setQuery] = createSignal("");
+  const [results, setResults] = createSignal<SearchResult[]>([]);
+  const [selectedIndex, setSelectedIndex] = createSignal(0);
+  const [error, setError] = createSignal(false);
+  let inputRef: HTMLInputElement | undefined;
+  let listRef: HTMLDivElement | undefined;
+  let searchTimer: number | undefined;
+  // Only the newest search may update the list
+  let searchId = 0;
+  const listboxId = createUniqueId();
+  const optionId = (index: number) => `${listboxId}-${index}`;
+
+  const entries = createMemo(() =>
+    results().flatMap((result): Entry[] =>
+      result.matches.length > 0
+        ? result.matches.map((match) => ({ path: result.note.path, match }))
+        : [{ path: result.note.path, match: null }],
+    ),
+  );
+
+  const handleClose = () => {
+    clearTimeout(searchTimer);
+    setQuery("");
+    setResults([]);
+    setSelectedIndex(0);
+    props.onClose();
+  };
+
+  createEffect(() => {
+    if (props.open) setTimeout(() => inputRef?.focus(), 0);
+  });
+
+  // Search again as the query is refined; half-typed FTS syntax (an open
+  // quote, a trailing AND) fails, and keeps the previous results showing
+  createEffect(() => {
+    if (!props.open) return;
+    const q = query().trim();
+    clearTimeout(searchTimer);
+    if (!q) {
+      setResults([]);
+      setError(false);
+      return;
+    }
+    const id = ++searchId;
+    searchTimer = window.setTimeout(async () => {
+      try {
+        const found = await commands.searchWithMatches(q, notes.currentPath());
+        if (id !== searchId) return;
+        setResults(found);
+        setSelectedIndex(0);
+        setError(false);
+        const hits = found.reduce((sum, r) => sum + r.match_count, 0);
+        announce(
+          `${hits === 1 ? "1 match" : `${hits} matches`} in ` +
+            `${found.length === 1 ? "1 note" : `${found.length} notes`}`,
+        );
+      } catch (err) {
+        if (id !== searchId) return;
+        console.error("Search failed:", err);
+        setError(true);
+      }
+    }, SEARCH_DELAY);
+  });
+
+  onCleanup(() => clearTimeout(searchTimer));
+
+  createEffect(() => {
+    const option = listRef?.querySelector(`#${optionId(selectedIndex())}`);
+    option?.scrollIntoView({ block: "nearest" });
+  });
+
+  const open = (entry: Entry) => {
+    commands
+      .recordSearch(query().trim())
+      .catch((err) => console.error("Failed to record search:", err));
+    if (entry.match) {
+      const { text, start, end, occurrence } = entry.match;
+      requestReveal({
+        path: entry.path,
+        text: text.slice(start, end),
+        occurrence,
+      });
+    }
+    notes.setCurrentPath(entry.path);
+    handleClose();
+  };
+
+  const handleKeyDown = (e: KeyboardEvent) => {
+    const count = entries().length;
+    switch (e.key) {
+      case "ArrowDown":
+        e.preventDefault();
+        if (count > 0) setSelectedIndex((i) => (i + 1) % count);
+        break;
+      case "ArrowUp":
+        e.preventDefault();
+        if (count > 0) setSelectedIndex((i) => (i - 1 + count) % count);
+        break;
+      case "Enter": {
+        e.preventDefault();
+        const entry = entries()[selectedIndex()];
+        if (entry) open(entry);
+        break;
+      }
+    }
+  };
+
+  // Results of one note share a heading above their first line
+  const startsNote = (index: number) =>
+    index === 0 || entries()[index - 1].path !== entries()[index].path;
+  // Matching lines past the ones listed
+  const hiddenFor = (path: string) => {
+    const result = results().find((r) => r.note.path === path);
+    return result ? result.match_count - result.matches.length : 0;
+  };
+
+  return (
+    <Modal
+      open={props.open}
+      onClose={handleClose}
+      class="bg-paper text-text w-[560px] rounded-md border outline-none"
+    >
+      <div class="border-b px-2.5 py-1">
+        <input
+          ref={inputRef}
+          type="text"
+          dir="auto"
+          class="w-full bg-transparent px-2 py-1.5 outline-none"
+          placeholder="Search in notes..."
+          role="combobox"
+          aria-label="Search in notes"
+          aria-expanded={entries().length > 0}
+          aria-controls={listboxId}
+          aria-activedescendant={
+            entries().length > 0 ? optionId(selectedIndex()) : undefined
+          }
+          value={query()}
+          onInput={(e) => setQuery(e.currentTarget.value)}
+          onKeyDown={handleKeyDown}
+        />
+      </div>
+      <div
+        ref={listRef}
+        id={listboxId}
+        role="listbox"
+        aria-label="Search results"
+        class="max-h-[60vh] overflow-y-auto px-2.5 py-1"
+      >
+        <Show when={error()}>
+          <div class="px-2 py-1 text-xs opacity-60">
+            Incomplete query, showing earlier results
+          </div>
+        </Show>
+        <Show when={query().trim() && entries().length === 0 && !error()}>
+          <div class="px-2 py-1.5 opacity-60">No results found</div>
+        </Show>
+        <For each={entries()}>
+          {(entry, index) => (
+            <>
+              <Show when={startsNote(index())}>
+                <div class="text-text-muted flex gap-2 px-2 pt-2 text-xs">
+                  <span class="truncate">{entry.path || "(root)"}</span>
+                  <Show when={hiddenFor(entry.path) > 0}>
+                    <span class="shrink-0">
+                      +{hiddenFor(entry.path)} more
+                    </span>
+                  </Show>
+                </div>
+              </Show>
+              <div
+                id={optionId(index())}
+                role="option"
+                aria-selected={index() === selectedIndex()}
+                class="flex cursor-pointer gap-2 rounded px-2 py-1"
+                classList={{ "bg-button-bg": index() === selectedIndex() }}
+                onClick={() => open(entry)}
+                onMouseEnter={() => setSelectedIndex(index())}
+              >
+                <Show
+                  when={entry.match}
+                  fallback={<span class="opacity-60">Matched by name</span>}
+                >
+                  {(match) => (
+                    <>
+                      <span class="text-text-muted w-8 shrink-0 text-right text-xs leading-6">
+                        {match().line}
+                      </span>
+                      <span class="min-w-0 truncate" dir="auto">
+                        {match().text.slice(0, match().start)}
+                        <mark>
+                          {match().text.slice(match().start, match().end)}
+                        </mark>
+                        {match().text.slice(match().end)}
+                      </span>
+                    </>
+                  )}
+                </Show>
+              </div>
+            </>
+          )}
+        </For>
+      </div>
+    </Modal>
+  );
+}
@@ -8,25 +8,48 @@ import {
   DEFAULT_SETTINGS,
   type Settings as SettingsType,
 } from "../../utils/settings";
+import {
+  DEFAULT_FONT_SIZE,
+  updateAppearance,
+  UI_SCALE_MAX,
+  UI_SCALE_MIN,
+  UI_SCALE_STEP,
+} from "../../utils/appearance";
+import { editorConfig, updateEditorConfig } from "../../utils/editorConfig";
+import { timingConfig, updateTimingConfig } from "../../utils/timing";
+import { commands } from "../../api/commands";
+import { StorageSettings } from "./StorageSettings";
+import { DiagnosticsSettings } from "./DiagnosticsSettings";
+import { ExportSettings } from "./ExportSettings";
+import { GitSettings } from "./GitSettings";
+import { RemoteSyncSettings } from "./RemoteSyncSettings";
+import type { AppearanceConfig } from "../../types";
 // import { open as openDialog } from "@tauri-apps/plugin-dialog";
 
 export function Settings(props: { open: boolean; onClose: () => void }) {
   const [settings, setSettings] = createSignal<SettingsType | null>(null);
+  const [appearance, setAppearance] = createSignal<AppearanceConfig | null>(
+    null,
+  );
   const [loading, setLoading] = createSignal(true);
 
   onMount(async () => {
     const loaded = await loadSettings();
     setSettings(loaded);
+    setAppearance((await commands.getVaultConfig()).appearance);
     setLoading(false);
   });
 
-  const updateFontSize = async (value: number) => {
-    const current = settings();
-    if (!current) return;
-    const updated = { ...current, fontSize: value };
-    setSettings(updated);
-    document.documentElement.style.setProperty("--text-base", `${value}px`);
-    await saveSettings(updated);
+  // Appearance is stored in the vault config and applied immediately
+  const updateAppearanceField = async <K extends keyof AppearanceConfig>(
+    key: K,
+    value: AppearanceConfig[K],
+  ) => {
+    const updated = await updateAppearance((current) => ({
+      ...current,
+      [key]: value,
+    }));
+    setAppearance(updated);
   };
 
   // const updateNotesLocation = async (value: string) => {
@@ -67,25 +90,32 @@ export function Settings(props: { open: boolean; onClose: () => void }) {
   return (
     <Modal open={props.open} onClose={props.onClose}>
       <Card class="w-[400px] px-4 py-4 pb-8">
-        <Show when={!loading() && settings()}>
+        <Show when={!loading() && settings() && appearance()}>
           <div class="space-y-4">
             <h1 class="text-text pb-1">Settings</h1>
 
             <div class="flex items-center justify-between">
               <label>Font Size</label>
               <div class="flex items-center gap-2">
-                {settings()!.fontSize !== DEFAULT_SETTINGS.fontSize && (
+                {appearance()!.editor_font_size !== DEFAULT_FONT_SIZE && (
                   <button
                     type="button"
-                    onClick={() => updateFontSize(DEFAULT_SETTINGS.fontSize)}
+                    onClick={() =>
+                      updateAppearanceField(
+                        "editor_font_size",
+                        DEFAULT_FONT_SIZE,
+                      )
+                    }
                     class="text-text-muted hover:text-text text-xs hover:underline"
                   >
                     Reset
                   </button>
                 )}
                 <NumberInput
-                  value={settings()!.fontSize}
-                  onChange={updateFontSize}
+                  value={appearance()!.editor_font_size}
+                  onChange={(value) =>
+                    updateAppearanceField("editor_font_size", value)
+                  }
                   min={10}
                   max={32}
                   step={1}
@@ -93,6 +123,115 @@ export function Settings(props: { open: boolean; onClose: () => void }) {
               </div>
             </div>
 
+            <div class="flex items-center justify-between">
+              <label>Editor Font</label>
+              <input
+                type="text"
+                value={appearance()!.editor_font_family ?? ""}
+                onChange={(e) =>
+                  updateAppearanceField(
+                    "editor_font_family",
+                    e.currentTarget.value.trim() || null,
+                  )
+                }
+                class="text-text w-40 rounded border bg-transparent px-2 py-1 outline-none"
+                placeholder="Default"
+                aria-label="Editor font"
+              />
+            </div>
+
+            <div class="flex items-center justify-between">
+              <label>Interface Scale (%)</label>
+              <div class="flex items-center gap-2">
+                {appearance()!.ui_scale !== 1 && (
+                  <button
+                    type="button"
+                    onClick={() => updateAppearanceField("ui_scale", 1)}
+                    class="text-text-muted hover:text-text text-xs hover:underline"
+                  >
+                    Reset
+                  </button>
+                )}
+                <NumberInput
+                  value={Math.round(appearance()!.ui_scale * 100)}
+                  onChange={(value) =>
+                    updateAppearanceField("ui_scale", value / 100)
+                  }
+                  min={UI_SCALE_MIN * 100}
+                  max={UI_SCALE_MAX * 100}
+                  step={UI_SCALE_STEP * 100}
+                />
+              </div>
+            </div>
+
+            <div class="flex items-center justify-between">
+              <label>High contrast</label>
+              <Checkbox
+                checked={appearance()!.high_contrast}
+                onChange={(value) =>
+                  updateAppearanceField("high_contrast", value)
+                }
+              />
+            </div>
+
+            <div class="flex items-center justify-between">
+              <label>Smart quotes and dashes</label>
+              <Checkbox
+                checked={editorConfig().smart_typography}
+                onChange={(value) =>
+                  updateEditorConfig((current) => ({
+                    ...current,
+                    smart_typography: value,
+                  }))
+                }
+              />
+            </div>
+
+            <div class="flex items-center justify-between">
+              <label>Name new notes from their first line</label>
+              <Checkbox
+                checked={editorConfig().auto_title}
+                onChange={(value) =>
+                  updateEditorConfig((current) => ({
+                    ...current,
+                    auto_title: value,
+                  }))
+                }
+              />
+            </div>
+
+            <div class="flex items-center justify-between">
+              <label>Autosave delay (ms)</label>
+              <NumberInput
+                value={timingConfig().autosave_delay_ms}
+                onChange={(value) =>
+                  updateTimingConfig((current) => ({
+                    ...current,
+                    autosave_delay_ms: value,
+                  }))
+                }
+                min={100}
+                max={10000}
+                step={100}
+              />
+            </div>
+
+            <div class="flex items-center justify-between">
+              <label>File change delay (ms)</label>
+              <NumberInput
+                value={timingConfig().watcher_debounce_ms}
+                onChange={(value) =>
+                  updateTimingConfig((current) => ({
+                    ...current,
+                    watcher_debounce_ms: value,
+                  }))
+                }
+                min={100}
+                max={30000}
+                step={100}
+              />
+            </div>
+
             <hr />
 
             {/*<div class="flex items-center justify-between">
@@ -169,6 +308,26 @@ export function Settings(props: { open: boolean; onClose: () => void }) {
                 />
               </div>
             </div>
+
+            <hr />
+
+            <ExportSettings />
+
+            <hr />
+
+            <GitSettings />
+
+            <hr />
+
+            <RemoteSyncSettings />
+
+            <hr />
+
+            <StorageSettings />
+
+            <hr />
+
+            <DiagnosticsSettings />
           </div>
         </Show>
 
@@ -0,0 +1,110 @@
+import { createResource, For, Show } from "solid-js";
+import { ask } from "@tauri-apps/plugin-dialog";
+import { commands } from "../../api/commands";
+import type { StorageReport } from "../../types";
+import { useToast } from "./Toast";
+
+const UNITS = ["B", "KB", "MB", "GB"];
+
+function formatBytes(bytes: number): string {
+  let value = bytes;
+  let unit = 0;
+  while (value >= 1024 && unit < UNITS.length - 1) {
+    value /= 1024;
+    unit++;
+  }
+  return `${unit === 0 ? value : value.toFixed(1)} ${UNITS[unit]}`;
+}
+
+const ROWS: { key: keyof StorageReport; label: string }[] = [
+  { key: "notes", label: "Notes" },
+  { key: "archive", label: "Archive" },
+  { key: "attachments", label: "Attachments" },
+  { key: "history", label: "Checkpoints" },
+  { key: "trash", label: "Trash" },
+  { key: "database", label: "Index database" },
+];
+
+export function StorageSettings() {
+  const toast = useToast();
+  const [report, { refetch }] = createResource(commands.getStorageReport);
+
+  const run = async (confirm: string, action: () => Promise<string>) => {
+    if (!(await ask(confirm))) return;
+    try {
+      toast.success(await action());
+    } catch (error) {
+      toast.error(String(error));
+    }
+    refetch();
+  };
+
+  const emptyArchive = () =>
+    run("Move all archived notes to the trash?", async () => {
+      const revision = await commands.emptyArchive();
+      return revision === null ? "Archive is already empty" : "Archive emptied";
+    });
+
+  const deleteCheckpoints = () =>
+    run("Delete all checkpoints? They cannot be restored.", async () => {
+      const count = await commands.deleteAllCheckpoints();
+      return `Deleted ${count} checkpoint${count === 1 ? "" : "s"}`;
+    });
+
+  const compactDatabase = () =>
+    run("Compact the index database now?", async () => {
+      const result = await commands.runMaintenance();
+      return result === null
+        ? "Maintenance is already running"
+        : `Reclaimed ${formatBytes(result.reclaimed)}`;
+    });
+
+  return (
+    <div class="space-y-2">
+      <label>Storage</label>
+      <Show when={report()}>
+        {(report) => (
+          <div class="text-text-muted space-y-1 text-sm">
+            <For each={ROWS}>
+              {(row) => (
+                <div class="flex justify-between">
+                  <span>{row.label}</span>
+                  <span>{formatBytes(report()[row.key])}</span>
+                </div>
+              )}
+            </For>
+            <div class="text-text flex justify-between">
+              <span>Total</span>
+              <span>{formatBytes(report().total)}</span>
+            </div>
+          </div>
+        )}
+      </Show>
+      <div class="flex flex-wrap gap-2 pt-1">
+        <button
+          type="button"
+          onClick={emptyArchive}
+          disabled={!report()?.archive}
+          class="bg-button-bg hover:bg-button-hover rounded border px-2 py-1 text-sm disabled:opacity-50"
+        >
+          Empty archive
+        </button>
+        <button
+          type="button"
+          onClick={deleteCheckpoints}
+          disabled={!report()?.history}
+          class="bg-button-bg hover:bg-button-hover rounded border px-2 py-1 text-sm disabled:opacity-50"
+        >
+          Delete checkpoints
+        </button>
+        <button
+          type="button"
+          onClick={compactDatabase}
+          class="bg-button-bg hover:bg-button-hover rounded border px-2 py-1 text-sm"
+        >
+          Compact database
+        </button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,57 @@
+import { createEffect, createSignal, For } from "solid-js";
+import type { TemplateField } from "../../types";
+import { Modal } from "../primitives/Modal";
+
+// Asks for a template's variables, one input per number; an empty input
+// leaves the variable's label in the note
+export function TemplatePrompt(props: {
+  fields: TemplateField[] | null;
+  onSubmit: (values: Record<number, string>) => void;
+  onClose: () => void;
+}) {
+  const [values, setValues] = createSignal<Record<number, string>>({});
+  let formRef: HTMLFormElement | undefined;
+
+  createEffect(() => {
+    if (props.fields) {
+      setValues({});
+      setTimeout(() => formRef?.querySelector("input")?.focus(), 0);
+    }
+  });
+
+  const handleSubmit = (e: Event) => {
+    e.preventDefault();
+    props.onSubmit(values());
+  };
+
+  return (
+    <Modal
+      open={props.fields !== null}
+      onClose={props.onClose}
+      class="bg-button-bg w-[400px] rounded border p-4"
+    >
+      <form ref={formRef} onSubmit={handleSubmit} class="flex flex-col gap-2">
+        <For each={props.fields}>
+          {(field) => (
+            <input
+              type="text"
+              dir="auto"
+              class="text-text w-full bg-transparent outline-none"
+              placeholder={field.label || `Value ${field.index}`}
+              aria-label={field.label || `Value ${field.index}`}
+              value={values()[field.index] ?? ""}
+              onInput={(e) =>
+                setValues((prev) => ({
+                  ...prev,
+                  [field.index]: e.currentTarget.value,
+                }))
+              }
+            />
+          )}
+        </For>
+        {/* Enter submits from any field */}
+        <button type="submit" hidden />
+      </form>
+    </Modal>
+  );
+}
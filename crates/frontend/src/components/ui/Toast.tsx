@@ -114,7 +114,11 @@ export const ToastProvider: ParentComponent = (props) => {
   return (
     <ToastContext.Provider value={contextValue}>
       {props.children}
-      <div class="pointer-events-none fixed right-3 bottom-3 z-[200] flex flex-col gap-1.5">
+      <div
+        class="pointer-events-none fixed right-3 bottom-3 z-[200] flex flex-col gap-1.5"
+        role="status"
+        aria-live="polite"
+      >
         <For each={toasts()}>
           {(toast) => (
             <div class="bg-paper pointer-events-auto rounded border px-3 py-2 shadow-md">
@@ -0,0 +1,96 @@
+import { createResource, For, Show } from "solid-js";
+import { commands } from "../../api/commands";
+import { useNotes } from "../../api";
+import type { UnlinkedMention } from "../../types";
+import { getPathTitle } from "../../utils/paths";
+import { Modal } from "../primitives/Modal";
+import { useToast } from "./Toast";
+
+// Places where other notes name this note (by title or alias) without linking
+// to it, each of which can be turned into a [[wiki-link]]
+export function UnlinkedMentions(props: {
+  path: string | null;
+  onClose: () => void;
+}) {
+  const notes = useNotes();
+  const toast = useToast();
+  const [mentions, { refetch }] = createResource(
+    () => props.path,
+    (path) => commands.getUnlinkedMentions(path),
+  );
+
+  // Linking shifts the text after it, so the list is reloaded every time
+  const handleLink = async (mention: UnlinkedMention) => {
+    const target = props.path;
+    if (!target) return;
+    try {
+      await commands.linkMention(mention, target);
+    } catch (err) {
+      console.error("Failed to link mention:", err);
+      toast.error(`Failed to link: ${err}`);
+    }
+    refetch();
+  };
+
+  const handleOpen = (source: string) => {
+    notes.setCurrentPath(source);
+    props.onClose();
+  };
+
+  return (
+    <Modal
+      open={props.path !== null}
+      onClose={props.onClose}
+      class="bg-paper text-text w-[520px] rounded-md border outline-none"
+    >
+      <div class="border-b px-4 py-3">
+        Unlinked mentions of {props.path ? getPathTitle(props.path) : ""}
+      </div>
+      <div
+        role="list"
+        aria-label="Unlinked mentions"
+        class="max-h-[60vh] overflow-y-auto px-4 py-2"
+      >
+        <Show
+          when={!mentions.error}
+          fallback={<div class="opacity-60">Could not load mentions</div>}
+        >
+          <For
+            each={mentions()}
+            fallback={
+              <div class="opacity-60">
+                {mentions.loading ? "Loading..." : "No unlinked mentions"}
+              </div>
+            }
+          >
+            {(mention) => (
+              <div role="listitem" class="flex items-start gap-2 py-1">
+                <div class="min-w-0 flex-1">
+                  <button
+                    type="button"
+                    class="text-text-muted text-xs hover:underline"
+                    onClick={() => handleOpen(mention.source)}
+                  >
+                    {mention.source}
+                  </button>
+                  <div class="truncate" dir="auto" title={mention.context}>
+                    {mention.context}
+                  </div>
+                </div>
+                <button
+                  type="button"
+                  disabled={!notes.editable()}
+                  onClick={() => handleLink(mention)}
+                  class="bg-button-bg hover:bg-button-hover rounded border px-2 py-0.5 text-xs disabled:opacity-50"
+                  aria-label={`Link "${mention.text}" in ${mention.source}`}
+                >
+                  Link
+                </button>
+              </div>
+            )}
+          </For>
+        </Show>
+      </div>
+    </Modal>
+  );
+}
@@ -3,6 +3,53 @@ export interface Note {
   path: string;
   content: string;
   modified: number;
+  // Sub-second part of `modified`, for saveNoteIfUnchanged
+  modified_nanos: number;
+  revision: number;
+}
+
+// A note plus the hash to pass back when saving it
+export interface VersionedNote {
+  note: Note;
+  content_hash: string;
+}
+
+// Result of a versioned save; a conflict means the note changed on disk
+export type SaveOutcome =
+  | { status: "saved"; revision: number; content_hash: string }
+  | {
+      status: "conflict";
+      revision: number;
+      content: string;
+      content_hash: string;
+    };
+
+// Result of saveNoteIfUnchanged; `modified` is the base for the next save
+export type SaveIfUnchanged =
+  | {
+      status: "saved";
+      revision: number;
+      modified: number;
+      modified_nanos: number;
+    }
+  | { status: "conflict"; content: string };
+
+// A run of lines in a three-way merge of the editor's edits ("mine") with a
+// conflicting version on disk ("theirs"); "conflict" hunks need a choice
+export type HunkKind = "unchanged" | "mine" | "theirs" | "both" | "conflict";
+
+export type HunkChoice = "mine" | "theirs" | "both";
+
+export interface MergeHunk {
+  kind: HunkKind;
+  base: string[];
+  mine: string[];
+  theirs: string[];
+}
+
+export interface Merge {
+  hunks: MergeHunk[];
+  trailing_newline: boolean;
 }
 
 export interface NoteMetadata {
@@ -10,12 +57,638 @@ export interface NoteMetadata {
   path: string;
   modified: number;
   archived: boolean;
+  /** Only present on results of getChildrenWithStats and detail queries */
+  stats?: NoteStats;
+}
+
+export interface NoteStats {
+  preview: string;
+  word_count: number;
+  // Frontmatter `cover`, relative to the note's folder
+  cover: string | null;
+  // Frontmatter `private: true`; hidden from search and exports
+  private: boolean;
+  // Frontmatter `title`, or the last path segment
+  title: string;
+  // Frontmatter `icon`, usually an emoji
+  icon: string | null;
+  // Stored encrypted; the other stats are empty
+  encrypted: boolean;
+  tags: string[];
+  // Non-archived children
+  child_count: number;
+  // Frontmatter `status`, if it is one of the workflow statuses
+  status: NoteStatus | null;
+  // Kept at the top of its parent's children
+  pinned: boolean;
+}
+
+export type NoteStatus = "todo" | "doing" | "done";
+
+// Whether encrypted notes can be read; the first unlock sets the passphrase
+export interface VaultLock {
+  has_passphrase: boolean;
+  unlocked: boolean;
+}
+
+// An edit made while the notes folder was unavailable, applied once it's back
+export type PendingOp =
+  | { op: "create"; path: string }
+  | { op: "save"; path: string; content: string }
+  | { op: "rename"; from: string; to: string };
+
+export interface GitStatus {
+  // null until git is turned on for the vault
+  branch: string | null;
+  // Notes changed since the last commit
+  changed: string[];
+  ahead: number;
+  // As of the last pull
+  behind: number;
+  // Notes a pull left with conflict markers
+  conflicts: string[];
+}
+
+export interface GitCommit {
+  id: string;
+  summary: string;
+  author: string;
+  // Seconds since the epoch
+  time: number;
+}
+
+export type PullOutcome =
+  | { status: "up_to_date" }
+  | { status: "fast_forward" }
+  | { status: "merged" }
+  | { status: "conflicts"; notes: string[] };
+
+// The WebDAV remote the vault syncs with; the password is never sent back
+export interface RemoteStatus {
+  // null while sync is off
+  url: string | null;
+  username: string | null;
+  // Files changed differently on both sides, waiting for a side to be kept
+  conflicts: string[];
+}
+
+export type SyncAction =
+  | "uploaded"
+  | "downloaded"
+  | "deleted_remote"
+  | "deleted_local";
+
+export interface SyncReport {
+  // Vault files, e.g. "reading/_index.md"
+  applied: { path: string; action: SyncAction }[];
+  conflicts: string[];
+}
+
+// Payload of the notes:sync-progress event
+export interface SyncProgress {
+  done: number;
+  total: number;
+}
+
+export interface Canvas {
+  path: string;
+  data: unknown;
+  modified: number;
+}
+
+export interface BoardColumn {
+  name: string;
+  cards: NoteMetadata[];
+}
+
+export interface Board {
+  path: string;
+  columns: BoardColumn[];
+  uncategorized: NoteMetadata[];
+}
+
+export type PropertyValue = string | string[];
+
+export interface ViewRow {
+  note: NoteMetadata;
+  // Frontmatter as [key, value] pairs in file order
+  properties: [string, PropertyValue][];
+}
+
+// A parent note's `view:` over its children, already filtered and sorted
+export interface ViewData {
+  path: string;
+  kind: "list" | "table" | "board";
+  // Table columns; empty for other kinds
+  properties: string[];
+  board: Board | null;
+  rows: ViewRow[];
 }
 
+export type NoteSelection =
+  | { kind: "paths"; paths: string[] }
+  | { kind: "children_of"; path: string }
+  | { kind: "search"; query: string };
+
+// Payload of notes:changed and notes:renamed; null when many notes changed.
+// notes:conflicted always carries a "conflicted" change.
+export type NoteChange =
+  | { kind: "created"; path: string }
+  | { kind: "modified"; path: string }
+  | { kind: "deleted"; path: string }
+  | { kind: "renamed"; from: string; to: string }
+  | { kind: "conflicted"; path: string };
+
+// What moveNote does when the new parent has a note of the same name:
+// fail, or pick a free name like "Idea 2"
+export type MoveCollision = "error" | "suffix";
+
+// Payload of notes:moved; `to` may carry a suffix the move added
+export interface NoteMoved {
+  from: string;
+  to: string;
+  revision: number;
+}
+
+// One step of a `batch`, applied in order
+export type NoteOp =
+  | { kind: "create"; path: string }
+  | { kind: "save"; path: string; content: string }
+  | { kind: "rename"; from: string; to: string }
+  | { kind: "delete"; path: string }
+  | { kind: "archive"; path: string };
+
+// null means the property is unset
+export interface PropertyChange {
+  path: string;
+  before: PropertyValue | null;
+  after: PropertyValue | null;
+}
+
+export interface BulkPropertyEdit {
+  changes: PropertyChange[];
+  // null for a dry run or when nothing changed
+  revision: number | null;
+}
+
+// A tag and the tags nested below it (`project` > `project/rust`)
+export interface TagNode {
+  name: string;
+  tag: string;
+  // Notes tagged with this tag or any tag below it
+  count: number;
+  direct_count: number;
+  children: TagNode[];
+}
+
+// revision is null for a dry run or when no note used the tag
+export interface TagRename {
+  notes: string[];
+  revision: number | null;
+}
+
+// A generated map of content; revision is null when it was already current
+export interface MocUpdate {
+  path: string;
+  links: number;
+  revision: number | null;
+}
+
+export interface ReplaceEdit {
+  changes: { path: string; matches: number }[];
+  // null for a dry run or when nothing matched
+  revision: number | null;
+}
+
+export type HistoryKind = "search" | "replace";
+
+export interface SearchHistoryEntry {
+  id: number;
+  kind: HistoryKind;
+  query: string;
+  // Only set for replaces
+  replacement: string | null;
+  used_at: number;
+}
+
+export interface SearchMatch {
+  // 1-based line in the note's Markdown
+  line: number;
+  text: string;
+  // Range of the matched term in text
+  start: number;
+  end: number;
+  // Earlier occurrences of the matched text in the note
+  occurrence: number;
+}
+
+export interface SearchResult {
+  note: NoteMetadata;
+  // The first few matching lines; empty when only the path matched
+  matches: SearchMatch[];
+  match_count: number;
+}
+
+export interface LinkSuggestion {
+  path: string;
+  // The title property, or the last path segment
+  title: string;
+  // Higher is better; only comparable between suggestions of one call
+  score: number;
+}
+
+export interface Reminder {
+  id: number;
+  path: string;
+  remind_at: number;
+  message: string;
+  fired: boolean;
+}
+
+export interface ArchiveSummary {
+  notes: number;
+  files: number;
+}
+
+export type AccessLevel = "read" | "append" | "write";
+
+export interface AccessToken {
+  id: number;
+  name: string;
+  secret: string;
+  access: AccessLevel;
+  subtree: string | null;
+  created: number;
+}
+
+export interface Change {
+  revision: number;
+  kind: "added" | "removed" | "moved" | "modified";
+  path: string;
+  old_path: string | null;
+  // For "modified": what the edit did, unless the note was encrypted
+  edit: EditSummary | null;
+}
+
+export interface EditSummary {
+  words_added: number;
+  words_removed: number;
+  // Headings added, removed, or renamed by the edit
+  headings: string[];
+}
+
+export interface Changes {
+  revision: number;
+  reset: boolean;
+  changes: Change[];
+}
+
+export type TreeChange =
+  | { kind: "added"; path: string; note: NoteMetadata | null }
+  | { kind: "removed"; path: string }
+  | { kind: "moved"; from: string; to: string; note: NoteMetadata | null }
+  | { kind: "retitled"; from: string; to: string; note: NoteMetadata | null };
+
+export interface TreeDiff {
+  from_revision: number;
+  revision: number;
+  reset: boolean;
+  changes: TreeChange[];
+}
+
+export interface QueueDepth {
+  interactive: number;
+  background: number;
+}
+
+export interface Capabilities {
+  editable: boolean;
+  available: boolean;
+  // Started with --safe-mode: read-only, no watcher or background jobs
+  safe_mode: boolean;
+}
+
+export interface Checkpoint {
+  id: string;
+  label: string;
+  created_at: number;
+  files: number;
+}
+
+// One line of a diff between two versions of a note; line numbers are
+// 1-based and null on the side the line isn't in
+export interface DiffLine {
+  kind: "same" | "added" | "removed";
+  text: string;
+  old_line: number | null;
+  new_line: number | null;
+}
+
+// A day with a daily note; date is YYYY-MM-DD
+export interface DailyNote {
+  date: string;
+  path: string;
+}
+
+// A deleted note waiting in the vault's trash
+export interface TrashedNote {
+  id: string;
+  original_path: string;
+  deleted_at: number;
+}
+
+export type CsvImportMode = "child_notes" | "table";
+
+export interface CsvImport {
+  rows: number;
+  // Notes created in "child_notes" mode
+  created: string[];
+  delimiter: string;
+  encoding: string;
+}
+
+export interface VaultImport {
+  // Notes created, or that a dry run would create
+  created: string[];
+  // Notes and files skipped because they already exist
+  conflicts: string[];
+  attachments: string[];
+  links_rewritten: number;
+}
+
+export type FormatAction =
+  | { kind: "bold" }
+  | { kind: "italic" }
+  | { kind: "heading"; level: number }
+  | { kind: "bullet_list" };
+
+// Offsets are UTF-16 code units, like a textarea's selection
+export interface TextEdit {
+  text: string;
+  selection_start: number;
+  selection_end: number;
+}
+
+// Both forms of a link to a note; see copyNoteLink
+export interface NoteLink {
+  path: string;
+  url: string;
+  wiki_link: string;
+}
+
+// Shown when hovering a [[wiki-link]]
+export interface NotePreview {
+  path: string;
+  title: string;
+  modified: number; // Unix timestamp
+  excerpt: string;
+}
+
+// Plain-text mention of a note's title or alias in another note. `start` and
+// `end` locate it in the source note and are passed back to linkMention.
+export interface UnlinkedMention {
+  source: string;
+  start: number;
+  end: number;
+  text: string;
+  context: string;
+}
+
+export interface ExportFormat {
+  id: string;
+  name: string;
+  extension: string;
+}
+
+export interface MaintenanceReport {
+  size_before: number;
+  size_after: number;
+  reclaimed: number;
+  duration_ms: number;
+}
+
+// Sizes in bytes
+export interface StorageReport {
+  notes: number;
+  archive: number;
+  attachments: number;
+  history: number;
+  trash: number;
+  database: number;
+  total: number;
+}
+
+// What was repaired when the vault was opened after an unclean shutdown
+export interface StartupReport {
+  stale_files: string[];
+  wal_checkpointed: boolean;
+  index_rebuilt: boolean;
+  salvaged: number | null;
+  journal_replayed: string[];
+  open_duration_ms: number;
+}
+
+export type FieldKind = "any" | "text" | "list" | "date" | "number";
+
+export interface SchemaField {
+  key: string;
+  required: boolean;
+  kind: FieldKind;
+  // Permitted values; anything goes when empty
+  allowed: string[];
+}
+
+// Frontmatter rules for children of `parent` and/or notes with `type: <type>`
+export interface NoteSchema {
+  name: string;
+  parent: string | null;
+  type: string | null;
+  fields: SchemaField[];
+}
+
+export interface SchemaWarning {
+  schema: string;
+  field: string;
+  issue: "missing" | "wrong_kind" | "not_allowed";
+  // Expected kind for "wrong_kind", offending value for "not_allowed"
+  detail: string | null;
+}
+
+// A value a template asks for: `${1:project name}` has index 1 and that label
+export interface TemplateField {
+  index: number;
+  label: string;
+}
+
+export interface TemplateSchema {
+  fields: TemplateField[];
+  // Whether the template marks a caret position with `${cursor}`
+  has_cursor: boolean;
+}
+
+export interface TemplateNote {
+  revision: number;
+  // Caret offset into the new note's content
+  cursor: number | null;
+}
+
+// Command round trips measured since launch
+export interface CommandPerf {
+  command: string;
+  count: number;
+  errors: number;
+  p50_ms: number;
+  p95_ms: number;
+  // Over the sampled calls that measured payload sizes
+  mean_request_bytes: number;
+  mean_response_bytes: number;
+}
+
+export interface HealthReport {
+  notes_checked: number;
+  schema_warnings: { path: string; warnings: SchemaWarning[] }[];
+}
+
+export type LinkProblem =
+  | { kind: "status"; code: number }
+  | { kind: "unreachable"; message: string };
+
+export interface DeadLink {
+  url: string;
+  // Notes linking to the URL
+  notes: string[];
+  problem: LinkProblem;
+}
+
+export interface LinkReport {
+  // Distinct URLs checked
+  checked: number;
+  dead: DeadLink[];
+}
+
+export interface Snippet {
+  trigger: string;
+  expansion: string;
+}
+
+export interface AppearanceConfig {
+  editor_font_size: number;
+  // CSS font stack; the interface font is used when null
+  editor_font_family: string | null;
+  ui_scale: number;
+  high_contrast: boolean;
+}
+
+export interface EditorConfig {
+  // Curly quotes, dashes, and ellipses as you type (outside code)
+  smart_typography: boolean;
+  // New "untitled" notes take their name from their first H1 or line
+  auto_title: boolean;
+}
+
+// How links between notes are written in exported and copied notes
+export type LinkStyle = "wiki" | "relative" | "published";
+
+export interface ExportConfig {
+  links: LinkStyle;
+  // Used by "published" links
+  base_url: string;
+}
+
+export interface GitConfig {
+  // Turned on by gitInit
+  enabled: boolean;
+  // Commit in the background once saving has paused for commit_delay_ms
+  auto_commit: boolean;
+  commit_delay_ms: number;
+  remote: string;
+}
+
+export interface TimingConfig {
+  // Quiet time before changed files are re-indexed
+  watcher_debounce_ms: number;
+  // Bursts touching more notes than this trigger one full rescan
+  rescan_threshold: number;
+  autosave_delay_ms: number;
+}
+
+// Replaces the typed character and the `delete` characters before it
+export interface TypographyReplacement {
+  delete: number;
+  insert: string;
+}
+
+export interface VaultConfig {
+  mentions: {
+    people_root: string;
+    auto_create: boolean;
+  };
+  snippets: Snippet[];
+  paste: {
+    fetch_url_titles: boolean;
+    url_title_timeout_ms: number;
+  };
+  appearance: AppearanceConfig;
+  editor: EditorConfig;
+  // Weights for ranking search results around the open note
+  search: {
+    ranking_weight: number;
+    proximity_weight: number;
+    recency_weight: number;
+  };
+  // Hot folder: .md/.txt files dropped into `dir` become notes under `parent`
+  ingest: {
+    dir: string | null;
+    parent: string;
+  };
+  checkpoints: {
+    keep: number;
+    max_bytes: number;
+  };
+  // Crash reports are written to .crash-reports in the vault; needs a restart
+  diagnostics: {
+    crash_reports: boolean;
+  };
+  timing: TimingConfig;
+  // Memory limits: note contents kept in memory, and the index's page cache
+  cache: {
+    content_entries: number;
+    content_mb: number;
+    index_mb: number;
+  };
+  // Open on the existing index and sync it with the files afterwards
+  startup: {
+    background_sync: boolean;
+  };
+  // Daily note path pattern ({{date}}, {{year}}, {{month}}, {{day}}) and
+  // the note new daily notes are copied from
+  daily: {
+    path: string;
+    template: string | null;
+  };
+  export: ExportConfig;
+  git: GitConfig;
+  schemas: NoteSchema[];
+  // UI language override (e.g. "nl"); the OS locale is used when null
+  locale: string | null;
+  // File each note is stored in; change it with setNoteFile
+  note_file: string;
+  // Whether childless notes are folders or plain files; change it with
+  // setVaultLayout
+  layout: VaultLayout;
+}
+
+// "flat" keeps notes without children as <name>.md files, like Obsidian
+export type VaultLayout = "folders" | "flat";
+
 export type NotesError =
   | { type: "Io"; message: string }
   | { type: "Database"; message: string }
   | { type: "DatabaseCorrupted" }
   | { type: "NotFound"; path: string }
   | { type: "AlreadyExists"; path: string }
-  | { type: "ParentNotFound"; path: string };
+  | { type: "ParentNotFound"; path: string }
+  | { type: "InvalidInput"; message: string }
+  | { type: "ReadOnly" }
+  | { type: "PermissionDenied"; message: string }
+  | { type: "VaultUnavailable" };
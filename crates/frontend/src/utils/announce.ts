@@ -0,0 +1,13 @@
+import { createSignal } from "solid-js";
+
+// Screen-reader announcements for results that don't move focus
+// (saves, search results). Rendered by <LiveRegion />.
+const [announcement, setAnnouncement] = createSignal("");
+
+export { announcement };
+
+export function announce(message: string) {
+  // Clear first so repeating the same message is read out again
+  setAnnouncement("");
+  setTimeout(() => setAnnouncement(message), 50);
+}
@@ -5,6 +5,16 @@ import { Store } from "@tauri-apps/plugin-store";
 export interface AppState {
   lastOpenedNote?: string;
   lastAppVersion?: string;
+  // Folded headings and code blocks, keyed by note path
+  noteFolds?: Record<string, string[]>;
+  // Where the reader left each note, keyed by note path
+  noteViews?: Record<string, NoteViewState>;
+}
+
+export interface NoteViewState {
+  // ProseMirror position of the cursor
+  cursor: number;
+  scrollTop: number;
 }
 
 let store: Store | null = null;
@@ -37,3 +47,36 @@ export async function clearAppState(): Promise<void> {
   await store.clear();
   await store.save();
 }
+
+export async function getNoteFolds(path: string): Promise<string[]> {
+  const folds = await getAppState("noteFolds");
+  return folds?.[path] ?? [];
+}
+
+export async function setNoteFolds(
+  path: string,
+  folded: string[],
+): Promise<void> {
+  const folds = { ...(await getAppState("noteFolds")) };
+  if (folded.length > 0) {
+    folds[path] = folded;
+  } else {
+    delete folds[path];
+  }
+  await setAppState("noteFolds", folds);
+}
+
+export async function getNoteViewState(
+  path: string,
+): Promise<NoteViewState | null> {
+  const views = await getAppState("noteViews");
+  return views?.[path] ?? null;
+}
+
+export async function setNoteViewState(
+  path: string,
+  view: NoteViewState,
+): Promise<void> {
+  const views = { ...(await getAppState("noteViews")), [path]: view };
+  await setAppState("noteViews", views);
+}
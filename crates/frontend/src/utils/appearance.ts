@@ -0,0 +1,40 @@
+import { commands } from "../api/commands";
+import type { AppearanceConfig } from "../types";
+
+export const DEFAULT_FONT_SIZE = 14;
+export const UI_SCALE_MIN = 0.5;
+export const UI_SCALE_MAX = 2;
+export const UI_SCALE_STEP = 0.1;
+
+// Applies the vault's appearance settings to the whole document; the UI scale
+// is applied by the backend as the window's zoom
+export function applyAppearance(appearance: AppearanceConfig) {
+  const root = document.documentElement;
+  root.style.setProperty("--text-base", `${appearance.editor_font_size}px`);
+  if (appearance.editor_font_family) {
+    root.style.setProperty("--font-editor", appearance.editor_font_family);
+  } else {
+    root.style.removeProperty("--font-editor");
+  }
+  root.dataset.contrast = appearance.high_contrast ? "high" : "normal";
+}
+
+// Reads, updates, applies, and persists the appearance section of the config
+export async function updateAppearance(
+  update: (current: AppearanceConfig) => AppearanceConfig,
+): Promise<AppearanceConfig> {
+  const config = await commands.getVaultConfig();
+  const appearance = update(config.appearance);
+  applyAppearance(appearance);
+  await commands.setVaultConfig({ ...config, appearance });
+  return appearance;
+}
+
+// Cmd/Ctrl + "+" / "-" / "0" zoom the window; resolves to the new scale
+export function stepUiScale(direction: 1 | -1 | 0) {
+  return direction === 1
+    ? commands.zoomIn()
+    : direction === -1
+      ? commands.zoomOut()
+      : commands.zoomReset();
+}
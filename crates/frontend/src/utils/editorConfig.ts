@@ -0,0 +1,32 @@
+import { createSignal } from "solid-js";
+import { commands } from "../api/commands";
+import type { EditorConfig } from "../types";
+
+const [editorConfig, setEditorConfig] = createSignal<EditorConfig>({
+  smart_typography: false,
+  auto_title: false,
+});
+
+// The vault's editing behaviour, read by both editors as they handle input
+export { editorConfig };
+
+export function applyEditorConfig(config: EditorConfig) {
+  setEditorConfig(config);
+}
+
+// Reads, updates, applies, and persists the editor section of the config
+export async function updateEditorConfig(
+  update: (current: EditorConfig) => EditorConfig,
+): Promise<EditorConfig> {
+  const config = await commands.getVaultConfig();
+  const editor = update(config.editor);
+  applyEditorConfig(editor);
+  await commands.setVaultConfig({ ...config, editor });
+  return editor;
+}
+
+const TYPOGRAPHY_TRIGGERS = new Set(['"', "'", "-", "."]);
+
+// Whether typing `key` may need a smart-typography replacement
+export const isTypographyTrigger = (key: string) =>
+  editorConfig().smart_typography && TYPOGRAPHY_TRIGGERS.has(key);
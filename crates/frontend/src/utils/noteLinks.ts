@@ -0,0 +1,25 @@
+import { commands } from "../api/commands";
+
+// A note URL copied from the app, e.g. zinnia://note/projects/plan
+export const NOTE_URL = /^zinnia:\/\/note\/\S+$/;
+
+const escapeHtml = (text: string) =>
+  text.replace(/&/g, "&amp;").replace(/</g, "&lt;").replace(/>/g, "&gt;");
+
+// Puts the note's URL (for other apps) and a rich link on the clipboard.
+// Pasting the URL back into a note inserts its [[wiki-link]] instead.
+export async function copyNoteLink(path: string): Promise<void> {
+  const link = await commands.copyNoteLink(path);
+  const html = `<a href="${link.url}">${escapeHtml(link.wiki_link)}</a>`;
+  try {
+    await navigator.clipboard.write([
+      new ClipboardItem({
+        "text/plain": new Blob([link.url], { type: "text/plain" }),
+        "text/html": new Blob([html], { type: "text/html" }),
+      }),
+    ]);
+  } catch {
+    // Not every webview supports multi-format clipboard items
+    await navigator.clipboard.writeText(link.url);
+  }
+}
@@ -10,6 +10,11 @@ export function getParentPath(path: string): string {
   return segments.slice(0, -1).join("/");
 }
 
+// "untitled" or "untitled 2", as new notes are named until they get a title
+export function isUntitled(path: string): boolean {
+  return /^untitled( \d+)?$/.test(getPathTitle(path));
+}
+
 export function getPathTitle(path: string): string {
   if (!path) return "Root";
   const segments = getPathSegments(path);
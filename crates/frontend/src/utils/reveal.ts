@@ -0,0 +1,45 @@
+import { createSignal } from "solid-js";
+
+// A spot in a note to select once its editor is showing, e.g. a search match.
+// `occurrence` counts earlier matches of `text` (ignoring case) in the note.
+export interface RevealRequest {
+  path: string;
+  text: string;
+  occurrence: number;
+}
+
+const [revealRequest, setRevealRequest] = createSignal<RevealRequest | null>(
+  null,
+);
+
+export { revealRequest };
+
+export function requestReveal(request: RevealRequest) {
+  setRevealRequest(request);
+}
+
+// Called by the editor that handled the request
+export function clearReveal() {
+  setRevealRequest(null);
+}
+
+// Where to put the caret in a note once its editor is showing, as an offset
+// into its Markdown, e.g. a template's `${cursor}`
+export interface CaretRequest {
+  path: string;
+  offset: number;
+}
+
+const [caretRequest, setCaretRequest] = createSignal<CaretRequest | null>(
+  null,
+);
+
+export { caretRequest };
+
+export function requestCaret(request: CaretRequest) {
+  setCaretRequest(request);
+}
+
+export function clearCaret() {
+  setCaretRequest(null);
+}
@@ -6,15 +6,14 @@ import {
 } from "@tauri-apps/plugin-fs";
 import { homeDir } from "@tauri-apps/api/path";
 
+// Appearance (fonts, UI scale, contrast) is per vault; see utils/appearance
 export interface Settings {
-  fontSize: number;
   notesLocation: string;
   autoCheckUpdates: boolean;
   openLastNote: boolean;
 }
 
 export const DEFAULT_SETTINGS: Settings = {
-  fontSize: 14,
   notesLocation: "",
   autoCheckUpdates: true,
   openLastNote: true,
@@ -54,25 +53,35 @@ export async function loadSettings(): Promise<Settings> {
     const content = await readTextFile(path);
     const parsed = JSON.parse(content);
 
-    const settings = {
+    return {
       ...DEFAULT_SETTINGS,
       ...parsed,
     };
-
-    // Migration: If fontSize is 16 (old default), reset to 14 (new default)
-    if (parsed.fontSize === 16) {
-      settings.fontSize = 14;
-      // Save the migrated settings
-      await saveSettings(settings);
-    }
-
-    return settings;
   } catch (error) {
     console.error("Failed to load settings:", error);
     return { ...DEFAULT_SETTINGS };
   }
 }
 
+// Removes the old app-wide font size from the settings file and returns it, if
+// it was customized (14 was the default; 16 an older default)
+export async function takeLegacyFontSize(): Promise<number | null> {
+  try {
+    const path = await getSettingsPath();
+    if (!(await exists(path))) return null;
+
+    const parsed = JSON.parse(await readTextFile(path));
+    if (!("fontSize" in parsed)) return null;
+
+    const { fontSize, ...rest } = parsed;
+    await saveSettings({ ...DEFAULT_SETTINGS, ...rest });
+    return fontSize === 14 || fontSize === 16 ? null : fontSize;
+  } catch (error) {
+    console.error("Failed to migrate font size:", error);
+    return null;
+  }
+}
+
 export async function saveSettings(settings: Settings): Promise<void> {
   try {
     await ensureSettingsDir();
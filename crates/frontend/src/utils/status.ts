@@ -0,0 +1,23 @@
+import type { NoteStatus } from "../types";
+
+// Shown next to note titles in the tree and the breadcrumb
+export const STATUS_MARKS: Record<NoteStatus, string> = {
+  todo: "○",
+  doing: "◐",
+  done: "●",
+};
+
+export const STATUS_LABELS: Record<NoteStatus, string> = {
+  todo: "To do",
+  doing: "Doing",
+  done: "Done",
+};
+
+// Cmd/Ctrl + Shift + S moves a note to its next status
+export function isCycleStatusKey(e: KeyboardEvent) {
+  return (e.metaKey || e.ctrlKey) && e.shiftKey && e.key.toLowerCase() === "s";
+}
+
+export function statusAnnouncement(status: NoteStatus | null) {
+  return status ? `Status: ${STATUS_LABELS[status]}` : "Status cleared";
+}
@@ -0,0 +1,30 @@
+import { createSignal } from "solid-js";
+import { commands } from "../api/commands";
+import type { TimingConfig } from "../types";
+
+const [timingConfig, setTimingConfig] = createSignal<TimingConfig>({
+  watcher_debounce_ms: 500,
+  rescan_threshold: 200,
+  autosave_delay_ms: 500,
+});
+
+// The vault's save and file-watching delays; the watcher picks up changes
+// as soon as they are saved
+export { timingConfig };
+
+export const autosaveDelay = () => timingConfig().autosave_delay_ms;
+
+export function applyTimingConfig(config: TimingConfig) {
+  setTimingConfig(config);
+}
+
+// Reads, updates, applies, and persists the timing section of the config
+export async function updateTimingConfig(
+  update: (current: TimingConfig) => TimingConfig,
+): Promise<TimingConfig> {
+  const config = await commands.getVaultConfig();
+  const timing = update(config.timing);
+  applyTimingConfig(timing);
+  await commands.setVaultConfig({ ...config, timing });
+  return timing;
+}
@@ -0,0 +1,39 @@
+import type { NoteMetadata, TreeDiff } from "../types";
+import { getParentPath } from "./paths";
+
+/**
+ * Applies a tree diff to a list of the children of `parentPath`.
+ * Renamed nodes keep their position; nodes arriving from elsewhere are
+ * appended.
+ */
+export function applyTreeDiff(
+  items: NoteMetadata[],
+  parentPath: string,
+  diff: TreeDiff,
+): NoteMetadata[] {
+  let next = items;
+  for (const change of diff.changes) {
+    if (change.kind === "removed") {
+      next = next.filter((item) => item.path !== change.path);
+      continue;
+    }
+
+    const note = change.note;
+    const from = change.kind === "added" ? null : change.from;
+    const index = from === null ? -1 : next.findIndex((i) => i.path === from);
+    const belongsHere =
+      note !== null && getParentPath(note.path) === parentPath;
+
+    if (index >= 0 && belongsHere) {
+      next = next.map((item, i) => (i === index ? note : item));
+    } else {
+      if (index >= 0) {
+        next = next.filter((_, i) => i !== index);
+      }
+      if (belongsHere && !next.some((item) => item.path === note.path)) {
+        next = [...next, note];
+      }
+    }
+  }
+  return next;
+}